@@ -0,0 +1,129 @@
+// Benchmarks for the paths that run on every poll or every frame. Run with
+// `cargo bench`; compare against a saved baseline with `cargo bench -- --baseline <name>`
+use chrono::{Duration, Utc};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use simple_performance_dashboard::model::{downsample, AppState, MetricValue, METRIC_DESCRIPTORS};
+use simple_performance_dashboard::ui::decimate_min_max;
+
+const HISTORY_SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+// Stress harness: 500 series polled every 100 ms, with ten minutes already recorded
+const STRESS_METRICS: usize = 500;
+const STRESS_INTERVAL_MS: i64 = 100;
+const STRESS_PRELOADED_POLLS: usize = 6_000;
+
+fn series_with_history(samples: usize) -> MetricValue {
+    let start = Utc::now() - Duration::seconds(samples as i64);
+    let mut series = MetricValue::default();
+    for index in 0..samples {
+        series.record_at(start + Duration::seconds(index as i64), (index % 100) as f64);
+    }
+    series
+}
+
+// Every catalog metric with an hour of one-second samples
+fn populated_state() -> AppState {
+    let mut state = AppState::new(1000);
+    let start = state.session_start;
+    for descriptor in METRIC_DESCRIPTORS {
+        if let Some(series) = state.metric_mut(descriptor.id) {
+            for second in 0..3_600 {
+                series.record_at(start + Duration::seconds(second), (second % 100) as f64);
+            }
+        }
+    }
+    state
+}
+
+fn bench_metric_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("metric_update");
+    group.bench_function("update", |b| {
+        let mut series = series_with_history(10_000);
+        b.iter(|| series.update(black_box(42.5f32)));
+    });
+    group.bench_function("record_at", |b| {
+        let mut series = series_with_history(10_000);
+        let timestamp = Utc::now();
+        b.iter(|| series.record_at(black_box(timestamp), black_box(42.5)));
+    });
+    group.finish();
+}
+
+fn bench_plot_data(c: &mut Criterion) {
+    let mut group = c.benchmark_group("plot_data");
+    for size in HISTORY_SIZES {
+        let series = series_with_history(size);
+        let session_start = series.history[0].0;
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &series, |b, series| {
+            b.iter(|| series.plot_data(black_box(session_start)));
+        });
+    }
+    group.finish();
+}
+
+fn bench_snapshot(c: &mut Criterion) {
+    let state = populated_state();
+    let mut group = c.benchmark_group("state");
+    group.bench_function("snapshot", |b| b.iter(|| state.snapshot()));
+    group.bench_function("recording", |b| b.iter(|| state.recording()));
+    group.finish();
+}
+
+fn bench_downsample(c: &mut Criterion) {
+    let mut group = c.benchmark_group("downsample");
+    for size in HISTORY_SIZES {
+        let series = series_with_history(size);
+        let samples = series.samples();
+        let session_start = samples[0].0;
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &samples, |b, samples| {
+            b.iter(|| downsample(black_box(samples), session_start, 2_000));
+        });
+    }
+    group.finish();
+}
+
+// Full-session plot reduced to a 1000 pixel wide chart
+fn bench_decimate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decimate_min_max");
+    for size in HISTORY_SIZES {
+        let series = series_with_history(size);
+        let points = series.plot_data(series.history[0].0);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &points, |b, points| {
+            b.iter(|| decimate_min_max(black_box(points), 1_000));
+        });
+    }
+    group.finish();
+}
+
+// One poll across every series plus what the poller and UI do with it each tick.
+// Has to stay far below STRESS_INTERVAL_MS to keep up
+fn bench_stress(c: &mut Criterion) {
+    let mut state = AppState::new(STRESS_INTERVAL_MS as u64);
+    let start = state.session_start;
+    state.cpu.core_utilization.resize_with(STRESS_METRICS, MetricValue::default);
+    for poll in 0..STRESS_PRELOADED_POLLS {
+        let timestamp = start + Duration::milliseconds(poll as i64 * STRESS_INTERVAL_MS);
+        for (index, series) in state.cpu.core_utilization.iter_mut().enumerate() {
+            series.record_at(timestamp, ((poll + index) % 100) as f64);
+        }
+    }
+    let values: Vec<f32> = (0..STRESS_METRICS).map(|index| (index % 100) as f32).collect();
+
+    let mut group = c.benchmark_group("stress");
+    group.throughput(Throughput::Elements(STRESS_METRICS as u64));
+    group.bench_function("poll_500_metrics", |b| {
+        b.iter(|| {
+            state.cpu.update_core_utilization(black_box(&values));
+            // Generous budget: measures the accounting, not disk writes
+            state.enforce_history_budget(usize::MAX).expect("Budget check failed");
+            black_box(state.snapshot());
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_metric_update, bench_plot_data, bench_snapshot, bench_downsample, bench_decimate, bench_stress);
+criterion_main!(benches);
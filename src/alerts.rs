@@ -0,0 +1,1277 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::model::{AppState, EventMetric};
+use crate::logger;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    Warning,
+    #[default]
+    Critical,
+}
+
+// A threshold on a single metric, identified by its descriptor id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub metric: String,
+    pub threshold: f64,
+    #[serde(default)]
+    pub severity: AlertSeverity,
+    #[serde(default)]
+    pub duration_secs: u64,              // Breach must last this long before firing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clear_threshold: Option<f64>,    // Fired alert clears only below this value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<AlertAction>,
+}
+
+// Command run when a rule fires, e.g. a script that sets fans to max or shuts down
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertAction {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "AlertAction::default_cooldown_secs")]
+    pub cooldown_secs: u64,              // Minimum time between two runs of this action
+    #[serde(default)]
+    pub require_confirmation: bool,      // Wait for the user to confirm in the UI
+}
+
+impl AlertAction {
+    fn default_cooldown_secs() -> u64 {
+        300
+    }
+    
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.command.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl AlertRule {
+    pub fn new(metric: &str, threshold: f64, severity: AlertSeverity) -> Self {
+        Self {
+            metric: metric.to_string(),
+            threshold,
+            severity,
+            duration_secs: 0,
+            clear_threshold: None,
+            action: None,
+        }
+    }
+    
+    // A clear threshold above the trigger threshold would make the alert flap
+    pub fn effective_clear_threshold(&self) -> f64 {
+        self.clear_threshold.unwrap_or(self.threshold).min(self.threshold)
+    }
+}
+
+// A ready-made rule offered in Settings, e.g. VRAM pressure during a training run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlertTemplate {
+    pub name: &'static str,
+    pub metric: &'static str,
+    pub threshold: f64,
+    pub duration_secs: u64,
+    pub clear_threshold: Option<f64>,
+    pub severity: AlertSeverity,
+}
+
+impl AlertTemplate {
+    pub fn rule(&self) -> AlertRule {
+        AlertRule {
+            duration_secs: self.duration_secs,
+            clear_threshold: self.clear_threshold,
+            ..AlertRule::new(self.metric, self.threshold, self.severity)
+        }
+    }
+}
+
+pub const ALERT_TEMPLATES: &[AlertTemplate] = &[
+    // Sustained, not momentary: frameworks grab and release VRAM in bursts
+    AlertTemplate {
+        name: "VRAM pressure (above 90% for 30 s)",
+        metric: "gpu.memory_utilization_percent",
+        threshold: 90.0,
+        duration_secs: 30,
+        clear_threshold: Some(85.0),
+        severity: AlertSeverity::Warning,
+    },
+    AlertTemplate {
+        name: "VRAM nearly full (above 97% for 5 s)",
+        metric: "gpu.memory_utilization_percent",
+        threshold: 97.0,
+        duration_secs: 5,
+        clear_threshold: Some(95.0),
+        severity: AlertSeverity::Critical,
+    },
+    AlertTemplate {
+        name: "CPU overheating (above 95 °C for 10 s)",
+        metric: "cpu.package_temperature",
+        threshold: 95.0,
+        duration_secs: 10,
+        clear_threshold: Some(90.0),
+        severity: AlertSeverity::Critical,
+    },
+    AlertTemplate {
+        name: "GPU hotspot (above 100 °C for 10 s)",
+        metric: "gpu.hotspot_temperature",
+        threshold: 100.0,
+        duration_secs: 10,
+        clear_threshold: Some(95.0),
+        severity: AlertSeverity::Critical,
+    },
+    // Failed self-assessment, or new reallocated sectors or media errors since the last session
+    AlertTemplate {
+        name: "Drive health degraded",
+        metric: "storage.health_degraded",
+        threshold: 1.0,
+        duration_secs: 0,
+        clear_threshold: None,
+        severity: AlertSeverity::Critical,
+    },
+    // Warranties end at the rated TBW; SMART data is read every few minutes
+    AlertTemplate {
+        name: "SSD near its rated endurance (above 90%)",
+        metric: "storage.endurance_used",
+        threshold: 90.0,
+        duration_secs: 0,
+        clear_threshold: None,
+        severity: AlertSeverity::Warning,
+    },
+    // A RAID array or ZFS pool lost a member or a device reported errors
+    AlertTemplate {
+        name: "Storage pool degraded",
+        metric: "storage.pool_degraded",
+        threshold: 1.0,
+        duration_secs: 0,
+        clear_threshold: None,
+        severity: AlertSeverity::Critical,
+    },
+    // ATX allows ±5% on the 12 V, 5 V and 3.3 V rails; a failing PSU sags under load first
+    AlertTemplate {
+        name: "Voltage rail outside ATX tolerance (±5%)",
+        metric: "motherboard.rail_deviation",
+        threshold: crate::monitors::super_io::RAIL_TOLERANCE_PERCENT,
+        duration_secs: 5,
+        clear_threshold: Some(4.0),
+        severity: AlertSeverity::Warning,
+    },
+    // The Super I/O chip latches the switch until the alarm is cleared
+    AlertTemplate {
+        name: "Chassis intrusion",
+        metric: "motherboard.chassis_intrusion",
+        threshold: 1.0,
+        duration_secs: 0,
+        clear_threshold: None,
+        severity: AlertSeverity::Critical,
+    },
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    pub rules: Vec<AlertRule>,
+    pub alarm: AlarmConfig,
+    pub prediction: PredictionConfig,
+}
+
+// Soft warnings extrapolated from the recent trend of each rule's metric
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PredictionConfig {
+    pub enabled: bool,
+    pub window_secs: u64,     // How much recent history the trend is fitted over
+    pub horizon_secs: u64,    // Warn when the threshold is this close at the current rate
+    pub min_samples: usize,
+}
+
+impl Default for PredictionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 60,
+            horizon_secs: 300,
+            min_samples: 5,
+        }
+    }
+}
+
+// Audible alarm for critical thresholds that stay breached
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlarmConfig {
+    pub enabled: bool,
+    pub sound_file: Option<PathBuf>,    // Platform default sound when unset
+    pub persist_secs: u64,              // How long a breach must last before sounding
+    pub repeat_interval_secs: u64,      // Minimum gap between repeated alarms
+    pub quiet_hours: Option<TimeWindow>,
+}
+
+impl Default for AlarmConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sound_file: None,
+            persist_secs: 10,
+            repeat_interval_secs: 60,
+            quiet_hours: None,
+        }
+    }
+}
+
+// Local time window, e.g. the alarm's quiet hours; may wrap past midnight
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+// Current evaluation result for one rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleStatus {
+    pub rule: AlertRule,
+    pub breach_since: Option<DateTime<Utc>>,    // Start of the current breach
+    pub fired_at: Option<DateTime<Utc>>,        // Set once the breach outlasted duration_secs
+    pub peak: Option<f64>,                      // Highest value seen during the current breach
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertTransitionKind {
+    Fired,
+    Cleared,
+}
+
+// Emitted when a rule starts or stops firing
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertTransition {
+    pub rule: AlertRule,
+    pub kind: AlertTransitionKind,
+    pub at: DateTime<Utc>,
+    pub value: Option<f64>,
+}
+
+impl RuleStatus {
+    pub fn breach_duration_secs(&self, now: DateTime<Utc>) -> Option<f64> {
+        self.breach_since
+            .map(|since| (now - since).num_milliseconds() as f64 / 1000.0)
+    }
+    
+    pub fn is_firing(&self) -> bool {
+        self.fired_at.is_some()
+    }
+}
+
+// Tracks how long each configured rule has been breached
+#[derive(Debug, Default)]
+pub struct AlertEngine {
+    statuses: Vec<RuleStatus>,
+}
+
+impl AlertEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn statuses(&self) -> &[RuleStatus] {
+        &self.statuses
+    }
+
+    pub fn evaluate(&mut self, state: &AppState, rules: &[AlertRule], now: DateTime<Utc>) -> Vec<AlertTransition> {
+        // When the rule set is edited, rules left as they were keep their breach and
+        // edited ones start over. Removed or edited rules that were firing are cleared,
+        // so their history records don't stay open forever
+        let mut transitions = Vec::new();
+        let unchanged = self.statuses.len() == rules.len()
+            && self.statuses.iter().zip(rules).all(|(status, rule)| status.rule == *rule);
+        if !unchanged {
+            let mut previous = std::mem::take(&mut self.statuses);
+            self.statuses = rules
+                .iter()
+                .map(|rule| match previous.iter().position(|status| status.rule == *rule) {
+                    Some(index) => previous.remove(index),
+                    None => RuleStatus { rule: rule.clone(), breach_since: None, fired_at: None, peak: None },
+                })
+                .collect();
+            for status in previous.into_iter().filter(RuleStatus::is_firing) {
+                transitions.push(AlertTransition {
+                    value: state.current_value(&status.rule.metric),
+                    rule: status.rule,
+                    kind: AlertTransitionKind::Cleared,
+                    at: now,
+                });
+            }
+        }
+
+        for status in &mut self.statuses {
+            let value = state.current_value(&status.rule.metric);
+
+            if let (Some(v), Some(_)) = (value, status.breach_since) {
+                status.peak = Some(status.peak.map_or(v, |peak| peak.max(v)));
+            }
+
+            match value {
+                Some(v) if v >= status.rule.threshold => {
+                    if status.breach_since.is_none() {
+                        status.breach_since = Some(now);
+                        status.peak = Some(v);
+                    }
+                    let held = status
+                        .breach_duration_secs(now)
+                        .is_some_and(|secs| secs >= status.rule.duration_secs as f64);
+                    if !status.is_firing() && held {
+                        status.fired_at = Some(now);
+                        transitions.push(AlertTransition {
+                            rule: status.rule.clone(),
+                            kind: AlertTransitionKind::Fired,
+                            at: now,
+                            value,
+                        });
+                    }
+                }
+                // Hysteresis band: a fired alert holds until the value drops below the clear threshold
+                Some(v) if status.is_firing() && v >= status.rule.effective_clear_threshold() => {}
+                _ => {
+                    if status.is_firing() {
+                        transitions.push(AlertTransition {
+                            rule: status.rule.clone(),
+                            kind: AlertTransitionKind::Cleared,
+                            at: now,
+                            value,
+                        });
+                    }
+                    status.breach_since = None;
+                    status.fired_at = None;
+                    status.peak = None;
+                }
+            }
+        }
+        transitions
+    }
+}
+
+// Estimated time until a rule's threshold is reached if the current trend holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrendWarning {
+    pub rule: AlertRule,
+    pub current: f64,
+    pub rate_per_sec: f64,
+    pub eta_secs: f64,
+}
+
+pub fn predict_threshold_crossings(
+    state: &AppState,
+    statuses: &[RuleStatus],
+    config: &PredictionConfig,
+    now: DateTime<Utc>,
+) -> Vec<TrendWarning> {
+    if !config.enabled {
+        return Vec::new();
+    }
+    
+    let elapsed_now = crate::model::elapsed_seconds(state.session_start, now);
+    let window_start = elapsed_now - config.window_secs as f64;
+    
+    statuses
+        .iter()
+        .filter(|status| status.breach_since.is_none())
+        .filter_map(|status| {
+            let metric = state.metric(&status.rule.metric)?;
+            let current = metric.current?;
+            let recent: Vec<(f64, f64)> = metric
+                .plot_data(state.session_start)
+                .into_iter()
+                .filter(|(t, _)| *t >= window_start)
+                .collect();
+            if recent.len() < config.min_samples.max(2) {
+                return None;
+            }
+            
+            let rate_per_sec = linear_slope(&recent)?;
+            if rate_per_sec <= 0.0 || current >= status.rule.threshold {
+                return None;
+            }
+            
+            let eta_secs = (status.rule.threshold - current) / rate_per_sec;
+            (eta_secs <= config.horizon_secs as f64).then(|| TrendWarning {
+                rule: status.rule.clone(),
+                current,
+                rate_per_sec,
+                eta_secs,
+            })
+        })
+        .collect()
+}
+
+// Least-squares slope of (time, value) points
+fn linear_slope(points: &[(f64, f64)]) -> Option<f64> {
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_v = points.iter().map(|(_, v)| v).sum::<f64>() / n;
+    
+    let covariance: f64 = points.iter().map(|(t, v)| (t - mean_t) * (v - mean_v)).sum();
+    let variance: f64 = points.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+    
+    (variance > f64::EPSILON).then(|| covariance / variance)
+}
+
+// One fired alert, kept for the rest of the session
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRecord {
+    pub rule: AlertRule,
+    pub started: DateTime<Utc>,
+    pub ended: Option<DateTime<Utc>>,
+    pub peak: f64,
+    pub acknowledged: bool,
+}
+
+impl AlertRecord {
+    pub fn is_active(&self) -> bool {
+        self.ended.is_none()
+    }
+    
+    pub fn duration_secs(&self, now: DateTime<Utc>) -> i64 {
+        (self.ended.unwrap_or(now) - self.started).num_seconds()
+    }
+}
+
+// An alert action waiting for the user to confirm it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingAction {
+    pub rule: AlertRule,
+    pub action: AlertAction,
+    pub requested: DateTime<Utc>,
+    pub confirmed: bool,
+}
+
+// Session log of fired alerts plus the user's acknowledgements and mutes
+#[derive(Debug, Clone, Default)]
+pub struct AlertHistory {
+    pub records: Vec<AlertRecord>,
+    pub muted: Vec<AlertRule>,
+    pub pending_actions: Vec<PendingAction>,
+    pub predictions: Vec<TrendWarning>,
+    pub firing: EventMetric, // Whether any rule is firing, as the "alerts.firing" metric
+}
+
+impl AlertHistory {
+    pub fn apply(&mut self, transitions: &[AlertTransition], statuses: &[RuleStatus], now: DateTime<Utc>) {
+        for transition in transitions {
+            match transition.kind {
+                AlertTransitionKind::Fired => {
+                    let status = statuses.iter().find(|status| status.rule == transition.rule);
+                    self.records.push(AlertRecord {
+                        rule: transition.rule.clone(),
+                        started: status.and_then(|s| s.breach_since).unwrap_or(transition.at),
+                        ended: None,
+                        peak: status.and_then(|s| s.peak).or(transition.value).unwrap_or_default(),
+                        acknowledged: false,
+                    });
+                }
+                AlertTransitionKind::Cleared => {
+                    if let Some(record) = self.open_record_mut(&transition.rule) {
+                        record.ended = Some(transition.at);
+                    }
+                }
+            }
+        }
+        
+        // Keep peaks of still-active alerts current
+        for status in statuses.iter().filter(|status| status.is_firing()) {
+            let peak = status.peak;
+            if let (Some(record), Some(peak)) = (self.open_record_mut(&status.rule), peak) {
+                record.peak = record.peak.max(peak);
+            }
+        }
+        self.firing.record_at(now, statuses.iter().any(RuleStatus::is_firing));
+    }
+    
+    fn open_record_mut(&mut self, rule: &AlertRule) -> Option<&mut AlertRecord> {
+        self.records
+            .iter_mut()
+            .rev()
+            .find(|record| record.is_active() && record.rule == *rule)
+    }
+    
+    pub fn acknowledge(&mut self, index: usize) {
+        if let Some(record) = self.records.get_mut(index) {
+            record.acknowledged = true;
+        }
+    }
+    
+    pub fn acknowledge_all(&mut self) {
+        for record in &mut self.records {
+            record.acknowledged = true;
+        }
+    }
+    
+    pub fn unacknowledged_count(&self) -> usize {
+        self.records.iter().filter(|record| !record.acknowledged).count()
+    }
+    
+    pub fn set_muted(&mut self, rule: &AlertRule, muted: bool) {
+        self.muted.retain(|muted_rule| muted_rule != rule);
+        if muted {
+            self.muted.push(rule.clone());
+        }
+    }
+    
+    pub fn confirm_action(&mut self, index: usize) {
+        if let Some(pending) = self.pending_actions.get_mut(index) {
+            pending.confirmed = true;
+        }
+    }
+    
+    pub fn dismiss_action(&mut self, index: usize) {
+        if index < self.pending_actions.len() {
+            self.pending_actions.remove(index);
+        }
+    }
+    
+    pub fn take_confirmed_actions(&mut self) -> Vec<PendingAction> {
+        let (confirmed, waiting) = std::mem::take(&mut self.pending_actions)
+            .into_iter()
+            .partition(|pending| pending.confirmed);
+        self.pending_actions = waiting;
+        confirmed
+    }
+    
+    pub fn is_muted(&self, rule: &AlertRule) -> bool {
+        self.muted.contains(rule)
+    }
+    
+    // Muted rules and acknowledged active alerts no longer demand attention
+    pub fn is_silenced(&self, rule: &AlertRule) -> bool {
+        self.is_muted(rule)
+            || self.records
+                .iter()
+                .rev()
+                .find(|record| record.is_active() && record.rule == *rule)
+                .is_some_and(|record| record.acknowledged)
+    }
+}
+
+// Runs or queues alert actions, enforcing each rule's cooldown
+#[derive(Debug, Default)]
+pub struct ActionDispatcher {
+    last_triggered: Vec<(AlertRule, DateTime<Utc>)>,
+    pub read_only: bool, // Guest mode: alerts still fire, but no action runs or is offered
+}
+
+impl ActionDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // Returns actions that should run now; confirmation-gated ones are queued on the history
+    pub fn dispatch(
+        &mut self,
+        transitions: &[AlertTransition],
+        history: &mut AlertHistory,
+        now: DateTime<Utc>,
+    ) -> Vec<AlertAction> {
+        let mut ready = Vec::new();
+        
+        for transition in transitions {
+            if transition.kind != AlertTransitionKind::Fired || history.is_muted(&transition.rule) {
+                continue;
+            }
+            let Some(action) = transition.rule.action.clone() else {
+                continue;
+            };
+            if self.read_only {
+                logger::log_info(&format!("Read-only mode, not running alert action: {}", action.command_line()));
+                continue;
+            }
+            
+            let cooling_down = self.last_triggered.iter().any(|(rule, at)| {
+                *rule == transition.rule && (now - *at).num_seconds() < action.cooldown_secs as i64
+            });
+            if cooling_down {
+                continue;
+            }
+            self.last_triggered.retain(|(rule, _)| *rule != transition.rule);
+            self.last_triggered.push((transition.rule.clone(), now));
+            
+            if action.require_confirmation {
+                let already_pending = history.pending_actions.iter().any(|p| p.rule == transition.rule);
+                if !already_pending {
+                    history.pending_actions.push(PendingAction {
+                        rule: transition.rule.clone(),
+                        action,
+                        requested: now,
+                        confirmed: false,
+                    });
+                }
+            } else {
+                ready.push(action);
+            }
+        }
+        
+        let confirmed = history.take_confirmed_actions();
+        if !self.read_only {
+            ready.extend(confirmed.into_iter().map(|pending| pending.action));
+        }
+        ready
+    }
+}
+
+pub fn run_action(action: &AlertAction) {
+    logger::log_warning(&format!("Running alert action: {}", action.command_line()));
+    let mut command = Command::new(&action.command);
+    command.args(&action.args);
+    if let Err(e) = spawn_detached(command) {
+        logger::log_error(&format!("Failed to run alert action '{}'", action.command), &e);
+    }
+}
+
+// Spawn a process and reap it in the background once it exits
+fn spawn_detached(mut command: Command) -> std::io::Result<()> {
+    let mut child = command.spawn()?;
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+// Decides when the audible alarm fires based on persisted critical breaches
+#[derive(Debug, Default)]
+pub struct Alarm {
+    last_sounded: Option<DateTime<Utc>>,
+}
+
+impl Alarm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn should_sound(
+        &mut self,
+        config: &AlarmConfig,
+        statuses: &[RuleStatus],
+        now: DateTime<Utc>,
+        local_time: NaiveTime,
+    ) -> bool {
+        if !config.enabled {
+            return false;
+        }
+
+        let persisted = statuses.iter().any(|status| {
+            status.rule.severity == AlertSeverity::Critical
+                && status.is_firing()
+                && status
+                    .breach_duration_secs(now)
+                    .is_some_and(|secs| secs >= config.persist_secs as f64)
+        });
+        if !persisted {
+            // Let the next breach sound immediately once it persists
+            self.last_sounded = None;
+            return false;
+        }
+
+        if config.quiet_hours.is_some_and(|quiet| quiet.contains(local_time)) {
+            return false;
+        }
+
+        let repeat_due = self.last_sounded.is_none_or(|last| {
+            (now - last).num_seconds() >= config.repeat_interval_secs as i64
+        });
+        if repeat_due {
+            self.last_sounded = Some(now);
+        }
+        repeat_due
+    }
+}
+
+// Play the alarm without blocking the caller
+pub fn play_alarm_sound(sound_file: Option<&Path>) {
+    match alarm_command(sound_file) {
+        Ok(command) => {
+            if let Err(e) = spawn_detached(command) {
+                logger::log_error("Failed to play alarm sound", &e);
+            }
+        }
+        Err(reason) => logger::log_warning(&format!("Alarm not played: {}", reason)),
+    }
+}
+
+// Why the alarm can't be played on this machine, for the settings page
+pub fn alarm_unavailable(sound_file: Option<&Path>) -> Option<String> {
+    alarm_command(sound_file).err()
+}
+
+#[cfg(target_os = "windows")]
+fn alarm_command(sound_file: Option<&Path>) -> Result<Command, String> {
+    let script = match sound_file {
+        Some(path) => format!(
+            "(New-Object Media.SoundPlayer '{}').PlaySync()",
+            path.display().to_string().replace('\'', "''")
+        ),
+        None => "[System.Media.SystemSounds]::Exclamation.Play(); Start-Sleep -Seconds 1".to_string(),
+    };
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    Ok(command)
+}
+
+#[cfg(target_os = "macos")]
+fn alarm_command(sound_file: Option<&Path>) -> Result<Command, String> {
+    let mut command = Command::new("afplay");
+    match sound_file {
+        Some(path) => command.arg(path),
+        None => command.arg("/System/Library/Sounds/Sosumi.aiff"),
+    };
+    Ok(command)
+}
+
+#[cfg(target_os = "linux")]
+fn alarm_command(sound_file: Option<&Path>) -> Result<Command, String> {
+    let installed = |program: &str| {
+        std::env::var_os("PATH").is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+    };
+    linux_alarm_command(sound_file, installed, Path::new(FREEDESKTOP_ALARM).is_file())
+}
+
+// The sound theme's alarm, which most desktops ship with the freedesktop theme
+#[cfg(any(target_os = "linux", test))]
+const FREEDESKTOP_ALARM: &str = "/usr/share/sounds/freedesktop/stereo/alarm-clock-elapsed.oga";
+
+// canberra-gtk-play looks the alarm up in the desktop's sound theme; paplay needs a file,
+// either the configured one or the freedesktop theme's
+#[cfg(any(target_os = "linux", test))]
+fn linux_alarm_command(
+    sound_file: Option<&Path>,
+    installed: impl Fn(&str) -> bool,
+    freedesktop_alarm_exists: bool,
+) -> Result<Command, String> {
+    if let Some(path) = sound_file {
+        if !installed("paplay") {
+            return Err("paplay (PulseAudio or PipeWire) is needed to play the sound file".to_string());
+        }
+        let mut command = Command::new("paplay");
+        command.arg(path);
+        return Ok(command);
+    }
+    if installed("canberra-gtk-play") {
+        let mut command = Command::new("canberra-gtk-play");
+        command.args(["-i", "alarm-clock-elapsed", "-d", "Simple Performance Dashboard"]);
+        return Ok(command);
+    }
+    if installed("paplay") && freedesktop_alarm_exists {
+        let mut command = Command::new("paplay");
+        command.arg(FREEDESKTOP_ALARM);
+        return Ok(command);
+    }
+    Err("no sound player found; install canberra-gtk-play (libcanberra) or paplay with the freedesktop sound theme, or set a sound file".to_string())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn alarm_command(_sound_file: Option<&Path>) -> Result<Command, String> {
+    Err("audible alarms are not supported on this platform".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn rule(metric: &str, threshold: f64) -> AlertRule {
+        AlertRule::new(metric, threshold, AlertSeverity::Critical)
+    }
+
+    fn firing(rule: AlertRule, since: DateTime<Utc>) -> RuleStatus {
+        RuleStatus { rule, breach_since: Some(since), fired_at: Some(since), peak: None }
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_engine_tracks_breach_start() {
+        let mut state = AppState::default();
+        let rules = vec![rule("gpu.package_temperature", 90.0)];
+        let mut engine = AlertEngine::new();
+        let start = Utc::now();
+
+        state.gpu.package_temperature.update(85.0);
+        engine.evaluate(&state, &rules, start);
+        assert!(engine.statuses()[0].breach_since.is_none());
+
+        state.gpu.package_temperature.update(92.0);
+        engine.evaluate(&state, &rules, start + Duration::seconds(1));
+        state.gpu.package_temperature.update(93.0);
+        engine.evaluate(&state, &rules, start + Duration::seconds(2));
+        assert_eq!(engine.statuses()[0].breach_since, Some(start + Duration::seconds(1)));
+
+        state.gpu.package_temperature.update(80.0);
+        engine.evaluate(&state, &rules, start + Duration::seconds(3));
+        assert!(engine.statuses()[0].breach_since.is_none());
+    }
+
+    #[test]
+    fn test_engine_fires_only_after_duration() {
+        let mut state = AppState::default();
+        let mut sustained = rule("cpu.package_temperature", 90.0);
+        sustained.duration_secs = 5;
+        let rules = vec![sustained];
+        let mut engine = AlertEngine::new();
+        let start = Utc::now();
+
+        // One-sample spike never fires
+        state.cpu.package_temperature.update(99.0);
+        assert!(engine.evaluate(&state, &rules, start).is_empty());
+        state.cpu.package_temperature.update(70.0);
+        assert!(engine.evaluate(&state, &rules, start + Duration::seconds(1)).is_empty());
+
+        // Sustained breach fires once
+        state.cpu.package_temperature.update(95.0);
+        assert!(engine.evaluate(&state, &rules, start + Duration::seconds(2)).is_empty());
+        assert!(engine.evaluate(&state, &rules, start + Duration::seconds(6)).is_empty());
+        let transitions = engine.evaluate(&state, &rules, start + Duration::seconds(7));
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].kind, AlertTransitionKind::Fired);
+        assert_eq!(transitions[0].value, Some(95.0));
+        assert!(engine.evaluate(&state, &rules, start + Duration::seconds(8)).is_empty());
+        assert!(engine.statuses()[0].is_firing());
+    }
+
+    #[test]
+    fn test_vram_pressure_template() {
+        let template = ALERT_TEMPLATES.iter().find(|template| template.name.starts_with("VRAM pressure")).unwrap();
+        let rules = vec![template.rule()];
+        assert_eq!(rules[0].duration_secs, 30);
+        assert!(ALERT_TEMPLATES.iter().all(|template| crate::model::MetricDescriptor::find(template.metric).is_some()));
+
+        let mut state = AppState::default();
+        state.gpu.memory_total_mb = Some(24576);
+        let mut engine = AlertEngine::new();
+        let start = Utc::now();
+        state.gpu.update_memory_usage(23000);
+        assert!(engine.evaluate(&state, &rules, start).is_empty());
+        let transitions = engine.evaluate(&state, &rules, start + Duration::seconds(30));
+        assert_eq!(transitions[0].kind, AlertTransitionKind::Fired);
+
+        // Holds while a training step frees a little, clears once well below
+        state.gpu.update_memory_usage(21500);
+        assert!(engine.evaluate(&state, &rules, start + Duration::seconds(31)).is_empty());
+        state.gpu.update_memory_usage(18000);
+        assert_eq!(engine.evaluate(&state, &rules, start + Duration::seconds(32))[0].kind, AlertTransitionKind::Cleared);
+    }
+
+    #[test]
+    fn test_engine_hysteresis_holds_until_clear_threshold() {
+        let mut state = AppState::default();
+        let mut with_clear = rule("gpu.hotspot_temperature", 90.0);
+        with_clear.clear_threshold = Some(85.0);
+        let rules = vec![with_clear];
+        let mut engine = AlertEngine::new();
+        let start = Utc::now();
+
+        state.gpu.hotspot_temperature.update(91.0);
+        assert_eq!(engine.evaluate(&state, &rules, start)[0].kind, AlertTransitionKind::Fired);
+
+        // Dipping inside the hysteresis band keeps the alert active
+        state.gpu.hotspot_temperature.update(87.0);
+        assert!(engine.evaluate(&state, &rules, start + Duration::seconds(1)).is_empty());
+        state.gpu.hotspot_temperature.update(90.5);
+        assert!(engine.evaluate(&state, &rules, start + Duration::seconds(2)).is_empty());
+        assert_eq!(engine.statuses()[0].breach_since, Some(start));
+
+        state.gpu.hotspot_temperature.update(84.0);
+        let transitions = engine.evaluate(&state, &rules, start + Duration::seconds(3));
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].kind, AlertTransitionKind::Cleared);
+        assert!(!engine.statuses()[0].is_firing());
+    }
+
+    #[test]
+    fn test_editing_rules_clears_firing_alerts_of_changed_rules() {
+        let mut state = AppState::default();
+        let hotspot = rule("gpu.hotspot_temperature", 90.0);
+        let package = rule("gpu.package_temperature", 80.0);
+        let mut engine = AlertEngine::new();
+        let mut history = AlertHistory::default();
+        let start = Utc::now();
+
+        state.gpu.hotspot_temperature.update(95.0);
+        state.gpu.package_temperature.update(85.0);
+        let rules = vec![hotspot.clone(), package.clone()];
+        let transitions = engine.evaluate(&state, &rules, start);
+        history.apply(&transitions, engine.statuses(), start);
+        assert_eq!(transitions.len(), 2);
+
+        // Raising the hotspot threshold closes its record; the untouched rule keeps firing
+        let mut raised = hotspot.clone();
+        raised.threshold = 100.0;
+        let rules = vec![package.clone(), raised];
+        let now = start + Duration::seconds(5);
+        let transitions = engine.evaluate(&state, &rules, now);
+        history.apply(&transitions, engine.statuses(), now);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!((transitions[0].kind, &transitions[0].rule), (AlertTransitionKind::Cleared, &hotspot));
+        assert_eq!(engine.statuses()[0].fired_at, Some(start));
+        assert!(!engine.statuses()[1].is_firing());
+        assert_eq!(history.records[0].ended, Some(now));
+        assert!(history.records[1].is_active());
+
+        // Removing the remaining rule clears it too
+        let transitions = engine.evaluate(&state, &[], now);
+        assert_eq!((transitions[0].kind, &transitions[0].rule), (AlertTransitionKind::Cleared, &package));
+    }
+
+    #[test]
+    fn test_history_records_peak_and_duration() {
+        let mut state = AppState::default();
+        let rules = vec![rule("gpu.package_temperature", 90.0)];
+        let mut engine = AlertEngine::new();
+        let mut history = AlertHistory::default();
+        let start = Utc::now();
+
+        for (offset, value) in [(0, 91.0), (1, 96.5), (2, 93.0), (3, 70.0)] {
+            state.gpu.package_temperature.update(value);
+            let transitions = engine.evaluate(&state, &rules, start + Duration::seconds(offset));
+            history.apply(&transitions, engine.statuses(), start + Duration::seconds(offset));
+        }
+
+        assert_eq!(history.records.len(), 1);
+        let record = &history.records[0];
+        assert_eq!(record.started, start);
+        assert_eq!(record.ended, Some(start + Duration::seconds(3)));
+        assert_eq!(record.peak, 96.5);
+        assert_eq!(record.duration_secs(start + Duration::seconds(10)), 3);
+        assert!(!record.is_active());
+        assert_eq!(history.firing.activations, 1);
+        assert_eq!(history.firing.active_duration(), Duration::seconds(3));
+    }
+
+    #[test]
+    fn test_history_acknowledge_and_mute() {
+        let critical = rule("gpu.package_temperature", 90.0);
+        let start = Utc::now();
+        let statuses = vec![firing(critical.clone(), start)];
+        let fired = AlertTransition {
+            rule: critical.clone(),
+            kind: AlertTransitionKind::Fired,
+            at: start,
+            value: Some(92.0),
+        };
+        let mut history = AlertHistory::default();
+        history.apply(&[fired], &statuses, start);
+
+        assert_eq!(history.unacknowledged_count(), 1);
+        assert!(!history.is_silenced(&critical));
+
+        history.acknowledge(0);
+        assert_eq!(history.unacknowledged_count(), 0);
+        assert!(history.is_silenced(&critical));
+
+        let other = rule("cpu.package_temperature", 95.0);
+        history.set_muted(&other, true);
+        assert!(history.is_muted(&other));
+        assert!(history.is_silenced(&other));
+        history.set_muted(&other, false);
+        assert!(!history.is_silenced(&other));
+    }
+
+    fn fired(rule: &AlertRule, at: DateTime<Utc>) -> AlertTransition {
+        AlertTransition {
+            rule: rule.clone(),
+            kind: AlertTransitionKind::Fired,
+            at,
+            value: Some(100.0),
+        }
+    }
+
+    fn with_action(require_confirmation: bool) -> AlertRule {
+        let mut protected = rule("cpu.package_temperature", 98.0);
+        protected.action = Some(AlertAction {
+            command: "shutdown".to_string(),
+            args: vec!["-h".to_string(), "now".to_string()],
+            cooldown_secs: 60,
+            require_confirmation,
+        });
+        protected
+    }
+
+    #[test]
+    fn test_dispatcher_runs_action_with_cooldown() {
+        let protected = with_action(false);
+        let mut dispatcher = ActionDispatcher::new();
+        let mut history = AlertHistory::default();
+        let start = Utc::now();
+
+        let ready = dispatcher.dispatch(&[fired(&protected, start)], &mut history, start);
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].command_line(), "shutdown -h now");
+
+        let later = start + Duration::seconds(30);
+        assert!(dispatcher.dispatch(&[fired(&protected, later)], &mut history, later).is_empty());
+
+        let after_cooldown = start + Duration::seconds(61);
+        assert_eq!(dispatcher.dispatch(&[fired(&protected, after_cooldown)], &mut history, after_cooldown).len(), 1);
+    }
+
+    #[test]
+    fn test_dispatcher_queues_actions_needing_confirmation() {
+        let protected = with_action(true);
+        let mut dispatcher = ActionDispatcher::new();
+        let mut history = AlertHistory::default();
+        let start = Utc::now();
+
+        assert!(dispatcher.dispatch(&[fired(&protected, start)], &mut history, start).is_empty());
+        assert_eq!(history.pending_actions.len(), 1);
+
+        // Nothing runs until the user confirms
+        assert!(dispatcher.dispatch(&[], &mut history, start).is_empty());
+        history.confirm_action(0);
+        let ready = dispatcher.dispatch(&[], &mut history, start);
+        assert_eq!(ready.len(), 1);
+        assert!(history.pending_actions.is_empty());
+    }
+
+    #[test]
+    fn test_dispatcher_runs_nothing_when_read_only() {
+        let mut dispatcher = ActionDispatcher::new();
+        dispatcher.read_only = true;
+        let mut history = AlertHistory::default();
+        let start = Utc::now();
+
+        let fired = [fired(&with_action(false), start), fired(&with_action(true), start)];
+        assert!(dispatcher.dispatch(&fired, &mut history, start).is_empty());
+        assert!(history.pending_actions.is_empty());
+    }
+
+    #[test]
+    fn test_dispatcher_skips_muted_rules() {
+        let protected = with_action(false);
+        let mut dispatcher = ActionDispatcher::new();
+        let mut history = AlertHistory::default();
+        history.set_muted(&protected, true);
+        let start = Utc::now();
+
+        assert!(dispatcher.dispatch(&[fired(&protected, start)], &mut history, start).is_empty());
+    }
+
+    #[test]
+    fn test_linear_slope() {
+        assert_eq!(linear_slope(&[(0.0, 10.0), (1.0, 12.0), (2.0, 14.0)]), Some(2.0));
+        assert_eq!(linear_slope(&[(5.0, 10.0), (5.0, 12.0)]), None);
+    }
+
+    #[test]
+    fn test_prediction_warns_before_threshold() {
+        let mut state = AppState::default();
+        let start = state.session_start;
+        // Rising 0.5 °C/s from 80 °C
+        for second in 0..10 {
+            let value = 80.0 + 0.5 * second as f64;
+            let timestamp = start + Duration::seconds(second);
+            state.gpu.package_temperature.history.push_back((timestamp, value));
+            state.gpu.package_temperature.current = Some(value);
+        }
+        let statuses = vec![RuleStatus {
+            rule: rule("gpu.package_temperature", 90.0),
+            breach_since: None,
+            fired_at: None,
+            peak: None,
+        }];
+        let now = start + Duration::seconds(9);
+
+        let warnings = predict_threshold_crossings(&state, &statuses, &PredictionConfig::default(), now);
+        assert_eq!(warnings.len(), 1);
+        assert!((warnings[0].rate_per_sec - 0.5).abs() < 1e-9);
+        assert!((warnings[0].eta_secs - 11.0).abs() < 1e-9);
+
+        // Too far out for a short horizon
+        let short = PredictionConfig { horizon_secs: 5, ..Default::default() };
+        assert!(predict_threshold_crossings(&state, &statuses, &short, now).is_empty());
+
+        let disabled = PredictionConfig { enabled: false, ..Default::default() };
+        assert!(predict_threshold_crossings(&state, &statuses, &disabled, now).is_empty());
+    }
+
+    #[test]
+    fn test_prediction_ignores_falling_and_breached_metrics() {
+        let mut state = AppState::default();
+        let start = state.session_start;
+        for second in 0..10 {
+            let value = 85.0 - second as f64;
+            state.cpu.package_temperature.history.push_back((start + Duration::seconds(second), value));
+            state.cpu.package_temperature.current = Some(value);
+        }
+        let mut status = RuleStatus {
+            rule: rule("cpu.package_temperature", 90.0),
+            breach_since: None,
+            fired_at: None,
+            peak: None,
+        };
+        let now = start + Duration::seconds(9);
+        let config = PredictionConfig::default();
+        assert!(predict_threshold_crossings(&state, &[status.clone()], &config, now).is_empty());
+
+        status.breach_since = Some(now);
+        assert!(predict_threshold_crossings(&state, &[status], &config, now).is_empty());
+    }
+
+    #[test]
+    fn test_clear_threshold_above_trigger_is_ignored() {
+        let mut invalid = rule("gpu.hotspot_temperature", 90.0);
+        invalid.clear_threshold = Some(95.0);
+        assert_eq!(invalid.effective_clear_threshold(), 90.0);
+        assert_eq!(rule("gpu.hotspot_temperature", 90.0).effective_clear_threshold(), 90.0);
+    }
+
+    #[test]
+    fn test_engine_ignores_unknown_and_missing_metrics() {
+        let state = AppState::default();
+        let rules = vec![rule("gpu.package_temperature", 90.0), rule("not.a.metric", 1.0)];
+        let mut engine = AlertEngine::new();
+
+        engine.evaluate(&state, &rules, Utc::now());
+        assert!(engine.statuses().iter().all(|status| status.breach_since.is_none()));
+    }
+
+    #[test]
+    fn test_fan_stop_is_a_reading_not_a_missing_sensor() {
+        let rules = vec![rule("gpu.fan_stop", 1.0), rule("gpu.fan_speed", 80.0)];
+        let mut engine = AlertEngine::new();
+        let now = Utc::now();
+
+        // No fan sensor: nothing to evaluate, so neither rule breaches
+        let mut state = AppState::default();
+        engine.evaluate(&state, &rules, now);
+        assert!(engine.statuses().iter().all(|status| status.breach_since.is_none()));
+        assert_eq!(state.current_value("gpu.fan_speed"), None);
+
+        // Fan stop reads 0% and is reported as its own state
+        state.gpu.update_fan_speed(0);
+        engine.evaluate(&state, &rules, now);
+        assert_eq!(state.current_value("gpu.fan_speed"), Some(0.0));
+        assert!(engine.statuses()[0].is_firing());
+        assert!(engine.statuses()[1].breach_since.is_none());
+
+        state.gpu.update_fan_speed(45);
+        engine.evaluate(&state, &rules, now);
+        assert!(!engine.statuses()[0].is_firing());
+        assert_eq!(state.gpu.fan_stop.activations, 1);
+    }
+
+    #[test]
+    fn test_alarm_waits_for_persistence_and_repeats() {
+        let config = AlarmConfig {
+            enabled: true,
+            persist_secs: 10,
+            repeat_interval_secs: 60,
+            ..Default::default()
+        };
+        let start = Utc::now();
+        let statuses = vec![firing(rule("cpu.package_temperature", 95.0), start)];
+        let mut alarm = Alarm::new();
+        let noon = time(12, 0);
+
+        assert!(!alarm.should_sound(&config, &statuses, start + Duration::seconds(5), noon));
+        assert!(alarm.should_sound(&config, &statuses, start + Duration::seconds(10), noon));
+        assert!(!alarm.should_sound(&config, &statuses, start + Duration::seconds(30), noon));
+        assert!(alarm.should_sound(&config, &statuses, start + Duration::seconds(70), noon));
+    }
+
+    #[test]
+    fn test_alarm_ignores_warnings_and_disabled_config() {
+        let start = Utc::now();
+        let mut warning = rule("cpu.package_temperature", 95.0);
+        warning.severity = AlertSeverity::Warning;
+        let statuses = vec![firing(warning, start)];
+        let config = AlarmConfig { enabled: true, persist_secs: 0, ..Default::default() };
+        let mut alarm = Alarm::new();
+        assert!(!alarm.should_sound(&config, &statuses, start, time(12, 0)));
+
+        let statuses = vec![firing(rule("cpu.package_temperature", 95.0), start)];
+        let disabled = AlarmConfig { enabled: false, persist_secs: 0, ..Default::default() };
+        assert!(!alarm.should_sound(&disabled, &statuses, start, time(12, 0)));
+    }
+
+    #[test]
+    fn test_alarm_respects_quiet_hours() {
+        let config = AlarmConfig {
+            enabled: true,
+            persist_secs: 0,
+            quiet_hours: Some(TimeWindow { start: time(22, 0), end: time(7, 0) }),
+            ..Default::default()
+        };
+        let start = Utc::now();
+        let statuses = vec![firing(rule("cpu.package_temperature", 95.0), start)];
+        let mut alarm = Alarm::new();
+
+        assert!(!alarm.should_sound(&config, &statuses, start, time(23, 30)));
+        assert!(!alarm.should_sound(&config, &statuses, start, time(6, 59)));
+        assert!(alarm.should_sound(&config, &statuses, start, time(7, 0)));
+    }
+
+    #[test]
+    fn test_linux_alarm_falls_back_to_the_sound_theme() {
+        let program = |command: Command| command.get_program().to_string_lossy().into_owned();
+        
+        let canberra = linux_alarm_command(None, |_| true, true).expect("canberra-gtk-play is installed");
+        assert_eq!(program(canberra), "canberra-gtk-play");
+        
+        let paplay = linux_alarm_command(None, |program| program == "paplay", true).expect("paplay is installed");
+        assert_eq!(paplay.get_args().collect::<Vec<_>>(), [FREEDESKTOP_ALARM]);
+        
+        let configured = linux_alarm_command(Some(Path::new("/tmp/siren.wav")), |_| true, true).expect("paplay is installed");
+        assert_eq!(program(configured), "paplay");
+        
+        // Without a player, or with paplay but no theme sound, it reports rather than going silent
+        assert!(linux_alarm_command(None, |_| false, true).is_err());
+        assert!(linux_alarm_command(None, |program| program == "paplay", false).is_err());
+        assert!(linux_alarm_command(Some(Path::new("/tmp/siren.wav")), |_| false, true).is_err());
+    }
+
+    #[test]
+    fn test_quiet_hours_same_day_window() {
+        let quiet = TimeWindow { start: time(13, 0), end: time(14, 0) };
+        assert!(quiet.contains(time(13, 30)));
+        assert!(!quiet.contains(time(14, 0)));
+        assert!(!quiet.contains(time(12, 59)));
+    }
+
+    #[test]
+    fn test_alert_config_parses_from_toml() {
+        let config: AlertConfig = toml::from_str(r#"
+            [[rules]]
+            metric = "gpu.hotspot_temperature"
+            threshold = 95.0
+            duration_secs = 30
+            clear_threshold = 88.0
+
+            [alarm]
+            enabled = true
+            sound_file = "/tmp/siren.wav"
+            quiet_hours = { start = "23:00", end = "07:30" }
+        "#).expect("Failed to parse alert config");
+
+        let mut expected = rule("gpu.hotspot_temperature", 95.0);
+        expected.duration_secs = 30;
+        expected.clear_threshold = Some(88.0);
+        assert_eq!(config.rules, vec![expected]);
+        assert!(config.alarm.enabled);
+        assert_eq!(config.alarm.persist_secs, 10);
+        assert_eq!(config.alarm.quiet_hours, Some(TimeWindow { start: time(23, 0), end: time(7, 30) }));
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use parking_lot::RwLock;
+
+// Shorter identifiers would also match inside ordinary words
+const MIN_IDENTIFIER_LEN: usize = 3;
+
+// Readable without root on most Linux systems; the DMI serials usually need root
+#[cfg(target_os = "linux")]
+const DMI_SERIAL_FILES: &[&str] = &[
+    "/sys/class/dmi/id/product_serial",
+    "/sys/class/dmi/id/board_serial",
+    "/sys/class/dmi/id/chassis_serial",
+    "/sys/class/dmi/id/product_uuid",
+];
+
+// Active while "anonymize shared reports" is on
+static ACTIVE: RwLock<Option<Anonymizer>> = RwLock::new(None);
+
+// Replaces identifying strings (host name, user name, serial numbers) with stable
+// pseudonyms like "host-3fa2c1". The pseudonyms are keyed by a per-install salt, so
+// the same machine always gets the same ones but they can't be reversed by guessing
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizer {
+    salt: u64,
+    replacements: Vec<(String, String)>, // Longest first, so no identifier is cut short by another
+}
+
+impl Anonymizer {
+    pub fn new(salt: u64) -> Self {
+        Self { salt, replacements: Vec::new() }
+    }
+
+    // Anonymizer for the identifiers found on this machine
+    pub fn detect(salt: u64) -> Self {
+        let mut anonymizer = Self::new(salt);
+        if let Some(host) = sysinfo::System::host_name() {
+            anonymizer.add("host", &host);
+        }
+        for variable in ["USER", "USERNAME"] {
+            if let Ok(user) = std::env::var(variable) {
+                anonymizer.add("user", &user);
+            }
+        }
+        for serial in serial_numbers() {
+            anonymizer.add("serial", &serial);
+        }
+        anonymizer
+    }
+
+    pub fn add(&mut self, kind: &str, identifier: &str) {
+        let identifier = identifier.trim();
+        if identifier.chars().count() < MIN_IDENTIFIER_LEN {
+            return;
+        }
+        let pseudonym = self.pseudonym(kind, identifier);
+        // Host names turn up in either case, e.g. in paths and in DNS names
+        for variant in [identifier.to_string(), identifier.to_lowercase(), identifier.to_uppercase()] {
+            if !self.replacements.iter().any(|(known, _)| *known == variant) {
+                self.replacements.push((variant, pseudonym.clone()));
+            }
+        }
+        self.replacements.sort_by_key(|(identifier, _)| std::cmp::Reverse(identifier.len()));
+    }
+
+    // FNV-1a over the salt and the identifier; unlike DefaultHasher it is the same in every build
+    pub fn pseudonym(&self, kind: &str, identifier: &str) -> String {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in self.salt.to_le_bytes().iter().chain(kind.as_bytes()).chain(identifier.as_bytes()) {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        format!("{}-{:06x}", kind, hash & 0xff_ffff)
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (identifier, pseudonym) in &self.replacements {
+            text = text.replace(identifier.as_str(), pseudonym);
+        }
+        text
+    }
+}
+
+// Key for a new install's pseudonyms
+pub fn new_salt() -> u64 {
+    RandomState::new().build_hasher().finish().max(1)
+}
+
+// Turn anonymization of shared reports on or off; called at startup and when the setting changes
+pub fn configure(enabled: bool, salt: u64) {
+    *ACTIVE.write() = enabled.then(|| Anonymizer::detect(salt));
+}
+
+pub fn enabled() -> bool {
+    ACTIVE.read().is_some()
+}
+
+// Text as it may be shared: anonymized when the setting is on, unchanged otherwise
+pub fn scrub(text: &str) -> String {
+    match ACTIVE.read().as_ref() {
+        Some(anonymizer) => anonymizer.apply(text),
+        None => text.to_string(),
+    }
+}
+
+// Host name for exported data, or its pseudonym
+pub fn host_name() -> Option<String> {
+    let host = sysinfo::System::host_name()?;
+    Some(match ACTIVE.read().as_ref() {
+        Some(anonymizer) => anonymizer.pseudonym("host", host.trim()),
+        None => host,
+    })
+}
+
+// Board, system and drive serial numbers this user can read
+#[cfg(target_os = "linux")]
+fn serial_numbers() -> Vec<String> {
+    let mut paths: Vec<std::path::PathBuf> = DMI_SERIAL_FILES.iter().map(std::path::PathBuf::from).collect();
+    if let Ok(entries) = std::fs::read_dir("/sys/block") {
+        paths.extend(entries.flatten().map(|entry| entry.path().join("device/serial")));
+    }
+    paths
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|serial| serial.trim().to_string())
+        .filter(|serial| !serial.is_empty() && serial != "Not Specified" && serial != "To Be Filled By O.E.M.")
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn serial_numbers() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifiers_get_stable_pseudonyms() {
+        let mut anonymizer = Anonymizer::new(42);
+        anonymizer.add("host", "Gaming-PC");
+        anonymizer.add("user", "alice");
+        anonymizer.add("serial", "S4EWNX0R123456");
+        anonymizer.add("user", "al");
+
+        let report = "Host: Gaming-PC (gaming-pc.local)\nPanic at /home/alice/src/main.rs\nDrive S4EWNX0R123456 failed\nalgorithm";
+        let scrubbed = anonymizer.apply(report);
+        let host = anonymizer.pseudonym("host", "Gaming-PC");
+        assert!(scrubbed.contains(&format!("Host: {} ({}.local)", host, host)));
+        assert!(scrubbed.contains(&format!("/home/{}/src", anonymizer.pseudonym("user", "alice"))));
+        assert!(!scrubbed.contains("S4EWNX0R123456"));
+        // Too short to replace safely
+        assert!(scrubbed.ends_with("algorithm"));
+
+        // Same salt, same pseudonyms; another install's salt gives different ones
+        assert_eq!(Anonymizer::new(42).pseudonym("host", "Gaming-PC"), host);
+        assert_ne!(Anonymizer::new(7).pseudonym("host", "Gaming-PC"), host);
+        assert!(host.starts_with("host-") && host.len() == "host-".len() + 6);
+    }
+}
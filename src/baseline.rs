@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use crate::hardware_detection::SoftwareVersions;
+use crate::model::{AppState, METRIC_DESCRIPTORS};
+
+// Summaries live next to the config and log file
+const SUMMARY_DIR: &str = "session_summaries";
+
+// Oldest untagged summaries beyond this are removed when a new one is saved;
+// tagged ones are kept until deleted
+const KEPT_SUMMARIES: usize = 20;
+
+// Shorter sessions say little about typical temperatures and aren't kept
+pub const MIN_SESSION_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MetricSummary {
+    pub average: f64,
+    pub min: f64,
+    pub max: f64,
+    pub samples: usize,
+    // Missing from summaries saved by older versions
+    #[serde(default)]
+    pub std_dev: Option<f64>, // Sample standard deviation; None below two samples
+    #[serde(default)]
+    pub p99: Option<f64>,
+}
+
+impl MetricSummary {
+    pub fn from_values(values: impl Iterator<Item = f64>) -> Option<Self> {
+        let mut values: Vec<f64> = values.filter(|value| value.is_finite()).collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let samples = values.len();
+        let average = values.iter().sum::<f64>() / samples as f64;
+        let std_dev = (samples > 1).then(|| {
+            let squares: f64 = values.iter().map(|value| (value - average).powi(2)).sum();
+            (squares / (samples - 1) as f64).sqrt()
+        });
+        // Nearest rank
+        let p99 = values[((samples as f64 * 0.99).ceil() as usize).clamp(1, samples) - 1];
+        Some(Self { average, min: values[0], max: values[samples - 1], samples, std_dev, p99: Some(p99) })
+    }
+}
+
+// Per-metric stats of one session, saved when it ends so the next session on the
+// same hardware can show how it compares
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub hardware: String, // HardwareInfo::fingerprint; only equal ones are compared
+    #[serde(default)]
+    pub versions: SoftwareVersions,
+    pub session_start: DateTime<Utc>,
+    pub session_end: DateTime<Utc>,
+    pub metrics: BTreeMap<String, MetricSummary>, // Keyed by metric id
+    #[serde(default)]
+    pub tags: Vec<String>, // e.g. "stock", "undervolt -50mV", "summer"
+}
+
+impl SessionSummary {
+    // Stats over the in-memory history, or with `full` the spilled samples as well
+    pub fn from_state(state: &AppState, full: bool) -> Self {
+        let mut metrics = BTreeMap::new();
+        for descriptor in METRIC_DESCRIPTORS {
+            let Some(metric) = state.metric(descriptor.id) else {
+                continue;
+            };
+            let summary = if full && metric.spilled > 0 {
+                MetricSummary::from_values(state.session_samples(descriptor.id).unwrap_or_default().into_iter().map(|(_, value)| value))
+            } else {
+                MetricSummary::from_values(metric.history.iter().map(|(_, value)| *value))
+            };
+            if let Some(summary) = summary {
+                metrics.insert(descriptor.id.to_string(), summary);
+            }
+        }
+        Self {
+            hardware: state.poller.hardware_fingerprint.clone(),
+            versions: state.poller.versions.clone(),
+            session_start: state.session_start,
+            session_end: Utc::now(),
+            metrics,
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn duration(&self) -> chrono::Duration {
+        self.session_end - self.session_start
+    }
+
+    // Per-metric stats as CSV with a header row, for a spreadsheet
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("metric,unit,average,min,max,samples\n");
+        for (id, metric) in &self.metrics {
+            let unit = crate::model::MetricDescriptor::find(id).map_or(String::new(), |descriptor| descriptor.displayed().unit);
+            csv.push_str(&format!("{},{},{},{},{},{}\n", id, unit, metric.average, metric.min, metric.max, metric.samples));
+        }
+        csv
+    }
+
+    // Difference in averages against an earlier session, when both recorded the metric
+    pub fn average_delta(&self, baseline: &SessionSummary, id: &str) -> Option<f64> {
+        Some(self.metrics.get(id)?.average - baseline.metrics.get(id)?.average)
+    }
+
+    pub fn default_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Ok(crate::paths::data_dir()?.join(SUMMARY_DIR))
+    }
+
+    // Write as `summary_<start>.toml`, pruning the oldest untagged ones beyond KEPT_SUMMARIES
+    pub fn save_to(&self, dir: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("summary_{}.toml", self.session_start.format("%Y%m%d_%H%M%S")));
+        self.rewrite(&path)?;
+
+        let mut untagged: Vec<PathBuf> = Self::load_all(dir)
+            .into_iter()
+            .filter(|(_, summary)| summary.tags.is_empty())
+            .map(|(path, _)| path)
+            .collect();
+        if untagged.len() > KEPT_SUMMARIES {
+            untagged.sort();
+            for old in &untagged[..untagged.len() - KEPT_SUMMARIES] {
+                let _ = fs::remove_file(old);
+            }
+        }
+        Ok(path)
+    }
+
+    // Overwrite a saved summary in place, e.g. after its tags were edited
+    pub fn rewrite(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    // Tags from comma-separated text, trimmed, without empties or repeats
+    pub fn set_tags(&mut self, text: &str) {
+        self.tags.clear();
+        for tag in text.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+            if !self.tags.iter().any(|known| known.eq_ignore_ascii_case(tag)) {
+                self.tags.push(tag.to_string());
+            }
+        }
+    }
+
+    // Most recent summary recorded on the same hardware; unreadable files are skipped
+    pub fn load_latest(dir: &Path, hardware: &str) -> Option<Self> {
+        summary_files(dir)
+            .ok()?
+            .iter()
+            .filter_map(|path| toml::from_str::<Self>(&fs::read_to_string(path).ok()?).ok())
+            .filter(|summary| summary.hardware == hardware)
+            .max_by_key(|summary| summary.session_start)
+    }
+
+    // Every saved session on any hardware, newest first, with the file it was read from
+    pub fn load_all(dir: &Path) -> Vec<(PathBuf, Self)> {
+        let mut sessions: Vec<(PathBuf, Self)> = summary_files(dir)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|path| {
+                let summary = toml::from_str::<Self>(&fs::read_to_string(&path).ok()?).ok()?;
+                Some((path, summary))
+            })
+            .collect();
+        sessions.sort_by_key(|(_, summary)| std::cmp::Reverse(summary.session_start));
+        sessions
+    }
+}
+
+// Narrows the session list: every word of the query must appear in a tag or the
+// hardware fingerprint, and the session must start within the (local) date range
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionFilter {
+    pub query: String,
+    pub hardware: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>, // Inclusive
+}
+
+impl SessionFilter {
+    pub fn matches(&self, summary: &SessionSummary) -> bool {
+        if self.hardware.as_ref().is_some_and(|hardware| *hardware != summary.hardware) {
+            return false;
+        }
+        let started = summary.session_start.with_timezone(&Local).date_naive();
+        if self.from.is_some_and(|from| started < from) || self.to.is_some_and(|to| started > to) {
+            return false;
+        }
+        let searched: Vec<String> = summary
+            .tags
+            .iter()
+            .chain(std::iter::once(&summary.hardware))
+            .map(|text| text.to_lowercase())
+            .collect();
+        self.query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| searched.iter().any(|text| text.contains(word)))
+    }
+}
+
+fn summary_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    Ok(fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("summary_") && name.ends_with(".toml"))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn summary_at(hardware: &str, session_start: DateTime<Utc>, gpu_temperature: f64) -> SessionSummary {
+        let mut state = AppState::new(1000);
+        state.session_start = session_start;
+        state.poller.hardware_fingerprint = hardware.to_string();
+        for offset in [-2.0, 0.0, 2.0] {
+            state.gpu.package_temperature.update((gpu_temperature + offset) as f32);
+        }
+        SessionSummary::from_state(&state, true)
+    }
+
+    #[test]
+    fn test_latest_summary_on_same_hardware() {
+        let dir = std::env::temp_dir().join(format!("spd_baseline_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let start = Utc::now() - Duration::days(1);
+        summary_at("desktop", start, 60.0).save_to(&dir).unwrap();
+        summary_at("desktop", start + Duration::hours(2), 62.0).save_to(&dir).unwrap();
+        summary_at("laptop", start + Duration::hours(4), 80.0).save_to(&dir).unwrap();
+
+        let baseline = SessionSummary::load_latest(&dir, "desktop");
+        let missing = SessionSummary::load_latest(&dir, "server");
+        let _ = fs::remove_dir_all(&dir);
+
+        let baseline = baseline.expect("No baseline loaded");
+        assert_eq!(baseline.session_start.timestamp(), (start + Duration::hours(2)).timestamp());
+        let gpu = baseline.metrics["gpu.package_temperature"];
+        assert_eq!((gpu.average, gpu.min, gpu.max, gpu.samples), (62.0, 60.0, 64.0, 3));
+        assert!(missing.is_none());
+
+        let current = summary_at("desktop", Utc::now(), 66.0);
+        assert_eq!(current.average_delta(&baseline, "gpu.package_temperature"), Some(4.0));
+        assert_eq!(current.average_delta(&baseline, "cpu.utilization"), None);
+    }
+
+    #[test]
+    fn test_all_sessions_newest_first() {
+        let dir = std::env::temp_dir().join(format!("spd_sessions_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let start = Utc::now() - Duration::days(1);
+        summary_at("desktop", start, 60.0).save_to(&dir).unwrap();
+        summary_at("laptop", start + Duration::hours(4), 80.0).save_to(&dir).unwrap();
+        fs::write(dir.join("summary_broken.toml"), "not a summary").unwrap();
+
+        let sessions = SessionSummary::load_all(&dir);
+        let _ = fs::remove_dir_all(&dir);
+
+        let machines: Vec<&str> = sessions.iter().map(|(_, summary)| summary.hardware.as_str()).collect();
+        assert_eq!(machines, vec!["laptop", "desktop"]);
+        assert!(sessions[0].0.file_name().unwrap().to_str().unwrap().starts_with("summary_"));
+
+        let csv = sessions[1].1.to_csv();
+        assert!(csv.starts_with("metric,unit,average,min,max,samples\n"));
+        assert!(csv.contains("gpu.package_temperature,°C,60,58,62,3\n"));
+    }
+
+    #[test]
+    fn test_tagged_sessions_are_searchable_and_kept() {
+        let dir = std::env::temp_dir().join(format!("spd_tags_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let start = Utc::now() - Duration::days(30);
+        let mut tagged = summary_at("desktop", start, 60.0);
+        tagged.set_tags(" stock, summer,,Stock ");
+        assert_eq!(tagged.tags, vec!["stock", "summer"]);
+        tagged.save_to(&dir).unwrap();
+        for hour in 1..=KEPT_SUMMARIES as i64 + 1 {
+            summary_at("laptop", start + Duration::hours(hour), 70.0).save_to(&dir).unwrap();
+        }
+
+        // Only untagged sessions are pruned
+        let sessions = SessionSummary::load_all(&dir);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(sessions.len(), KEPT_SUMMARIES + 1);
+        let (_, tagged) = sessions.last().unwrap();
+        assert_eq!(tagged.hardware, "desktop");
+
+        let mut filter = SessionFilter { query: "STOCK".to_string(), ..SessionFilter::default() };
+        assert!(filter.matches(tagged));
+        filter.query = "stock undervolt".to_string();
+        assert!(!filter.matches(tagged));
+        filter.query = "desk".to_string();
+        assert!(filter.matches(tagged));
+
+        let day = tagged.session_start.with_timezone(&Local).date_naive();
+        let range = SessionFilter { from: Some(day), to: Some(day), ..SessionFilter::default() };
+        assert!(range.matches(tagged));
+        let later = SessionFilter { from: day.succ_opt(), ..SessionFilter::default() };
+        assert!(!later.matches(tagged));
+        let laptop = SessionFilter { hardware: Some("laptop".to_string()), ..SessionFilter::default() };
+        assert!(!laptop.matches(tagged));
+    }
+}
@@ -0,0 +1,243 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use chrono::Utc;
+use tokio::sync::broadcast::error::TryRecvError;
+use crate::config::AppConfig;
+use crate::hardware::{HardwarePoller, TickScheduler};
+use crate::hardware_detection::HardwareMonitor;
+use crate::model::{AppState, MonitorSelection, Sample, SharedAppState};
+use crate::schedule::{RecordingSchedule, RecordingScheduler};
+
+type SampleCallback = Box<dyn FnMut(&Sample) + Send>;
+
+// Embeds just the collection layer in another program, without the GUI:
+//
+//     let collector = DashboardBuilder::new()
+//         .polling_interval(Duration::from_millis(500))
+//         .only_monitors(["generic", "nvidia"])
+//         .on_sample(|sample| println!("{} = {}", sample.metric_id, sample.value))
+//         .start();
+pub struct DashboardBuilder {
+    config: AppConfig,
+    polling_interval: Duration,
+    only_monitors: Option<Vec<String>>,
+    extra_monitors: Vec<Box<dyn HardwareMonitor>>,
+    callbacks: Vec<SampleCallback>,
+    senders: Vec<Sender<Sample>>,
+}
+
+impl Default for DashboardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DashboardBuilder {
+    pub fn new() -> Self {
+        let config = AppConfig::default();
+        Self {
+            polling_interval: Duration::from_millis(config.polling_interval_ms),
+            config,
+            only_monitors: None,
+            extra_monitors: Vec::new(),
+            callbacks: Vec::new(),
+            senders: Vec::new(),
+        }
+    }
+
+    // Start from a full configuration, e.g. one loaded with AppConfig::load_from
+    pub fn config(mut self, config: AppConfig) -> Self {
+        self.polling_interval = Duration::from_millis(config.polling_interval_ms);
+        self.config = config;
+        self
+    }
+
+    pub fn polling_interval(mut self, interval: Duration) -> Self {
+        self.polling_interval = interval;
+        self.config.polling_interval_ms = interval.as_millis() as u64;
+        self
+    }
+
+    // Keep only these hardware monitors (names as in the config, case-insensitive);
+    // by default every monitor matching the detected hardware runs
+    pub fn only_monitors<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.only_monitors = Some(names.into_iter().map(|name| name.into().to_lowercase()).collect());
+        self
+    }
+
+    // Poll a monitor of the embedder's own alongside the built-in ones
+    pub fn monitor(mut self, monitor: Box<dyn HardwareMonitor>) -> Self {
+        self.extra_monitors.push(monitor);
+        self
+    }
+
+    // Deliver samples only during these windows or while a trigger holds
+    pub fn recording_schedule(mut self, schedule: RecordingSchedule) -> Self {
+        self.config.recording = schedule;
+        self
+    }
+
+    // Called on the collector thread for every new sample
+    pub fn on_sample(mut self, callback: impl FnMut(&Sample) + Send + 'static) -> Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    // Forward every new sample to a channel; a dropped receiver is ignored
+    pub fn send_samples_to(mut self, sender: Sender<Sample>) -> Self {
+        self.senders.push(sender);
+        self
+    }
+
+    // Detect hardware, initialize the monitors and start polling on a background thread
+    pub fn start(self) -> Collector {
+        let mut scheduler = RecordingScheduler::new(self.config.recording.clone());
+        // Process-wide, so an embedder without overrides leaves the host's alone
+        if !self.config.metric_overrides.is_empty() {
+            crate::model::set_display_overrides(&self.config.metric_overrides);
+        }
+        if !self.config.sensor_mappings.is_empty() {
+            crate::lm_sensors::set_mappings(&self.config.sensor_mappings);
+        }
+        if !self.config.drive_rated_tbw.is_empty() {
+            crate::monitors::smart::set_rated_tbw(&self.config.drive_rated_tbw);
+        }
+        let state = AppState::new_shared(self.polling_interval.as_millis() as u64);
+        let only_monitors = self.only_monitors;
+        let extra_monitors = self.extra_monitors;
+        let poller = HardwarePoller::with_monitors(
+            state.clone(),
+            self.polling_interval.as_millis() as u64,
+            AppConfig::new_shared(self.config),
+            move |registry, hardware_info| {
+                let mut selections = registry.register_for_hardware(hardware_info);
+                if let Some(only) = &only_monitors {
+                    let keep = |name: &str| only.contains(&name.to_lowercase());
+                    registry.retain_monitors(keep);
+                    for selection in selections.iter_mut().filter(|selection| selection.selected && !keep(&selection.name)) {
+                        selection.selected = false;
+                        selection.reason = "not requested by the embedding program".to_string();
+                    }
+                }
+                for monitor in extra_monitors {
+                    selections.push(MonitorSelection {
+                        name: monitor.name().to_string(),
+                        selected: true,
+                        reason: "added by the embedding program".to_string(),
+                    });
+                    registry.register(monitor);
+                }
+                selections
+            },
+        );
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let state = state.clone();
+            let stop = stop.clone();
+            let mut callbacks = self.callbacks;
+            let senders = self.senders;
+            let mut poller = poller;
+            let mut samples = state.read().subscribe();
+            thread::spawn(move || {
+                let mut ticks = TickScheduler::new();
+                while !stop.load(Ordering::Relaxed) {
+                    poller.poll_hardware();
+                    let recording = scheduler.update(&state.read(), Utc::now());
+                    loop {
+                        let sample = match samples.try_recv() {
+                            Ok(sample) => sample,
+                            Err(TryRecvError::Lagged(_)) => continue,
+                            Err(_) => break,
+                        };
+                        if !scheduler.admit(&sample, recording) {
+                            continue;
+                        }
+                        for callback in &mut callbacks {
+                            callback(&sample);
+                        }
+                        for sender in &senders {
+                            let _ = sender.send(sample.clone());
+                        }
+                    }
+                    // Follows burst sampling
+                    let interval = Duration::from_millis(state.read().polling_interval_ms);
+                    ticks.wait(interval);
+                }
+            })
+        };
+
+        Collector { state, stop, thread: Some(thread) }
+    }
+}
+
+// Running collection layer; polling stops when this is stopped or dropped
+pub struct Collector {
+    state: SharedAppState,
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Collector {
+    // Full state, including histories and which monitors were selected and why
+    pub fn state(&self) -> SharedAppState {
+        self.state.clone()
+    }
+
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Collector {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use crate::model::METRIC_DESCRIPTORS;
+
+    #[test]
+    fn test_builder_delivers_samples_from_selected_monitors() {
+        let (sender, receiver) = mpsc::channel();
+        let callback_count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = callback_count.clone();
+        let collector = DashboardBuilder::new()
+            .polling_interval(Duration::from_millis(20))
+            .only_monitors(["Generic"])
+            .on_sample(move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            })
+            .send_samples_to(sender)
+            .start();
+
+        let sample = receiver.recv_timeout(Duration::from_secs(5)).expect("No sample delivered");
+        assert!(METRIC_DESCRIPTORS.iter().any(|descriptor| descriptor.id == sample.metric_id));
+
+        let state = collector.state();
+        collector.stop();
+        assert!(callback_count.load(Ordering::Relaxed) > 0);
+        let app_state = state.read();
+        let selected: Vec<_> = app_state.poller.monitor_selection.iter().filter(|selection| selection.selected).collect();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].name, "Generic");
+    }
+}
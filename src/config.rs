@@ -0,0 +1,450 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use crate::logger;
+use crate::alerts::AlertConfig;
+use crate::remote_write::RemoteWriteConfig;
+use crate::privileged::HelperConfig;
+use crate::monitors::fault::FaultPlan;
+use crate::schedule::{BurstSampling, RecordingSchedule};
+
+// Command-line flag starting the dashboard in read-only mode whatever the config says
+pub const READ_ONLY_FLAG: &str = "--read-only";
+
+pub const MIN_UI_SCALE: f32 = 0.5;
+pub const MAX_UI_SCALE: f32 = 4.0;
+pub const MIN_POLLING_INTERVAL_MS: u64 = 100;
+pub const MAX_POLLING_INTERVAL_MS: u64 = 60_000;
+pub const MIN_PLOT_REFRESH_HZ: f32 = 1.0;
+pub const MAX_PLOT_REFRESH_HZ: f32 = 60.0;
+const DEFAULT_PLOT_REFRESH_HZ: f32 = 10.0;
+// First readings of a monitor left out of session min/max; sysinfo's first CPU usage is 0
+pub const DEFAULT_WARMUP_SAMPLES: usize = 1;
+
+// Colour scheme; System follows the OS light/dark setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    System,
+    Dark,
+    Light,
+}
+
+// A user-defined tab showing an arbitrary selection of metrics, e.g. "Thermals"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DashboardConfig {
+    pub name: String,
+    #[serde(default)]
+    pub metrics: Vec<String>, // Metric descriptor ids, in display order
+    #[serde(default)]
+    pub charts: Vec<DualAxisChart>,
+}
+
+// Two metrics with different units on one chart, e.g. GPU temperature vs fan RPM
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DualAxisChart {
+    pub left: String,  // Metric id plotted against the left Y axis
+    pub right: String, // Metric id plotted against the right Y axis
+}
+
+// Display name, unit and precision replacing a metric's built-in ones, e.g.
+// `[metric_overrides."cpu.package_temperature"]` with `label = "CPU Temp"`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricOverride {
+    pub label: Option<String>,
+    pub unit: Option<String>,    // Appended to the value as written, e.g. " °C" or "%"
+    pub decimals: Option<usize>, // Ignored for on/off metrics
+}
+
+// Labels added to every exported sample, so machines can be told apart downstream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportLabels {
+    // Fixed labels, e.g. location = "office" or rig = "bench-2"
+    pub custom: BTreeMap<String, String>,
+    // cpu_model and gpu_model from the detected hardware
+    pub hardware: bool,
+}
+
+impl Default for ExportLabels {
+    fn default() -> Self {
+        Self {
+            custom: BTreeMap::new(),
+            hardware: true,
+        }
+    }
+}
+
+// User configuration persisted between sessions as TOML
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    // Explicit pixels-per-point override; None follows the OS scaling factor
+    pub ui_scale: Option<f32>,
+    pub theme: Theme,
+    pub polling_interval_ms: u64,
+    // How often the dashboard redraws without input; independent of the display's refresh rate
+    pub plot_refresh_hz: f32,
+    pub alerts: AlertConfig,
+    pub remote_write: RemoteWriteConfig,
+    pub export_labels: ExportLabels,
+    // Per-monitor enable flags keyed by lowercase monitor name, e.g. nvidia = false
+    pub monitors: BTreeMap<String, bool>,
+    pub helper: HelperConfig,
+    // Block system sleep and display blanking while the dashboard is recording
+    pub keep_awake: bool,
+    // Guest mode, also set by --read-only: monitoring only, with alert actions, sleep
+    // inhibits and settings changes from the UI all disabled
+    pub read_only: bool,
+    // Replace host names, user names and serial numbers in crash reports, copied system
+    // info and exported instance labels with stable pseudonyms, so they can be posted publicly
+    pub anonymize: bool,
+    // Keys the pseudonyms; generated once so they stay the same across sessions
+    pub anonymize_salt: u64,
+    // Ask GitHub for the latest release at startup; off unless opted into
+    pub check_updates: bool,
+    // Custom tabs shown next to the default hardware layout
+    pub dashboards: Vec<DashboardConfig>,
+    // Memory histories may use before older samples are spilled to disk; 0 keeps everything in memory
+    pub history_budget_mb: u64,
+    // When the headless collector delivers samples; always when empty
+    pub recording: RecordingSchedule,
+    // Poll faster for a while when a trigger holds
+    pub burst: BurstSampling,
+    // Samples per monitor left out of session min/max while it warms up, keyed like `monitors`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub warmup_samples: BTreeMap<String, usize>,
+    // Per-metric display overrides keyed by metric id; applied in the UI, alerts and exports
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metric_overrides: BTreeMap<String, MetricOverride>,
+    // Names usable in query expressions, e.g. ambient = "motherboard.chassis_temperature"
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metric_aliases: BTreeMap<String, String>,
+    // Linux sensors chosen for temperature metrics, as `sensors -j` names them,
+    // e.g. "cpu.package_temperature" = "k10temp-pci-00c3/Tctl"
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub sensor_mappings: BTreeMap<String, String>,
+    // Rated endurance in TBW by drive model as SMART reports it, from the warranty,
+    // e.g. "Samsung SSD 980 PRO 1TB" = 600.0
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub drive_rated_tbw: BTreeMap<String, f64>,
+    // Testing only: faults injected into monitors, keyed like `monitors`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fault_injection: BTreeMap<String, FaultPlan>,
+    // Hardware fingerprint of the machine profile this was loaded for; saves go there
+    #[serde(skip)]
+    pub profile: Option<String>,
+}
+
+pub type SharedConfig = Arc<RwLock<AppConfig>>;
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            ui_scale: None,
+            theme: Theme::default(),
+            polling_interval_ms: 1000,
+            plot_refresh_hz: DEFAULT_PLOT_REFRESH_HZ,
+            alerts: AlertConfig::default(),
+            remote_write: RemoteWriteConfig::default(),
+            export_labels: ExportLabels::default(),
+            monitors: BTreeMap::new(),
+            helper: HelperConfig::default(),
+            keep_awake: false,
+            read_only: false,
+            anonymize: false,
+            anonymize_salt: 0,
+            check_updates: false,
+            dashboards: Vec::new(),
+            history_budget_mb: 256,
+            recording: RecordingSchedule::default(),
+            burst: BurstSampling::default(),
+            warmup_samples: BTreeMap::new(),
+            metric_overrides: BTreeMap::new(),
+            metric_aliases: BTreeMap::new(),
+            sensor_mappings: BTreeMap::new(),
+            drive_rated_tbw: BTreeMap::new(),
+            fault_injection: BTreeMap::new(),
+            profile: None,
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn new_shared(config: AppConfig) -> SharedConfig {
+        Arc::new(RwLock::new(config))
+    }
+
+    pub fn config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        // Config file lives in the config directory, or next to the executable when portable
+        Ok(crate::paths::config_dir()?.join("simple_performance_dashboard.toml"))
+    }
+
+    // Per-machine config for an install shared between machines (USB stick, synced
+    // dotfiles): profiles/<fingerprint>.toml next to the shared config file
+    pub fn profile_path(fingerprint: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let mut path = Self::config_path()?;
+        path.pop(); // Remove config file name
+        path.push("profiles");
+        let name: String = fingerprint
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        path.push(format!("{}.toml", name));
+        Ok(path)
+    }
+
+    // This machine's profile by HardwareInfo::fingerprint. A machine without one starts
+    // from the shared config, and its first save creates the profile
+    pub fn load_for_machine(fingerprint: &str) -> Self {
+        let mut config = match Self::profile_path(fingerprint) {
+            Ok(path) if path.exists() => match Self::load_from(&path) {
+                Ok(config) => {
+                    logger::log_info(&format!("Loaded machine profile from {}", path.display()));
+                    config
+                }
+                Err(e) => {
+                    logger::log_error("Failed to load machine profile, using the shared config", &*e);
+                    Self::load()
+                }
+            },
+            _ => Self::load(),
+        };
+        config.profile = Some(fingerprint.to_string());
+        config
+    }
+
+    // Load the config file, falling back to defaults when it is missing or invalid
+    pub fn load() -> Self {
+        let path = match Self::config_path() {
+            Ok(path) => path,
+            Err(e) => {
+                logger::log_error("Failed to resolve config path", &*e);
+                return Self::default();
+            }
+        };
+
+        if !path.exists() {
+            logger::log_info("No config file found, using defaults");
+            return Self::default();
+        }
+
+        match Self::load_from(&path) {
+            Ok(config) => {
+                logger::log_info(&format!("Loaded config from {}", path.display()));
+                config
+            }
+            Err(e) => {
+                logger::log_error("Failed to load config, using defaults", &*e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = fs::read_to_string(path)?;
+        Self::from_toml(&contents)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut config: AppConfig = toml::from_str(contents)?;
+        config.sanitize();
+        Ok(config)
+    }
+
+    pub fn to_toml(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = match &self.profile {
+            Some(fingerprint) => Self::profile_path(fingerprint)?,
+            None => Self::config_path()?,
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        self.save_to(&path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    // Monitors are enabled unless explicitly switched off
+    pub fn monitor_enabled(&self, name: &str) -> bool {
+        self.monitors.get(&name.to_lowercase()).copied().unwrap_or(true)
+    }
+    
+    pub fn set_monitor_enabled(&mut self, name: &str, enabled: bool) {
+        self.monitors.insert(name.to_lowercase(), enabled);
+    }
+    
+    pub fn warmup_samples(&self, monitor: &str) -> usize {
+        self.warmup_samples.get(&monitor.to_lowercase()).copied().unwrap_or(DEFAULT_WARMUP_SAMPLES)
+    }
+    
+    pub fn fault_plan(&self, monitor: &str) -> Option<&FaultPlan> {
+        self.fault_injection.get(&monitor.to_lowercase())
+    }
+    
+    // Expressions alert rules and dashboards use in place of a metric id, evaluated every poll
+    pub fn query_expressions(&self) -> Vec<String> {
+        let mut expressions: Vec<String> = self
+            .alerts
+            .rules
+            .iter()
+            .map(|rule| &rule.metric)
+            .chain(self.dashboards.iter().flat_map(|dashboard| &dashboard.metrics))
+            .filter(|text| !text.trim().is_empty() && !crate::query::is_metric_id(text))
+            .cloned()
+            .collect();
+        expressions.sort();
+        expressions.dedup();
+        expressions
+    }
+    
+    // Salt for anonymized reports, created the first time they are turned on
+    pub fn ensure_anonymize_salt(&mut self) -> u64 {
+        if self.anonymize_salt == 0 {
+            self.anonymize_salt = crate::anonymize::new_salt();
+        }
+        self.anonymize_salt
+    }
+    
+    // Clamp values that would leave the application unusable
+    pub(crate) fn sanitize(&mut self) {
+        if let Some(scale) = self.ui_scale {
+            self.ui_scale = if scale.is_finite() {
+                Some(scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE))
+            } else {
+                None
+            };
+        }
+        self.polling_interval_ms = self.polling_interval_ms.clamp(MIN_POLLING_INTERVAL_MS, MAX_POLLING_INTERVAL_MS);
+        self.plot_refresh_hz = if self.plot_refresh_hz.is_finite() {
+            self.plot_refresh_hz.clamp(MIN_PLOT_REFRESH_HZ, MAX_PLOT_REFRESH_HZ)
+        } else {
+            DEFAULT_PLOT_REFRESH_HZ
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_default() {
+        let config = AppConfig::default();
+        assert!(config.ui_scale.is_none());
+    }
+
+    #[test]
+    fn test_config_toml_round_trip() {
+        let config = AppConfig {
+            ui_scale: Some(2.0),
+            ..Default::default()
+        };
+
+        let toml = config.to_toml().expect("Failed to serialize config");
+        let loaded = AppConfig::from_toml(&toml).expect("Failed to parse config");
+
+        assert_eq!(loaded, config);
+    }
+
+    #[test]
+    fn test_config_missing_fields_use_defaults() {
+        let config = AppConfig::from_toml("").expect("Empty config should parse");
+        assert_eq!(config, AppConfig::default());
+    }
+
+    #[test]
+    fn test_config_ui_scale_is_clamped() {
+        let config = AppConfig::from_toml("ui_scale = 25.0").unwrap();
+        assert_eq!(config.ui_scale, Some(MAX_UI_SCALE));
+
+        let config = AppConfig::from_toml("ui_scale = 0.1").unwrap();
+        assert_eq!(config.ui_scale, Some(MIN_UI_SCALE));
+    }
+
+    #[test]
+    fn test_config_polling_interval_is_clamped() {
+        assert_eq!(AppConfig::default().polling_interval_ms, 1000);
+        assert_eq!(AppConfig::from_toml("polling_interval_ms = 5").unwrap().polling_interval_ms, MIN_POLLING_INTERVAL_MS);
+        assert_eq!(AppConfig::from_toml("theme = \"dark\"").unwrap().theme, Theme::Dark);
+    }
+
+    #[test]
+    fn test_config_plot_refresh_is_clamped() {
+        assert_eq!(AppConfig::default().plot_refresh_hz, 10.0);
+        assert_eq!(AppConfig::from_toml("plot_refresh_hz = 144.0").unwrap().plot_refresh_hz, MAX_PLOT_REFRESH_HZ);
+        assert_eq!(AppConfig::from_toml("plot_refresh_hz = 0.0").unwrap().plot_refresh_hz, MIN_PLOT_REFRESH_HZ);
+    }
+
+    #[test]
+    fn test_config_monitor_flags() {
+        let mut config = AppConfig::from_toml("[monitors]\nnvidia = false\n").unwrap();
+        assert!(!config.monitor_enabled("NVIDIA"));
+        assert!(config.monitor_enabled("Generic"));
+
+        config.set_monitor_enabled("NVIDIA", true);
+        assert!(config.monitor_enabled("NVIDIA"));
+    }
+
+    #[test]
+    fn test_config_dashboards() {
+        let toml = "[[dashboards]]\nname = \"Thermals\"\nmetrics = [\"cpu.package_temperature\", \"gpu.hotspot_temperature\"]\n";
+        let mut config = AppConfig::from_toml(toml).unwrap();
+        assert_eq!(config.dashboards.len(), 1);
+        assert_eq!(config.dashboards[0].name, "Thermals");
+        assert_eq!(config.dashboards[0].metrics[1], "gpu.hotspot_temperature");
+        assert!(config.dashboards[0].charts.is_empty());
+
+        config.dashboards[0].charts.push(DualAxisChart {
+            left: "gpu.hotspot_temperature".to_string(),
+            right: "motherboard.chassis_fan_speed".to_string(),
+        });
+
+        let reloaded = AppConfig::from_toml(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(reloaded, config);
+    }
+
+    #[test]
+    fn test_config_profile_per_fingerprint() {
+        let path = AppConfig::profile_path("Amd/1x8/[Nvidia]/Linux").unwrap();
+        assert_eq!(path.file_name().unwrap(), "Amd_1x8__Nvidia__Linux.toml");
+        assert_eq!(path.parent().unwrap().file_name().unwrap(), "profiles");
+        assert_ne!(path, AppConfig::profile_path("Intel/1x8/[Nvidia]/Linux").unwrap());
+
+        // Which profile a config belongs to is never written into it
+        let config = AppConfig { profile: Some("Amd/1x8/[Nvidia]/Linux".to_string()), ..Default::default() };
+        assert_eq!(AppConfig::from_toml(&config.to_toml().unwrap()).unwrap(), AppConfig::default());
+    }
+
+    #[test]
+    fn test_config_invalid_toml_is_error() {
+        assert!(AppConfig::from_toml("ui_scale = \"large\"").is_err());
+    }
+
+    #[test]
+    fn test_config_save_and_load_file() {
+        let path = std::env::temp_dir().join("spd_config_save_and_load_test.toml");
+        let config = AppConfig {
+            ui_scale: Some(1.5),
+            ..Default::default()
+        };
+
+        config.save_to(&path).expect("Failed to save config");
+        let loaded = AppConfig::load_from(&path).expect("Failed to load config");
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded, config);
+    }
+}
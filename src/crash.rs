@@ -0,0 +1,164 @@
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use chrono::Local;
+use parking_lot::Mutex;
+use crate::logger;
+
+// Log lines included in a report
+pub const LOG_TAIL_LINES: usize = 50;
+
+// Points at the newest report so the next launch can offer it if the crash was fatal
+const LAST_CRASH_MARKER: &str = "last_crash.txt";
+
+static HARDWARE_SUMMARY: OnceLock<String> = OnceLock::new();
+static NEW_REPORT: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// Write a crash report for every panic, then fall through to the default hook.
+// A panic that isn't caught ends the process, so the report is offered again on the next launch
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write_report(info) {
+            Ok(path) => {
+                logger::log_warning(&format!("Panic: crash report written to {}", path.display()));
+                *NEW_REPORT.lock() = Some(path);
+            }
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+        default_hook(info);
+    }));
+}
+
+// Included in reports once detection has run
+pub fn set_hardware_summary(summary: String) {
+    let _ = HARDWARE_SUMMARY.set(summary);
+}
+
+// Report written during this session (e.g. a monitor panicked), taken once for the UI.
+// The session survived it, so the next launch needn't offer it again
+pub fn take_new_report() -> Option<PathBuf> {
+    let report = NEW_REPORT.lock().take()?;
+    if let Ok(dir) = report_dir() {
+        let _ = fs::remove_file(dir.join(LAST_CRASH_MARKER));
+    }
+    Some(report)
+}
+
+// Report left by a crash in a previous session, cleared once read
+pub fn take_previous_report() -> Option<PathBuf> {
+    let marker = report_dir().ok()?.join(LAST_CRASH_MARKER);
+    let path = PathBuf::from(fs::read_to_string(&marker).ok()?.trim());
+    let _ = fs::remove_file(&marker);
+    path.exists().then_some(path)
+}
+
+// Reveal the report in the platform's default viewer
+pub fn open_report(path: &Path) -> std::io::Result<()> {
+    let opener = if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(opener).arg(path).spawn().map(|_| ())
+}
+
+fn write_report(info: &PanicHookInfo) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let message = panic_payload(info);
+    let location = info.location().map(|location| location.to_string()).unwrap_or_else(|| "unknown".to_string());
+    let thread = std::thread::current().name().unwrap_or("unnamed").to_string();
+    let log_tail = logger::log_file_path()
+        .map(|path| log_tail(&path, LOG_TAIL_LINES))
+        .unwrap_or_default();
+    let hardware = HARDWARE_SUMMARY.get().map(String::as_str).unwrap_or("not detected yet");
+
+    let report = build_report(&message, &location, &thread, &Backtrace::force_capture().to_string(), hardware, &log_tail);
+    // Paths in the backtrace and log lines carry the user name
+    let report = crate::anonymize::scrub(&report);
+
+    let dir = report_dir()?;
+    let path = dir.join(format!("crash_{}.txt", Local::now().format("%Y%m%d_%H%M%S")));
+    fs::write(&path, report)?;
+    fs::write(dir.join(LAST_CRASH_MARKER), path.display().to_string())?;
+    Ok(path)
+}
+
+pub fn build_report(
+    message: &str,
+    location: &str,
+    thread: &str,
+    backtrace: &str,
+    hardware: &str,
+    log_tail: &[String],
+) -> String {
+    let mut report = String::new();
+    report.push_str("Simple Performance Dashboard crash report\n");
+    report.push_str(&format!("Version: {}\n", env!("CARGO_PKG_VERSION")));
+    report.push_str(&format!("Time: {}\n", Local::now().format("%Y-%m-%d %H:%M:%S %z")));
+    report.push_str(&format!("OS: {} {}\n", std::env::consts::OS, std::env::consts::ARCH));
+    report.push_str(&format!("Hardware: {}\n\n", hardware));
+    report.push_str(&format!("Panic in thread '{}' at {}:\n{}\n\n", thread, location, message));
+    report.push_str("Backtrace:\n");
+    report.push_str(backtrace);
+    report.push_str(&format!("\n\nLast {} log lines:\n", log_tail.len()));
+    for line in log_tail {
+        report.push_str(line);
+        report.push('\n');
+    }
+    report
+}
+
+pub fn log_tail(path: &Path, lines: usize) -> Vec<String> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let all: Vec<&str> = contents.lines().collect();
+    all[all.len().saturating_sub(lines)..].iter().map(|line| line.to_string()).collect()
+}
+
+// Reports sit next to the log file
+fn report_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    crate::paths::data_dir()
+}
+
+fn panic_payload(info: &PanicHookInfo) -> String {
+    if let Some(message) = info.payload().downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_report_contains_diagnostics() {
+        let log = vec!["12:00:00 [INFO] started".to_string(), "12:00:01 [WARN] slow poll".to_string()];
+        let report = build_report("sensor exploded", "src/monitors/nvidia.rs:42:5", "spd-worker", "0: main", "CPU=AMD", &log);
+
+        assert!(report.contains("Panic in thread 'spd-worker' at src/monitors/nvidia.rs:42:5"));
+        assert!(report.contains("sensor exploded"));
+        assert!(report.contains("Hardware: CPU=AMD"));
+        assert!(report.contains("Last 2 log lines:\n12:00:00 [INFO] started\n12:00:01 [WARN] slow poll\n"));
+    }
+
+    #[test]
+    fn test_log_tail_keeps_last_lines() {
+        let path = std::env::temp_dir().join("spd_crash_log_tail_test.log");
+        fs::write(&path, "one\ntwo\nthree\n").expect("Failed to write log");
+
+        assert_eq!(log_tail(&path, 2), vec!["two".to_string(), "three".to_string()]);
+        assert_eq!(log_tail(&path, 10).len(), 3);
+        let _ = fs::remove_file(&path);
+
+        assert!(log_tail(&path, 2).is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use chrono::{DateTime, Local, Utc};
+use crate::config::ExportLabels;
+use crate::model::{AppState, METRIC_DESCRIPTORS};
+
+// One recorded sample in long format: which metric, when, and its value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub metric: &'static str,
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+}
+
+// Every sample of the session, grouped by metric id in catalog order
+pub fn session_samples(state: &AppState) -> Vec<Sample> {
+    METRIC_DESCRIPTORS
+        .iter()
+        .filter_map(|descriptor| state.session_samples(descriptor.id).map(|samples| (descriptor.id, samples)))
+        .flat_map(|(id, samples)| {
+            samples
+                .into_iter()
+                .map(move |(timestamp, value)| Sample { metric: id, timestamp, value })
+        })
+        .collect()
+}
+
+// One metric's samples as CSV with a header row, for a spreadsheet or the clipboard
+pub fn metric_csv(state: &AppState, metric_id: &str) -> String {
+    let mut csv = String::from("timestamp,value\n");
+    for (timestamp, value) in state.session_samples(metric_id).unwrap_or_default() {
+        csv.push_str(&format!("{},{}\n", timestamp.to_rfc3339(), value));
+    }
+    csv
+}
+
+// Label name usable by Prometheus and line protocol alike: letters, digits and
+// underscores, not starting with a digit
+pub fn label_name(name: &str) -> String {
+    let mut sanitized: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if sanitized.is_empty() || sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+// Labels every exporter attaches to its samples: the detected hardware models, then
+// the configured ones, which win on a clash. Empty values are left out
+pub fn sample_labels(labels: &ExportLabels, state: &AppState) -> BTreeMap<String, String> {
+    let mut all = BTreeMap::new();
+    if labels.hardware {
+        for (name, model) in [("cpu_model", &state.cpu.name), ("gpu_model", &state.gpu.name)] {
+            if let Some(model) = model {
+                all.insert(name.to_string(), model.trim().to_string());
+            }
+        }
+    }
+    for (name, value) in &labels.custom {
+        all.insert(label_name(name), value.trim().to_string());
+    }
+    all.retain(|_, value| !value.is_empty());
+    all
+}
+
+// Default export location: the data directory, named after the session start
+pub fn default_export_path(state: &AppState, extension: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut path = crate::paths::data_dir()?;
+    let started = state.session_start.with_timezone(&Local).format("%Y%m%d_%H%M%S");
+    path.push(format!("session_{}.{}", started, extension));
+    Ok(path)
+}
+
+// Write the session as a Parquet file with columns (metric, timestamp, value), sorted by
+// metric then time, so it loads straight into pandas/polars for analysis
+#[cfg(feature = "parquet")]
+pub fn export_parquet(state: &AppState, path: &std::path::Path) -> Result<usize, Box<dyn std::error::Error>> {
+    use std::fs::File;
+    use std::sync::Arc;
+    use arrow_array::{Float64Array, RecordBatch, StringArray, TimestampMillisecondArray};
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::metadata::KeyValue;
+    use parquet::file::properties::WriterProperties;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("metric", DataType::Utf8, false),
+        Field::new("timestamp", DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())), false),
+        Field::new("value", DataType::Float64, false),
+    ]));
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_key_value_metadata(Some(vec![KeyValue::new(
+            "session_start".to_string(),
+            state.session_start.to_rfc3339(),
+        )]))
+        .build();
+
+    let file = File::create(path)?;
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))?;
+    let mut rows = 0;
+
+    // One record batch per metric keeps memory bounded for multi-hour sessions
+    for descriptor in METRIC_DESCRIPTORS {
+        let Some(samples) = state.session_samples(descriptor.id) else {
+            continue;
+        };
+        if samples.is_empty() {
+            continue;
+        }
+
+        let metrics = StringArray::from(vec![descriptor.id; samples.len()]);
+        let timestamps = TimestampMillisecondArray::from_iter_values(
+            samples.iter().map(|(timestamp, _)| timestamp.timestamp_millis()),
+        )
+        .with_timezone("UTC");
+        let values = Float64Array::from_iter_values(samples.iter().map(|(_, value)| *value));
+
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(metrics), Arc::new(timestamps), Arc::new(values)],
+        )?;
+        writer.write(&batch)?;
+        rows += samples.len();
+    }
+
+    writer.close()?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_sample_labels_add_hardware_and_custom_labels() {
+        let mut state = sample_state();
+        state.cpu.name = Some("AMD Ryzen 7 7800X3D 8-Core Processor ".to_string());
+        let mut labels = ExportLabels::default();
+        labels.custom.insert("Location".to_string(), "office".to_string());
+        labels.custom.insert("rig name".to_string(), "bench-2".to_string());
+        labels.custom.insert("gpu_model".to_string(), "RTX 4090 (loaner)".to_string());
+        labels.custom.insert("empty".to_string(), " ".to_string());
+
+        let all = sample_labels(&labels, &state);
+        assert_eq!(all.get("cpu_model").map(String::as_str), Some("AMD Ryzen 7 7800X3D 8-Core Processor"));
+        assert_eq!(all.get("gpu_model").map(String::as_str), Some("RTX 4090 (loaner)"));
+        assert_eq!(all.get("location").map(String::as_str), Some("office"));
+        assert_eq!(all.get("rig_name").map(String::as_str), Some("bench-2"));
+        assert!(!all.contains_key("empty"));
+
+        labels.hardware = false;
+        assert!(!sample_labels(&labels, &state).contains_key("cpu_model"));
+        assert_eq!(label_name("2nd-floor"), "_2nd_floor");
+    }
+
+    fn sample_state() -> AppState {
+        let mut state = AppState::default();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        state.session_start = start;
+        state.cpu.utilization.record_at(start, 10.0);
+        state.cpu.utilization.record_at(start + chrono::Duration::seconds(1), 20.0);
+        state.gpu.clock_speed.record_at(start, 1500u32);
+        state
+    }
+
+    #[test]
+    fn test_session_samples_grouped_by_metric() {
+        let samples = session_samples(&sample_state());
+
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[0].metric, "cpu.utilization");
+        assert_eq!(samples[1].value, 20.0);
+        assert_eq!(samples[2].metric, "gpu.clock_speed");
+        assert_eq!(samples[2].value, 1500.0);
+    }
+
+    #[test]
+    fn test_metric_csv_lists_one_metric() {
+        let csv = metric_csv(&sample_state(), "cpu.utilization");
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines, vec![
+            "timestamp,value",
+            "2024-01-01T12:00:00+00:00,10",
+            "2024-01-01T12:00:01+00:00,20",
+        ]);
+        assert_eq!(metric_csv(&sample_state(), "gpu.fan_speed"), "timestamp,value\n");
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_export_parquet_round_trip() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let path = std::env::temp_dir().join("spd_export_parquet_test.parquet");
+        let rows = export_parquet(&sample_state(), &path).expect("Failed to export parquet");
+        assert_eq!(rows, 3);
+
+        let file = std::fs::File::open(&path).expect("Failed to open export");
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .expect("Failed to read parquet metadata")
+            .build()
+            .expect("Failed to build parquet reader");
+        let read_rows: usize = reader.map(|batch| batch.expect("Failed to read batch").num_rows()).sum();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(read_rows, 3);
+    }
+}
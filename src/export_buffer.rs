@@ -0,0 +1,210 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Buffers live next to the config and log file, one directory per exporter
+const BUFFER_DIR: &str = "export_buffer";
+
+// Why a payload wasn't delivered. An unavailable target, e.g. down or overloaded, is
+// worth retrying; a rejected payload, e.g. HTTP 400 for a malformed or out-of-order
+// one, would fail the same way every time
+#[derive(Debug, PartialEq)]
+pub enum SendFailure<E> {
+    Unavailable(E),
+    Rejected(E),
+}
+
+// Payloads a network exporter couldn't deliver, kept on disk until the target is back.
+// One file per payload, numbered in the order they were buffered, so a crash or
+// restart loses nothing already written and replay keeps the original order
+#[derive(Debug, Clone)]
+pub struct ExportBuffer {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl ExportBuffer {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Result<Self, Box<dyn std::error::Error>> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, max_bytes })
+    }
+
+    // Buffer for one exporter, surviving across sessions
+    pub fn for_exporter(name: &str, max_bytes: u64) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::new(crate::paths::data_dir()?.join(BUFFER_DIR).join(name), max_bytes)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn set_max_bytes(&mut self, max_bytes: u64) {
+        self.max_bytes = max_bytes;
+    }
+
+    // Buffered payloads, oldest first
+    fn entries(&self) -> Vec<(u64, PathBuf)> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut entries: Vec<(u64, PathBuf)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension()? != "bin" {
+                    return None;
+                }
+                let sequence = path.file_stem()?.to_str()?.parse().ok()?;
+                Some((sequence, path))
+            })
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries().is_empty()
+    }
+
+    pub fn size_bytes(&self) -> u64 {
+        self.entries()
+            .iter()
+            .filter_map(|(_, path)| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    // Keep a payload for later, dropping the oldest ones once over the size limit
+    pub fn push(&self, payload: &[u8]) -> std::io::Result<()> {
+        let sequence = self.entries().last().map_or(0, |(sequence, _)| sequence + 1);
+        let path = self.dir.join(format!("{:020}.bin", sequence));
+        // Written aside and renamed, so a crash mid-write never leaves a truncated payload
+        let partial = path.with_extension("partial");
+        fs::write(&partial, payload)?;
+        fs::rename(&partial, &path)?;
+        self.trim();
+        Ok(())
+    }
+
+    fn trim(&self) {
+        let entries = self.entries();
+        let mut total = self.size_bytes();
+        for (_, path) in &entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            let size = fs::metadata(path).map_or(0, |metadata| metadata.len());
+            if fs::remove_file(path).is_ok() {
+                total = total.saturating_sub(size);
+                crate::logger::log_warning(&format!("Export buffer full, dropped {}", path.display()));
+            }
+        }
+    }
+
+    // Hand buffered payloads to send, oldest first, removing each one it accepts or
+    // rejects; stops once the target is unavailable so the rest wait for the next attempt
+    pub fn replay<E: std::fmt::Display>(&self, mut send: impl FnMut(&[u8]) -> Result<(), SendFailure<E>>) -> Result<usize, E> {
+        let mut replayed = 0;
+        for (_, path) in self.entries() {
+            let Ok(payload) = fs::read(&path) else {
+                continue;
+            };
+            match send(&payload) {
+                Ok(()) => replayed += 1,
+                Err(SendFailure::Rejected(e)) => {
+                    crate::logger::log_warning(&format!("Export target rejected {} ({}), dropped it", path.display(), e));
+                }
+                Err(SendFailure::Unavailable(e)) => return Err(e),
+            }
+            let _ = fs::remove_file(&path);
+        }
+        Ok(replayed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(name: &str, max_bytes: u64) -> ExportBuffer {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        ExportBuffer::new(dir, max_bytes).expect("Failed to create buffer dir")
+    }
+
+    #[test]
+    fn test_buffer_replays_in_order_and_stops_when_unavailable() {
+        let buffer = buffer("spd_export_buffer_replay_test", 1024);
+        for payload in [b"one", b"two", b"six"] {
+            buffer.push(payload).unwrap();
+        }
+        assert_eq!(buffer.len(), 3);
+
+        // The target drops out again after the first payload
+        let mut sent = Vec::new();
+        let result = buffer.replay(|payload| {
+            if sent.is_empty() {
+                sent.push(payload.to_vec());
+                Ok(())
+            } else {
+                Err(SendFailure::Unavailable("unreachable"))
+            }
+        });
+        assert_eq!(result, Err("unreachable"));
+        assert_eq!(sent, vec![b"one".to_vec()]);
+
+        // A new buffer over the same directory, as after a restart, picks up the rest
+        let reopened = ExportBuffer::new(buffer.dir().to_path_buf(), 1024).unwrap();
+        reopened.push(b"ten").unwrap();
+        let mut sent = Vec::new();
+        let replayed = reopened.replay(|payload| {
+            sent.push(payload.to_vec());
+            Ok::<(), SendFailure<&str>>(())
+        });
+        assert_eq!(replayed, Ok(3));
+        assert_eq!(sent, vec![b"two".to_vec(), b"six".to_vec(), b"ten".to_vec()]);
+        assert!(reopened.is_empty());
+        let _ = fs::remove_dir_all(reopened.dir());
+    }
+
+    #[test]
+    fn test_buffer_drops_oldest_over_limit() {
+        let buffer = buffer("spd_export_buffer_limit_test", 10);
+        for payload in [b"aaaa", b"bbbb", b"cccc"] {
+            buffer.push(payload).unwrap();
+        }
+        assert_eq!(buffer.size_bytes(), 8);
+
+        let mut sent = Vec::new();
+        buffer.replay(|payload| {
+            sent.push(payload.to_vec());
+            Ok::<(), SendFailure<&str>>(())
+        }).unwrap();
+        assert_eq!(sent, vec![b"bbbb".to_vec(), b"cccc".to_vec()]);
+        let _ = fs::remove_dir_all(buffer.dir());
+    }
+
+    #[test]
+    fn test_buffer_drops_rejected_payloads_and_carries_on() {
+        let buffer = buffer("spd_export_buffer_rejected_test", 1024);
+        for payload in [b"bad", b"one", b"two"] {
+            buffer.push(payload).unwrap();
+        }
+
+        let mut sent = Vec::new();
+        let replayed = buffer.replay(|payload| {
+            if payload == b"bad" {
+                return Err(SendFailure::Rejected("HTTP 400"));
+            }
+            sent.push(payload.to_vec());
+            Ok(())
+        });
+        assert_eq!(replayed, Ok(2));
+        assert_eq!(sent, vec![b"one".to_vec(), b"two".to_vec()]);
+        assert!(buffer.is_empty());
+        let _ = fs::remove_dir_all(buffer.dir());
+    }
+}
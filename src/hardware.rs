@@ -1,56 +1,519 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
-use crate::model::SharedAppState;
+use std::time::{Duration, Instant};
+use chrono::{Local, Utc};
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+use tokio::time::{Interval, MissedTickBehavior};
+use crate::model::{AppState, MonitorSelection, SharedAppState};
+use crate::config::{AppConfig, SharedConfig};
+use crate::alerts::{self, ActionDispatcher, AlertEngine, AlertTransition, AlertTransitionKind, Alarm};
 use crate::logger;
 use crate::hardware_detection::{HardwareDetector, HardwareInfo};
-use crate::monitors::MonitorRegistry;
+use crate::monitors::{panic_message, MonitorHeartbeat, MonitorRegistry};
+use crate::monitors::synthetic::SyntheticLoad;
+use crate::schedule::BurstController;
+use crate::scripting::{self, ScriptHost};
+use crate::query::QueryEngine;
+
+// A monitor stuck in update_metrics this long is treated as hung
+const MONITOR_TIMEOUT: Duration = Duration::from_secs(10);
+// How often the watchdog checks on the monitor tasks
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+// Wait before rerunning a failed task, doubled after each further failure in a row
+const RESTART_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
 
 pub struct HardwarePoller {
     state: SharedAppState,
     polling_interval: Duration,
     hardware_info: HardwareInfo,
     monitor_registry: MonitorRegistry,
+    config: SharedConfig,
+    alert_engine: AlertEngine,
+    action_dispatcher: ActionDispatcher,
+    alarm: Alarm,
+    spill_failed: bool, // Logged once rather than on every poll
+    burst: BurstController,
+    scripts: ScriptHost,
+    scripts_generation: Option<u64>, // Reload generation the scripts were loaded at; None before the first load
+    queries: QueryEngine,
 }
 
 impl HardwarePoller {
     pub fn new(state: SharedAppState, polling_interval_ms: u64) -> Self {
+        Self::with_config(state, polling_interval_ms, AppConfig::new_shared(AppConfig::default()))
+    }
+    
+    pub fn with_config(state: SharedAppState, polling_interval_ms: u64, config: SharedConfig) -> Self {
+        Self::with_monitors(state, polling_interval_ms, config, |registry, hardware_info| {
+            registry.register_for_hardware(hardware_info)
+        })
+    }
+    
+    // Developer mode: generated data instead of the hardware monitors, for profiling
+    // the UI and storage without real sensors
+    pub fn with_synthetic_load(
+        state: SharedAppState,
+        polling_interval_ms: u64,
+        config: SharedConfig,
+        load: SyntheticLoad,
+    ) -> Self {
+        let poller = Self::with_monitors(state, polling_interval_ms, config, |registry, _| registry.register_synthetic_load(load));
+        // Generated sessions only compare with each other, never with the real hardware
+        poller.state.write().poller.hardware_fingerprint = "synthetic".to_string();
+        poller
+    }
+    
+    // Register monitors with a custom closure, e.g. a subset of the detected hardware
+    // monitors plus an embedder's own; the closure returns what it decided for each
+    pub fn with_monitors(
+        state: SharedAppState,
+        polling_interval_ms: u64,
+        config: SharedConfig,
+        register: impl FnOnce(&mut MonitorRegistry, &HardwareInfo) -> Vec<MonitorSelection>,
+    ) -> Self {
         let hardware_info = HardwareDetector::detect();
-        let mut monitor_registry = MonitorRegistry::new();
-        monitor_registry.register_all_monitors();
-        
-        logger::log_info(&format!("Detected hardware: CPU={:?}, GPUs={:?}, Platform={:?}", 
+        let topology = &hardware_info.cpu_topology;
+        let hardware_summary = format!("CPU={:?} ({} sockets, {} cores, clusters: {}), GPUs={:?}, Platform={:?}, {}", 
             hardware_info.cpu_vendor, 
+            topology.sockets,
+            topology.physical_cores,
+            topology.clusters.iter().map(|cluster| cluster.name.as_str()).collect::<Vec<_>>().join("/"),
             hardware_info.gpu_vendors, 
-            hardware_info.platform
-        ));
+            hardware_info.platform,
+            hardware_info.versions.summary()
+        );
+        logger::log_info(&format!("Detected hardware: {}", hardware_summary));
+        
+        let mut monitor_registry = MonitorRegistry::new();
+        monitor_registry.set_config(config.clone());
+        let selections = register(&mut monitor_registry, &hardware_info);
+        {
+            let mut app_state = state.write();
+            app_state.poller.hardware_summary = hardware_summary;
+            app_state.poller.hardware_fingerprint = hardware_info.fingerprint();
+            app_state.poller.versions = hardware_info.versions.clone();
+            app_state.poller.monitor_selection = selections;
+            app_state.cpu.topology = hardware_info.cpu_topology.clone();
+        }
+        
+        // Initialize monitors for detected hardware
+        if let Err(e) = monitor_registry.initialize_for_hardware(&hardware_info) {
+            logger::log_error("Failed to initialize hardware monitors", &*e);
+        }
         
+        Self {
+            monitor_registry,
+            ..Self::without_monitors(state, Duration::from_millis(polling_interval_ms), hardware_info, config)
+        }
+    }
+    
+    // Alert, script and query state start empty, e.g. for the alerts task restarted
+    // after a panic; the monitors run in their own tasks
+    fn without_monitors(state: SharedAppState, polling_interval: Duration, hardware_info: HardwareInfo, config: SharedConfig) -> Self {
         Self {
             state,
-            polling_interval: Duration::from_millis(polling_interval_ms),
+            polling_interval,
             hardware_info,
-            monitor_registry,
+            monitor_registry: MonitorRegistry::new(),
+            config,
+            alert_engine: AlertEngine::new(),
+            action_dispatcher: ActionDispatcher::new(),
+            alarm: Alarm::new(),
+            spill_failed: false,
+            burst: BurstController::new(),
+            scripts: ScriptHost::new(),
+            scripts_generation: None,
+            queries: QueryEngine::new(),
         }
     }
     
+    pub fn hardware_info(&self) -> &HardwareInfo {
+        &self.hardware_info
+    }
+    
     pub fn start_polling_thread(mut self) -> thread::JoinHandle<()> {
-        // Initialize monitors for detected hardware
-        if let Err(e) = self.monitor_registry.initialize_for_hardware(&self.hardware_info) {
-            logger::log_error("Failed to initialize hardware monitors", &*e);
-        }
-        
         thread::spawn(move || {
+            let mut ticks = TickScheduler::new();
             loop {
                 self.poll_hardware();
-                thread::sleep(self.polling_interval);
+                let interval_ms = self.state.read().polling_interval_ms;
+                ticks.wait(Duration::from_millis(interval_ms));
             }
         })
     }
     
+    // Poll each monitor in its own task on the runtime, so a slow or hung backend
+    // only delays itself. Alert evaluation runs as a separate task, and a watchdog
+    // task disables any monitor stuck past MONITOR_TIMEOUT. A task that panics is
+    // run again after a backoff, counted in poller.restarts.
+    pub fn start_async(mut self, runtime: &Handle) -> Vec<JoinHandle<()>> {
+        // Restarts rely on catching the panic; the release profile unwinds for this
+        if cfg!(panic = "abort") {
+            logger::log_warning("Built with panic = \"abort\": a panicking monitor or task will end the dashboard instead of being restarted");
+        }
+        let registry = std::mem::take(&mut self.monitor_registry);
+        let mut handles = Vec::new();
+        let mut watched = Vec::new();
+        
+        for mut task in registry.into_tasks() {
+            let state = self.state.clone();
+            let polling_interval = self.polling_interval;
+            watched.push((task.name(), task.heartbeat(), handles.len()));
+            
+            handles.push(runtime.spawn(async move {
+                let mut ticker = tokio::time::interval(polling_interval);
+                ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                let mut failures = 0;
+                loop {
+                    ticker.tick().await;
+                    follow_polling_interval(&mut ticker, &state);
+                    // Sensor APIs block, so run them off the async workers. Panics in
+                    // update_metrics disable the monitor; any other panic, e.g. while
+                    // initializing it, leaves the task to be run again
+                    let poll_state = state.clone();
+                    let result = tokio::task::spawn_blocking(move || {
+                        let outcome = panic::catch_unwind(AssertUnwindSafe(|| task.poll(&poll_state)));
+                        (task, outcome)
+                    }).await;
+                    match result {
+                        Ok((returned, Ok(true))) => {
+                            task = returned;
+                            failures = 0;
+                        }
+                        Ok((_, Ok(false))) => break,
+                        Ok((returned, Err(payload))) => {
+                            task = returned;
+                            // Not stuck in a monitor, so the watchdog shouldn't disable it while it waits
+                            task.heartbeat().beat(None);
+                            let message = format!("{} monitor task panicked: {}", task.name(), panic_message(&*payload));
+                            tokio::time::sleep(record_restart(&state, &mut failures, message)).await;
+                            ticker.reset();
+                        }
+                        // Only cancelled when the runtime shuts down
+                        Err(_) => break,
+                    }
+                }
+            }));
+        }
+        
+        let aborts: Vec<_> = watched
+            .into_iter()
+            .map(|(name, heartbeat, index)| (name, heartbeat, handles[index].abort_handle()))
+            .collect();
+        handles.push(runtime.spawn(Self::watchdog(self.state.clone(), aborts)));
+        
+        let polling_interval = self.polling_interval;
+        let state = self.state.clone();
+        let config = self.config.clone();
+        let hardware_info = self.hardware_info.clone();
+        handles.push(runtime.spawn(async move {
+            let mut ticker = tokio::time::interval(polling_interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            let mut failures = 0;
+            loop {
+                ticker.tick().await;
+                let started = Utc::now();
+                follow_polling_interval(&mut ticker, &self.state);
+                // Alert actions may spawn processes or play sounds
+                let result = tokio::task::spawn_blocking(move || {
+                    self.record_async_cycle(started);
+                    self.run_scripts();
+                    self.evaluate_queries();
+                    self.evaluate_alerts();
+                    self.apply_burst_sampling();
+                    self.enforce_history_budget();
+                    self
+                }).await;
+                match result {
+                    Ok(poller) => {
+                        self = poller;
+                        failures = 0;
+                    }
+                    // The poller went down with the panic, so alerts, scripts and queries
+                    // start over with a fresh one
+                    Err(e) if e.is_panic() => {
+                        let message = format!("Alert evaluation task panicked: {}", panic_message(&*e.into_panic()));
+                        tokio::time::sleep(record_restart(&state, &mut failures, message)).await;
+                        ticker.reset();
+                        self = Self::without_monitors(state.clone(), polling_interval, hardware_info.clone(), config.clone());
+                    }
+                    Err(e) => {
+                        logger::log_error("Alert evaluation task failed", &e);
+                        state.write().poller.last_error = Some(format!("Alert evaluation stopped: {}", e));
+                        break;
+                    }
+                }
+            }
+        }));
+        
+        handles
+    }
+    
+    async fn watchdog(
+        state: SharedAppState,
+        mut monitors: Vec<(&'static str, Arc<MonitorHeartbeat>, tokio::task::AbortHandle)>,
+    ) {
+        let mut ticker = tokio::time::interval(WATCHDOG_INTERVAL);
+        loop {
+            ticker.tick().await;
+            
+            monitors.retain(|(name, heartbeat, abort)| {
+                let stalled = heartbeat.current_monitor().is_some() && heartbeat.since_last_beat() > MONITOR_TIMEOUT;
+                if !stalled {
+                    return !abort.is_finished();
+                }
+                
+                // The blocked sensor call can't be interrupted; stop scheduling it and
+                // let its result be discarded whenever it returns
+                abort.abort();
+                let message = format!(
+                    "{} monitor stopped responding for {}s and was disabled",
+                    name,
+                    heartbeat.since_last_beat().as_secs()
+                );
+                logger::log_warning(&message);
+                
+                // Don't wait on a lock the hung call may still hold
+                if let Some(mut app_state) = state.try_write_for(WATCHDOG_INTERVAL) {
+                    app_state.poller.disabled_monitors.push(name.to_string());
+                    app_state.poller.last_error = Some(message);
+                }
+                false
+            });
+        }
+    }
+    
     pub fn poll_hardware(&mut self) {
+        let started = Utc::now();
+        let timer = Instant::now();
         // Update all metrics using the monitor registry
         if let Err(e) = self.monitor_registry.update_all_metrics(&self.state) {
             logger::log_error("Failed to update hardware metrics", &*e);
         }
+        {
+            let mut state = self.state.write();
+            let interval_ms = state.polling_interval_ms;
+            state.poller.record_cycle(started, timer.elapsed().as_secs_f64() * 1000.0, interval_ms);
+        }
+        
+        self.run_scripts();
+        self.evaluate_queries();
+        self.evaluate_alerts();
+        self.apply_burst_sampling();
+        self.enforce_history_budget();
+        self.state.write().poller.last_poll = Some(Utc::now());
+    }
+    
+    // Poll at the burst interval while a burst trigger holds, then return to the
+    // configured interval. The polling tasks follow state.polling_interval_ms
+    fn apply_burst_sampling(&mut self) {
+        let (burst, baseline_ms) = {
+            let config = self.config.read();
+            (config.burst.clone(), config.polling_interval_ms)
+        };
+        let mut state = self.state.write();
+        let was_bursting = state.poller.burst_until.is_some();
+        if let Some(interval_ms) = self.burst.update(&state, &burst, baseline_ms, Utc::now()) {
+            state.polling_interval_ms = interval_ms;
+        }
+        state.poller.burst_until = self.burst.until();
+        match (was_bursting, state.poller.burst_until.is_some()) {
+            (false, true) => logger::log_info(&format!("Burst sampling started at {}ms", burst.interval_ms)),
+            (true, false) => logger::log_info("Burst sampling ended"),
+            _ => {}
+        }
+    }
+    
+    // Monitors poll in parallel tasks on the same schedule as this one, so a cycle
+    // takes as long as its slowest monitor
+    fn record_async_cycle(&self, started: chrono::DateTime<Utc>) {
+        let mut state = self.state.write();
+        let duration_ms = state.poller.monitor_timings.iter().map(|timing| timing.last_duration_ms).fold(0.0, f64::max);
+        let interval_ms = state.polling_interval_ms;
+        state.poller.record_cycle(started, duration_ms, interval_ms);
+    }
+    
+    // Spill the oldest history to disk once the configured memory budget is exceeded,
+    // so long headless runs don't grow without bound
+    fn enforce_history_budget(&mut self) {
+        let budget_mb = self.config.read().history_budget_mb;
+        if budget_mb == 0 {
+            return;
+        }
+        // Copied under a read lock and written without one, so the UI never waits on the disk
+        let batch = self.state.read().take_over_budget(budget_mb as usize * 1024 * 1024);
+        let Some(mut batch) = batch else {
+            return;
+        };
+        let written = batch.write();
+        let spilled = self.state.write().finish_spill(batch);
+        match written.map(|()| spilled) {
+            Ok(0) => {}
+            Ok(spilled) => logger::log_info(&format!(
+                "Spilled {} history samples to disk to stay within {} MB",
+                spilled, budget_mb
+            )),
+            Err(e) if !self.spill_failed => {
+                self.spill_failed = true;
+                logger::log_error("Failed to spill history to disk", &*e);
+            }
+            Err(_) => {}
+        }
+    }
+    
+    // After the monitors, so derived metrics and script alerts see this cycle's readings;
+    // the scripts are (re)loaded on the first cycle and whenever a reload is requested
+    fn run_scripts(&mut self) {
+        let generation = scripting::reload_generation();
+        if self.scripts_generation != Some(generation) {
+            self.scripts_generation = Some(generation);
+            match scripting::default_dir() {
+                Ok(dir) => self.scripts.load_dir(&dir),
+                Err(e) => logger::log_error("Failed to locate the scripts directory", &*e),
+            }
+            self.state.write().scripts.loaded = self.scripts.loaded();
+        }
+        let run = self.scripts.run(&self.state.read());
+        scripting::apply(&mut self.state.write(), run);
+    }
+    
+    // Expressions used by alert rules and dashboards, ahead of the alerts that read them
+    fn evaluate_queries(&mut self) {
+        let (expressions, aliases) = {
+            let config = self.config.read();
+            (config.query_expressions(), config.metric_aliases.clone())
+        };
+        if expressions.is_empty() && self.state.read().queries.is_empty() {
+            return;
+        }
+        self.queries.evaluate_all(&mut self.state.write(), &expressions, &aliases, Utc::now());
+    }
+    
+    fn evaluate_alerts(&mut self) {
+        let alert_config = self.config.read().alerts.clone();
+        let now = Utc::now();
+        
+        let audible_statuses = {
+            let mut state = self.state.write();
+            let transitions = self.alert_engine.evaluate(&state, &alert_config.rules, now);
+            state.alerts.apply(&transitions, self.alert_engine.statuses(), now);
+            Self::log_alert_transitions(&transitions, &state);
+            
+            self.action_dispatcher.read_only = self.config.read().read_only;
+            for action in self.action_dispatcher.dispatch(&transitions, &mut state.alerts, now) {
+                alerts::run_action(&action);
+            }
+            
+            state.alerts.predictions = alerts::predict_threshold_crossings(
+                &state,
+                self.alert_engine.statuses(),
+                &alert_config.prediction,
+                now,
+            );
+            
+            // Muted or acknowledged alerts stay quiet
+            self.alert_engine
+                .statuses()
+                .iter()
+                .filter(|status| !state.alerts.is_silenced(&status.rule))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        
+        let alarm_config = &alert_config.alarm;
+        if self.alarm.should_sound(alarm_config, &audible_statuses, now, Local::now().time()) {
+            logger::log_warning("Critical threshold persisted, sounding alarm");
+            alerts::play_alarm_sound(alarm_config.sound_file.as_deref());
+        }
+    }
+    
+    fn log_alert_transitions(transitions: &[AlertTransition], state: &AppState) {
+        for transition in transitions {
+            let value = transition.value.map_or_else(|| "N/A".to_string(), |v| format!("{:.1}", v));
+            // Name who holds the VRAM, since that's what gets killed or resized
+            let culprits = transition.rule.metric
+                .starts_with("gpu.memory")
+                .then(|| state.gpu.largest_memory_users(3))
+                .flatten()
+                .map_or_else(String::new, |users| format!("; largest VRAM users: {}", users));
+            match transition.kind {
+                AlertTransitionKind::Fired => logger::log_warning(&format!(
+                    "Alert fired: {} >= {} (value {}){}",
+                    transition.rule.metric, transition.rule.threshold, value, culprits
+                )),
+                AlertTransitionKind::Cleared => logger::log_info(&format!(
+                    "Alert cleared: {} (value {})",
+                    transition.rule.metric, value
+                )),
+            }
+        }
+    }
+}
+
+// Count a task failure and report it; returns how long to wait before running the
+// task again, longer after each failure in a row
+fn record_restart(state: &SharedAppState, failures: &mut u32, message: String) -> Duration {
+    let backoff = RESTART_BACKOFF.saturating_mul(1 << (*failures).min(6)).min(MAX_RESTART_BACKOFF);
+    *failures += 1;
+    logger::log_warning(&format!("{}; restarting in {}s", message, backoff.as_secs()));
+    let mut app_state = state.write();
+    app_state.poller.restarts += 1;
+    app_state.poller.last_error = Some(format!("{}; restarted", message));
+    backoff
+}
+
+// Pick up polling interval changes made in Settings without restarting the tasks
+fn follow_polling_interval(ticker: &mut Interval, state: &SharedAppState) {
+    let interval = Duration::from_millis(state.read().polling_interval_ms.max(1));
+    if ticker.period() != interval {
+        *ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    }
+}
+
+// Sleeps until fixed deadlines rather than for the interval after each poll, so
+// samples stay evenly spaced however long a poll took. A poll overrunning its slot
+// starts the next one straight away and later deadlines count from then
+pub struct TickScheduler {
+    next: Instant,
+}
+
+impl Default for TickScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TickScheduler {
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+    
+    // First deadline one interval after `start`
+    pub fn starting_at(start: Instant) -> Self {
+        Self { next: start }
+    }
+    
+    // Wait for the next deadline. Taking the interval on each tick lets Settings
+    // changes and burst sampling apply from the next sample
+    pub fn wait(&mut self, interval: Duration) {
+        if let Some(remaining) = self.advance(interval, Instant::now()) {
+            thread::sleep(remaining);
+        }
+    }
+    
+    // Move to the next deadline and return how long to sleep until it, if at all
+    pub fn advance(&mut self, interval: Duration, now: Instant) -> Option<Duration> {
+        self.next += interval;
+        if self.next > now {
+            Some(self.next - now)
+        } else {
+            self.next = now;
+            None
+        }
     }
 }
 
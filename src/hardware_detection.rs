@@ -1,5 +1,8 @@
 
-#[derive(Debug, Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CpuVendor {
     Intel,
     AMD,
@@ -7,7 +10,8 @@ pub enum CpuVendor {
     Unknown,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GpuVendor {
     NVIDIA,
     AMD,
@@ -16,14 +20,18 @@ pub enum GpuVendor {
     Unknown,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HardwareInfo {
     pub cpu_vendor: CpuVendor,
     pub gpu_vendors: Vec<GpuVendor>,
     pub platform: Platform,
+    #[serde(default)]
+    pub cpu_topology: CpuTopology,
+    #[serde(default)]
+    pub versions: SoftwareVersions,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Platform {
     Windows,
     MacOS,
@@ -31,6 +39,17 @@ pub enum Platform {
     Unknown,
 }
 
+impl HardwareInfo {
+    // Identifies the machine across sessions; versions are left out so sessions
+    // before and after a driver or BIOS update still compare
+    pub fn fingerprint(&self) -> String {
+        format!(
+            "{:?}/{}x{}/{:?}/{:?}",
+            self.cpu_vendor, self.cpu_topology.sockets, self.cpu_topology.physical_cores, self.gpu_vendors, self.platform
+        )
+    }
+}
+
 pub struct HardwareDetector;
 
 impl HardwareDetector {
@@ -38,11 +57,15 @@ impl HardwareDetector {
         let platform = Self::detect_platform();
         let cpu_vendor = Self::detect_cpu_vendor();
         let gpu_vendors = Self::detect_gpu_vendors();
+        let cpu_topology = CpuTopology::detect();
+        let versions = SoftwareVersions::detect();
         
         HardwareInfo {
             cpu_vendor,
             gpu_vendors,
             platform,
+            cpu_topology,
+            versions,
         }
     }
     
@@ -165,7 +188,7 @@ impl HardwareDetector {
     
     #[cfg(target_os = "linux")]
     fn detect_linux_gpus() -> Vec<GpuVendor> {
-        let mut vendors = Vec::new();
+        let vendors = Vec::new();
         
         // Check /sys/class/drm for GPU devices
         // Check lspci output
@@ -210,9 +233,608 @@ impl HardwareDetector {
     }
 }
 
+// Where a logical CPU sits: socket, physical core, L3 domain and core type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CpuPlacement {
+    pub cpu: usize,
+    pub socket: usize,
+    pub core: usize,            // Logical CPUs sharing this are SMT siblings
+    pub l3: Option<usize>,      // Shared L3 domain, one per CCD/CCX on Ryzen
+    pub efficiency_class: u8,   // Higher is faster; differs on big.LITTLE/hybrid chips
+}
+
+// Logical CPUs aggregated together in the UI, e.g. "CCD 1" or "E-cores"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CpuCluster {
+    pub name: String,
+    pub socket: usize,
+    pub cpus: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CpuTopology {
+    pub sockets: usize,
+    pub physical_cores: usize,
+    pub clusters: Vec<CpuCluster>,
+    pub smt_siblings: Vec<Vec<usize>>, // Logical CPUs per physical core
+}
+
+impl CpuTopology {
+    pub fn detect() -> Self {
+        #[cfg(target_os = "linux")]
+        let placements = Self::placements_from_sysfs(std::path::Path::new("/sys/devices"));
+        
+        #[cfg(target_os = "windows")]
+        let placements = Self::detect_windows_placements();
+        
+        #[cfg(target_os = "macos")]
+        let placements = Self::detect_macos_placements();
+        
+        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+        let placements: Option<Vec<CpuPlacement>> = None;
+        
+        Self::from_placements(&placements.unwrap_or_else(Self::flat_placements))
+    }
+    
+    // Group placements into sockets, SMT siblings and clusters. Hybrid chips are split
+    // by core type, multi-CCD chips by L3 domain, anything else is one cluster per socket
+    pub fn from_placements(placements: &[CpuPlacement]) -> Self {
+        let mut sockets: Vec<usize> = placements.iter().map(|p| p.socket).collect();
+        sockets.sort_unstable();
+        sockets.dedup();
+        
+        let mut smt_siblings: Vec<((usize, usize), Vec<usize>)> = Vec::new();
+        for placement in placements {
+            let key = (placement.socket, placement.core);
+            match smt_siblings.iter_mut().find(|(existing, _)| *existing == key) {
+                Some((_, cpus)) => cpus.push(placement.cpu),
+                None => smt_siblings.push((key, vec![placement.cpu])),
+            }
+        }
+        
+        let mut classes: Vec<u8> = placements.iter().map(|p| p.efficiency_class).collect();
+        classes.sort_unstable();
+        classes.dedup();
+        let hybrid = classes.len() > 1;
+        
+        let mut clusters: Vec<CpuCluster> = Vec::new();
+        for &socket in &sockets {
+            let in_socket: Vec<&CpuPlacement> = placements.iter().filter(|p| p.socket == socket).collect();
+            let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+            if hybrid {
+                // Fastest core type first
+                for (rank, class) in classes.iter().rev().enumerate() {
+                    let name = match rank {
+                        0 => "P-cores",
+                        _ if rank == classes.len() - 1 => "E-cores",
+                        _ => "Mid cores",
+                    };
+                    let cpus = in_socket.iter().filter(|p| p.efficiency_class == *class).map(|p| p.cpu).collect();
+                    groups.push((name.to_string(), cpus));
+                }
+            } else {
+                let mut l3_domains: Vec<Option<usize>> = in_socket.iter().map(|p| p.l3).collect();
+                l3_domains.sort_unstable();
+                l3_domains.dedup();
+                if l3_domains.len() > 1 {
+                    for (index, l3) in l3_domains.iter().enumerate() {
+                        let cpus = in_socket.iter().filter(|p| p.l3 == *l3).map(|p| p.cpu).collect();
+                        groups.push((format!("CCD {}", index), cpus));
+                    }
+                } else {
+                    groups.push(("All cores".to_string(), in_socket.iter().map(|p| p.cpu).collect()));
+                }
+            }
+            
+            for (name, mut cpus) in groups.into_iter().filter(|(_, cpus)| !cpus.is_empty()) {
+                cpus.sort_unstable();
+                let name = if sockets.len() > 1 { format!("Socket {} {}", socket, name) } else { name };
+                clusters.push(CpuCluster { name, socket, cpus });
+            }
+        }
+        
+        Self {
+            sockets: sockets.len(),
+            physical_cores: smt_siblings.len(),
+            clusters,
+            smt_siblings: smt_siblings.into_iter().map(|(_, cpus)| cpus).collect(),
+        }
+    }
+    
+    // Mean of a per-logical-CPU metric over each cluster, in cluster order
+    pub fn aggregate(&self, per_cpu: &[f32]) -> Vec<Option<f32>> {
+        self.clusters
+            .iter()
+            .map(|cluster| {
+                let values: Vec<f32> = cluster.cpus.iter().filter_map(|cpu| per_cpu.get(*cpu).copied()).collect();
+                (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+            })
+            .collect()
+    }
+    
+    pub fn cluster_of(&self, cpu: usize) -> Option<&CpuCluster> {
+        self.clusters.iter().find(|cluster| cluster.cpus.contains(&cpu))
+    }
+    
+    // Read `<devices>/system/cpu/cpuN/{topology,cache,cpu_capacity}`; Intel hybrid parts
+    // list their E-cores in `<devices>/cpu_atom/cpus` instead of reporting a capacity
+    pub fn placements_from_sysfs(devices: &std::path::Path) -> Option<Vec<CpuPlacement>> {
+        let read_number = |path: std::path::PathBuf| -> Option<usize> {
+            std::fs::read_to_string(path).ok()?.trim().parse().ok()
+        };
+        
+        let cpu_root = devices.join("system/cpu");
+        let mut cpus: Vec<usize> = std::fs::read_dir(&cpu_root)
+            .ok()?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.strip_prefix("cpu")?.parse().ok())
+            .collect();
+        cpus.sort_unstable();
+        if cpus.is_empty() {
+            return None;
+        }
+        
+        let atom_cpus = std::fs::read_to_string(devices.join("cpu_atom/cpus")).ok().map(|list| parse_cpu_list(&list));
+        let capacities: Vec<Option<usize>> = cpus
+            .iter()
+            .map(|cpu| read_number(cpu_root.join(format!("cpu{}/cpu_capacity", cpu))))
+            .collect();
+        let mut distinct_capacities: Vec<usize> = capacities.iter().flatten().copied().collect();
+        distinct_capacities.sort_unstable();
+        distinct_capacities.dedup();
+        
+        let placements = cpus
+            .iter()
+            .zip(&capacities)
+            .map(|(&cpu, capacity)| {
+                let dir = cpu_root.join(format!("cpu{}", cpu));
+                let efficiency_class = match (&atom_cpus, capacity) {
+                    (Some(atom_cpus), _) => u8::from(!atom_cpus.contains(&cpu)),
+                    (None, Some(capacity)) => distinct_capacities.iter().position(|c| c == capacity).unwrap_or(0) as u8,
+                    (None, None) => 0,
+                };
+                CpuPlacement {
+                    cpu,
+                    socket: read_number(dir.join("topology/physical_package_id")).unwrap_or(0),
+                    core: read_number(dir.join("topology/core_id")).unwrap_or(cpu),
+                    l3: read_number(dir.join("cache/index3/id")),
+                    efficiency_class,
+                }
+            })
+            .collect();
+        Some(placements)
+    }
+    
+    #[cfg(target_os = "windows")]
+    fn detect_windows_placements() -> Option<Vec<CpuPlacement>> {
+        use windows::Win32::System::SystemInformation::{
+            GetLogicalProcessorInformationEx, GROUP_AFFINITY, RelationAll, RelationCache, RelationProcessorCore,
+            RelationProcessorPackage, SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
+        };
+        
+        let mut length = 0u32;
+        unsafe {
+            let _ = GetLogicalProcessorInformationEx(RelationAll, None, &mut length);
+        }
+        if length == 0 {
+            return None;
+        }
+        // u64 backing keeps the records 8-byte aligned
+        let mut buffer = vec![0u64; (length as usize).div_ceil(8)];
+        unsafe {
+            GetLogicalProcessorInformationEx(
+                RelationAll,
+                Some(buffer.as_mut_ptr() as *mut SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX),
+                &mut length,
+            ).ok()?;
+        }
+        
+        let mask_cpus = |mask: GROUP_AFFINITY| -> Vec<usize> {
+            (0..usize::BITS as usize)
+                .filter(|bit| mask.Mask & (1 << bit) != 0)
+                .map(|bit| mask.Group as usize * usize::BITS as usize + bit)
+                .collect()
+        };
+        
+        fn placement(placements: &mut Vec<CpuPlacement>, cpu: usize) -> &mut CpuPlacement {
+            match placements.iter().position(|p| p.cpu == cpu) {
+                Some(index) => &mut placements[index],
+                None => {
+                    placements.push(CpuPlacement { cpu, socket: 0, core: cpu, l3: None, efficiency_class: 0 });
+                    placements.last_mut().expect("just pushed")
+                }
+            }
+        }
+        
+        let mut placements: Vec<CpuPlacement> = Vec::new();
+        let (mut cores, mut packages, mut l3_domains) = (0, 0, 0);
+        let mut offset = 0;
+        while offset < length as usize {
+            let record = unsafe {
+                &*((buffer.as_ptr() as *const u8).add(offset) as *const SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX)
+            };
+            if record.Relationship == RelationProcessorCore {
+                let processor = unsafe { record.Anonymous.Processor };
+                for cpu in mask_cpus(processor.GroupMask[0]) {
+                    let entry = placement(&mut placements, cpu);
+                    entry.core = cores;
+                    entry.efficiency_class = processor.EfficiencyClass;
+                }
+                cores += 1;
+            } else if record.Relationship == RelationProcessorPackage {
+                let processor = unsafe { record.Anonymous.Processor };
+                for cpu in mask_cpus(processor.GroupMask[0]) {
+                    placement(&mut placements, cpu).socket = packages;
+                }
+                packages += 1;
+            } else if record.Relationship == RelationCache {
+                let cache = unsafe { record.Anonymous.Cache };
+                if cache.Level == 3 {
+                    for cpu in mask_cpus(unsafe { cache.Anonymous.GroupMask }) {
+                        placement(&mut placements, cpu).l3 = Some(l3_domains);
+                    }
+                    l3_domains += 1;
+                }
+            }
+            offset += record.Size as usize;
+        }
+        
+        placements.sort_by_key(|p| p.cpu);
+        (!placements.is_empty()).then_some(placements)
+    }
+    
+    // Apple Silicon reports performance levels; efficiency cores are numbered first
+    #[cfg(target_os = "macos")]
+    fn detect_macos_placements() -> Option<Vec<CpuPlacement>> {
+        let sysctl = |name: &str| -> Option<usize> {
+            let output = std::process::Command::new("sysctl").args(["-n", name]).output().ok()?;
+            String::from_utf8(output.stdout).ok()?.trim().parse().ok()
+        };
+        let performance = sysctl("hw.perflevel0.logicalcpu")?;
+        let efficiency = sysctl("hw.perflevel1.logicalcpu").unwrap_or(0);
+        
+        let placements = (0..efficiency + performance)
+            .map(|cpu| CpuPlacement {
+                cpu,
+                socket: 0,
+                core: cpu,
+                l3: None,
+                efficiency_class: u8::from(cpu >= efficiency),
+            })
+            .collect();
+        Some(placements)
+    }
+    
+    // No topology information: every logical CPU is its own core in one cluster
+    fn flat_placements() -> Vec<CpuPlacement> {
+        let count = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        (0..count)
+            .map(|cpu| CpuPlacement { cpu, socket: 0, core: cpu, l3: None, efficiency_class: 0 })
+            .collect()
+    }
+}
+
+// Parse a kernel CPU list such as "0-3,8,10-11"
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    list.trim()
+        .split(',')
+        .filter_map(|range| match range.split_once('-') {
+            Some((start, end)) => Some((start.trim().parse::<usize>().ok()?..=end.trim().parse().ok()?).collect()),
+            None => range.trim().parse().ok().map(|cpu| vec![cpu]),
+        })
+        .flatten()
+        .collect()
+}
+
+// Driver and firmware versions, the first thing to compare when temperatures or
+// clocks change between sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoftwareVersions {
+    pub gpu_driver: Option<String>,
+    pub bios: Option<String>,
+    pub kernel: Option<String>,
+}
+
+impl SoftwareVersions {
+    pub fn detect() -> Self {
+        #[cfg(target_os = "linux")]
+        let (gpu_driver, bios) = (
+            Self::gpu_driver_from_sysfs(std::path::Path::new("/sys/module")),
+            Self::bios_from_dmi(std::path::Path::new("/sys/class/dmi/id")),
+        );
+        
+        #[cfg(target_os = "windows")]
+        let (gpu_driver, bios) = Self::detect_windows();
+        
+        #[cfg(target_os = "macos")]
+        let (gpu_driver, bios) = (None, Self::detect_macos_firmware());
+        
+        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+        let (gpu_driver, bios): (Option<String>, Option<String>) = (None, None);
+        
+        Self {
+            // NVML knows the exact driver build on every platform
+            gpu_driver: Self::nvml_driver_version().or(gpu_driver),
+            bios,
+            kernel: sysinfo::System::kernel_version(),
+        }
+    }
+    
+    // e.g. "GPU driver 550.54.14, BIOS 1.2.3, kernel 6.8.0"
+    pub fn summary(&self) -> String {
+        let unknown = |version: &Option<String>| version.clone().unwrap_or_else(|| "unknown".to_string());
+        format!(
+            "GPU driver {}, BIOS {}, kernel {}",
+            unknown(&self.gpu_driver),
+            unknown(&self.bios),
+            unknown(&self.kernel)
+        )
+    }
+    
+    fn nvml_driver_version() -> Option<String> {
+        #[cfg(feature = "nvidia")]
+        {
+            let nvml = nvml_wrapper::Nvml::init().ok()?;
+            nvml.sys_driver_version().ok().map(|version| format!("NVIDIA {}", version))
+        }
+        #[cfg(not(feature = "nvidia"))]
+        None
+    }
+    
+    // Out-of-tree modules such as nvidia report their own version; in-tree ones
+    // (amdgpu, i915, xe) ship with the kernel, so the kernel version identifies them
+    pub fn gpu_driver_from_sysfs(modules: &std::path::Path) -> Option<String> {
+        ["nvidia", "amdgpu", "i915", "xe"].iter().find_map(|module| {
+            let dir = modules.join(module);
+            if !dir.is_dir() {
+                return None;
+            }
+            match std::fs::read_to_string(dir.join("version")) {
+                Ok(version) => Some(format!("{} {}", module, version.trim())),
+                Err(_) => Some(format!("{} (in-kernel)", module)),
+            }
+        })
+    }
+    
+    // e.g. "American Megatrends Inc. 1.40 (07/12/2023)"
+    pub fn bios_from_dmi(dmi: &std::path::Path) -> Option<String> {
+        let read = |name: &str| {
+            std::fs::read_to_string(dmi.join(name))
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let version = read("bios_version")?;
+        let mut bios = match read("bios_vendor") {
+            Some(vendor) => format!("{} {}", vendor, version),
+            None => version,
+        };
+        if let Some(date) = read("bios_date") {
+            bios.push_str(&format!(" ({})", date));
+        }
+        Some(bios)
+    }
+    
+    // BIOS from the registry copy of the SMBIOS tables, driver from the display adapter class
+    #[cfg(target_os = "windows")]
+    fn detect_windows() -> (Option<String>, Option<String>) {
+        use windows::core::{w, PCWSTR};
+        use windows::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ};
+        
+        let read = |key: PCWSTR, value: PCWSTR| -> Option<String> {
+            let mut buffer = [0u16; 256];
+            let mut size = (buffer.len() * 2) as u32;
+            unsafe {
+                RegGetValueW(
+                    HKEY_LOCAL_MACHINE,
+                    key,
+                    value,
+                    RRF_RT_REG_SZ,
+                    None,
+                    Some(buffer.as_mut_ptr() as *mut _),
+                    Some(&mut size as *mut u32),
+                ).ok().ok()?;
+            }
+            let length = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+            Some(String::from_utf16_lossy(&buffer[..length])).filter(|value| !value.is_empty())
+        };
+        
+        let bios_key = w!("HARDWARE\\DESCRIPTION\\System\\BIOS");
+        let bios = read(bios_key, w!("BIOSVersion")).map(|version| {
+            match read(bios_key, w!("BIOSReleaseDate")) {
+                Some(date) => format!("{} ({})", version, date),
+                None => version,
+            }
+        });
+        let adapter_key = w!("SYSTEM\\CurrentControlSet\\Control\\Class\\{4d36e968-e325-11ce-bfc1-08002be10318}\\0000");
+        let driver = read(adapter_key, w!("DriverVersion")).map(|version| {
+            match read(adapter_key, w!("DriverDesc")) {
+                Some(adapter) => format!("{} {}", adapter, version),
+                None => version,
+            }
+        });
+        (driver, bios)
+    }
+    
+    // The GPU driver ships with macOS itself; the firmware version is what changes
+    #[cfg(target_os = "macos")]
+    fn detect_macos_firmware() -> Option<String> {
+        let output = std::process::Command::new("system_profiler").arg("SPHardwareDataType").output().ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("System Firmware Version:").map(|version| version.trim().to_string()))
+    }
+}
+
+// Why a monitor failed, so the registry, logger and UI can react to the cause
+#[derive(Debug, Clone, PartialEq)]
+pub enum MonitorError {
+    PermissionDenied(String), // e.g. MSR or SMBus access without elevation
+    DriverMissing(String),    // Vendor library or driver not installed
+    DeviceGone(String),       // Device removed, lost or needing a reset
+    Timeout(String),
+    Parse(String),            // Unexpected sensor output
+}
+
+impl MonitorError {
+    // Errors after which polling the monitor again is pointless
+    pub fn stops_monitor(&self) -> bool {
+        matches!(
+            self,
+            MonitorError::PermissionDenied(_) | MonitorError::DriverMissing(_) | MonitorError::DeviceGone(_)
+        )
+    }
+    
+    // What the user can do about it
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            MonitorError::PermissionDenied(_) => Some("run the dashboard as Administrator (or root) to read these sensors"),
+            MonitorError::DriverMissing(_) => Some("install or update the vendor driver"),
+            MonitorError::DeviceGone(_) => Some("restart the dashboard once the device is back"),
+            MonitorError::Timeout(_) | MonitorError::Parse(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MonitorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MonitorError::PermissionDenied(detail) => write!(f, "permission denied: {}", detail),
+            MonitorError::DriverMissing(detail) => write!(f, "driver missing: {}", detail),
+            MonitorError::DeviceGone(detail) => write!(f, "device gone: {}", detail),
+            MonitorError::Timeout(detail) => write!(f, "timed out: {}", detail),
+            MonitorError::Parse(detail) => write!(f, "unexpected sensor data: {}", detail),
+        }
+    }
+}
+
+impl std::error::Error for MonitorError {}
+
+// Sensor files and device handles report most failures as I/O errors
+impl From<std::io::Error> for MonitorError {
+    fn from(error: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+        let detail = error.to_string();
+        match error.kind() {
+            ErrorKind::PermissionDenied => MonitorError::PermissionDenied(detail),
+            ErrorKind::TimedOut | ErrorKind::WouldBlock => MonitorError::Timeout(detail),
+            ErrorKind::InvalidData | ErrorKind::UnexpectedEof => MonitorError::Parse(detail),
+            _ => MonitorError::DeviceGone(detail),
+        }
+    }
+}
+
+impl From<std::num::ParseIntError> for MonitorError {
+    fn from(error: std::num::ParseIntError) -> Self {
+        MonitorError::Parse(error.to_string())
+    }
+}
+
+impl From<std::num::ParseFloatError> for MonitorError {
+    fn from(error: std::num::ParseFloatError) -> Self {
+        MonitorError::Parse(error.to_string())
+    }
+}
+
 // Trait for hardware-specific monitoring implementations
 pub trait HardwareMonitor: Send + Sync {
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>>;
-    fn update_metrics(&mut self, state: &crate::model::SharedAppState) -> Result<(), Box<dyn std::error::Error>>;
+    fn name(&self) -> &'static str;
+    fn initialize(&mut self) -> Result<(), MonitorError>;
+    fn update_metrics(&mut self, state: &crate::model::SharedAppState) -> Result<(), MonitorError>;
     fn supports_hardware(&self, info: &HardwareInfo) -> bool;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placement(cpu: usize, core: usize, l3: Option<usize>, efficiency_class: u8) -> CpuPlacement {
+        CpuPlacement { cpu, socket: 0, core, l3, efficiency_class }
+    }
+
+    #[test]
+    fn test_dual_ccd_topology_with_smt() {
+        // 4 cores / 8 threads split over two CCDs, SMT siblings numbered n and n+4
+        let placements: Vec<CpuPlacement> = (0..8).map(|cpu| placement(cpu, cpu % 4, Some((cpu % 4) / 2), 0)).collect();
+        let topology = CpuTopology::from_placements(&placements);
+
+        assert_eq!(topology.sockets, 1);
+        assert_eq!(topology.physical_cores, 4);
+        assert_eq!(topology.smt_siblings[1], vec![1, 5]);
+        assert_eq!(topology.clusters.len(), 2);
+        assert_eq!(topology.clusters[1].name, "CCD 1");
+        assert_eq!(topology.clusters[1].cpus, vec![2, 3, 6, 7]);
+        assert_eq!(topology.cluster_of(6).unwrap().name, "CCD 1");
+
+        let utilization = [100.0, 100.0, 0.0, 0.0, 50.0, 50.0, 10.0, 30.0];
+        assert_eq!(topology.aggregate(&utilization), vec![Some(75.0), Some(10.0)]);
+    }
+
+    #[test]
+    fn test_hybrid_topology_groups_by_core_type() {
+        let mut placements: Vec<CpuPlacement> = (0..4).map(|cpu| placement(cpu, cpu / 2, Some(0), 1)).collect();
+        placements.extend((4..8).map(|cpu| placement(cpu, cpu, Some(0), 0)));
+        let topology = CpuTopology::from_placements(&placements);
+
+        let names: Vec<&str> = topology.clusters.iter().map(|cluster| cluster.name.as_str()).collect();
+        assert_eq!(names, vec!["P-cores", "E-cores"]);
+        assert_eq!(topology.physical_cores, 6);
+        assert_eq!(topology.clusters[1].cpus, vec![4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_placements_from_sysfs() {
+        let devices = std::env::temp_dir().join("spd_topology_sysfs_test");
+        let _ = std::fs::remove_dir_all(&devices);
+        for cpu in 0..4 {
+            let dir = devices.join(format!("system/cpu/cpu{}", cpu));
+            std::fs::create_dir_all(dir.join("topology")).unwrap();
+            std::fs::create_dir_all(dir.join("cache/index3")).unwrap();
+            std::fs::write(dir.join("topology/physical_package_id"), "0\n").unwrap();
+            std::fs::write(dir.join("topology/core_id"), format!("{}\n", cpu % 2)).unwrap();
+            std::fs::write(dir.join("cache/index3/id"), "0\n").unwrap();
+        }
+        std::fs::create_dir_all(devices.join("cpu_atom")).unwrap();
+        std::fs::write(devices.join("cpu_atom/cpus"), "2-3\n").unwrap();
+
+        let placements = CpuTopology::placements_from_sysfs(&devices).unwrap();
+        let _ = std::fs::remove_dir_all(&devices);
+
+        assert_eq!(placements.len(), 4);
+        assert_eq!(placements[3].core, 1);
+        assert_eq!(placements[3].l3, Some(0));
+        assert_eq!(placements[0].efficiency_class, 1);
+        assert_eq!(placements[2].efficiency_class, 0);
+        assert_eq!(parse_cpu_list("0-2,8,10-11"), vec![0, 1, 2, 8, 10, 11]);
+    }
+
+    #[test]
+    fn test_versions_from_sysfs() {
+        let root = std::env::temp_dir().join("spd_versions_sysfs_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let dmi = root.join("dmi");
+        std::fs::create_dir_all(&dmi).unwrap();
+        std::fs::write(dmi.join("bios_vendor"), "American Megatrends Inc.\n").unwrap();
+        std::fs::write(dmi.join("bios_version"), "1.40\n").unwrap();
+        std::fs::write(dmi.join("bios_date"), "07/12/2023\n").unwrap();
+        std::fs::create_dir_all(root.join("modules/amdgpu")).unwrap();
+        std::fs::create_dir_all(root.join("modules/nvidia")).unwrap();
+        std::fs::write(root.join("modules/nvidia/version"), "550.54.14\n").unwrap();
+
+        let bios = SoftwareVersions::bios_from_dmi(&dmi);
+        let nvidia = SoftwareVersions::gpu_driver_from_sysfs(&root.join("modules"));
+        std::fs::remove_dir_all(root.join("modules/nvidia")).unwrap();
+        let amdgpu = SoftwareVersions::gpu_driver_from_sysfs(&root.join("modules"));
+        let missing = SoftwareVersions::bios_from_dmi(&root.join("missing"));
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(bios.as_deref(), Some("American Megatrends Inc. 1.40 (07/12/2023)"));
+        assert_eq!(nvidia.as_deref(), Some("nvidia 550.54.14"));
+        assert_eq!(amdgpu.as_deref(), Some("amdgpu (in-kernel)"));
+        assert_eq!(missing, None);
+
+        let versions = SoftwareVersions { kernel: Some("6.8.0".to_string()), ..SoftwareVersions::default() };
+        assert_eq!(versions.summary(), "GPU driver unknown, BIOS unknown, kernel 6.8.0");
+    }
+}
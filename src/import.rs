@@ -0,0 +1,281 @@
+use std::fs;
+use std::path::Path;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use crate::model::AppState;
+
+// Third-party sensor logs that can be loaded alongside the live session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    HwInfo,
+    GpuZ,
+}
+
+impl LogFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogFormat::HwInfo => "HWiNFO64",
+            LogFormat::GpuZ => "GPU-Z",
+        }
+    }
+
+    // HWiNFO logs start with separate Date and Time columns, GPU-Z with a combined Date column
+    pub fn detect(header: &[&str]) -> Option<Self> {
+        let first = header.first()?.trim();
+        let second = header.get(1).map(|column| column.trim()).unwrap_or("");
+        if !first.eq_ignore_ascii_case("date") {
+            return None;
+        }
+        if second.eq_ignore_ascii_case("time") {
+            Some(LogFormat::HwInfo)
+        } else {
+            Some(LogFormat::GpuZ)
+        }
+    }
+
+    // Number of leading columns holding the timestamp
+    fn timestamp_columns(&self) -> usize {
+        match self {
+            LogFormat::HwInfo => 2,
+            LogFormat::GpuZ => 1,
+        }
+    }
+
+    // Sensor name (without the bracketed unit) -> metric descriptor id
+    fn column_map(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            LogFormat::HwInfo => HWINFO_COLUMNS,
+            LogFormat::GpuZ => GPUZ_COLUMNS,
+        }
+    }
+}
+
+const HWINFO_COLUMNS: &[(&str, &str)] = &[
+    ("Total CPU Usage", "cpu.utilization"),
+    ("Core Clocks (avg)", "cpu.clock_speed"),
+    ("Core VIDs (avg)", "cpu.core_voltage"),
+    ("Vcore", "cpu.core_voltage"),
+    ("CPU Package Power", "cpu.power_consumption"),
+    ("CPU Package", "cpu.package_temperature"),
+    ("CPU (Tctl/Tdie)", "cpu.package_temperature"),
+    ("Core Max", "cpu.hotspot_temperature"),
+    ("Core Thermal Throttling", "cpu.thermal_throttling"),
+    ("GPU Core Load", "gpu.utilization"),
+    ("GPU Clock", "gpu.clock_speed"),
+    ("GPU Memory Clock", "gpu.memory_clock_speed"),
+    ("GPU Video Clock", "gpu.video_clock_speed"),
+    ("GPU Memory Allocated", "gpu.memory_utilization"),
+    ("GPU Memory Usage", "gpu.memory_utilization_percent"),
+    ("GPU Core Voltage", "gpu.core_voltage"),
+    ("GPU Power", "gpu.power_consumption"),
+    ("GPU Temperature", "gpu.package_temperature"),
+    ("GPU Hot Spot Temperature", "gpu.hotspot_temperature"),
+    ("Physical Memory Used", "memory.utilization_mb"),
+    ("Memory Clock", "memory.clock_speed"),
+    ("Read Rate", "storage.read_speed"),
+    ("Write Rate", "storage.write_speed"),
+    ("Drive Temperature", "storage.temperature"),
+];
+
+const GPUZ_COLUMNS: &[(&str, &str)] = &[
+    ("GPU Clock", "gpu.clock_speed"),
+    ("Memory Clock", "gpu.memory_clock_speed"),
+    ("Video Clock", "gpu.video_clock_speed"),
+    ("GPU Temperature", "gpu.package_temperature"),
+    ("Hot Spot", "gpu.hotspot_temperature"),
+    ("GPU Load", "gpu.utilization"),
+    ("Memory Used", "gpu.memory_utilization"),
+    ("Board Power Draw", "gpu.power_consumption"),
+    ("GPU Voltage", "gpu.core_voltage"),
+    ("CPU Temperature", "cpu.package_temperature"),
+    ("System Memory Used", "memory.utilization_mb"),
+];
+
+// Date formats seen in logs from different locales
+const TIMESTAMP_FORMATS: &[&str] = &[
+    "%d.%m.%Y %H:%M:%S%.f",
+    "%m/%d/%Y %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+];
+
+// A recording loaded from another tool, held in the same model as a live session
+#[derive(Debug, Clone)]
+pub struct ImportedSession {
+    pub name: String,
+    pub format: LogFormat,
+    pub state: AppState,
+    pub metric_ids: Vec<&'static str>,
+    pub samples: usize,
+}
+
+pub fn import_log(path: &Path) -> Result<ImportedSession, Box<dyn std::error::Error>> {
+    // HWiNFO writes logs in the system ANSI code page, so decode lossily
+    let bytes = fs::read(path)?;
+    let contents = String::from_utf8_lossy(&bytes);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    parse_log(&name, &contents)
+}
+
+pub fn parse_log(name: &str, contents: &str) -> Result<ImportedSession, Box<dyn std::error::Error>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(contents.as_bytes());
+    let mut records = reader.records();
+
+    let header = records.next().ok_or("Log file is empty")??;
+    let header: Vec<&str> = header.iter().collect();
+    let format = LogFormat::detect(&header).ok_or("Unrecognised log format: expected HWiNFO64 or GPU-Z CSV")?;
+
+    // Resolve each column to a metric, keeping the first column that maps to a given id
+    let mut columns: Vec<(usize, &'static str)> = Vec::new();
+    for (index, column) in header.iter().enumerate().skip(format.timestamp_columns()) {
+        if let Some(metric_id) = map_column(format, column) {
+            if !columns.iter().any(|(_, id)| *id == metric_id) {
+                columns.push((index, metric_id));
+            }
+        }
+    }
+    if columns.is_empty() {
+        return Err("Log contains no columns the dashboard understands".into());
+    }
+
+    let mut state = AppState::default();
+    let mut first_timestamp = None;
+    let mut samples = 0;
+
+    for record in records {
+        let record = record?;
+        // Footer rows repeat the header or list sensor groups; they carry no timestamp
+        let Some(timestamp) = parse_timestamp(&record, format) else {
+            continue;
+        };
+        first_timestamp.get_or_insert(timestamp);
+        samples += 1;
+
+        for (index, metric_id) in &columns {
+            let Some(value) = record.get(*index).and_then(parse_value) else {
+                continue;
+            };
+            if let Some(metric) = state.metric_mut(metric_id) {
+                metric.record_at(timestamp, value);
+            } else if let Some(event) = state.event_mut(metric_id) {
+                event.record_at(timestamp, value >= 0.5);
+            }
+        }
+    }
+
+    state.session_start = first_timestamp.ok_or("Log contains no samples")?;
+
+    Ok(ImportedSession {
+        name: name.to_string(),
+        format,
+        state,
+        metric_ids: columns.into_iter().map(|(_, id)| id).collect(),
+        samples,
+    })
+}
+
+fn map_column(format: LogFormat, column: &str) -> Option<&'static str> {
+    // Compare on the sensor name only; the unit's degree sign rarely survives decoding
+    let sensor = column.split(" [").next().unwrap_or(column).trim();
+    format
+        .column_map()
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(sensor))
+        .map(|(_, id)| *id)
+}
+
+fn parse_timestamp(record: &csv::StringRecord, format: LogFormat) -> Option<DateTime<Utc>> {
+    let text = match format {
+        LogFormat::HwInfo => format!("{} {}", record.get(0)?, record.get(1)?),
+        LogFormat::GpuZ => record.get(0)?.to_string(),
+    };
+    // Both tools log local wall-clock time
+    let naive = TIMESTAMP_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&text, format).ok())?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+}
+
+fn parse_value(text: &str) -> Option<f64> {
+    match text {
+        "Yes" => Some(1.0),
+        "No" => Some(0.0),
+        _ => text.parse::<f64>().ok().filter(|value| value.is_finite()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HWINFO_LOG: &str = "\
+Date,Time,Total CPU Usage [%],CPU Package [\u{fffd}C],Core Thermal Throttling [Yes/No],GPU Clock [MHz],Unknown Sensor [X],
+16.10.2024,12:00:00.123,12.5,45.0,No,1500,7,
+16.10.2024,12:00:02.123,80.0,71.5,Yes,2100,7,
+,,Total CPU Usage [%],CPU Package [\u{fffd}C],Core Thermal Throttling [Yes/No],GPU Clock [MHz],Unknown Sensor [X],
+";
+
+    const GPUZ_LOG: &str = "\
+        Date        , GPU Clock [MHz] , Memory Clock [MHz] , GPU Temperature [\u{b0}C] , Memory Used [MB] , GPU Load [%] ,
+2024-10-16 12:00:00 , 1800.0 , 1250.0 , 55.0 , 2048 , 30 ,
+2024-10-16 12:00:01 , 1950.0 , 1750.0 , 57.0 , 4096 , 95 ,
+";
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(LogFormat::detect(&["Date", "Time", "CPU"]), Some(LogFormat::HwInfo));
+        assert_eq!(LogFormat::detect(&["  Date  ", "GPU Clock [MHz]"]), Some(LogFormat::GpuZ));
+        assert_eq!(LogFormat::detect(&["timestamp", "cpu"]), None);
+    }
+
+    #[test]
+    fn test_parse_hwinfo_log() {
+        let session = parse_log("hwinfo.csv", HWINFO_LOG).expect("Failed to parse HWiNFO log");
+
+        assert_eq!(session.format, LogFormat::HwInfo);
+        assert_eq!(session.samples, 2);
+        assert_eq!(
+            session.metric_ids,
+            vec!["cpu.utilization", "cpu.package_temperature", "cpu.thermal_throttling", "gpu.clock_speed"]
+        );
+
+        let state = &session.state;
+        assert_eq!(state.cpu.utilization.session_min, Some(12.5));
+        assert_eq!(state.cpu.utilization.session_max, Some(80.0));
+        assert_eq!(state.cpu.package_temperature.current, Some(71.5));
+        assert_eq!(state.cpu.thermal_throttling.active, Some(true));
+        assert_eq!(state.gpu.clock_speed.history.len(), 2);
+
+        // Plot time is relative to the first sample in the log
+        let plot = state.cpu.utilization.plot_data(state.session_start);
+        assert_eq!(plot[0].0, 0.0);
+        assert_eq!(plot[1].0, 2.0);
+    }
+
+    #[test]
+    fn test_parse_gpuz_log() {
+        let session = parse_log("gpuz.txt", GPUZ_LOG).expect("Failed to parse GPU-Z log");
+
+        assert_eq!(session.format, LogFormat::GpuZ);
+        assert_eq!(session.samples, 2);
+        assert_eq!(session.state.gpu.clock_speed.session_max, Some(1950.0));
+        assert_eq!(session.state.gpu.memory_clock_speed.current, Some(1750.0));
+        assert_eq!(session.state.gpu.memory_utilization.current, Some(4096.0));
+        assert_eq!(session.state.gpu.utilization.session_min, Some(30.0));
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_logs() {
+        assert!(parse_log("empty.csv", "").is_err());
+        assert!(parse_log("other.csv", "timestamp,cpu\n1,2\n").is_err());
+        assert!(parse_log("unmapped.csv", "Date,Time,Mystery [X]\n16.10.2024,12:00:00,1\n").is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use eframe::egui;
+
+// Starts another collector even when one is running, e.g. to compare two builds
+pub const NEW_INSTANCE_FLAG: &str = "--new-instance";
+
+// Holds "<pid> <port>" of the running instance, whose port accepts activation requests
+const LOCK_FILE: &str = "simple_performance_dashboard.lock";
+
+const ACTIVATE_TIMEOUT: Duration = Duration::from_millis(500);
+
+// Set when a second launch asked this instance to come to the front before its window existed
+static ACTIVATION_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+// The dashboard window, which the listener brings to the front itself. An idle window
+// may not redraw for a while, so waiting for its next frame could leave it minimized
+static WINDOW: OnceLock<egui::Context> = OnceLock::new();
+
+pub enum Startup {
+    Primary(InstanceGuard), // No other instance; this one holds the lock until dropped
+    Activated,              // Another instance is running and was asked to come to the front
+}
+
+// Removes the lock file when the primary instance exits
+pub struct InstanceGuard {
+    path: PathBuf,
+    contents: String,
+}
+
+impl Drop for InstanceGuard {
+    fn drop(&mut self) {
+        // Only our own, in case a --new-instance launch took the lock over
+        if fs::read_to_string(&self.path).is_ok_and(|contents| contents.trim() == self.contents) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+// Become the one running instance, or hand over to the one already running. A lock
+// file whose instance doesn't answer was left by a crash and is taken over
+pub fn acquire(dir: &Path) -> Result<Startup, Box<dyn std::error::Error>> {
+    let path = dir.join(LOCK_FILE);
+    if let Some(port) = running_port(&path) {
+        if activate(port).is_ok() {
+            return Ok(Startup::Activated);
+        }
+        fs::remove_file(&path)?;
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let contents = format!("{} {}", std::process::id(), listener.local_addr()?.port());
+    // create_new, so of two instances starting at once only one gets the lock
+    let mut file = OpenOptions::new().write(true).create_new(true).open(&path)?;
+    writeln!(file, "{}", contents)?;
+    std::thread::Builder::new()
+        .name("spd-instance".to_string())
+        .spawn(move || serve(listener))?;
+    Ok(Startup::Primary(InstanceGuard { path, contents }))
+}
+
+fn running_port(path: &Path) -> Option<u16> {
+    let contents = fs::read_to_string(path).ok()?;
+    contents.split_whitespace().nth(1)?.parse().ok()
+}
+
+// Ask the instance listening on port to come to the front
+pub fn activate(port: u16) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stream = TcpStream::connect_timeout(&([127, 0, 0, 1], port).into(), ACTIVATE_TIMEOUT)?;
+    stream.set_read_timeout(Some(ACTIVATE_TIMEOUT))?;
+    writeln!(stream, "ACTIVATE")?;
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+    if response.trim() != "OK" {
+        return Err(format!("unexpected response from the running instance: {}", response.trim()).into());
+    }
+    Ok(())
+}
+
+fn serve(listener: TcpListener) {
+    for stream in listener.incoming().flatten() {
+        let _ = stream.set_read_timeout(Some(ACTIVATE_TIMEOUT));
+        let mut request = String::new();
+        if BufReader::new(&stream).read_line(&mut request).is_err() {
+            continue;
+        }
+        let response = if request.trim() == "ACTIVATE" {
+            match WINDOW.get() {
+                Some(ctx) => bring_to_front(ctx),
+                None => ACTIVATION_REQUESTED.store(true, Ordering::Relaxed),
+            }
+            crate::logger::log_info("Another launch asked this instance to come to the front");
+            "OK"
+        } else {
+            "ERR unknown request"
+        };
+        let _ = writeln!(&stream, "{}", response);
+    }
+}
+
+// Hand the listener the window once it's created, answering any activation that came first
+pub fn set_window(ctx: &egui::Context) {
+    if WINDOW.set(ctx.clone()).is_ok() && take_activation_request() {
+        bring_to_front(ctx);
+    }
+}
+
+fn bring_to_front(ctx: &egui::Context) {
+    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+    ctx.request_repaint();
+}
+
+// Whether a second launch asked to see this instance since the last call
+fn take_activation_request() -> bool {
+    ACTIVATION_REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_launch_activates_the_first() {
+        let dir = std::env::temp_dir().join(format!("spd_instance_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // A lock left by a crashed instance, whose port nobody listens on
+        let closed_port = TcpListener::bind(("127.0.0.1", 0)).unwrap().local_addr().unwrap().port();
+        fs::write(dir.join(LOCK_FILE), format!("1 {}", closed_port)).unwrap();
+
+        let Ok(Startup::Primary(guard)) = acquire(&dir) else {
+            panic!("a stale lock should be taken over");
+        };
+        assert!(!take_activation_request());
+        assert!(matches!(acquire(&dir), Ok(Startup::Activated)));
+        assert!(take_activation_request());
+        assert!(!take_activation_request());
+
+        // Once the window exists it's brought forward right away, without waiting for a frame
+        assert!(matches!(acquire(&dir), Ok(Startup::Activated)));
+        let ctx = egui::Context::default();
+        set_window(&ctx);
+        assert!(!take_activation_request());
+        let commands = |ctx: &egui::Context| ctx.run(Default::default(), |_| {}).viewport_output[&egui::ViewportId::ROOT].commands.clone();
+        assert_eq!(commands(&ctx), vec![egui::ViewportCommand::Minimized(false), egui::ViewportCommand::Focus]);
+        assert!(matches!(acquire(&dir), Ok(Startup::Activated)));
+        assert!(!take_activation_request());
+        assert!(commands(&ctx).contains(&egui::ViewportCommand::Focus));
+
+        drop(guard);
+        assert!(!dir.join(LOCK_FILE).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,4 +6,30 @@ pub mod hardware;
 pub mod hardware_detection;
 pub mod monitors;
 pub mod logger;
-pub mod ui;
\ No newline at end of file
+pub mod config;
+pub mod alerts;
+pub mod import;
+pub mod export;
+pub mod anonymize;
+pub mod paths;
+pub mod instance;
+pub mod update;
+pub mod remote_write;
+pub mod export_buffer;
+pub mod privileged;
+pub mod power;
+pub mod crash;
+pub mod spill;
+pub mod baseline;
+pub mod regression;
+pub mod scripting;
+pub mod query;
+pub mod lm_sensors;
+pub mod schedule;
+pub mod temperature;
+pub mod builder;
+pub mod ui;
+
+// Embedding the collection layer without the GUI
+pub use builder::{Collector, DashboardBuilder};
+pub use model::Sample;
\ No newline at end of file
@@ -0,0 +1,303 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+use parking_lot::RwLock;
+use crate::model::{MetricDescriptor, METRIC_DESCRIPTORS};
+use crate::monitors::generic::{classify_temperature_sensor, SensorDevice};
+
+// Mappings from the config, consulted by the generic monitor before its own guesses
+static MAPPINGS: RwLock<Vec<SensorMapping>> = RwLock::new(Vec::new());
+
+// What a sensor measures, from the prefix of its subfeature names, e.g. "temp1_input"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadingKind {
+    Temperature,
+    Fan,
+    Voltage,
+    Power,
+    Current,
+    Intrusion, // 1 once the case was opened
+    Other,
+}
+
+impl ReadingKind {
+    pub fn from_subfeature(name: &str) -> Self {
+        let prefix = name.split('_').next().unwrap_or(name);
+        match prefix.trim_end_matches(|c: char| c.is_ascii_digit()) {
+            "temp" => ReadingKind::Temperature,
+            "fan" => ReadingKind::Fan,
+            "in" => ReadingKind::Voltage,
+            "power" => ReadingKind::Power,
+            "curr" => ReadingKind::Current,
+            "intrusion" => ReadingKind::Intrusion,
+            _ => ReadingKind::Other,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReadingKind::Temperature => "temperature",
+            ReadingKind::Fan => "fan",
+            ReadingKind::Voltage => "voltage",
+            ReadingKind::Power => "power",
+            ReadingKind::Current => "current",
+            ReadingKind::Intrusion => "intrusion",
+            ReadingKind::Other => "other",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            ReadingKind::Temperature => "°C",
+            ReadingKind::Fan => " RPM",
+            ReadingKind::Voltage => " V",
+            ReadingKind::Power => " W",
+            ReadingKind::Current => " A",
+            ReadingKind::Intrusion | ReadingKind::Other => "",
+        }
+    }
+}
+
+// One sensor from `sensors -j`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorReading {
+    pub chip: String,  // e.g. "k10temp-pci-00c3"
+    pub label: String, // e.g. "Tctl", or "temp1" when the driver gives no label
+    pub kind: ReadingKind,
+    pub value: f64,
+}
+
+impl SensorReading {
+    // How the reading is written in sensor_mappings
+    pub fn path(&self) -> String {
+        format!("{}/{}", self.chip, self.label)
+    }
+
+    // The temperature metric the generic monitor would most likely fill from this sensor
+    pub fn suggested_metric(&self) -> Option<&'static str> {
+        if self.kind != ReadingKind::Temperature {
+            return None;
+        }
+        match classify_temperature_sensor(&format!("{} {}", hwmon_name(&self.chip), self.label)) {
+            Some(SensorDevice::Cpu) => Some("cpu.package_temperature"),
+            Some(SensorDevice::Gpu) => Some("gpu.package_temperature"),
+            Some(SensorDevice::Storage) => Some("storage.temperature"),
+            Some(SensorDevice::Motherboard) => None,
+            None => Some("memory.temperature"),
+        }
+    }
+}
+
+// A metric read from a chosen sensor, written in the config as
+// "cpu.package_temperature" = "k10temp-pci-00c3/Tctl"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SensorMapping {
+    pub metric_id: String,
+    pub chip: String,
+    pub label: String,
+}
+
+impl SensorMapping {
+    pub fn parse(metric_id: &str, path: &str) -> Option<Self> {
+        let (chip, label) = path.trim().split_once('/')?;
+        if chip.is_empty() || label.is_empty() {
+            return None;
+        }
+        Some(Self { metric_id: metric_id.to_string(), chip: chip.to_string(), label: label.to_string() })
+    }
+
+    pub fn path(&self) -> String {
+        format!("{}/{}", self.chip, self.label)
+    }
+
+    // sysinfo names a sensor by its hwmon name and label, e.g. "k10temp Tctl", and
+    // appends the model and channel for drives, e.g. "nvme Composite Samsung SSD 980 temp1"
+    pub fn matches_component(&self, component_label: &str) -> bool {
+        let expected = format!("{} {}", hwmon_name(&self.chip), self.label);
+        component_label == expected
+            || component_label.strip_prefix(&expected).is_some_and(|rest| rest.starts_with(' '))
+    }
+}
+
+// lm-sensors appends the bus and address to the driver's name, e.g. "nvme-pci-0100"
+fn hwmon_name(chip: &str) -> &str {
+    chip.split('-').next().unwrap_or(chip)
+}
+
+// Replace the mappings, e.g. at startup and when the settings change; malformed entries are left out
+pub fn set_mappings(mappings: &BTreeMap<String, String>) {
+    *MAPPINGS.write() = mappings
+        .iter()
+        .filter_map(|(metric_id, path)| SensorMapping::parse(metric_id, path))
+        .collect();
+}
+
+pub fn mappings() -> Vec<SensorMapping> {
+    MAPPINGS.read().clone()
+}
+
+// Metrics a sensor can be mapped to
+pub fn temperature_metrics() -> impl Iterator<Item = &'static MetricDescriptor> {
+    METRIC_DESCRIPTORS.iter().filter(|descriptor| descriptor.unit.trim() == "°C")
+}
+
+// Run `sensors -j`, as the user would to look up chip and label names
+pub fn read_live() -> Result<String, Box<dyn std::error::Error>> {
+    let output = Command::new("sensors")
+        .arg("-j")
+        .output()
+        .map_err(|e| format!("could not run `sensors -j` ({}); is lm-sensors installed?", e))?;
+    // sensors exits with an error when one chip fails but still prints the others
+    if output.stdout.is_empty() {
+        return Err(format!("`sensors -j` failed: {}", String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+// Every sensor with a reading in `sensors -j` output, by chip and label:
+//
+//     { "k10temp-pci-00c3": { "Adapter": "PCI adapter", "Tctl": { "temp1_input": 45.5 } } }
+#[cfg(feature = "lm-sensors")]
+pub fn parse(json: &str) -> Result<Vec<SensorReading>, Box<dyn std::error::Error>> {
+    let chips: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json)?;
+    let mut readings = Vec::new();
+    for (chip, sensors) in &chips {
+        let Some(sensors) = sensors.as_object() else {
+            continue;
+        };
+        // "Adapter" is a string; every sensor is an object of subfeatures
+        for (label, subfeatures) in sensors.iter().filter_map(|(label, value)| Some((label, value.as_object()?))) {
+            // Power meters report an average rather than an input, and chassis intrusion only an alarm
+            let reading = subfeatures.iter().find_map(|(name, value)| {
+                let is_reading = name.ends_with("_input") || name.ends_with("_average") || name.starts_with("intrusion");
+                (is_reading && !name.ends_with("_beep")).then_some((name, value.as_f64()?))
+            });
+            if let Some((name, value)) = reading {
+                readings.push(SensorReading {
+                    chip: chip.clone(),
+                    label: label.clone(),
+                    kind: ReadingKind::from_subfeature(name),
+                    value,
+                });
+            }
+        }
+    }
+    if readings.is_empty() {
+        return Err("no sensor readings found; expected the output of `sensors -j`".into());
+    }
+    Ok(readings)
+}
+
+#[cfg(not(feature = "lm-sensors"))]
+pub fn parse(_json: &str) -> Result<Vec<SensorReading>, Box<dyn std::error::Error>> {
+    Err("this build was compiled without lm-sensors support".into())
+}
+
+// A mapping that won't work on this machine, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappingProblem {
+    pub metric_id: String,
+    pub message: String,
+}
+
+// Check each mapping against the sensors actually present
+pub fn validate(mappings: &BTreeMap<String, String>, readings: &[SensorReading]) -> Vec<MappingProblem> {
+    let mut problems = Vec::new();
+    for (metric_id, path) in mappings {
+        let mut problem = |message: String| problems.push(MappingProblem { metric_id: metric_id.clone(), message });
+        if !temperature_metrics().any(|descriptor| descriptor.id == metric_id) {
+            problem(format!("\"{}\" is not a temperature metric", metric_id));
+            continue;
+        }
+        let Some(mapping) = SensorMapping::parse(metric_id, path) else {
+            problem(format!("\"{}\" should be written as \"chip/label\"", path));
+            continue;
+        };
+        let on_chip: Vec<&SensorReading> = readings.iter().filter(|reading| reading.chip == mapping.chip).collect();
+        if on_chip.is_empty() {
+            let mut chips: Vec<&str> = readings.iter().map(|reading| reading.chip.as_str()).collect();
+            chips.dedup();
+            problem(format!("no chip \"{}\"; found {}", mapping.chip, chips.join(", ")));
+            continue;
+        }
+        match on_chip.iter().find(|reading| reading.label == mapping.label) {
+            None => {
+                let labels: Vec<&str> = on_chip.iter().map(|reading| reading.label.as_str()).collect();
+                problem(format!("{} has no sensor \"{}\"; it has {}", mapping.chip, mapping.label, labels.join(", ")));
+            }
+            Some(reading) if reading.kind != ReadingKind::Temperature => {
+                problem(format!("{} is a {} reading, not a temperature", mapping.path(), reading.kind.name()));
+            }
+            Some(_) => {}
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(chip: &str, label: &str, kind: ReadingKind, value: f64) -> SensorReading {
+        SensorReading { chip: chip.to_string(), label: label.to_string(), kind, value }
+    }
+
+    #[cfg(feature = "lm-sensors")]
+    #[test]
+    fn test_sensors_json_is_parsed() {
+        let json = r#"{
+            "k10temp-pci-00c3": { "Adapter": "PCI adapter", "Tctl": { "temp1_input": 45.5 }, "Tccd1": { "temp3_input": 40.25 } },
+            "nct6798-isa-0290": { "Adapter": "ISA adapter", "fan2": { "fan2_input": 912.0, "fan2_min": 0.0 }, "in0": { "in0_input": 0.304 },
+                "intrusion0": { "intrusion0_beep": 0.0, "intrusion0_alarm": 1.0 } },
+            "amdgpu-pci-0300": { "Adapter": "PCI adapter", "PPT": { "power1_average": 18.0, "power1_cap": 200.0 } }
+        }"#;
+        let readings = parse(json).unwrap();
+        assert_eq!(readings, vec![
+            reading("amdgpu-pci-0300", "PPT", ReadingKind::Power, 18.0),
+            reading("k10temp-pci-00c3", "Tccd1", ReadingKind::Temperature, 40.25),
+            reading("k10temp-pci-00c3", "Tctl", ReadingKind::Temperature, 45.5),
+            reading("nct6798-isa-0290", "fan2", ReadingKind::Fan, 912.0),
+            reading("nct6798-isa-0290", "in0", ReadingKind::Voltage, 0.304),
+            reading("nct6798-isa-0290", "intrusion0", ReadingKind::Intrusion, 1.0),
+        ]);
+        assert_eq!(readings[2].suggested_metric(), Some("cpu.package_temperature"));
+        assert!(parse("{}").is_err());
+        assert!(parse("k10temp-pci-00c3\nTctl: +45.5°C").is_err());
+    }
+
+    #[test]
+    fn test_mappings_are_validated_and_matched() {
+        let readings = vec![
+            reading("k10temp-pci-00c3", "Tctl", ReadingKind::Temperature, 45.5),
+            reading("nvme-pci-0100", "Composite", ReadingKind::Temperature, 38.0),
+            reading("nct6798-isa-0290", "fan2", ReadingKind::Fan, 912.0),
+        ];
+        let mappings: BTreeMap<String, String> = [
+            ("cpu.package_temperature", "k10temp-pci-00c3/Tctl"),
+            ("storage.temperature", "nvme-pci-0100/Composite"),
+            ("cpu.hotspot_temperature", "k10temp-pci-00c3/Tdie"),
+            ("motherboard.chassis_temperature", "nct6798-isa-0290/fan2"),
+            ("gpu.package_temperature", "amdgpu-pci-0300/edge"),
+            ("memory.temperature", "spd5118"),
+            ("cpu.utilization", "k10temp-pci-00c3/Tctl"),
+        ]
+        .into_iter()
+        .map(|(id, path)| (id.to_string(), path.to_string()))
+        .collect();
+
+        let problems = validate(&mappings, &readings);
+        let problems: Vec<(&str, &str)> = problems.iter().map(|problem| (problem.metric_id.as_str(), problem.message.as_str())).collect();
+        assert_eq!(problems, vec![
+            ("cpu.hotspot_temperature", "k10temp-pci-00c3 has no sensor \"Tdie\"; it has Tctl"),
+            ("cpu.utilization", "\"cpu.utilization\" is not a temperature metric"),
+            ("gpu.package_temperature", "no chip \"amdgpu-pci-0300\"; found k10temp-pci-00c3, nvme-pci-0100, nct6798-isa-0290"),
+            ("memory.temperature", "\"spd5118\" should be written as \"chip/label\""),
+            ("motherboard.chassis_temperature", "nct6798-isa-0290/fan2 is a fan reading, not a temperature"),
+        ]);
+
+        let cpu = SensorMapping::parse("cpu.package_temperature", "k10temp-pci-00c3/Tctl").unwrap();
+        assert!(cpu.matches_component("k10temp Tctl"));
+        assert!(!cpu.matches_component("k10temp Tctl2"));
+        let drive = SensorMapping::parse("storage.temperature", "nvme-pci-0100/Composite").unwrap();
+        assert!(drive.matches_component("nvme Composite Samsung SSD 980 PRO 1TB temp1"));
+    }
+}
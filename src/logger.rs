@@ -1,8 +1,14 @@
 use std::fs::{File, OpenOptions};
 use std::io::Write;
+use std::cell::Cell;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use chrono::Utc;
-use log::{error, warn, info};
+use log::{debug, error, warn, info};
+use crate::hardware_detection::MonitorError;
+
+// Traces the first polling cycle, as the Trace sensors button does later on
+pub const TRACE_SENSORS_FLAG: &str = "--trace-sensors";
 
 pub struct AppLogger {
     log_file_path: PathBuf,
@@ -10,10 +16,9 @@ pub struct AppLogger {
 
 impl AppLogger {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        // Create log file in application directory
-        let mut log_path = std::env::current_exe()?;
-        log_path.pop(); // Remove executable name
-        log_path.push("simple_performance_dashboard.log");
+        // Create log file in the data directory; beside the executable only when portable,
+        // since installs under Program Files can't write there
+        let log_path = crate::paths::data_dir()?.join("simple_performance_dashboard.log");
         
         let logger = Self {
             log_file_path: log_path,
@@ -67,6 +72,31 @@ impl AppLogger {
         warn!("{}", message);
     }
     
+    // Missing drivers and bad readings are expected on some machines; the rest are errors
+    pub fn log_monitor_error(&self, monitor: &str, error: &MonitorError) {
+        let mut message = format!("{} monitor: {}", monitor, error);
+        if let Some(hint) = error.hint() {
+            message.push_str(&format!(" - {}", hint));
+        }
+        match error {
+            MonitorError::PermissionDenied(_) | MonitorError::DeviceGone(_) => {
+                self.write_log_entry("ERROR", &message);
+                error!("{}", message);
+            }
+            MonitorError::DriverMissing(_) | MonitorError::Timeout(_) | MonitorError::Parse(_) => {
+                self.write_log_entry("WARN", &message);
+                warn!("{}", message);
+            }
+        }
+    }
+    
+    // Raw sensor label and value and what the monitor did with it, while tracing
+    pub fn log_sensor_trace(&self, monitor: &str, label: &str, value: f64, mapping: &str) {
+        let message = format!("Sensor trace - {}: \"{}\" = {} -> {}", monitor, label, value, mapping);
+        self.write_log_entry("TRACE", &message);
+        debug!("{}", message);
+    }
+    
     pub fn log_hardware_polling_error(&self, error: &dyn std::error::Error) {
         let message = format!("Hardware polling error: {}", error);
         self.write_log_entry("ERROR", &message);
@@ -77,6 +107,15 @@ impl AppLogger {
 // Global logger instance
 static mut LOGGER: Option<AppLogger> = None;
 
+// Bumped for every sensor trace request; each monitor traces its next poll once it
+// sees a generation it hasn't traced yet
+static SENSOR_TRACE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    // Set by the poller around the update of a monitor being traced
+    static SENSOR_TRACING: Cell<bool> = const { Cell::new(false) };
+}
+
 pub fn initialize_logger() -> Result<(), Box<dyn std::error::Error>> {
     let logger = AppLogger::new()?;
     unsafe {
@@ -85,6 +124,15 @@ pub fn initialize_logger() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+pub fn log_file_path() -> Option<PathBuf> {
+    unsafe {
+        if let Some(ref logger) = LOGGER {
+            return Some(logger.log_file_path.clone());
+        }
+    }
+    None
+}
+
 pub fn log_info(message: &str) {
     unsafe {
         if let Some(ref logger) = LOGGER {
@@ -125,6 +173,44 @@ pub fn log_sensor_unavailable(sensor_name: &str) {
     }
 }
 
+pub fn log_monitor_error(monitor: &str, error: &MonitorError) {
+    unsafe {
+        if let Some(ref logger) = LOGGER {
+            logger.log_monitor_error(monitor, error);
+        }
+    }
+}
+
+// Trace every sensor reading of the next polling cycle, from the UI or --trace-sensors
+pub fn request_sensor_trace() {
+    SENSOR_TRACE_GENERATION.fetch_add(1, Ordering::Relaxed);
+    log_info("Sensor trace requested for the next polling cycle");
+}
+
+pub fn sensor_trace_generation() -> u64 {
+    SENSOR_TRACE_GENERATION.load(Ordering::Relaxed)
+}
+
+pub fn set_sensor_tracing(tracing: bool) {
+    SENSOR_TRACING.with(|cell| cell.set(tracing));
+}
+
+pub fn sensor_tracing() -> bool {
+    SENSOR_TRACING.with(Cell::get)
+}
+
+// No-op unless the current monitor update is being traced
+pub fn trace_sensor(monitor: &str, label: &str, value: f64, mapping: &str) {
+    if !sensor_tracing() {
+        return;
+    }
+    unsafe {
+        if let Some(ref logger) = LOGGER {
+            logger.log_sensor_trace(monitor, label, value, mapping);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -181,6 +267,31 @@ mod tests {
         logger.log_sensor_unavailable("GPU Clock Speed");
     }
 
+    #[test]
+    fn test_log_monitor_error() {
+        let logger = AppLogger::new().expect("Failed to create logger");
+        
+        // Test that each severity path doesn't panic
+        logger.log_monitor_error("Intel", &MonitorError::PermissionDenied("MSR access denied".to_string()));
+        logger.log_monitor_error("NVIDIA", &MonitorError::DriverMissing("NVML not found".to_string()));
+    }
+
+    #[test]
+    fn test_sensor_trace_request_bumps_generation() {
+        let before = sensor_trace_generation();
+        request_sensor_trace();
+        assert!(sensor_trace_generation() > before);
+        
+        // Tracing is per thread and off unless the poller turns it on
+        assert!(!sensor_tracing());
+        set_sensor_tracing(true);
+        assert!(sensor_tracing());
+        assert!(!std::thread::spawn(sensor_tracing).join().unwrap());
+        trace_sensor("Generic", "k10temp Tctl", 72.0, "cpu sensors");
+        set_sensor_tracing(false);
+        assert!(!sensor_tracing());
+    }
+    
     #[test]
     fn test_log_hardware_polling_error() {
         let logger = AppLogger::new().expect("Failed to create logger");
@@ -206,7 +317,7 @@ mod tests {
         log_info("Global info test");
         log_warning("Global warning test");
         
-        let test_error = std::io::Error::new(std::io::ErrorKind::Other, "Global error test");
+        let test_error = std::io::Error::other("Global error test");
         log_error("Global error context", &test_error);
         
         log_sensor_error("Test Sensor", &test_error);
@@ -224,7 +335,7 @@ mod tests {
         log_info("Should not crash");
         log_warning("Should not crash");
         
-        let test_error = std::io::Error::new(std::io::ErrorKind::Other, "Should not crash");
+        let test_error = std::io::Error::other("Should not crash");
         log_error("Should not crash", &test_error);
         log_sensor_error("Should not crash", &test_error);
         log_sensor_unavailable("Should not crash");
@@ -1,35 +1,165 @@
-mod model;
-mod hardware;
-mod hardware_detection;
-mod monitors;
-mod ui;
-mod logger;
-
-use model::AppState;
-use hardware::HardwarePoller;
-use ui::run_app;
+use simple_performance_dashboard::logger;
+use simple_performance_dashboard::config::{AppConfig, READ_ONLY_FLAG};
+use simple_performance_dashboard::model::{self, AppState};
+use simple_performance_dashboard::hardware::HardwarePoller;
+use simple_performance_dashboard::hardware_detection::HardwareDetector;
+use simple_performance_dashboard::ui::run_app;
+use simple_performance_dashboard::privileged;
+use simple_performance_dashboard::crash;
+use simple_performance_dashboard::anonymize;
+use simple_performance_dashboard::update;
+use simple_performance_dashboard::paths;
+use simple_performance_dashboard::instance::{self, Startup};
+use simple_performance_dashboard::lm_sensors;
+use simple_performance_dashboard::monitors::smart;
+use simple_performance_dashboard::monitors::synthetic::SyntheticLoad;
+#[cfg(feature = "remote-write")]
+use simple_performance_dashboard::remote_write::RemoteWriter;
 
 fn main() -> eframe::Result<()> {
+    // Scriptable version check, e.g. from cron on a headless install
+    let args: Vec<String> = std::env::args().collect();
+    paths::set_portable(args.iter().any(|arg| arg == paths::PORTABLE_FLAG));
+    if args.iter().any(|arg| arg == update::VERSION_FLAG || arg == update::CHECK_UPDATE_FLAG) {
+        println!("simple_performance_dashboard {}", env!("CARGO_PKG_VERSION"));
+        if args.iter().any(|arg| arg == update::CHECK_UPDATE_FLAG) {
+            match update::check_for_update() {
+                Ok(Some(release)) => println!("Version {} is available: {}", release.version(), release.html_url),
+                Ok(None) => println!("This is the latest version"),
+                Err(e) => {
+                    eprintln!("Update check failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        return Ok(());
+    }
+    
+    // Run elevated as the sensor helper instead of the GUI, e.g.
+    // `sudo simple_performance_dashboard --privileged-helper`. Checked before the
+    // logger starts so the helper doesn't overwrite the GUI's log file. It serves the
+    // user who ran sudo and reads none of root's settings
+    if std::env::args().any(|arg| arg == privileged::HELPER_FLAG) {
+        if let Err(e) = privileged::serve() {
+            eprintln!("Privileged helper failed: {}", e);
+        }
+        return Ok(());
+    }
+    
+    // Developer mode generating fake data instead of reading sensors
+    let synthetic_load = match std::env::args().find_map(|arg| SyntheticLoad::from_arg(&arg)) {
+        Some(Ok(load)) => Some(load),
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            return Ok(());
+        }
+        None => None,
+    };
+    
+    // One collector per user, so two don't fight over the sensors: a second launch brings
+    // the running window to the front instead. Checked before the logger starts so it
+    // doesn't overwrite the running instance's log file
+    let instance = if args.iter().any(|arg| arg == instance::NEW_INSTANCE_FLAG) {
+        Ok(None)
+    } else {
+        match paths::data_dir().and_then(|dir| instance::acquire(&dir)) {
+            Ok(Startup::Activated) => {
+                println!("Simple Performance Dashboard is already running; brought it to the front");
+                return Ok(());
+            }
+            Ok(Startup::Primary(guard)) => Ok(Some(guard)),
+            Err(e) => Err(e),
+        }
+    };
+    
     // Initialize logging system
     if let Err(e) = logger::initialize_logger() {
         eprintln!("Failed to initialize logger: {}", e);
     }
+    crash::install_panic_hook();
     
     logger::log_info("Simple Performance Dashboard starting...");
+    let _instance = match instance {
+        Ok(guard) => guard,
+        Err(e) => {
+            logger::log_error("Failed to check for a running instance, starting anyway", &*e);
+            None
+        }
+    };
+    if paths::is_portable() {
+        logger::log_info("Portable mode: config, logs and sessions are kept beside the executable");
+    }
+    match paths::migrate_legacy_files() {
+        Ok(copied) if !copied.is_empty() => {
+            logger::log_info(&format!("Copied {} settings and data files from beside the executable to the config and data directories", copied.len()));
+        }
+        Ok(_) => {}
+        Err(e) => logger::log_error("Failed to copy settings from beside the executable", &*e),
+    }
+    if std::env::args().any(|arg| arg == logger::TRACE_SENSORS_FLAG) {
+        logger::request_sensor_trace();
+    }
+    
+    // Load user configuration, this machine's profile when it has one
+    let fingerprint = HardwareDetector::detect().fingerprint();
+    let config = AppConfig::new_shared(AppConfig::load_for_machine(&fingerprint));
+    if std::env::args().any(|arg| arg == READ_ONLY_FLAG) {
+        config.write().read_only = true;
+    }
+    if config.read().read_only {
+        logger::log_info("Running read-only: alert actions, keep awake and settings changes are disabled");
+    }
+    privileged::configure(&config.read().helper);
+    // Saved once generated, so pseudonyms stay the same across sessions
+    if config.read().anonymize && config.read().anonymize_salt == 0 {
+        let mut config = config.write();
+        config.ensure_anonymize_salt();
+        if !config.read_only {
+            if let Err(e) = config.save() {
+                logger::log_error("Failed to save config", &*e);
+            }
+        }
+    }
+    anonymize::configure(config.read().anonymize, config.read().anonymize_salt);
+    model::set_display_overrides(&config.read().metric_overrides);
+    lm_sensors::set_mappings(&config.read().sensor_mappings);
+    smart::set_rated_tbw(&config.read().drive_rated_tbw);
     
     // Initialize shared application state
-    let polling_interval_ms = 1000; // 1 second default
+    let polling_interval_ms = config.read().polling_interval_ms;
     let app_state = AppState::new_shared(polling_interval_ms);
     
     logger::log_info(&format!("Initialized application state with {}ms polling interval", polling_interval_ms));
     
-    // Start hardware polling thread
-    let poller = HardwarePoller::new(app_state.clone(), polling_interval_ms);
-    let _polling_handle = poller.start_polling_thread();
+    // Background work (polling, alerts, exporters) runs on a tokio runtime while
+    // the GUI keeps the main thread
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .thread_name("spd-worker")
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            logger::log_error("Failed to start async runtime", &e);
+            return Ok(());
+        }
+    };
+    
+    // One polling task per monitor, supervised by a watchdog task
+    let poller = match synthetic_load {
+        Some(load) => HardwarePoller::with_synthetic_load(app_state.clone(), polling_interval_ms, config.clone(), load),
+        None => HardwarePoller::with_config(app_state.clone(), polling_interval_ms, config.clone()),
+    };
+    crash::set_hardware_summary(app_state.read().poller.hardware_summary.clone());
+    let _polling_tasks = poller.start_async(runtime.handle());
+    
+    logger::log_info("Hardware polling tasks started");
     
-    logger::log_info("Hardware polling thread started");
+    // Push samples to a Prometheus remote_write endpoint when configured
+    #[cfg(feature = "remote-write")]
+    let _remote_write_task = RemoteWriter::new(app_state.clone(), config.clone()).start(runtime.handle());
     
     // Run the GUI application
     logger::log_info("Starting GUI application");
-    run_app(app_state)
+    run_app(app_state, config)
 }
@@ -1,122 +1,947 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::Arc;
 use parking_lot::RwLock;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use crate::alerts::AlertHistory;
+use crate::config::MetricOverride;
+use crate::hardware_detection::{CpuTopology, SoftwareVersions};
+use crate::logger;
+use crate::spill::HistorySpill;
 
-#[derive(Debug, Clone)]
-pub struct MetricValue<T> {
-    pub current: Option<T>,
-    pub session_min: Option<T>,
-    pub session_max: Option<T>,
-    pub history: VecDeque<(DateTime<Utc>, T)>,
+// Every metric is stored as f64, so any series can be looked up, plotted and exported
+// the same way; its descriptor's MetricKind decides how values are shown
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "StoredMetricValue")]
+pub struct MetricValue {
+    pub current: Option<f64>,
+    pub session_min: Option<f64>,
+    pub session_max: Option<f64>,
+    pub history: VecDeque<(DateTime<Utc>, f64)>, // In-memory history, newest last
+    #[serde(skip)]
+    pub spilled: usize, // Older samples moved to the HistorySpill, not in `history`
 }
 
-impl<T> Default for MetricValue<T> 
-where 
-    T: Clone + PartialOrd,
-{
-    fn default() -> Self {
-        Self {
-            current: None,
-            session_min: None,
-            session_max: None,
-            history: VecDeque::new(), // Full session history
-        }
+impl MetricValue {
+    // Monitors record in their native types (f32, u32, u64 or bool)
+    pub fn update(&mut self, value: impl ToF64) {
+        self.record_at(Utc::now(), value);
     }
-}
-
-impl<T> MetricValue<T> 
-where 
-    T: Clone + PartialOrd,
-{
-    pub fn update(&mut self, value: T) {
-        let timestamp = Utc::now();
-        
-        // Update current value
-        self.current = Some(value.clone());
+    
+    // Record a sample taken at a known time, e.g. when replaying an imported log
+    pub fn record_at(&mut self, timestamp: DateTime<Utc>, value: impl ToF64) {
+        let value = value.to_f64();
+        self.current = Some(value);
         
         // Update session min/max
-        if let Some(ref min) = self.session_min {
-            if value < *min {
-                self.session_min = Some(value.clone());
-            }
-        } else {
-            self.session_min = Some(value.clone());
+        if self.session_min.is_none_or(|min| value < min) {
+            self.session_min = Some(value);
         }
-        
-        if let Some(ref max) = self.session_max {
-            if value > *max {
-                self.session_max = Some(value.clone());
-            }
-        } else {
-            self.session_max = Some(value.clone());
+        if self.session_max.is_none_or(|max| value > max) {
+            self.session_max = Some(value);
         }
         
         // Add to history (keep full session history)
         self.history.push_back((timestamp, value));
     }
     
-    pub fn get_history_for_plot(&self, session_start: DateTime<Utc>) -> Vec<(f64, f64)> {
+    pub fn plot_data(&self, session_start: DateTime<Utc>) -> Vec<(f64, f64)> {
         self.history
             .iter()
-            .map(|(timestamp, value)| {
-                let elapsed_seconds = (*timestamp - session_start).num_seconds() as f64;
-                (elapsed_seconds, self.value_to_f64(value))
-            })
+            .map(|(timestamp, value)| (elapsed_seconds(session_start, *timestamp), *value))
             .collect()
     }
     
-    fn value_to_f64(&self, _value: &T) -> f64 {
-        // This is a placeholder - in practice, you'd implement this for each concrete type
-        // For now, we'll handle this in the specific metric implementations
-        0.0
+    pub fn samples(&self) -> Vec<(DateTime<Utc>, f64)> {
+        self.history.iter().copied().collect()
+    }
+    
+    // Samples newer than `since`, oldest first. Walks back from the newest, so only
+    // what's new is visited
+    pub fn samples_since(&self, since: DateTime<Utc>) -> Vec<(DateTime<Utc>, f64)> {
+        let mut samples: Vec<_> = self.history.iter().rev().take_while(|(timestamp, _)| *timestamp > since).copied().collect();
+        samples.reverse();
+        samples
+    }
+    
+    // Walks back from the newest sample, so only the recent window is visited
+    pub fn recent_stats(&self, since: DateTime<Utc>) -> Option<RecentStats> {
+        let mut recent = self.history.iter().rev().take_while(|(timestamp, _)| *timestamp >= since);
+        let first = recent.next()?.1;
+        let mut stats = RecentStats { min: first, max: first, average: first, samples: 1 };
+        for (_, value) in recent {
+            stats.min = stats.min.min(*value);
+            stats.max = stats.max.max(*value);
+            stats.average += value;
+            stats.samples += 1;
+        }
+        stats.average /= stats.samples as f64;
+        Some(stats)
     }
 }
 
-#[derive(Debug, Clone, Default)]
+// Min, max and mean over the samples in a trailing window
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RecentStats {
+    pub min: f64,
+    pub max: f64,
+    pub average: f64,
+    pub samples: usize,
+}
+
+// On-disk form of a MetricValue. Fields default individually, so a recording written
+// before a field existed still loads, and flags recorded before f64 storage were booleans
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct StoredMetricValue {
+    current: Option<StoredValue>,
+    session_min: Option<StoredValue>,
+    session_max: Option<StoredValue>,
+    history: VecDeque<(DateTime<Utc>, StoredValue)>,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(untagged)]
+enum StoredValue {
+    Number(f64),
+    Flag(bool),
+}
+
+impl ToF64 for StoredValue {
+    fn to_f64(&self) -> f64 {
+        match self {
+            StoredValue::Number(value) => *value,
+            StoredValue::Flag(flag) => flag.to_f64(),
+        }
+    }
+}
+
+impl From<StoredMetricValue> for MetricValue {
+    fn from(stored: StoredMetricValue) -> Self {
+        Self {
+            current: stored.current.as_ref().map(ToF64::to_f64),
+            session_min: stored.session_min.as_ref().map(ToF64::to_f64),
+            session_max: stored.session_max.as_ref().map(ToF64::to_f64),
+            history: stored.history.into_iter().map(|(timestamp, stored)| (timestamp, stored.to_f64())).collect(),
+            spilled: 0,
+        }
+    }
+}
+
+// On/off state kept as its transitions rather than a 0/1 sample every poll, e.g.
+// thermal throttling, an alert firing or running on battery
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EventMetric {
+    pub active: Option<bool>,
+    pub changes: Vec<(DateTime<Utc>, bool)>, // First observation, then every change of state
+    pub activations: usize,
+    pub last_seen: Option<DateTime<Utc>>,    // Newest observation, which closes the final span
+}
+
+impl EventMetric {
+    pub fn update(&mut self, active: bool) {
+        self.record_at(Utc::now(), active);
+    }
+    
+    pub fn record_at(&mut self, timestamp: DateTime<Utc>, active: bool) {
+        self.last_seen = Some(timestamp);
+        if self.active == Some(active) {
+            return;
+        }
+        if active {
+            self.activations += 1;
+        }
+        self.active = Some(active);
+        self.changes.push((timestamp, active));
+    }
+    
+    // Total time spent active up to the newest observation
+    pub fn active_duration(&self) -> chrono::Duration {
+        let mut total = chrono::Duration::zero();
+        for (index, (start, active)) in self.changes.iter().enumerate() {
+            let end = self.changes.get(index + 1).map(|(end, _)| *end).or(self.last_seen);
+            if let (true, Some(end)) = (*active, end) {
+                total += end - *start;
+            }
+        }
+        total
+    }
+    
+    // The changes as 0/1 samples, plus the newest observation so the last span has an end
+    pub fn samples(&self) -> Vec<(DateTime<Utc>, f64)> {
+        let mut samples: Vec<(DateTime<Utc>, f64)> =
+            self.changes.iter().map(|(timestamp, active)| (*timestamp, active.to_f64())).collect();
+        if let (Some(last_seen), Some(active)) = (self.last_seen, self.active) {
+            if samples.last().is_some_and(|(timestamp, _)| *timestamp < last_seen) {
+                samples.push((last_seen, active.to_f64()));
+            }
+        }
+        samples
+    }
+    
+    // Stepped line: each state holds until the instant of the next change
+    pub fn step_plot_data(&self, session_start: DateTime<Utc>) -> Vec<(f64, f64)> {
+        let elapsed = |timestamp: DateTime<Utc>| elapsed_seconds(session_start, timestamp);
+        let mut points: Vec<(f64, f64)> = Vec::new();
+        for (timestamp, value) in self.samples() {
+            if let Some(&(_, previous)) = points.last() {
+                points.push((elapsed(timestamp), previous));
+            }
+            points.push((elapsed(timestamp), value));
+        }
+        points
+    }
+}
+
+// Recordings from before events stored flags as a sampled series, which is replayed
+impl<'de> Deserialize<'de> for EventMetric {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Default, Deserialize)]
+        #[serde(default)]
+        struct StoredEventMetric {
+            active: Option<bool>,
+            changes: Vec<(DateTime<Utc>, bool)>,
+            activations: usize,
+            last_seen: Option<DateTime<Utc>>,
+            history: Vec<(DateTime<Utc>, StoredValue)>,
+        }
+        
+        let stored = StoredEventMetric::deserialize(deserializer)?;
+        if stored.changes.is_empty() && !stored.history.is_empty() {
+            let mut event = EventMetric::default();
+            for (timestamp, value) in stored.history {
+                event.record_at(timestamp, value.to_f64() >= 0.5);
+            }
+            return Ok(event);
+        }
+        Ok(EventMetric {
+            active: stored.active,
+            changes: stored.changes,
+            activations: stored.activations,
+            last_seen: stored.last_seen,
+        })
+    }
+}
+
+// Monotonically increasing source (bytes transferred, energy in µJ) kept as its raw
+// reading, so rates come from one place that handles wraparound and resets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CounterMetric {
+    pub range: Option<u64>,                  // Value the counter wraps back to 0 at, if known
+    pub last: Option<(DateTime<Utc>, u64)>,
+    pub total: u64,                          // Increase over the session, across wraps
+    pub resets: usize,
+}
+
+impl CounterMetric {
+    pub fn with_range(range: u64) -> Self {
+        Self { range: Some(range), ..Self::default() }
+    }
+    
+    pub fn update(&mut self, value: u64) -> Option<f64> {
+        self.record_at(Utc::now(), value)
+    }
+    
+    // Per-second increase since the previous reading. None on the first reading and when
+    // the counter went backwards without a known range (driver reload, device removed)
+    pub fn record_at(&mut self, timestamp: DateTime<Utc>, value: u64) -> Option<f64> {
+        let previous = self.last.replace((timestamp, value));
+        let (at, previous) = previous?;
+        let increase = match (value.checked_sub(previous), self.range) {
+            (Some(increase), _) => increase,
+            (None, Some(range)) if previous <= range => range - previous + value,
+            (None, _) => {
+                self.resets += 1;
+                return None;
+            }
+        };
+        self.total += increase;
+        let seconds = (timestamp - at).num_milliseconds() as f64 / 1000.0;
+        (seconds > 0.0).then(|| increase as f64 / seconds)
+    }
+}
+
+// Every temperature sensor a device exposes, by name (e.g. Tctl, Tdie, Tccd1 or GPU
+// edge/junction/mem), so nothing is lost by picking one for package_temperature
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemperatureSensors(BTreeMap<String, MetricValue>);
+
+impl TemperatureSensors {
+    pub fn update(&mut self, name: &str, celsius: f32) {
+        self.0.entry(name.to_string()).or_default().update(celsius);
+    }
+    
+    pub fn record_at(&mut self, name: &str, timestamp: DateTime<Utc>, celsius: f64) {
+        self.0.entry(name.to_string()).or_default().record_at(timestamp, celsius);
+    }
+    
+    pub fn get(&self, name: &str) -> Option<&MetricValue> {
+        self.0.get(name)
+    }
+    
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &MetricValue)> {
+        self.0.iter()
+    }
+    
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut MetricValue)> {
+        self.0.iter_mut()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// Distribution of latencies in power-of-two microsecond buckets, the layout kernel
+// probes (e.g. a biolatency-style eBPF program) aggregate into. Averages over a poll
+// hide the occasional slow I/O that causes a stutter; the tail percentiles don't
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyHistogram {
+    pub buckets: Vec<u64>, // Bucket i counts latencies in [2^i, 2^(i+1)) µs
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, micros: u64) {
+        let slot = (u64::BITS - micros.max(1).leading_zeros() - 1) as usize;
+        if self.buckets.len() <= slot {
+            self.buckets.resize(slot + 1, 0);
+        }
+        self.buckets[slot] += 1;
+    }
+    
+    // Add counts read from a probe's log2 slot array since the last poll
+    pub fn add_slots(&mut self, slots: &[u64]) {
+        if self.buckets.len() < slots.len() {
+            self.buckets.resize(slots.len(), 0);
+        }
+        for (bucket, count) in self.buckets.iter_mut().zip(slots) {
+            *bucket += count;
+        }
+    }
+    
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+    
+    // Upper bound in milliseconds of the bucket holding the given quantile, e.g. 0.99
+    pub fn quantile_ms(&self, quantile: f64) -> Option<f64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let target = (quantile.clamp(0.0, 1.0) * count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (slot, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket;
+            if seen >= target {
+                return Some((1u64 << (slot + 1)) as f64 / 1000.0);
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CpuMetrics {
-    pub utilization: MetricValue<f32>,           // Percentage
-    pub clock_speed: MetricValue<u32>,           // MHz
-    pub core_voltage: MetricValue<f32>,          // Volts
-    pub power_consumption: MetricValue<f32>,     // Watts
-    pub package_temperature: MetricValue<f32>,   // Celsius
-    pub hotspot_temperature: MetricValue<f32>,   // Celsius
-    pub thermal_throttling: MetricValue<bool>,   // Active/Inactive
+    pub name: Option<String>,                    // Processor brand string
+    pub utilization: MetricValue,           // Percentage
+    pub clock_speed: MetricValue,           // MHz, average across cores
+    pub clock_speed_min: MetricValue,       // MHz, slowest core this poll
+    pub clock_speed_max: MetricValue,       // MHz, fastest core this poll
+    pub core_voltage: MetricValue,          // Volts
+    pub power_consumption: MetricValue,     // Watts
+    pub package_temperature: MetricValue,   // Celsius
+    pub hotspot_temperature: MetricValue,   // Celsius
+    pub thermal_throttling: EventMetric,
+    pub core_utilization: Vec<MetricValue>, // Percentage, one series per logical core
+    pub ccd_temperatures: Vec<MetricValue>, // Celsius, one series per CCD
+    pub topology: CpuTopology,                   // Sockets, CCDs/clusters and SMT siblings
+    pub temperature_sensors: TemperatureSensors,
+    pub dpc_time: MetricValue,              // Percentage of all CPU time spent in DPCs
+    pub interrupt_time: MetricValue,        // Percentage of all CPU time spent in ISRs
+    pub top_processes: Vec<ProcessUsage>,   // Busiest processes over the last poll
+    // Set while a tracing backend (ETW) records exact utilization; sysinfo's sampled
+    // values are skipped meanwhile
+    #[serde(skip)]
+    pub exact_utilization: bool,
 }
 
-#[derive(Debug, Clone, Default)]
+// CPU time one process used over the last poll, as a share of all cores
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessUsage {
+    pub pid: u32,
+    pub name: String,
+    pub percent: f64,
+}
+
+impl CpuMetrics {
+    // Record the average clock plus the slowest and fastest core, since any single
+    // core's frequency is misleading under boost. Idle cores reporting 0 are ignored
+    pub fn update_core_clocks(&mut self, core_mhz: &[u32]) {
+        let active: Vec<u32> = core_mhz.iter().copied().filter(|mhz| *mhz > 0).collect();
+        let (Some(min), Some(max)) = (active.iter().min(), active.iter().max()) else {
+            return;
+        };
+        let average = active.iter().map(|mhz| *mhz as u64).sum::<u64>() / active.len() as u64;
+        self.clock_speed.update(average as u32);
+        self.clock_speed_min.update(*min);
+        self.clock_speed_max.update(*max);
+    }
+    
+    // Per-core utilization averaged over each topology cluster, as one series per cluster
+    pub fn cluster_utilization(&self) -> Vec<MetricValue> {
+        let samples = self.core_utilization.iter().map(|core| core.history.len()).min().unwrap_or(0);
+        let mut clusters = vec![MetricValue::default(); self.topology.clusters.len()];
+        for back in (0..samples).rev() {
+            let timestamp = self.core_utilization[0].history[self.core_utilization[0].history.len() - 1 - back].0;
+            let per_cpu: Vec<f32> = self.core_utilization
+                .iter()
+                .map(|core| core.history[core.history.len() - 1 - back].1 as f32)
+                .collect();
+            for (cluster, value) in clusters.iter_mut().zip(self.topology.aggregate(&per_cpu)) {
+                if let Some(value) = value {
+                    cluster.record_at(timestamp, value);
+                }
+            }
+        }
+        clusters
+    }
+    
+    pub fn update_core_utilization(&mut self, values: &[f32]) {
+        update_per_unit(&mut self.core_utilization, values);
+    }
+    
+    pub fn update_ccd_temperatures(&mut self, values: &[f32]) {
+        update_per_unit(&mut self.ccd_temperatures, values);
+    }
+}
+
+// Record one sample per core/CCD, adding series as units are first seen
+fn update_per_unit(series: &mut Vec<MetricValue>, values: &[f32]) {
+    if series.len() < values.len() {
+        series.resize_with(values.len(), MetricValue::default);
+    }
+    for (unit, value) in series.iter_mut().zip(values) {
+        unit.update(*value);
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GpuMetrics {
-    pub utilization: MetricValue<f32>,           // Percentage
-    pub clock_speed: MetricValue<u32>,           // MHz
-    pub memory_utilization: MetricValue<u64>,    // MB
-    pub core_voltage: MetricValue<f32>,          // Volts
-    pub power_consumption: MetricValue<f32>,     // Watts
-    pub package_temperature: MetricValue<f32>,   // Celsius
-    pub hotspot_temperature: MetricValue<f32>,   // Celsius
-    pub thermal_throttling: MetricValue<bool>,   // Active/Inactive
+    pub name: Option<String>,                    // Device name reported by the driver
+    pub adapters: Vec<String>,                   // Every GPU the backend enumerated, in its order; the metrics are the first one's
+    pub utilization: MetricValue,           // Percentage
+    pub graphics_utilization: MetricValue,  // Percentage, 3D/graphics engine
+    pub compute_utilization: MetricValue,   // Percentage, compute engines (CUDA, OpenCL, ROCm)
+    pub copy_utilization: MetricValue,      // Percentage, copy/DMA engines
+    pub clock_speed: MetricValue,           // MHz, graphics (core) clock
+    pub memory_clock_speed: MetricValue,    // MHz
+    pub video_clock_speed: MetricValue,     // MHz, video encode/decode engine
+    pub memory_utilization: MetricValue,    // MB
+    pub memory_utilization_percent: MetricValue, // Percentage of total VRAM
+    pub memory_total_mb: Option<u64>,            // Total VRAM, queried once
+    pub memory_free_mb: MetricValue,        // MB, headroom left before allocations fail
+    pub process_memory: Vec<GpuProcessMemory>,   // VRAM per process, largest first
+    pub memory_bandwidth: MetricValue,      // Percentage of time the memory controller was busy
+    pub core_voltage: MetricValue,          // Volts
+    pub power_consumption: MetricValue,     // Watts
+    pub package_temperature: MetricValue,   // Celsius
+    pub hotspot_temperature: MetricValue,   // Celsius
+    pub thermal_throttling: EventMetric,
+    pub fan_speed: MetricValue,             // Percentage of maximum
+    pub fan_stop: EventMetric,              // Fans deliberately off at low load (zero-RPM mode)
+    pub driver_reset: EventMetric,          // Driver reset (TDR, amdgpu reset) until the backend reconnects
+    pub temperature_sensors: TemperatureSensors,
 }
 
-#[derive(Debug, Clone, Default)]
+// VRAM one process has allocated on the GPU
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuProcessMemory {
+    pub pid: u32,
+    pub name: String,
+    pub used_mb: u64,
+    pub compute: bool,    // Has a compute context (CUDA), as opposed to graphics only
+}
+
+impl GpuMetrics {
+    // A fan reading of zero is the card's fan-stop mode, not a failed sensor; a
+    // missing sensor records nothing, so the two stay distinguishable
+    pub fn update_fan_speed(&mut self, percent: u32) {
+        self.fan_speed.update(percent);
+        self.fan_stop.update(percent == 0);
+    }
+    
+    // Record VRAM usage, deriving the percentage once the total is known
+    pub fn update_memory_usage(&mut self, used_mb: u64) {
+        self.memory_utilization.update(used_mb);
+        if let Some(total_mb) = self.memory_total_mb.filter(|total| *total > 0) {
+            let percent = used_mb as f64 / total_mb as f64 * 100.0;
+            self.memory_utilization_percent.update(percent as f32);
+            self.memory_free_mb.update(total_mb.saturating_sub(used_mb));
+        }
+    }
+    
+    // The largest VRAM users, e.g. "python (pid 4242) 9800 MB, Xorg (pid 1200) 310 MB"
+    pub fn largest_memory_users(&self, limit: usize) -> Option<String> {
+        if self.process_memory.is_empty() {
+            return None;
+        }
+        let users: Vec<String> = self.process_memory
+            .iter()
+            .take(limit)
+            .map(|process| format!("{} (pid {}) {} MB", process.name, process.pid, process.used_mb))
+            .collect();
+        Some(users.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MemoryMetrics {
-    pub utilization_mb: MetricValue<u64>,        // MB
-    pub clock_speed: MetricValue<u32>,           // MHz
-    pub temperature: MetricValue<f32>,           // Celsius
+    pub utilization_mb: MetricValue,        // MB, used by applications
+    pub cached_mb: MetricValue,             // MB, reclaimable cache and buffers
+    pub free_mb: MetricValue,               // MB
+    pub swap_used_mb: MetricValue,          // MB
+    pub clock_speed: MetricValue,           // MHz
+    pub temperature: MetricValue,           // Celsius
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct StorageMetrics {
-    pub read_speed: MetricValue<f32>,              // MB/s
-    pub write_speed: MetricValue<f32>,             // MB/s
-    pub temperature: MetricValue<f32>,             // Celsius
+    pub read_speed: MetricValue,              // MB/s
+    pub write_speed: MetricValue,             // MB/s
+    pub busy_percent: MetricValue,            // Percentage of time servicing I/O
+    pub temperature: MetricValue,             // Celsius
+    pub temperature_sensors: TemperatureSensors,
+    pub io_latency: LatencyHistogram,         // Block I/O completion latency over the session
+    pub io_latency_p99: MetricValue,          // Milliseconds, 99th percentile over the last poll
+    pub process_io: Vec<ProcessIo>,           // Block I/O per process over the last poll, busiest first
+    pub disks: Vec<DiskIo>,                   // Per physical disk where the OS counts them, in the order first seen
+    pub endurance_used: MetricValue,          // Percentage of rated endurance, most worn drive
+    pub session_writes: MetricValue,          // GB written by the host this session, all drives
+    pub drives: Vec<DriveEndurance>,          // From SMART, in the order first seen
+    pub health: Vec<DriveHealth>,             // From SMART, as of the latest read
+    pub health_degraded: EventMetric,         // A drive failed its self-assessment or got worse since the last session
+    pub pools: Vec<StoragePool>,              // mdraid arrays and ZFS pools, as of the latest read
+    pub pool_degraded: EventMetric,           // Any pool lost redundancy or has device errors
 }
 
-#[derive(Debug, Clone, Default)]
+// Bytes one process sent to block devices over the last poll
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessIo {
+    pub name: String,                         // Command name, as the kernel knows it
+    pub mb_per_sec: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiskIo {
+    pub name: String,                         // e.g. "nvme0n1", "sda"
+    pub read_speed: MetricValue,              // MB/s
+    pub write_speed: MetricValue,             // MB/s
+    pub busy_percent: MetricValue,
+}
+
+// One disk's rates over the last poll
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskReading {
+    pub name: String,
+    pub read_speed: f64,                      // MB/s
+    pub write_speed: f64,                     // MB/s
+    pub busy_percent: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PoolKind {
+    Mdraid,
+    Zfs,
+}
+
+// A RAID array or ZFS pool with its member devices
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoragePool {
+    pub name: String,                         // e.g. "md0" or "tank"
+    pub kind: PoolKind,
+    pub state: String,                        // As the tool puts it, e.g. "active raid5" or "DEGRADED"
+    pub healthy: bool,
+    pub scan: Option<String>,                 // Scrub, resync or rebuild, running or last finished
+    pub scan_progress: Option<f64>,           // Percentage while one runs
+    pub devices: Vec<PoolDevice>,
+}
+
+// A member disk, or a ZFS vdev grouping them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolDevice {
+    pub name: String,
+    pub depth: usize,                         // Nesting under the pool, 0 for top-level vdevs
+    pub state: String,                        // e.g. "ONLINE", "FAULTED", "in_sync", "faulty"
+    pub read_errors: u64,
+    pub write_errors: u64,
+    pub checksum_errors: u64,
+}
+
+impl PoolDevice {
+    pub fn errors(&self) -> u64 {
+        self.read_errors + self.write_errors + self.checksum_errors
+    }
+}
+
+// SMART health of one drive, with what got worse since the previous session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DriveHealth {
+    pub device: String,
+    pub model: String,
+    pub serial: Option<String>,
+    pub passed: Option<bool>,                 // The drive's overall self-assessment
+    pub reallocated_sectors: Option<u64>,     // ATA attribute 5
+    pub media_errors: Option<u64>,            // NVMe unrecovered data integrity errors
+    pub power_on_hours: Option<u64>,
+    pub temperature: Option<f64>,             // Celsius
+    pub wear_percent: Option<f64>,            // Share of rated life used, from the drive's own estimate
+    pub critical_warning: Option<u64>,        // NVMe critical warning bits, 0 when none are set
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub degradations: Vec<String>,            // e.g. "reallocated sectors 0 → 8"
+}
+
+// NVMe critical warning bits, lowest first
+const NVME_CRITICAL_WARNINGS: [&str; 6] = [
+    "spare capacity below threshold",
+    "temperature outside limits",
+    "reliability degraded",
+    "read-only",
+    "volatile memory backup failed",
+    "persistent memory read-only",
+];
+
+impl DriveHealth {
+    // The same drive across sessions, even when it moves to another port
+    pub fn key(&self) -> String {
+        match &self.serial {
+            Some(serial) => format!("{} {}", self.model, serial),
+            None => format!("{} {}", self.model, self.device),
+        }
+    }
+    
+    pub fn is_healthy(&self) -> bool {
+        self.passed != Some(false) && self.degradations.is_empty() && self.critical_warnings().is_empty()
+    }
+    
+    pub fn critical_warnings(&self) -> Vec<&'static str> {
+        let bits = self.critical_warning.unwrap_or(0);
+        NVME_CRITICAL_WARNINGS
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| bits & (1 << bit) != 0)
+            .map(|(_, warning)| *warning)
+            .collect()
+    }
+    
+    // What got worse compared with a reading from an earlier session. Counters only
+    // ever grow, so any increase is a new defect
+    pub fn degradations_since(&self, previous: &DriveHealth) -> Vec<String> {
+        let mut degradations = Vec::new();
+        if previous.passed == Some(true) && self.passed == Some(false) {
+            degradations.push("self-assessment PASSED → FAILED".to_string());
+        }
+        let counters = [
+            ("reallocated sectors", previous.reallocated_sectors, self.reallocated_sectors),
+            ("media errors", previous.media_errors, self.media_errors),
+        ];
+        for (name, before, now) in counters {
+            if let (Some(before), Some(now)) = (before, now) {
+                if now > before {
+                    degradations.push(format!("{} {} → {}", name, before, now));
+                }
+            }
+        }
+        degradations
+    }
+}
+
+// A drive's host write counter as SMART reports it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriveWrites {
+    pub device: String,                       // e.g. "/dev/nvme0"
+    pub model: String,
+    pub host_writes_bytes: u64,               // Over the drive's life
+    pub wear_percent: Option<f64>,            // The drive's own estimate, NVMe "Percentage Used"
+}
+
+// Write endurance of one drive, against its rated TBW (terabytes written) when configured
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DriveEndurance {
+    pub device: String,
+    pub model: String,
+    pub lifetime_writes_bytes: u64,
+    pub session_start_bytes: u64,             // Lifetime writes when first read this session
+    pub rated_tbw: Option<f64>,
+    pub wear_percent: Option<f64>,
+}
+
+impl DriveEndurance {
+    // TBW ratings count decimal terabytes
+    pub fn lifetime_tb(&self) -> f64 {
+        self.lifetime_writes_bytes as f64 / 1e12
+    }
+    
+    pub fn session_gb(&self) -> f64 {
+        self.lifetime_writes_bytes.saturating_sub(self.session_start_bytes) as f64 / 1e9
+    }
+    
+    // Share of the rated TBW written, or the drive's own wear estimate without a rating
+    pub fn endurance_used(&self) -> Option<f64> {
+        match self.rated_tbw.filter(|tbw| *tbw > 0.0) {
+            Some(tbw) => Some(self.lifetime_tb() / tbw * 100.0),
+            None => self.wear_percent,
+        }
+    }
+}
+
+impl StorageMetrics {
+    // Record each drive's write counter, rated endurance looked up by model
+    pub fn update_health(&mut self, drives: Vec<DriveHealth>) {
+        if drives.is_empty() {
+            return;
+        }
+        self.health_degraded.update(drives.iter().any(|drive| !drive.is_healthy()));
+        self.health = drives;
+    }
+    
+    // Latency slots traced since the last poll, as LatencyHistogram buckets, and the
+    // I/O of each process over the same interval
+    pub fn update_io_trace(&mut self, slots: &[u64], processes: Vec<ProcessIo>) {
+        let interval = LatencyHistogram { buckets: slots.to_vec() };
+        if let Some(p99) = interval.quantile_ms(0.99) {
+            self.io_latency_p99.update(p99);
+        }
+        self.io_latency.add_slots(slots);
+        self.process_io = processes;
+    }
+    
+    // Record every disk read this poll, adding new ones, with throughput summed over
+    // them and the busiest disk's busy time as the totals
+    pub fn update_disks(&mut self, readings: &[DiskReading]) {
+        if readings.is_empty() {
+            return;
+        }
+        for reading in readings {
+            let position = self.disks.iter().position(|disk| disk.name == reading.name);
+            let index = position.unwrap_or_else(|| {
+                self.disks.push(DiskIo { name: reading.name.clone(), ..DiskIo::default() });
+                self.disks.len() - 1
+            });
+            let disk = &mut self.disks[index];
+            disk.read_speed.update(reading.read_speed);
+            disk.write_speed.update(reading.write_speed);
+            disk.busy_percent.update(reading.busy_percent);
+        }
+        self.read_speed.update(readings.iter().map(|reading| reading.read_speed).sum::<f64>());
+        self.write_speed.update(readings.iter().map(|reading| reading.write_speed).sum::<f64>());
+        self.busy_percent.update(readings.iter().map(|reading| reading.busy_percent).fold(0.0, f64::max));
+    }
+    
+    pub fn update_pools(&mut self, pools: Vec<StoragePool>) {
+        self.pool_degraded.update(pools.iter().any(|pool| !pool.healthy));
+        self.pools = pools;
+    }
+    
+    pub fn update_endurance(&mut self, readings: &[DriveWrites], rated_tbw: &BTreeMap<String, f64>) {
+        if readings.is_empty() {
+            return;
+        }
+        for reading in readings {
+            let position = self.drives.iter().position(|drive| drive.device == reading.device);
+            let index = position.unwrap_or_else(|| {
+                self.drives.push(DriveEndurance {
+                    device: reading.device.clone(),
+                    session_start_bytes: reading.host_writes_bytes,
+                    ..DriveEndurance::default()
+                });
+                self.drives.len() - 1
+            });
+            let drive = &mut self.drives[index];
+            drive.model = reading.model.clone();
+            drive.lifetime_writes_bytes = reading.host_writes_bytes;
+            drive.wear_percent = reading.wear_percent;
+            drive.rated_tbw = rated_tbw.get(&reading.model).copied();
+        }
+        let worn = self.drives.iter().filter_map(DriveEndurance::endurance_used).reduce(f64::max);
+        if let Some(percent) = worn {
+            self.endurance_used.update(percent);
+        }
+        self.session_writes.update(self.drives.iter().map(DriveEndurance::session_gb).sum::<f64>());
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkMetrics {
+    pub receive_speed: MetricValue,           // MB/s, all interfaces
+    pub transmit_speed: MetricValue,          // MB/s, all interfaces
+    pub packet_errors: MetricValue,           // Packets/s received or sent with errors, all interfaces
+    pub link_utilization: MetricValue,        // Percentage of link speed the busiest interface uses
+    pub interfaces: Vec<NetworkInterface>,    // In the order first seen; unplugged ones keep their history
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkInterface {
+    pub name: String,
+    pub receive_speed: MetricValue,           // MB/s
+    pub transmit_speed: MetricValue,          // MB/s
+    pub packet_errors: MetricValue,           // Packets/s with errors
+    pub link_speed_mbps: Option<u64>,         // Negotiated link speed, where the OS reports it
+}
+
+// One interface's rates over the last poll
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceReading {
+    pub name: String,
+    pub receive_speed: f64,                   // MB/s
+    pub transmit_speed: f64,                  // MB/s
+    pub packet_errors: f64,                   // Packets/s
+    pub link_speed_mbps: Option<u64>,
+}
+
+impl NetworkInterface {
+    // The busier direction as a share of the link, since links are full duplex
+    pub fn link_utilization(&self) -> Option<f64> {
+        let link_mbps = self.link_speed_mbps.filter(|speed| *speed > 0)? as f64;
+        let busiest = self.receive_speed.current?.max(self.transmit_speed.current?);
+        Some(busiest * 1024.0 * 1024.0 * 8.0 / 1_000_000.0 / link_mbps * 100.0)
+    }
+}
+
+impl NetworkMetrics {
+    // Record every interface read this poll, adding new ones, and the totals over them.
+    // Interfaces missing from the poll aren't counted
+    pub fn update_interfaces(&mut self, readings: &[InterfaceReading]) {
+        if readings.is_empty() {
+            return;
+        }
+        let mut utilization: Option<f64> = None;
+        for reading in readings {
+            let position = self.interfaces.iter().position(|interface| interface.name == reading.name);
+            let index = position.unwrap_or_else(|| {
+                self.interfaces.push(NetworkInterface { name: reading.name.clone(), ..NetworkInterface::default() });
+                self.interfaces.len() - 1
+            });
+            let interface = &mut self.interfaces[index];
+            interface.receive_speed.update(reading.receive_speed);
+            interface.transmit_speed.update(reading.transmit_speed);
+            interface.packet_errors.update(reading.packet_errors);
+            interface.link_speed_mbps = reading.link_speed_mbps;
+            if let Some(percent) = interface.link_utilization() {
+                utilization = Some(utilization.map_or(percent, |busiest| busiest.max(percent)));
+            }
+        }
+        self.receive_speed.update(readings.iter().map(|reading| reading.receive_speed).sum::<f64>());
+        self.transmit_speed.update(readings.iter().map(|reading| reading.transmit_speed).sum::<f64>());
+        self.packet_errors.update(readings.iter().map(|reading| reading.packet_errors).sum::<f64>());
+        if let Some(percent) = utilization {
+            self.link_utilization.update(percent);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerMetrics {
+    pub battery_discharge: MetricValue,       // Watts drawn from the battery
+    pub charging_power: MetricValue,          // Watts the charger puts into the battery
+    pub display_brightness: MetricValue,      // Percent of the panel's maximum
+    pub on_battery: EventMetric,              // Unplugged from the charger
+}
+
+// Laptop battery state; the power it delivers or takes is in PowerMetrics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatteryMetrics {
+    pub charge_percent: MetricValue,          // Percentage of the current full capacity
+    pub voltage: MetricValue,                 // Volts
+    pub time_remaining: MetricValue,          // Minutes until empty, or until full while charging
+    pub status: Option<BatteryStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BatteryStatus {
+    Charging,
+    Discharging,
+    Full,
+    NotCharging, // Plugged in but held below full, e.g. by a charge limit
+}
+
+impl BatteryStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BatteryStatus::Charging => "Charging",
+            BatteryStatus::Discharging => "Discharging",
+            BatteryStatus::Full => "Full",
+            BatteryStatus::NotCharging => "Not charging",
+        }
+    }
+}
+
+// System clock against NTP time, as the local time daemon last measured it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClockMetrics {
+    pub ntp_offset: MetricValue,              // Milliseconds the system clock is ahead of NTP time, negative when behind
+    pub ntp_source: Option<String>,           // Daemon and server, e.g. "chrony, 162.159.200.123"
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MotherboardMetrics {
-    pub chipset_temperature: MetricValue<f32>,     // Celsius
-    pub chassis_temperature: MetricValue<f32>,     // Celsius
-    pub aio_pump_speed: MetricValue<u32>,          // RPM
-    pub chassis_fan_speed: MetricValue<u32>,       // RPM
-    pub chipset_fan_speed: MetricValue<u32>,       // RPM
+    pub chipset_temperature: MetricValue,     // Celsius
+    pub chassis_temperature: MetricValue,     // Celsius
+    pub aio_pump_speed: MetricValue,          // RPM
+    pub chassis_fan_speed: MetricValue,       // RPM
+    pub chipset_fan_speed: MetricValue,       // RPM
+    pub battery_temperature: MetricValue,     // Celsius, laptops only
+    pub vrm_temperature: MetricValue,         // Celsius
+    pub temperature_sensors: TemperatureSensors,
+    pub vcore: MetricValue,                   // Volts, CPU core supply
+    pub rail_12v: MetricValue,                // Volts
+    pub rail_5v: MetricValue,                 // Volts
+    pub rail_3v3: MetricValue,                // Volts
+    pub rail_deviation: MetricValue,          // Percent, the 12 V, 5 V or 3.3 V rail furthest from nominal
+    pub chassis_intrusion: EventMetric,       // The case was opened; latched by the chip until cleared
+}
+
+// Condition a user script raised on the last poll
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptAlert {
+    pub script: String, // File name
+    pub message: String,
+}
+
+// Latest results of a query expression (see query.rs), recorded every poll
+#[derive(Debug, Clone, Default)]
+pub struct QuerySeries {
+    pub values: MetricValue,
+    pub error: Option<String>, // Why the last evaluation failed; current is None meanwhile
+}
+
+// What the user scripts (see scripting.rs) produced, refreshed every poll
+#[derive(Debug, Clone, Default)]
+pub struct ScriptOutputs {
+    pub loaded: Vec<String>, // Script file names
+    pub metrics: BTreeMap<String, MetricValue>, // Derived metrics, by the name the script gave them
+    pub alerts: Vec<ScriptAlert>,
+    pub reports: Vec<(String, String)>, // (script, text) report sections
+    pub errors: Vec<String>, // Load and run errors, one per script at most
 }
 
 #[derive(Debug, Clone)]
@@ -125,33 +950,250 @@ pub struct AppState {
     pub gpu: GpuMetrics,
     pub memory: MemoryMetrics,
     pub storage: StorageMetrics,
+    pub network: NetworkMetrics,
+    pub battery: BatteryMetrics,
+    pub power: PowerMetrics,
     pub motherboard: MotherboardMetrics,
+    pub clock: ClockMetrics,
     pub polling_interval_ms: u64,
     pub session_start: DateTime<Utc>,
-    pub ui_state: UiState,
+    pub alerts: AlertHistory,
+    pub poller: PollerStatus,
+    pub metric_sources: HashMap<String, MetricSource>, // Keyed by metric id
+    pub history_spill: Option<HistorySpill>, // Created the first time the budget is exceeded
+    spilled_energy: HashMap<String, EnergyTotal>, // Integrated over each series' spilled samples, by key
+    pub scripts: ScriptOutputs,
+    pub queries: BTreeMap<String, QuerySeries>, // Keyed by expression text
+    pending_source_details: HashMap<String, String>,
+    samples: broadcast::Sender<Sample>,
+    subscriptions: Subscriptions,
 }
 
-#[derive(Debug, Clone)]
-pub struct UiState {
-    pub cpu_section_expanded: bool,
-    pub gpu_section_expanded: bool,
-    pub memory_section_expanded: bool,
-    pub storage_section_expanded: bool,
-    pub motherboard_section_expanded: bool,
+// One new reading, as published to subscribers
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub metric_id: &'static str, // Descriptor id, e.g. "cpu.utilization"
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,              // Events read as 1.0 while active
 }
 
-impl Default for UiState {
-    fn default() -> Self {
-        Self {
-            cpu_section_expanded: true,    // Default expanded
-            gpu_section_expanded: true,    // Default expanded
-            memory_section_expanded: true, // Default expanded
-            storage_section_expanded: true, // Default expanded
-            motherboard_section_expanded: true, // Default expanded
+// Samples a subscriber may fall behind by before it starts missing them
+pub const SAMPLE_CHANNEL_CAPACITY: usize = 4096;
+
+type UpdateCallback = Box<dyn FnMut(&Sample) + Send + Sync>;
+
+// Per-metric callbacks registered with AppState::on_update. Shared between clones of
+// the state, since they belong to whoever registered them rather than to the data
+#[derive(Clone, Default)]
+struct Subscriptions(Arc<parking_lot::Mutex<Vec<(String, UpdateCallback)>>>);
+
+impl std::fmt::Debug for Subscriptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Subscriptions({})", self.0.lock().len())
+    }
+}
+
+// Bumped when reading older recordings takes more than defaulting a missing field;
+// `StateRecording::upgrade` migrates each earlier version. Version 2 stores on/off
+// metrics as events; version 1 series are replayed into events while parsing
+pub const RECORDING_VERSION: u32 = 2;
+
+// Serializable copy of every metric series: what exporters, recordings and remote
+// agents exchange. UI, alert and poller state stay behind
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateRecording {
+    pub version: u32, // 0 for recordings written before versioning
+    pub session_start: DateTime<Utc>,
+    pub polling_interval_ms: u64,
+    pub cpu: CpuMetrics,
+    pub gpu: GpuMetrics,
+    pub memory: MemoryMetrics,
+    pub storage: StorageMetrics,
+    pub network: NetworkMetrics,
+    pub battery: BatteryMetrics,
+    pub power: PowerMetrics,
+    pub motherboard: MotherboardMetrics,
+    pub clock: ClockMetrics,
+}
+
+impl StateRecording {
+    pub fn upgrade(mut self) -> Result<Self, Box<dyn std::error::Error>> {
+        if self.version > RECORDING_VERSION {
+            return Err(format!(
+                "recording version {} is newer than this build supports ({})",
+                self.version, RECORDING_VERSION
+            )
+            .into());
+        }
+        // Version 0 only lacked the header, and version 1 flag series are converted while
+        // parsing; every other field still means the same
+        self.version = RECORDING_VERSION;
+        Ok(self)
+    }
+}
+
+// Window the "recent" figures in a `StateSnapshot` cover
+pub const RECENT_STATS_SECONDS: i64 = 60;
+
+// Approximate memory one history sample takes, for the history budget
+pub const HISTORY_SAMPLE_BYTES: usize = std::mem::size_of::<(DateTime<Utc>, f64)>();
+
+// Series key and the samples leaving memory
+type SpilledSeries = (String, Vec<(DateTime<Utc>, f64)>);
+
+// Oldest samples on their way to the HistorySpill: taken under the state lock, written
+// without it, then dropped from memory by AppState::finish_spill
+#[derive(Debug)]
+pub struct SpillBatch {
+    spill: Option<HistorySpill>, // Created on the first write if the session has none yet
+    session_start: DateTime<Utc>,
+    series: Vec<SpilledSeries>,
+    written: usize, // Leading series safely on disk
+}
+
+impl SpillBatch {
+    // Stops at the first failure; the series before it are still dropped from memory
+    pub fn write(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let spill = match &self.spill {
+            Some(spill) => spill.clone(),
+            None => {
+                let spill = HistorySpill::for_session(self.session_start)?;
+                logger::log_info(&format!("Spilling older history to {}", spill.dir().display()));
+                self.spill = Some(spill.clone());
+                spill
+            }
+        };
+        for (key, samples) in &self.series[self.written..] {
+            spill.append(key, samples)?;
+            self.written += 1;
+        }
+        Ok(())
+    }
+}
+
+// Current values and summary stats without any history: cheap enough to take under
+// the read lock on every export, API request or clipboard copy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub session_start: DateTime<Utc>,
+    pub metrics: Vec<MetricSnapshot>, // METRIC_DESCRIPTORS order, metrics with data only
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricSnapshot {
+    pub id: String,
+    pub label: String,
+    pub unit: String,
+    pub current: Option<f64>,
+    pub session_min: Option<f64>,
+    pub session_max: Option<f64>,
+    pub recent: Option<RecentStats>, // None when nothing arrived within the window
+    pub samples: usize,
+}
+
+impl StateSnapshot {
+    pub fn get(&self, id: &str) -> Option<&MetricSnapshot> {
+        self.metrics.iter().find(|metric| metric.id == id)
+    }
+    
+    // One "Label: value (min .. max)" line per metric, for pasting into bug reports
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Simple Performance Dashboard at {}\n", self.taken_at.format("%Y-%m-%d %H:%M:%S UTC"));
+        for metric in &self.metrics {
+            let descriptor = MetricDescriptor::find(&metric.id).map(MetricDescriptor::displayed);
+            let format = |value: Option<f64>| match (value, &descriptor) {
+                (Some(value), Some(descriptor)) => descriptor.format_value(value),
+                (Some(value), None) => format!("{}{}", value, metric.unit),
+                (None, _) => "N/A".to_string(),
+            };
+            text.push_str(&format!(
+                "{}: {} (min {}, max {})\n",
+                metric.label,
+                format(metric.current),
+                format(metric.session_min),
+                format(metric.session_max)
+            ));
         }
+        text
     }
 }
 
+// Which monitor produced a metric's latest value
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSource {
+    pub monitor: String,
+    pub detail: Option<String>, // e.g. the sysinfo sensor label a temperature was matched from
+}
+
+// Health of the polling thread, surfaced in the UI when something goes wrong
+#[derive(Debug, Clone, Default)]
+pub struct PollerStatus {
+    pub last_poll: Option<DateTime<Utc>>,
+    pub cycle_duration: MetricValue, // ms each polling cycle spent collecting
+    pub jitter: MetricValue,         // ms each cycle started off its scheduled spacing
+    last_cycle_start: Option<DateTime<Utc>>,
+    pub burst_until: Option<DateTime<Utc>>, // Set while burst sampling is polling faster
+    pub restarts: u32,                  // Polling or alert tasks restarted after failing
+    pub disabled_monitors: Vec<String>, // Monitors that panicked, hung or hit a fatal error
+    pub last_error: Option<String>,
+    pub samples_published: u64, // Every sample published this session, for the status bar's rate
+    pub monitor_timings: Vec<MonitorTiming>,
+    pub hardware_summary: String,
+    pub hardware_fingerprint: String, // HardwareInfo::fingerprint, to find comparable sessions
+    pub versions: SoftwareVersions,
+    pub monitor_selection: Vec<MonitorSelection>,
+}
+
+// Whether a monitor was registered for this machine, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorSelection {
+    pub name: String,
+    pub selected: bool,
+    pub reason: String,
+}
+
+impl PollerStatus {
+    // Record one polling cycle, so whether the configured rate is actually achieved
+    // can be plotted and exported like any other metric
+    pub fn record_cycle(&mut self, started: DateTime<Utc>, duration_ms: f64, interval_ms: u64) {
+        if let Some(previous) = self.last_cycle_start {
+            let spacing_ms = (started - previous).num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0;
+            self.jitter.record_at(started, (spacing_ms - interval_ms as f64).abs());
+        }
+        self.last_cycle_start = Some(started);
+        self.cycle_duration.record_at(started, duration_ms);
+    }
+    
+    // Monitors selected for this machine that haven't since been disabled
+    pub fn active_monitors(&self) -> usize {
+        self.monitor_selection
+            .iter()
+            .filter(|selection| selection.selected && !self.disabled_monitors.contains(&selection.name))
+            .count()
+    }
+    
+    // Replace the timing entry for a monitor, adding it on first report
+    pub fn record_timing(&mut self, timing: MonitorTiming) {
+        match self.monitor_timings.iter_mut().find(|existing| existing.name == timing.name) {
+            Some(existing) => *existing = timing,
+            None => self.monitor_timings.push(timing),
+        }
+    }
+}
+
+// How long a monitor's last update took and how often it is currently polled
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonitorTiming {
+    pub name: String,
+    pub last_duration_ms: f64,
+    pub slow_polls: u32,
+    pub cadence: u32, // Polled every N cycles; above 1 when backed off
+    pub error: Option<String>, // Last failure reported by the monitor
+    pub hint: Option<&'static str>, // Suggested fix for that failure
+}
+
 impl Default for AppState {
     fn default() -> Self {
         Self {
@@ -159,10 +1201,23 @@ impl Default for AppState {
             gpu: GpuMetrics::default(),
             memory: MemoryMetrics::default(),
             storage: StorageMetrics::default(),
+            network: NetworkMetrics::default(),
+            battery: BatteryMetrics::default(),
+            power: PowerMetrics::default(),
             motherboard: MotherboardMetrics::default(),
+            clock: ClockMetrics::default(),
             polling_interval_ms: 1000,
             session_start: Utc::now(),
-            ui_state: UiState::default(),
+            alerts: AlertHistory::default(),
+            poller: PollerStatus::default(),
+            metric_sources: HashMap::new(),
+            history_spill: None,
+            spilled_energy: HashMap::new(),
+            scripts: ScriptOutputs::default(),
+            queries: BTreeMap::new(),
+            pending_source_details: HashMap::new(),
+            samples: broadcast::channel(SAMPLE_CHANNEL_CAPACITY).0,
+            subscriptions: Subscriptions::default(),
         }
     }
 }
@@ -182,76 +1237,933 @@ impl AppState {
         Arc::new(RwLock::new(Self::new(polling_interval_ms)))
     }
     
-    pub fn has_cpu_data(&self) -> bool {
-        self.cpu.utilization.current.is_some() || 
-        self.cpu.clock_speed.current.is_some() || 
-        self.cpu.package_temperature.current.is_some()
+    pub fn snapshot(&self) -> StateSnapshot {
+        let taken_at = Utc::now();
+        let since = taken_at - chrono::Duration::seconds(RECENT_STATS_SECONDS);
+        let metrics = METRIC_DESCRIPTORS
+            .iter()
+            .filter_map(|descriptor| {
+                let descriptor = descriptor.displayed();
+                let mut snapshot = MetricSnapshot {
+                    id: descriptor.id.to_string(),
+                    label: descriptor.label.to_string(),
+                    unit: descriptor.unit.to_string(),
+                    current: self.current_value(descriptor.id),
+                    session_min: None,
+                    session_max: None,
+                    recent: None,
+                    samples: 0,
+                };
+                snapshot.current?;
+                if let Some(metric) = self.metric(descriptor.id) {
+                    snapshot.session_min = metric.session_min;
+                    snapshot.session_max = metric.session_max;
+                    snapshot.recent = metric.recent_stats(since);
+                    snapshot.samples = metric.history.len() + metric.spilled;
+                } else if let Some(event) = self.event(descriptor.id) {
+                    snapshot.samples = event.changes.len();
+                }
+                Some(snapshot)
+            })
+            .collect();
+        StateSnapshot { taken_at, session_start: self.session_start, metrics }
+    }
+    
+    // Full copy including histories, for saving and restoring a session. Spilled
+    // samples are read back, so the copy holds the whole session
+    pub fn recording(&self) -> StateRecording {
+        let mut full = AppState {
+            cpu: self.cpu.clone(),
+            gpu: self.gpu.clone(),
+            memory: self.memory.clone(),
+            storage: self.storage.clone(),
+            network: self.network.clone(),
+            battery: self.battery.clone(),
+            power: self.power.clone(),
+            motherboard: self.motherboard.clone(),
+            clock: self.clock.clone(),
+            ..Default::default()
+        };
+        full.for_each_series_mut(&mut |key, series| {
+            if series.spilled > 0 {
+                let mut history: VecDeque<_> = self.spilled_samples(key).into();
+                history.append(&mut series.history);
+                series.history = history;
+                series.spilled = 0;
+            }
+        });
+        StateRecording {
+            version: RECORDING_VERSION,
+            session_start: self.session_start,
+            polling_interval_ms: self.polling_interval_ms,
+            cpu: full.cpu,
+            gpu: full.gpu,
+            memory: full.memory,
+            storage: full.storage,
+            network: full.network,
+            battery: full.battery,
+            power: full.power,
+            motherboard: full.motherboard,
+            clock: full.clock,
+        }
+    }
+    
+    pub fn from_recording(recording: StateRecording) -> Result<Self, Box<dyn std::error::Error>> {
+        let recording = recording.upgrade()?;
+        Ok(Self {
+            session_start: recording.session_start,
+            cpu: recording.cpu,
+            gpu: recording.gpu,
+            memory: recording.memory,
+            storage: recording.storage,
+            network: recording.network,
+            battery: recording.battery,
+            power: recording.power,
+            motherboard: recording.motherboard,
+            clock: recording.clock,
+            ..Self::new(recording.polling_interval_ms)
+        })
+    }
+    
+    // Devices with data, their metrics grouped by id prefix so new descriptors appear
+    // under their device without UI changes
+    pub fn device_tree(&self) -> DeviceNode {
+        let metrics = |prefix: &str| -> Vec<&'static str> {
+            METRIC_DESCRIPTORS
+                .iter()
+                .filter(|descriptor| descriptor.id.split('.').next() == Some(prefix))
+                .filter(|descriptor| match self.metric(descriptor.id) {
+                    Some(metric) => metric.current.is_some(),
+                    None => self.event(descriptor.id).is_some_and(|event| event.active.is_some()),
+                })
+                .map(|descriptor| descriptor.id)
+                .collect()
+        };
+        let named = |base: &str, name: Option<&str>| match name {
+            Some(name) => format!("{} — {}", base, name),
+            None => base.to_string(),
+        };
+        let device = |id: &str, name: String, kind: DeviceKind, prefix: &str| DeviceNode {
+            metrics: metrics(prefix),
+            ..DeviceNode::new(id.to_string(), name, kind)
+        };
+        
+        let mut cpu = device("system/cpu0", named("CPU0", self.cpu.name.as_deref()), DeviceKind::Cpu, "cpu");
+        if !self.cpu.core_utilization.is_empty() {
+            let name = format!("Cores ({})", self.cpu.core_utilization.len());
+            cpu.children.push(DeviceNode::new("system/cpu0/cores".to_string(), name, DeviceKind::Cores));
+        }
+        if !self.cpu.ccd_temperatures.is_empty() {
+            let name = format!("CCDs ({})", self.cpu.ccd_temperatures.len());
+            cpu.children.push(DeviceNode::new("system/cpu0/ccds".to_string(), name, DeviceKind::Ccds));
+        }
+        
+        // One node per enumerated GPU, with the metrics on the monitored first one; a
+        // backend that doesn't enumerate gets one node once it reports data
+        let mut gpus = DeviceNode::new("system/gpus".to_string(), "GPUs".to_string(), DeviceKind::Group);
+        let gpu0_name = self.gpu.name.as_deref().or(self.gpu.adapters.first().map(String::as_str));
+        let gpu0 = device("system/gpus/gpu0", named("GPU0", gpu0_name), DeviceKind::Gpu, "gpu");
+        if !gpu0.metrics.is_empty() || !self.gpu.adapters.is_empty() {
+            gpus.children.push(gpu0);
+        }
+        for (index, adapter) in self.gpu.adapters.iter().enumerate().skip(1) {
+            let id = format!("system/gpus/gpu{}", index);
+            gpus.children.push(DeviceNode::new(id, named(&format!("GPU{}", index), Some(adapter)), DeviceKind::Gpu));
+        }
+        // Storage counters are summed over all physical disks, with a node per disk
+        // where the OS counts them separately
+        let mut drives = device("system/drives", "Drives".to_string(), DeviceKind::Drive, "storage");
+        for disk in &self.storage.disks {
+            drives.children.push(DeviceNode::new(format!("system/drives/{}", disk.name), disk.name.clone(), DeviceKind::Disk));
+        }
+        
+        let mut system = DeviceNode::new("system".to_string(), "System".to_string(), DeviceKind::Group);
+        system.children = vec![
+            cpu,
+            gpus,
+            device("system/memory", "Memory".to_string(), DeviceKind::Memory, "memory"),
+            drives,
+            device("system/network", "Network".to_string(), DeviceKind::Network, "network"),
+            device("system/battery", "Battery".to_string(), DeviceKind::Battery, "battery"),
+            device("system/power", "Power".to_string(), DeviceKind::Power, "power"),
+            device("system/motherboard", "Motherboard".to_string(), DeviceKind::Motherboard, "motherboard"),
+            device("system/clock", "Clock".to_string(), DeviceKind::Clock, "clock"),
+        ];
+        for group in &mut system.children {
+            group.children.retain(|child| !child.is_empty());
+        }
+        system.children.retain(|child| !child.is_empty());
+        system
+    }
+    
+    pub fn has_cpu_data(&self) -> bool {
+        self.cpu.utilization.current.is_some() || 
+        self.cpu.clock_speed.current.is_some() || 
+        self.cpu.package_temperature.current.is_some()
+    }
+    
+    pub fn has_gpu_data(&self) -> bool {
+        self.gpu.clock_speed.current.is_some() || 
+        self.gpu.package_temperature.current.is_some()
+    }
+    
+    pub fn has_memory_data(&self) -> bool {
+        self.memory.utilization_mb.current.is_some() ||
+        self.memory.clock_speed.current.is_some() ||
+        self.memory.temperature.current.is_some()
+    }
+    
+    pub fn has_storage_data(&self) -> bool {
+        self.storage.read_speed.current.is_some() ||
+        self.storage.write_speed.current.is_some() ||
+        self.storage.busy_percent.current.is_some() ||
+        self.storage.temperature.current.is_some()
+    }
+    
+    pub fn has_network_data(&self) -> bool {
+        self.network.receive_speed.current.is_some() ||
+        self.network.transmit_speed.current.is_some()
+    }
+    
+    // Energy a power metric has used this session, or None for unknown ids. Spilled
+    // samples were integrated as they left memory, so this never reads the disk
+    pub fn session_energy(&self, id: &str) -> Option<EnergyTotal> {
+        let samples = self.samples(id)?;
+        let mut total = integrate_energy(&samples, std::time::Duration::from_millis(self.polling_interval_ms));
+        if let Some(spilled) = self.spilled_energy.get(id) {
+            total.add(spilled);
+        }
+        Some(total)
+    }
+    
+    pub fn has_battery_data(&self) -> bool {
+        self.battery.charge_percent.current.is_some() ||
+        self.battery.voltage.current.is_some()
+    }
+    
+    pub fn has_power_data(&self) -> bool {
+        self.power.battery_discharge.current.is_some() ||
+        self.power.charging_power.current.is_some() ||
+        self.power.display_brightness.current.is_some() ||
+        self.power.on_battery.active.is_some()
+    }
+    
+    pub fn has_motherboard_data(&self) -> bool {
+        self.motherboard.chipset_temperature.current.is_some() || 
+        self.motherboard.chassis_temperature.current.is_some() ||
+        self.motherboard.aio_pump_speed.current.is_some() ||
+        self.motherboard.chassis_fan_speed.current.is_some() ||
+        self.motherboard.chipset_fan_speed.current.is_some() ||
+        self.motherboard.battery_temperature.current.is_some() ||
+        self.motherboard.vrm_temperature.current.is_some() ||
+        self.motherboard.vcore.current.is_some() ||
+        self.motherboard.rail_12v.current.is_some() ||
+        self.motherboard.chassis_intrusion.active.is_some() ||
+        !self.motherboard.temperature_sensors.is_empty()
+    }
+}
+
+// Plot x coordinate of a sample, kept to the millisecond so sub-second polls don't
+// collapse onto whole seconds
+pub fn elapsed_seconds(session_start: DateTime<Utc>, timestamp: DateTime<Utc>) -> f64 {
+    (timestamp - session_start).num_milliseconds() as f64 / 1000.0
+}
+
+// Average consecutive samples into at most `max_points` plot points, each placed at
+// its first sample's elapsed seconds
+pub fn downsample(samples: &[(DateTime<Utc>, f64)], session_start: DateTime<Utc>, max_points: usize) -> Vec<(f64, f64)> {
+    let bucket = samples.len().div_ceil(max_points.max(1)).max(1);
+    samples
+        .chunks(bucket)
+        .map(|chunk| {
+            (elapsed_seconds(session_start, chunk[0].0), chunk.iter().map(|(_, value)| value).sum::<f64>() / chunk.len() as f64)
+        })
+        .collect()
+}
+
+// Native sample types monitors record, converted to the stored f64
+pub trait ToF64 {
+    fn to_f64(&self) -> f64;
+}
+
+impl ToF64 for f64 {
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+}
+
+impl ToF64 for f32 {
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl ToF64 for u32 {
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl ToF64 for u64 {
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl ToF64 for bool {
+    fn to_f64(&self) -> f64 {
+        if *self { 1.0 } else { 0.0 }
+    }
+}
+
+// Power metrics whose session energy is worth totalling
+pub const ENERGY_METRICS: [&str; 3] = ["cpu.power_consumption", "gpu.power_consumption", "power.battery_discharge"];
+
+// An interval only counts as a gap (suspend, hibernate, a stalled poller) when it is this
+// many times longer than the polling around it, and at least ENERGY_GAP_MIN_SECONDS
+pub const ENERGY_GAP_FACTOR: f64 = 5.0;
+pub const ENERGY_GAP_MIN_SECONDS: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EnergyTotal {
+    pub watt_hours: f64,
+    pub measured_seconds: f64,
+    pub skipped_seconds: f64, // Time inside gaps, which is not integrated
+}
+
+impl EnergyTotal {
+    pub fn add(&mut self, other: &EnergyTotal) {
+        self.watt_hours += other.watt_hours;
+        self.measured_seconds += other.measured_seconds;
+        self.skipped_seconds += other.skipped_seconds;
+    }
+}
+
+// Integrate watts over the actual sample timestamps (trapezoids), so samples taken at a
+// changed polling interval weigh by the time they cover rather than by count. Gaps are
+// judged against the neighbouring intervals and the nominal polling interval, so both a
+// slower polling rate and a suspend in the middle of fast polling are handled
+pub fn integrate_energy(samples: &[(DateTime<Utc>, f64)], nominal_interval: std::time::Duration) -> EnergyTotal {
+    let intervals: Vec<f64> = samples
+        .windows(2)
+        .map(|pair| (pair[1].0 - pair[0].0).num_milliseconds() as f64 / 1000.0)
+        .collect();
+    let nominal = nominal_interval.as_secs_f64();
+    
+    let mut total = EnergyTotal::default();
+    for (index, seconds) in intervals.iter().enumerate() {
+        if *seconds <= 0.0 {
+            continue;
+        }
+        let previous = index.checked_sub(1).map_or(0.0, |previous| intervals[previous]);
+        let next = intervals.get(index + 1).copied().unwrap_or(0.0);
+        let reference = previous.max(next).max(nominal);
+        if *seconds > (reference * ENERGY_GAP_FACTOR).max(ENERGY_GAP_MIN_SECONDS) {
+            total.skipped_seconds += seconds;
+            continue;
+        }
+        let watts = (samples[index].1 + samples[index + 1].1) / 2.0;
+        total.watt_hours += watts * seconds / 3600.0;
+        total.measured_seconds += seconds;
+    }
+    total
+}
+
+// How a metric's stored f64 values are shown
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricKind {
+    Gauge { decimals: usize }, // Measured quantity, shown with a fixed precision
+    Event,                     // On/off state, stored as an EventMetric
+}
+
+impl MetricKind {
+    // Precision for axis labels and statistics of the raw values
+    pub fn decimals(&self) -> usize {
+        match self {
+            MetricKind::Gauge { decimals } => *decimals,
+            MetricKind::Event => 0,
+        }
+    }
+}
+
+// What a device-tree node shows besides its metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind {
+    Group, // Only holds other devices, e.g. "GPUs"
+    Cpu,
+    Cores, // Per-core utilization of the parent CPU
+    Ccds,  // Per-CCD temperatures of the parent CPU
+    Gpu,
+    Memory,
+    Drive, // All drives together; health, pools and endurance
+    Disk,  // I/O of one physical disk, a child of Drive
+    Network,
+    Battery,
+    Power,
+    Motherboard,
+    Clock,
+}
+
+// One node of the device tree the dashboard is organized around, e.g. System → CPU0 → Cores
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceNode {
+    pub id: String, // Path from the root, e.g. "system/cpu0/cores"; stable across sessions
+    pub name: String,
+    pub kind: DeviceKind,
+    pub metrics: Vec<&'static str>, // Descriptor ids with data, in registry order
+    pub children: Vec<DeviceNode>,
+}
+
+impl DeviceNode {
+    fn new(id: String, name: String, kind: DeviceKind) -> Self {
+        Self { id, name, kind, metrics: Vec::new(), children: Vec::new() }
+    }
+    
+    // Nodes are dropped when there is nothing to show under them
+    fn is_empty(&self) -> bool {
+        self.metrics.is_empty() && self.children.is_empty() && !matches!(self.kind, DeviceKind::Cores | DeviceKind::Ccds | DeviceKind::Gpu | DeviceKind::Disk)
+    }
+}
+
+// Static description of a metric: stable id, display label, unit and kind
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetricDescriptor {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub unit: &'static str,
+    pub kind: MetricKind,
+}
+
+impl MetricDescriptor {
+    const fn new(id: &'static str, label: &'static str, unit: &'static str, decimals: usize) -> Self {
+        Self { id, label, unit, kind: MetricKind::Gauge { decimals } }
+    }
+    
+    const fn event(id: &'static str, label: &'static str) -> Self {
+        Self { id, label, unit: "", kind: MetricKind::Event }
+    }
+    
+    // Collection timing the poller records itself, rather than a monitor
+    pub fn is_poller_metric(&self) -> bool {
+        self.id.starts_with("poller.")
+    }
+    
+    pub fn find(id: &str) -> Option<&'static MetricDescriptor> {
+        METRIC_DESCRIPTORS.iter().find(|descriptor| descriptor.id == id)
+    }
+    
+    // This metric as shown, with the user's display overrides applied
+    pub fn displayed(&self) -> DisplayedMetric {
+        DISPLAY_OVERRIDES.read().apply(self)
+    }
+    
+    pub fn format_value(&self, value: f64) -> String {
+        format_value(self.kind, self.unit, value)
+    }
+}
+
+fn format_value(kind: MetricKind, unit: &str, value: f64) -> String {
+    match kind {
+        MetricKind::Gauge { decimals } => format!("{:.*}{}", decimals, value, unit),
+        MetricKind::Event if value >= 0.5 => "Active".to_string(),
+        MetricKind::Event => "Inactive".to_string(),
+    }
+}
+
+// A metric's label, unit and precision as the UI, alerts and reports show them.
+// Resolved when shown rather than stored, so it can borrow nothing from the overrides
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayedMetric {
+    pub id: &'static str,
+    pub label: String,
+    pub unit: String,
+    pub kind: MetricKind,
+}
+
+impl DisplayedMetric {
+    pub fn format_value(&self, value: f64) -> String {
+        format_value(self.kind, &self.unit, value)
+    }
+}
+
+// The config's metric_overrides, keyed by metric id
+#[derive(Debug, Clone, Default)]
+pub struct DisplayOverrides(BTreeMap<String, MetricOverride>);
+
+impl DisplayOverrides {
+    pub fn new(overrides: &BTreeMap<String, MetricOverride>) -> Self {
+        Self(overrides.clone())
+    }
+    
+    pub fn apply(&self, descriptor: &MetricDescriptor) -> DisplayedMetric {
+        let overrides = self.0.get(descriptor.id);
+        let kind = match (descriptor.kind, overrides.and_then(|overrides| overrides.decimals)) {
+            (MetricKind::Gauge { .. }, Some(decimals)) => MetricKind::Gauge { decimals },
+            (kind, _) => kind,
+        };
+        DisplayedMetric {
+            id: descriptor.id,
+            label: overrides.and_then(|overrides| overrides.label.clone()).unwrap_or_else(|| descriptor.label.to_string()),
+            unit: overrides.and_then(|overrides| overrides.unit.clone()).unwrap_or_else(|| descriptor.unit.to_string()),
+            kind,
+        }
+    }
+}
+
+// Overrides in effect for MetricDescriptor::displayed
+static DISPLAY_OVERRIDES: RwLock<DisplayOverrides> = RwLock::new(DisplayOverrides(BTreeMap::new()));
+
+// Replace the display overrides, e.g. at startup and when the settings change
+pub fn set_display_overrides(overrides: &BTreeMap<String, MetricOverride>) {
+    *DISPLAY_OVERRIDES.write() = DisplayOverrides::new(overrides);
+}
+
+pub const METRIC_DESCRIPTORS: &[MetricDescriptor] = &[
+    MetricDescriptor::new("cpu.utilization", "CPU Utilization", "%", 1),
+    MetricDescriptor::new("cpu.clock_speed", "CPU Clock Speed", " MHz", 0),
+    MetricDescriptor::new("cpu.clock_speed_min", "CPU Clock Speed (Slowest Core)", " MHz", 0),
+    MetricDescriptor::new("cpu.clock_speed_max", "CPU Clock Speed (Fastest Core)", " MHz", 0),
+    MetricDescriptor::new("cpu.core_voltage", "CPU Core Voltage", " V", 2),
+    MetricDescriptor::new("cpu.power_consumption", "CPU Power Consumption", " W", 1),
+    MetricDescriptor::new("cpu.package_temperature", "CPU Package Temperature", "°C", 1),
+    MetricDescriptor::new("cpu.hotspot_temperature", "CPU Hotspot Temperature", "°C", 1),
+    MetricDescriptor::new("cpu.dpc_time", "CPU DPC Time", "%", 2),
+    MetricDescriptor::new("cpu.interrupt_time", "CPU Interrupt Time", "%", 2),
+    MetricDescriptor::event("cpu.thermal_throttling", "CPU Thermal Throttling"),
+    MetricDescriptor::new("gpu.utilization", "GPU Utilization", "%", 1),
+    MetricDescriptor::new("gpu.graphics_utilization", "GPU Graphics Engine", "%", 1),
+    MetricDescriptor::new("gpu.compute_utilization", "GPU Compute Engine", "%", 1),
+    MetricDescriptor::new("gpu.copy_utilization", "GPU Copy Engine", "%", 1),
+    MetricDescriptor::new("gpu.clock_speed", "GPU Clock Speed", " MHz", 0),
+    MetricDescriptor::new("gpu.memory_clock_speed", "GPU Memory Clock", " MHz", 0),
+    MetricDescriptor::new("gpu.video_clock_speed", "GPU Video Clock", " MHz", 0),
+    MetricDescriptor::new("gpu.memory_utilization", "GPU Memory Utilization", " MB", 0),
+    MetricDescriptor::new("gpu.memory_utilization_percent", "GPU Memory Utilization (%)", "%", 1),
+    MetricDescriptor::new("gpu.memory_free_mb", "GPU Memory Free", " MB", 0),
+    MetricDescriptor::new("gpu.memory_bandwidth", "GPU Memory Bandwidth Utilization", "%", 1),
+    MetricDescriptor::new("gpu.core_voltage", "GPU Core Voltage", " V", 2),
+    MetricDescriptor::new("gpu.power_consumption", "GPU Power Consumption", " W", 1),
+    MetricDescriptor::new("gpu.package_temperature", "GPU Package Temperature", "°C", 1),
+    MetricDescriptor::new("gpu.hotspot_temperature", "GPU Hotspot Temperature", "°C", 1),
+    MetricDescriptor::event("gpu.thermal_throttling", "GPU Thermal Throttling"),
+    MetricDescriptor::new("gpu.fan_speed", "GPU Fan Speed", "%", 0),
+    MetricDescriptor::event("gpu.fan_stop", "GPU Fan Stop"),
+    MetricDescriptor::event("gpu.driver_reset", "GPU Driver Reset"),
+    MetricDescriptor::new("memory.utilization_mb", "Memory Utilization", " MB", 0),
+    MetricDescriptor::new("memory.cached_mb", "Memory Cached", " MB", 0),
+    MetricDescriptor::new("memory.free_mb", "Memory Free", " MB", 0),
+    MetricDescriptor::new("memory.swap_used_mb", "Swap Used", " MB", 0),
+    MetricDescriptor::new("memory.clock_speed", "Memory Clock Speed", " MHz", 0),
+    MetricDescriptor::new("memory.temperature", "Memory Temperature", "°C", 1),
+    MetricDescriptor::new("storage.read_speed", "Drive Read Speed", " MB/s", 1),
+    MetricDescriptor::new("storage.write_speed", "Drive Write Speed", " MB/s", 1),
+    MetricDescriptor::new("storage.busy_percent", "Drive Busy", "%", 1),
+    MetricDescriptor::new("storage.temperature", "Drive Temperature", "°C", 1),
+    MetricDescriptor::new("storage.io_latency_p99", "Drive I/O Latency (p99)", " ms", 2),
+    MetricDescriptor::new("storage.endurance_used", "Drive Endurance Used", "%", 1),
+    MetricDescriptor::new("storage.session_writes", "Drive Session Writes", " GB", 1),
+    MetricDescriptor::new("network.receive_speed", "Network Receive", " MB/s", 2),
+    MetricDescriptor::new("network.transmit_speed", "Network Transmit", " MB/s", 2),
+    MetricDescriptor::new("network.packet_errors", "Network Packet Errors", " /s", 1),
+    MetricDescriptor::new("network.link_utilization", "Network Link Utilization", "%", 1),
+    MetricDescriptor::new("battery.charge_percent", "Battery Charge", "%", 0),
+    MetricDescriptor::new("battery.voltage", "Battery Voltage", " V", 2),
+    MetricDescriptor::new("battery.time_remaining", "Battery Time Remaining", " min", 0),
+    MetricDescriptor::new("power.battery_discharge", "Battery Discharge", " W", 1),
+    MetricDescriptor::new("power.charging_power", "Charging Power", " W", 1),
+    MetricDescriptor::new("power.display_brightness", "Display Brightness", "%", 0),
+    MetricDescriptor::event("power.on_battery", "On Battery"),
+    MetricDescriptor::event("storage.health_degraded", "Drive Health Degraded"),
+    MetricDescriptor::event("storage.pool_degraded", "Storage Pool Degraded"),
+    MetricDescriptor::new("motherboard.chipset_temperature", "Chipset Temperature", "°C", 1),
+    MetricDescriptor::new("motherboard.chassis_temperature", "Chassis Temperature", "°C", 1),
+    MetricDescriptor::new("motherboard.aio_pump_speed", "AIO Pump Speed", " RPM", 0),
+    MetricDescriptor::new("motherboard.chassis_fan_speed", "Chassis Fan Speed", " RPM", 0),
+    MetricDescriptor::new("motherboard.chipset_fan_speed", "Chipset Fan Speed", " RPM", 0),
+    MetricDescriptor::new("motherboard.battery_temperature", "Battery Temperature", "°C", 1),
+    MetricDescriptor::new("motherboard.vrm_temperature", "VRM Temperature", "°C", 1),
+    MetricDescriptor::new("motherboard.vcore", "CPU Vcore", " V", 3),
+    MetricDescriptor::new("motherboard.rail_12v", "+12V Rail", " V", 2),
+    MetricDescriptor::new("motherboard.rail_5v", "+5V Rail", " V", 2),
+    MetricDescriptor::new("motherboard.rail_3v3", "+3.3V Rail", " V", 2),
+    MetricDescriptor::new("motherboard.rail_deviation", "Voltage Rail Deviation", "%", 1),
+    MetricDescriptor::event("motherboard.chassis_intrusion", "Chassis Intrusion"),
+    MetricDescriptor::event("alerts.firing", "Alert Firing"),
+    MetricDescriptor::new("clock.ntp_offset", "NTP Clock Offset", " ms", 3),
+    MetricDescriptor::new("poller.cycle_duration", "Collection Duration", " ms", 1),
+    MetricDescriptor::new("poller.jitter", "Polling Jitter", " ms", 1),
+];
+
+// Single id -> field table shared by the read-only and mutable metric lookups
+macro_rules! metric_by_id {
+    ($state:expr, $id:expr, $series:ty, $($borrow:tt)+) => {{
+        let metric: $series = match $id {
+            "cpu.utilization" => $($borrow)+ $state.cpu.utilization,
+            "cpu.clock_speed" => $($borrow)+ $state.cpu.clock_speed,
+            "cpu.clock_speed_min" => $($borrow)+ $state.cpu.clock_speed_min,
+            "cpu.clock_speed_max" => $($borrow)+ $state.cpu.clock_speed_max,
+            "cpu.core_voltage" => $($borrow)+ $state.cpu.core_voltage,
+            "cpu.power_consumption" => $($borrow)+ $state.cpu.power_consumption,
+            "cpu.package_temperature" => $($borrow)+ $state.cpu.package_temperature,
+            "cpu.hotspot_temperature" => $($borrow)+ $state.cpu.hotspot_temperature,
+            "cpu.dpc_time" => $($borrow)+ $state.cpu.dpc_time,
+            "cpu.interrupt_time" => $($borrow)+ $state.cpu.interrupt_time,
+            "gpu.utilization" => $($borrow)+ $state.gpu.utilization,
+            "gpu.graphics_utilization" => $($borrow)+ $state.gpu.graphics_utilization,
+            "gpu.compute_utilization" => $($borrow)+ $state.gpu.compute_utilization,
+            "gpu.copy_utilization" => $($borrow)+ $state.gpu.copy_utilization,
+            "gpu.clock_speed" => $($borrow)+ $state.gpu.clock_speed,
+            "gpu.memory_clock_speed" => $($borrow)+ $state.gpu.memory_clock_speed,
+            "gpu.video_clock_speed" => $($borrow)+ $state.gpu.video_clock_speed,
+            "gpu.memory_utilization" => $($borrow)+ $state.gpu.memory_utilization,
+            "gpu.memory_utilization_percent" => $($borrow)+ $state.gpu.memory_utilization_percent,
+            "gpu.memory_free_mb" => $($borrow)+ $state.gpu.memory_free_mb,
+            "gpu.memory_bandwidth" => $($borrow)+ $state.gpu.memory_bandwidth,
+            "gpu.core_voltage" => $($borrow)+ $state.gpu.core_voltage,
+            "gpu.power_consumption" => $($borrow)+ $state.gpu.power_consumption,
+            "gpu.package_temperature" => $($borrow)+ $state.gpu.package_temperature,
+            "gpu.hotspot_temperature" => $($borrow)+ $state.gpu.hotspot_temperature,
+            "gpu.fan_speed" => $($borrow)+ $state.gpu.fan_speed,
+            "memory.utilization_mb" => $($borrow)+ $state.memory.utilization_mb,
+            "memory.cached_mb" => $($borrow)+ $state.memory.cached_mb,
+            "memory.free_mb" => $($borrow)+ $state.memory.free_mb,
+            "memory.swap_used_mb" => $($borrow)+ $state.memory.swap_used_mb,
+            "memory.clock_speed" => $($borrow)+ $state.memory.clock_speed,
+            "memory.temperature" => $($borrow)+ $state.memory.temperature,
+            "storage.read_speed" => $($borrow)+ $state.storage.read_speed,
+            "storage.write_speed" => $($borrow)+ $state.storage.write_speed,
+            "storage.busy_percent" => $($borrow)+ $state.storage.busy_percent,
+            "storage.temperature" => $($borrow)+ $state.storage.temperature,
+            "storage.io_latency_p99" => $($borrow)+ $state.storage.io_latency_p99,
+            "storage.endurance_used" => $($borrow)+ $state.storage.endurance_used,
+            "storage.session_writes" => $($borrow)+ $state.storage.session_writes,
+            "network.receive_speed" => $($borrow)+ $state.network.receive_speed,
+            "network.transmit_speed" => $($borrow)+ $state.network.transmit_speed,
+            "network.packet_errors" => $($borrow)+ $state.network.packet_errors,
+            "network.link_utilization" => $($borrow)+ $state.network.link_utilization,
+            "battery.charge_percent" => $($borrow)+ $state.battery.charge_percent,
+            "battery.voltage" => $($borrow)+ $state.battery.voltage,
+            "battery.time_remaining" => $($borrow)+ $state.battery.time_remaining,
+            "power.battery_discharge" => $($borrow)+ $state.power.battery_discharge,
+            "power.charging_power" => $($borrow)+ $state.power.charging_power,
+            "power.display_brightness" => $($borrow)+ $state.power.display_brightness,
+            "motherboard.chipset_temperature" => $($borrow)+ $state.motherboard.chipset_temperature,
+            "motherboard.chassis_temperature" => $($borrow)+ $state.motherboard.chassis_temperature,
+            "motherboard.aio_pump_speed" => $($borrow)+ $state.motherboard.aio_pump_speed,
+            "motherboard.chassis_fan_speed" => $($borrow)+ $state.motherboard.chassis_fan_speed,
+            "motherboard.chipset_fan_speed" => $($borrow)+ $state.motherboard.chipset_fan_speed,
+            "motherboard.battery_temperature" => $($borrow)+ $state.motherboard.battery_temperature,
+            "motherboard.vrm_temperature" => $($borrow)+ $state.motherboard.vrm_temperature,
+            "motherboard.vcore" => $($borrow)+ $state.motherboard.vcore,
+            "motherboard.rail_12v" => $($borrow)+ $state.motherboard.rail_12v,
+            "motherboard.rail_5v" => $($borrow)+ $state.motherboard.rail_5v,
+            "motherboard.rail_3v3" => $($borrow)+ $state.motherboard.rail_3v3,
+            "motherboard.rail_deviation" => $($borrow)+ $state.motherboard.rail_deviation,
+            "clock.ntp_offset" => $($borrow)+ $state.clock.ntp_offset,
+            "poller.cycle_duration" => $($borrow)+ $state.poller.cycle_duration,
+            "poller.jitter" => $($borrow)+ $state.poller.jitter,
+            _ => return None,
+        };
+        Some(metric)
+    }};
+}
+
+impl AppState {
+    // Look up a metric by its descriptor id
+    pub fn metric(&self, id: &str) -> Option<&MetricValue> {
+        metric_by_id!(self, id, &MetricValue, &)
+    }
+    
+    pub fn metric_mut(&mut self, id: &str) -> Option<&mut MetricValue> {
+        metric_by_id!(self, id, &mut MetricValue, &mut)
+    }
+    
+    // Look up an event metric (MetricKind::Event) by its descriptor id
+    pub fn event(&self, id: &str) -> Option<&EventMetric> {
+        match id {
+            "cpu.thermal_throttling" => Some(&self.cpu.thermal_throttling),
+            "gpu.thermal_throttling" => Some(&self.gpu.thermal_throttling),
+            "gpu.fan_stop" => Some(&self.gpu.fan_stop),
+            "gpu.driver_reset" => Some(&self.gpu.driver_reset),
+            "power.on_battery" => Some(&self.power.on_battery),
+            "storage.health_degraded" => Some(&self.storage.health_degraded),
+            "storage.pool_degraded" => Some(&self.storage.pool_degraded),
+            "motherboard.chassis_intrusion" => Some(&self.motherboard.chassis_intrusion),
+            "alerts.firing" => Some(&self.alerts.firing),
+            _ => None,
+        }
+    }
+    
+    pub fn event_mut(&mut self, id: &str) -> Option<&mut EventMetric> {
+        match id {
+            "cpu.thermal_throttling" => Some(&mut self.cpu.thermal_throttling),
+            "gpu.thermal_throttling" => Some(&mut self.gpu.thermal_throttling),
+            "gpu.fan_stop" => Some(&mut self.gpu.fan_stop),
+            "gpu.driver_reset" => Some(&mut self.gpu.driver_reset),
+            "power.on_battery" => Some(&mut self.power.on_battery),
+            "storage.health_degraded" => Some(&mut self.storage.health_degraded),
+            "storage.pool_degraded" => Some(&mut self.storage.pool_degraded),
+            "motherboard.chassis_intrusion" => Some(&mut self.motherboard.chassis_intrusion),
+            "alerts.firing" => Some(&mut self.alerts.firing),
+            _ => None,
+        }
+    }
+    
+    // Latest value of any metric, with events reading as 1.0 while active
+    pub fn current_value(&self, id: &str) -> Option<f64> {
+        if let Some(metric) = self.metric(id) {
+            return metric.current;
+        }
+        if let Some(event) = self.event(id) {
+            return event.active.map(|active| active.to_f64());
+        }
+        // Alert rules may name an expression instead of a metric
+        self.queries.get(id)?.values.current
+    }
+    
+    // The samples of any metric still in memory, bounded by the history budget. Cheap
+    // enough for every frame and every push
+    pub fn samples(&self, id: &str) -> Option<Vec<(DateTime<Utc>, f64)>> {
+        match self.metric(id) {
+            Some(metric) => Some(metric.samples()),
+            None => Some(self.event(id)?.samples()),
+        }
+    }
+    
+    // Samples of any metric newer than `since`, e.g. what a pusher hasn't sent yet. The
+    // spill is only read when `since` is older than anything left in memory
+    pub fn samples_since(&self, id: &str, since: DateTime<Utc>) -> Option<Vec<(DateTime<Utc>, f64)>> {
+        match self.metric(id) {
+            Some(metric) if metric.spilled > 0 && metric.history.front().is_none_or(|(oldest, _)| *oldest > since) => {
+                let mut samples: Vec<_> = self.spilled_samples(id).into_iter().filter(|(timestamp, _)| *timestamp > since).collect();
+                samples.extend(metric.history.iter().copied());
+                Some(samples)
+            }
+            Some(metric) => Some(metric.samples_since(since)),
+            None => Some(self.event(id)?.samples().into_iter().filter(|(timestamp, _)| *timestamp > since).collect()),
+        }
+    }
+    
+    // Every sample of any metric this session, reading back any spilled to disk; for
+    // exports and other explicit looks at the whole session
+    pub fn session_samples(&self, id: &str) -> Option<Vec<(DateTime<Utc>, f64)>> {
+        match self.metric(id) {
+            Some(metric) if metric.spilled > 0 => {
+                let mut samples = self.spilled_samples(id);
+                samples.extend(metric.history.iter().copied());
+                Some(samples)
+            }
+            _ => self.samples(id),
+        }
+    }
+    
+    // Spilled part of a metric's history, averaged down to at most `max_points`
+    // for plotting the whole session
+    pub fn spilled_plot_data(&self, id: &str, max_points: usize) -> Vec<(f64, f64)> {
+        if self.metric(id).is_none_or(|metric| metric.spilled == 0) {
+            return Vec::new();
+        }
+        downsample(&self.spilled_samples(id), self.session_start, max_points)
+    }
+    
+    fn spilled_samples(&self, key: &str) -> Vec<(DateTime<Utc>, f64)> {
+        let Some(spill) = &self.history_spill else {
+            return Vec::new();
+        };
+        spill.load(key).unwrap_or_else(|e| {
+            logger::log_error(&format!("Failed to read spilled history for {}", key), &e);
+            Vec::new()
+        })
+    }
+    
+    // Keep histories within `budget_bytes` by moving the oldest samples of every
+    // series to disk; returns the number of samples spilled. The poller splits this
+    // into its three steps so no lock is held while writing
+    pub fn enforce_history_budget(&mut self, budget_bytes: usize) -> Result<usize, Box<dyn std::error::Error>> {
+        let Some(mut batch) = self.take_over_budget(budget_bytes) else {
+            return Ok(0);
+        };
+        let written = batch.write();
+        let spilled = self.finish_spill(batch);
+        written.map(|()| spilled)
+    }
+    
+    // Copies of the oldest samples to spill when over budget, or None within it. Spills
+    // down to half the budget so this runs rarely. Only reads the state
+    pub fn take_over_budget(&self, budget_bytes: usize) -> Option<SpillBatch> {
+        let mut total = 0;
+        self.for_each_series(&mut |_, series| total += series.history.len());
+        if total * HISTORY_SAMPLE_BYTES <= budget_bytes {
+            return None;
+        }
+        
+        let keep_fraction = (budget_bytes / 2 / HISTORY_SAMPLE_BYTES) as f64 / total as f64;
+        let mut series = Vec::new();
+        self.for_each_series(&mut |key, metric| {
+            let keep = (metric.history.len() as f64 * keep_fraction).ceil() as usize;
+            let count = metric.history.len().saturating_sub(keep);
+            if count > 0 {
+                series.push((key.to_string(), metric.history.range(..count).copied().collect()));
+            }
+        });
+        Some(SpillBatch {
+            spill: self.history_spill.clone(),
+            session_start: self.session_start,
+            series,
+            written: 0,
+        })
     }
     
-    pub fn has_gpu_data(&self) -> bool {
-        self.gpu.clock_speed.current.is_some() || 
-        self.gpu.package_temperature.current.is_some()
+    // Drop what the batch wrote from memory, keeping the energy it covered. A series
+    // whose oldest sample changed since the batch was taken is left alone
+    pub fn finish_spill(&mut self, batch: SpillBatch) -> usize {
+        if self.history_spill.is_none() {
+            self.history_spill = batch.spill;
+        }
+        let interval = std::time::Duration::from_millis(self.polling_interval_ms);
+        let written: HashMap<String, Vec<(DateTime<Utc>, f64)>> = batch.series.into_iter().take(batch.written).collect();
+        let mut spilled = 0;
+        let mut energy = Vec::new();
+        self.for_each_series_mut(&mut |key, series| {
+            let Some(samples) = written.get(key) else {
+                return;
+            };
+            if series.history.front() != samples.first() {
+                return;
+            }
+            let count = samples.len();
+            // Up to the first sample left in memory, so the interval across the cut counts once
+            let covered: Vec<_> = series.history.range(..(count + 1).min(series.history.len())).copied().collect();
+            energy.push((key.to_string(), integrate_energy(&covered, interval)));
+            series.history.drain(..count);
+            series.spilled += count;
+            spilled += count;
+        });
+        for (key, total) in energy {
+            self.spilled_energy.entry(key).or_default().add(&total);
+        }
+        spilled
     }
     
-    pub fn has_memory_data(&self) -> bool {
-        self.memory.utilization_mb.current.is_some() ||
-        self.memory.clock_speed.current.is_some() ||
-        self.memory.temperature.current.is_some()
+    // Every MetricValue in the state with a key stable for the session: descriptor
+    // ids for catalog metrics, plus indexed per-core/CCD series and named sensors
+    pub fn for_each_series(&self, visit: &mut dyn FnMut(&str, &MetricValue)) {
+        for descriptor in METRIC_DESCRIPTORS {
+            if let Some(series) = self.metric(descriptor.id) {
+                visit(descriptor.id, series);
+            }
+        }
+        for (index, series) in self.cpu.core_utilization.iter().enumerate() {
+            visit(&format!("cpu.core_utilization.{}", index), series);
+        }
+        for (index, series) in self.cpu.ccd_temperatures.iter().enumerate() {
+            visit(&format!("cpu.ccd_temperatures.{}", index), series);
+        }
+        let sensors = [
+            ("cpu", &self.cpu.temperature_sensors),
+            ("gpu", &self.gpu.temperature_sensors),
+            ("storage", &self.storage.temperature_sensors),
+            ("motherboard", &self.motherboard.temperature_sensors),
+        ];
+        for (section, sensors) in sensors {
+            for (name, series) in sensors.iter() {
+                visit(&format!("{}.temperature_sensors.{}", section, name), series);
+            }
+        }
     }
     
-    pub fn has_storage_data(&self) -> bool {
-        self.storage.read_speed.current.is_some() ||
-        self.storage.write_speed.current.is_some() ||
-        self.storage.temperature.current.is_some()
+    // Same keys as for_each_series
+    fn for_each_series_mut(&mut self, visit: &mut dyn FnMut(&str, &mut MetricValue)) {
+        for descriptor in METRIC_DESCRIPTORS {
+            if let Some(series) = self.metric_mut(descriptor.id) {
+                visit(descriptor.id, series);
+            }
+        }
+        for (index, series) in self.cpu.core_utilization.iter_mut().enumerate() {
+            visit(&format!("cpu.core_utilization.{}", index), series);
+        }
+        for (index, series) in self.cpu.ccd_temperatures.iter_mut().enumerate() {
+            visit(&format!("cpu.ccd_temperatures.{}", index), series);
+        }
+        let sensors = [
+            ("cpu", &mut self.cpu.temperature_sensors),
+            ("gpu", &mut self.gpu.temperature_sensors),
+            ("storage", &mut self.storage.temperature_sensors),
+            ("motherboard", &mut self.motherboard.temperature_sensors),
+        ];
+        for (section, sensors) in sensors {
+            for (name, series) in sensors.iter_mut() {
+                visit(&format!("{}.temperature_sensors.{}", section, name), series);
+            }
+        }
     }
     
-    pub fn has_motherboard_data(&self) -> bool {
-        self.motherboard.chipset_temperature.current.is_some() || 
-        self.motherboard.chassis_temperature.current.is_some() ||
-        self.motherboard.aio_pump_speed.current.is_some() ||
-        self.motherboard.chassis_fan_speed.current.is_some() ||
-        self.motherboard.chipset_fan_speed.current.is_some()
+    // Timestamp of each metric's newest sample, in METRIC_DESCRIPTORS order
+    pub fn latest_sample_times(&self) -> Vec<Option<DateTime<Utc>>> {
+        METRIC_DESCRIPTORS
+            .iter()
+            .map(|descriptor| match self.metric(descriptor.id) {
+                Some(metric) => metric.history.back().map(|(timestamp, _)| *timestamp),
+                None => self.event(descriptor.id).and_then(|event| event.last_seen),
+            })
+            .collect()
     }
-}
-
-// Helper trait for converting values to f64 for plotting
-pub trait ToF64 {
-    fn to_f64(&self) -> f64;
-}
-
-impl ToF64 for f32 {
-    fn to_f64(&self) -> f64 {
-        *self as f64
+    
+    // Let a monitor explain where the value it is about to record came from
+    pub fn note_source_detail(&mut self, id: &str, detail: String) {
+        self.pending_source_details.insert(id.to_string(), detail);
     }
-}
-
-impl ToF64 for u32 {
-    fn to_f64(&self) -> f64 {
-        *self as f64
+    
+    // Credit every metric updated since `before` to `monitor`, and publish its newest
+    // sample to subscribers. The first `warmup_samples` samples of a metric are often
+    // garbage (sysinfo reports 0% CPU before it has two readings) and are left out of
+    // session min/max
+    pub fn attribute_updates(&mut self, before: &[Option<DateTime<Utc>>], monitor: &str, warmup_samples: usize) {
+        let after = self.latest_sample_times();
+        for ((descriptor, before), after) in METRIC_DESCRIPTORS.iter().zip(before).zip(after) {
+            let Some(timestamp) = after.filter(|_| after != *before) else {
+                continue;
+            };
+            if let Some(metric) = self.metric_mut(descriptor.id) {
+                if metric.history.len() + metric.spilled <= warmup_samples {
+                    metric.session_min = None;
+                    metric.session_max = None;
+                }
+            }
+            let detail = self.pending_source_details.remove(descriptor.id);
+            self.metric_sources.insert(descriptor.id.to_string(), MetricSource {
+                monitor: monitor.to_string(),
+                detail,
+            });
+            if let Some(value) = self.current_value(descriptor.id) {
+                self.publish(Sample { metric_id: descriptor.id, timestamp, value });
+            }
+        }
+        self.pending_source_details.clear();
     }
-}
-
-impl ToF64 for u64 {
-    fn to_f64(&self) -> f64 {
-        *self as f64
+    
+    // Every published sample, across all metrics. Only the newest sample of each metric
+    // a monitor updated is published per poll; a receiver more than
+    // SAMPLE_CHANNEL_CAPACITY samples behind skips ahead (RecvError::Lagged)
+    pub fn subscribe(&self) -> broadcast::Receiver<Sample> {
+        self.samples.subscribe()
     }
-}
-
-impl ToF64 for bool {
-    fn to_f64(&self) -> f64 {
-        if *self { 1.0 } else { 0.0 }
+    
+    // Call `callback` with each new sample of one metric. It runs on the polling thread
+    // while the state is locked for writing, so it must not lock the state itself
+    pub fn on_update(&mut self, metric_id: &str, callback: impl FnMut(&Sample) + Send + Sync + 'static) {
+        self.subscriptions.0.lock().push((metric_id.to_string(), Box::new(callback)));
     }
-}
-
-impl<T: ToF64> MetricValue<T> {
-    pub fn get_plot_data(&self, session_start: DateTime<Utc>) -> Vec<(f64, f64)> {
-        self.history
-            .iter()
-            .map(|(timestamp, value)| {
-                let elapsed_seconds = (*timestamp - session_start).num_seconds() as f64;
-                (elapsed_seconds, value.to_f64())
-            })
-            .collect()
+    
+    pub fn publish(&mut self, sample: Sample) {
+        self.poller.samples_published += 1;
+        for (metric_id, callback) in self.subscriptions.0.lock().iter_mut() {
+            if metric_id == sample.metric_id {
+                callback(&sample);
+            }
+        }
+        // Nobody listening is not an error
+        let _ = self.samples.send(sample);
     }
 }
 
@@ -264,7 +2176,7 @@ mod tests {
 
     #[test]
     fn test_metric_value_default() {
-        let metric: MetricValue<f32> = MetricValue::default();
+        let metric: MetricValue = MetricValue::default();
         assert!(metric.current.is_none());
         assert!(metric.session_min.is_none());
         assert!(metric.session_max.is_none());
@@ -331,26 +2243,25 @@ mod tests {
         let mut metric = MetricValue::default();
         
         metric.update(false);
-        assert_eq!(metric.current, Some(false));
-        assert_eq!(metric.session_min, Some(false));
-        assert_eq!(metric.session_max, Some(false));
+        assert_eq!(metric.current, Some(0.0));
+        assert_eq!(metric.session_min, Some(0.0));
+        assert_eq!(metric.session_max, Some(0.0));
         
         metric.update(true);
-        assert_eq!(metric.current, Some(true));
-        assert_eq!(metric.session_min, Some(false));
-        assert_eq!(metric.session_max, Some(true));
+        assert_eq!(metric.current, Some(1.0));
+        assert_eq!(metric.session_min, Some(0.0));
+        assert_eq!(metric.session_max, Some(1.0));
         
         metric.update(false);
-        assert_eq!(metric.current, Some(false));
-        assert_eq!(metric.session_min, Some(false));
-        assert_eq!(metric.session_max, Some(true));
+        assert_eq!(metric.current, Some(0.0));
+        assert_eq!(metric.session_min, Some(0.0));
+        assert_eq!(metric.session_max, Some(1.0));
     }
 
     #[test]
     fn test_metric_value_history_ordering() {
         let mut metric = MetricValue::default();
         
-        let start_time = Utc::now();
         metric.update(10.0f32);
         
         // Small delay to ensure different timestamps
@@ -369,7 +2280,7 @@ mod tests {
         }
         
         // Verify values are in correct order
-        let values: Vec<f32> = metric.history.iter().map(|(_, v)| *v).collect();
+        let values: Vec<f64> = metric.history.iter().map(|(_, v)| *v).collect();
         assert_eq!(values, vec![10.0, 20.0, 30.0]);
     }
 
@@ -392,11 +2303,11 @@ mod tests {
         let timestamp2 = session_start + Duration::seconds(20);
         let timestamp3 = session_start + Duration::seconds(30);
         
-        metric.history.push_back((timestamp1, 50.0f32));
-        metric.history.push_back((timestamp2, 75.0f32));
-        metric.history.push_back((timestamp3, 25.0f32));
+        metric.history.push_back((timestamp1, 50.0));
+        metric.history.push_back((timestamp2, 75.0));
+        metric.history.push_back((timestamp3, 25.0));
         
-        let plot_data = metric.get_plot_data(session_start);
+        let plot_data = metric.plot_data(session_start);
         
         assert_eq!(plot_data.len(), 3);
         assert_eq!(plot_data[0], (10.0, 50.0));
@@ -407,36 +2318,159 @@ mod tests {
     #[test]
     fn test_cpu_metrics_default() {
         let cpu = CpuMetrics::default();
+        assert!(cpu.name.is_none());
         assert!(cpu.utilization.current.is_none());
         assert!(cpu.clock_speed.current.is_none());
         assert!(cpu.core_voltage.current.is_none());
         assert!(cpu.power_consumption.current.is_none());
         assert!(cpu.package_temperature.current.is_none());
         assert!(cpu.hotspot_temperature.current.is_none());
-        assert!(cpu.thermal_throttling.current.is_none());
+        assert!(cpu.thermal_throttling.active.is_none());
+        assert!(cpu.core_utilization.is_empty());
+    }
+
+    #[test]
+    fn test_cpu_per_core_utilization() {
+        let mut cpu = CpuMetrics::default();
+        cpu.update_core_utilization(&[10.0, 90.0]);
+        cpu.update_core_utilization(&[30.0, 50.0, 70.0, 5.0]);
+        
+        assert_eq!(cpu.core_utilization.len(), 4);
+        assert_eq!(cpu.core_utilization[0].history.len(), 2);
+        assert_eq!(cpu.core_utilization[1].session_max, Some(90.0));
+        assert_eq!(cpu.core_utilization[3].current, Some(5.0));
+        
+        cpu.update_ccd_temperatures(&[61.5, 58.0]);
+        assert_eq!(cpu.ccd_temperatures[1].current, Some(58.0));
+    }
+
+    #[test]
+    fn test_cpu_core_clocks() {
+        let mut cpu = CpuMetrics::default();
+        cpu.update_core_clocks(&[3600, 5100, 0, 4200]);
+        
+        assert_eq!(cpu.clock_speed.current, Some(4300.0));
+        assert_eq!(cpu.clock_speed_min.current, Some(3600.0));
+        assert_eq!(cpu.clock_speed_max.current, Some(5100.0));
+        
+        // Nothing reported, nothing recorded
+        cpu.update_core_clocks(&[0, 0]);
+        assert_eq!(cpu.clock_speed.history.len(), 1);
+    }
+
+    #[test]
+    fn test_temperature_sensors() {
+        let mut sensors = TemperatureSensors::default();
+        sensors.update("Tctl", 71.0);
+        sensors.update("Tccd1", 64.5);
+        sensors.update("Tctl", 75.0);
+        
+        assert_eq!(sensors.len(), 2);
+        let tctl = sensors.get("Tctl").unwrap();
+        assert_eq!(tctl.current, Some(75.0));
+        assert_eq!(tctl.session_min, Some(71.0));
+        
+        // Listed in name order for a stable UI
+        let names: Vec<&String> = sensors.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["Tccd1", "Tctl"]);
+    }
+
+    #[test]
+    fn test_cpu_cluster_utilization() {
+        use crate::hardware_detection::{CpuPlacement, CpuTopology};
+        
+        let mut cpu = CpuMetrics::default();
+        cpu.topology = CpuTopology::from_placements(
+            &(0..4)
+                .map(|id| CpuPlacement { cpu: id, socket: 0, core: id, l3: Some(id / 2), efficiency_class: 0 })
+                .collect::<Vec<_>>(),
+        );
+        cpu.update_core_utilization(&[100.0, 50.0, 0.0, 20.0]);
+        cpu.update_core_utilization(&[80.0, 60.0, 10.0, 30.0]);
+        
+        let clusters = cpu.cluster_utilization();
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].history.len(), 2);
+        assert_eq!(clusters[0].current, Some(70.0));
+        assert_eq!(clusters[0].session_max, Some(75.0));
+        assert_eq!(clusters[1].current, Some(20.0));
     }
 
     #[test]
     fn test_gpu_metrics_default() {
         let gpu = GpuMetrics::default();
+        assert!(gpu.name.is_none());
         assert!(gpu.utilization.current.is_none());
         assert!(gpu.clock_speed.current.is_none());
         assert!(gpu.memory_utilization.current.is_none());
+        assert!(gpu.memory_utilization_percent.current.is_none());
+        assert!(gpu.memory_total_mb.is_none());
         assert!(gpu.core_voltage.current.is_none());
         assert!(gpu.power_consumption.current.is_none());
         assert!(gpu.package_temperature.current.is_none());
         assert!(gpu.hotspot_temperature.current.is_none());
-        assert!(gpu.thermal_throttling.current.is_none());
+        assert!(gpu.thermal_throttling.active.is_none());
+    }
+
+    #[test]
+    fn test_gpu_memory_usage_percentage() {
+        let mut gpu = GpuMetrics::default();
+        
+        // Without a known total only the used amount is recorded
+        gpu.update_memory_usage(2048);
+        assert_eq!(gpu.memory_utilization.current, Some(2048.0));
+        assert!(gpu.memory_utilization_percent.current.is_none());
+        
+        gpu.memory_total_mb = Some(8192);
+        gpu.update_memory_usage(4096);
+        assert_eq!(gpu.memory_utilization.current, Some(4096.0));
+        assert_eq!(gpu.memory_utilization_percent.current, Some(50.0));
+        assert_eq!(gpu.memory_free_mb.current, Some(4096.0));
+        
+        assert_eq!(gpu.largest_memory_users(2), None);
+        gpu.process_memory = vec![
+            GpuProcessMemory { pid: 4242, name: "python".to_string(), used_mb: 3600, compute: true },
+            GpuProcessMemory { pid: 1200, name: "Xorg".to_string(), used_mb: 310, compute: false },
+            GpuProcessMemory { pid: 1300, name: "firefox".to_string(), used_mb: 120, compute: false },
+        ];
+        assert_eq!(gpu.largest_memory_users(2).unwrap(), "python (pid 4242) 3600 MB, Xorg (pid 1200) 310 MB");
     }
 
     #[test]
     fn test_memory_metrics_default() {
         let memory = MemoryMetrics::default();
         assert!(memory.utilization_mb.current.is_none());
+        assert!(memory.cached_mb.current.is_none());
+        assert!(memory.swap_used_mb.current.is_none());
         assert!(memory.clock_speed.current.is_none());
         assert!(memory.temperature.current.is_none());
     }
 
+    #[test]
+    fn test_network_interfaces_and_totals() {
+        let reading = |name: &str, receive: f64, transmit: f64, link: Option<u64>| InterfaceReading {
+            name: name.to_string(),
+            receive_speed: receive,
+            transmit_speed: transmit,
+            packet_errors: 0.5,
+            link_speed_mbps: link,
+        };
+        let mut network = NetworkMetrics::default();
+        network.update_interfaces(&[reading("eth0", 59.6, 2.0, Some(1000)), reading("wlan0", 1.0, 0.5, None)]);
+        assert_eq!(network.interfaces.len(), 2);
+        assert_eq!(network.receive_speed.current, Some(60.6));
+        assert_eq!(network.packet_errors.current, Some(1.0));
+        // 59.6 MB/s is 500 Mb/s, half of a gigabit link
+        let utilization = network.link_utilization.current.unwrap();
+        assert!((utilization - 50.0).abs() < 0.1, "{}", utilization);
+
+        // An unplugged interface keeps its history but leaves the totals
+        network.update_interfaces(&[reading("wlan0", 3.0, 1.0, None)]);
+        assert_eq!(network.interfaces.len(), 2);
+        assert_eq!(network.interfaces[1].receive_speed.current, Some(3.0));
+        assert_eq!(network.receive_speed.current, Some(3.0));
+    }
+
     #[test]
     fn test_storage_metrics_default() {
         let storage = StorageMetrics::default();
@@ -445,6 +2479,79 @@ mod tests {
         assert!(storage.temperature.current.is_none());
     }
 
+    #[test]
+    fn test_per_disk_io_and_totals() {
+        let reading = |name: &str, read: f64, write: f64, busy: f64| DiskReading {
+            name: name.to_string(),
+            read_speed: read,
+            write_speed: write,
+            busy_percent: busy,
+        };
+        let mut storage = StorageMetrics::default();
+        storage.update_disks(&[reading("nvme0n1", 400.0, 20.0, 35.0), reading("sda", 100.0, 5.0, 80.0)]);
+        assert_eq!(storage.disks.len(), 2);
+        assert_eq!(storage.disks[1].read_speed.current, Some(100.0));
+        assert_eq!((storage.read_speed.current, storage.write_speed.current), (Some(500.0), Some(25.0)));
+        assert_eq!(storage.busy_percent.current, Some(80.0));
+
+        storage.update_disks(&[]);
+        assert_eq!(storage.read_speed.current, Some(500.0));
+    }
+
+    #[test]
+    fn test_drive_endurance_against_rating() {
+        let writes = |device: &str, model: &str, bytes: u64, wear: Option<f64>| DriveWrites {
+            device: device.to_string(),
+            model: model.to_string(),
+            host_writes_bytes: bytes,
+            wear_percent: wear,
+        };
+        let rated: BTreeMap<String, f64> = [("Samsung SSD 980 PRO 1TB".to_string(), 600.0)].into_iter().collect();
+        let mut storage = StorageMetrics::default();
+        storage.update_endurance(&[
+            writes("/dev/nvme0", "Samsung SSD 980 PRO 1TB", 150_000_000_000_000, Some(9.0)),
+            writes("/dev/sda", "CT1000MX500SSD1", 20_000_000_000_000, Some(30.0)),
+        ], &rated);
+        // 150 of 600 TBW; the unrated drive falls back to its own estimate
+        assert_eq!(storage.drives[0].endurance_used(), Some(25.0));
+        assert_eq!(storage.endurance_used.current, Some(30.0));
+        assert_eq!(storage.session_writes.current, Some(0.0));
+
+        storage.update_endurance(&[writes("/dev/nvme0", "Samsung SSD 980 PRO 1TB", 150_012_500_000_000, Some(9.0))], &rated);
+        assert_eq!(storage.drives[0].session_gb(), 12.5);
+        assert_eq!(storage.session_writes.current, Some(12.5));
+    }
+
+    #[test]
+    fn test_drive_health_degradation() {
+        let previous = DriveHealth {
+            model: "CT1000MX500SSD1".to_string(),
+            serial: Some("2203E5F1".to_string()),
+            passed: Some(true),
+            reallocated_sectors: Some(0),
+            power_on_hours: Some(1200),
+            ..DriveHealth::default()
+        };
+        let mut current = DriveHealth { reallocated_sectors: Some(8), power_on_hours: Some(1300), device: "/dev/sdb".to_string(), ..previous.clone() };
+        assert_eq!(current.key(), previous.key());
+        current.degradations = current.degradations_since(&previous);
+        assert_eq!(current.degradations, vec!["reallocated sectors 0 → 8"]);
+
+        let mut storage = StorageMetrics::default();
+        storage.update_health(vec![previous.clone()]);
+        assert_eq!(storage.health_degraded.active, Some(false));
+        storage.update_health(vec![current]);
+        assert_eq!(storage.health_degraded.active, Some(true));
+        let failed = DriveHealth { passed: Some(false), ..previous.clone() };
+        assert!(!failed.is_healthy());
+        assert_eq!(failed.degradations_since(&previous), vec!["self-assessment PASSED → FAILED"]);
+
+        let overheating = DriveHealth { critical_warning: Some(0b10), ..previous.clone() };
+        assert_eq!(overheating.critical_warnings(), vec!["temperature outside limits"]);
+        assert!(!overheating.is_healthy());
+        assert!(DriveHealth { critical_warning: Some(0), ..previous }.is_healthy());
+    }
+
     #[test]
     fn test_motherboard_metrics_default() {
         let motherboard = MotherboardMetrics::default();
@@ -455,21 +2562,10 @@ mod tests {
         assert!(motherboard.chipset_fan_speed.current.is_none());
     }
 
-    #[test]
-    fn test_ui_state_default() {
-        let ui_state = UiState::default();
-        assert!(ui_state.cpu_section_expanded);
-        assert!(ui_state.gpu_section_expanded);
-        assert!(ui_state.memory_section_expanded);
-        assert!(ui_state.storage_section_expanded);
-        assert!(ui_state.motherboard_section_expanded);
-    }
-
     #[test]
     fn test_app_state_default() {
         let app_state = AppState::default();
         assert_eq!(app_state.polling_interval_ms, 1000);
-        assert!(app_state.ui_state.cpu_section_expanded);
         assert!(!app_state.has_cpu_data());
         assert!(!app_state.has_gpu_data());
         assert!(!app_state.has_memory_data());
@@ -500,7 +2596,7 @@ mod tests {
         assert!(app_state.has_cpu_data());
         
         let mut app_state2 = AppState::default();
-        app_state2.cpu.clock_speed.update(3000);
+        app_state2.cpu.clock_speed.update(3000u32);
         assert!(app_state2.has_cpu_data());
         
         let mut app_state3 = AppState::default();
@@ -513,7 +2609,7 @@ mod tests {
         let mut app_state = AppState::default();
         assert!(!app_state.has_gpu_data());
         
-        app_state.gpu.clock_speed.update(1500);
+        app_state.gpu.clock_speed.update(1500u32);
         assert!(app_state.has_gpu_data());
         
         let mut app_state2 = AppState::default();
@@ -526,11 +2622,11 @@ mod tests {
         let mut app_state = AppState::default();
         assert!(!app_state.has_memory_data());
         
-        app_state.memory.utilization_mb.update(8192);
+        app_state.memory.utilization_mb.update(8192u32);
         assert!(app_state.has_memory_data());
         
         let mut app_state2 = AppState::default();
-        app_state2.memory.clock_speed.update(3200);
+        app_state2.memory.clock_speed.update(3200u32);
         assert!(app_state2.has_memory_data());
         
         let mut app_state3 = AppState::default();
@@ -568,16 +2664,24 @@ mod tests {
         assert!(app_state2.has_motherboard_data());
         
         let mut app_state3 = AppState::default();
-        app_state3.motherboard.aio_pump_speed.update(2500);
+        app_state3.motherboard.aio_pump_speed.update(2500u32);
         assert!(app_state3.has_motherboard_data());
         
         let mut app_state4 = AppState::default();
-        app_state4.motherboard.chassis_fan_speed.update(1200);
+        app_state4.motherboard.chassis_fan_speed.update(1200u32);
         assert!(app_state4.has_motherboard_data());
         
         let mut app_state5 = AppState::default();
-        app_state5.motherboard.chipset_fan_speed.update(800);
+        app_state5.motherboard.chipset_fan_speed.update(800u32);
         assert!(app_state5.has_motherboard_data());
+        
+        let mut app_state6 = AppState::default();
+        app_state6.motherboard.battery_temperature.update(31.5);
+        assert!(app_state6.has_motherboard_data());
+        
+        let mut app_state7 = AppState::default();
+        app_state7.motherboard.temperature_sensors.update("Thermal Zone TZ00", 48.0);
+        assert!(app_state7.has_motherboard_data());
     }
 
     #[test]
@@ -620,9 +2724,115 @@ mod tests {
         
         // Test with very large values
         metric.update(f32::MAX);
-        assert_eq!(metric.current, Some(f32::MAX));
+        assert_eq!(metric.current, Some(f32::MAX as f64));
         assert_eq!(metric.session_min, Some(-10.0));
-        assert_eq!(metric.session_max, Some(f32::MAX));
+        assert_eq!(metric.session_max, Some(f32::MAX as f64));
+    }
+
+    #[test]
+    fn test_every_descriptor_resolves_to_a_metric() {
+        let app_state = AppState::default();
+        for descriptor in METRIC_DESCRIPTORS {
+            let resolved = match descriptor.kind {
+                MetricKind::Event => app_state.event(descriptor.id).is_some(),
+                MetricKind::Gauge { .. } => app_state.metric(descriptor.id).is_some(),
+            };
+            assert!(resolved, "unresolved metric {}", descriptor.id);
+        }
+        assert!(app_state.metric("cpu.unknown").is_none());
+    }
+
+    #[test]
+    fn test_metric_lookup_reads_values() {
+        let mut app_state = AppState::default();
+        app_state.gpu.hotspot_temperature.update(81.5);
+        app_state.gpu.hotspot_temperature.update(77.0);
+        app_state.cpu.thermal_throttling.update(true);
+
+        let hotspot = app_state.metric("gpu.hotspot_temperature").unwrap();
+        assert_eq!(hotspot.current, Some(77.0));
+        assert_eq!(hotspot.session_min, Some(77.0));
+        assert_eq!(hotspot.session_max, Some(81.5));
+        assert_eq!(hotspot.plot_data(app_state.session_start).len(), 2);
+
+        // On/off metrics are events, not sampled series
+        assert!(app_state.metric("cpu.thermal_throttling").is_none());
+        assert_eq!(app_state.event("cpu.thermal_throttling").unwrap().active, Some(true));
+        assert_eq!(app_state.current_value("cpu.thermal_throttling"), Some(1.0));
+    }
+
+    #[test]
+    fn test_metric_record_at_and_mutable_lookup() {
+        let mut state = AppState::default();
+        let timestamp = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        
+        state.metric_mut("cpu.clock_speed").unwrap().record_at(timestamp, 4499.6);
+        state.event_mut("cpu.thermal_throttling").unwrap().record_at(timestamp, true);
+        assert!(state.metric_mut("cpu.unknown").is_none());
+        
+        assert_eq!(state.cpu.clock_speed.current, Some(4499.6));
+        assert_eq!(state.cpu.clock_speed.history[0].0, timestamp);
+        assert_eq!(state.cpu.thermal_throttling.changes, vec![(timestamp, true)]);
+    }
+
+    #[test]
+    fn test_attribute_updates_records_source() {
+        let mut state = AppState::default();
+        
+        let before = state.latest_sample_times();
+        state.note_source_detail("cpu.package_temperature", "sysinfo sensor \"k10temp Tctl\"".to_string());
+        state.cpu.package_temperature.update(55.0);
+        state.cpu.utilization.update(10.0);
+        state.attribute_updates(&before, "Generic", 0);
+        
+        let source = &state.metric_sources["cpu.package_temperature"];
+        assert_eq!(source.monitor, "Generic");
+        assert_eq!(source.detail.as_deref(), Some("sysinfo sensor \"k10temp Tctl\""));
+        assert_eq!(state.metric_sources["cpu.utilization"].detail, None);
+        
+        // A later monitor overriding the value takes over attribution
+        let before = state.latest_sample_times();
+        state.cpu.package_temperature.update(56.0);
+        state.attribute_updates(&before, "AMD", 0);
+        assert_eq!(state.metric_sources["cpu.package_temperature"].monitor, "AMD");
+        assert_eq!(state.metric_sources["cpu.utilization"].monitor, "Generic");
+    }
+
+    #[test]
+    fn test_metric_descriptor_format() {
+        let descriptor = MetricDescriptor::find("gpu.hotspot_temperature").unwrap();
+        assert_eq!(descriptor.label, "GPU Hotspot Temperature");
+        assert_eq!(descriptor.format_value(81.26), "81.3°C");
+        assert!(MetricDescriptor::find("gpu.unknown").is_none());
+        
+        let throttling = MetricDescriptor::find("cpu.thermal_throttling").unwrap();
+        assert_eq!(throttling.kind, MetricKind::Event);
+        assert_eq!(throttling.format_value(1.0), "Active");
+        assert_eq!(throttling.format_value(0.0), "Inactive");
+    }
+    
+    #[test]
+    fn test_display_overrides_replace_label_unit_and_decimals() {
+        let overrides = DisplayOverrides::new(&BTreeMap::from([
+            ("motherboard.chipset_fan_speed".to_string(), MetricOverride {
+                label: Some("PCH Fan".to_string()),
+                unit: Some(" rpm".to_string()),
+                decimals: Some(1),
+            }),
+            ("cpu.thermal_throttling".to_string(), MetricOverride { decimals: Some(2), ..Default::default() }),
+            ("gpu.unknown".to_string(), MetricOverride::default()),
+        ]));
+        let fan = MetricDescriptor::find("motherboard.chipset_fan_speed").unwrap();
+        let displayed = overrides.apply(fan);
+        
+        assert_eq!((displayed.label.as_str(), displayed.format_value(1200.0)), ("PCH Fan", "1200.0 rpm".to_string()));
+        // The catalog itself is untouched
+        assert_eq!((fan.label, fan.format_value(1200.0)), ("Chipset Fan Speed", "1200 RPM".to_string()));
+        assert_eq!(DisplayOverrides::default().apply(fan).label, "Chipset Fan Speed");
+        // On/off metrics have no precision to change
+        let throttling = overrides.apply(MetricDescriptor::find("cpu.thermal_throttling").unwrap());
+        assert_eq!(throttling.kind, MetricKind::Event);
+        assert!(MetricDescriptor::find("gpu.unknown").is_none());
     }
 
     #[test]
@@ -634,4 +2844,370 @@ mod tests {
         assert!(app_state.session_start >= before);
         assert!(app_state.session_start <= after);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_integrate_energy_uses_timestamps() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let at = |seconds: i64, watts: f64| (start + Duration::seconds(seconds), watts);
+        
+        // 10 W for an hour polled every second, then every 10 s: the same energy either way
+        let mut samples: Vec<_> = (0..=1800).map(|second| at(second, 10.0)).collect();
+        samples.extend((1..=180).map(|step| at(1800 + step * 10, 10.0)));
+        let total = integrate_energy(&samples, StdDuration::from_secs(1));
+        assert!((total.watt_hours - 10.0).abs() < 1e-9);
+        assert_eq!(total.skipped_seconds, 0.0);
+    }
+    
+    #[test]
+    fn test_integrate_energy_skips_suspend_gap() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let at = |seconds: i64, watts: f64| (start + Duration::seconds(seconds), watts);
+        
+        // 36 W for 100 s, suspended for two hours, then 36 W for another 100 s
+        let mut samples: Vec<_> = (0..=100).map(|second| at(second, 36.0)).collect();
+        samples.extend((0..=100).map(|second| at(7300 + second, 36.0)));
+        let total = integrate_energy(&samples, StdDuration::from_secs(1));
+        assert!((total.watt_hours - 2.0).abs() < 1e-9);
+        assert_eq!(total.measured_seconds, 200.0);
+        assert_eq!(total.skipped_seconds, 7200.0);
+    }
+    
+    #[test]
+    fn test_session_energy() {
+        let mut state = AppState::new(1000);
+        state.cpu.power_consumption.update(15.0);
+        assert_eq!(state.session_energy("cpu.power_consumption").map(|total| total.watt_hours), Some(0.0));
+        assert!(state.session_energy("cpu.unknown").is_none());
+    }
+
+    #[test]
+    fn test_recording_round_trip() {
+        let mut state = AppState::new(500);
+        state.cpu.utilization.update(42.5);
+        state.cpu.thermal_throttling.update(true);
+        state.gpu.name = Some("Test GPU".to_string());
+        state.motherboard.temperature_sensors.update("VRM", 61.0);
+        
+        let text = toml::to_string(&state.recording()).expect("Failed to serialize recording");
+        let restored = AppState::from_recording(toml::from_str(&text).expect("Failed to parse recording"))
+            .expect("Failed to restore recording");
+        
+        assert_eq!(restored.polling_interval_ms, 500);
+        assert_eq!(restored.session_start, state.session_start);
+        assert_eq!(restored.cpu.utilization.current, Some(42.5));
+        assert_eq!(restored.cpu.utilization.history, state.cpu.utilization.history);
+        assert_eq!(restored.cpu.thermal_throttling.active, Some(true));
+        assert_eq!(restored.gpu.name.as_deref(), Some("Test GPU"));
+        assert!(restored.motherboard.temperature_sensors.get("VRM").is_some());
+    }
+    
+    #[test]
+    fn test_recording_versioning() {
+        // A pre-versioning recording with only some metrics still loads
+        let old: StateRecording = toml::from_str(
+            "polling_interval_ms = 1000\n[cpu.utilization]\ncurrent = 12.0\nhistory = []\n",
+        )
+        .expect("Failed to parse old recording");
+        assert_eq!(old.version, 0);
+        let state = AppState::from_recording(old).expect("Old recording should load");
+        assert_eq!(state.cpu.utilization.current, Some(12.0));
+        assert!(state.gpu.utilization.current.is_none());
+        
+        // Version 1 sampled flags every poll, as booleans; they are replayed into events
+        let flags: StateRecording = toml::from_str(
+            "version = 1\n[cpu.thermal_throttling]\ncurrent = true\nhistory = [[\"2024-01-01T00:00:00Z\", false], [\"2024-01-01T00:00:01Z\", true], [\"2024-01-01T00:00:02Z\", true]]\n",
+        )
+        .expect("Failed to parse version 1 flags");
+        let throttling = &flags.cpu.thermal_throttling;
+        assert_eq!(throttling.active, Some(true));
+        assert_eq!(throttling.changes.len(), 2);
+        assert_eq!(throttling.activations, 1);
+        assert_eq!(throttling.active_duration(), Duration::seconds(1));
+        
+        let future = StateRecording { version: RECORDING_VERSION + 1, ..Default::default() };
+        assert!(AppState::from_recording(future).is_err());
+    }
+    
+    #[test]
+    fn test_snapshot_recent_stats() {
+        let mut state = AppState::new(1000);
+        let now = Utc::now();
+        // Outside the recent window: counts for the session but not the recent stats
+        state.cpu.utilization.record_at(now - Duration::seconds(RECENT_STATS_SECONDS + 30), 90.0);
+        state.cpu.utilization.record_at(now - Duration::seconds(20), 10.0);
+        state.cpu.utilization.record_at(now - Duration::seconds(10), 30.0);
+        
+        let snapshot = state.snapshot();
+        let cpu = snapshot.get("cpu.utilization").expect("Utilization should be in the snapshot");
+        assert_eq!(cpu.current, Some(30.0));
+        assert_eq!(cpu.session_max, Some(90.0));
+        assert_eq!(cpu.samples, 3);
+        assert_eq!(cpu.recent, Some(RecentStats { min: 10.0, max: 30.0, average: 20.0, samples: 2 }));
+        
+        // Metrics without data are left out
+        assert!(snapshot.get("gpu.utilization").is_none());
+        assert!(snapshot.to_text().contains("CPU Utilization: 30.0% (min 10.0%, max 90.0%)"));
+        
+        let text = toml::to_string(&snapshot).expect("Failed to serialize snapshot");
+        let parsed: StateSnapshot = toml::from_str(&text).expect("Failed to parse snapshot");
+        assert_eq!(parsed, snapshot);
+    }
+    
+    #[test]
+    fn test_history_budget_spills_oldest_samples() {
+        let dir = std::env::temp_dir().join("spd_history_budget_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut state = AppState::new(1000);
+        state.history_spill = Some(HistorySpill::new(dir.clone()).expect("Failed to create spill dir"));
+        let start = state.session_start;
+        for second in 0..100 {
+            state.cpu.utilization.record_at(start + Duration::seconds(second), second as f64);
+            state.cpu.temperature_sensors.update("Tctl", 50.0);
+        }
+        
+        // Within budget nothing moves
+        assert_eq!(state.enforce_history_budget(200 * HISTORY_SAMPLE_BYTES).unwrap(), 0);
+        
+        // Over budget each series is cut to its share of half the budget, oldest first
+        assert_eq!(state.enforce_history_budget(100 * HISTORY_SAMPLE_BYTES).unwrap(), 150);
+        assert_eq!(state.cpu.utilization.history.len(), 25);
+        assert_eq!(state.cpu.utilization.spilled, 75);
+        assert_eq!(state.cpu.utilization.history.front().map(|(_, value)| *value), Some(75.0));
+        assert_eq!(state.cpu.utilization.session_min, Some(0.0));
+        
+        // Exports, recordings and snapshots still see the whole session
+        let samples = state.session_samples("cpu.utilization").unwrap();
+        assert_eq!(samples.len(), 100);
+        assert!(samples.iter().enumerate().all(|(index, (_, value))| *value == index as f64));
+        assert_eq!(state.snapshot().get("cpu.utilization").unwrap().samples, 100);
+        let recording = state.recording();
+        assert_eq!(recording.cpu.utilization.history.len(), 100);
+        assert_eq!(recording.cpu.temperature_sensors.get("Tctl").unwrap().history.len(), 100);
+        
+        // Zoomed-out plots get the spilled part averaged down
+        let plot = state.spilled_plot_data("cpu.utilization", 25);
+        assert_eq!(plot.len(), 25);
+        assert_eq!(plot[0], (0.0, 1.0));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn test_session_energy_counts_spilled_samples_without_reading_them() {
+        let dir = std::env::temp_dir().join("spd_spilled_energy_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut state = AppState::new(1000);
+        state.history_spill = Some(HistorySpill::new(dir.clone()).expect("Failed to create spill dir"));
+        let start = state.session_start;
+        // 36 W for 100 s is 1 Wh
+        for second in 0..=100 {
+            state.cpu.power_consumption.record_at(start + Duration::seconds(second), 36.0);
+        }
+        let before = state.session_energy("cpu.power_consumption").unwrap();
+        
+        assert_eq!(state.enforce_history_budget(50 * HISTORY_SAMPLE_BYTES).unwrap(), 76);
+        let _ = std::fs::remove_dir_all(&dir);
+        let after = state.session_energy("cpu.power_consumption").unwrap();
+        assert!((after.watt_hours - before.watt_hours).abs() < 1e-9);
+        assert_eq!(after.measured_seconds, 100.0);
+        // Per-frame readers only see what is in memory
+        assert_eq!(state.samples("cpu.power_consumption").unwrap().len(), 25);
+    }
+    
+    #[test]
+    fn test_samples_since_reads_the_spill_only_when_needed() {
+        let dir = std::env::temp_dir().join("spd_samples_since_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut state = AppState::new(1000);
+        state.history_spill = Some(HistorySpill::new(dir.clone()).expect("Failed to create spill dir"));
+        let start = state.session_start;
+        let at = |second: i64| start + Duration::seconds(second);
+        for second in 0..100 {
+            state.cpu.utilization.record_at(at(second), second as f64);
+        }
+        state.enforce_history_budget(50 * HISTORY_SAMPLE_BYTES).unwrap();
+        assert_eq!(state.cpu.utilization.spilled, 75);
+        
+        let values = |samples: Vec<(DateTime<Utc>, f64)>| samples.into_iter().map(|(_, value)| value).collect::<Vec<_>>();
+        assert_eq!(values(state.samples_since("cpu.utilization", at(96)).unwrap()), vec![97.0, 98.0, 99.0]);
+        // Behind what memory holds, the rest comes back from disk
+        assert_eq!(values(state.samples_since("cpu.utilization", at(69)).unwrap()).len(), 30);
+        assert_eq!(state.samples_since("cpu.utilization", at(99)).unwrap(), Vec::new());
+        assert!(state.samples_since("cpu.unknown", at(0)).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn test_warmup_samples_left_out_of_min_max() {
+        let mut state = AppState::default();
+        
+        // sysinfo's first CPU usage reading is 0 before it has a second to compare
+        let before = state.latest_sample_times();
+        state.cpu.utilization.update(0.0);
+        state.attribute_updates(&before, "Generic", 1);
+        assert_eq!(state.cpu.utilization.current, Some(0.0));
+        assert_eq!(state.cpu.utilization.session_min, None);
+        
+        for value in [35.0, 20.0, 60.0] {
+            let before = state.latest_sample_times();
+            state.cpu.utilization.update(value);
+            state.attribute_updates(&before, "Generic", 1);
+        }
+        assert_eq!(state.cpu.utilization.session_min, Some(20.0));
+        assert_eq!(state.cpu.utilization.session_max, Some(60.0));
+        // Still plotted, just not counted
+        assert_eq!(state.cpu.utilization.history.len(), 4);
+    }
+    
+    #[test]
+    fn test_subscribers_receive_attributed_updates() {
+        let mut state = AppState::new(1000);
+        let mut receiver = state.subscribe();
+        let temperatures = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let seen = temperatures.clone();
+        state.on_update("cpu.package_temperature", move |sample| seen.lock().push(sample.value));
+        
+        let before = state.latest_sample_times();
+        state.cpu.utilization.update(40.0f32);
+        state.cpu.package_temperature.update(65.0f32);
+        state.cpu.thermal_throttling.update(true);
+        state.attribute_updates(&before, "Generic", 0);
+        
+        let mut published = Vec::new();
+        while let Ok(sample) = receiver.try_recv() {
+            published.push((sample.metric_id, sample.value));
+        }
+        assert_eq!(published, vec![
+            ("cpu.utilization", 40.0),
+            ("cpu.package_temperature", 65.0),
+            ("cpu.thermal_throttling", 1.0),
+        ]);
+        assert_eq!(*temperatures.lock(), vec![65.0]);
+        
+        // Nothing new, nothing published
+        let before = state.latest_sample_times();
+        state.attribute_updates(&before, "Generic", 0);
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(state.poller.samples_published, 3);
+    }
+    
+    #[test]
+    fn test_active_monitors_exclude_disabled() {
+        let mut poller = PollerStatus::default();
+        for (name, selected) in [("Generic", true), ("NVIDIA", true), ("AMD", false)] {
+            poller.monitor_selection.push(MonitorSelection { name: name.to_string(), selected, reason: String::new() });
+        }
+        assert_eq!(poller.active_monitors(), 2);
+        
+        poller.disabled_monitors.push("NVIDIA".to_string());
+        assert_eq!(poller.active_monitors(), 1);
+    }
+    
+    #[test]
+    fn test_poller_cycle_metrics() {
+        let mut state = AppState::new(100);
+        let start = Utc::now();
+        state.poller.record_cycle(start, 12.5, 100);
+        state.poller.record_cycle(start + chrono::Duration::milliseconds(104), 8.0, 100);
+        state.poller.record_cycle(start + chrono::Duration::milliseconds(201), 9.0, 100);
+        
+        // No spacing for the first cycle, then 4 ms late and 3 ms early
+        let jitter: Vec<f64> = state.metric("poller.jitter").unwrap().history.iter().map(|(_, ms)| *ms).collect();
+        assert_eq!(jitter, vec![4.0, 3.0]);
+        assert_eq!(state.current_value("poller.cycle_duration"), Some(9.0));
+        assert_eq!(state.metric("poller.cycle_duration").unwrap().session_max, Some(12.5));
+    }
+    
+    #[test]
+    fn test_event_metric_tracks_transitions() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let mut event = EventMetric::default();
+        for (second, active) in [(0, false), (1, true), (2, true), (4, false), (5, true), (6, true)] {
+            event.record_at(start + Duration::seconds(second), active);
+        }
+        
+        assert_eq!(event.changes.len(), 4);
+        assert_eq!(event.activations, 2);
+        // 1s..4s plus 5s..6s, the latter closed by the newest observation
+        assert_eq!(event.active_duration(), Duration::seconds(4));
+        
+        let steps = event.step_plot_data(start);
+        assert_eq!(&steps[..4], &[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (4.0, 1.0)]);
+        assert_eq!(steps.last(), Some(&(6.0, 1.0)));
+    }
+    
+    #[test]
+    fn test_counter_metric_rates() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let mut bytes = CounterMetric::default();
+        assert_eq!(bytes.record_at(start, 1_000), None);
+        assert_eq!(bytes.record_at(start + Duration::seconds(2), 3_000), Some(1_000.0));
+        // A reset counter skips one interval instead of reporting a huge spike
+        assert_eq!(bytes.record_at(start + Duration::seconds(3), 10), None);
+        assert_eq!(bytes.record_at(start + Duration::seconds(4), 110), Some(100.0));
+        assert_eq!((bytes.total, bytes.resets), (2_100, 1));
+        
+        // With a known range the wrap is counted instead
+        let mut energy = CounterMetric::with_range(1_000_000);
+        energy.record_at(start, 900_000);
+        assert_eq!(energy.record_at(start + Duration::seconds(1), 100_000), Some(200_000.0));
+        assert_eq!(energy.resets, 0);
+    }
+    
+    #[test]
+    fn test_latency_histogram_quantiles() {
+        let mut latency = LatencyHistogram::default();
+        assert_eq!(latency.quantile_ms(0.5), None);
+        
+        // 98 fast I/Os around 100 µs and two 40 ms stalls
+        latency.add_slots(&[0, 0, 0, 0, 0, 0, 90]);
+        for _ in 0..8 {
+            latency.record(100);
+        }
+        latency.record(40_000);
+        latency.record(45_000);
+        assert_eq!(latency.count(), 100);
+        assert_eq!(latency.quantile_ms(0.5), Some(0.128));
+        assert_eq!(latency.quantile_ms(0.98), Some(0.128));
+        assert_eq!(latency.quantile_ms(0.99), Some(65.536));
+        assert_eq!(latency.buckets.len(), 16);
+    }
+    
+    #[test]
+    fn test_device_tree_shows_devices_with_data() {
+        let mut state = AppState::new(1000);
+        assert!(state.device_tree().children.is_empty());
+        
+        state.cpu.name = Some("Ryzen 9 7950X".to_string());
+        state.cpu.utilization.update(25.0);
+        state.cpu.thermal_throttling.update(false);
+        state.cpu.update_core_utilization(&[10.0, 20.0, 30.0, 40.0]);
+        state.gpu.package_temperature.update(55.0);
+        
+        let tree = state.device_tree();
+        let ids: Vec<&str> = tree.children.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(ids, vec!["system/cpu0", "system/gpus"]);
+        
+        let cpu = &tree.children[0];
+        assert_eq!(cpu.name, "CPU0 — Ryzen 9 7950X");
+        assert_eq!(cpu.metrics, vec!["cpu.utilization", "cpu.thermal_throttling"]);
+        assert_eq!(cpu.children[0].name, "Cores (4)");
+        assert_eq!(cpu.children[0].kind, DeviceKind::Cores);
+        
+        let gpu = &tree.children[1].children[0];
+        assert_eq!((gpu.id.as_str(), gpu.metrics.clone()), ("system/gpus/gpu0", vec!["gpu.package_temperature"]));
+        
+        // Every enumerated GPU and every disk gets a node of its own
+        state.gpu.adapters = vec!["RTX 4090".to_string(), "RTX 3060".to_string()];
+        state.storage.update_disks(&[
+            DiskReading { name: "nvme0n1".to_string(), read_speed: 120.0, write_speed: 8.0, busy_percent: 12.0 },
+            DiskReading { name: "sda".to_string(), read_speed: 0.0, write_speed: 1.5, busy_percent: 2.0 },
+        ]);
+        let tree = state.device_tree();
+        let gpus: Vec<(&str, &str)> = tree.children[1].children.iter().map(|node| (node.id.as_str(), node.name.as_str())).collect();
+        assert_eq!(gpus, vec![("system/gpus/gpu0", "GPU0 — RTX 4090"), ("system/gpus/gpu1", "GPU1 — RTX 3060")]);
+        let drives = tree.children.iter().find(|node| node.id == "system/drives").unwrap();
+        assert_eq!(drives.metrics, vec!["storage.read_speed", "storage.write_speed", "storage.busy_percent"]);
+        let disks: Vec<(&str, DeviceKind)> = drives.children.iter().map(|node| (node.id.as_str(), node.kind)).collect();
+        assert_eq!(disks, vec![("system/drives/nvme0n1", DeviceKind::Disk), ("system/drives/sda", DeviceKind::Disk)]);
+    }
+}
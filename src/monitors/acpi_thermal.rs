@@ -0,0 +1,130 @@
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, Platform};
+use crate::model::SharedAppState;
+
+// Firmware thermal zones from WMI's MSAcpi_ThermalZoneTemperature. Coarse and often
+// slow to update, but on laptops without a vendor backend it is the only temperature
+pub struct AcpiThermalMonitor {
+    #[cfg(windows)]
+    connection: Option<super::wmi::WmiConnection>,
+}
+
+impl Default for AcpiThermalMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AcpiThermalMonitor {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(windows)]
+            connection: None,
+        }
+    }
+}
+
+impl HardwareMonitor for AcpiThermalMonitor {
+    fn name(&self) -> &'static str {
+        "ACPI Thermal"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        #[cfg(windows)]
+        {
+            let connection = super::wmi::WmiConnection::connect("ROOT\\WMI")?;
+            // Fail now rather than on every poll when the firmware exposes no zones
+            if read_zones(&connection)?.is_empty() {
+                return Err(MonitorError::DriverMissing("firmware reports no ACPI thermal zones".to_string()));
+            }
+            self.connection = Some(connection);
+            crate::logger::log_info("ACPI thermal monitor initialized using WMI thermal zones");
+            Ok(())
+        }
+
+        #[cfg(not(windows))]
+        {
+            Err(MonitorError::DriverMissing("ACPI thermal zones are read through WMI on Windows only".to_string()))
+        }
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        #[cfg(windows)]
+        {
+            let Some(connection) = self.connection.as_ref() else {
+                return Ok(());
+            };
+            let readings = read_zones(connection)?;
+
+            let mut app_state = state.write();
+            for (instance, tenths_kelvin) in readings {
+                let celsius = tenths_kelvin_to_celsius(tenths_kelvin);
+                let mapping = if celsius.is_some() { "Motherboard temperature sensors" } else { "ignored, out of range" };
+                crate::logger::trace_sensor("ACPI Thermal", &instance, tenths_kelvin as f64, mapping);
+                if let Some(celsius) = celsius {
+                    app_state.motherboard.temperature_sensors.update(&thermal_zone_label(&instance), celsius);
+                }
+            }
+            Ok(())
+        }
+
+        #[cfg(not(windows))]
+        {
+            let _ = state;
+            Ok(())
+        }
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        info.platform == Platform::Windows
+    }
+}
+
+// Thermal zones report tenths of a kelvin. Firmware without a real sensor behind the
+// zone reports 0 K or a fixed 0 °C, neither of which is worth plotting
+pub fn tenths_kelvin_to_celsius(tenths_kelvin: u32) -> Option<f32> {
+    let celsius = tenths_kelvin as f32 / 10.0 - 273.15;
+    (celsius > 0.5 && celsius < 150.0).then_some(celsius)
+}
+
+// "ACPI\ThermalZone\TZ00_0" becomes "Thermal Zone TZ00"
+pub fn thermal_zone_label(instance: &str) -> String {
+    let zone = instance.rsplit('\\').next().unwrap_or(instance);
+    let zone = zone.strip_suffix("_0").unwrap_or(zone);
+    format!("Thermal Zone {}", zone)
+}
+
+// Instance name and temperature in tenths of a kelvin for every zone
+#[cfg(windows)]
+fn read_zones(connection: &super::wmi::WmiConnection) -> Result<Vec<(String, u32)>, MonitorError> {
+    use super::wmi::property;
+
+    let mut zones = Vec::new();
+    for zone in connection.query("SELECT InstanceName, CurrentTemperature FROM MSAcpi_ThermalZoneTemperature")? {
+        let Ok(tenths_kelvin) = u32::try_from(&property(&zone, "CurrentTemperature")?) else {
+            continue;
+        };
+        zones.push((property(&zone, "InstanceName")?.to_string(), tenths_kelvin));
+    }
+    Ok(zones)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenths_kelvin_to_celsius() {
+        let celsius = tenths_kelvin_to_celsius(3232).unwrap();
+        assert!((celsius - 50.05).abs() < 0.01);
+        // Placeholder zones without a real sensor
+        assert_eq!(tenths_kelvin_to_celsius(0), None);
+        assert_eq!(tenths_kelvin_to_celsius(2732), None);
+    }
+
+    #[test]
+    fn test_thermal_zone_label() {
+        assert_eq!(thermal_zone_label("ACPI\\ThermalZone\\TZ00_0"), "Thermal Zone TZ00");
+        assert_eq!(thermal_zone_label("ACPI\\ThermalZone\\CPUZ_0"), "Thermal Zone CPUZ");
+        assert_eq!(thermal_zone_label("THRM"), "Thermal Zone THRM");
+    }
+}
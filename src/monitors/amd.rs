@@ -1,10 +1,16 @@
-use crate::hardware_detection::{HardwareMonitor, HardwareInfo, GpuVendor, CpuVendor};
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, GpuVendor, CpuVendor};
 use crate::model::SharedAppState;
 
 pub struct AmdMonitor {
     initialized: bool,
 }
 
+impl Default for AmdMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AmdMonitor {
     pub fn new() -> Self {
         Self {
@@ -14,7 +20,11 @@ impl AmdMonitor {
 }
 
 impl HardwareMonitor for AmdMonitor {
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn name(&self) -> &'static str {
+        "AMD"
+    }
+    
+    fn initialize(&mut self) -> Result<(), MonitorError> {
         // For AMD GPU monitoring, we would typically use:
         // - ADL (AMD Display Library) for older GPUs
         // - ROCm for newer GPUs
@@ -34,11 +44,11 @@ impl HardwareMonitor for AmdMonitor {
         
         #[cfg(not(feature = "amd"))]
         {
-            Err("AMD support not compiled in".into())
+            Err(MonitorError::DriverMissing("AMD support not compiled in".to_string()))
         }
     }
     
-    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), Box<dyn std::error::Error>> {
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
         if !self.initialized {
             return Ok(());
         }
@@ -51,6 +61,12 @@ impl HardwareMonitor for AmdMonitor {
             // - CPU voltage, power consumption via AMD-specific APIs
             // - Thermal throttling detection
             
+            // amdgpu reports memory controller busy time separately from VRAM allocation
+            #[cfg(target_os = "linux")]
+            if let Some(percent) = mem_busy_percent(std::path::Path::new("/sys/class/drm")) {
+                state.write().gpu.memory_bandwidth.update(percent);
+            }
+            
             let mut _app_state = state.write();
             
             // Placeholder implementation
@@ -65,4 +81,54 @@ impl HardwareMonitor for AmdMonitor {
     fn supports_hardware(&self, info: &HardwareInfo) -> bool {
         info.gpu_vendors.contains(&GpuVendor::AMD) || info.cpu_vendor == CpuVendor::AMD
     }
-}
\ No newline at end of file
+}
+
+// mem_busy_percent of the first amdgpu card under /sys/class/drm
+pub fn mem_busy_percent(drm: &std::path::Path) -> Option<u32> {
+    let mut cards: Vec<_> = std::fs::read_dir(drm)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("card") && !name.contains('-'))
+        })
+        .collect();
+    cards.sort();
+    cards.iter().find_map(|card| {
+        let device = card.join("device");
+        let vendor = std::fs::read_to_string(device.join("vendor")).ok()?;
+        if vendor.trim() != "0x1002" {
+            return None;
+        }
+        std::fs::read_to_string(device.join("mem_busy_percent")).ok()?.trim().parse().ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mem_busy_percent_from_sysfs() {
+        let drm = std::env::temp_dir().join(format!("spd_amd_drm_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&drm);
+        // An Intel iGPU first, then the AMD card; connectors like card1-DP-1 are skipped
+        for (card, vendor, busy) in [("card0", "0x8086", None), ("card1", "0x1002", Some("37\n"))] {
+            let device = drm.join(card).join("device");
+            std::fs::create_dir_all(&device).unwrap();
+            std::fs::write(device.join("vendor"), format!("{}\n", vendor)).unwrap();
+            if let Some(busy) = busy {
+                std::fs::write(device.join("mem_busy_percent"), busy).unwrap();
+            }
+        }
+        std::fs::create_dir_all(drm.join("card1-DP-1")).unwrap();
+
+        let busy = mem_busy_percent(&drm);
+        let missing = mem_busy_percent(&drm.join("missing"));
+        let _ = std::fs::remove_dir_all(&drm);
+
+        assert_eq!(busy, Some(37));
+        assert_eq!(missing, None);
+    }
+}
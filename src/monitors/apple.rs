@@ -1,73 +1,411 @@
-use crate::hardware_detection::{HardwareMonitor, HardwareInfo, GpuVendor, CpuVendor, Platform};
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, Platform};
 use crate::model::SharedAppState;
 
 pub struct AppleMonitor {
     initialized: bool,
+    #[cfg(all(target_os = "macos", feature = "apple"))]
+    smc: Option<smc::Smc>,
+    #[cfg(all(target_os = "macos", feature = "apple"))]
+    sensors: Vec<SmcSensor>, // Temperature keys found on this machine
+    #[cfg(all(target_os = "macos", feature = "apple"))]
+    fan_count: u8,
+}
+
+impl Default for AppleMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AppleMonitor {
     pub fn new() -> Self {
         Self {
             initialized: false,
+            #[cfg(all(target_os = "macos", feature = "apple"))]
+            smc: None,
+            #[cfg(all(target_os = "macos", feature = "apple"))]
+            sensors: Vec::new(),
+            #[cfg(all(target_os = "macos", feature = "apple"))]
+            fan_count: 0,
         }
     }
 }
 
 impl HardwareMonitor for AppleMonitor {
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // For Apple Silicon monitoring, we would use:
-        // - IOKit framework for hardware information
-        // - powermetrics command-line tool
-        // - Metal Performance Shaders for GPU metrics
-        // - System Management Controller (SMC) for temperatures and fans
-        // - Activity Monitor APIs
-        
+    fn name(&self) -> &'static str {
+        "Apple"
+    }
+    
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        // Fans and temperatures come from the System Management Controller, which both
+        // Intel Macs and Apple Silicon expose through the AppleSMC IOKit service
         #[cfg(all(target_os = "macos", feature = "apple"))]
         {
-            // Apple-specific initialization would go here
-            // This might include setting up IOKit connections
-            // or Metal device enumeration
+            let mut smc = smc::Smc::open()?;
+            
+            // Sensor keys differ between models, so enumerate what this SMC offers
+            for index in 0..smc.key_count()? {
+                let key = smc.key_at(index)?;
+                if let Some((device, label)) = classify_smc_key(&key) {
+                    self.sensors.push(SmcSensor { key, device, label });
+                }
+            }
+            self.fan_count = smc
+                .read("FNum")
+                .ok()
+                .and_then(|(data_type, bytes)| decode_smc_value(&data_type, &bytes))
+                .unwrap_or(0.0) as u8;
             
+            self.smc = Some(smc);
             self.initialized = true;
-            crate::logger::log_info("Apple monitor initialized (placeholder)");
+            crate::logger::log_info(&format!(
+                "Apple monitor initialized using SMC ({} temperature sensors, {} fans)",
+                self.sensors.len(),
+                self.fan_count
+            ));
             Ok(())
         }
         
         #[cfg(not(all(target_os = "macos", feature = "apple")))]
         {
-            Err("Apple support not available on this platform".into())
+            Err(MonitorError::DriverMissing("Apple support not available on this platform".to_string()))
         }
     }
     
-    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), Box<dyn std::error::Error>> {
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
         if !self.initialized {
             return Ok(());
         }
         
         #[cfg(all(target_os = "macos", feature = "apple"))]
         {
-            // Apple Silicon metrics would be implemented here
-            // This would include:
-            // - CPU efficiency/performance core utilization
-            // - Neural Engine utilization (if applicable)
-            // - GPU utilization via Metal
-            // - Unified memory bandwidth
-            // - Power consumption via powermetrics
-            // - Thermal state via IOKit
+            let Some(smc) = self.smc.as_mut() else {
+                return Ok(());
+            };
             
-            let mut _app_state = state.write();
+            // Read everything before taking the state lock; a key that fails to read is skipped
+            let mut read = |key: &str| {
+                smc.read(key).ok().and_then(|(data_type, bytes)| decode_smc_value(&data_type, &bytes))
+            };
+            let temperatures: Vec<(&SmcSensor, f32)> = self
+                .sensors
+                .iter()
+                .filter_map(|sensor| read(&sensor.key).map(|celsius| (sensor, celsius)))
+                .filter(|(_, celsius)| is_plausible_temperature(*celsius))
+                .collect();
+            let fans: Vec<f32> = (0..self.fan_count).filter_map(|fan| read(&format!("F{}Ac", fan))).collect();
             
-            // Placeholder implementation
-            // In production, this would make actual Apple framework calls
+            let mut app_state = state.write();
+            let mut battery: Option<(&str, f32)> = None;
+            for (sensor, celsius) in &temperatures {
+                match sensor.device {
+                    SmcDevice::Cpu => app_state.cpu.temperature_sensors.update(&sensor.label, *celsius),
+                    SmcDevice::Gpu => app_state.gpu.temperature_sensors.update(&sensor.label, *celsius),
+                    SmcDevice::Chipset => {
+                        app_state.motherboard.chipset_temperature.update(*celsius);
+                        app_state.note_source_detail("motherboard.chipset_temperature", format!("SMC key {}", sensor.key));
+                    }
+                    SmcDevice::Battery => {
+                        // Packs with several cells report each; the hottest one matters
+                        if battery.is_none_or(|(_, hottest)| *celsius > hottest) {
+                            battery = Some((sensor.key.as_str(), *celsius));
+                        }
+                    }
+                    SmcDevice::Motherboard => app_state.motherboard.temperature_sensors.update(&sensor.label, *celsius),
+                }
+            }
+            if let Some((key, celsius)) = battery {
+                app_state.motherboard.battery_temperature.update(celsius);
+                app_state.note_source_detail("motherboard.battery_temperature", format!("SMC key {}", key));
+            }
             
-            crate::logger::log_info("Apple metrics updated (placeholder)");
+            // Macs have no separate chassis fans, so the fastest system fan stands in for them
+            if let Some(rpm) = fans.iter().copied().reduce(f32::max) {
+                app_state.motherboard.chassis_fan_speed.update(rpm.round() as u32);
+                app_state.note_source_detail("motherboard.chassis_fan_speed", format!("fastest of {} SMC fans", fans.len()));
+            }
         }
         
+        #[cfg(not(all(target_os = "macos", feature = "apple")))]
+        let _ = state;
+        
         Ok(())
     }
     
     fn supports_hardware(&self, info: &HardwareInfo) -> bool {
-        (info.cpu_vendor == CpuVendor::Apple || info.gpu_vendors.contains(&GpuVendor::Apple)) 
-        && info.platform == Platform::MacOS
+        // Intel Macs have an SMC too
+        info.platform == Platform::MacOS
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmcDevice {
+    Cpu,
+    Gpu,
+    Chipset,
+    Battery,
+    Motherboard,
+}
+
+#[cfg(all(target_os = "macos", feature = "apple"))]
+#[derive(Debug, Clone)]
+struct SmcSensor {
+    key: String,
+    device: SmcDevice,
+    label: String,
+}
+
+// Temperature keys worth showing, with a readable label. Intel Macs use the documented
+// TC/TG keys; Apple Silicon die sensors (Tp/Te/Tg) have no public per-core mapping,
+// so they keep their key in the label
+pub fn classify_smc_key(key: &str) -> Option<(SmcDevice, String)> {
+    let known = match key {
+        "TC0P" => Some((SmcDevice::Cpu, "CPU Proximity")),
+        "TC0D" | "TC0E" | "TC0F" => Some((SmcDevice::Cpu, "CPU Die")),
+        "TG0P" => Some((SmcDevice::Gpu, "GPU Proximity")),
+        "TG0D" => Some((SmcDevice::Gpu, "GPU Die")),
+        "TPCD" => Some((SmcDevice::Chipset, "PCH Die")),
+        "Ts0P" => Some((SmcDevice::Motherboard, "Palm Rest")),
+        "TA0P" => Some((SmcDevice::Motherboard, "Ambient")),
+        _ => None,
+    };
+    if let Some((device, label)) = known {
+        // TC0D/TC0E/TC0F are the same die read three ways, so keep them apart
+        let label = if key.starts_with("TC0") && key != "TC0P" { format!("{} ({})", label, key) } else { label.to_string() };
+        return Some((device, label));
+    }
+    
+    let bytes = key.as_bytes();
+    if bytes.len() != 4 || bytes[0] != b'T' {
+        return None;
+    }
+    match (bytes[1], bytes[2], bytes[3]) {
+        // Per-core sensors on Intel Macs: TC1C, TC2C, ...
+        (b'C', core @ b'1'..=b'9', b'C') => Some((SmcDevice::Cpu, format!("CPU Core {}", core - b'0'))),
+        (b'p', _, _) => Some((SmcDevice::Cpu, format!("P-cluster {}", key))),
+        (b'e', _, _) => Some((SmcDevice::Cpu, format!("E-cluster {}", key))),
+        (b'g', _, _) => Some((SmcDevice::Gpu, format!("GPU {}", key))),
+        (b'B', b'0'..=b'9', b'T') => Some((SmcDevice::Battery, format!("Battery {}", key))),
+        _ => None,
+    }
+}
+
+// Decode the SMC's fixed-point and float encodings. Integers and fixed point are
+// big-endian; `flt ` values on Apple Silicon are native little-endian
+pub fn decode_smc_value(data_type: &[u8; 4], bytes: &[u8]) -> Option<f32> {
+    let be16 = || Some(u16::from_be_bytes([*bytes.first()?, *bytes.get(1)?]));
+    match data_type {
+        b"sp78" => Some(be16()? as i16 as f32 / 256.0),
+        b"fpe2" => Some(be16()? as f32 / 4.0),
+        b"flt " => Some(f32::from_le_bytes(bytes.get(..4)?.try_into().ok()?)),
+        b"ui8 " => bytes.first().map(|value| *value as f32),
+        b"ui16" => Some(be16()? as f32),
+        b"ui32" => Some(u32::from_be_bytes(bytes.get(..4)?.try_into().ok()?) as f32),
+        _ => None,
+    }
+}
+
+// Absent sensors read as 0 and some report sentinel values well above any real reading
+#[cfg(all(target_os = "macos", feature = "apple"))]
+fn is_plausible_temperature(celsius: f32) -> bool {
+    celsius > 0.0 && celsius < 130.0
+}
+
+pub fn fourcc(key: &str) -> u32 {
+    key.bytes().take(4).fold(0, |code, byte| (code << 8) | byte as u32)
+}
+
+#[cfg(all(target_os = "macos", feature = "apple"))]
+mod smc {
+    use std::collections::HashMap;
+    use std::ffi::{c_char, c_void};
+    use crate::hardware_detection::MonitorError;
+    
+    const KERNEL_INDEX_SMC: u32 = 2;
+    const CMD_READ_BYTES: u8 = 5;
+    const CMD_READ_INDEX: u8 = 8;
+    const CMD_READ_KEYINFO: u8 = 9;
+    
+    // Mirrors the AppleSMC user client's SMCKeyData_t (80 bytes)
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct Version {
+        major: u8,
+        minor: u8,
+        build: u8,
+        reserved: u8,
+        release: u16,
+    }
+    
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct PowerLimits {
+        version: u16,
+        length: u16,
+        cpu: u32,
+        gpu: u32,
+        memory: u32,
     }
-}
\ No newline at end of file
+    
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct KeyInfo {
+        data_size: u32,
+        data_type: u32,
+        data_attributes: u8,
+    }
+    
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct KeyData {
+        key: u32,
+        version: Version,
+        power_limits: PowerLimits,
+        info: KeyInfo,
+        result: u8,
+        status: u8,
+        data8: u8,
+        data32: u32,
+        bytes: [u8; 32],
+    }
+    
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const c_char) -> *mut c_void;
+        fn IOServiceGetMatchingService(main_port: u32, matching: *mut c_void) -> u32;
+        fn IOServiceOpen(service: u32, owning_task: u32, kind: u32, connection: *mut u32) -> i32;
+        fn IOServiceClose(connection: u32) -> i32;
+        fn IOObjectRelease(object: u32) -> i32;
+        fn IOConnectCallStructMethod(
+            connection: u32,
+            selector: u32,
+            input: *const c_void,
+            input_size: usize,
+            output: *mut c_void,
+            output_size: *mut usize,
+        ) -> i32;
+    }
+    
+    extern "C" {
+        static mach_task_self_: u32;
+    }
+    
+    pub struct Smc {
+        connection: u32,
+        key_info: HashMap<u32, KeyInfo>, // Cached, since every read needs the key's size
+    }
+    
+    impl Smc {
+        pub fn open() -> Result<Self, MonitorError> {
+            unsafe {
+                // IOServiceGetMatchingService consumes the matching dictionary
+                let matching = IOServiceMatching(b"AppleSMC\0".as_ptr() as *const c_char);
+                let service = IOServiceGetMatchingService(0, matching);
+                if service == 0 {
+                    return Err(MonitorError::DriverMissing("AppleSMC service not found".to_string()));
+                }
+                let mut connection = 0;
+                let result = IOServiceOpen(service, mach_task_self_, 0, &mut connection);
+                IOObjectRelease(service);
+                if result != 0 {
+                    return Err(MonitorError::PermissionDenied(format!("opening AppleSMC failed ({:#x})", result)));
+                }
+                Ok(Self { connection, key_info: HashMap::new() })
+            }
+        }
+        
+        fn call(&self, input: &KeyData) -> Result<KeyData, MonitorError> {
+            let mut output = KeyData::default();
+            let mut output_size = std::mem::size_of::<KeyData>();
+            let result = unsafe {
+                IOConnectCallStructMethod(
+                    self.connection,
+                    KERNEL_INDEX_SMC,
+                    input as *const KeyData as *const c_void,
+                    std::mem::size_of::<KeyData>(),
+                    &mut output as *mut KeyData as *mut c_void,
+                    &mut output_size,
+                )
+            };
+            if result != 0 {
+                return Err(MonitorError::DeviceGone(format!("SMC call failed ({:#x})", result)));
+            }
+            if output.result != 0 {
+                return Err(MonitorError::Parse(format!("SMC returned status {}", output.result)));
+            }
+            Ok(output)
+        }
+        
+        pub fn key_count(&mut self) -> Result<u32, MonitorError> {
+            let (data_type, bytes) = self.read("#KEY")?;
+            super::decode_smc_value(&data_type, &bytes)
+                .map(|count| count as u32)
+                .ok_or_else(|| MonitorError::Parse("unreadable SMC key count".to_string()))
+        }
+        
+        pub fn key_at(&self, index: u32) -> Result<String, MonitorError> {
+            let output = self.call(&KeyData { data8: CMD_READ_INDEX, data32: index, ..Default::default() })?;
+            Ok(String::from_utf8_lossy(&output.key.to_be_bytes()).into_owned())
+        }
+        
+        // Data type and raw bytes of a key
+        pub fn read(&mut self, key: &str) -> Result<([u8; 4], Vec<u8>), MonitorError> {
+            let code = super::fourcc(key);
+            let info = match self.key_info.get(&code) {
+                Some(info) => *info,
+                None => {
+                    let info = self.call(&KeyData { key: code, data8: CMD_READ_KEYINFO, ..Default::default() })?.info;
+                    self.key_info.insert(code, info);
+                    info
+                }
+            };
+            let output = self.call(&KeyData { key: code, info, data8: CMD_READ_BYTES, ..Default::default() })?;
+            let size = (info.data_size as usize).min(output.bytes.len());
+            Ok((info.data_type.to_be_bytes(), output.bytes[..size].to_vec()))
+        }
+    }
+    
+    impl Drop for Smc {
+        fn drop(&mut self) {
+            unsafe {
+                IOServiceClose(self.connection);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_classify_smc_key() {
+        assert_eq!(classify_smc_key("TC0P"), Some((SmcDevice::Cpu, "CPU Proximity".to_string())));
+        assert_eq!(classify_smc_key("TC0E"), Some((SmcDevice::Cpu, "CPU Die (TC0E)".to_string())));
+        assert_eq!(classify_smc_key("TC3C"), Some((SmcDevice::Cpu, "CPU Core 3".to_string())));
+        assert_eq!(classify_smc_key("Tp09"), Some((SmcDevice::Cpu, "P-cluster Tp09".to_string())));
+        assert_eq!(classify_smc_key("Tg0f"), Some((SmcDevice::Gpu, "GPU Tg0f".to_string())));
+        assert_eq!(classify_smc_key("TB1T"), Some((SmcDevice::Battery, "Battery TB1T".to_string())));
+        assert_eq!(classify_smc_key("TPCD"), Some((SmcDevice::Chipset, "PCH Die".to_string())));
+        // Fans, voltages and undocumented sensors are left out
+        assert_eq!(classify_smc_key("F0Ac"), None);
+        assert_eq!(classify_smc_key("TW0P"), None);
+    }
+    
+    #[test]
+    fn test_decode_smc_value() {
+        assert_eq!(decode_smc_value(b"sp78", &[0x2d, 0x80]), Some(45.5));
+        assert_eq!(decode_smc_value(b"fpe2", &[0x1f, 0x40]), Some(2000.0));
+        assert_eq!(decode_smc_value(b"flt ", &1234.5f32.to_le_bytes()), Some(1234.5));
+        assert_eq!(decode_smc_value(b"ui8 ", &[2]), Some(2.0));
+        assert_eq!(decode_smc_value(b"ui32", &[0, 0, 0x04, 0xd2]), Some(1234.0));
+        assert_eq!(decode_smc_value(b"sp78", &[0x2d]), None);
+        assert_eq!(decode_smc_value(b"ch8*", b"abcd"), None);
+    }
+    
+    #[test]
+    fn test_fourcc() {
+        assert_eq!(fourcc("#KEY"), 0x234b_4559);
+        assert_eq!(fourcc("TC0P"), u32::from_be_bytes(*b"TC0P"));
+    }
+}
@@ -0,0 +1,425 @@
+use std::path::Path;
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo};
+use crate::model::{AppState, BatteryStatus, SharedAppState};
+
+// Battery drain, charging power and display brightness, so a laptop's battery life can
+// be set against what the CPU, GPU and screen draw
+pub struct BatteryMonitor {
+    initialized: bool,
+    #[cfg(windows)]
+    connection: Option<super::wmi::WmiConnection>,
+}
+
+impl Default for BatteryMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatteryMonitor {
+    pub fn new() -> Self {
+        Self {
+            initialized: false,
+            #[cfg(windows)]
+            connection: None,
+        }
+    }
+
+    fn sample(&self) -> Result<PowerSample, MonitorError> {
+        #[cfg(target_os = "linux")]
+        {
+            Ok(sample_from_sysfs(Path::new("/sys/class/power_supply"), Path::new("/sys/class/backlight")))
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            let output = std::process::Command::new("ioreg").args(["-r", "-n", "AppleSmartBattery"]).output()?;
+            Ok(parse_smart_battery(&String::from_utf8_lossy(&output.stdout)))
+        }
+
+        #[cfg(windows)]
+        {
+            match self.connection.as_ref() {
+                Some(connection) => sample_from_wmi(connection),
+                None => Ok(PowerSample::default()),
+            }
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+        {
+            Ok(PowerSample::default())
+        }
+    }
+}
+
+impl HardwareMonitor for BatteryMonitor {
+    fn name(&self) -> &'static str {
+        "Battery"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        #[cfg(windows)]
+        {
+            self.connection = Some(super::wmi::WmiConnection::connect("ROOT\\WMI")?);
+        }
+
+        // Desktops have neither a battery nor a backlight to read
+        if self.sample()?.is_empty() {
+            return Err(MonitorError::DriverMissing("no battery or display backlight found".to_string()));
+        }
+        self.initialized = true;
+        crate::logger::log_info("Battery monitor initialized");
+        Ok(())
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        if !self.initialized {
+            return Ok(());
+        }
+        let sample = self.sample()?;
+        sample.apply(&mut state.write());
+        Ok(())
+    }
+
+    fn supports_hardware(&self, _info: &HardwareInfo) -> bool {
+        // Whether this is a laptop is only known once the battery is looked for
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PowerSample {
+    pub battery_watts: Option<f32>, // Flowing out of the battery, or into it while charging
+    pub charging: bool,
+    pub brightness_percent: Option<f32>,
+    pub on_battery: Option<bool>,   // None when there's no charger state to read
+    pub charge_percent: Option<f32>,
+    pub voltage: Option<f32>,
+    pub energy_wh: Option<f64>,     // Left in the battery
+    pub full_wh: Option<f64>,       // When fully charged, as the battery has worn
+    pub minutes_remaining: Option<f32>, // The OS's own estimate, preferred where given
+    pub status: Option<BatteryStatus>,
+}
+
+impl PowerSample {
+    pub fn is_empty(&self) -> bool {
+        self.battery_watts.is_none() && self.brightness_percent.is_none() && self.charge_percent.is_none()
+    }
+
+    // Time to empty at the current drain, or to full at the current charging power.
+    // Near-zero power would give absurd estimates, e.g. while the charger tops up
+    pub fn estimate_minutes_remaining(&self) -> Option<f32> {
+        let watts = self.battery_watts.filter(|watts| *watts >= 0.5)? as f64;
+        let hours = match self.status? {
+            BatteryStatus::Discharging => self.energy_wh? / watts,
+            BatteryStatus::Charging => (self.full_wh? - self.energy_wh?).max(0.0) / watts,
+            BatteryStatus::Full | BatteryStatus::NotCharging => return None,
+        };
+        Some((hours * 60.0) as f32)
+    }
+
+    // Battery power is split by direction, so plugging in never reads as drain
+    pub fn apply(&self, state: &mut AppState) {
+        if let Some(watts) = self.battery_watts {
+            let (discharge, charging) = if self.charging { (0.0, watts) } else { (watts, 0.0) };
+            state.power.battery_discharge.update(discharge);
+            state.power.charging_power.update(charging);
+        }
+        if let Some(percent) = self.brightness_percent {
+            state.power.display_brightness.update(percent);
+        }
+        if let Some(on_battery) = self.on_battery {
+            state.power.on_battery.update(on_battery);
+        }
+        let charge_percent = self.charge_percent.or_else(|| {
+            let (energy, full) = self.energy_wh.zip(self.full_wh.filter(|full| *full > 0.0))?;
+            Some((energy / full * 100.0) as f32)
+        });
+        if let Some(percent) = charge_percent {
+            state.battery.charge_percent.update(percent.clamp(0.0, 100.0));
+        }
+        if let Some(voltage) = self.voltage {
+            state.battery.voltage.update(voltage);
+        }
+        if let Some(minutes) = self.minutes_remaining.or_else(|| self.estimate_minutes_remaining()) {
+            state.battery.time_remaining.update(minutes);
+        }
+        if self.status.is_some() {
+            state.battery.status = self.status;
+        }
+    }
+}
+
+// `<power_supply>/BAT*/{power_now | current_now + voltage_now, status, capacity,
+// energy_now + energy_full | charge_now + charge_full}`, the charger's
+// `<power_supply>/*/online` and `<backlight>/*/{actual_brightness, max_brightness}`.
+// Values are in µW, µA, µV, µWh and µAh
+pub fn sample_from_sysfs(power_supply: &Path, backlight: &Path) -> PowerSample {
+    let read = |path: &Path, name: &str| std::fs::read_to_string(path.join(name)).ok().map(|value| value.trim().to_string());
+    let number = |path: &Path, name: &str| read(path, name)?.parse::<f64>().ok();
+    let entries = |root: &Path| -> Vec<std::path::PathBuf> {
+        let mut paths: Vec<_> = std::fs::read_dir(root)
+            .map(|entries| entries.filter_map(|entry| Some(entry.ok()?.path())).collect())
+            .unwrap_or_default();
+        paths.sort();
+        paths
+    };
+
+    let mut sample = PowerSample::default();
+    let mut mains_online = None;
+    for supply in entries(power_supply) {
+        if read(&supply, "type").as_deref() == Some("Mains") {
+            let online = read(&supply, "online").as_deref() == Some("1");
+            mains_online = Some(mains_online.unwrap_or(false) || online);
+            continue;
+        }
+        // Wireless mice and keyboards report as "Device"-scoped batteries
+        if read(&supply, "type").as_deref() != Some("Battery") || read(&supply, "scope").as_deref() == Some("Device") {
+            continue;
+        }
+        let microwatts = number(&supply, "power_now")
+            .or_else(|| Some(number(&supply, "current_now")? * number(&supply, "voltage_now")? / 1_000_000.0));
+        if let Some(microwatts) = microwatts {
+            // Machines with two batteries drain both at once
+            sample.battery_watts = Some(sample.battery_watts.unwrap_or(0.0) + (microwatts.abs() / 1_000_000.0) as f32);
+        }
+        sample.charging |= read(&supply, "status").as_deref() == Some("Charging");
+
+        // Drivers that count charge rather than energy give µAh, converted at the current voltage
+        let voltage = number(&supply, "voltage_now").map(|microvolts| microvolts / 1_000_000.0);
+        let energy = |energy_name: &str, charge_name: &str| {
+            number(&supply, energy_name).or_else(|| Some(number(&supply, charge_name)? * voltage?)).map(|micro| micro / 1_000_000.0)
+        };
+        if let (Some(now), Some(full)) = (energy("energy_now", "charge_now"), energy("energy_full", "charge_full")) {
+            sample.energy_wh = Some(sample.energy_wh.unwrap_or(0.0) + now);
+            sample.full_wh = Some(sample.full_wh.unwrap_or(0.0) + full);
+        }
+        sample.charge_percent = sample.charge_percent.or_else(|| Some(number(&supply, "capacity")? as f32));
+        sample.voltage = sample.voltage.or(voltage.map(|volts| volts as f32));
+        let status = match read(&supply, "status").as_deref() {
+            Some("Charging") => Some(BatteryStatus::Charging),
+            Some("Discharging") => Some(BatteryStatus::Discharging),
+            Some("Full") => Some(BatteryStatus::Full),
+            Some("Not charging") => Some(BatteryStatus::NotCharging),
+            _ => None,
+        };
+        // With two batteries, one charging or draining decides
+        if matches!(status, Some(BatteryStatus::Charging | BatteryStatus::Discharging)) || sample.status.is_none() {
+            sample.status = status;
+        }
+    }
+    // Both batteries together; a single reading's capacity is only used without energies
+    if let (Some(energy), Some(full)) = (sample.energy_wh, sample.full_wh.filter(|full| *full > 0.0)) {
+        sample.charge_percent = Some((energy / full * 100.0) as f32);
+    }
+    // Only a laptop's charger is worth reporting; desktops list no battery
+    if sample.battery_watts.is_some() {
+        sample.on_battery = mains_online.map(|online| !online);
+    }
+
+    sample.brightness_percent = entries(backlight).iter().find_map(|panel| {
+        let max = number(panel, "max_brightness").filter(|max| *max > 0.0)?;
+        let current = number(panel, "actual_brightness").or_else(|| number(panel, "brightness"))?;
+        Some((current / max * 100.0) as f32)
+    });
+    sample
+}
+
+// `ioreg -r -n AppleSmartBattery`. Amperage is signed mA, printed as an unsigned
+// 64-bit number while discharging; voltage is in mV. CurrentCapacity is a percentage
+// of MaxCapacity on Apple silicon and mAh on Intel Macs, so only their ratio is used.
+// TimeRemaining is in minutes, 65535 while macOS is still estimating
+pub fn parse_smart_battery(output: &str) -> PowerSample {
+    let value = |key: &str| {
+        let prefix = format!("\"{}\" = ", key);
+        output.lines().find_map(|line| line.trim().strip_prefix(prefix.as_str()).map(str::trim))
+    };
+    let amperage = value("InstantAmperage")
+        .or_else(|| value("Amperage"))
+        .and_then(|raw| raw.parse::<i64>().ok().or_else(|| raw.parse::<u64>().ok().map(|raw| raw as i64)));
+    let voltage = value("Voltage").and_then(|raw| raw.parse::<f64>().ok());
+    let number = |key: &str| value(key).and_then(|raw| raw.parse::<f64>().ok());
+    let charging = value("IsCharging") == Some("Yes");
+    let on_battery = value("ExternalConnected").map(|connected| connected == "No");
+    let status = match (value("FullyCharged"), charging, on_battery) {
+        (Some("Yes"), _, Some(false)) => Some(BatteryStatus::Full),
+        (_, true, _) => Some(BatteryStatus::Charging),
+        (_, false, Some(true)) => Some(BatteryStatus::Discharging),
+        (_, false, Some(false)) => Some(BatteryStatus::NotCharging),
+        _ => None,
+    };
+
+    PowerSample {
+        battery_watts: amperage.zip(voltage).map(|(ma, mv)| (ma as f64 * mv / 1_000_000.0).abs() as f32),
+        charging,
+        brightness_percent: None, // Not exposed outside private display frameworks
+        on_battery,
+        charge_percent: number("CurrentCapacity")
+            .zip(number("MaxCapacity").filter(|max| *max > 0.0))
+            .map(|(current, max)| (current / max * 100.0) as f32),
+        voltage: voltage.map(|mv| (mv / 1000.0) as f32),
+        energy_wh: None,
+        full_wh: None,
+        minutes_remaining: number("TimeRemaining").filter(|minutes| *minutes < 65535.0).map(|minutes| minutes as f32),
+        status,
+    }
+}
+
+// ROOT\WMI BatteryStatus reports rates in mW, capacity in mWh and voltage in mV;
+// WmiMonitorBrightness is missing on external monitors and desktops, which just leaves
+// brightness empty
+#[cfg(windows)]
+fn sample_from_wmi(connection: &super::wmi::WmiConnection) -> Result<PowerSample, MonitorError> {
+    use super::wmi::property;
+
+    let mut sample = PowerSample::default();
+    let batteries = connection
+        .query("SELECT ChargeRate, DischargeRate, PowerOnline, Charging, Discharging, RemainingCapacity, Voltage FROM BatteryStatus")
+        .unwrap_or_default();
+    for battery in &batteries {
+        let flag = |name: &str| property(battery, name).ok().and_then(|value| bool::try_from(&value).ok());
+        if let Some(online) = flag("PowerOnline") {
+            sample.on_battery = Some(!online);
+        }
+        let rate = |name: &str| property(battery, name).ok().and_then(|value| u32::try_from(&value).ok()).unwrap_or(0);
+        let (charge, discharge) = (rate("ChargeRate"), rate("DischargeRate"));
+        sample.charging |= charge > 0;
+        let milliwatts = if charge > 0 { charge } else { discharge };
+        sample.battery_watts = Some(sample.battery_watts.unwrap_or(0.0) + milliwatts as f32 / 1000.0);
+        sample.energy_wh = Some(sample.energy_wh.unwrap_or(0.0) + rate("RemainingCapacity") as f64 / 1000.0);
+        if rate("Voltage") > 0 {
+            sample.voltage = Some(rate("Voltage") as f32 / 1000.0);
+        }
+        sample.status = match (flag("Charging"), flag("Discharging"), flag("PowerOnline")) {
+            (Some(true), _, _) => Some(BatteryStatus::Charging),
+            (_, Some(true), _) => Some(BatteryStatus::Discharging),
+            (_, _, Some(true)) => Some(BatteryStatus::NotCharging),
+            _ => sample.status,
+        };
+    }
+    if let Ok(capacities) = connection.query("SELECT FullChargedCapacity FROM BatteryFullChargedCapacity") {
+        let full: u32 = capacities
+            .iter()
+            .filter_map(|battery| property(battery, "FullChargedCapacity").ok().and_then(|value| u32::try_from(&value).ok()))
+            .sum();
+        if full > 0 {
+            sample.full_wh = Some(full as f64 / 1000.0);
+        }
+    }
+    // Windows has no full state of its own; a plugged-in battery that isn't charging may be either
+    if sample.status == Some(BatteryStatus::NotCharging) && sample.energy_wh.zip(sample.full_wh).is_some_and(|(energy, full)| energy >= full * 0.99) {
+        sample.status = Some(BatteryStatus::Full);
+    }
+
+    if let Ok(panels) = connection.query("SELECT CurrentBrightness FROM WmiMonitorBrightness") {
+        sample.brightness_percent = panels
+            .first()
+            .and_then(|panel| property(panel, "CurrentBrightness").ok())
+            .and_then(|value| u32::try_from(&value).ok())
+            .map(|percent| percent as f32);
+    }
+    Ok(sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_sample_from_sysfs() {
+        let root = std::env::temp_dir().join("spd_battery_sysfs_test");
+        let _ = fs::remove_dir_all(&root);
+        let write = |path: &str, contents: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).expect("Failed to create dir");
+            fs::write(path, contents).expect("Failed to write file");
+        };
+        write("power_supply/BAT0/type", "Battery\n");
+        write("power_supply/BAT0/status", "Discharging\n");
+        write("power_supply/BAT0/power_now", "12500000\n");
+        write("power_supply/BAT0/voltage_now", "12000000\n");
+        write("power_supply/BAT0/energy_now", "30000000\n");
+        write("power_supply/BAT0/energy_full", "50000000\n");
+        write("power_supply/BAT0/capacity", "60\n");
+        write("power_supply/BAT1/type", "Battery\n");
+        write("power_supply/BAT1/current_now", "500000\n");
+        write("power_supply/BAT1/voltage_now", "11000000\n");
+        write("power_supply/BAT1/charge_now", "1000000\n");
+        write("power_supply/BAT1/charge_full", "4000000\n");
+        write("power_supply/BAT1/status", "Full\n");
+        write("power_supply/hidpp_battery_0/type", "Battery\n");
+        write("power_supply/hidpp_battery_0/scope", "Device\n");
+        write("power_supply/hidpp_battery_0/power_now", "99000000\n");
+        write("power_supply/AC/type", "Mains\n");
+        write("power_supply/AC/online", "0\n");
+        write("backlight/intel_backlight/max_brightness", "96000\n");
+        write("backlight/intel_backlight/actual_brightness", "48000\n");
+
+        let sample = sample_from_sysfs(&root.join("power_supply"), &root.join("backlight"));
+        assert_eq!(sample.battery_watts, Some(18.0));
+        assert!(!sample.charging);
+        assert_eq!(sample.on_battery, Some(true));
+        assert_eq!(sample.brightness_percent, Some(50.0));
+        // 30 + 11 Wh of 50 + 44 Wh across both batteries, the second counted in µAh
+        assert_eq!(sample.energy_wh, Some(41.0));
+        assert_eq!(sample.full_wh, Some(94.0));
+        assert_eq!(sample.voltage, Some(12.0));
+        assert_eq!(sample.status, Some(BatteryStatus::Discharging));
+        assert_eq!(sample.estimate_minutes_remaining(), Some(136.66667));
+
+        let _ = fs::remove_dir_all(&root);
+        assert!(sample_from_sysfs(&root.join("power_supply"), &root.join("backlight")).is_empty());
+    }
+
+    #[test]
+    fn test_parse_smart_battery() {
+        let output = r#"
++-o AppleSmartBattery  <class AppleSmartBattery, id 0x100000263>
+    {
+      "Amperage" = 18446744073709550616
+      "InstantAmperage" = 18446744073709550616
+      "Voltage" = 12500
+      "CurrentCapacity" = 80
+      "MaxCapacity" = 100
+      "TimeRemaining" = 65535
+      "IsCharging" = No
+      "ExternalConnected" = No
+      "BatteryData" = {"Voltage"=12480}
+    }
+"#;
+        let sample = parse_smart_battery(output);
+        assert_eq!(sample.battery_watts, Some(12.5));
+        assert!(!sample.charging);
+        assert_eq!(sample.on_battery, Some(true));
+        assert_eq!(sample.charge_percent, Some(80.0));
+        assert_eq!(sample.voltage, Some(12.5));
+        assert_eq!(sample.status, Some(BatteryStatus::Discharging));
+        assert_eq!(sample.minutes_remaining, None);
+        assert!(parse_smart_battery("").is_empty());
+    }
+
+    #[test]
+    fn test_power_sample_splits_direction() {
+        let mut state = AppState::default();
+        let sample = PowerSample {
+            battery_watts: Some(30.0),
+            charging: true,
+            brightness_percent: Some(80.0),
+            on_battery: Some(false),
+            energy_wh: Some(20.0),
+            full_wh: Some(50.0),
+            status: Some(BatteryStatus::Charging),
+            ..PowerSample::default()
+        };
+        sample.apply(&mut state);
+        assert_eq!(state.power.charging_power.current, Some(30.0));
+        assert_eq!(state.power.battery_discharge.current, Some(0.0));
+        assert_eq!(state.power.display_brightness.current, Some(80.0));
+        assert_eq!(state.power.on_battery.active, Some(false));
+        // 30 Wh to go at 30 W
+        assert_eq!(state.battery.charge_percent.current, Some(40.0));
+        assert_eq!(state.battery.time_remaining.current, Some(60.0));
+        assert_eq!(state.battery.status, Some(BatteryStatus::Charging));
+    }
+}
@@ -0,0 +1,369 @@
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, Platform};
+use crate::model::{AppState, SharedAppState};
+
+// Fan and temperature sensors that ASUS and Gigabyte boards publish through vendor WMI
+// classes. They cover what generic APIs never expose: chassis/chipset fans, the AIO pump
+// header and VRM temperature
+pub struct BoardWmiMonitor {
+    #[cfg(windows)]
+    backend: Option<backend::VendorBackend>,
+}
+
+impl Default for BoardWmiMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BoardWmiMonitor {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(windows)]
+            backend: None,
+        }
+    }
+}
+
+impl HardwareMonitor for BoardWmiMonitor {
+    fn name(&self) -> &'static str {
+        "Board WMI"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        #[cfg(windows)]
+        {
+            let backend = backend::VendorBackend::open()?;
+            crate::logger::log_info(&format!("Board WMI monitor initialized using {}", backend.describe()));
+            self.backend = Some(backend);
+            Ok(())
+        }
+
+        #[cfg(not(windows))]
+        {
+            Err(MonitorError::DriverMissing("board vendor WMI interfaces are only available on Windows".to_string()))
+        }
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        #[cfg(windows)]
+        {
+            let Some(backend) = self.backend.as_ref() else {
+                return Ok(());
+            };
+            let readings = backend.read()?;
+            apply_board_readings(&mut state.write(), &readings);
+            Ok(())
+        }
+
+        #[cfg(not(windows))]
+        {
+            let _ = state;
+            Ok(())
+        }
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        info.platform == Platform::Windows
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardSensorKind {
+    Temperature,
+    Fan,
+    Other, // Voltages, currents and flow meters
+}
+
+// Where a board sensor's reading ends up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardTarget {
+    ChassisFan,
+    ChipsetFan,
+    AioPump,
+    ChipsetTemperature,
+    VrmTemperature,
+    Temperature, // Listed under the motherboard's temperature sensors only
+    Ignored,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardReading {
+    pub name: String,
+    pub target: BoardTarget,
+    pub value: f32, // Celsius or RPM
+}
+
+// Judged by the header names vendors print on the board, e.g. "Chassis Fan 1",
+// "CHA_FAN1", "W_PUMP+" or "VRM MOS"
+pub fn board_target(name: &str, kind: BoardSensorKind) -> BoardTarget {
+    let name = name.to_ascii_lowercase();
+    let is_chipset = name.contains("chipset") || name.contains("pch");
+    match kind {
+        BoardSensorKind::Fan if name.contains("pump") => BoardTarget::AioPump,
+        BoardSensorKind::Fan if is_chipset => BoardTarget::ChipsetFan,
+        BoardSensorKind::Fan if ["chassis", "cha_", "system", "sys_"].iter().any(|header| name.contains(header)) => {
+            BoardTarget::ChassisFan
+        }
+        BoardSensorKind::Temperature if name.contains("vrm") => BoardTarget::VrmTemperature,
+        BoardSensorKind::Temperature if is_chipset => BoardTarget::ChipsetTemperature,
+        BoardSensorKind::Temperature => BoardTarget::Temperature,
+        _ => BoardTarget::Ignored,
+    }
+}
+
+// ASUS reports each sensor's class and location; the location is more reliable than
+// the name for the chipset and VRM
+pub fn asus_target(name: &str, class: u32, location: u32) -> BoardTarget {
+    const CLASS_TEMPERATURE: u32 = 1;
+    const CLASS_FAN: u32 = 2;
+    const LOCATION_CHIPSET: u32 = 4;
+    const LOCATION_VRM: u32 = 6;
+
+    let kind = match class {
+        CLASS_TEMPERATURE => BoardSensorKind::Temperature,
+        CLASS_FAN => BoardSensorKind::Fan,
+        _ => BoardSensorKind::Other,
+    };
+    match (kind, location) {
+        (BoardSensorKind::Temperature, LOCATION_VRM) => BoardTarget::VrmTemperature,
+        (BoardSensorKind::Temperature, LOCATION_CHIPSET) => BoardTarget::ChipsetTemperature,
+        (BoardSensorKind::Fan, LOCATION_CHIPSET) => BoardTarget::ChipsetFan,
+        _ => board_target(name, kind),
+    }
+}
+
+// Gigabyte's EC class has one method per sensor (getTemp1..., getRpm1...) and no names.
+// RPM 1 is the CPU fan header, which has no motherboard metric
+pub fn gigabyte_sensor(method: &str) -> Option<(String, BoardTarget)> {
+    let lower = method.to_ascii_lowercase();
+    let (kind, index) = if let Some(index) = lower.strip_prefix("gettemp") {
+        (BoardSensorKind::Temperature, index)
+    } else if let Some(index) = lower.strip_prefix("getrpm") {
+        (BoardSensorKind::Fan, index)
+    } else {
+        return None;
+    };
+    let index: u32 = index.parse().ok()?;
+    match kind {
+        BoardSensorKind::Fan if index == 1 => Some((format!("EC Fan {}", index), BoardTarget::Ignored)),
+        BoardSensorKind::Fan => Some((format!("EC Fan {}", index), BoardTarget::ChassisFan)),
+        _ => Some((format!("EC Temperature {}", index), BoardTarget::Temperature)),
+    }
+}
+
+pub fn apply_board_readings(state: &mut AppState, readings: &[BoardReading]) {
+    // Boards have several chassis headers; the fastest fan stands for all of them
+    let mut chassis_fan: Option<f32> = None;
+    for reading in readings {
+        let is_temperature = matches!(
+            reading.target,
+            BoardTarget::Temperature | BoardTarget::ChipsetTemperature | BoardTarget::VrmTemperature
+        );
+        // Unpopulated headers read as placeholders like -128
+        if is_temperature && !crate::temperature::is_plausible(reading.value) {
+            crate::logger::trace_sensor("Board WMI", &reading.name, reading.value as f64, "ignored, placeholder reading");
+            continue;
+        }
+        crate::logger::trace_sensor("Board WMI", &reading.name, reading.value as f64, &format!("{:?}", reading.target));
+        let motherboard = &mut state.motherboard;
+        match reading.target {
+            BoardTarget::ChassisFan => chassis_fan = Some(chassis_fan.map_or(reading.value, |rpm| rpm.max(reading.value))),
+            BoardTarget::ChipsetFan => motherboard.chipset_fan_speed.update(reading.value.round() as u32),
+            BoardTarget::AioPump => motherboard.aio_pump_speed.update(reading.value.round() as u32),
+            BoardTarget::ChipsetTemperature => motherboard.chipset_temperature.update(reading.value),
+            BoardTarget::VrmTemperature => motherboard.vrm_temperature.update(reading.value),
+            BoardTarget::Temperature | BoardTarget::Ignored => {}
+        }
+        if is_temperature {
+            motherboard.temperature_sensors.update(&reading.name, reading.value);
+        }
+    }
+    if let Some(rpm) = chassis_fan {
+        state.motherboard.chassis_fan_speed.update(rpm.round() as u32);
+    }
+}
+
+#[cfg(windows)]
+mod backend {
+    use super::{asus_target, gigabyte_sensor, BoardReading, BoardTarget};
+    use crate::hardware_detection::MonitorError;
+    use crate::monitors::wmi::{property, WmiConnection};
+    use windows::Win32::System::Wmi::IWbemClassObject;
+    use windows::core::VARIANT;
+
+    const ASUS_CLASS: &str = "ASUSHW";
+    const GIGABYTE_CLASS: &str = "GSA1_ACPIMethod";
+
+    struct AsusSensor {
+        index: u32,
+        source: u32, // Super I/O chip or embedded controller, each with its own buffer
+        name: String,
+        target: BoardTarget,
+    }
+
+    struct GigabyteSensor {
+        method: String,
+        name: String,
+        target: BoardTarget,
+    }
+
+    pub enum VendorBackend {
+        Asus { connection: WmiConnection, instance: String, sensors: Vec<AsusSensor> },
+        Gigabyte { connection: WmiConnection, instance: String, sensors: Vec<GigabyteSensor> },
+    }
+
+    impl VendorBackend {
+        pub fn open() -> Result<Self, MonitorError> {
+            let connection = WmiConnection::connect("ROOT\\WMI")?;
+            if let Some(instance) = instance_path(&connection, ASUS_CLASS)? {
+                return Self::open_asus(connection, instance);
+            }
+            if let Some(instance) = instance_path(&connection, GIGABYTE_CLASS)? {
+                return Self::open_gigabyte(connection, instance);
+            }
+            Err(MonitorError::DriverMissing("no ASUS or Gigabyte sensor interface on this board".to_string()))
+        }
+
+        fn open_asus(connection: WmiConnection, instance: String) -> Result<Self, MonitorError> {
+            let count = number(&connection.call(&instance, ASUS_CLASS, "sensor_get_number", &[])?, "Num")?;
+            let mut sensors = Vec::new();
+            for index in 0..count as u32 {
+                let info = connection.call(&instance, ASUS_CLASS, "sensor_get_info", &[("Index", VARIANT::from(index))])?;
+                let name = property(&info, "Name")?.to_string();
+                let target = asus_target(&name, number(&info, "Data_Type")? as u32, number(&info, "Location")? as u32);
+                if target != BoardTarget::Ignored {
+                    sensors.push(AsusSensor { index, source: number(&info, "Source")? as u32, name, target });
+                }
+            }
+            Ok(Self::Asus { connection, instance, sensors })
+        }
+
+        fn open_gigabyte(connection: WmiConnection, instance: String) -> Result<Self, MonitorError> {
+            let sensors = connection
+                .methods(GIGABYTE_CLASS)?
+                .into_iter()
+                .filter_map(|method| gigabyte_sensor(&method).map(|(name, target)| GigabyteSensor { method, name, target }))
+                .filter(|sensor| sensor.target != BoardTarget::Ignored)
+                .collect();
+            Ok(Self::Gigabyte { connection, instance, sensors })
+        }
+
+        pub fn describe(&self) -> String {
+            match self {
+                Self::Asus { sensors, .. } => format!("ASUS WMI ({} sensors)", sensors.len()),
+                Self::Gigabyte { sensors, .. } => format!("Gigabyte EC WMI ({} sensors)", sensors.len()),
+            }
+        }
+
+        pub fn read(&self) -> Result<Vec<BoardReading>, MonitorError> {
+            let mut readings = Vec::new();
+            match self {
+                Self::Asus { connection, instance, sensors } => {
+                    // Values are cached per source and only refreshed on request
+                    let mut sources: Vec<u32> = sensors.iter().map(|sensor| sensor.source).collect();
+                    sources.sort_unstable();
+                    sources.dedup();
+                    for source in sources {
+                        connection.call(instance, ASUS_CLASS, "sensor_update_buffer", &[("Source", VARIANT::from(source))])?;
+                    }
+                    for sensor in sensors {
+                        let output = connection.call(instance, ASUS_CLASS, "sensor_get_value", &[("Index", VARIANT::from(sensor.index))])?;
+                        readings.push(BoardReading {
+                            name: sensor.name.clone(),
+                            target: sensor.target,
+                            value: number(&output, "Data")? as f32,
+                        });
+                    }
+                }
+                Self::Gigabyte { connection, instance, sensors } => {
+                    for sensor in sensors {
+                        let output = connection.call(instance, GIGABYTE_CLASS, &sensor.method, &[])?;
+                        readings.push(BoardReading {
+                            name: sensor.name.clone(),
+                            target: sensor.target,
+                            value: number(&output, "Data")? as f32,
+                        });
+                    }
+                }
+            }
+            Ok(readings)
+        }
+    }
+
+    // The class's single instance, or None on boards from other vendors
+    fn instance_path(connection: &WmiConnection, class: &str) -> Result<Option<String>, MonitorError> {
+        match connection.query(&format!("SELECT __PATH FROM {}", class)) {
+            Ok(instances) => instances.first().map(|instance| Ok(property(instance, "__PATH")?.to_string())).transpose(),
+            Err(MonitorError::DriverMissing(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Output parameters are named after the MOF declaration; fall back to the return value
+    fn number(output: &IWbemClassObject, name: &str) -> Result<f64, MonitorError> {
+        for candidate in [name, "ReturnValue"] {
+            if let Ok(value) = property(output, candidate) {
+                if let Ok(number) = f64::try_from(&value) {
+                    return Ok(number);
+                }
+            }
+        }
+        Err(MonitorError::Parse(format!("vendor WMI method returned no {}", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_board_target_by_name() {
+        assert_eq!(board_target("Chassis Fan 2", BoardSensorKind::Fan), BoardTarget::ChassisFan);
+        assert_eq!(board_target("CHA_FAN1", BoardSensorKind::Fan), BoardTarget::ChassisFan);
+        assert_eq!(board_target("W_PUMP+", BoardSensorKind::Fan), BoardTarget::AioPump);
+        assert_eq!(board_target("PCH Fan", BoardSensorKind::Fan), BoardTarget::ChipsetFan);
+        assert_eq!(board_target("CPU Fan", BoardSensorKind::Fan), BoardTarget::Ignored);
+        assert_eq!(board_target("VRM MOS", BoardSensorKind::Temperature), BoardTarget::VrmTemperature);
+        assert_eq!(board_target("T_Sensor", BoardSensorKind::Temperature), BoardTarget::Temperature);
+        assert_eq!(board_target("CPU Core Voltage", BoardSensorKind::Other), BoardTarget::Ignored);
+    }
+
+    #[test]
+    fn test_asus_target_prefers_location() {
+        assert_eq!(asus_target("Motherboard", 1, 6), BoardTarget::VrmTemperature);
+        assert_eq!(asus_target("Temperature", 1, 4), BoardTarget::ChipsetTemperature);
+        assert_eq!(asus_target("Chassis Fan 1", 2, 3), BoardTarget::ChassisFan);
+        assert_eq!(asus_target("+12V Voltage", 0, 3), BoardTarget::Ignored);
+    }
+
+    #[test]
+    fn test_gigabyte_sensor() {
+        assert_eq!(gigabyte_sensor("getTemp3"), Some(("EC Temperature 3".to_string(), BoardTarget::Temperature)));
+        assert_eq!(gigabyte_sensor("getRpm2"), Some(("EC Fan 2".to_string(), BoardTarget::ChassisFan)));
+        assert_eq!(gigabyte_sensor("getRpm1").map(|(_, target)| target), Some(BoardTarget::Ignored));
+        assert_eq!(gigabyte_sensor("setFanDuty"), None);
+    }
+
+    #[test]
+    fn test_apply_board_readings() {
+        let reading = |name: &str, target, value| BoardReading { name: name.to_string(), target, value };
+        let mut state = AppState::default();
+        apply_board_readings(
+            &mut state,
+            &[
+                reading("Chassis Fan 1", BoardTarget::ChassisFan, 850.0),
+                reading("Chassis Fan 2", BoardTarget::ChassisFan, 1210.4),
+                reading("W_PUMP+", BoardTarget::AioPump, 2400.0),
+                reading("VRM", BoardTarget::VrmTemperature, 61.0),
+                reading("T_Sensor", BoardTarget::Temperature, 33.0),
+            ],
+        );
+
+        assert_eq!(state.motherboard.chassis_fan_speed.current, Some(1210.0));
+        assert_eq!(state.motherboard.aio_pump_speed.current, Some(2400.0));
+        assert_eq!(state.motherboard.vrm_temperature.current, Some(61.0));
+        assert!(state.motherboard.temperature_sensors.get("VRM").is_some());
+        assert!(state.motherboard.temperature_sensors.get("T_Sensor").is_some());
+        assert!(state.motherboard.chipset_fan_speed.current.is_none());
+    }
+}
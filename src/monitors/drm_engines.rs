@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, GpuVendor, Platform};
+use crate::model::SharedAppState;
+
+// Drivers whose fdinfo reports busy time per engine class in nanoseconds
+const SUPPORTED_DRIVERS: &[&str] = &["amdgpu", "i915"];
+
+// Engine classes the dashboard splits GPU utilization into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineClass {
+    Graphics,
+    Compute,
+    Copy,
+}
+
+impl EngineClass {
+    // amdgpu calls them gfx, compute and dma; i915 render, compute and copy
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "gfx" | "render" => Some(EngineClass::Graphics),
+            "compute" => Some(EngineClass::Compute),
+            "dma" | "copy" => Some(EngineClass::Copy),
+            _ => None,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+// One open DRM file as /proc/<pid>/fdinfo/<fd> describes it:
+//
+//     drm-driver:     amdgpu
+//     drm-client-id:  42
+//     drm-engine-gfx: 1234567 ns
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrmClient {
+    pub key: String,           // Driver, device and client id; forked processes share a client
+    pub busy_ns: [u64; 3],     // By EngineClass
+    pub capacity: [u64; 3],    // Engines of each class, whose busy times add up
+}
+
+pub fn parse_fdinfo(text: &str) -> Option<DrmClient> {
+    let field = |name: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':').map(str::trim))
+    };
+    let driver = field("drm-driver")?;
+    if !SUPPORTED_DRIVERS.contains(&driver) {
+        return None;
+    }
+    let client_id = field("drm-client-id")?;
+    let mut client = DrmClient {
+        key: format!("{} {} {}", driver, field("drm-pdev").unwrap_or(""), client_id),
+        busy_ns: [0; 3],
+        capacity: [1; 3],
+    };
+    for line in text.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().trim_end_matches(" ns");
+        if let Some(class) = name.strip_prefix("drm-engine-capacity-").and_then(EngineClass::from_name) {
+            client.capacity[class.index()] = value.parse().unwrap_or(1).max(1);
+        } else if let Some(class) = name.strip_prefix("drm-engine-").and_then(EngineClass::from_name) {
+            client.busy_ns[class.index()] = value.parse().unwrap_or(0);
+        }
+    }
+    Some(client)
+}
+
+// Busy time per engine class between two scans of every client
+#[derive(Debug, Default)]
+pub struct EngineSampler {
+    previous: Option<(Instant, HashMap<String, [u64; 3]>)>,
+}
+
+impl EngineSampler {
+    // Percent busy per EngineClass since the previous scan; None on the first. A client
+    // opened since then was busy only within the interval, so all of its time counts
+    pub fn update(&mut self, now: Instant, clients: &[DrmClient]) -> Option<[f64; 3]> {
+        let current: HashMap<String, [u64; 3]> = clients.iter().map(|client| (client.key.clone(), client.busy_ns)).collect();
+        let previous = self.previous.replace((now, current));
+        let (then, before) = previous?;
+        let elapsed_ns = now.duration_since(then).as_nanos() as f64;
+        if elapsed_ns == 0.0 {
+            return None;
+        }
+        let mut percent = [0.0; 3];
+        for client in clients {
+            let earlier = before.get(&client.key).copied().unwrap_or([0; 3]);
+            for class in 0..3 {
+                let busy = client.busy_ns[class].saturating_sub(earlier[class]) as f64;
+                percent[class] += busy / elapsed_ns / client.capacity[class] as f64 * 100.0;
+            }
+        }
+        Some(percent.map(|value| value.min(100.0)))
+    }
+}
+
+// Every open DRM client on the system. Only files that are DRM devices are read, and
+// processes of other users are skipped as unreadable
+pub fn scan_clients(proc: &Path) -> Vec<DrmClient> {
+    let mut clients: HashMap<String, DrmClient> = HashMap::new();
+    let Ok(processes) = std::fs::read_dir(proc) else {
+        return Vec::new();
+    };
+    for process in processes.flatten() {
+        let Ok(fds) = std::fs::read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let is_drm = std::fs::read_link(fd.path()).is_ok_and(|target| target.starts_with("/dev/dri"));
+            if !is_drm {
+                continue;
+            }
+            let fdinfo = process.path().join("fdinfo").join(fd.file_name());
+            if let Some(client) = std::fs::read_to_string(fdinfo).ok().as_deref().and_then(parse_fdinfo) {
+                clients.insert(client.key.clone(), client);
+            }
+        }
+    }
+    clients.into_values().collect()
+}
+
+// Graphics, compute and copy engine utilization for AMD and Intel GPUs on Linux, from
+// the per-client engine busy times the kernel exposes in fdinfo
+pub struct DrmEnginesMonitor {
+    sampler: EngineSampler,
+    initialized: bool,
+}
+
+impl Default for DrmEnginesMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DrmEnginesMonitor {
+    pub fn new() -> Self {
+        Self {
+            sampler: EngineSampler::default(),
+            initialized: false,
+        }
+    }
+}
+
+impl HardwareMonitor for DrmEnginesMonitor {
+    fn name(&self) -> &'static str {
+        "DRM Engines"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        if !Path::new("/proc/self/fdinfo").exists() {
+            return Err(MonitorError::DriverMissing("per-client DRM engine statistics need Linux".to_string()));
+        }
+        self.initialized = true;
+        crate::logger::log_info("DRM engine monitor initialized using fdinfo");
+        Ok(())
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        if !self.initialized {
+            return Ok(());
+        }
+        // Scan before taking the state lock; it reads a file per open GPU handle
+        let clients = scan_clients(Path::new("/proc"));
+        let Some([graphics, compute, copy]) = self.sampler.update(Instant::now(), &clients) else {
+            return Ok(());
+        };
+
+        let mut app_state = state.write();
+        app_state.gpu.graphics_utilization.update(graphics);
+        app_state.gpu.compute_utilization.update(compute);
+        app_state.gpu.copy_utilization.update(copy);
+        Ok(())
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        info.platform == Platform::Linux
+            && (info.gpu_vendors.contains(&GpuVendor::AMD) || info.gpu_vendors.contains(&GpuVendor::Intel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_engine_busy_time_from_fdinfo() {
+        let amd = "pos:\t0\nflags:\t02100002\ndrm-driver:\tamdgpu\ndrm-pdev:\t0000:03:00.0\ndrm-client-id:\t42\n\
+            drm-engine-gfx:\t500000000 ns\ndrm-engine-compute:\t0 ns\ndrm-engine-dma:\t100000000 ns\ndrm-engine-dec:\t5 ns\n";
+        let client = parse_fdinfo(amd).unwrap();
+        assert_eq!(client.key, "amdgpu 0000:03:00.0 42");
+        assert_eq!(client.busy_ns, [500_000_000, 0, 100_000_000]);
+        assert!(parse_fdinfo("drm-driver:\tnvidia-drm\ndrm-client-id:\t1\n").is_none());
+        assert!(parse_fdinfo("pos:\t0\n").is_none());
+
+        // Two copy engines, so one busy for the whole interval is half the capacity
+        let intel = |render: u64, copy: u64, id: u32| {
+            parse_fdinfo(&format!(
+                "drm-driver:\ti915\ndrm-client-id:\t{}\ndrm-engine-render:\t{} ns\ndrm-engine-copy:\t{} ns\ndrm-engine-capacity-copy:\t2\n",
+                id, render, copy
+            ))
+            .unwrap()
+        };
+        let mut sampler = EngineSampler::default();
+        let start = Instant::now();
+        assert_eq!(sampler.update(start, &[intel(1_000_000_000, 0, 1)]), None);
+        let second = start + Duration::from_secs(1);
+        // Client 1 rendered for 250 ms; client 2 opened since and copied for the whole second
+        let percent = sampler.update(second, &[intel(1_250_000_000, 0, 1), intel(0, 1_000_000_000, 2)]).unwrap();
+        assert_eq!(percent, [25.0, 0.0, 50.0]);
+    }
+}
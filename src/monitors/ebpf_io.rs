@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::time::Instant;
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, Platform};
+use crate::model::{ProcessIo, SharedAppState};
+use super::etw::TOP_PROCESSES;
+
+// Block I/O latency per request and bytes per process, traced in the kernel. The probes
+// time each request from issue to completion, then print and clear both maps every second.
+// Bytes are counted as processes read and write regular files, not when requests reach the
+// disk: buffered writes are issued later by writeback, which would credit kworker and jbd2.
+// Reads served from the page cache count too
+pub const PROBE_PROGRAM: &str = "\
+tracepoint:block:block_rq_issue { @start[args->dev, args->sector] = nsecs; }
+kretfunc:vfs_read, kretfunc:vfs_write /retval > 0 && (args->file->f_inode->i_mode & 0xf000) == 0x8000/ {
+    @io_bytes[comm] = sum(retval);
+}
+tracepoint:block:block_rq_complete /@start[args->dev, args->sector]/ {
+    @usecs = hist((nsecs - @start[args->dev, args->sector]) / 1000); delete(@start[args->dev, args->sector]);
+}
+interval:s:1 { print(@usecs); print(@io_bytes); clear(@usecs); clear(@io_bytes); }";
+
+// One line of `bpftrace -f json` output the dashboard uses
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceOutput {
+    Latency(Vec<u64>),              // Counts in LatencyHistogram's power-of-two µs slots
+    ProcessBytes(Vec<(String, u64)>),
+}
+
+// Latency slots and the busiest processes over one poll interval
+#[derive(Debug, Clone, PartialEq)]
+pub struct IoTraceSample {
+    pub slots: Vec<u64>,
+    pub processes: Vec<ProcessIo>,
+}
+
+// What the probes printed since the last poll
+#[derive(Debug, Default)]
+pub struct IoTrace {
+    slots: Vec<u64>,
+    process_bytes: HashMap<String, u64>,
+    started: Option<Instant>,
+}
+
+impl IoTrace {
+    pub fn record(&mut self, output: TraceOutput) {
+        match output {
+            TraceOutput::Latency(slots) => {
+                if self.slots.len() < slots.len() {
+                    self.slots.resize(slots.len(), 0);
+                }
+                for (slot, count) in self.slots.iter_mut().zip(slots) {
+                    *slot += count;
+                }
+            }
+            TraceOutput::ProcessBytes(processes) => {
+                for (name, bytes) in processes {
+                    *self.process_bytes.entry(name).or_default() += bytes;
+                }
+            }
+        }
+    }
+
+    // The latency slots and the busiest processes' throughput since the last call; the
+    // first call only starts the interval
+    pub fn take_interval(&mut self, now: Instant) -> Option<IoTraceSample> {
+        let started = self.started.replace(now);
+        let seconds = now.duration_since(started?).as_secs_f64();
+        if seconds <= 0.0 {
+            return None;
+        }
+        let mut processes: Vec<ProcessIo> = self
+            .process_bytes
+            .drain()
+            .map(|(name, bytes)| ProcessIo { name, mb_per_sec: bytes as f64 / 1_048_576.0 / seconds })
+            .collect();
+        processes.sort_by(|a, b| b.mb_per_sec.total_cmp(&a.mb_per_sec));
+        processes.truncate(TOP_PROCESSES);
+        Some(IoTraceSample { slots: std::mem::take(&mut self.slots), processes })
+    }
+}
+
+// bpftrace prints a hist as buckets like {"min": 4, "max": 7, "count": 12}; the bucket of
+// zero and one both fall in LatencyHistogram's first slot. Maps print as {"comm": bytes}.
+// Other lines, e.g. "attached_probes", are None
+#[cfg(feature = "ebpf")]
+pub fn parse_trace_line(line: &str) -> Result<Option<TraceOutput>, MonitorError> {
+    let output: serde_json::Value = serde_json::from_str(line).map_err(|e| MonitorError::Parse(format!("bpftrace: {}", e)))?;
+    match output["type"].as_str() {
+        Some("hist") => {
+            let buckets = output["data"]["@usecs"].as_array().map(Vec::as_slice).unwrap_or_default();
+            let mut slots = Vec::new();
+            for bucket in buckets {
+                // Only negative latencies, from a clock going backwards, lack a minimum
+                let (Some(min), Some(count)) = (bucket["min"].as_u64(), bucket["count"].as_u64()) else {
+                    continue;
+                };
+                let slot = (u64::BITS - min.max(1).leading_zeros() - 1) as usize;
+                if slots.len() <= slot {
+                    slots.resize(slot + 1, 0);
+                }
+                slots[slot] += count;
+            }
+            Ok(Some(TraceOutput::Latency(slots)))
+        }
+        Some("map") => {
+            let Some(processes) = output["data"]["@io_bytes"].as_object() else {
+                return Ok(None);
+            };
+            Ok(Some(TraceOutput::ProcessBytes(
+                processes.iter().filter_map(|(name, bytes)| Some((name.clone(), bytes.as_u64()?))).collect(),
+            )))
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(not(feature = "ebpf"))]
+pub fn parse_trace_line(_line: &str) -> Result<Option<TraceOutput>, MonitorError> {
+    Err(MonitorError::DriverMissing("this build was compiled without eBPF support".to_string()))
+}
+
+// Block I/O latency distribution and per-process I/O from eBPF probes, loaded through
+// bpftrace. Disk counters only give averages per poll; these catch the single slow
+// request behind a stutter and name who's hammering the disk. Needs root
+pub struct EbpfIoMonitor {
+    #[cfg(target_os = "linux")]
+    session: Option<trace::TraceSession>,
+}
+
+impl Default for EbpfIoMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EbpfIoMonitor {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(target_os = "linux")]
+            session: None,
+        }
+    }
+}
+
+impl HardwareMonitor for EbpfIoMonitor {
+    fn name(&self) -> &'static str {
+        "eBPF I/O"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        #[cfg(target_os = "linux")]
+        {
+            self.session = Some(trace::TraceSession::start()?);
+            crate::logger::log_info("eBPF I/O monitor initialized using bpftrace");
+            Ok(())
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            Err(MonitorError::DriverMissing("eBPF block I/O tracing is only available on Linux".to_string()))
+        }
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        #[cfg(target_os = "linux")]
+        {
+            let Some(session) = self.session.as_ref() else {
+                return Ok(());
+            };
+            let Some(sample) = session.take_interval()? else {
+                return Ok(());
+            };
+
+            let mut app_state = state.write();
+            app_state.storage.update_io_trace(&sample.slots, sample.processes);
+            Ok(())
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = state;
+            Ok(())
+        }
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        info.platform == Platform::Linux
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod trace {
+    use std::io::{BufRead, BufReader, Read};
+    use std::process::{Child, ChildStdout, Command, Stdio};
+    use std::sync::Arc;
+    use std::thread::JoinHandle;
+    use std::time::Instant;
+    use parking_lot::Mutex;
+    use super::{parse_trace_line, IoTrace, IoTraceSample, PROBE_PROGRAM};
+    use crate::hardware_detection::MonitorError;
+
+    // The bpftrace process holding the probes plus the thread reading what it prints
+    pub struct TraceSession {
+        child: Child,
+        trace: Arc<Mutex<IoTrace>>,
+        reader: Option<JoinHandle<()>>,
+    }
+
+    impl TraceSession {
+        pub fn start() -> Result<Self, MonitorError> {
+            let mut child = Command::new("bpftrace")
+                .args(["-f", "json", "-e", PROBE_PROGRAM])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| MonitorError::DriverMissing(format!("could not run bpftrace ({})", e)))?;
+
+            // bpftrace reports the attached probes once they're loaded, or exits with the reason
+            let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+            let mut line = String::new();
+            if stdout.read_line(&mut line).unwrap_or(0) == 0 {
+                let mut reason = String::new();
+                if let Some(mut stderr) = child.stderr.take() {
+                    let _ = stderr.read_to_string(&mut reason);
+                }
+                let _ = child.wait();
+                let reason = reason.trim().to_string();
+                return Err(if reason.contains("root") || reason.contains("ermission") {
+                    MonitorError::PermissionDenied(format!("loading eBPF probes requires root ({})", reason))
+                } else {
+                    MonitorError::DriverMissing(format!("bpftrace could not attach the block I/O probes ({})", reason))
+                });
+            }
+            if let Err(e) = parse_trace_line(&line) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(e);
+            }
+            // Warnings after attaching are discarded, but still drained so bpftrace never blocks on them
+            if let Some(mut stderr) = child.stderr.take() {
+                std::thread::spawn(move || std::io::copy(&mut stderr, &mut std::io::sink()));
+            }
+
+            let trace = Arc::new(Mutex::new(IoTrace::default()));
+            let reader = std::thread::Builder::new()
+                .name("bpftrace-reader".to_string())
+                .spawn({
+                    let trace = Arc::clone(&trace);
+                    move || read_output(stdout, &trace)
+                })
+                .map_err(|e| {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    MonitorError::Parse(format!("failed to start the bpftrace reader thread: {}", e))
+                })?;
+
+            Ok(Self { child, trace, reader: Some(reader) })
+        }
+
+        pub fn take_interval(&self) -> Result<Option<IoTraceSample>, MonitorError> {
+            if self.reader.as_ref().is_some_and(|reader| reader.is_finished()) {
+                return Err(MonitorError::DeviceGone("bpftrace exited".to_string()));
+            }
+            Ok(self.trace.lock().take_interval(Instant::now()))
+        }
+    }
+
+    impl Drop for TraceSession {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+            if let Some(reader) = self.reader.take() {
+                let _ = reader.join();
+            }
+        }
+    }
+
+    // Runs until bpftrace exits or is killed
+    fn read_output(stdout: BufReader<ChildStdout>, trace: &Mutex<IoTrace>) {
+        for line in stdout.lines().map_while(Result::ok) {
+            if let Ok(Some(output)) = parse_trace_line(&line) {
+                trace.lock().record(output);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[cfg(feature = "ebpf")]
+    #[test]
+    fn test_parse_bpftrace_output() {
+        assert_eq!(parse_trace_line(r#"{"type": "attached_probes", "data": {"probes": 3}}"#).unwrap(), None);
+
+        let hist = r#"{"type": "hist", "data": {"@usecs": [{"max": -1, "count": 1}, {"min": 0, "max": 0, "count": 2},
+            {"min": 1, "max": 1, "count": 3}, {"min": 64, "max": 127, "count": 40}, {"min": 128, "max": 255, "count": 5}]}}"#;
+        assert_eq!(parse_trace_line(hist).unwrap(), Some(TraceOutput::Latency(vec![5, 0, 0, 0, 0, 0, 40, 5])));
+
+        let map = r#"{"type": "map", "data": {"@io_bytes": {"postgres": 1048576, "rsync": 4096}}}"#;
+        let Some(TraceOutput::ProcessBytes(mut processes)) = parse_trace_line(map).unwrap() else {
+            panic!("Expected per-process bytes");
+        };
+        processes.sort();
+        assert_eq!(processes, vec![("postgres".to_string(), 1048576), ("rsync".to_string(), 4096)]);
+    }
+
+    #[test]
+    fn test_trace_interval_rates() {
+        let start = Instant::now();
+        let mut trace = IoTrace::default();
+        assert!(trace.take_interval(start).is_none());
+
+        trace.record(TraceOutput::Latency(vec![0, 2]));
+        trace.record(TraceOutput::Latency(vec![1, 1, 1]));
+        trace.record(TraceOutput::ProcessBytes(vec![("postgres".to_string(), 2_097_152)]));
+        trace.record(TraceOutput::ProcessBytes(vec![("postgres".to_string(), 2_097_152)]));
+        let sample = trace.take_interval(start + Duration::from_secs(2)).unwrap();
+        assert_eq!(sample.slots, vec![1, 3, 1]);
+        assert_eq!(sample.processes, vec![ProcessIo { name: "postgres".to_string(), mb_per_sec: 2.0 }]);
+
+        // Each interval starts empty
+        let sample = trace.take_interval(start + Duration::from_secs(3)).unwrap();
+        assert!(sample.slots.is_empty() && sample.processes.is_empty());
+    }
+}
@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, Platform};
+use crate::model::SharedAppState;
+
+// Processes listed in the CPU section, busiest first
+pub const TOP_PROCESSES: usize = 10;
+
+// Exact per-core busy time, DPC/ISR time and per-process CPU time from an NT Kernel
+// Logger session. sysinfo samples utilization at each poll and misses short spikes;
+// context switches and DPC/ISR events account for every tick. Needs Administrator
+pub struct EtwMonitor {
+    #[cfg(windows)]
+    session: Option<kernel::KernelSession>,
+    #[cfg(windows)]
+    system: sysinfo::System, // Only used to name the busiest processes
+}
+
+impl Default for EtwMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EtwMonitor {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(windows)]
+            session: None,
+            #[cfg(windows)]
+            system: sysinfo::System::new(),
+        }
+    }
+}
+
+impl HardwareMonitor for EtwMonitor {
+    fn name(&self) -> &'static str {
+        "ETW"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        #[cfg(windows)]
+        {
+            let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+            self.session = Some(kernel::KernelSession::start(cores)?);
+            crate::logger::log_info("ETW monitor initialized using the NT Kernel Logger");
+            Ok(())
+        }
+
+        #[cfg(not(windows))]
+        {
+            Err(MonitorError::DriverMissing("Event Tracing for Windows is only available on Windows".to_string()))
+        }
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        #[cfg(windows)]
+        {
+            use sysinfo::{Pid, ProcessesToUpdate};
+            use crate::model::ProcessUsage;
+
+            let Some(session) = self.session.as_ref() else {
+                return Ok(());
+            };
+            // The first poll only starts the interval
+            let Some(sample) = session.take_interval()? else {
+                return Ok(());
+            };
+
+            let pids: Vec<Pid> = sample.processes.iter().map(|(pid, _)| Pid::from_u32(*pid)).collect();
+            self.system.refresh_processes(ProcessesToUpdate::Some(&pids), true);
+            let top_processes = sample
+                .processes
+                .iter()
+                .map(|(pid, percent)| ProcessUsage {
+                    pid: *pid,
+                    name: self
+                        .system
+                        .process(Pid::from_u32(*pid))
+                        .map_or_else(|| format!("PID {}", pid), |process| process.name().to_string_lossy().into_owned()),
+                    percent: *percent,
+                })
+                .collect();
+
+            let core_busy: Vec<f32> = sample.core_busy.iter().map(|busy| *busy as f32).collect();
+            let mut app_state = state.write();
+            app_state.cpu.exact_utilization = true;
+            app_state.cpu.utilization.update(sample.total_busy());
+            app_state.cpu.update_core_utilization(&core_busy);
+            app_state.cpu.dpc_time.update(sample.dpc_percent);
+            app_state.cpu.interrupt_time.update(sample.interrupt_percent);
+            app_state.cpu.top_processes = top_processes;
+            Ok(())
+        }
+
+        #[cfg(not(windows))]
+        {
+            let _ = state;
+            Ok(())
+        }
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        info.platform == Platform::Windows
+    }
+}
+
+// Kernel events the accounting needs, timestamps in QueryPerformanceCounter ticks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KernelEvent {
+    ContextSwitch { cpu: usize, timestamp: u64, new_thread: u32 }, // Thread 0 is the idle thread
+    ThreadStart { thread: u32, process: u32 },
+    Dpc { cpu: usize, started: u64, timestamp: u64 },
+    Interrupt { cpu: usize, started: u64, timestamp: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KernelProvider {
+    Thread,
+    PerfInfo,
+}
+
+// Decode the fields used from a classic kernel event's payload
+pub fn parse_event(provider: KernelProvider, opcode: u8, cpu: usize, timestamp: u64, data: &[u8]) -> Option<KernelEvent> {
+    let u32_at = |offset: usize| Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?));
+    let u64_at = |offset: usize| Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?));
+    match (provider, opcode) {
+        // CSwitch: NewThreadId, OldThreadId, ...
+        (KernelProvider::Thread, 36) => Some(KernelEvent::ContextSwitch { cpu, timestamp, new_thread: u32_at(0)? }),
+        // Start and DCStart (threads running when the session started): ProcessId, TThreadId, ...
+        (KernelProvider::Thread, 1 | 3) => Some(KernelEvent::ThreadStart { process: u32_at(0)?, thread: u32_at(4)? }),
+        // ThreadDPC, DPC and TimerDPC are logged when the DPC returns: InitialTime, Routine
+        (KernelProvider::PerfInfo, 66 | 68 | 69) => Some(KernelEvent::Dpc { cpu, started: u64_at(0)?, timestamp }),
+        // ISR: InitialTime, Routine, ...
+        (KernelProvider::PerfInfo, 67) => Some(KernelEvent::Interrupt { cpu, started: u64_at(0)?, timestamp }),
+        _ => None,
+    }
+}
+
+// CPU time over one poll, as percentages
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuTimeSample {
+    pub core_busy: Vec<f64>,        // Per core
+    pub dpc_percent: f64,           // Of all cores' time
+    pub interrupt_percent: f64,     // Of all cores' time
+    pub processes: Vec<(u32, f64)>, // Pid and share of all cores' time, busiest first
+}
+
+impl CpuTimeSample {
+    pub fn total_busy(&self) -> f64 {
+        if self.core_busy.is_empty() {
+            return 0.0;
+        }
+        self.core_busy.iter().sum::<f64>() / self.core_busy.len() as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct CoreTime {
+    running: Option<u32>, // Thread on the core since `since`; unknown before its first switch
+    since: u64,
+    busy: u64,
+    dpc: u64,
+    interrupt: u64,
+}
+
+// Busy, DPC and ISR ticks per core and busy ticks per process, between polls
+#[derive(Debug, Default)]
+pub struct CpuTimeAccounting {
+    cores: Vec<CoreTime>,
+    threads: HashMap<u32, u32>,   // Thread id -> process id
+    processes: HashMap<u32, u64>, // Busy ticks per process this interval
+    interval_start: Option<u64>,
+}
+
+impl CpuTimeAccounting {
+    pub fn new(cores: usize) -> Self {
+        Self { cores: vec![CoreTime::default(); cores], ..Self::default() }
+    }
+
+    pub fn record(&mut self, event: KernelEvent) {
+        match event {
+            KernelEvent::ThreadStart { thread, process } => {
+                self.threads.insert(thread, process);
+            }
+            KernelEvent::ContextSwitch { cpu, timestamp, new_thread } => {
+                let Some(core) = self.cores.get_mut(cpu) else {
+                    return;
+                };
+                credit(core, &self.threads, &mut self.processes, timestamp);
+                core.running = Some(new_thread);
+            }
+            KernelEvent::Dpc { cpu, started, timestamp } | KernelEvent::Interrupt { cpu, started, timestamp } => {
+                let Some(core) = self.cores.get_mut(cpu) else {
+                    return;
+                };
+                let duration = timestamp.saturating_sub(started);
+                if matches!(event, KernelEvent::Dpc { .. }) {
+                    core.dpc += duration;
+                } else {
+                    core.interrupt += duration;
+                }
+                // Time a thread was interrupted for is already busy; the idle thread's is not
+                if core.running == Some(0) {
+                    core.busy += duration;
+                }
+            }
+        }
+    }
+
+    // Close the interval at `now`; None for the first call, which only starts one
+    pub fn take_interval(&mut self, now: u64) -> Option<CpuTimeSample> {
+        let start = self.interval_start.replace(now)?;
+        let elapsed = now.saturating_sub(start);
+        if elapsed == 0 {
+            return None;
+        }
+
+        let mut sample = CpuTimeSample { core_busy: Vec::new(), dpc_percent: 0.0, interrupt_percent: 0.0, processes: Vec::new() };
+        let capacity = elapsed as f64 * self.cores.len().max(1) as f64;
+        for core in &mut self.cores {
+            credit(core, &self.threads, &mut self.processes, now);
+            sample.core_busy.push((core.busy as f64 / elapsed as f64 * 100.0).min(100.0));
+            sample.dpc_percent += core.dpc as f64 / capacity * 100.0;
+            sample.interrupt_percent += core.interrupt as f64 / capacity * 100.0;
+            core.busy = 0;
+            core.dpc = 0;
+            core.interrupt = 0;
+        }
+
+        let mut processes: Vec<(u32, f64)> = self
+            .processes
+            .drain()
+            .filter(|(pid, _)| *pid != 0)
+            .map(|(pid, ticks)| (pid, ticks as f64 / capacity * 100.0))
+            .collect();
+        processes.sort_by(|a, b| b.1.total_cmp(&a.1));
+        processes.truncate(TOP_PROCESSES);
+        sample.processes = processes;
+        Some(sample)
+    }
+}
+
+// Charge the time since the core's last switch to the thread that was running
+fn credit(core: &mut CoreTime, threads: &HashMap<u32, u32>, processes: &mut HashMap<u32, u64>, until: u64) {
+    // Buffers from different cores arrive out of order, so time never runs backwards here
+    let elapsed = until.saturating_sub(core.since);
+    if let Some(thread) = core.running.filter(|thread| *thread != 0) {
+        core.busy += elapsed;
+        if let Some(process) = threads.get(&thread) {
+            *processes.entry(*process).or_default() += elapsed;
+        }
+    }
+    core.since = core.since.max(until);
+}
+
+#[cfg(windows)]
+mod kernel {
+    use std::sync::Arc;
+    use std::thread::JoinHandle;
+    use parking_lot::Mutex;
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_ALREADY_EXISTS, ERROR_SUCCESS, WIN32_ERROR};
+    use windows::Win32::System::Diagnostics::Etw::{
+        CloseTrace, ControlTraceW, OpenTraceW, ProcessTrace, StartTraceW, CONTROLTRACE_HANDLE, EVENT_RECORD,
+        EVENT_TRACE_CONTROL_STOP, EVENT_TRACE_FLAG, EVENT_TRACE_FLAG_CSWITCH, EVENT_TRACE_FLAG_DPC,
+        EVENT_TRACE_FLAG_INTERRUPT, EVENT_TRACE_FLAG_THREAD, EVENT_TRACE_LOGFILEW, EVENT_TRACE_PROPERTIES,
+        EVENT_TRACE_REAL_TIME_MODE, KERNEL_LOGGER_NAMEW, PROCESS_TRACE_MODE_EVENT_RECORD,
+        PROCESS_TRACE_MODE_RAW_TIMESTAMP, PROCESS_TRACE_MODE_REAL_TIME, PerfInfoGuid, SystemTraceControlGuid,
+        ThreadGuid, WNODE_FLAG_TRACED_GUID,
+    };
+    use windows::Win32::System::Performance::QueryPerformanceCounter;
+    use super::{parse_event, CpuTimeAccounting, CpuTimeSample, KernelProvider};
+    use crate::hardware_detection::MonitorError;
+
+    // Invalid handle returned by OpenTrace
+    const INVALID_PROCESSTRACE_HANDLE: u64 = u64::MAX;
+
+    // The system-wide kernel logger session plus the thread consuming its events
+    pub struct KernelSession {
+        control: CONTROLTRACE_HANDLE,
+        accounting: Arc<Mutex<CpuTimeAccounting>>,
+        consumer: Option<JoinHandle<()>>,
+    }
+
+    impl KernelSession {
+        pub fn start(cores: usize) -> Result<Self, MonitorError> {
+            let mut properties = properties_buffer();
+            let header = properties.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES;
+            unsafe {
+                (*header).Wnode.Guid = SystemTraceControlGuid;
+                (*header).Wnode.ClientContext = 1; // QueryPerformanceCounter timestamps
+                (*header).Wnode.Flags = WNODE_FLAG_TRACED_GUID;
+                (*header).LogFileMode = EVENT_TRACE_REAL_TIME_MODE;
+                (*header).FlushTimer = 1;
+                (*header).EnableFlags = EVENT_TRACE_FLAG(
+                    EVENT_TRACE_FLAG_CSWITCH.0 | EVENT_TRACE_FLAG_DPC.0 | EVENT_TRACE_FLAG_INTERRUPT.0 | EVENT_TRACE_FLAG_THREAD.0,
+                );
+            }
+
+            let mut control = CONTROLTRACE_HANDLE { Value: 0 };
+            match unsafe { StartTraceW(&mut control, KERNEL_LOGGER_NAMEW, header) } {
+                ERROR_SUCCESS => {}
+                ERROR_ACCESS_DENIED => {
+                    return Err(MonitorError::PermissionDenied("starting the NT Kernel Logger requires Administrator".to_string()));
+                }
+                ERROR_ALREADY_EXISTS => {
+                    return Err(MonitorError::DriverMissing(
+                        "the NT Kernel Logger is in use by another tool or an earlier crashed session \
+                         (stop it with `logman stop \"NT Kernel Logger\" -ets`)"
+                            .to_string(),
+                    ));
+                }
+                status => return Err(MonitorError::Parse(format!("StartTrace failed with error {}", status.0))),
+            }
+
+            let accounting = Arc::new(Mutex::new(CpuTimeAccounting::new(cores)));
+            let mut logfile = EVENT_TRACE_LOGFILEW {
+                LoggerName: PWSTR(KERNEL_LOGGER_NAMEW.0 as *mut u16),
+                Context: Arc::as_ptr(&accounting) as *mut core::ffi::c_void,
+                ..Default::default()
+            };
+            logfile.Anonymous1.ProcessTraceMode =
+                PROCESS_TRACE_MODE_REAL_TIME | PROCESS_TRACE_MODE_EVENT_RECORD | PROCESS_TRACE_MODE_RAW_TIMESTAMP;
+            logfile.Anonymous2.EventRecordCallback = Some(on_event);
+
+            let trace = unsafe { OpenTraceW(&mut logfile) };
+            if trace.Value == INVALID_PROCESSTRACE_HANDLE {
+                stop(control);
+                return Err(MonitorError::Parse("OpenTrace failed for the NT Kernel Logger".to_string()));
+            }
+
+            // ProcessTrace blocks until the session stops; the accounting outlives it since
+            // the thread is joined before the session is dropped
+            let consumer = std::thread::Builder::new()
+                .name("etw-consumer".to_string())
+                .spawn(move || unsafe {
+                    ProcessTrace(&[trace], None, None);
+                    CloseTrace(trace);
+                })
+                .map_err(|e| MonitorError::Parse(format!("failed to start the ETW consumer thread: {}", e)))?;
+
+            Ok(Self { control, accounting, consumer: Some(consumer) })
+        }
+
+        pub fn take_interval(&self) -> Result<Option<CpuTimeSample>, MonitorError> {
+            let mut now = 0i64;
+            unsafe { QueryPerformanceCounter(&mut now) }
+                .map_err(|e| MonitorError::Parse(format!("QueryPerformanceCounter failed: {}", e)))?;
+            Ok(self.accounting.lock().take_interval(now as u64))
+        }
+    }
+
+    impl Drop for KernelSession {
+        fn drop(&mut self) {
+            stop(self.control);
+            if let Some(consumer) = self.consumer.take() {
+                let _ = consumer.join();
+            }
+        }
+    }
+
+    fn stop(control: CONTROLTRACE_HANDLE) {
+        let mut properties = properties_buffer();
+        let _: WIN32_ERROR = unsafe {
+            ControlTraceW(control, KERNEL_LOGGER_NAMEW, properties.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES, EVENT_TRACE_CONTROL_STOP)
+        };
+    }
+
+    // EVENT_TRACE_PROPERTIES followed by room for the logger name, 8-byte aligned
+    fn properties_buffer() -> Vec<u64> {
+        let name_bytes = ("NT Kernel Logger".len() + 1) * 2;
+        let size = std::mem::size_of::<EVENT_TRACE_PROPERTIES>() + name_bytes;
+        let mut buffer = vec![0u64; size.div_ceil(8)];
+        let header = buffer.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES;
+        unsafe {
+            (*header).Wnode.BufferSize = (buffer.len() * 8) as u32;
+            (*header).LoggerNameOffset = std::mem::size_of::<EVENT_TRACE_PROPERTIES>() as u32;
+        }
+        buffer
+    }
+
+    unsafe extern "system" fn on_event(record: *mut EVENT_RECORD) {
+        let record = &*record;
+        let provider = match record.EventHeader.ProviderId {
+            id if id == ThreadGuid => KernelProvider::Thread,
+            id if id == PerfInfoGuid => KernelProvider::PerfInfo,
+            _ => return,
+        };
+        let data = if record.UserData.is_null() {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(record.UserData as *const u8, record.UserDataLength as usize)
+        };
+        let cpu = record.BufferContext.Anonymous.ProcessorIndex as usize;
+        let event = parse_event(
+            provider,
+            record.EventHeader.EventDescriptor.Opcode,
+            cpu,
+            record.EventHeader.TimeStamp as u64,
+            data,
+        );
+        if let Some(event) = event {
+            let accounting = &*(record.UserContext as *const Mutex<CpuTimeAccounting>);
+            accounting.lock().record(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kernel_events() {
+        let mut cswitch = 4242u32.to_le_bytes().to_vec();
+        cswitch.extend(7u32.to_le_bytes());
+        assert_eq!(
+            parse_event(KernelProvider::Thread, 36, 2, 1000, &cswitch),
+            Some(KernelEvent::ContextSwitch { cpu: 2, timestamp: 1000, new_thread: 4242 })
+        );
+        // ProcessId comes before the thread id
+        assert_eq!(
+            parse_event(KernelProvider::Thread, 3, 0, 0, &cswitch),
+            Some(KernelEvent::ThreadStart { process: 4242, thread: 7 })
+        );
+        assert_eq!(
+            parse_event(KernelProvider::PerfInfo, 67, 1, 900, &800u64.to_le_bytes()),
+            Some(KernelEvent::Interrupt { cpu: 1, started: 800, timestamp: 900 })
+        );
+        // Truncated payloads and unrelated opcodes are ignored
+        assert_eq!(parse_event(KernelProvider::PerfInfo, 68, 0, 0, &[1, 2, 3]), None);
+        assert_eq!(parse_event(KernelProvider::Thread, 50, 0, 0, &cswitch), None);
+    }
+
+    #[test]
+    fn test_accounting_charges_cores_and_processes() {
+        let mut accounting = CpuTimeAccounting::new(2);
+        accounting.record(KernelEvent::ThreadStart { thread: 10, process: 100 });
+        accounting.record(KernelEvent::ThreadStart { thread: 20, process: 200 });
+        assert!(accounting.take_interval(0).is_none());
+
+        // Core 0: thread 10 from 0 to 600, then idle. Core 1: idle, thread 20 from 250
+        accounting.record(KernelEvent::ContextSwitch { cpu: 0, timestamp: 0, new_thread: 10 });
+        accounting.record(KernelEvent::ContextSwitch { cpu: 1, timestamp: 0, new_thread: 0 });
+        accounting.record(KernelEvent::ContextSwitch { cpu: 0, timestamp: 600, new_thread: 0 });
+        accounting.record(KernelEvent::ContextSwitch { cpu: 1, timestamp: 250, new_thread: 20 });
+        // A DPC while core 0 idles counts as busy; an interrupt of thread 20 already is
+        accounting.record(KernelEvent::Dpc { cpu: 0, started: 700, timestamp: 800 });
+        accounting.record(KernelEvent::Interrupt { cpu: 1, started: 500, timestamp: 550 });
+
+        let sample = accounting.take_interval(1000).expect("No sample");
+        assert_eq!(sample.core_busy, vec![70.0, 75.0]);
+        assert_eq!(sample.total_busy(), 72.5);
+        assert_eq!(sample.dpc_percent, 5.0);
+        assert_eq!(sample.interrupt_percent, 2.5);
+        assert_eq!(sample.processes, vec![(200, 37.5), (100, 30.0)]);
+
+        // Thread 20 keeps running into the next interval
+        let sample = accounting.take_interval(2000).expect("No sample");
+        assert_eq!(sample.core_busy, vec![0.0, 100.0]);
+        assert_eq!(sample.processes, vec![(200, 50.0)]);
+    }
+}
@@ -0,0 +1,153 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo};
+use crate::model::{SharedAppState, METRIC_DESCRIPTORS};
+
+// Values successive garbage faults record in place of real readings, in turn
+pub const GARBAGE_VALUES: [f64; 4] = [1.0e12, f64::NAN, -273_150.0, f64::INFINITY];
+
+// Testing mode: failures injected into one monitor's polls, configured per monitor as
+// e.g. `[fault_injection.nvidia]` with `fail_rate = 0.2`. Rates are chances per poll;
+// the same seed always produces the same sequence of faults
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaultPlan {
+    pub seed: u64,
+    pub fail_rate: f64,    // Poll returns a transient error
+    pub timeout_rate: f64, // Poll stalls for timeout_ms, then reports a timeout
+    pub timeout_ms: u64,
+    pub garbage_rate: f64, // Poll succeeds but what it recorded is replaced with garbage
+}
+
+impl Default for FaultPlan {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            fail_rate: 0.0,
+            timeout_rate: 0.0,
+            timeout_ms: 2000,
+            garbage_rate: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    Fail,
+    Timeout,
+    Garbage,
+}
+
+// Wraps a monitor and applies its FaultPlan on every poll
+pub struct FaultInjector {
+    inner: Box<dyn HardwareMonitor>,
+    plan: FaultPlan,
+    rng: u64,
+    pub injected: Vec<Fault>, // Every fault so far, in order
+}
+
+impl FaultInjector {
+    pub fn new(inner: Box<dyn HardwareMonitor>, plan: FaultPlan) -> Self {
+        // xorshift never leaves zero, so a zero seed is nudged
+        let rng = plan.seed.max(1);
+        Self { inner, plan, rng, injected: Vec::new() }
+    }
+
+    // Fault for the next poll, if any
+    pub fn next_fault(&mut self) -> Option<Fault> {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let roll = (self.rng >> 11) as f64 / (1u64 << 53) as f64;
+
+        let fault = if roll < self.plan.fail_rate {
+            Fault::Fail
+        } else if roll < self.plan.fail_rate + self.plan.timeout_rate {
+            Fault::Timeout
+        } else if roll < self.plan.fail_rate + self.plan.timeout_rate + self.plan.garbage_rate {
+            Fault::Garbage
+        } else {
+            return None;
+        };
+        self.injected.push(fault);
+        Some(fault)
+    }
+
+    // Poll the real monitor, then overwrite the newest sample of every metric it updated
+    fn poll_with_garbage(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        let before = state.read().latest_sample_times();
+        self.inner.update_metrics(state)?;
+
+        let mut app_state = state.write();
+        let after = app_state.latest_sample_times();
+        let garbages = self.injected.iter().filter(|fault| **fault == Fault::Garbage).count();
+        let garbage = GARBAGE_VALUES[(garbages - 1) % GARBAGE_VALUES.len()];
+        for ((descriptor, before), after) in METRIC_DESCRIPTORS.iter().zip(before).zip(after) {
+            // The poller may record its own timing meanwhile; only the monitor's readings are garbled
+            if descriptor.is_poller_metric() {
+                continue;
+            }
+            let (Some(timestamp), Some(metric)) = (after.filter(|_| after != before), app_state.metric_mut(descriptor.id)) else {
+                continue;
+            };
+            metric.history.pop_back();
+            metric.record_at(timestamp, garbage);
+        }
+        Ok(())
+    }
+}
+
+impl HardwareMonitor for FaultInjector {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        self.inner.initialize()
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        match self.next_fault() {
+            None => self.inner.update_metrics(state),
+            Some(Fault::Fail) => Err(MonitorError::Parse(format!("injected failure in {}", self.name()))),
+            Some(Fault::Timeout) => {
+                std::thread::sleep(Duration::from_millis(self.plan.timeout_ms));
+                Err(MonitorError::Timeout(format!("injected timeout in {}", self.name())))
+            }
+            Some(Fault::Garbage) => self.poll_with_garbage(state),
+        }
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        self.inner.supports_hardware(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitors::generic::GenericMonitor;
+
+    #[test]
+    fn test_fault_sequence_is_deterministic() {
+        let plan = FaultPlan { seed: 42, fail_rate: 0.3, timeout_rate: 0.1, garbage_rate: 0.2, ..FaultPlan::default() };
+        let sequence = |plan: &FaultPlan| {
+            let mut injector = FaultInjector::new(Box::new(GenericMonitor::new()), plan.clone());
+            (0..200).map(|_| injector.next_fault()).collect::<Vec<_>>()
+        };
+
+        let first = sequence(&plan);
+        assert_eq!(first, sequence(&plan));
+        assert_ne!(first, sequence(&FaultPlan { seed: 7, ..plan.clone() }));
+
+        // Roughly the configured share of each fault
+        let count = |fault| first.iter().filter(|injected| **injected == Some(fault)).count();
+        assert!((40..80).contains(&count(Fault::Fail)), "{} failures", count(Fault::Fail));
+        assert!((5..40).contains(&count(Fault::Timeout)));
+        assert!((20..60).contains(&count(Fault::Garbage)));
+
+        // No rates, no faults
+        let mut quiet = FaultInjector::new(Box::new(GenericMonitor::new()), FaultPlan::default());
+        assert!((0..100).all(|_| quiet.next_fault().is_none()));
+    }
+}
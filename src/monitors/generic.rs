@@ -1,13 +1,48 @@
-use crate::hardware_detection::{HardwareMonitor, HardwareInfo};
-use crate::model::SharedAppState;
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo};
+use crate::model::{AppState, SharedAppState};
+use crate::temperature;
+use crate::lm_sensors;
 use sysinfo::{System, Components};
 
+// Which device a sysinfo temperature sensor belongs to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SensorDevice {
+    Cpu,
+    Gpu,
+    Storage,
+    Motherboard,
+}
+
+// Classify a sensor by its label, e.g. "k10temp Tccd1", "amdgpu junction", "nvme Composite".
+// Memory sensors are recorded as memory.temperature instead
+pub fn classify_temperature_sensor(label: &str) -> Option<SensorDevice> {
+    let label = label.to_lowercase();
+    let matches = |keywords: &[&str]| keywords.iter().any(|keyword| label.contains(keyword));
+    if matches(&["gpu", "amdgpu", "nouveau", "radeon", "graphics", "video"]) {
+        Some(SensorDevice::Gpu)
+    } else if matches(&["nvme", "drivetemp", "ssd", "disk"]) {
+        Some(SensorDevice::Storage)
+    } else if matches(&["memory", "ram", "dimm"]) {
+        None
+    } else if matches(&["cpu", "core", "package", "k10temp", "coretemp", "zenpower", "tctl", "tdie", "tccd"]) {
+        Some(SensorDevice::Cpu)
+    } else {
+        Some(SensorDevice::Motherboard)
+    }
+}
+
 pub struct GenericMonitor {
     system: System,
     components: Components,
     initialized: bool,
 }
 
+impl Default for GenericMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GenericMonitor {
     pub fn new() -> Self {
         Self {
@@ -17,31 +52,99 @@ impl GenericMonitor {
         }
     }
     
-    fn get_cpu_temperature(&self) -> Option<f32> {
-        for component in &self.components {
+    fn get_cpu_temperature(&self) -> Option<(String, f32)> {
+        for component in self.plausible_components() {
             let label = component.label().to_lowercase();
             if label.contains("cpu") || label.contains("core") || label.contains("package") {
-                return Some(component.temperature());
+                return Some((component.label().to_string(), component.temperature()));
             }
         }
         None
     }
     
-    fn get_gpu_temperature(&self) -> Option<f32> {
+    // Sensors reading an actual temperature rather than a driver placeholder
+    fn plausible_components(&self) -> impl Iterator<Item = &sysinfo::Component> {
+        self.components.iter().filter(|component| temperature::is_plausible(component.temperature()))
+    }
+    
+    // AMD k10temp exposes one "Tccd<n>" sensor per core complex die
+    fn get_ccd_temperatures(&self) -> Vec<f32> {
+        let mut ccds: Vec<(&str, f32)> = self.plausible_components()
+            .filter(|component| component.label().to_lowercase().contains("tccd"))
+            .map(|component| (component.label(), component.temperature()))
+            .collect();
+        ccds.sort_by(|a, b| a.0.cmp(b.0));
+        ccds.into_iter().map(|(_, temp)| temp).collect()
+    }
+    
+    // Record every sensor under its device so none are lost to the single-value fields
+    fn record_temperature_sensors(&self, app_state: &mut AppState) {
+        if crate::logger::sensor_tracing() {
+            self.trace_components();
+        }
+        for component in self.plausible_components() {
+            let temperature = component.temperature();
+            let sensors = match classify_temperature_sensor(component.label()) {
+                Some(SensorDevice::Cpu) => &mut app_state.cpu.temperature_sensors,
+                Some(SensorDevice::Gpu) => &mut app_state.gpu.temperature_sensors,
+                Some(SensorDevice::Storage) => &mut app_state.storage.temperature_sensors,
+                Some(SensorDevice::Motherboard) => &mut app_state.motherboard.temperature_sensors,
+                None => continue,
+            };
+            sensors.update(component.label(), temperature);
+        }
+    }
+    
+    // Sensors the user mapped to metrics; returns the metrics they filled, which the
+    // guesses below leave alone
+    fn record_mapped_sensors(&self, app_state: &mut AppState) -> Vec<String> {
+        let mut mapped = Vec::new();
+        for mapping in lm_sensors::mappings() {
+            let Some(component) = self.plausible_components().find(|component| mapping.matches_component(component.label())) else {
+                continue;
+            };
+            let Some(metric) = app_state.metric_mut(&mapping.metric_id) else {
+                continue;
+            };
+            metric.update(component.temperature());
+            app_state.note_source_detail(&mapping.metric_id, format!("lm-sensors mapping \"{}\"", mapping.path()));
+            crate::logger::trace_sensor("Generic", component.label(), component.temperature() as f64, &mapping.metric_id);
+            mapped.push(mapping.metric_id);
+        }
+        mapped
+    }
+    
+    // Every sensor sysinfo reports, with the device it was classified under
+    fn trace_components(&self) {
         for component in &self.components {
+            let temperature = component.temperature();
+            let mapping = if !temperature::is_plausible(temperature) {
+                "ignored, placeholder reading".to_string()
+            } else {
+                match classify_temperature_sensor(component.label()) {
+                    Some(device) => format!("{:?} temperature sensors", device),
+                    None => "ignored, unclassified".to_string(),
+                }
+            };
+            crate::logger::trace_sensor("Generic", component.label(), temperature as f64, &mapping);
+        }
+    }
+    
+    fn get_gpu_temperature(&self) -> Option<(String, f32)> {
+        for component in self.plausible_components() {
             let label = component.label().to_lowercase();
             if label.contains("gpu") || label.contains("graphics") || label.contains("video") {
-                return Some(component.temperature());
+                return Some((component.label().to_string(), component.temperature()));
             }
         }
         None
     }
     
-    fn get_memory_temperature(&self) -> Option<f32> {
-        for component in &self.components {
+    fn get_memory_temperature(&self) -> Option<(String, f32)> {
+        for component in self.plausible_components() {
             let label = component.label().to_lowercase();
             if label.contains("memory") || label.contains("ram") || label.contains("dimm") {
-                return Some(component.temperature());
+                return Some((component.label().to_string(), component.temperature()));
             }
         }
         None
@@ -49,7 +152,11 @@ impl GenericMonitor {
 }
 
 impl HardwareMonitor for GenericMonitor {
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn name(&self) -> &'static str {
+        "Generic"
+    }
+    
+    fn initialize(&mut self) -> Result<(), MonitorError> {
         self.system.refresh_all();
         self.components.refresh();
         self.initialized = true;
@@ -57,7 +164,7 @@ impl HardwareMonitor for GenericMonitor {
         Ok(())
     }
     
-    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), Box<dyn std::error::Error>> {
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
         if !self.initialized {
             return Ok(());
         }
@@ -68,36 +175,81 @@ impl HardwareMonitor for GenericMonitor {
         
         let mut app_state = state.write();
         
-        // CPU metrics that sysinfo can provide
-        let cpu_usage = self.system.global_cpu_usage();
-        app_state.cpu.utilization.update(cpu_usage);
+        // CPU metrics that sysinfo can provide, unless a tracing backend measures them exactly
+        if !app_state.cpu.exact_utilization {
+            let cpu_usage = self.system.global_cpu_usage();
+            app_state.cpu.utilization.update(cpu_usage);
+            
+            // Per-core utilization for the heatmap
+            let core_usage: Vec<f32> = self.system.cpus().iter().map(|cpu| cpu.cpu_usage()).collect();
+            app_state.cpu.update_core_utilization(&core_usage);
+        }
         
-        // CPU frequency from first core
         if let Some(cpu) = self.system.cpus().first() {
-            let frequency_mhz = cpu.frequency() as u32;
-            if frequency_mhz > 0 {
-                app_state.cpu.clock_speed.update(frequency_mhz);
+            if app_state.cpu.name.is_none() {
+                let brand = cpu.brand().trim();
+                if !brand.is_empty() {
+                    app_state.cpu.name = Some(brand.to_string());
+                }
             }
         }
         
-        // CPU temperature
-        if let Some(temp) = self.get_cpu_temperature() {
+        // CPU frequency as min/avg/max across all cores
+        let core_clocks: Vec<u32> = self.system.cpus().iter().map(|cpu| cpu.frequency() as u32).collect();
+        app_state.cpu.update_core_clocks(&core_clocks);
+        
+        // CPU temperature, as the die temperature on AMD so it compares with other backends
+        self.record_temperature_sensors(&mut app_state);
+        let mapped = self.record_mapped_sensors(&mut app_state);
+        let guess = |id: &str| !mapped.iter().any(|mapped| mapped == id);
+        let cpu_name = app_state.cpu.name.clone();
+        let cpu_temperature = temperature::cpu_temperature(&app_state.cpu.temperature_sensors, cpu_name.as_deref());
+        if let Some(normalized) = cpu_temperature.filter(|_| guess("cpu.package_temperature")) {
+            app_state.note_source_detail("cpu.package_temperature", format!("sysinfo {}", normalized.detail));
+            crate::logger::trace_sensor("Generic", &normalized.detail, normalized.celsius, "cpu.package_temperature");
+            app_state.cpu.package_temperature.update(normalized.celsius);
+        } else if let Some((label, temp)) = self.get_cpu_temperature().filter(|_| guess("cpu.package_temperature")) {
+            app_state.note_source_detail("cpu.package_temperature", format!("sysinfo sensor \"{}\"", label));
+            crate::logger::trace_sensor("Generic", &label, temp as f64, "cpu.package_temperature");
             app_state.cpu.package_temperature.update(temp);
         }
         
-        // Memory utilization
+        // Drive temperature: NVMe Composite rather than whichever sensor comes first
+        let storage_temperature = temperature::storage_temperature(&app_state.storage.temperature_sensors);
+        if let Some(normalized) = storage_temperature.filter(|_| guess("storage.temperature")) {
+            app_state.note_source_detail("storage.temperature", format!("sysinfo {}", normalized.detail));
+            crate::logger::trace_sensor("Generic", &normalized.detail, normalized.celsius, "storage.temperature");
+            app_state.storage.temperature.update(normalized.celsius);
+        }
+        
+        let ccd_temperatures = self.get_ccd_temperatures();
+        if !ccd_temperatures.is_empty() {
+            app_state.cpu.update_ccd_temperatures(&ccd_temperatures);
+        }
+        
+        // Memory utilization and composition. Available memory counts reclaimable
+        // cache, so whatever is available but not free is cache
         let used_memory = self.system.used_memory();
-        let usage_mb = (used_memory / 1024 / 1024) as u64;
+        let usage_mb = used_memory / 1024 / 1024;
         app_state.memory.utilization_mb.update(usage_mb);
+        let free_memory = self.system.free_memory();
+        let cached_memory = self.system.available_memory().saturating_sub(free_memory);
+        app_state.memory.cached_mb.update(cached_memory / 1024 / 1024);
+        app_state.memory.free_mb.update(free_memory / 1024 / 1024);
+        app_state.memory.swap_used_mb.update(self.system.used_swap() / 1024 / 1024);
         
         // Memory temperature
-        if let Some(temp) = self.get_memory_temperature() {
+        if let Some((label, temp)) = self.get_memory_temperature().filter(|_| guess("memory.temperature")) {
+            app_state.note_source_detail("memory.temperature", format!("sysinfo sensor \"{}\"", label));
+            crate::logger::trace_sensor("Generic", &label, temp as f64, "memory.temperature");
             app_state.memory.temperature.update(temp);
         }
         
         // GPU temperature (basic fallback)
         if app_state.gpu.package_temperature.current.is_none() {
-            if let Some(temp) = self.get_gpu_temperature() {
+            if let Some((label, temp)) = self.get_gpu_temperature().filter(|_| guess("gpu.package_temperature")) {
+                app_state.note_source_detail("gpu.package_temperature", format!("sysinfo sensor \"{}\"", label));
+                crate::logger::trace_sensor("Generic", &label, temp as f64, "gpu.package_temperature");
                 app_state.gpu.package_temperature.update(temp);
             }
         }
@@ -109,4 +261,20 @@ impl HardwareMonitor for GenericMonitor {
         // Generic monitor supports all hardware as a fallback
         true
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_temperature_sensor() {
+        assert_eq!(classify_temperature_sensor("k10temp Tctl"), Some(SensorDevice::Cpu));
+        assert_eq!(classify_temperature_sensor("k10temp Tccd2"), Some(SensorDevice::Cpu));
+        assert_eq!(classify_temperature_sensor("coretemp Package id 0"), Some(SensorDevice::Cpu));
+        assert_eq!(classify_temperature_sensor("amdgpu junction"), Some(SensorDevice::Gpu));
+        assert_eq!(classify_temperature_sensor("nvme Composite"), Some(SensorDevice::Storage));
+        assert_eq!(classify_temperature_sensor("acpitz temp1"), Some(SensorDevice::Motherboard));
+        assert_eq!(classify_temperature_sensor("spd5118 DIMM A1"), None);
+    }
+}
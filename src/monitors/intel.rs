@@ -1,20 +1,48 @@
-use crate::hardware_detection::{HardwareMonitor, HardwareInfo, GpuVendor, CpuVendor};
-use crate::model::SharedAppState;
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, GpuVendor, CpuVendor};
+use crate::model::{CounterMetric, SharedAppState};
+use crate::privileged;
+
+// Package energy counter; root-only on many distros since the PLATYPUS mitigation
+const RAPL_ENERGY_PATH: &str = "/sys/class/powercap/intel-rapl:0/energy_uj";
+// Where the energy counter wraps back to 0; readable without privileges
+const RAPL_RANGE_PATH: &str = "/sys/class/powercap/intel-rapl:0/max_energy_range_uj";
 
 pub struct IntelMonitor {
     initialized: bool,
+    rapl_available: bool,
+    package_energy: CounterMetric, // RAPL reading in microjoules
+}
+
+impl Default for IntelMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl IntelMonitor {
     pub fn new() -> Self {
         Self {
             initialized: false,
+            rapl_available: cfg!(target_os = "linux"),
+            package_energy: CounterMetric::default(),
         }
     }
+    
+    // Average package power since the previous reading, read directly or via the helper
+    fn read_package_power(&mut self) -> Result<Option<f32>, MonitorError> {
+        let energy_uj: u64 = privileged::read_sensor_file(std::path::Path::new(RAPL_ENERGY_PATH))?
+            .trim()
+            .parse()?;
+        Ok(self.package_energy.update(energy_uj).map(|microwatts| (microwatts / 1_000_000.0) as f32))
+    }
 }
 
 impl HardwareMonitor for IntelMonitor {
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn name(&self) -> &'static str {
+        "Intel"
+    }
+    
+    fn initialize(&mut self) -> Result<(), MonitorError> {
         // For Intel monitoring, we would typically use:
         // - Intel Power Gadget API for CPU power/voltage
         // - Intel GPU Performance Counters for GPU metrics
@@ -28,17 +56,21 @@ impl HardwareMonitor for IntelMonitor {
             // or setting up MSR access on Linux
             
             self.initialized = true;
+            self.rapl_available = std::path::Path::new(RAPL_ENERGY_PATH).exists();
+            if let Some(range) = std::fs::read_to_string(RAPL_RANGE_PATH).ok().and_then(|range| range.trim().parse().ok()) {
+                self.package_energy = CounterMetric::with_range(range);
+            }
             crate::logger::log_info("Intel monitor initialized (placeholder)");
             Ok(())
         }
         
         #[cfg(not(feature = "intel"))]
         {
-            Err("Intel support not compiled in".into())
+            Err(MonitorError::DriverMissing("Intel support not compiled in".to_string()))
         }
     }
     
-    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), Box<dyn std::error::Error>> {
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
         if !self.initialized {
             return Ok(());
         }
@@ -52,12 +84,30 @@ impl HardwareMonitor for IntelMonitor {
             // - Intel GPU utilization and frequencies
             // - Thermal throttling detection via thermal status registers
             
-            let mut _app_state = state.write();
-            
-            // Placeholder implementation
-            // In production, this would make actual Intel API calls
+            // Without RAPL access the rest of the monitor keeps running, so only
+            // this reading is given up
+            let mut package_power = None;
+            if self.rapl_available {
+                match self.read_package_power() {
+                    Ok(power) => package_power = power,
+                    Err(e) => {
+                        crate::logger::log_monitor_error(self.name(), &e);
+                        if !privileged::helper_configured() {
+                            if let Some(command) = privileged::setcap_command() {
+                                crate::logger::log_info(&format!(
+                                    "Enable the privileged helper or run `{}` to read CPU package power", command
+                                ));
+                            }
+                        }
+                        self.rapl_available = false;
+                    }
+                }
+            }
             
-            crate::logger::log_info("Intel metrics updated (placeholder)");
+            let mut app_state = state.write();
+            if let Some(power) = package_power {
+                app_state.cpu.power_consumption.update(power);
+            }
         }
         
         Ok(())
@@ -0,0 +1,217 @@
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, Platform};
+use crate::model::{CounterMetric, SharedAppState};
+
+// Disk and network throughput on macOS, where sysinfo reports neither. Disk bytes come
+// from the IOBlockStorageDriver statistics in the IOKit registry, network bytes from the
+// 64-bit interface counters the routing sysctl reports, as `netstat -ib` shows them.
+// Both are read in-process, so fast polling doesn't fork a tool every time
+pub struct MacIoMonitor {
+    initialized: bool,
+    disk_read: CounterMetric,
+    disk_write: CounterMetric,
+    network_receive: CounterMetric,
+    network_transmit: CounterMetric,
+}
+
+impl Default for MacIoMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MacIoMonitor {
+    pub fn new() -> Self {
+        Self {
+            initialized: false,
+            disk_read: CounterMetric::default(),
+            disk_write: CounterMetric::default(),
+            network_receive: CounterMetric::default(),
+            network_transmit: CounterMetric::default(),
+        }
+    }
+}
+
+impl HardwareMonitor for MacIoMonitor {
+    fn name(&self) -> &'static str {
+        "MacIO"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        native::disk_bytes()?;
+        native::network_bytes()?;
+        self.initialized = true;
+        crate::logger::log_info("macOS I/O monitor initialized using IOKit and interface statistics");
+        Ok(())
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        if !self.initialized {
+            return Ok(());
+        }
+
+        // Read the counters before taking the state lock
+        let disk = native::disk_bytes().ok();
+        let network = native::network_bytes().ok();
+        let now = chrono::Utc::now();
+
+        let mut app_state = state.write();
+        if let Some((read, write)) = disk {
+            if let Some(rate) = self.disk_read.record_at(now, read) {
+                app_state.storage.read_speed.update(bytes_to_mb(rate));
+            }
+            if let Some(rate) = self.disk_write.record_at(now, write) {
+                app_state.storage.write_speed.update(bytes_to_mb(rate));
+            }
+        }
+        if let Some((received, transmitted)) = network {
+            if let Some(rate) = self.network_receive.record_at(now, received) {
+                app_state.network.receive_speed.update(bytes_to_mb(rate));
+            }
+            if let Some(rate) = self.network_transmit.record_at(now, transmitted) {
+                app_state.network.transmit_speed.update(bytes_to_mb(rate));
+            }
+        }
+        Ok(())
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        info.platform == Platform::MacOS
+    }
+}
+
+fn bytes_to_mb(bytes: f64) -> f32 {
+    (bytes / 1024.0 / 1024.0) as f32
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use std::ffi::{c_char, c_int, c_void};
+    use core_foundation_sys::base::{kCFAllocatorDefault, CFAllocatorRef, CFRelease, CFTypeRef};
+    use core_foundation_sys::dictionary::{CFDictionaryGetValue, CFDictionaryRef, CFMutableDictionaryRef};
+    use core_foundation_sys::number::{kCFNumberSInt64Type, CFNumberGetValue, CFNumberRef};
+    use core_foundation_sys::string::{kCFStringEncodingUTF8, CFStringCreateWithCString, CFStringRef};
+    use crate::hardware_detection::MonitorError;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const c_char) -> *mut c_void;
+        fn IOServiceGetMatchingServices(main_port: u32, matching: *mut c_void, iterator: *mut u32) -> i32;
+        fn IOIteratorNext(iterator: u32) -> u32;
+        fn IORegistryEntryCreateCFProperties(
+            entry: u32,
+            properties: *mut CFMutableDictionaryRef,
+            allocator: CFAllocatorRef,
+            options: u32,
+        ) -> i32;
+        fn IOObjectRelease(object: u32) -> i32;
+    }
+
+    // A CFString key, released when dropped
+    struct Key(CFStringRef);
+
+    impl Key {
+        // From a nul-terminated byte string
+        fn new(name: &[u8]) -> Self {
+            unsafe { Self(CFStringCreateWithCString(kCFAllocatorDefault, name.as_ptr() as *const c_char, kCFStringEncodingUTF8)) }
+        }
+    }
+
+    impl Drop for Key {
+        fn drop(&mut self) {
+            unsafe { CFRelease(self.0 as CFTypeRef) }
+        }
+    }
+
+    unsafe fn number(dictionary: CFDictionaryRef, key: &Key) -> Option<u64> {
+        let value = CFDictionaryGetValue(dictionary, key.0 as *const c_void) as CFNumberRef;
+        if value.is_null() {
+            return None;
+        }
+        let mut number: i64 = 0;
+        CFNumberGetValue(value, kCFNumberSInt64Type, &mut number as *mut i64 as *mut c_void).then_some(number as u64)
+    }
+
+    // Total bytes read and written across every block storage driver
+    pub fn disk_bytes() -> Result<(u64, u64), MonitorError> {
+        let statistics = Key::new(b"Statistics\0");
+        let read = Key::new(b"Bytes (Read)\0");
+        let written = Key::new(b"Bytes (Write)\0");
+        let mut totals = (0, 0);
+        unsafe {
+            // IOServiceGetMatchingServices consumes the matching dictionary
+            let matching = IOServiceMatching(b"IOBlockStorageDriver\0".as_ptr() as *const c_char);
+            let mut iterator = 0;
+            let result = IOServiceGetMatchingServices(0, matching, &mut iterator);
+            if result != 0 {
+                return Err(MonitorError::DriverMissing(format!("no IOBlockStorageDriver services ({:#x})", result)));
+            }
+            loop {
+                let entry = IOIteratorNext(iterator);
+                if entry == 0 {
+                    break;
+                }
+                let mut properties: CFMutableDictionaryRef = std::ptr::null_mut();
+                if IORegistryEntryCreateCFProperties(entry, &mut properties, kCFAllocatorDefault, 0) == 0 && !properties.is_null() {
+                    let stats = CFDictionaryGetValue(properties as CFDictionaryRef, statistics.0 as *const c_void) as CFDictionaryRef;
+                    if !stats.is_null() {
+                        totals.0 += number(stats, &read).unwrap_or(0);
+                        totals.1 += number(stats, &written).unwrap_or(0);
+                    }
+                    CFRelease(properties as CFTypeRef);
+                }
+                IOObjectRelease(entry);
+            }
+            IOObjectRelease(iterator);
+        }
+        Ok(totals)
+    }
+
+    // Total bytes received and sent over all non-loopback interfaces, from the
+    // RTM_IFINFO2 messages of the NET_RT_IFLIST2 sysctl
+    pub fn network_bytes() -> Result<(u64, u64), MonitorError> {
+        let mut mib: [c_int; 6] = [libc::CTL_NET, libc::PF_ROUTE, 0, 0, libc::NET_RT_IFLIST2, 0];
+        let mut length = 0;
+        let mut buffer = Vec::new();
+        unsafe {
+            // Sized first, then filled
+            for _ in 0..2 {
+                let data = if buffer.is_empty() { std::ptr::null_mut() } else { buffer.as_mut_ptr() as *mut c_void };
+                if libc::sysctl(mib.as_mut_ptr(), mib.len() as u32, data, &mut length, std::ptr::null_mut(), 0) != 0 {
+                    return Err(std::io::Error::last_os_error().into());
+                }
+                buffer.resize(length, 0u8);
+            }
+        }
+
+        let mut totals = (0, 0);
+        let mut offset = 0;
+        while offset + std::mem::size_of::<libc::if_msghdr>() <= buffer.len() {
+            let header = unsafe { std::ptr::read_unaligned(buffer.as_ptr().add(offset) as *const libc::if_msghdr) };
+            if header.ifm_msglen == 0 {
+                break;
+            }
+            if header.ifm_type as c_int == libc::RTM_IFINFO2 && offset + std::mem::size_of::<libc::if_msghdr2>() <= buffer.len() {
+                let interface = unsafe { std::ptr::read_unaligned(buffer.as_ptr().add(offset) as *const libc::if_msghdr2) };
+                if interface.ifm_flags & libc::IFF_LOOPBACK == 0 {
+                    totals.0 += interface.ifm_data.ifi_ibytes;
+                    totals.1 += interface.ifm_data.ifi_obytes;
+                }
+            }
+            offset += header.ifm_msglen as usize;
+        }
+        Ok(totals)
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod native {
+    use crate::hardware_detection::MonitorError;
+
+    pub fn disk_bytes() -> Result<(u64, u64), MonitorError> {
+        Err(MonitorError::DriverMissing("IOKit statistics are only available on macOS".to_string()))
+    }
+
+    pub fn network_bytes() -> Result<(u64, u64), MonitorError> {
+        Err(MonitorError::DriverMissing("interface statistics are read this way only on macOS".to_string()))
+    }
+}
@@ -3,47 +3,786 @@ pub mod amd;
 pub mod intel;
 pub mod apple;
 pub mod generic;
+pub mod storage;
+pub mod macos_io;
+pub mod acpi_thermal;
+pub mod wmi;
+pub mod board_wmi;
+pub mod battery;
+pub mod etw;
+pub mod drm_engines;
+pub mod network;
+pub mod smart;
+pub mod pools;
+pub mod super_io;
+pub mod ntp;
+pub mod ebpf_io;
+pub mod synthetic;
+pub mod fault;
 
-use crate::hardware_detection::{HardwareMonitor, HardwareInfo};
-use crate::model::SharedAppState;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use parking_lot::Mutex;
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo};
+use crate::config::{SharedConfig, DEFAULT_WARMUP_SAMPLES};
+use crate::model::{MonitorSelection, MonitorTiming, SharedAppState};
+
+// Progress marker shared with the watchdog so it can spot a hung monitor
+#[derive(Debug)]
+pub struct MonitorHeartbeat {
+    last_beat: Mutex<Instant>,
+    current_monitor: Mutex<Option<&'static str>>,
+}
+
+impl Default for MonitorHeartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MonitorHeartbeat {
+    pub fn new() -> Self {
+        Self {
+            last_beat: Mutex::new(Instant::now()),
+            current_monitor: Mutex::new(None),
+        }
+    }
+    
+    // Record progress; `current_monitor` is the monitor about to run, if any
+    pub fn beat(&self, current_monitor: Option<&'static str>) {
+        *self.last_beat.lock() = Instant::now();
+        *self.current_monitor.lock() = current_monitor;
+    }
+    
+    pub fn since_last_beat(&self) -> Duration {
+        self.last_beat.lock().elapsed()
+    }
+    
+    pub fn current_monitor(&self) -> Option<&'static str> {
+        *self.current_monitor.lock()
+    }
+}
+
+// Between attempts to re-initialize a backend after a driver reset
+pub const RESET_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+// A device still gone after this was removed rather than reset
+pub const RESET_GIVE_UP_AFTER: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetStep {
+    Wait,   // Not time for another attempt yet
+    Retry,  // Try re-initializing now
+    GiveUp, // Report the device gone, which stops the monitor
+}
+
+// Recovery from a driver reset (TDR on Windows, amdgpu reset on Linux), after which
+// the backend's handles are invalid: rather than stopping, the monitor re-initializes
+// on a backoff until the driver is back
+#[derive(Debug, Default)]
+pub struct DriverReset {
+    since: Option<Instant>,
+    last_attempt: Option<Instant>,
+}
+
+impl DriverReset {
+    pub fn in_progress(&self) -> bool {
+        self.since.is_some()
+    }
+    
+    // Device-lost error seen; returns false if a reset was already in progress
+    pub fn begin(&mut self, now: Instant) -> bool {
+        if self.since.is_some() {
+            return false;
+        }
+        self.since = Some(now);
+        self.last_attempt = Some(now);
+        true
+    }
+    
+    pub fn next_step(&mut self, now: Instant) -> ResetStep {
+        let Some(since) = self.since else {
+            return ResetStep::Wait;
+        };
+        if now.duration_since(since) > RESET_GIVE_UP_AFTER {
+            self.since = None;
+            return ResetStep::GiveUp;
+        }
+        if self.last_attempt.is_some_and(|last| now.duration_since(last) < RESET_RETRY_INTERVAL) {
+            return ResetStep::Wait;
+        }
+        self.last_attempt = Some(now);
+        ResetStep::Retry
+    }
+    
+    // Re-initialized; returns how long the device was unavailable
+    pub fn recovered(&mut self, now: Instant) -> Option<Duration> {
+        let since = self.since.take()?;
+        Some(now.duration_since(since))
+    }
+}
+
+// Time a single monitor update may take before it counts as a slow poll
+pub const DEFAULT_MONITOR_BUDGET: Duration = Duration::from_millis(250);
+// Consecutive slow polls before a monitor's cadence is halved
+const SLOW_POLLS_BEFORE_BACKOFF: u32 = 3;
+// Consecutive on-budget polls before a backed-off monitor speeds up again
+const FAST_POLLS_BEFORE_RECOVERY: u32 = 10;
+// Slowest cadence: polled once every this many cycles
+const MAX_CADENCE: u32 = 16;
+
+// A registered monitor plus the timing bookkeeping used to back it off
+struct MonitorSlot {
+    monitor: Box<dyn HardwareMonitor>,
+    last_duration: Duration,
+    slow_polls: u32,
+    slow_streak: u32,
+    fast_streak: u32,
+    cadence: u32,      // Polled every N cycles
+    skip_cycles: u32,  // Cycles left before the next poll
+    initialized: bool, // Initialization attempted
+    error: Option<MonitorError>, // Last failure, cleared by a successful poll
+    warmup_samples: usize,       // First samples left out of session min/max
+    traced_generation: u64,      // Last sensor trace request this monitor has traced
+}
+
+impl MonitorSlot {
+    fn new(monitor: Box<dyn HardwareMonitor>) -> Self {
+        Self {
+            monitor,
+            last_duration: Duration::ZERO,
+            slow_polls: 0,
+            slow_streak: 0,
+            fast_streak: 0,
+            cadence: 1,
+            skip_cycles: 0,
+            initialized: false,
+            error: None,
+            warmup_samples: DEFAULT_WARMUP_SAMPLES,
+            traced_generation: 0,
+        }
+    }
+    
+    fn initialize(&mut self) {
+        self.initialized = true;
+        if let Err(e) = self.monitor.initialize() {
+            crate::logger::log_monitor_error(self.monitor.name(), &e);
+            self.error = Some(e);
+        }
+    }
+    
+    // Whether the monitor should be polled at all: skipped while disabled in the
+    // config, and initialized on first use when it was enabled at runtime
+    fn ensure_ready(&mut self, config: Option<&SharedConfig>) -> bool {
+        let enabled = monitor_enabled(config, self.monitor.name());
+        if let Some(config) = config {
+            self.warmup_samples = config.read().warmup_samples(self.monitor.name());
+        }
+        if enabled && !self.initialized {
+            crate::logger::log_info(&format!("{} monitor enabled, initializing", self.monitor.name()));
+            self.initialize();
+        }
+        enabled
+    }
+    
+    // Whether the monitor is due this cycle, counting down skipped cycles otherwise
+    fn is_due(&mut self) -> bool {
+        if self.skip_cycles > 0 {
+            self.skip_cycles -= 1;
+            return false;
+        }
+        self.skip_cycles = self.cadence - 1;
+        true
+    }
+    
+    // Record how long an update took and adjust the cadence
+    fn record_duration(&mut self, elapsed: Duration, budget: Duration) {
+        self.last_duration = elapsed;
+        let name = self.monitor.name();
+        
+        if elapsed > budget {
+            self.slow_polls += 1;
+            self.slow_streak += 1;
+            self.fast_streak = 0;
+            crate::logger::log_warning(&format!(
+                "Slow poll: {} monitor took {}ms (budget {}ms)",
+                name, elapsed.as_millis(), budget.as_millis()
+            ));
+            
+            if self.slow_streak >= SLOW_POLLS_BEFORE_BACKOFF && self.cadence < MAX_CADENCE {
+                self.cadence *= 2;
+                self.slow_streak = 0;
+                crate::logger::log_warning(&format!(
+                    "{} monitor repeatedly over budget, now polled every {} cycles",
+                    name, self.cadence
+                ));
+            }
+        } else {
+            self.slow_streak = 0;
+            self.fast_streak += 1;
+            
+            if self.fast_streak >= FAST_POLLS_BEFORE_RECOVERY && self.cadence > 1 {
+                self.cadence /= 2;
+                self.fast_streak = 0;
+                crate::logger::log_info(&format!(
+                    "{} monitor back within budget, now polled every {} cycles",
+                    name, self.cadence
+                ));
+            }
+        }
+    }
+    
+    fn timing(&self) -> MonitorTiming {
+        MonitorTiming {
+            name: self.monitor.name().to_string(),
+            last_duration_ms: self.last_duration.as_secs_f64() * 1000.0,
+            slow_polls: self.slow_polls,
+            cadence: self.cadence,
+            error: self.error.as_ref().map(|e| e.to_string()),
+            hint: self.error.as_ref().and_then(MonitorError::hint),
+        }
+    }
+}
 
 pub struct MonitorRegistry {
-    monitors: Vec<Box<dyn HardwareMonitor>>,
+    monitors: Vec<MonitorSlot>,
+    heartbeat: Arc<MonitorHeartbeat>,
+    budget: Duration,
+    config: Option<SharedConfig>, // Source of the per-monitor enable flags
+}
+
+impl Default for MonitorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MonitorRegistry {
     pub fn new() -> Self {
         Self {
             monitors: Vec::new(),
+            heartbeat: Arc::new(MonitorHeartbeat::new()),
+            budget: DEFAULT_MONITOR_BUDGET,
+            config: None,
         }
     }
     
-    pub fn register_all_monitors(&mut self) {
-        // Register all available monitors
-        self.monitors.push(Box::new(nvidia::NvidiaMonitor::new()));
-        self.monitors.push(Box::new(amd::AmdMonitor::new()));
-        self.monitors.push(Box::new(intel::IntelMonitor::new()));
-        self.monitors.push(Box::new(apple::AppleMonitor::new()));
-        self.monitors.push(Box::new(generic::GenericMonitor::new()));
+    pub fn set_config(&mut self, config: SharedConfig) {
+        self.config = Some(config);
+    }
+    
+    pub fn heartbeat(&self) -> Arc<MonitorHeartbeat> {
+        self.heartbeat.clone()
+    }
+    
+    pub fn set_budget(&mut self, budget: Duration) {
+        self.budget = budget;
+    }
+    
+    // Monitors with a fault plan in the config are wrapped to inject it
+    pub fn register(&mut self, monitor: Box<dyn HardwareMonitor>) {
+        let plan = self.config.as_ref().and_then(|config| config.read().fault_plan(monitor.name()).cloned());
+        let monitor: Box<dyn HardwareMonitor> = match plan {
+            Some(plan) => {
+                crate::logger::log_warning(&format!("Fault injection enabled for the {} monitor", monitor.name()));
+                Box::new(fault::FaultInjector::new(monitor, plan))
+            }
+            None => monitor,
+        };
+        self.monitors.push(MonitorSlot::new(monitor));
+    }
+    
+    // Drop registered monitors `keep` rejects, before they are initialized
+    pub fn retain_monitors(&mut self, keep: impl Fn(&str) -> bool) {
+        self.monitors.retain(|slot| keep(slot.monitor.name()));
+    }
+    
+    pub fn monitor_names(&self) -> Vec<&'static str> {
+        self.monitors.iter().map(|slot| slot.monitor.name()).collect()
+    }
+    
+    pub fn timings(&self) -> Vec<MonitorTiming> {
+        self.monitors.iter().map(MonitorSlot::timing).collect()
+    }
+    
+    // Register only the vendor monitors that are compiled in and match the detected
+    // hardware, plus the generic sysinfo fallback; returns the decision for each
+    pub fn register_for_hardware(&mut self, hardware_info: &HardwareInfo) -> Vec<MonitorSelection> {
+        type MonitorFactory = fn() -> Box<dyn HardwareMonitor>;
+        let candidates: [(&str, bool, MonitorFactory); 17] = [
+            ("nvidia", cfg!(feature = "nvidia"), || Box::new(nvidia::NvidiaMonitor::new())),
+            ("amd", cfg!(feature = "amd"), || Box::new(amd::AmdMonitor::new())),
+            ("intel", cfg!(feature = "intel"), || Box::new(intel::IntelMonitor::new())),
+            ("apple", cfg!(feature = "apple"), || Box::new(apple::AppleMonitor::new())),
+            ("storage", true, || Box::new(storage::StorageMonitor::new())),
+            ("macos_io", true, || Box::new(macos_io::MacIoMonitor::new())),
+            ("acpi_thermal", true, || Box::new(acpi_thermal::AcpiThermalMonitor::new())),
+            ("board_wmi", true, || Box::new(board_wmi::BoardWmiMonitor::new())),
+            ("battery", true, || Box::new(battery::BatteryMonitor::new())),
+            ("etw", true, || Box::new(etw::EtwMonitor::new())),
+            ("drm_engines", true, || Box::new(drm_engines::DrmEnginesMonitor::new())),
+            ("network", true, || Box::new(network::NetworkMonitor::new())),
+            ("smart", cfg!(feature = "smart"), || Box::new(smart::SmartMonitor::new())),
+            ("pools", true, || Box::new(pools::PoolMonitor::new())),
+            ("lm-sensors", cfg!(feature = "lm-sensors"), || Box::new(super_io::SuperIoMonitor::new())),
+            ("ntp", true, || Box::new(ntp::NtpMonitor::new())),
+            ("ebpf", cfg!(feature = "ebpf"), || Box::new(ebpf_io::EbpfIoMonitor::new())),
+        ];
+        
+        let mut selections = Vec::new();
+        for (feature, compiled_in, make_monitor) in candidates {
+            let monitor = make_monitor();
+            let (selected, reason) = if !compiled_in {
+                (false, format!("not compiled in (feature \"{}\" disabled)", feature))
+            } else if monitor.supports_hardware(hardware_info) {
+                (true, "matching hardware detected".to_string())
+            } else {
+                (false, "no matching hardware detected".to_string())
+            };
+            
+            selections.push(MonitorSelection {
+                name: monitor.name().to_string(),
+                selected,
+                reason,
+            });
+            if selected {
+                self.register(monitor);
+            }
+        }
+        
+        // Generic monitor supports all hardware as a fallback
+        self.register(Box::new(generic::GenericMonitor::new()));
+        selections.push(MonitorSelection {
+            name: "Generic".to_string(),
+            selected: true,
+            reason: "sysinfo fallback, always used".to_string(),
+        });
+        
+        for selection in &selections {
+            crate::logger::log_info(&format!(
+                "{} monitor {}: {}",
+                selection.name,
+                if selection.selected { "selected" } else { "skipped" },
+                selection.reason
+            ));
+        }
+        selections
+    }
+    
+    // Developer mode: only the synthetic load generator, no hardware monitors
+    pub fn register_synthetic_load(&mut self, load: synthetic::SyntheticLoad) -> Vec<MonitorSelection> {
+        self.register(Box::new(synthetic::SyntheticLoadMonitor::new(load)));
+        crate::logger::log_info("Synthetic load requested, hardware monitors skipped");
+        vec![MonitorSelection {
+            name: "Synthetic".to_string(),
+            selected: true,
+            reason: format!("{} requested on the command line", synthetic::SYNTHETIC_LOAD_FLAG),
+        }]
     }
     
     pub fn initialize_for_hardware(&mut self, hardware_info: &HardwareInfo) -> Result<(), Box<dyn std::error::Error>> {
-        for monitor in &mut self.monitors {
-            if monitor.supports_hardware(hardware_info) {
-                if let Err(e) = monitor.initialize() {
-                    crate::logger::log_error(&format!("Failed to initialize monitor: {}", e), &*e);
-                }
+        for slot in &mut self.monitors {
+            if !slot.monitor.supports_hardware(hardware_info) {
+                continue;
+            }
+            // Disabled monitors aren't touched until they're switched back on
+            let enabled = monitor_enabled(self.config.as_ref(), slot.monitor.name());
+            if enabled {
+                slot.initialize();
+            } else {
+                crate::logger::log_info(&format!("{} monitor disabled in config, not initializing", slot.monitor.name()));
             }
         }
         Ok(())
     }
     
+    // Split into one task per monitor for independent scheduling
+    pub fn into_tasks(self) -> Vec<MonitorTask> {
+        let budget = self.budget;
+        let config = self.config;
+        self.monitors
+            .into_iter()
+            .map(|slot| MonitorTask {
+                slot,
+                heartbeat: Arc::new(MonitorHeartbeat::new()),
+                budget,
+                config: config.clone(),
+            })
+            .collect()
+    }
+    
     pub fn update_all_metrics(&mut self, state: &SharedAppState) -> Result<(), Box<dyn std::error::Error>> {
-        for monitor in &mut self.monitors {
-            if let Err(e) = monitor.update_metrics(state) {
-                crate::logger::log_error(&format!("Monitor update failed: {}", e), &*e);
+        let mut panicked = Vec::new();
+        
+        for (index, slot) in self.monitors.iter_mut().enumerate() {
+            if !slot.ensure_ready(self.config.as_ref()) {
+                continue;
+            }
+            // Monitors that keep blowing their budget are polled less often
+            if slot.is_due() && !poll_slot(slot, &self.heartbeat, self.budget, state) {
+                panicked.push(index);
             }
         }
+        self.heartbeat.beat(None);
+        
+        for index in panicked.into_iter().rev() {
+            self.monitors.remove(index);
+        }
+        
+        let mut app_state = state.write();
+        for slot in &self.monitors {
+            app_state.poller.record_timing(slot.timing());
+        }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+// One monitor polled on its own schedule, so a slow backend can't delay the others
+pub struct MonitorTask {
+    slot: MonitorSlot,
+    heartbeat: Arc<MonitorHeartbeat>,
+    budget: Duration,
+    config: Option<SharedConfig>,
+}
+
+impl MonitorTask {
+    pub fn name(&self) -> &'static str {
+        self.slot.monitor.name()
+    }
+    
+    pub fn heartbeat(&self) -> Arc<MonitorHeartbeat> {
+        self.heartbeat.clone()
+    }
+    
+    // Run one polling cycle; returns false once the monitor panicked and should stop
+    pub fn poll(&mut self, state: &SharedAppState) -> bool {
+        if !self.slot.ensure_ready(self.config.as_ref()) || !self.slot.is_due() {
+            return true;
+        }
+        let healthy = poll_slot(&mut self.slot, &self.heartbeat, self.budget, state);
+        self.heartbeat.beat(None);
+        
+        if healthy {
+            let mut app_state = state.write();
+            app_state.poller.record_timing(self.slot.timing());
+            app_state.poller.last_poll = Some(chrono::Utc::now());
+        }
+        healthy
+    }
+}
+
+// Update one monitor, timing it and catching panics; returns false if it panicked
+fn poll_slot(
+    slot: &mut MonitorSlot,
+    heartbeat: &MonitorHeartbeat,
+    budget: Duration,
+    state: &SharedAppState,
+) -> bool {
+    let monitor = &mut slot.monitor;
+    heartbeat.beat(Some(monitor.name()));
+    let before = state.read().latest_sample_times();
+    let started = Instant::now();
+    
+    // A requested sensor trace covers one poll of every monitor
+    let trace_generation = crate::logger::sensor_trace_generation();
+    let tracing = slot.traced_generation != trace_generation;
+    slot.traced_generation = trace_generation;
+    if tracing {
+        crate::logger::log_info(&format!("Sensor trace - {} monitor poll", monitor.name()));
+    }
+    crate::logger::set_sensor_tracing(tracing);
+    
+    // A panicking monitor is disabled instead of taking the polling thread down
+    let result = panic::catch_unwind(AssertUnwindSafe(|| monitor.update_metrics(state)));
+    crate::logger::set_sensor_tracing(false);
+    let error = match result {
+        Ok(result) => result.err(),
+        Err(payload) => {
+            let message = format!("{} monitor panicked: {}", monitor.name(), panic_message(&*payload));
+            crate::logger::log_warning(&format!("{}; disabling it", message));
+            disable_monitor(state, monitor.name(), message);
+            return false;
+        }
+    };
+    
+    let mut elapsed = started.elapsed();
+    if let Some(e) = &error {
+        crate::logger::log_monitor_error(monitor.name(), e);
+        if e.stops_monitor() {
+            let mut message = format!("{} monitor stopped: {}", monitor.name(), e);
+            if let Some(hint) = e.hint() {
+                message.push_str(&format!(" ({})", hint));
+            }
+            disable_monitor(state, monitor.name(), message);
+            return false;
+        }
+        // A backend that gave up waiting on its device counts as a slow poll
+        if matches!(e, MonitorError::Timeout(_)) {
+            elapsed = elapsed.max(budget + Duration::from_millis(1));
+        }
+    }
+    
+    slot.error = error;
+    slot.record_duration(elapsed, budget);
+    state.write().attribute_updates(&before, slot.monitor.name(), slot.warmup_samples);
+    true
+}
+
+fn disable_monitor(state: &SharedAppState, name: &str, message: String) {
+    let mut app_state = state.write();
+    app_state.poller.disabled_monitors.push(name.to_string());
+    app_state.poller.last_error = Some(message);
+}
+
+fn monitor_enabled(config: Option<&SharedConfig>, name: &str) -> bool {
+    config.is_none_or(|config| config.read().monitor_enabled(name))
+}
+
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::AppState;
+    
+    struct PanickingMonitor;
+    
+    impl HardwareMonitor for PanickingMonitor {
+        fn name(&self) -> &'static str {
+            "Panicking"
+        }
+        
+        fn initialize(&mut self) -> Result<(), MonitorError> {
+            Ok(())
+        }
+        
+        fn update_metrics(&mut self, _state: &SharedAppState) -> Result<(), MonitorError> {
+            panic!("sensor exploded");
+        }
+        
+        fn supports_hardware(&self, _info: &HardwareInfo) -> bool {
+            true
+        }
+    }
+    
+    struct FailingMonitor(MonitorError);
+    
+    impl HardwareMonitor for FailingMonitor {
+        fn name(&self) -> &'static str {
+            "Failing"
+        }
+        
+        fn initialize(&mut self) -> Result<(), MonitorError> {
+            Ok(())
+        }
+        
+        fn update_metrics(&mut self, _state: &SharedAppState) -> Result<(), MonitorError> {
+            Err(self.0.clone())
+        }
+        
+        fn supports_hardware(&self, _info: &HardwareInfo) -> bool {
+            true
+        }
+    }
+    
+    #[test]
+    fn test_monitor_errors_handled_by_kind() {
+        let state = AppState::new_shared(1000);
+        let budget = Duration::from_millis(100);
+        let heartbeat = MonitorHeartbeat::new();
+        
+        // Bad readings are transient: the monitor keeps running and reports the error
+        let mut slot = MonitorSlot::new(Box::new(FailingMonitor(MonitorError::Parse("bad value".to_string()))));
+        assert!(poll_slot(&mut slot, &heartbeat, budget, &state));
+        assert_eq!(slot.timing().error.as_deref(), Some("unexpected sensor data: bad value"));
+        assert_eq!(slot.slow_polls, 0);
+        
+        // Timeouts count against the budget
+        let mut slot = MonitorSlot::new(Box::new(FailingMonitor(MonitorError::Timeout("SMBus".to_string()))));
+        assert!(poll_slot(&mut slot, &heartbeat, budget, &state));
+        assert_eq!(slot.slow_polls, 1);
+        
+        // Permission problems stop the monitor and tell the user how to fix them
+        let mut slot = MonitorSlot::new(Box::new(FailingMonitor(MonitorError::PermissionDenied("MSR".to_string()))));
+        assert!(!poll_slot(&mut slot, &heartbeat, budget, &state));
+        let app_state = state.read();
+        assert_eq!(app_state.poller.disabled_monitors, vec!["Failing".to_string()]);
+        assert!(app_state.poller.last_error.as_deref().unwrap().contains("Administrator"));
+    }
+    
+    #[test]
+    fn test_io_errors_map_to_monitor_errors() {
+        use std::io::{Error, ErrorKind};
+        
+        assert!(matches!(MonitorError::from(Error::from(ErrorKind::PermissionDenied)), MonitorError::PermissionDenied(_)));
+        assert!(matches!(MonitorError::from(Error::from(ErrorKind::TimedOut)), MonitorError::Timeout(_)));
+        assert!(matches!(MonitorError::from(Error::from(ErrorKind::NotFound)), MonitorError::DeviceGone(_)));
+        assert!(matches!(MonitorError::from("x".parse::<u32>().unwrap_err()), MonitorError::Parse(_)));
+    }
+    
+    #[test]
+    fn test_panicking_monitor_is_disabled() {
+        let state = AppState::new_shared(1000);
+        let mut registry = MonitorRegistry::new();
+        registry.register(Box::new(PanickingMonitor));
+        registry.register(Box::new(generic::GenericMonitor::new()));
+        
+        registry.update_all_metrics(&state).expect("Update should not fail");
+        
+        assert_eq!(registry.monitor_names(), vec!["Generic"]);
+        let app_state = state.read();
+        assert_eq!(app_state.poller.disabled_monitors, vec!["Panicking".to_string()]);
+        assert!(app_state.poller.last_error.as_deref().unwrap().contains("sensor exploded"));
+        
+        // Heartbeat is cleared once the cycle finishes
+        assert!(registry.heartbeat().current_monitor().is_none());
+    }
+    
+    #[test]
+    fn test_driver_reset_retries_then_gives_up() {
+        let start = Instant::now();
+        let mut reset = DriverReset::default();
+        assert_eq!(reset.next_step(start), ResetStep::Wait);
+        
+        assert!(reset.begin(start));
+        assert!(!reset.begin(start + Duration::from_millis(10)));
+        assert_eq!(reset.next_step(start + Duration::from_secs(1)), ResetStep::Wait);
+        assert_eq!(reset.next_step(start + RESET_RETRY_INTERVAL), ResetStep::Retry);
+        assert_eq!(reset.next_step(start + RESET_RETRY_INTERVAL), ResetStep::Wait);
+        assert_eq!(reset.recovered(start + Duration::from_secs(3)), Some(Duration::from_secs(3)));
+        assert!(!reset.in_progress());
+        
+        // A device that never comes back is given up on
+        reset.begin(start);
+        assert_eq!(reset.next_step(start + RESET_GIVE_UP_AFTER + Duration::from_secs(1)), ResetStep::GiveUp);
+        assert!(!reset.in_progress());
+    }
+    
+    #[test]
+    fn test_slow_monitor_backs_off_and_recovers() {
+        let budget = Duration::from_millis(100);
+        let mut slot = MonitorSlot::new(Box::new(PanickingMonitor));
+        
+        for _ in 0..SLOW_POLLS_BEFORE_BACKOFF {
+            assert!(slot.is_due());
+            slot.record_duration(Duration::from_millis(500), budget);
+        }
+        assert_eq!(slot.cadence, 2);
+        assert_eq!(slot.slow_polls, SLOW_POLLS_BEFORE_BACKOFF);
+        
+        // Every other cycle is skipped at cadence 2
+        assert!(slot.is_due());
+        assert!(!slot.is_due());
+        assert!(slot.is_due());
+        
+        for _ in 0..FAST_POLLS_BEFORE_RECOVERY {
+            slot.record_duration(Duration::from_millis(10), budget);
+        }
+        assert_eq!(slot.cadence, 1);
+        assert_eq!(slot.timing().last_duration_ms, 10.0);
+    }
+    
+    #[test]
+    fn test_update_records_timings() {
+        let state = AppState::new_shared(1000);
+        let mut registry = MonitorRegistry::new();
+        registry.register(Box::new(generic::GenericMonitor::new()));
+        
+        registry.update_all_metrics(&state).expect("Update should not fail");
+        
+        let timings = state.read().poller.monitor_timings.clone();
+        assert_eq!(timings.len(), 1);
+        assert_eq!(timings[0].name, "Generic");
+        assert_eq!(timings[0].cadence, 1);
+    }
+    
+    #[test]
+    fn test_register_for_hardware_skips_unmatched_vendors() {
+        use crate::hardware_detection::{CpuTopology, CpuVendor, GpuVendor, Platform, SoftwareVersions};
+        
+        let info = HardwareInfo {
+            cpu_vendor: CpuVendor::Intel,
+            gpu_vendors: vec![GpuVendor::Intel],
+            platform: Platform::Linux,
+            cpu_topology: CpuTopology::default(),
+            versions: SoftwareVersions::default(),
+        };
+        let mut registry = MonitorRegistry::new();
+        let selections = registry.register_for_hardware(&info);
+        
+        let names = registry.monitor_names();
+        assert!(!names.contains(&"NVIDIA"));
+        assert!(!names.contains(&"AMD"));
+        assert!(!names.contains(&"Apple"));
+        assert!(names.contains(&"Generic"));
+        assert_eq!(names.contains(&"Intel"), cfg!(feature = "intel"));
+        
+        let nvidia = selections.iter().find(|selection| selection.name == "NVIDIA").unwrap();
+        assert!(!nvidia.selected);
+        assert!(names.contains(&"Storage"));
+        assert!(!names.contains(&"MacIO"));
+        assert!(!names.contains(&"ACPI Thermal"));
+        assert!(!names.contains(&"Board WMI"));
+        assert!(names.contains(&"Battery"));
+        assert!(!names.contains(&"ETW"));
+        assert!(names.contains(&"DRM Engines"));
+        assert!(names.contains(&"Network"));
+        assert_eq!(names.contains(&"SMART"), cfg!(feature = "smart"));
+        assert!(names.contains(&"Storage Pools"));
+        assert_eq!(names.contains(&"Super I/O"), cfg!(feature = "lm-sensors"));
+        assert!(names.contains(&"NTP"));
+        assert_eq!(names.contains(&"eBPF I/O"), cfg!(feature = "ebpf"));
+        assert_eq!(selections.len(), 18);
+    }
+    
+    #[test]
+    fn test_disabled_monitor_is_skipped_until_enabled() {
+        use crate::config::AppConfig;
+        
+        let state = AppState::new_shared(1000);
+        let config = AppConfig::new_shared(AppConfig::default());
+        config.write().set_monitor_enabled("Panicking", false);
+        
+        let mut registry = MonitorRegistry::new();
+        registry.set_config(config.clone());
+        registry.register(Box::new(PanickingMonitor));
+        
+        // Disabled: never polled, so it can't panic
+        registry.update_all_metrics(&state).expect("Update should not fail");
+        assert_eq!(registry.monitor_names(), vec!["Panicking"]);
+        
+        // Re-enabled at runtime: polled on the next cycle
+        config.write().set_monitor_enabled("Panicking", true);
+        registry.update_all_metrics(&state).expect("Update should not fail");
+        assert!(registry.monitor_names().is_empty());
+    }
+    
+    #[test]
+    fn test_monitor_tasks_poll_independently() {
+        let state = AppState::new_shared(1000);
+        let mut registry = MonitorRegistry::new();
+        registry.register(Box::new(PanickingMonitor));
+        registry.register(Box::new(generic::GenericMonitor::new()));
+        
+        let mut tasks = registry.into_tasks();
+        assert_eq!(tasks.len(), 2);
+        
+        // The panicking task stops, the other keeps reporting
+        assert!(!tasks[0].poll(&state));
+        assert!(tasks[1].poll(&state));
+        
+        let app_state = state.read();
+        assert!(app_state.poller.last_poll.is_some());
+        assert_eq!(app_state.poller.monitor_timings.len(), 1);
+        assert_eq!(app_state.poller.monitor_timings[0].name, "Generic");
+    }
+
+}
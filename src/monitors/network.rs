@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::path::Path;
+use chrono::Utc;
+use sysinfo::Networks;
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, Platform};
+use crate::model::{CounterMetric, InterfaceReading, SharedAppState};
+
+// Linux lists bridges, veth pairs and tunnels here; their traffic also crosses a physical
+// interface, so counting them would double it
+const VIRTUAL_INTERFACES: &str = "/sys/devices/virtual/net";
+
+// Cumulative counters of one interface between polls
+#[derive(Debug, Default)]
+struct InterfaceCounters {
+    received: CounterMetric,
+    transmitted: CounterMetric,
+    errors: CounterMetric,
+}
+
+// Per-interface throughput, packet errors and link speed from sysinfo's interface list.
+// MacIO reads macOS's interface counters instead
+pub struct NetworkMonitor {
+    networks: Networks,
+    counters: HashMap<String, InterfaceCounters>,
+    initialized: bool,
+}
+
+impl Default for NetworkMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetworkMonitor {
+    pub fn new() -> Self {
+        Self {
+            networks: Networks::new(),
+            counters: HashMap::new(),
+            initialized: false,
+        }
+    }
+}
+
+impl HardwareMonitor for NetworkMonitor {
+    fn name(&self) -> &'static str {
+        "Network"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        self.networks.refresh_list();
+        if !self.networks.list().keys().any(|name| is_physical(name)) {
+            return Err(MonitorError::DriverMissing("no network interfaces found".to_string()));
+        }
+        self.initialized = true;
+        crate::logger::log_info("Network monitor initialized using sysinfo");
+        Ok(())
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        if !self.initialized {
+            return Ok(());
+        }
+        // Picks up interfaces plugged in since the last poll, e.g. a USB adapter or VPN
+        self.networks.refresh_list();
+        let now = Utc::now();
+        let mut readings = Vec::new();
+        for (name, data) in self.networks.list().iter().filter(|(name, _)| is_physical(name)) {
+            let counters = self.counters.entry(name.clone()).or_default();
+            let received = counters.received.record_at(now, data.total_received());
+            let transmitted = counters.transmitted.record_at(now, data.total_transmitted());
+            let errors = counters.errors.record_at(now, data.total_errors_on_received() + data.total_errors_on_transmitted());
+            if let (Some(received), Some(transmitted), Some(errors)) = (received, transmitted, errors) {
+                readings.push(InterfaceReading {
+                    name: name.clone(),
+                    receive_speed: received / 1024.0 / 1024.0,
+                    transmit_speed: transmitted / 1024.0 / 1024.0,
+                    packet_errors: errors,
+                    link_speed_mbps: link_speed_mbps(name),
+                });
+            }
+        }
+        // Stable order for interfaces first seen in the same poll
+        readings.sort_by(|a, b| a.name.cmp(&b.name));
+
+        state.write().network.update_interfaces(&readings);
+        Ok(())
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        info.platform != Platform::MacOS
+    }
+}
+
+fn is_physical(name: &str) -> bool {
+    name != "lo" && !name.to_lowercase().contains("loopback") && !Path::new(VIRTUAL_INTERFACES).join(name).exists()
+}
+
+// Negotiated speed in Mb/s; sysfs reads -1 or fails while the link is down
+#[cfg(target_os = "linux")]
+fn link_speed_mbps(name: &str) -> Option<u64> {
+    let speed = std::fs::read_to_string(Path::new("/sys/class/net").join(name).join("speed")).ok()?;
+    speed.trim().parse::<i64>().ok().filter(|speed| *speed > 0).map(|speed| speed as u64)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn link_speed_mbps(_name: &str) -> Option<u64> {
+    None
+}
@@ -0,0 +1,215 @@
+use std::process::Command;
+use std::time::{Duration, Instant};
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, Platform};
+use crate::model::SharedAppState;
+
+// Time daemons measure the offset every minute or more, so reading it more often only repeats it
+const READ_INTERVAL: Duration = Duration::from_secs(10);
+
+// The system clock against NTP time, as the local time daemon measured it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockReading {
+    pub offset_ms: f64, // Positive when the system clock is ahead
+    pub server: Option<String>,
+}
+
+// The daemon keeping the clock in sync, which is asked for its latest measurement
+// rather than querying a server from the dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeDaemon {
+    Chrony,
+    Timesyncd,
+    Ntpd,
+    W32time,
+}
+
+impl TimeDaemon {
+    const ALL: [TimeDaemon; 4] = [TimeDaemon::Chrony, TimeDaemon::Timesyncd, TimeDaemon::Ntpd, TimeDaemon::W32time];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimeDaemon::Chrony => "chrony",
+            TimeDaemon::Timesyncd => "systemd-timesyncd",
+            TimeDaemon::Ntpd => "ntpd",
+            TimeDaemon::W32time => "Windows Time",
+        }
+    }
+
+    fn command(&self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            TimeDaemon::Chrony => ("chronyc", &["-c", "tracking"]),
+            TimeDaemon::Timesyncd => ("timedatectl", &["timesync-status"]),
+            TimeDaemon::Ntpd => ("ntpq", &["-c", "rv 0 offset"]),
+            TimeDaemon::W32time => ("w32tm", &["/query", "/status", "/verbose"]),
+        }
+    }
+
+    pub fn parse(&self, output: &str) -> Option<ClockReading> {
+        match self {
+            TimeDaemon::Chrony => parse_chrony_tracking(output),
+            TimeDaemon::Timesyncd => parse_timesyncd_status(output),
+            TimeDaemon::Ntpd => parse_ntpq_offset(output),
+            TimeDaemon::W32time => parse_w32tm_status(output),
+        }
+    }
+
+    fn read(&self) -> Result<ClockReading, MonitorError> {
+        let (program, args) = self.command();
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| MonitorError::DriverMissing(format!("could not run {} ({})", program, e)))?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        self.parse(&text)
+            .ok_or_else(|| MonitorError::Parse(format!("{} reported no clock offset; is it synchronized?", self.name())))
+    }
+}
+
+// `chronyc -c tracking`: reference id, server, stratum, reference time, system time,
+// last offset, ... leap status. The last offset is in seconds, positive when the clock
+// was ahead; a stratum of 0 means no server was selected yet
+pub fn parse_chrony_tracking(csv: &str) -> Option<ClockReading> {
+    let fields: Vec<&str> = csv.trim().split(',').collect();
+    let stratum: u32 = fields.get(2)?.parse().ok()?;
+    if stratum == 0 || fields.last().is_some_and(|leap| leap.starts_with("Not synchronised")) {
+        return None;
+    }
+    let offset: f64 = fields.get(5)?.parse().ok()?;
+    Some(ClockReading { offset_ms: offset * 1000.0, server: Some(fields[1].to_string()) })
+}
+
+// `timedatectl timesync-status`, e.g. "Server: 162.159.200.1 (time.cloudflare.com)"
+// and "Offset: -1.146ms". The offset is NTP's, the server's time minus ours
+pub fn parse_timesyncd_status(text: &str) -> Option<ClockReading> {
+    let field = |name: &str| {
+        text.lines()
+            .find_map(|line| line.trim().strip_prefix(name)?.strip_prefix(':').map(str::trim))
+    };
+    let offset = field("Offset")?;
+    let (number, scale) = if let Some(us) = offset.strip_suffix("us").or_else(|| offset.strip_suffix("µs")) {
+        (us, 0.001)
+    } else if let Some(ms) = offset.strip_suffix("ms") {
+        (ms, 1.0)
+    } else {
+        (offset.strip_suffix('s')?, 1000.0)
+    };
+    let offset_ms: f64 = number.trim_start_matches('+').parse::<f64>().ok()? * scale;
+    Some(ClockReading { offset_ms: -offset_ms, server: field("Server").map(str::to_string) })
+}
+
+// `ntpq -c "rv 0 offset"`: "offset=-0.123456", NTP's offset in milliseconds
+pub fn parse_ntpq_offset(text: &str) -> Option<ClockReading> {
+    let offset = text.split([',', '\n']).find_map(|pair| pair.trim().strip_prefix("offset="))?;
+    let offset_ms: f64 = offset.trim().parse().ok()?;
+    Some(ClockReading { offset_ms: -offset_ms, server: None })
+}
+
+// `w32tm /query /status /verbose`: "Source: time.windows.com,0x9" and "Phase Offset:
+// 0.0002156s", NTP's offset in seconds. Unsynchronized clocks use the "Local CMOS Clock"
+pub fn parse_w32tm_status(text: &str) -> Option<ClockReading> {
+    let field = |name: &str| {
+        text.lines()
+            .find_map(|line| line.trim().strip_prefix(name)?.strip_prefix(':').map(str::trim))
+    };
+    let source = field("Source")?;
+    if source.starts_with("Local CMOS Clock") || source.starts_with("Free-running") {
+        return None;
+    }
+    let offset: f64 = field("Phase Offset")?.strip_suffix('s')?.parse().ok()?;
+    let server = source.split(',').next().unwrap_or(source).to_string();
+    Some(ClockReading { offset_ms: -offset * 1000.0, server: Some(server) })
+}
+
+// Offset of the system clock from NTP time, from whichever time daemon is running
+pub struct NtpMonitor {
+    daemon: Option<TimeDaemon>,
+    last_read: Option<Instant>,
+}
+
+impl Default for NtpMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NtpMonitor {
+    pub fn new() -> Self {
+        Self {
+            daemon: None,
+            last_read: None,
+        }
+    }
+}
+
+impl HardwareMonitor for NtpMonitor {
+    fn name(&self) -> &'static str {
+        "NTP"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        let daemon = TimeDaemon::ALL
+            .into_iter()
+            .find(|daemon| daemon.read().is_ok())
+            .ok_or_else(|| MonitorError::DriverMissing("no synchronized chrony, systemd-timesyncd, ntpd or Windows Time service".to_string()))?;
+        self.daemon = Some(daemon);
+        crate::logger::log_info(&format!("NTP monitor initialized using {}", daemon.name()));
+        Ok(())
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        let Some(daemon) = self.daemon else {
+            return Ok(());
+        };
+        if self.last_read.is_some_and(|at| at.elapsed() < READ_INTERVAL) {
+            return Ok(());
+        }
+        self.last_read = Some(Instant::now());
+        // Lost sync, e.g. the network is down; the offset is unknown until it's back
+        let Ok(reading) = daemon.read() else {
+            return Ok(());
+        };
+
+        let mut app_state = state.write();
+        app_state.clock.ntp_offset.update(reading.offset_ms);
+        app_state.clock.ntp_source = Some(match reading.server {
+            Some(server) => format!("{}, {}", daemon.name(), server),
+            None => daemon.name().to_string(),
+        });
+        Ok(())
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        matches!(info.platform, Platform::Linux | Platform::Windows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_offset_from_time_daemons() {
+        let chrony = "A29FC87B,162.159.200.123,3,1760600000.123,0.000012345,-0.000250000,0.000180,-3.214,-0.001,0.034,0.011,0.001,1031.9,Normal\n";
+        let reading = parse_chrony_tracking(chrony).unwrap();
+        assert!((reading.offset_ms + 0.25).abs() < 1e-9);
+        assert_eq!(reading.server.as_deref(), Some("162.159.200.123"));
+        assert_eq!(parse_chrony_tracking("00000000,,0,0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0,Not synchronised\n"), None);
+
+        // Behind the server by 1.146 ms, so -1.146 ms ahead of it
+        let timesyncd = "       Server: 162.159.200.1 (time.cloudflare.com)\nPoll interval: 34min 8s (min: 32s; max 34min 8s)\n\
+            \x20        Leap: normal\n       Offset: +1.146ms\n        Delay: 8.721ms\n";
+        let reading = parse_timesyncd_status(timesyncd).unwrap();
+        assert_eq!(reading.offset_ms, -1.146);
+        assert_eq!(reading.server.as_deref(), Some("162.159.200.1 (time.cloudflare.com)"));
+        assert!((parse_timesyncd_status("Offset: -345us\n").unwrap().offset_ms - 0.345).abs() < 1e-9);
+
+        assert_eq!(parse_ntpq_offset("offset=-0.512\n").unwrap().offset_ms, 0.512);
+
+        let w32tm = "Leap Indicator: 0(no warning)\nStratum: 4 (secondary reference - syncd by (S)NTP)\n\
+            Phase Offset: 0.0020000s\nSource: time.windows.com,0x9\n";
+        let reading = parse_w32tm_status(w32tm).unwrap();
+        assert!((reading.offset_ms + 2.0).abs() < 1e-9);
+        assert_eq!(reading.server.as_deref(), Some("time.windows.com"));
+        assert_eq!(parse_w32tm_status("Phase Offset: 0.0000000s\nSource: Local CMOS Clock\n"), None);
+    }
+}
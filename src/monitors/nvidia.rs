@@ -1,27 +1,311 @@
-use crate::hardware_detection::{HardwareMonitor, HardwareInfo, GpuVendor};
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, GpuVendor};
 use crate::model::SharedAppState;
+use std::collections::HashMap;
+#[cfg(feature = "nvidia")]
+use crate::monitors::{DriverReset, ResetStep};
 
 #[cfg(feature = "nvidia")]
-use nvml_wrapper::Nvml;
+use nvml_wrapper::{enums::device::UsedGpuMemory, error::NvmlError, struct_wrappers::device::ProcessInfo, Nvml};
+#[cfg(feature = "nvidia")]
+use crate::model::GpuProcessMemory;
 
 pub struct NvidiaMonitor {
     #[cfg(feature = "nvidia")]
     nvml: Option<Nvml>,
+    #[cfg(feature = "nvidia")]
+    reset: DriverReset,
+    #[cfg(feature = "nvidia")]
+    last_process_sample: u64, // NVML timestamp of the newest per-process sample seen
     initialized: bool,
 }
 
+impl Default for NvidiaMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NvidiaMonitor {
     pub fn new() -> Self {
         Self {
             #[cfg(feature = "nvidia")]
             nvml: None,
+            #[cfg(feature = "nvidia")]
+            reset: DriverReset::default(),
+            #[cfg(feature = "nvidia")]
+            last_process_sample: 0,
             initialized: false,
         }
     }
 }
 
+// After a TDR or driver upgrade every NVML handle is stale: drop them and reconnect
+#[cfg(feature = "nvidia")]
+impl NvidiaMonitor {
+    fn begin_reset(&mut self, state: &SharedAppState, detail: &str) {
+        self.nvml = None;
+        if self.reset.begin(std::time::Instant::now()) {
+            crate::logger::log_warning(&format!("NVIDIA driver reset ({}); reconnecting", detail));
+            state.write().gpu.driver_reset.update(true);
+        }
+    }
+    
+    // Whether NVML is connected again and the device can be polled
+    fn reconnect(&mut self, state: &SharedAppState) -> Result<bool, MonitorError> {
+        match self.reset.next_step(std::time::Instant::now()) {
+            ResetStep::Wait => Ok(false),
+            ResetStep::GiveUp => {
+                state.write().gpu.driver_reset.update(false);
+                Err(MonitorError::DeviceGone("GPU did not come back after a driver reset".to_string()))
+            }
+            ResetStep::Retry => match Nvml::init() {
+                Ok(nvml) => {
+                    self.nvml = Some(nvml);
+                    Ok(true)
+                }
+                Err(_) => Ok(false),
+            },
+        }
+    }
+    
+    // The reset is over once a poll gets through, not merely when NVML loads again
+    fn finish_reset(&mut self, state: &SharedAppState) {
+        if let Some(down) = self.reset.recovered(std::time::Instant::now()) {
+            crate::logger::log_info(&format!("NVIDIA driver back after {:.1}s", down.as_secs_f64()));
+            state.write().gpu.driver_reset.update(false);
+        }
+    }
+    
+    fn poll(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        let Some(ref nvml) = self.nvml else {
+            return Ok(());
+        };
+        
+        // Try to get the first GPU device
+        let device_count = nvml.device_count().unwrap_or(0);
+        if device_count == 0 {
+            return Ok(());
+        }
+        // Every GPU the driver sees is listed, though only the first one is monitored
+        let adapters: Option<Vec<String>> = state.read().gpu.adapters.is_empty().then(|| {
+            (0..device_count).map(|index| nvml.device_by_index(index).and_then(|device| device.name()).unwrap_or_else(|_| "NVIDIA GPU".to_string())).collect()
+        });
+        let device = match nvml.device_by_index(0) {
+            Ok(device) => device,
+            Err(e @ (NvmlError::GpuLost | NvmlError::NotFound | NvmlError::ResetRequired)) => return Err(e.into()),
+            Err(_) => return Ok(()),
+        };
+        
+        // Query the driver before taking the state lock, so a hung NVML call
+        // doesn't freeze the UI or block the watchdog from restarting polling
+        let name = device.name().ok();
+        let utilization = match device.utilization_rates() {
+            // The first query after a reset is where a stale handle shows
+            Err(e @ (NvmlError::GpuLost | NvmlError::ResetRequired)) => return Err(e.into()),
+            result => result.ok(),
+        };
+        let clock_speed = device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics).ok();
+        let memory_clock_speed = device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory).ok();
+        let video_clock_speed = device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Video).ok();
+        let memory_info = device.memory_info().ok();
+        let temperature = device.temperature(nvml_wrapper::enum_wrappers::device::TemperatureSensor::Gpu).ok();
+        let power = device.power_usage().ok();
+        let throttle_reasons = device.current_throttle_reasons().ok();
+        // NotSupported on passively cooled and most laptop GPUs: no fan sensor at all
+        let fan_speed = device.fan_speed(0).ok();
+        let compute_processes = device.running_compute_processes().ok();
+        let graphics_processes = device.running_graphics_processes().ok();
+        let pids = |processes: &Vec<ProcessInfo>| -> Vec<u32> {
+            processes.iter().map(|process| process.pid).collect()
+        };
+        let compute_pids = compute_processes.as_ref().map(pids);
+        let graphics_pids = graphics_processes.as_ref().map(pids);
+        let process_memory = match (&compute_processes, &graphics_processes) {
+            (Some(compute), Some(graphics)) => {
+                // Unavailable under WDDM, where Windows rather than the driver manages VRAM
+                let usage = |processes: &[ProcessInfo]| -> Vec<(u32, Option<u64>)> {
+                    processes
+                        .iter()
+                        .map(|process| match process.used_gpu_memory {
+                            UsedGpuMemory::Used(bytes) => (process.pid, Some(bytes / 1024 / 1024)),
+                            UsedGpuMemory::Unavailable => (process.pid, None),
+                        })
+                        .collect()
+                };
+                let processes = vram_by_process(&usage(compute), &usage(graphics))
+                    .into_iter()
+                    .map(|(pid, used_mb, compute)| GpuProcessMemory {
+                        pid,
+                        name: process_name(nvml, pid),
+                        used_mb,
+                        compute,
+                    })
+                    .collect::<Vec<_>>();
+                Some(processes)
+            }
+            _ => None,
+        };
+        let process_samples = match device.process_utilization_stats(self.last_process_sample) {
+            Ok(samples) => Some(samples),
+            // Nothing ran on the GPU since the last poll
+            Err(NvmlError::NotFound) => Some(Vec::new()),
+            Err(_) => None,
+        };
+        let engine_split = match (process_samples, compute_pids, graphics_pids) {
+            (Some(samples), Some(compute_pids), Some(graphics_pids)) => {
+                let samples: Vec<(u32, u64, u32)> = samples.iter().map(|sample| (sample.pid, sample.timestamp, sample.sm_util)).collect();
+                self.last_process_sample = samples.iter().map(|(_, timestamp, _)| *timestamp).max().unwrap_or(self.last_process_sample);
+                Some(split_sm_utilization(&samples, &compute_pids, &graphics_pids))
+            }
+            _ => None,
+        };
+        
+        let mut app_state = state.write();
+        
+        if app_state.gpu.name.is_none() {
+            app_state.gpu.name = name;
+        }
+        if let Some(adapters) = adapters {
+            app_state.gpu.adapters = adapters;
+        }
+        
+        // GPU Utilization
+        if let Some(utilization) = utilization {
+            app_state.gpu.utilization.update(utilization.gpu as f32);
+            // Memory controller busy time, unrelated to how much VRAM is allocated
+            app_state.gpu.memory_bandwidth.update(utilization.memory);
+        }
+        
+        // NVML has no copy engine counter, so only graphics and compute are split out
+        if let Some((graphics, compute)) = engine_split {
+            app_state.gpu.graphics_utilization.update(graphics);
+            app_state.gpu.compute_utilization.update(compute);
+        }
+        
+        // GPU Clock Speed
+        if let Some(clock_speed) = clock_speed {
+            app_state.gpu.clock_speed.update(clock_speed);
+        }
+        if let Some(memory_clock_speed) = memory_clock_speed {
+            app_state.gpu.memory_clock_speed.update(memory_clock_speed);
+        }
+        if let Some(video_clock_speed) = video_clock_speed {
+            app_state.gpu.video_clock_speed.update(video_clock_speed);
+        }
+        
+        // GPU Memory Utilization
+        if let Some(memory_info) = memory_info {
+            // Total VRAM doesn't change, so only record it once
+            if app_state.gpu.memory_total_mb.is_none() {
+                app_state.gpu.memory_total_mb = Some(memory_info.total / 1024 / 1024);
+            }
+            let used_mb = memory_info.used / 1024 / 1024;
+            app_state.gpu.update_memory_usage(used_mb);
+        }
+        
+        if let Some(process_memory) = process_memory {
+            app_state.gpu.process_memory = process_memory;
+        }
+        
+        // GPU Temperature
+        if let Some(temp) = temperature {
+            app_state.gpu.package_temperature.update(temp as f32);
+            app_state.gpu.temperature_sensors.update("GPU Core", temp as f32);
+        }
+        
+        // GPU Power Consumption
+        if let Some(power) = power {
+            let power_watts = (power as f32) / 1000.0; // Convert mW to W
+            app_state.gpu.power_consumption.update(power_watts);
+        }
+        
+        if let Some(fan_speed) = fan_speed {
+            app_state.gpu.update_fan_speed(fan_speed);
+        }
+        
+        // GPU Thermal Throttling
+        if let Some(throttle_reasons) = throttle_reasons {
+            let is_throttling = !throttle_reasons.is_empty();
+            app_state.gpu.thermal_throttling.update(is_throttling);
+        }
+        
+        Ok(())
+    }
+}
+
+// NVML gives the executable's path; the dashboard shows its file name
+#[cfg(feature = "nvidia")]
+fn process_name(nvml: &Nvml, pid: u32) -> String {
+    match nvml.sys_process_name(pid, 256) {
+        Ok(path) => path.rsplit(['/', '\\']).next().unwrap_or(&path).to_string(),
+        Err(_) => format!("pid {}", pid),
+    }
+}
+
+// VRAM per process from NVML's compute and graphics process lists, as (pid, MB, has a
+// compute context), largest first. A process with both contexts is listed twice with the
+// same allocation, so it counts once; processes without a reading are left out
+pub fn vram_by_process(compute: &[(u32, Option<u64>)], graphics: &[(u32, Option<u64>)]) -> Vec<(u32, u64, bool)> {
+    let mut processes: Vec<(u32, u64, bool)> = Vec::new();
+    for (list, is_compute) in [(compute, true), (graphics, false)] {
+        for &(pid, used_mb) in list {
+            let Some(used_mb) = used_mb else {
+                continue;
+            };
+            match processes.iter_mut().find(|(known, _, _)| *known == pid) {
+                Some(process) => process.1 = process.1.max(used_mb),
+                None => processes.push((pid, used_mb, is_compute)),
+            }
+        }
+    }
+    processes.sort_by_key(|(_, used_mb, _)| std::cmp::Reverse(*used_mb));
+    processes
+}
+
+// NVML reports streaming multiprocessor busy time per process rather than per engine.
+// A process with only a compute context (CUDA) counts as compute; anything else,
+// including processes that have exited since, as graphics. Takes (pid, timestamp, SM %)
+// samples and uses the newest of each process
+pub fn split_sm_utilization(samples: &[(u32, u64, u32)], compute_pids: &[u32], graphics_pids: &[u32]) -> (f32, f32) {
+    let mut latest: HashMap<u32, (u64, u32)> = HashMap::new();
+    for &(pid, timestamp, sm_util) in samples {
+        let entry = latest.entry(pid).or_insert((timestamp, sm_util));
+        if timestamp >= entry.0 {
+            *entry = (timestamp, sm_util);
+        }
+    }
+    let (mut graphics, mut compute) = (0.0, 0.0);
+    for (pid, (_, sm_util)) in latest {
+        if compute_pids.contains(&pid) && !graphics_pids.contains(&pid) {
+            compute += sm_util as f32;
+        } else {
+            graphics += sm_util as f32;
+        }
+    }
+    (graphics.min(100.0), compute.min(100.0))
+}
+
+#[cfg(feature = "nvidia")]
+impl From<NvmlError> for MonitorError {
+    fn from(error: NvmlError) -> Self {
+        let detail = error.to_string();
+        match error {
+            NvmlError::NoPermission => MonitorError::PermissionDenied(detail),
+            NvmlError::GpuLost | NvmlError::NotFound | NvmlError::ResetRequired => MonitorError::DeviceGone(detail),
+            NvmlError::Timeout => MonitorError::Timeout(detail),
+            NvmlError::Utf8Error(_) | NvmlError::UnexpectedVariant(_) => MonitorError::Parse(detail),
+            // Library/driver load failures and anything NVML can't do on this system
+            _ => MonitorError::DriverMissing(detail),
+        }
+    }
+}
+
 impl HardwareMonitor for NvidiaMonitor {
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn name(&self) -> &'static str {
+        "NVIDIA"
+    }
+    
+    fn initialize(&mut self) -> Result<(), MonitorError> {
         #[cfg(feature = "nvidia")]
         {
             match Nvml::init() {
@@ -31,69 +315,37 @@ impl HardwareMonitor for NvidiaMonitor {
                     crate::logger::log_info("NVIDIA NVML initialized successfully");
                     Ok(())
                 }
-                Err(e) => {
-                    crate::logger::log_error("Failed to initialize NVIDIA NVML", &e);
-                    Err(Box::new(e))
-                }
+                Err(e) => Err(e.into()),
             }
         }
         
         #[cfg(not(feature = "nvidia"))]
         {
-            Err("NVIDIA support not compiled in".into())
+            Err(MonitorError::DriverMissing("NVIDIA support not compiled in".to_string()))
         }
     }
     
-    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), Box<dyn std::error::Error>> {
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
         #[cfg(feature = "nvidia")]
         {
             if !self.initialized {
                 return Ok(());
             }
             
-            if let Some(ref nvml) = self.nvml {
-                let mut app_state = state.write();
-                
-                // Try to get the first GPU device
-                if let Ok(device_count) = nvml.device_count() {
-                    if device_count > 0 {
-                        if let Ok(device) = nvml.device_by_index(0) {
-                            // GPU Utilization
-                            if let Ok(utilization) = device.utilization_rates() {
-                                app_state.gpu.utilization.update(utilization.gpu as f32);
-                            }
-                            
-                            // GPU Clock Speed
-                            if let Ok(clock_speed) = device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics) {
-                                app_state.gpu.clock_speed.update(clock_speed as u32);
-                            }
-                            
-                            // GPU Memory Utilization
-                            if let Ok(memory_info) = device.memory_info() {
-                                let used_mb = (memory_info.used / 1024 / 1024) as u64;
-                                app_state.gpu.memory_utilization.update(used_mb);
-                            }
-                            
-                            // GPU Temperature
-                            if let Ok(temp) = device.temperature(nvml_wrapper::enum_wrappers::device::TemperatureSensor::Gpu) {
-                                app_state.gpu.package_temperature.update(temp as f32);
-                            }
-                            
-                            // GPU Power Consumption
-                            if let Ok(power) = device.power_usage() {
-                                let power_watts = (power as f32) / 1000.0; // Convert mW to W
-                                app_state.gpu.power_consumption.update(power_watts);
-                            }
-                            
-                            // GPU Thermal Throttling
-                            if let Ok(throttle_reasons) = device.current_throttle_reasons() {
-                                let is_throttling = !throttle_reasons.is_empty();
-                                app_state.gpu.thermal_throttling.update(is_throttling);
-                            }
-                        }
-                    }
-                }
+            // Handles are stale after a driver reset; reconnect before polling again
+            if self.reset.in_progress() && self.nvml.is_none() && !self.reconnect(state)? {
+                return Ok(());
             }
+            match self.poll(state) {
+                Err(MonitorError::DeviceGone(detail)) => self.begin_reset(state, &detail),
+                Err(e) => return Err(e),
+                Ok(()) => self.finish_reset(state),
+            }
+        }
+        
+        #[cfg(not(feature = "nvidia"))]
+        {
+            let _ = state;
         }
         
         Ok(())
@@ -102,4 +354,29 @@ impl HardwareMonitor for NvidiaMonitor {
     fn supports_hardware(&self, info: &HardwareInfo) -> bool {
         info.gpu_vendors.contains(&GpuVendor::NVIDIA)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sm_utilization_split_by_context() {
+        // A game (pid 10), a CUDA job (20) and an older sample of the CUDA job
+        let samples = [(10, 5, 30), (20, 3, 90), (20, 6, 55), (30, 6, 5)];
+        assert_eq!(split_sm_utilization(&samples, &[20], &[10]), (35.0, 55.0));
+        // An app with both contexts counts as graphics, and sums are capped
+        assert_eq!(split_sm_utilization(&[(10, 1, 80), (20, 1, 70)], &[10, 20], &[10]), (80.0, 70.0));
+        assert_eq!(split_sm_utilization(&[(10, 1, 80), (11, 1, 70)], &[], &[]), (100.0, 0.0));
+    }
+
+    #[test]
+    fn test_vram_attributed_per_process() {
+        // A training job (20), a game (10) that also has a compute context, and a
+        // process whose usage NVML can't report
+        let compute = [(20, Some(9800)), (10, Some(1500))];
+        let graphics = [(10, Some(1500)), (30, Some(200)), (40, None)];
+        assert_eq!(vram_by_process(&compute, &graphics), vec![(20, 9800, true), (10, 1500, true), (30, 200, false)]);
+        assert!(vram_by_process(&[], &[(40, None)]).is_empty());
+    }
+}
@@ -0,0 +1,351 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, Platform};
+use crate::model::{PoolDevice, PoolKind, SharedAppState, StoragePool};
+
+// Pool state changes rarely, and a scrub moves a fraction of a percent a minute
+const READ_INTERVAL: Duration = Duration::from_secs(15);
+
+const MDSTAT: &str = "/proc/mdstat";
+
+// Arrays in /proc/mdstat:
+//
+//     md1 : active raid5 sdc[0] sdd[1](F) sde[3]
+//           1953260544 blocks super 1.2 level 5, 512k chunk, algorithm 2 [3/2] [U_U]
+//           [===>.................]  recovery = 17.3% (169162496/976630272) finish=80.2min
+//
+// A member marked (F) has failed and (S) is a spare; an underscore in [U_U] is a missing one
+pub fn parse_mdstat(text: &str) -> Vec<StoragePool> {
+    let mut pools: Vec<StoragePool> = Vec::new();
+    for line in text.lines() {
+        if let Some((name, description)) = line.split_once(" : ").filter(|(name, _)| name.starts_with("md")) {
+            let mut words = description.split_whitespace();
+            let activity = words.next().unwrap_or_default();
+            let mut state = activity.to_string();
+            let mut devices = Vec::new();
+            for word in words {
+                let Some((device, flags)) = word.split_once('[') else {
+                    // Level, e.g. raid1, and "(auto-read-only)"
+                    state = format!("{} {}", state, word);
+                    continue;
+                };
+                let state = if flags.ends_with("(F)") {
+                    "faulty"
+                } else if flags.ends_with("(S)") {
+                    "spare"
+                } else {
+                    "in_sync"
+                };
+                devices.push(PoolDevice {
+                    name: device.to_string(),
+                    depth: 0,
+                    state: state.to_string(),
+                    read_errors: 0,
+                    write_errors: 0,
+                    checksum_errors: 0,
+                });
+            }
+            pools.push(StoragePool {
+                name: name.trim().to_string(),
+                kind: PoolKind::Mdraid,
+                healthy: activity == "active" && devices.iter().all(|device| device.state != "faulty"),
+                state,
+                scan: None,
+                scan_progress: None,
+                devices,
+            });
+            continue;
+        }
+        let Some(pool) = pools.last_mut() else {
+            continue;
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        // Members present, e.g. [UU_]; only on the blocks line
+        if let Some(members) = line.split_whitespace().last().and_then(|word| word.strip_prefix('[')?.strip_suffix(']')) {
+            if !members.is_empty() && members.chars().all(|c| c == 'U' || c == '_') {
+                if members.contains('_') {
+                    pool.healthy = false;
+                }
+                continue;
+            }
+        }
+        for action in ["resync", "recovery", "check", "repair", "reshape"] {
+            let Some(rest) = line.split_once(&format!("{} =", action)).map(|(_, rest)| rest) else {
+                continue;
+            };
+            pool.scan = Some(format!("{} in progress", action));
+            pool.scan_progress = rest.split_whitespace().next().and_then(|percent| percent.trim_end_matches('%').parse().ok());
+        }
+    }
+    pools
+}
+
+// Corrected read errors of a member, from /sys/block/<md>/md/dev-<member>/errors
+pub fn md_device_errors(sys_block: &Path, array: &str, device: &str) -> Option<u64> {
+    let path = sys_block.join(array).join("md").join(format!("dev-{}", device)).join("errors");
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+// Pools in `zpool status -p`:
+//
+//       pool: tank
+//      state: DEGRADED
+//       scan: scrub in progress since Sun Oct 11 00:24:01 2026
+//             0B repaired, 21.70% done, 01:15:00 to go
+//     config:
+//
+//             NAME        STATE     READ WRITE CKSUM
+//             tank        DEGRADED     0     0     0
+//               raidz1-0  DEGRADED     0     0     0
+//                 sdb     FAULTED      3    12     0  too many errors
+//
+// The first row of config is the pool itself; the rows under it are its vdevs, indented
+// two spaces per level. Log, cache and spare sections have no error counts and are skipped
+pub fn parse_zpool_status(text: &str) -> Vec<StoragePool> {
+    let mut pools: Vec<StoragePool> = Vec::new();
+    let mut in_scan = false;
+    let mut in_config = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed.strip_prefix("pool:") {
+            pools.push(StoragePool {
+                name: name.trim().to_string(),
+                kind: PoolKind::Zfs,
+                state: String::new(),
+                healthy: true,
+                scan: None,
+                scan_progress: None,
+                devices: Vec::new(),
+            });
+            in_scan = false;
+            in_config = false;
+            continue;
+        }
+        let Some(pool) = pools.last_mut() else {
+            continue;
+        };
+        if let Some(state) = trimmed.strip_prefix("state:") {
+            pool.state = state.trim().to_string();
+            pool.healthy = pool.state == "ONLINE";
+            in_scan = false;
+        } else if let Some(scan) = trimmed.strip_prefix("scan:") {
+            pool.scan = Some(scan.trim().to_string());
+            in_scan = true;
+        } else if trimmed.starts_with("config:") || trimmed.starts_with("errors:") {
+            in_scan = false;
+            in_config = trimmed.starts_with("config:");
+        } else if in_scan {
+            if let Some(percent) = trimmed.split(", ").find_map(|part| part.strip_suffix("% done")) {
+                pool.scan_progress = percent.trim().parse().ok();
+            }
+        } else if in_config && !trimmed.is_empty() && !trimmed.starts_with("NAME") {
+            let columns: Vec<&str> = trimmed.split_whitespace().collect();
+            let [name, state, read, write, checksum, ..] = columns[..] else {
+                continue;
+            };
+            let (Ok(read_errors), Ok(write_errors), Ok(checksum_errors)) = (read.parse(), write.parse(), checksum.parse()) else {
+                continue;
+            };
+            let device = PoolDevice {
+                name: name.to_string(),
+                depth: 0,
+                state: state.to_string(),
+                read_errors,
+                write_errors,
+                checksum_errors,
+            };
+            if device.errors() > 0 {
+                pool.healthy = false;
+            }
+            if name == pool.name {
+                continue;
+            }
+            // Rows are tab-indented, then two spaces per level; top-level vdevs have two
+            let indent = line.trim_start_matches('\t').len() - line.trim_start_matches('\t').trim_start().len();
+            pool.devices.push(PoolDevice { depth: (indent / 2).saturating_sub(1), ..device });
+        }
+    }
+    pools
+}
+
+// Health, scrub or rebuild progress and per-device errors of Linux software RAID and
+// ZFS pools. mdraid comes from /proc/mdstat; ZFS needs zpool on the PATH
+pub struct PoolMonitor {
+    has_mdraid: bool,
+    has_zfs: bool,
+    last_read: Option<Instant>,
+    reported: Vec<String>, // Pools already warned about while unhealthy
+    initialized: bool,
+}
+
+impl Default for PoolMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoolMonitor {
+    pub fn new() -> Self {
+        Self {
+            has_mdraid: false,
+            has_zfs: false,
+            last_read: None,
+            reported: Vec::new(),
+            initialized: false,
+        }
+    }
+
+    fn read_pools(&self) -> Vec<StoragePool> {
+        let mut pools = Vec::new();
+        if self.has_mdraid {
+            let text = std::fs::read_to_string(MDSTAT).unwrap_or_default();
+            for mut pool in parse_mdstat(&text) {
+                for device in &mut pool.devices {
+                    device.read_errors = md_device_errors(Path::new("/sys/block"), &pool.name, &device.name).unwrap_or(0);
+                }
+                pools.push(pool);
+            }
+        }
+        if self.has_zfs {
+            match zpool_status() {
+                Ok(text) => pools.extend(parse_zpool_status(&text)),
+                Err(e) => crate::logger::log_warning(&format!("Skipping ZFS pool status: {}", e)),
+            }
+        }
+        pools
+    }
+}
+
+impl HardwareMonitor for PoolMonitor {
+    fn name(&self) -> &'static str {
+        "Storage Pools"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        let mdstat = std::fs::read_to_string(MDSTAT).unwrap_or_default();
+        self.has_mdraid = !parse_mdstat(&mdstat).is_empty();
+        self.has_zfs = zpool_status().is_ok_and(|text| !parse_zpool_status(&text).is_empty());
+        if !self.has_mdraid && !self.has_zfs {
+            return Err(MonitorError::DriverMissing("no mdraid arrays or ZFS pools found".to_string()));
+        }
+        self.initialized = true;
+        crate::logger::log_info(&format!(
+            "Storage pool monitor initialized (mdraid: {}, ZFS: {})",
+            self.has_mdraid, self.has_zfs
+        ));
+        Ok(())
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        if !self.initialized || self.last_read.is_some_and(|at| at.elapsed() < READ_INTERVAL) {
+            return Ok(());
+        }
+        self.last_read = Some(Instant::now());
+
+        // Run zpool before taking the state lock
+        let pools = self.read_pools();
+        for pool in &pools {
+            let reported = self.reported.contains(&pool.name);
+            if !pool.healthy && !reported {
+                let failing: Vec<String> = pool
+                    .devices
+                    .iter()
+                    .filter(|device| device.errors() > 0 || matches!(device.state.as_str(), "faulty" | "FAULTED" | "UNAVAIL" | "REMOVED" | "DEGRADED"))
+                    .map(|device| format!("{} {}", device.name, device.state))
+                    .collect();
+                crate::logger::log_warning(&format!("Storage pool {} is {}: {}", pool.name, pool.state, failing.join(", ")));
+                self.reported.push(pool.name.clone());
+            } else if pool.healthy && reported {
+                crate::logger::log_info(&format!("Storage pool {} is healthy again", pool.name));
+                self.reported.retain(|name| name != &pool.name);
+            }
+        }
+
+        state.write().storage.update_pools(pools);
+        Ok(())
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        info.platform == Platform::Linux
+    }
+}
+
+// -p prints exact error counts rather than e.g. 1.2K
+fn zpool_status() -> Result<String, MonitorError> {
+    let output = Command::new("zpool")
+        .args(["status", "-p"])
+        .output()
+        .map_err(|e| MonitorError::DriverMissing(format!("could not run zpool ({})", e)))?;
+    if !output.status.success() {
+        return Err(MonitorError::PermissionDenied(format!(
+            "zpool status failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(|e| MonitorError::Parse(format!("zpool status output: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mdraid_state_and_rebuild_progress() {
+        let mdstat = "Personalities : [raid1] [raid6] [raid5] [raid4]\n\
+            md0 : active raid1 sdb1[1] sda1[0]\n      976630464 blocks super 1.2 [2/2] [UU]\n      \
+            bitmap: 0/8 pages [0KB], 65536KB chunk\n\n\
+            md1 : active raid5 sdc[0] sdd[1](F) sde[3] sdf[4](S)\n      \
+            1953260544 blocks super 1.2 level 5, 512k chunk, algorithm 2 [3/2] [U_U]\n      \
+            [===>.................]  recovery = 17.3% (169162496/976630272) finish=80.2min speed=167744K/sec\n\n\
+            unused devices: <none>\n";
+        let pools = parse_mdstat(mdstat);
+        assert_eq!(pools.len(), 2);
+        assert_eq!((pools[0].name.as_str(), pools[0].state.as_str(), pools[0].healthy), ("md0", "active raid1", true));
+        assert_eq!(pools[0].scan, None);
+        assert!(!pools[1].healthy);
+        assert_eq!(pools[1].scan.as_deref(), Some("recovery in progress"));
+        assert_eq!(pools[1].scan_progress, Some(17.3));
+        let states: Vec<&str> = pools[1].devices.iter().map(|device| device.state.as_str()).collect();
+        assert_eq!(states, ["in_sync", "faulty", "in_sync", "spare"]);
+        assert!(parse_mdstat("Personalities : \nunused devices: <none>\n").is_empty());
+    }
+
+    #[test]
+    fn test_zpool_status_vdev_errors_and_scrub() {
+        let status = "  pool: tank\n state: DEGRADED\nstatus: One or more devices are faulted.\n\
+            \x20 scan: scrub in progress since Sun Oct 11 00:24:01 2026\n\
+            \t1.50T scanned at 1.2G/s, 800G issued at 650M/s, 3.60T total\n\
+            \t0B repaired, 21.70% done, 01:15:00 to go\nconfig:\n\n\
+            \tNAME        STATE     READ WRITE CKSUM\n\
+            \ttank        DEGRADED     0     0     0\n\
+            \t  raidz1-0  DEGRADED     0     0     0\n\
+            \t    sda     ONLINE       0     0     0\n\
+            \t    sdb     FAULTED      3    12     0  too many errors\n\
+            \t    sdc     ONLINE       0     0     2\n\
+            \tlogs\n\
+            \t  nvme0n1   ONLINE       0     0     0\n\
+            \tspares\n\
+            \t  sdd       AVAIL\n\n\
+            errors: No known data errors\n\n\
+            \x20 pool: backup\n state: ONLINE\n\
+            \x20 scan: scrub repaired 0B in 00:10:01 with 0 errors on Sun Oct 11 00:34:02 2026\nconfig:\n\n\
+            \tNAME        STATE     READ WRITE CKSUM\n\
+            \tbackup      ONLINE       0     0     0\n\
+            \t  sde       ONLINE       0     0     0\n\n\
+            errors: No known data errors\n";
+        let pools = parse_zpool_status(status);
+        assert_eq!(pools.len(), 2);
+        let tank = &pools[0];
+        assert_eq!((tank.state.as_str(), tank.healthy, tank.scan_progress), ("DEGRADED", false, Some(21.7)));
+        let rows: Vec<(&str, usize, u64)> = tank.devices.iter().map(|device| (device.name.as_str(), device.depth, device.errors())).collect();
+        assert_eq!(rows, [("raidz1-0", 0, 0), ("sda", 1, 0), ("sdb", 1, 15), ("sdc", 1, 2), ("nvme0n1", 0, 0)]);
+        let backup = &pools[1];
+        assert!(backup.healthy);
+        assert_eq!(backup.scan_progress, None);
+        assert!(backup.scan.as_deref().unwrap().starts_with("scrub repaired 0B"));
+    }
+}
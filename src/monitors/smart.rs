@@ -0,0 +1,348 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use parking_lot::RwLock;
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo};
+use crate::model::{DriveHealth, DriveWrites, SharedAppState};
+
+// SMART counters move slowly and smartctl wakes sleeping drives, so they're read rarely
+const READ_INTERVAL: Duration = Duration::from_secs(300);
+
+// NVMe counts data units of 1000 sectors of 512 bytes, whatever the drive's sector size
+#[cfg(feature = "smart")]
+const NVME_DATA_UNIT_BYTES: u64 = 512_000;
+
+// Each drive's health as last read, keyed by DriveHealth::key, in the data directory;
+// the next session compares against it
+const HEALTH_FILE: &str = "drive_health.toml";
+
+// Rated endurance in TBW by drive model, from the config
+static RATED_TBW: RwLock<BTreeMap<String, f64>> = RwLock::new(BTreeMap::new());
+
+// Replace the ratings, e.g. at startup and when the settings change
+pub fn set_rated_tbw(ratings: &BTreeMap<String, f64>) {
+    *RATED_TBW.write() = ratings.clone();
+}
+
+// A drive as `smartctl --scan -j` lists it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmartDevice {
+    pub name: String,        // e.g. "/dev/nvme0", "/dev/sda" or "/dev/pd0" on Windows
+    pub device_type: String, // e.g. "nvme", "sat"; passed back with -d
+}
+
+// What the dashboard uses from `smartctl -a -j`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmartReport {
+    pub model: String,
+    pub serial: Option<String>,
+    pub host_writes_bytes: Option<u64>,
+    pub wear_percent: Option<f64>,
+    pub passed: Option<bool>,
+    pub reallocated_sectors: Option<u64>,
+    pub media_errors: Option<u64>,
+    pub power_on_hours: Option<u64>,
+    pub temperature: Option<f64>,
+    pub critical_warning: Option<u64>,
+}
+
+// ATA attributes whose normalized value counts down from 100 as the flash wears:
+// Samsung's Wear_Leveling_Count, Crucial's Percent_Lifetime_Remain, SSD_Life_Left and
+// Intel's Media_Wearout_Indicator
+#[cfg(feature = "smart")]
+const ATA_WEAR_ATTRIBUTES: [u64; 4] = [177, 202, 231, 233];
+
+// Host writes, wear, endurance, temperature and health of every drive smartctl can read. Needs
+// smartmontools and usually root or Administrator
+pub struct SmartMonitor {
+    devices: Vec<SmartDevice>,
+    last_read: Option<Instant>,
+    previous_health: BTreeMap<String, DriveHealth>, // As the last session left it
+    reported: HashSet<String>,                      // Drives already warned about this session
+    initialized: bool,
+}
+
+impl Default for SmartMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SmartMonitor {
+    pub fn new() -> Self {
+        Self {
+            devices: Vec::new(),
+            last_read: None,
+            previous_health: BTreeMap::new(),
+            reported: HashSet::new(),
+            initialized: false,
+        }
+    }
+}
+
+impl HardwareMonitor for SmartMonitor {
+    fn name(&self) -> &'static str {
+        "SMART"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        self.devices = parse_scan(&smartctl(&["--scan", "-j"])?)?;
+        if self.devices.is_empty() {
+            return Err(MonitorError::PermissionDenied("smartctl found no drives it can open".to_string()));
+        }
+        self.previous_health = health_path().map(|path| load_health(&path)).unwrap_or_default();
+        self.initialized = true;
+        crate::logger::log_info(&format!("SMART monitor initialized using smartctl ({} drives)", self.devices.len()));
+        Ok(())
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        if !self.initialized || self.last_read.is_some_and(|at| at.elapsed() < READ_INTERVAL) {
+            return Ok(());
+        }
+        self.last_read = Some(Instant::now());
+
+        // Run smartctl before taking the state lock; a drive it can't read is skipped
+        let mut readings = Vec::new();
+        let mut health = Vec::new();
+        for device in &self.devices {
+            let output = smartctl(&["-a", "-j", "-d", &device.device_type, &device.name]);
+            let report = match output.and_then(|output| parse_report(&output)) {
+                Ok(report) => report,
+                Err(e) => {
+                    crate::logger::log_warning(&format!("Skipping SMART data of {}: {}", device.name, e));
+                    continue;
+                }
+            };
+            if let Some(host_writes_bytes) = report.host_writes_bytes {
+                readings.push(DriveWrites {
+                    device: device.name.clone(),
+                    model: report.model.clone(),
+                    host_writes_bytes,
+                    wear_percent: report.wear_percent,
+                });
+            }
+            let mut drive = DriveHealth {
+                device: device.name.clone(),
+                model: report.model,
+                serial: report.serial,
+                passed: report.passed,
+                reallocated_sectors: report.reallocated_sectors,
+                media_errors: report.media_errors,
+                power_on_hours: report.power_on_hours,
+                temperature: report.temperature,
+                wear_percent: report.wear_percent,
+                critical_warning: report.critical_warning,
+                degradations: Vec::new(),
+            };
+            if let Some(previous) = self.previous_health.get(&drive.key()) {
+                drive.degradations = drive.degradations_since(previous);
+            }
+            if !drive.is_healthy() && self.reported.insert(drive.key()) {
+                let mut problems: Vec<String> = drive.degradations.clone();
+                problems.extend(drive.critical_warnings().iter().map(|warning| warning.to_string()));
+                let problems = if problems.is_empty() { "self-assessment FAILED".to_string() } else { problems.join(", ") };
+                crate::logger::log_warning(&format!("Drive {} ({}) health degraded: {}", drive.model, drive.device, problems));
+            }
+            health.push(drive);
+        }
+        if let Err(e) = health_path().and_then(|path| save_health(&path, &health)) {
+            crate::logger::log_error("Failed to save drive health", &*e);
+        }
+
+        let rated_tbw = RATED_TBW.read().clone();
+        let mut app_state = state.write();
+        for drive in &health {
+            if let Some(celsius) = drive.temperature {
+                let name = drive.device.rsplit('/').next().unwrap_or(&drive.device);
+                app_state.storage.temperature_sensors.update(&format!("SMART {}", name), celsius as f32);
+            }
+        }
+        app_state.storage.update_endurance(&readings, &rated_tbw);
+        app_state.storage.update_health(health);
+        Ok(())
+    }
+
+    fn supports_hardware(&self, _info: &HardwareInfo) -> bool {
+        true
+    }
+}
+
+fn health_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(crate::paths::data_dir()?.join(HEALTH_FILE))
+}
+
+// Missing or unreadable on the first session, which then has nothing to compare against
+pub fn load_health(path: &Path) -> BTreeMap<String, DriveHealth> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+// Drives not read this time, e.g. an unplugged external disk, keep their last entry
+pub fn save_health(path: &Path, drives: &[DriveHealth]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut saved = load_health(path);
+    for drive in drives {
+        saved.insert(drive.key(), DriveHealth { degradations: Vec::new(), ..drive.clone() });
+    }
+    std::fs::write(path, toml::to_string(&saved)?)?;
+    Ok(())
+}
+
+// smartctl's exit status is a bit mask that is non-zero for drive warnings too, so
+// only missing output counts as failure
+fn smartctl(args: &[&str]) -> Result<String, MonitorError> {
+    let output = Command::new("smartctl")
+        .args(args)
+        .output()
+        .map_err(|e| MonitorError::DriverMissing(format!("could not run smartctl ({}); is smartmontools installed?", e)))?;
+    if output.stdout.is_empty() {
+        return Err(MonitorError::PermissionDenied(format!(
+            "smartctl failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(|e| MonitorError::Parse(format!("smartctl output: {}", e)))
+}
+
+// {"devices": [{"name": "/dev/nvme0", "info_name": "/dev/nvme0", "type": "nvme", "protocol": "NVMe"}]}
+#[cfg(feature = "smart")]
+pub fn parse_scan(json: &str) -> Result<Vec<SmartDevice>, MonitorError> {
+    let scan: serde_json::Value = serde_json::from_str(json).map_err(|e| MonitorError::Parse(format!("smartctl --scan: {}", e)))?;
+    let devices = scan["devices"].as_array().map(Vec::as_slice).unwrap_or_default();
+    Ok(devices
+        .iter()
+        .filter_map(|device| {
+            Some(SmartDevice {
+                name: device["name"].as_str()?.to_string(),
+                device_type: device["type"].as_str()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+// Host writes come from the NVMe health log, or from ATA attribute 241, whose raw value
+// is in logical blocks unless its name gives a larger unit. Reallocated sectors are ATA
+// attribute 5; NVMe drives count media errors instead. Wear is NVMe's Percentage Used,
+// or what a wear attribute has counted down
+#[cfg(feature = "smart")]
+pub fn parse_report(json: &str) -> Result<SmartReport, MonitorError> {
+    let report: serde_json::Value = serde_json::from_str(json).map_err(|e| MonitorError::Parse(format!("smartctl -a: {}", e)))?;
+    let Some(model) = report["model_name"].as_str() else {
+        return Err(MonitorError::Parse("no model_name in smartctl output".to_string()));
+    };
+
+    let nvme = &report["nvme_smart_health_information_log"];
+    let ata_attribute = |id: u64| {
+        report["ata_smart_attributes"]["table"]
+            .as_array()
+            .and_then(|table| table.iter().find(|attribute| attribute["id"].as_u64() == Some(id)))
+    };
+    let ata_writes = ata_attribute(241)
+        .and_then(|attribute| {
+            let raw = attribute["raw"]["value"].as_u64()?;
+            let name = attribute["name"].as_str().unwrap_or_default();
+            let unit = if name.contains("32MiB") {
+                32 * 1024 * 1024
+            } else if name.contains("GiB") {
+                1024 * 1024 * 1024
+            } else {
+                report["logical_block_size"].as_u64().unwrap_or(512)
+            };
+            Some(raw * unit)
+        });
+    let host_writes_bytes = nvme["data_units_written"].as_u64().map(|units| units * NVME_DATA_UNIT_BYTES).or(ata_writes);
+    let ata_wear = ATA_WEAR_ATTRIBUTES
+        .iter()
+        .find_map(|id| ata_attribute(*id)?["value"].as_u64())
+        .map(|remaining| 100.0 - remaining.min(100) as f64);
+
+    Ok(SmartReport {
+        model: model.trim().to_string(),
+        serial: report["serial_number"].as_str().map(|serial| serial.trim().to_string()),
+        host_writes_bytes,
+        wear_percent: nvme["percentage_used"].as_f64().or(ata_wear),
+        passed: report["smart_status"]["passed"].as_bool(),
+        reallocated_sectors: ata_attribute(5).and_then(|attribute| attribute["raw"]["value"].as_u64()),
+        media_errors: nvme["media_errors"].as_u64(),
+        power_on_hours: report["power_on_time"]["hours"].as_u64(),
+        temperature: report["temperature"]["current"].as_f64(),
+        critical_warning: nvme["critical_warning"].as_u64(),
+    })
+}
+
+#[cfg(not(feature = "smart"))]
+pub fn parse_scan(_json: &str) -> Result<Vec<SmartDevice>, MonitorError> {
+    Err(MonitorError::DriverMissing("this build was compiled without SMART support".to_string()))
+}
+
+#[cfg(not(feature = "smart"))]
+pub fn parse_report(_json: &str) -> Result<SmartReport, MonitorError> {
+    Err(MonitorError::DriverMissing("this build was compiled without SMART support".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "smart")]
+    #[test]
+    fn test_host_writes_from_smartctl() {
+        let scan = r#"{"devices": [{"name": "/dev/nvme0", "info_name": "/dev/nvme0", "type": "nvme", "protocol": "NVMe"},
+            {"name": "/dev/sda", "info_name": "/dev/sda [SAT]", "type": "sat", "protocol": "ATA"}]}"#;
+        assert_eq!(parse_scan(scan).unwrap()[1], SmartDevice { name: "/dev/sda".to_string(), device_type: "sat".to_string() });
+
+        let nvme = r#"{"model_name": "Samsung SSD 980 PRO 1TB", "serial_number": "S5GXNF0R123456", "logical_block_size": 512,
+            "smart_status": {"passed": true}, "power_on_time": {"hours": 2140}, "temperature": {"current": 41},
+            "nvme_smart_health_information_log": {"critical_warning": 0, "percentage_used": 2, "data_units_written": 29296875, "media_errors": 0}}"#;
+        let report = parse_report(nvme).unwrap();
+        assert_eq!(report.host_writes_bytes, Some(15_000_000_000_000));
+        assert_eq!(report.wear_percent, Some(2.0));
+        assert_eq!(report.serial.as_deref(), Some("S5GXNF0R123456"));
+        assert_eq!((report.passed, report.media_errors, report.power_on_hours), (Some(true), Some(0), Some(2140)));
+        assert_eq!(report.reallocated_sectors, None);
+        assert_eq!((report.temperature, report.critical_warning), (Some(41.0), Some(0)));
+
+        let ata = |name: &str| {
+            format!(
+                r#"{{"model_name": "CT1000MX500SSD1", "logical_block_size": 512, "ata_smart_attributes": {{"table": [
+                    {{"id": 5, "name": "Reallocated_Sector_Ct", "raw": {{"value": 8}}}},
+                    {{"id": 9, "name": "Power_On_Hours", "raw": {{"value": 1200}}}},
+                    {{"id": 202, "name": "Percent_Lifetime_Remain", "value": 93, "raw": {{"value": 7}}}},
+                    {{"id": 241, "name": "{}", "raw": {{"value": 1000}}}}]}}}}"#,
+                name
+            )
+        };
+        assert_eq!(parse_report(&ata("Total_LBAs_Written")).unwrap().host_writes_bytes, Some(512_000));
+        assert_eq!(parse_report(&ata("Host_Writes_32MiB")).unwrap().host_writes_bytes, Some(32_000 * 1024 * 1024));
+        assert_eq!(parse_report(&ata("Host_Writes_GiB")).unwrap().wear_percent, Some(7.0));
+        assert_eq!(parse_report(&ata("Total_LBAs_Written")).unwrap().reallocated_sectors, Some(8));
+        assert!(parse_report(r#"{"smartctl": {"exit_status": 2}}"#).is_err());
+    }
+
+    #[test]
+    fn test_health_saved_between_sessions() {
+        let path = std::env::temp_dir().join(format!("spd_drive_health_{}.toml", std::process::id()));
+        let drive = |device: &str, serial: &str, reallocated: u64| DriveHealth {
+            device: device.to_string(),
+            model: "CT1000MX500SSD1".to_string(),
+            serial: Some(serial.to_string()),
+            passed: Some(true),
+            reallocated_sectors: Some(reallocated),
+            degradations: vec!["reallocated sectors 0 → 8".to_string()],
+            ..DriveHealth::default()
+        };
+        save_health(&path, &[drive("/dev/sda", "A1", 0), drive("/dev/sdb", "B2", 3)]).unwrap();
+        // Only the drive read this time is replaced
+        save_health(&path, &[drive("/dev/sda", "A1", 8)]).unwrap();
+        let saved = load_health(&path);
+        assert_eq!(saved.len(), 2);
+        assert_eq!(saved["CT1000MX500SSD1 A1"].reallocated_sectors, Some(8));
+        assert!(saved["CT1000MX500SSD1 A1"].degradations.is_empty());
+        assert_eq!(saved["CT1000MX500SSD1 B2"].reallocated_sectors, Some(3));
+        std::fs::remove_file(&path).unwrap();
+        assert!(load_health(&path).is_empty());
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, Platform};
+use crate::model::{DiskReading, SharedAppState};
+
+// /proc/diskstats counts sectors of 512 bytes, whatever the disk's sector size
+const DISKSTATS_SECTOR_BYTES: u64 = 512;
+
+// Disk throughput and busy time for all physical disks; per disk on Linux
+pub struct StorageMonitor {
+    #[cfg(windows)]
+    counters: Option<pdh::DiskCounters>,
+    #[cfg(target_os = "linux")]
+    diskstats: DiskstatsSampler,
+}
+
+impl Default for StorageMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StorageMonitor {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(windows)]
+            counters: None,
+            #[cfg(target_os = "linux")]
+            diskstats: DiskstatsSampler::default(),
+        }
+    }
+}
+
+impl HardwareMonitor for StorageMonitor {
+    fn name(&self) -> &'static str {
+        "Storage"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        #[cfg(windows)]
+        {
+            self.counters = Some(pdh::DiskCounters::open()?);
+            crate::logger::log_info("Storage monitor initialized using per-disk PhysicalDisk performance counters");
+            Ok(())
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let text = std::fs::read_to_string("/proc/diskstats")
+                .map_err(|e| MonitorError::DriverMissing(format!("could not read /proc/diskstats: {}", e)))?;
+            let disks = physical_disks(&parse_diskstats(&text), Path::new("/sys/block"));
+            if disks.is_empty() {
+                return Err(MonitorError::DriverMissing("/proc/diskstats lists no physical disks".to_string()));
+            }
+            let names: Vec<&str> = disks.iter().map(|disk| disk.name.as_str()).collect();
+            crate::logger::log_info(&format!("Storage monitor initialized using /proc/diskstats ({})", names.join(", ")));
+            Ok(())
+        }
+
+        #[cfg(not(any(windows, target_os = "linux")))]
+        {
+            Err(MonitorError::DriverMissing("disk counters are not implemented on this platform".to_string()))
+        }
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        #[cfg(windows)]
+        {
+            let Some(counters) = self.counters.as_mut() else {
+                return Ok(());
+            };
+            // Rates need two collections, so the first poll only primes the counters
+            let Some(readings) = counters.sample()? else {
+                return Ok(());
+            };
+
+            state.write().storage.update_disks(&readings);
+            Ok(())
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let text = std::fs::read_to_string("/proc/diskstats")
+                .map_err(|e| MonitorError::DeviceGone(format!("could not read /proc/diskstats: {}", e)))?;
+            let disks = physical_disks(&parse_diskstats(&text), Path::new("/sys/block"));
+            // Rates need two reads, so the first poll only primes the sampler
+            let Some(readings) = self.diskstats.update(Instant::now(), &disks) else {
+                return Ok(());
+            };
+
+            state.write().storage.update_disks(&readings);
+            Ok(())
+        }
+
+        #[cfg(not(any(windows, target_os = "linux")))]
+        {
+            let _ = state;
+            Ok(())
+        }
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        matches!(info.platform, Platform::Windows | Platform::Linux)
+    }
+}
+
+// A block device's lifetime counters from one line of /proc/diskstats:
+//
+//     259       0 nvme0n1 48203 1520 3918294 10432 90211 40212 8823312 61230 0 51020 73580 ...
+//
+// After major, minor and name come reads completed, reads merged, sectors read, ms
+// reading, the same four for writes, I/Os in flight and ms spent doing I/O
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskCounters {
+    pub name: String,
+    pub sectors_read: u64,
+    pub sectors_written: u64,
+    pub io_ms: u64,
+}
+
+pub fn parse_diskstats(text: &str) -> Vec<DiskCounters> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let number = |index: usize| fields.get(index)?.parse::<u64>().ok();
+            Some(DiskCounters {
+                name: fields.get(2)?.to_string(),
+                sectors_read: number(5)?,
+                sectors_written: number(9)?,
+                io_ms: number(12)?,
+            })
+        })
+        .collect()
+}
+
+// Whole disks backed by hardware: partitions aren't directories of /sys/block, and
+// loop, ram, zram, device-mapper and md devices have no device link there
+pub fn physical_disks(disks: &[DiskCounters], sys_block: &Path) -> Vec<DiskCounters> {
+    disks.iter().filter(|disk| sys_block.join(&disk.name).join("device").exists()).cloned().collect()
+}
+
+// Read and write rates and busy time per disk between two reads of /proc/diskstats
+#[derive(Debug, Default)]
+pub struct DiskstatsSampler {
+    previous: Option<(Instant, HashMap<String, DiskCounters>)>,
+}
+
+impl DiskstatsSampler {
+    // None on the first read. A disk attached since the previous read has no rate yet
+    pub fn update(&mut self, now: Instant, disks: &[DiskCounters]) -> Option<Vec<DiskReading>> {
+        let current: HashMap<String, DiskCounters> = disks.iter().map(|disk| (disk.name.clone(), disk.clone())).collect();
+        let (then, before) = self.previous.replace((now, current))?;
+        let elapsed = now.duration_since(then).as_secs_f64();
+        if elapsed == 0.0 {
+            return None;
+        }
+        let mb_per_sec = |sectors: u64| (sectors * DISKSTATS_SECTOR_BYTES) as f64 / 1024.0 / 1024.0 / elapsed;
+        Some(
+            disks
+                .iter()
+                .filter_map(|disk| {
+                    let earlier = before.get(&disk.name)?;
+                    Some(DiskReading {
+                        name: disk.name.clone(),
+                        read_speed: mb_per_sec(disk.sectors_read.saturating_sub(earlier.sectors_read)),
+                        write_speed: mb_per_sec(disk.sectors_written.saturating_sub(earlier.sectors_written)),
+                        busy_percent: (disk.io_ms.saturating_sub(earlier.io_ms) as f64 / 10.0 / elapsed).clamp(0.0, 100.0),
+                    })
+                })
+                .collect(),
+        )
+    }
+}
+
+// Throughput over the last polling interval
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiskSample {
+    pub read_mb_per_sec: f32,
+    pub write_mb_per_sec: f32,
+    pub busy_percent: f32,
+}
+
+impl DiskSample {
+    // Counters report bytes/s; busy time is derived from idle time, clamped against rounding
+    pub fn from_counters(read_bytes_per_sec: f64, write_bytes_per_sec: f64, busy_percent: f64) -> Self {
+        Self {
+            read_mb_per_sec: (read_bytes_per_sec / 1024.0 / 1024.0) as f32,
+            write_mb_per_sec: (write_bytes_per_sec / 1024.0 / 1024.0) as f32,
+            busy_percent: busy_percent.clamp(0.0, 100.0) as f32,
+        }
+    }
+
+    pub fn reading(&self, name: &str) -> DiskReading {
+        DiskReading {
+            name: name.to_string(),
+            read_speed: self.read_mb_per_sec as f64,
+            write_speed: self.write_mb_per_sec as f64,
+            busy_percent: self.busy_percent as f64,
+        }
+    }
+}
+
+// PhysicalDisk instances are named by disk number and the volumes on it, e.g. "0 C:"
+// or "1 D: E:", plus "_Total" over all of them, which the dashboard sums itself
+#[cfg(any(windows, test))]
+fn is_disk_instance(name: &str) -> bool {
+    !name.is_empty() && name != "_Total"
+}
+
+#[cfg(windows)]
+mod pdh {
+    use std::collections::HashMap;
+    use super::{is_disk_instance, DiskSample};
+    use crate::hardware_detection::MonitorError;
+    use crate::model::DiskReading;
+    use windows::core::{w, PCWSTR};
+    use windows::Win32::System::Performance::{
+        PdhAddEnglishCounterW, PdhCloseQuery, PdhCollectQueryData, PdhGetFormattedCounterArrayW, PdhOpenQueryW,
+        PDH_CSTATUS_NO_COUNTER, PDH_CSTATUS_NO_OBJECT, PDH_FMT_COUNTERVALUE_ITEM_W, PDH_FMT_DOUBLE, PDH_MORE_DATA,
+    };
+
+    // English counter names so lookups work on localized Windows installs; the wildcard
+    // instance expands to every physical disk, including ones attached later
+    pub struct DiskCounters {
+        query: isize,
+        read: isize,
+        write: isize,
+        idle: isize,
+        primed: bool,
+    }
+
+    impl DiskCounters {
+        pub fn open() -> Result<Self, MonitorError> {
+            let mut query = 0isize;
+            check(unsafe { PdhOpenQueryW(PCWSTR::null(), 0, &mut query) }, "PdhOpenQuery")?;
+            let mut counters = Self { query, read: 0, write: 0, idle: 0, primed: false };
+            counters.read = counters.add(w!("\\PhysicalDisk(*)\\Disk Read Bytes/sec"))?;
+            counters.write = counters.add(w!("\\PhysicalDisk(*)\\Disk Write Bytes/sec"))?;
+            counters.idle = counters.add(w!("\\PhysicalDisk(*)\\% Idle Time"))?;
+            Ok(counters)
+        }
+
+        fn add(&self, path: PCWSTR) -> Result<isize, MonitorError> {
+            let mut counter = 0isize;
+            check(unsafe { PdhAddEnglishCounterW(self.query, path, 0, &mut counter) }, "PdhAddEnglishCounter")?;
+            Ok(counter)
+        }
+
+        // One reading per disk with all three counters
+        pub fn sample(&mut self) -> Result<Option<Vec<DiskReading>>, MonitorError> {
+            check(unsafe { PdhCollectQueryData(self.query) }, "PdhCollectQueryData")?;
+            if !self.primed {
+                self.primed = true;
+                return Ok(None);
+            }
+            let (read, write, idle) = (self.values(self.read)?, self.values(self.write)?, self.values(self.idle)?);
+            let mut names: Vec<&String> = read.keys().filter(|name| is_disk_instance(name)).collect();
+            names.sort();
+            Ok(Some(
+                names
+                    .into_iter()
+                    .filter_map(|name| {
+                        let sample = DiskSample::from_counters(read[name], *write.get(name)?, 100.0 - idle.get(name)?);
+                        Some(sample.reading(name))
+                    })
+                    .collect(),
+            ))
+        }
+
+        // Every instance's value by name; instances without valid data this time are left out
+        fn values(&self, counter: isize) -> Result<HashMap<String, f64>, MonitorError> {
+            let (mut size, mut count) = (0u32, 0u32);
+            let status = unsafe { PdhGetFormattedCounterArrayW(counter, PDH_FMT_DOUBLE, &mut size, &mut count, None) };
+            if status != PDH_MORE_DATA {
+                check(status, "PdhGetFormattedCounterArray")?;
+                return Ok(HashMap::new());
+            }
+            // The items are followed by the instance names they point to, in the same buffer
+            let items = size as usize / std::mem::size_of::<PDH_FMT_COUNTERVALUE_ITEM_W>() + 1;
+            let mut buffer = vec![PDH_FMT_COUNTERVALUE_ITEM_W::default(); items];
+            check(
+                unsafe { PdhGetFormattedCounterArrayW(counter, PDH_FMT_DOUBLE, &mut size, &mut count, Some(buffer.as_mut_ptr())) },
+                "PdhGetFormattedCounterArray",
+            )?;
+            Ok(buffer[..count as usize]
+                .iter()
+                .filter(|item| item.FmtValue.CStatus == 0)
+                .filter_map(|item| {
+                    let name = unsafe { item.szName.to_string() }.ok()?;
+                    Some((name, unsafe { item.FmtValue.Anonymous.doubleValue }))
+                })
+                .collect())
+        }
+    }
+
+    impl Drop for DiskCounters {
+        fn drop(&mut self) {
+            unsafe {
+                PdhCloseQuery(self.query);
+            }
+        }
+    }
+
+    fn check(status: u32, call: &str) -> Result<(), MonitorError> {
+        match status {
+            0 => Ok(()),
+            PDH_CSTATUS_NO_OBJECT | PDH_CSTATUS_NO_COUNTER => Err(MonitorError::DriverMissing(format!(
+                "{} failed: PhysicalDisk counters are disabled (re-enable with `lodctr /e:PerfDisk`)",
+                call
+            ))),
+            status => Err(MonitorError::Parse(format!("{} failed with PDH status {:#x}", call, status))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_sample_from_counters() {
+        let sample = DiskSample::from_counters(52_428_800.0, 1_048_576.0, 35.0);
+        assert_eq!(sample.read_mb_per_sec, 50.0);
+        assert_eq!(sample.write_mb_per_sec, 1.0);
+        assert_eq!(sample.busy_percent, 35.0);
+
+        // Idle time slightly above 100% comes back as a tiny negative busy value
+        assert_eq!(DiskSample::from_counters(0.0, 0.0, -0.4).busy_percent, 0.0);
+
+        assert_eq!(sample.reading("0 C:").read_speed, 50.0);
+        assert!(is_disk_instance("1 D: E:"));
+        assert!(!is_disk_instance("_Total"));
+    }
+
+    #[test]
+    fn test_disk_rates_from_diskstats() {
+        let diskstats = |read: u64, written: u64, io_ms: u64| {
+            format!(
+                "   7       0 loop0 52 0 2200 12 0 0 0 0 0 20 12 0 0 0 0
+                  259       0 nvme0n1 48203 1520 {} 10432 90211 40212 {} 61230 0 {} 73580 0 0 0 0
+                  259       1 nvme0n1p1 310 0 12000 40 2 0 16 0 0 50 40 0 0 0 0
+",
+                read, written, io_ms
+            )
+        };
+        let disks = parse_diskstats(&diskstats(1_000_000, 2_000_000, 5000));
+        assert_eq!(disks.len(), 3);
+        assert_eq!(disks[1], DiskCounters { name: "nvme0n1".to_string(), sectors_read: 1_000_000, sectors_written: 2_000_000, io_ms: 5000 });
+        assert!(parse_diskstats("garbage
+").is_empty());
+
+        let mut sampler = DiskstatsSampler::default();
+        let start = Instant::now();
+        assert_eq!(sampler.update(start, &disks), None);
+        // 204800 sectors is 100 MB, read in two seconds while busy for half of them
+        let later = parse_diskstats(&diskstats(1_204_800, 2_000_000, 6000));
+        let readings = sampler.update(start + std::time::Duration::from_secs(2), &later).unwrap();
+        assert_eq!(readings[1], DiskReading { name: "nvme0n1".to_string(), read_speed: 50.0, write_speed: 0.0, busy_percent: 50.0 });
+    }
+}
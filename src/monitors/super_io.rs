@@ -0,0 +1,221 @@
+use std::time::{Duration, Instant};
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo, Platform};
+use crate::lm_sensors::{self, ReadingKind, SensorReading};
+use crate::model::SharedAppState;
+
+// hwmon drivers of Super I/O chips, the motherboard's monitoring chip, as lm-sensors
+// names them, e.g. "nct6798-isa-0290" or "it8688-isa-0a40"
+const SUPER_IO_CHIPS: &[&str] = &["nct", "it8", "w83", "f71", "f81", "sch56"];
+
+// Each read runs `sensors`, which walks every chip; rails settle far slower than a
+// burst polls, so they're read at most this often like SMART and pool status
+const READ_INTERVAL: Duration = Duration::from_secs(2);
+
+// The ATX specification allows the 12 V, 5 V and 3.3 V rails to drift this far
+pub const RAIL_TOLERANCE_PERCENT: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rail {
+    Vcore,
+    V12,
+    V5,
+    V3_3,
+}
+
+impl Rail {
+    // From the label sensors.conf gives the input, e.g. "+12V", "5VCC" or "CPU Vcore".
+    // Standby and battery rails are left out; Nuvoton chips wire in0 to the CPU core
+    pub fn from_label(chip: &str, label: &str) -> Option<Self> {
+        let label: String = label.to_lowercase().chars().filter(|c| !matches!(c, '+' | ' ' | '_' | '-')).collect();
+        if label.contains("sb") || label.contains("bat") {
+            return None;
+        }
+        if label.contains("vcore") || label == "cpucore" || (label == "in0" && chip.starts_with("nct")) {
+            Some(Rail::Vcore)
+        } else if label.starts_with("12v") {
+            Some(Rail::V12)
+        } else if label.starts_with("5v") {
+            Some(Rail::V5)
+        } else if label.starts_with("3.3v") || label.starts_with("3v3") || label.starts_with("3vcc") {
+            Some(Rail::V3_3)
+        } else {
+            None
+        }
+    }
+
+    // Vcore follows the CPU's requests, so it has no nominal voltage
+    pub fn nominal(&self) -> Option<f64> {
+        match self {
+            Rail::Vcore => None,
+            Rail::V12 => Some(12.0),
+            Rail::V5 => Some(5.0),
+            Rail::V3_3 => Some(3.3),
+        }
+    }
+}
+
+// Rails and intrusion state from the Super I/O chips in `sensors -j` output
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BoardReadings {
+    pub vcore: Option<f64>,
+    pub rail_12v: Option<f64>,
+    pub rail_5v: Option<f64>,
+    pub rail_3v3: Option<f64>,
+    pub intrusion: Option<bool>,
+}
+
+impl BoardReadings {
+    // The first reading of each rail counts, should a board label two the same
+    pub fn from_readings(readings: &[SensorReading]) -> Self {
+        let mut board = BoardReadings::default();
+        for reading in readings.iter().filter(|reading| is_super_io(&reading.chip)) {
+            match reading.kind {
+                ReadingKind::Intrusion => {
+                    board.intrusion = Some(board.intrusion.unwrap_or(false) || reading.value > 0.0);
+                }
+                ReadingKind::Voltage => {
+                    let slot = match Rail::from_label(&reading.chip, &reading.label) {
+                        Some(Rail::Vcore) => &mut board.vcore,
+                        Some(Rail::V12) => &mut board.rail_12v,
+                        Some(Rail::V5) => &mut board.rail_5v,
+                        Some(Rail::V3_3) => &mut board.rail_3v3,
+                        None => continue,
+                    };
+                    slot.get_or_insert(reading.value);
+                }
+                _ => {}
+            }
+        }
+        board
+    }
+
+    // Percent the rail furthest from nominal is off by, over the rails read
+    pub fn worst_deviation(&self) -> Option<f64> {
+        [(Rail::V12, self.rail_12v), (Rail::V5, self.rail_5v), (Rail::V3_3, self.rail_3v3)]
+            .into_iter()
+            .filter_map(|(rail, value)| Some(((value? - rail.nominal()?) / rail.nominal()? * 100.0).abs()))
+            .reduce(f64::max)
+    }
+}
+
+pub fn is_super_io(chip: &str) -> bool {
+    SUPER_IO_CHIPS.iter().any(|prefix| chip.starts_with(prefix))
+}
+
+// Voltage rails and chassis intrusion from the motherboard's Super I/O chip on Linux.
+// Uses `sensors -j` rather than sysfs, since most rails are wired through dividers
+// that only the compute lines in sensors.conf scale back to volts
+pub struct SuperIoMonitor {
+    last_read: Option<Instant>,
+    initialized: bool,
+}
+
+impl Default for SuperIoMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SuperIoMonitor {
+    pub fn new() -> Self {
+        Self { last_read: None, initialized: false }
+    }
+
+    fn read() -> Result<BoardReadings, MonitorError> {
+        let output = lm_sensors::read_live().map_err(|e| MonitorError::DriverMissing(e.to_string()))?;
+        let readings = lm_sensors::parse(&output).map_err(|e| MonitorError::Parse(e.to_string()))?;
+        Ok(BoardReadings::from_readings(&readings))
+    }
+}
+
+impl HardwareMonitor for SuperIoMonitor {
+    fn name(&self) -> &'static str {
+        "Super I/O"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        let board = Self::read()?;
+        if board == BoardReadings::default() {
+            return Err(MonitorError::DriverMissing(
+                "no Super I/O rails or intrusion sensor in `sensors -j`; is the nct6775 or it87 driver loaded?".to_string(),
+            ));
+        }
+        self.initialized = true;
+        crate::logger::log_info("Super I/O monitor initialized using lm-sensors");
+        Ok(())
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        if !self.initialized || self.last_read.is_some_and(|at| at.elapsed() < READ_INTERVAL) {
+            return Ok(());
+        }
+        self.last_read = Some(Instant::now());
+
+        // Run sensors before taking the state lock
+        let board = Self::read()?;
+
+        let mut app_state = state.write();
+        let motherboard = &mut app_state.motherboard;
+        for (metric, value) in [
+            (&mut motherboard.vcore, board.vcore),
+            (&mut motherboard.rail_12v, board.rail_12v),
+            (&mut motherboard.rail_5v, board.rail_5v),
+            (&mut motherboard.rail_3v3, board.rail_3v3),
+            (&mut motherboard.rail_deviation, board.worst_deviation()),
+        ] {
+            if let Some(value) = value {
+                metric.update(value);
+            }
+        }
+        if let Some(intrusion) = board.intrusion {
+            if intrusion && motherboard.chassis_intrusion.active != Some(true) {
+                crate::logger::log_warning("Chassis intrusion detected: the case was opened");
+            }
+            motherboard.chassis_intrusion.update(intrusion);
+        }
+        Ok(())
+    }
+
+    fn supports_hardware(&self, info: &HardwareInfo) -> bool {
+        info.platform == Platform::Linux
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rails_and_intrusion_from_sensors() {
+        let reading = |chip: &str, label: &str, kind: ReadingKind, value: f64| SensorReading {
+            chip: chip.to_string(),
+            label: label.to_string(),
+            kind,
+            value,
+        };
+        let readings = [
+            reading("nct6798-isa-0290", "in0", ReadingKind::Voltage, 1.224),
+            reading("nct6798-isa-0290", "+12V", ReadingKind::Voltage, 11.328),
+            reading("nct6798-isa-0290", "+5V", ReadingKind::Voltage, 5.04),
+            reading("nct6798-isa-0290", "3VSB", ReadingKind::Voltage, 3.44),
+            reading("nct6798-isa-0290", "3VCC", ReadingKind::Voltage, 3.36),
+            reading("nct6798-isa-0290", "intrusion0", ReadingKind::Intrusion, 1.0),
+            reading("nct6798-isa-0290", "intrusion1", ReadingKind::Intrusion, 0.0),
+            // Not a Super I/O chip
+            reading("amdgpu-pci-0300", "vddgfx", ReadingKind::Voltage, 0.9),
+        ];
+        let board = BoardReadings::from_readings(&readings);
+        assert_eq!(board.vcore, Some(1.224));
+        assert_eq!((board.rail_12v, board.rail_5v, board.rail_3v3), (Some(11.328), Some(5.04), Some(3.36)));
+        assert_eq!(board.intrusion, Some(true));
+        // 11.328 V is 5.6% under 12 V, outside the ATX tolerance
+        let deviation = board.worst_deviation().unwrap();
+        assert!((deviation - 5.6).abs() < 0.01, "{}", deviation);
+        assert!(deviation > RAIL_TOLERANCE_PERCENT);
+
+        assert_eq!(Rail::from_label("it8688-isa-0a40", "CPU Vcore"), Some(Rail::Vcore));
+        assert_eq!(Rail::from_label("it8688-isa-0a40", "in0"), None);
+        assert_eq!(Rail::from_label("it8688-isa-0a40", "+3.3V"), Some(Rail::V3_3));
+        assert_eq!(BoardReadings::default().worst_deviation(), None);
+    }
+}
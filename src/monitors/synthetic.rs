@@ -0,0 +1,168 @@
+use chrono::{DateTime, Duration, Utc};
+use crate::hardware_detection::{HardwareMonitor, MonitorError, HardwareInfo};
+use crate::model::{SharedAppState, METRIC_DESCRIPTORS};
+
+// Developer mode replacing the hardware monitors with generated data, e.g.
+// `simple_performance_dashboard --synthetic-load=2000,50000` for 2000 metrics and
+// 50000 samples per second in total
+pub const SYNTHETIC_LOAD_FLAG: &str = "--synthetic-load";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntheticLoad {
+    pub metrics: usize,
+    pub samples_per_second: u64,
+}
+
+impl Default for SyntheticLoad {
+    fn default() -> Self {
+        Self { metrics: 1000, samples_per_second: 10_000 }
+    }
+}
+
+impl SyntheticLoad {
+    // Parse `--synthetic-load` or `--synthetic-load=METRICS[,SAMPLES_PER_SECOND]`;
+    // None for any other argument
+    pub fn from_arg(arg: &str) -> Option<Result<Self, String>> {
+        let rest = arg.strip_prefix(SYNTHETIC_LOAD_FLAG)?;
+        if rest.is_empty() {
+            return Some(Ok(Self::default()));
+        }
+        let spec = rest.strip_prefix('=')?;
+        let mut parts = spec.split(',');
+        let mut load = Self::default();
+        let parsed = (|| {
+            load.metrics = parts.next().unwrap_or_default().trim().parse().map_err(|_| "metric count")?;
+            if let Some(rate) = parts.next() {
+                load.samples_per_second = rate.trim().parse().map_err(|_| "samples per second")?;
+            }
+            if load.metrics == 0 || parts.next().is_some() {
+                return Err("format");
+            }
+            Ok(load)
+        })();
+        Some(parsed.map_err(|field| format!("Invalid {} in {}: expected {}=METRICS[,SAMPLES_PER_SECOND]", field, arg, SYNTHETIC_LOAD_FLAG)))
+    }
+}
+
+// Fills every catalog metric, then adds named sensors until `metrics` series exist.
+// Each poll records as many samples as the rate calls for since the last one, spread
+// evenly over that interval, so plots and downsampling see realistic dense data
+pub struct SyntheticLoadMonitor {
+    load: SyntheticLoad,
+    last_update: Option<DateTime<Utc>>,
+    step: u64,
+}
+
+impl SyntheticLoadMonitor {
+    pub fn new(load: SyntheticLoad) -> Self {
+        Self { load, last_update: None, step: 0 }
+    }
+
+    // Samples each series gets for a poll `elapsed` after the previous one
+    pub fn samples_per_series(&self, elapsed: Duration) -> usize {
+        let total = self.load.samples_per_second as f64 * elapsed.num_milliseconds() as f64 / 1000.0;
+        ((total / self.load.metrics as f64).round() as usize).max(1)
+    }
+
+    pub fn record(&mut self, state: &SharedAppState, now: DateTime<Utc>) {
+        let elapsed = self.last_update.map_or(Duration::seconds(1), |last| now - last);
+        self.last_update = Some(now);
+        let per_series = self.samples_per_series(elapsed);
+        let spacing = elapsed / per_series as i32;
+
+        let mut app_state = state.write();
+        for sample in 0..per_series {
+            let timestamp = now - spacing * (per_series - 1 - sample) as i32;
+            let step = self.step;
+            self.step += 1;
+
+            let mut series = 0;
+            for descriptor in METRIC_DESCRIPTORS {
+                // Measured by the poller itself, which is what the load is profiling
+                if descriptor.is_poller_metric() {
+                    continue;
+                }
+                if let Some(metric) = app_state.metric_mut(descriptor.id) {
+                    metric.record_at(timestamp, wave(step, series));
+                    series += 1;
+                } else if let Some(event) = app_state.event_mut(descriptor.id) {
+                    event.record_at(timestamp, (step / 50 + series as u64).is_multiple_of(7));
+                    series += 1;
+                }
+            }
+            for index in series..self.load.metrics {
+                let name = format!("Synthetic {:04}", index);
+                app_state.motherboard.temperature_sensors.record_at(&name, timestamp, wave(step, index));
+            }
+        }
+    }
+}
+
+// Slow sine per series with a faster ripple, offset by index so series differ
+fn wave(step: u64, series: usize) -> f64 {
+    let t = step as f64 / 20.0 + series as f64;
+    50.0 + 30.0 * (t / 5.0).sin() + 5.0 * (t * 1.7).sin()
+}
+
+impl HardwareMonitor for SyntheticLoadMonitor {
+    fn name(&self) -> &'static str {
+        "Synthetic"
+    }
+
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        crate::logger::log_info(&format!(
+            "Synthetic load: {} metrics, {} samples/s",
+            self.load.metrics, self.load.samples_per_second
+        ));
+        Ok(())
+    }
+
+    fn update_metrics(&mut self, state: &SharedAppState) -> Result<(), MonitorError> {
+        self.record(state, Utc::now());
+        Ok(())
+    }
+
+    // Only ever registered explicitly, in place of the hardware monitors
+    fn supports_hardware(&self, _info: &HardwareInfo) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::AppState;
+
+    #[test]
+    fn test_synthetic_load_from_arg() {
+        assert_eq!(SyntheticLoad::from_arg("--other"), None);
+        assert_eq!(SyntheticLoad::from_arg("--synthetic-load"), Some(Ok(SyntheticLoad::default())));
+        assert_eq!(
+            SyntheticLoad::from_arg("--synthetic-load=200, 5000"),
+            Some(Ok(SyntheticLoad { metrics: 200, samples_per_second: 5000 }))
+        );
+        assert_eq!(SyntheticLoad::from_arg("--synthetic-load=300").unwrap().unwrap().metrics, 300);
+        assert!(SyntheticLoad::from_arg("--synthetic-load=0").unwrap().is_err());
+        assert!(SyntheticLoad::from_arg("--synthetic-load=many").unwrap().is_err());
+    }
+
+    #[test]
+    fn test_synthetic_monitor_fills_requested_series() {
+        let state = AppState::new_shared(1000);
+        let mut monitor = SyntheticLoadMonitor::new(SyntheticLoad { metrics: 100, samples_per_second: 1000 });
+        let start = Utc::now();
+        monitor.record(&state, start);
+        monitor.record(&state, start + Duration::seconds(2));
+
+        // The first poll assumes a one second interval: 10 samples per series, then 20
+        let app_state = state.read();
+        let utilization = &app_state.cpu.utilization.history;
+        assert_eq!(utilization.len(), 30);
+        assert_eq!(utilization.back().map(|(timestamp, _)| *timestamp), Some(start + Duration::seconds(2)));
+        assert!(utilization.iter().zip(utilization.iter().skip(1)).all(|(a, b)| a.0 < b.0));
+
+        let catalog = METRIC_DESCRIPTORS.iter().filter(|descriptor| !descriptor.is_poller_metric()).count();
+        assert_eq!(app_state.motherboard.temperature_sensors.len(), 100 - catalog);
+        assert_eq!(app_state.motherboard.temperature_sensors.get("Synthetic 0099").unwrap().history.len(), 30);
+    }
+}
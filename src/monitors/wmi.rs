@@ -0,0 +1,199 @@
+use crate::hardware_detection::MonitorError;
+
+// Small WMI client shared by the Windows monitors that read firmware and board-vendor
+// sensors. WMI reports failures as WBEM HRESULTs
+pub fn wmi_error(code: u32, context: &str) -> MonitorError {
+    const WBEM_E_NOT_FOUND: u32 = 0x8004_1002;
+    const WBEM_E_ACCESS_DENIED: u32 = 0x8004_1003;
+    const WBEM_E_NOT_SUPPORTED: u32 = 0x8004_100c;
+    const WBEM_E_INVALID_NAMESPACE: u32 = 0x8004_100e;
+    const WBEM_E_INVALID_CLASS: u32 = 0x8004_1010;
+    const WBEM_E_INVALID_METHOD: u32 = 0x8004_1055;
+    const E_ACCESSDENIED: u32 = 0x8007_0005;
+
+    let detail = format!("{} failed ({:#x})", context, code);
+    match code {
+        WBEM_E_ACCESS_DENIED | E_ACCESSDENIED => MonitorError::PermissionDenied(detail),
+        WBEM_E_NOT_FOUND | WBEM_E_NOT_SUPPORTED | WBEM_E_INVALID_NAMESPACE | WBEM_E_INVALID_CLASS | WBEM_E_INVALID_METHOD => {
+            MonitorError::DriverMissing(format!("{}: the firmware does not provide this WMI class", detail))
+        }
+        _ => MonitorError::Parse(detail),
+    }
+}
+
+#[cfg(windows)]
+pub use client::{property, WmiConnection};
+
+#[cfg(windows)]
+mod client {
+    use super::wmi_error;
+    use crate::hardware_detection::MonitorError;
+    use windows::core::{BSTR, PCWSTR, VARIANT};
+    use windows::Win32::Foundation::RPC_E_CHANGED_MODE;
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoSetProxyBlanket, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED, EOAC_NONE,
+        RPC_C_AUTHN_LEVEL_CALL, RPC_C_IMP_LEVEL_IMPERSONATE,
+    };
+    use windows::Win32::System::Rpc::{RPC_C_AUTHN_WINNT, RPC_C_AUTHZ_NONE};
+    use windows::Win32::System::Wmi::{
+        IWbemClassObject, IWbemLocator, IWbemServices, WbemLocator, WBEM_FLAG_FORWARD_ONLY,
+        WBEM_FLAG_RETURN_IMMEDIATELY, WBEM_GENERIC_FLAG_TYPE, WBEM_INFINITE,
+    };
+
+    pub struct WmiConnection {
+        services: IWbemServices,
+    }
+
+    impl WmiConnection {
+        pub fn connect(namespace: &str) -> Result<Self, MonitorError> {
+            let error = |e: windows::core::Error| wmi_error(e.code().0 as u32, &format!("connecting to {}", namespace));
+            unsafe {
+                // Joining the multithreaded apartment once covers every thread that polls later.
+                // A thread already in an apartment can use COM as it is
+                let status = CoInitializeEx(None, COINIT_MULTITHREADED);
+                if status.is_err() && status != RPC_E_CHANGED_MODE {
+                    return Err(wmi_error(status.0 as u32, "CoInitializeEx"));
+                }
+                let locator: IWbemLocator = CoCreateInstance(&WbemLocator, None, CLSCTX_INPROC_SERVER).map_err(error)?;
+                let services = locator
+                    .ConnectServer(&BSTR::from(namespace), &BSTR::new(), &BSTR::new(), &BSTR::new(), 0, &BSTR::new(), None)
+                    .map_err(error)?;
+                CoSetProxyBlanket(
+                    &services,
+                    RPC_C_AUTHN_WINNT,
+                    RPC_C_AUTHZ_NONE,
+                    PCWSTR::null(),
+                    RPC_C_AUTHN_LEVEL_CALL,
+                    RPC_C_IMP_LEVEL_IMPERSONATE,
+                    None,
+                    EOAC_NONE,
+                )
+                .map_err(error)?;
+                Ok(Self { services })
+            }
+        }
+
+        pub fn query(&self, wql: &str) -> Result<Vec<IWbemClassObject>, MonitorError> {
+            let error = |e: windows::core::Error| wmi_error(e.code().0 as u32, wql);
+            let mut objects = Vec::new();
+            unsafe {
+                let results = self
+                    .services
+                    .ExecQuery(
+                        &BSTR::from("WQL"),
+                        &BSTR::from(wql),
+                        WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY,
+                        None,
+                    )
+                    .map_err(error)?;
+                loop {
+                    let mut batch: [Option<IWbemClassObject>; 1] = [None];
+                    let mut returned = 0;
+                    results.Next(WBEM_INFINITE, &mut batch, &mut returned).ok().map_err(error)?;
+                    match batch[0].take() {
+                        Some(object) if returned == 1 => objects.push(object),
+                        _ => break,
+                    }
+                }
+            }
+            Ok(objects)
+        }
+
+        // Names of the methods a class defines
+        pub fn methods(&self, class: &str) -> Result<Vec<String>, MonitorError> {
+            let error = |e: windows::core::Error| wmi_error(e.code().0 as u32, &format!("reading class {}", class));
+            let mut names = Vec::new();
+            unsafe {
+                let definition = self.class(class)?;
+                definition.BeginMethodEnumeration(0).map_err(error)?;
+                loop {
+                    let mut name = BSTR::new();
+                    let (mut input, mut output) = (None, None);
+                    definition.NextMethod(0, &mut name, &mut input, &mut output).map_err(error)?;
+                    // The enumeration ends with a success code and no name
+                    if name.is_empty() {
+                        break;
+                    }
+                    names.push(name.to_string());
+                }
+                let _ = definition.EndMethodEnumeration();
+            }
+            Ok(names)
+        }
+
+        // Invoke a method on an instance and return its output parameters
+        pub fn call(
+            &self,
+            instance_path: &str,
+            class: &str,
+            method: &str,
+            arguments: &[(&str, VARIANT)],
+        ) -> Result<IWbemClassObject, MonitorError> {
+            let error = |e: windows::core::Error| wmi_error(e.code().0 as u32, &format!("{}.{}", class, method));
+            unsafe {
+                let input = if arguments.is_empty() {
+                    None
+                } else {
+                    let mut signature = None;
+                    self.class(class)?
+                        .GetMethod(&BSTR::from(method), 0, &mut signature, std::ptr::null_mut())
+                        .map_err(error)?;
+                    let signature = signature.ok_or_else(|| {
+                        MonitorError::Parse(format!("{}.{} takes no arguments", class, method))
+                    })?;
+                    let input = signature.SpawnInstance(0).map_err(error)?;
+                    for (name, value) in arguments {
+                        input.Put(&BSTR::from(*name), 0, value, 0).map_err(error)?;
+                    }
+                    Some(input)
+                };
+
+                let mut output = None;
+                self.services
+                    .ExecMethod(
+                        &BSTR::from(instance_path),
+                        &BSTR::from(method),
+                        WBEM_GENERIC_FLAG_TYPE(0),
+                        None,
+                        input.as_ref(),
+                        Some(&mut output),
+                        None,
+                    )
+                    .map_err(error)?;
+                output.ok_or_else(|| MonitorError::Parse(format!("{}.{} returned nothing", class, method)))
+            }
+        }
+
+        fn class(&self, class: &str) -> Result<IWbemClassObject, MonitorError> {
+            let mut definition = None;
+            unsafe {
+                self.services
+                    .GetObject(&BSTR::from(class), WBEM_GENERIC_FLAG_TYPE(0), None, Some(&mut definition), None)
+                    .map_err(|e| wmi_error(e.code().0 as u32, &format!("reading class {}", class)))?;
+            }
+            definition.ok_or_else(|| MonitorError::DriverMissing(format!("WMI class {} not found", class)))
+        }
+    }
+
+    pub fn property(object: &IWbemClassObject, name: &str) -> Result<VARIANT, MonitorError> {
+        let mut value = VARIANT::default();
+        unsafe {
+            object
+                .Get(&BSTR::from(name), 0, &mut value, None, None)
+                .map_err(|e| wmi_error(e.code().0 as u32, &format!("reading property {}", name)))?;
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wmi_error() {
+        assert!(matches!(wmi_error(0x8004_1003, "query"), MonitorError::PermissionDenied(_)));
+        assert!(matches!(wmi_error(0x8004_1010, "query"), MonitorError::DriverMissing(_)));
+        assert!(matches!(wmi_error(0x8000_4005, "query"), MonitorError::Parse(_)));
+    }
+}
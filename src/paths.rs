@@ -0,0 +1,171 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Keeps config, logs and sessions beside the executable, e.g. on a USB stick. A file
+// named PORTABLE_MARKER there does the same, for launching without arguments
+pub const PORTABLE_FLAG: &str = "--portable";
+const PORTABLE_MARKER: &str = "portable";
+
+// Directory name under the platform's config and data locations
+#[cfg(windows)]
+const APP_DIR: &str = "SimplePerformanceDashboard";
+#[cfg(target_os = "macos")]
+const APP_DIR: &str = "Simple Performance Dashboard";
+#[cfg(not(any(windows, target_os = "macos")))]
+const APP_DIR: &str = "simple_performance_dashboard";
+
+// Config files and directories older versions kept beside the executable
+const LEGACY_CONFIG: &[&str] = &[
+    "simple_performance_dashboard.toml",
+    "simple_performance_dashboard_ui.toml",
+    "profiles",
+    "scripts",
+];
+
+// Data older versions kept beside the executable; spilled history is per session and not kept
+const LEGACY_DATA: &[&str] = &["session_summaries", "export_buffer"];
+
+static PORTABLE: AtomicBool = AtomicBool::new(false);
+
+// Set from --portable at startup, before the logger opens its file
+pub fn set_portable(portable: bool) {
+    PORTABLE.store(portable, Ordering::Relaxed);
+}
+
+pub fn is_portable() -> bool {
+    PORTABLE.load(Ordering::Relaxed) || exe_dir().is_ok_and(|dir| dir.join(PORTABLE_MARKER).exists())
+}
+
+pub fn exe_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut dir = std::env::current_exe()?;
+    dir.pop(); // Remove executable name
+    Ok(dir)
+}
+
+// Config, machine profiles, UI layout and scripts; created if missing
+pub fn config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = if is_portable() { exe_dir()? } else { platform_config_dir()?.join(APP_DIR) };
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// Log, crash reports, session summaries, spilled history and export buffers; created if missing
+pub fn data_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = if is_portable() { exe_dir()? } else { platform_data_dir()?.join(APP_DIR) };
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// Roaming AppData for settings, local AppData for data that belongs to this machine
+#[cfg(windows)]
+fn platform_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(PathBuf::from(std::env::var_os("APPDATA").ok_or("APPDATA is not set")?))
+}
+
+#[cfg(windows)]
+fn platform_data_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(PathBuf::from(std::env::var_os("LOCALAPPDATA").ok_or("LOCALAPPDATA is not set")?))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(home_dir()?.join("Library/Application Support"))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_data_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    platform_config_dir()
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn platform_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    xdg_dir(std::env::var_os("XDG_CONFIG_HOME"), ".config")
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn platform_data_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    xdg_dir(std::env::var_os("XDG_DATA_HOME"), ".local/share")
+}
+
+// An XDG base directory, or its default under the home directory. The spec says to
+// ignore relative paths
+#[cfg(not(any(windows, target_os = "macos")))]
+fn xdg_dir(value: Option<std::ffi::OsString>, default: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    match value.map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => Ok(dir),
+        _ => Ok(home_dir()?.join(default)),
+    }
+}
+
+#[cfg(not(windows))]
+fn home_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(PathBuf::from(std::env::var_os("HOME").ok_or("HOME is not set")?))
+}
+
+// Copy what an older version left beside the executable into the new locations, once:
+// only while the new config directory has no config of its own. Returns what was copied
+pub fn migrate_legacy_files() -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    if is_portable() {
+        return Ok(Vec::new());
+    }
+    let config = config_dir()?;
+    if config.join(LEGACY_CONFIG[0]).exists() {
+        return Ok(Vec::new());
+    }
+    let legacy = exe_dir()?;
+    let mut copied = copy_entries(&legacy, &config, LEGACY_CONFIG)?;
+    if !copied.is_empty() {
+        copied.extend(copy_entries(&legacy, &data_dir()?, LEGACY_DATA)?);
+    }
+    Ok(copied)
+}
+
+// Files, and directories of files, by name; missing ones are skipped
+fn copy_entries(from: &Path, to: &Path, names: &[&str]) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut copied = Vec::new();
+    for name in names {
+        let source = from.join(name);
+        if source.is_file() {
+            fs::copy(&source, to.join(name))?;
+            copied.push(source);
+        } else if source.is_dir() {
+            for entry in fs::read_dir(&source)?.flatten().filter(|entry| entry.path().is_file()) {
+                fs::create_dir_all(to.join(name))?;
+                fs::copy(entry.path(), to.join(name).join(entry.file_name()))?;
+            }
+            copied.push(source);
+        }
+    }
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_entries_are_copied() {
+        let root = std::env::temp_dir().join(format!("spd_paths_test_{}", std::process::id()));
+        let (legacy, config) = (root.join("legacy"), root.join("config"));
+        fs::create_dir_all(legacy.join("profiles")).unwrap();
+        fs::create_dir_all(&config).unwrap();
+        fs::write(legacy.join("simple_performance_dashboard.toml"), "theme = \"dark\"").unwrap();
+        fs::write(legacy.join("profiles/ryzen.toml"), "keep_awake = true").unwrap();
+
+        let copied = copy_entries(&legacy, &config, LEGACY_CONFIG).unwrap();
+        assert_eq!(copied, vec![legacy.join("simple_performance_dashboard.toml"), legacy.join("profiles")]);
+        assert_eq!(fs::read_to_string(config.join("simple_performance_dashboard.toml")).unwrap(), "theme = \"dark\"");
+        assert!(config.join("profiles/ryzen.toml").is_file());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    #[test]
+    fn test_xdg_dirs_fall_back_to_home() {
+        let home = home_dir().unwrap();
+        assert_eq!(xdg_dir(Some("/srv/config".into()), ".config").unwrap(), PathBuf::from("/srv/config"));
+        assert_eq!(xdg_dir(Some("relative".into()), ".config").unwrap(), home.join(".config"));
+        assert_eq!(xdg_dir(None, ".local/share").unwrap(), home.join(".local/share"));
+    }
+}
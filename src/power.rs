@@ -0,0 +1,102 @@
+// Keeps the system and display awake for as long as the inhibitor is held, so a
+// long benchmark recording isn't cut short by sleep or a blanked screen
+pub struct SleepInhibitor {
+    // Linux and macOS hold the inhibit through a helper process that lives as long as we do
+    #[cfg(not(windows))]
+    child: std::process::Child,
+}
+
+impl SleepInhibitor {
+    pub fn acquire(reason: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        #[cfg(windows)]
+        {
+            use windows::Win32::System::Power::{
+                SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+            };
+            let _ = reason;
+            // Called from the UI thread, which lives for the whole session
+            let previous = unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED) };
+            if previous.0 == 0 {
+                return Err("SetThreadExecutionState failed".into());
+            }
+            Ok(Self {})
+        }
+
+        #[cfg(not(windows))]
+        {
+            let child = inhibit_command(reason)
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn()?;
+            Ok(Self { child })
+        }
+    }
+
+    // False once the inhibit was refused or the helper process died
+    pub fn is_active(&mut self) -> bool {
+        #[cfg(windows)]
+        {
+            true
+        }
+
+        #[cfg(not(windows))]
+        {
+            matches!(self.child.try_wait(), Ok(None))
+        }
+    }
+}
+
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        #[cfg(windows)]
+        unsafe {
+            use windows::Win32::System::Power::{SetThreadExecutionState, ES_CONTINUOUS};
+            SetThreadExecutionState(ES_CONTINUOUS);
+        }
+
+        #[cfg(not(windows))]
+        {
+            // Closing stdin ends the inhibiting process's child too, so nothing is orphaned
+            drop(self.child.stdin.take());
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+// logind inhibitors are honoured by both Wayland compositors and X11 sessions. The
+// inhibit lasts until `cat` sees stdin close, which also happens if we crash
+#[cfg(all(unix, not(target_os = "macos")))]
+fn inhibit_command(reason: &str) -> std::process::Command {
+    let mut command = std::process::Command::new("systemd-inhibit");
+    command
+        .arg("--what=idle:sleep")
+        .arg("--who=Simple Performance Dashboard")
+        .arg(format!("--why={}", reason))
+        .arg("--mode=block")
+        .arg("cat");
+    command
+}
+
+// caffeinate holds IOKit power assertions until it exits
+#[cfg(target_os = "macos")]
+fn inhibit_command(_reason: &str) -> std::process::Command {
+    let mut command = std::process::Command::new("caffeinate");
+    command.arg("-dims").arg("-w").arg(std::process::id().to_string());
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_inhibit_command_blocks_idle_and_sleep() {
+        let command = super::inhibit_command("Recording benchmark");
+        let args: Vec<_> = command.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect();
+
+        assert_eq!(command.get_program(), "systemd-inhibit");
+        assert!(args.contains(&"--what=idle:sleep".to_string()));
+        assert!(args.contains(&"--why=Recording benchmark".to_string()));
+    }
+}
@@ -0,0 +1,386 @@
+use std::path::{Component, Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::hardware_detection::MonitorError;
+
+// Command-line flag that starts this executable as the helper instead of the GUI
+pub const HELPER_FLAG: &str = "--privileged-helper";
+
+// The only root-only sensors read through the helper: the RAPL energy counters, which
+// the kernel hides from users since they leak what other processes are doing
+const POWERCAP_DIR: &str = "/sys/class/powercap";
+const RAPL_ZONE_PREFIX: &str = "intel-rapl:";
+const RAPL_FILES: &[&str] = &["energy_uj", "max_energy_range_uj"];
+
+// Whether the GUI reads root-only sensors through the helper, which runs elevated so the
+// GUI doesn't have to. Its socket is found from the user's id, so there's nothing else to set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HelperConfig {
+    pub enabled: bool,
+}
+
+// One line of the helper protocol
+#[derive(Debug, Clone, PartialEq)]
+pub enum HelperRequest {
+    Ping,
+    ReadFile(PathBuf),
+}
+
+impl HelperRequest {
+    pub fn encode(&self) -> String {
+        match self {
+            HelperRequest::Ping => "PING".to_string(),
+            HelperRequest::ReadFile(path) => format!("READ {}", path.display()),
+        }
+    }
+
+    pub fn parse(line: &str) -> Result<Self, MonitorError> {
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "PING" => Ok(HelperRequest::Ping),
+            "READ" if !argument.is_empty() => Ok(HelperRequest::ReadFile(PathBuf::from(argument))),
+            _ => Err(MonitorError::Parse(format!("unknown helper request: {}", line))),
+        }
+    }
+}
+
+// Responses are "OK <value>" or "ERR <kind> <message>", one line each
+pub fn encode_response(result: &Result<String, MonitorError>) -> String {
+    match result {
+        Ok(value) => format!("OK {}", value.trim().replace('\n', " ")),
+        Err(error) => {
+            let (kind, detail) = match error {
+                MonitorError::PermissionDenied(detail) => ("permission", detail),
+                MonitorError::DriverMissing(detail) => ("driver", detail),
+                MonitorError::DeviceGone(detail) => ("gone", detail),
+                MonitorError::Timeout(detail) => ("timeout", detail),
+                MonitorError::Parse(detail) => ("parse", detail),
+            };
+            format!("ERR {} {}", kind, detail.replace('\n', " "))
+        }
+    }
+}
+
+pub fn decode_response(line: &str) -> Result<String, MonitorError> {
+    let line = line.trim_end();
+    if let Some(value) = line.strip_prefix("OK") {
+        return Ok(value.trim_start().to_string());
+    }
+    let error = line
+        .strip_prefix("ERR ")
+        .ok_or_else(|| MonitorError::Parse(format!("malformed helper response: {}", line)))?;
+    let (kind, detail) = error.split_once(' ').unwrap_or((error, ""));
+    let detail = detail.to_string();
+    Err(match kind {
+        "permission" => MonitorError::PermissionDenied(detail),
+        "driver" => MonitorError::DriverMissing(detail),
+        "gone" => MonitorError::DeviceGone(detail),
+        "timeout" => MonitorError::Timeout(detail),
+        _ => MonitorError::Parse(detail),
+    })
+}
+
+// Only the RAPL counters of a powercap zone or subzone, with no ".." to climb out of it
+pub fn is_allowed_path(path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(POWERCAP_DIR) else {
+        return false;
+    };
+    let mut components: Vec<Component> = relative.components().collect();
+    let Some(Component::Normal(file)) = components.pop() else {
+        return false;
+    };
+    let in_rapl_zone = |component: &Component| match component {
+        Component::Normal(zone) => zone.to_str().is_some_and(|zone| zone.starts_with(RAPL_ZONE_PREFIX)),
+        _ => false,
+    };
+    !components.is_empty() && components.iter().all(in_rapl_zone) && RAPL_FILES.iter().any(|allowed| file == *allowed)
+}
+
+// Execute a request in this process; the helper calls this with elevated rights
+pub fn handle_request(request: &HelperRequest) -> Result<String, MonitorError> {
+    match request {
+        HelperRequest::Ping => Ok("pong".to_string()),
+        HelperRequest::ReadFile(path) => {
+            if !is_allowed_path(path) {
+                return Err(MonitorError::PermissionDenied(format!("{} is not a sensor path", path.display())));
+            }
+            Ok(std::fs::read_to_string(path)?)
+        }
+    }
+}
+
+// The helper's socket, in the user's runtime directory so only they and root can reach it
+pub fn socket_path(uid: u32) -> PathBuf {
+    PathBuf::from(format!("/run/user/{}/simple_performance_dashboard_helper.sock", uid))
+}
+
+// The account that started the helper through sudo or pkexec. Its settings and socket are
+// that user's, never root's, and only that user's dashboard is answered
+pub fn invoking_uid() -> Result<u32, Box<dyn std::error::Error>> {
+    let uid = ["SUDO_UID", "PKEXEC_UID"]
+        .iter()
+        .find_map(|variable| std::env::var(variable).ok())
+        .ok_or("start the helper with sudo (or pkexec) from the account running the dashboard")?;
+    Ok(uid.parse()?)
+}
+
+// Run the helper for the user who started it: answer their dashboard until the process is
+// killed. It runs without the file logger, so it reports to the console it was started from
+#[cfg(target_os = "linux")]
+pub fn serve() -> Result<(), Box<dyn std::error::Error>> {
+    let uid = invoking_uid()?;
+    let listener = socket::bind(&socket_path(uid), uid)?;
+    eprintln!("Privileged helper listening on {} for user {}", socket_path(uid).display(), uid);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                std::thread::spawn(move || {
+                    if let Err(e) = socket::serve_connection(stream, uid) {
+                        eprintln!("Privileged helper connection failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => eprintln!("Privileged helper failed to accept connection: {}", e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn serve() -> Result<(), Box<dyn std::error::Error>> {
+    Err("the privileged helper is only needed, and available, on Linux".into())
+}
+
+#[cfg(target_os = "linux")]
+pub mod socket {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+    use super::{decode_response, encode_response, handle_request, HelperRequest};
+    use crate::hardware_detection::MonitorError;
+
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+    // Listen at path, owned by uid and closed to everyone else. A socket left by an
+    // earlier helper is replaced
+    pub fn bind(path: &Path, uid: u32) -> Result<UnixListener, Box<dyn std::error::Error>> {
+        let _ = std::fs::remove_file(path);
+        let listener = UnixListener::bind(path)?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+        // SAFETY: c_path is a valid NUL-terminated path; -1 leaves the group unchanged
+        if unsafe { libc::chown(c_path.as_ptr(), uid, u32::MAX) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(listener)
+    }
+
+    // The user id of the process at the other end, from the kernel rather than the client
+    pub fn peer_uid(stream: &UnixStream) -> std::io::Result<u32> {
+        let mut credentials = libc::ucred { pid: 0, uid: 0, gid: 0 };
+        let mut length = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+        // SAFETY: credentials and length describe a ucred-sized buffer, as SO_PEERCRED expects
+        let result = unsafe {
+            libc::getsockopt(
+                stream.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                &mut credentials as *mut libc::ucred as *mut libc::c_void,
+                &mut length,
+            )
+        };
+        if result != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(credentials.uid)
+    }
+
+    // Answer requests from the user the helper was started for; anyone else has their
+    // first request refused and is disconnected
+    pub fn serve_connection(stream: UnixStream, allowed_uid: u32) -> std::io::Result<()> {
+        let mut writer = stream.try_clone()?;
+        let peer = peer_uid(&stream)?;
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            if peer != allowed_uid {
+                let refused = Err(MonitorError::PermissionDenied(format!("user {} may not use this helper", peer)));
+                return writeln!(writer, "{}", encode_response(&refused));
+            }
+            let result = HelperRequest::parse(&line).and_then(|request| handle_request(&request));
+            writeln!(writer, "{}", encode_response(&result))?;
+        }
+        Ok(())
+    }
+
+    // Connection from the GUI to the helper, reopened after failures
+    pub struct HelperClient {
+        path: PathBuf,
+        connection: Option<BufReader<UnixStream>>,
+    }
+
+    impl HelperClient {
+        pub fn new(path: &Path) -> Self {
+            Self {
+                path: path.to_path_buf(),
+                connection: None,
+            }
+        }
+
+        pub fn request(&mut self, request: &HelperRequest) -> Result<String, MonitorError> {
+            let result = self.exchange(request);
+            if result.is_err() {
+                // Drop the connection so a restarted helper is picked up next time
+                self.connection = None;
+            }
+            result?
+        }
+
+        // Outer error: transport failure; inner: the helper's answer
+        fn exchange(&mut self, request: &HelperRequest) -> Result<Result<String, MonitorError>, MonitorError> {
+            if self.connection.is_none() {
+                self.connection = Some(BufReader::new(self.connect()?));
+            }
+            let connection = self.connection.as_mut().expect("connection opened above");
+            writeln!(connection.get_mut(), "{}", request.encode())?;
+            let mut line = String::new();
+            if connection.read_line(&mut line)? == 0 {
+                return Err(MonitorError::DeviceGone("privileged helper closed the connection".to_string()));
+            }
+            Ok(decode_response(&line))
+        }
+
+        fn connect(&self) -> Result<UnixStream, MonitorError> {
+            let stream = UnixStream::connect(&self.path).map_err(|e| {
+                MonitorError::PermissionDenied(format!("privileged helper not reachable at {}: {}", self.path.display(), e))
+            })?;
+            stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+            stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+            Ok(stream)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+static HELPER: std::sync::OnceLock<parking_lot::Mutex<socket::HelperClient>> = std::sync::OnceLock::new();
+
+// Point privileged reads at this user's helper when enabled; called once at startup
+pub fn configure(config: &HelperConfig) {
+    #[cfg(target_os = "linux")]
+    if config.enabled {
+        // SAFETY: getuid has no preconditions and always succeeds
+        let path = socket_path(unsafe { libc::getuid() });
+        crate::logger::log_info(&format!("Using privileged helper at {}", path.display()));
+        let _ = HELPER.set(parking_lot::Mutex::new(socket::HelperClient::new(&path)));
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = config;
+}
+
+pub fn helper_configured() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        HELPER.get().is_some()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        false
+    }
+}
+
+// Read a sensor file directly, going through the helper only when that is denied
+pub fn read_sensor_file(path: &Path) -> Result<String, MonitorError> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        #[cfg(target_os = "linux")]
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => match HELPER.get() {
+            Some(helper) => helper.lock().request(&HelperRequest::ReadFile(path.to_path_buf())),
+            None => Err(e.into()),
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Alternative to the helper on Linux: grant the GUI binary just the capabilities it needs
+pub fn setcap_command() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let exe = std::env::current_exe().ok()?;
+    Some(format!("sudo setcap cap_dac_read_search+ep {}", exe.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_round_trip() {
+        let requests = [
+            HelperRequest::Ping,
+            HelperRequest::ReadFile(PathBuf::from("/sys/class/powercap/intel-rapl:0/energy_uj")),
+        ];
+        for request in requests {
+            assert_eq!(HelperRequest::parse(&request.encode()).unwrap(), request);
+        }
+        assert!(HelperRequest::parse("DELETE /").is_err());
+        assert!(HelperRequest::parse("MSR 0 0x611").is_err());
+    }
+
+    #[test]
+    fn test_response_round_trip() {
+        assert_eq!(decode_response(&encode_response(&Ok("12345\n".to_string()))), Ok("12345".to_string()));
+
+        let denied = Err(MonitorError::PermissionDenied("/sys/class/powercap/intel-rapl:0/energy_uj".to_string()));
+        assert_eq!(decode_response(&encode_response(&denied)), denied);
+        assert!(decode_response("garbage").is_err());
+    }
+
+    #[test]
+    fn test_only_rapl_counters_are_allowed() {
+        assert!(is_allowed_path(Path::new("/sys/class/powercap/intel-rapl:0/energy_uj")));
+        assert!(is_allowed_path(Path::new("/sys/class/powercap/intel-rapl:0/intel-rapl:0:1/max_energy_range_uj")));
+        assert!(!is_allowed_path(Path::new("/etc/shadow")));
+        assert!(!is_allowed_path(Path::new("/sys/class/powercap/intel-rapl:0/../../../../etc/shadow")));
+        assert!(!is_allowed_path(Path::new("sys/class/powercap/intel-rapl:0/energy_uj")));
+        // Other sysfs files, even under powercap, aren't needed and aren't served
+        assert!(!is_allowed_path(Path::new("/sys/class/powercap/intel-rapl:0/name")));
+        assert!(!is_allowed_path(Path::new("/sys/class/powercap/energy_uj")));
+        assert!(!is_allowed_path(Path::new("/sys/devices/virtual/dmi/id/product_serial")));
+        assert!(!is_allowed_path(Path::new("/sys/class/hwmon/hwmon0/temp1_input")));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_helper_serves_only_the_invoking_user() {
+        let dir = std::env::temp_dir().join(format!("spd_helper_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("helper.sock");
+        // SAFETY: getuid has no preconditions and always succeeds
+        let uid = unsafe { libc::getuid() };
+
+        let listener = socket::bind(&path, uid).expect("Failed to bind helper");
+        std::thread::spawn(move || {
+            // This user first, then as if the helper had been started for someone else
+            for allowed_uid in [uid, uid.wrapping_add(1)] {
+                let (stream, _) = listener.accept().expect("Failed to accept client");
+                let _ = socket::serve_connection(stream, allowed_uid);
+            }
+        });
+
+        let mut client = socket::HelperClient::new(&path);
+        assert_eq!(client.request(&HelperRequest::Ping), Ok("pong".to_string()));
+        let denied = client.request(&HelperRequest::ReadFile(PathBuf::from("/etc/shadow")));
+        assert!(matches!(denied, Err(MonitorError::PermissionDenied(_))));
+        drop(client);
+
+        let mut stranger = socket::HelperClient::new(&path);
+        let refused = stranger.request(&HelperRequest::Ping);
+        assert!(matches!(refused, Err(MonitorError::PermissionDenied(message)) if message.contains("may not use this helper")));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,600 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use chrono::{DateTime, Duration, Utc};
+use crate::logger;
+use crate::model::{AppState, MetricDescriptor};
+
+// Aliases may refer to each other; nesting deeper than this is taken as a cycle
+const MAX_ALIAS_DEPTH: usize = 8;
+
+// Parentheses, negations, operators and call arguments nested deeper than this are
+// rejected while parsing, before they can exhaust the stack
+const MAX_NESTING: usize = 64;
+
+// Longest *_over() window accepted; anything longer is a typo rather than a query
+const MAX_WINDOW_HOURS: i64 = 24 * 366;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError(pub String);
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+fn error<T>(message: impl Into<String>) -> Result<T, QueryError> {
+    Err(QueryError(message.into()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Avg,
+    Min,
+    Max,
+    Sum,
+    Count,
+    Abs,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "avg" => Function::Avg,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "sum" => Function::Sum,
+            "count" => Function::Count,
+            "abs" => Function::Abs,
+            _ => return None,
+        })
+    }
+
+    fn apply(&self, values: &[f64]) -> Result<f64, QueryError> {
+        if values.is_empty() && *self != Function::Count && *self != Function::Sum {
+            return error("no data to aggregate");
+        }
+        Ok(match self {
+            Function::Avg => values.iter().sum::<f64>() / values.len() as f64,
+            Function::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            Function::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Function::Sum => values.iter().sum(),
+            Function::Count => values.len() as f64,
+            Function::Abs => scalar(values.to_vec(), "abs()")?.abs(),
+        })
+    }
+}
+
+// Parsed expression, e.g. `avg(cpu.core_utilization[*]) - ambient` or
+// `max_over(5m, gpu.power_consumption)`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Series(String), // Series key, glob pattern ("cpu.core_utilization.*") or alias
+    Negate(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+    Window(Function, Duration, String), // Aggregate over each matching series' samples in the window
+}
+
+// What an expression is evaluated against
+pub struct QueryContext<'a> {
+    pub state: &'a AppState,
+    pub aliases: &'a BTreeMap<String, String>, // Name -> expression, e.g. ambient = "motherboard.chassis_temperature"
+    pub now: DateTime<Utc>,
+}
+
+// True for a plain metric id, which alert rules and dashboards read directly; anything
+// else they are given is evaluated as an expression
+pub fn is_metric_id(text: &str) -> bool {
+    MetricDescriptor::find(text).is_some()
+}
+
+// `*` matches any run of characters, including none
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || text.len() < first.len() + last.len() || !text.ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn scalar(values: Vec<f64>, what: &str) -> Result<f64, QueryError> {
+    match values.len() {
+        1 => Ok(values[0]),
+        0 => error(format!("no data for {}", what)),
+        count => error(format!("{} selects {} series; aggregate it with avg(), min(), max() or sum()", what, count)),
+    }
+}
+
+impl Expr {
+    pub fn evaluate(&self, context: &QueryContext) -> Result<f64, QueryError> {
+        let value = scalar(self.values(context, 0)?, "the expression")?;
+        if !value.is_finite() {
+            return error("result is not a number (division by zero?)");
+        }
+        Ok(value)
+    }
+
+    fn values(&self, context: &QueryContext, depth: usize) -> Result<Vec<f64>, QueryError> {
+        match self {
+            Expr::Number(value) => Ok(vec![*value]),
+            Expr::Series(pattern) => {
+                if let Some(alias) = context.aliases.get(pattern) {
+                    if depth >= MAX_ALIAS_DEPTH {
+                        return error(format!("alias {} refers back to itself", pattern));
+                    }
+                    return parse(alias)
+                        .map_err(|e| QueryError(format!("in alias {}: {}", pattern, e)))?
+                        .values(context, depth + 1);
+                }
+                let mut values = Vec::new();
+                let mut matched = false;
+                context.state.for_each_series(&mut |key, series| {
+                    if glob_match(pattern, key) {
+                        matched = true;
+                        values.extend(series.current);
+                    }
+                });
+                if !matched && !pattern.contains('*') {
+                    if !is_metric_id(pattern) {
+                        return error(format!("unknown metric {}", pattern));
+                    }
+                    // On/off metrics read as 1 while active
+                    values.extend(context.state.current_value(pattern));
+                }
+                Ok(values)
+            }
+            Expr::Negate(inner) => Ok(vec![-scalar(inner.values(context, depth)?, "-")?]),
+            Expr::Binary(op, left, right) => {
+                let left = scalar(left.values(context, depth)?, "the left operand")?;
+                let right = scalar(right.values(context, depth)?, "the right operand")?;
+                Ok(vec![match op {
+                    Op::Add => left + right,
+                    Op::Sub => left - right,
+                    Op::Mul => left * right,
+                    Op::Div => left / right,
+                }])
+            }
+            Expr::Call(function, arguments) => {
+                let mut values = Vec::new();
+                for argument in arguments {
+                    values.extend(argument.values(context, depth)?);
+                }
+                Ok(vec![function.apply(&values)?])
+            }
+            Expr::Window(function, window, pattern) => {
+                // Capped at the history held, so a long window reads all of it
+                let mut oldest = context.now;
+                context.state.for_each_series(&mut |key, series| {
+                    if let Some((timestamp, _)) = series.history.front().filter(|_| glob_match(pattern, key)) {
+                        oldest = oldest.min(*timestamp);
+                    }
+                });
+                let window = (*window).min(context.now.signed_duration_since(oldest));
+                let since = context.now.checked_sub_signed(window).ok_or_else(|| QueryError("window is out of range".to_string()))?;
+                let mut values = Vec::new();
+                context.state.for_each_series(&mut |key, series| {
+                    if glob_match(pattern, key) {
+                        values.extend(
+                            series.history.iter().rev().take_while(|(timestamp, _)| *timestamp >= since).map(|(_, value)| *value),
+                        );
+                    }
+                });
+                Ok(vec![function.apply(&values)?])
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Duration(Duration),
+    Name(String),
+    Op(char), // + - * / ( ) ,
+}
+
+fn duration(value: f64, unit: &str) -> Result<Duration, QueryError> {
+    let unit_millis: i64 = match unit {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => return error(format!("unknown duration unit {} (use ms, s, m or h)", unit)),
+    };
+    // Whole units are multiplied as integers so an overlong window is an error, not an overflow
+    let millis = (value.trunc() as i64)
+        .checked_mul(unit_millis)
+        .and_then(|millis| millis.checked_add((value.fract() * unit_millis as f64) as i64))
+        .filter(|millis| *millis <= MAX_WINDOW_HOURS * 3_600_000);
+    match millis {
+        Some(millis) => Ok(Duration::milliseconds(millis)),
+        None => error(format!("window {}{} is longer than {}h", value, unit, MAX_WINDOW_HOURS)),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let number: String = chars[start..i].iter().collect();
+            let value: f64 = number.parse().map_err(|_| QueryError(format!("bad number {}", number)))?;
+            let unit_start = i;
+            while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                i += 1;
+            }
+            if unit_start == i {
+                tokens.push(Token::Number(value));
+            } else {
+                let unit: String = chars[unit_start..i].iter().collect();
+                tokens.push(Token::Duration(duration(value, &unit)?));
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            // Series keys: letters, digits, '_' and '.', with `[n]`/`[*]` as another spelling of `.n`/`.*`
+            let mut name = String::new();
+            while i < chars.len() {
+                let c = chars[i];
+                let after_separator = name.ends_with('.') || name.ends_with('[');
+                if c.is_alphanumeric() || c == '_' || c == '.' || c == '[' || c == ']' || (c == '*' && after_separator) {
+                    name.push(c);
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Name(name.replace('[', ".").replace(']', "")));
+        } else if c == '"' {
+            // Quoted keys for sensor names with spaces, e.g. "cpu.temperature_sensors.k10temp Tctl"
+            let end = chars[i + 1..].iter().position(|&c| c == '"').ok_or_else(|| QueryError("unterminated quote".to_string()))?;
+            tokens.push(Token::Name(chars[i + 1..i + 1 + end].iter().collect()));
+            i += end + 2;
+        } else if "+-*/(),".contains(c) {
+            tokens.push(Token::Op(c));
+            i += 1;
+        } else {
+            return error(format!("unexpected '{}'", c));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize, // Nesting of the expression being parsed
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: char) -> Result<(), QueryError> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            error(format!("expected '{}'", op))
+        }
+    }
+
+    // One level deeper; callers restore the depth they started at once the level is parsed.
+    // Evaluation recurses as deep as the tree, so operator chains count as well
+    fn enter(&mut self) -> Result<(), QueryError> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return error(format!("expression is nested more than {} levels deep", MAX_NESTING));
+        }
+        Ok(())
+    }
+
+    fn expression(&mut self) -> Result<Expr, QueryError> {
+        let entered = self.depth;
+        let mut left = self.term()?;
+        loop {
+            let op = if self.eat('+') {
+                Op::Add
+            } else if self.eat('-') {
+                Op::Sub
+            } else {
+                self.depth = entered;
+                return Ok(left);
+            };
+            self.enter()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, QueryError> {
+        let entered = self.depth;
+        let mut left = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                Op::Mul
+            } else if self.eat('/') {
+                Op::Div
+            } else {
+                self.depth = entered;
+                return Ok(left);
+            };
+            self.enter()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, QueryError> {
+        if self.eat('-') {
+            let entered = self.depth;
+            self.enter()?;
+            let inner = self.unary()?;
+            self.depth = entered;
+            return Ok(Expr::Negate(Box::new(inner)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, QueryError> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Op('(')) => {
+                let entered = self.depth;
+                self.enter()?;
+                let inner = self.expression()?;
+                self.depth = entered;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            Some(Token::Name(name)) if self.peek() == Some(&Token::Op('(')) => {
+                self.position += 1;
+                self.call(&name)
+            }
+            Some(Token::Name(name)) => Ok(Expr::Series(name)),
+            Some(Token::Duration(_)) => error("a duration is only allowed as the window of a *_over() function"),
+            Some(Token::Op(op)) => error(format!("unexpected '{}'", op)),
+            None => error("unexpected end of expression"),
+        }
+    }
+
+    // After the opening parenthesis
+    fn call(&mut self, name: &str) -> Result<Expr, QueryError> {
+        if let Some(function) = name.strip_suffix("_over").and_then(Function::from_name) {
+            let window = match self.next() {
+                Some(Token::Duration(window)) => window,
+                _ => return error(format!("{}() takes a window first, e.g. {}(5m, gpu.power_consumption)", name, name)),
+            };
+            self.expect(',')?;
+            let pattern = match self.next() {
+                Some(Token::Name(pattern)) => pattern,
+                _ => return error(format!("{}() takes a metric after the window", name)),
+            };
+            self.expect(')')?;
+            return Ok(Expr::Window(function, window, pattern));
+        }
+        let function = Function::from_name(name).ok_or_else(|| QueryError(format!("unknown function {}()", name)))?;
+        let mut arguments = Vec::new();
+        if !self.eat(')') {
+            let entered = self.depth;
+            self.enter()?;
+            loop {
+                arguments.push(self.expression()?);
+                if self.eat(')') {
+                    break;
+                }
+                if !self.eat(',') {
+                    return error(format!("expected ',' or ')' in {}()", name));
+                }
+            }
+            self.depth = entered;
+        }
+        if arguments.is_empty() {
+            return error(format!("{}() needs at least one argument", name));
+        }
+        Ok(Expr::Call(function, arguments))
+    }
+}
+
+pub fn parse(text: &str) -> Result<Expr, QueryError> {
+    let mut parser = Parser { tokens: tokenize(text)?, position: 0, depth: 0 };
+    let expr = parser.expression()?;
+    if parser.position < parser.tokens.len() {
+        return error(format!("unexpected {:?} after the expression", parser.tokens[parser.position]));
+    }
+    Ok(expr)
+}
+
+// Evaluates the expressions used by alert rules and dashboards once per poll, recording
+// each into AppState::queries like any other series. Parsed once per expression
+#[derive(Debug, Default)]
+pub struct QueryEngine {
+    parsed: HashMap<String, Result<Expr, QueryError>>,
+}
+
+impl QueryEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn evaluate_all(&mut self, state: &mut AppState, expressions: &[String], aliases: &BTreeMap<String, String>, now: DateTime<Utc>) {
+        self.parsed.retain(|text, _| expressions.contains(text));
+        state.queries.retain(|text, _| expressions.contains(text));
+        let mut results = Vec::new();
+        {
+            let context = QueryContext { state, aliases, now };
+            for text in expressions {
+                let parsed = self.parsed.entry(text.clone()).or_insert_with(|| parse(text));
+                let result = match parsed {
+                    Ok(expr) => expr.evaluate(&context),
+                    Err(e) => Err(e.clone()),
+                };
+                results.push((text, result));
+            }
+        }
+        for (text, result) in results {
+            let query = state.queries.entry(text.clone()).or_default();
+            match result {
+                Ok(value) => {
+                    query.values.record_at(now, value);
+                    query.error = None;
+                }
+                Err(e) => {
+                    if query.error.as_ref() != Some(&e.0) {
+                        logger::log_warning(&format!("Expression \"{}\": {}", text, e));
+                    }
+                    // Stale values would keep an alert on this expression firing
+                    query.values.current = None;
+                    query.error = Some(e.0);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(state: &AppState, aliases: &BTreeMap<String, String>, text: &str) -> Result<f64, QueryError> {
+        parse(text)?.evaluate(&QueryContext { state, aliases, now: Utc::now() })
+    }
+
+    #[test]
+    fn test_expressions_over_series_aliases_and_windows() {
+        let mut state = AppState::new(1000);
+        state.cpu.core_utilization = vec![Default::default(); 4];
+        for (core, value) in [10.0, 20.0, 30.0, 40.0].iter().enumerate() {
+            state.cpu.core_utilization[core].update(*value);
+        }
+        state.motherboard.chassis_temperature.update(5.0);
+        let now = Utc::now();
+        state.gpu.power_consumption.record_at(now - Duration::minutes(10), 300.0);
+        state.gpu.power_consumption.record_at(now - Duration::minutes(2), 180.0);
+        state.gpu.power_consumption.record_at(now, 150.0);
+        let aliases = BTreeMap::from([("ambient".to_string(), "motherboard.chassis_temperature".to_string())]);
+
+        assert_eq!(evaluate(&state, &aliases, "avg(cpu.core_utilization[*]) - ambient"), Ok(20.0));
+        assert_eq!(evaluate(&state, &aliases, "max(cpu.core_utilization.*) * 2 + -1"), Ok(79.0));
+        assert_eq!(evaluate(&state, &aliases, "count(cpu.core_utilization[*])"), Ok(4.0));
+        assert_eq!(evaluate(&state, &aliases, "cpu.core_utilization[2] / (1 + 1)"), Ok(15.0));
+        // The 10 minute old sample is outside the window
+        assert_eq!(evaluate(&state, &aliases, "max_over(5m, gpu.power_consumption)"), Ok(180.0));
+        assert_eq!(evaluate(&state, &aliases, "avg_over(1h, gpu.power_consumption)"), Ok(210.0));
+    }
+
+    #[test]
+    fn test_expression_errors() {
+        let state = AppState::new(1000);
+        let aliases = BTreeMap::from([("loop".to_string(), "loop + 1".to_string())]);
+        let message = |text: &str| evaluate(&state, &aliases, text).unwrap_err().0;
+
+        assert!(message("avg(cpu.utilization").contains("expected ',' or ')' in avg()"));
+        assert!(message("(1 + 2").contains("expected ')'"));
+        assert!(message("median(cpu.utilization)").contains("unknown function median()"));
+        assert!(message("max_over(cpu.utilization)").contains("takes a window first"));
+        assert!(message("gpu.nonexistent").contains("unknown metric gpu.nonexistent"));
+        assert!(message("cpu.utilization").contains("no data for"));
+        assert!(message("loop").contains("refers back to itself"));
+        assert!(message("1 / 0").contains("not a number"));
+
+        let mut state = AppState::new(1000);
+        state.cpu.core_utilization = vec![Default::default(); 2];
+        state.cpu.core_utilization[0].update(1.0);
+        state.cpu.core_utilization[1].update(2.0);
+        let error = evaluate(&state, &aliases, "cpu.core_utilization[*] + 1").unwrap_err().0;
+        assert!(error.contains("selects 2 series"));
+    }
+
+    #[test]
+    fn test_overlong_windows_are_errors_and_long_ones_read_all_history() {
+        let mut state = AppState::new(1000);
+        let now = Utc::now();
+        state.cpu.utilization.record_at(now - Duration::minutes(90), 10.0);
+        state.cpu.utilization.record_at(now, 30.0);
+        let aliases = BTreeMap::new();
+        let message = |text: &str| evaluate(&state, &aliases, text).unwrap_err().0;
+
+        assert!(message("max_over(99999999999999h, cpu.utilization)").contains("longer than"));
+        assert!(message("max_over(9223372036854775807ms, cpu.utilization)").contains("longer than"));
+        assert_eq!(evaluate(&state, &aliases, "avg_over(8000h, cpu.utilization)"), Ok(20.0));
+        assert_eq!(evaluate(&state, &aliases, "avg_over(1.5m, cpu.utilization)"), Ok(30.0));
+    }
+
+    #[test]
+    fn test_deeply_nested_expressions_are_rejected() {
+        let state = AppState::new(1000);
+        let aliases = BTreeMap::new();
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+
+        assert_eq!(evaluate(&state, &aliases, &nested(MAX_NESTING)), Ok(1.0));
+        assert!(parse(&nested(100_000)).unwrap_err().0.contains("nested more than"));
+        assert!(parse(&"-".repeat(100_000)).unwrap_err().0.contains("nested more than"));
+        assert!(parse(&vec!["1"; 100_000].join(" + ")).unwrap_err().0.contains("nested more than"));
+        assert!(parse(&format!("{}1{}", "avg(".repeat(100_000), ")".repeat(100_000))).unwrap_err().0.contains("nested more than"));
+        // Arguments sit side by side, so they don't add up
+        let arguments = vec![nested(MAX_NESTING - 1); 10].join(", ");
+        assert_eq!(evaluate(&state, &aliases, &format!("max({})", arguments)), Ok(1.0));
+    }
+
+    #[test]
+    fn test_engine_records_expressions_for_alerts() {
+        let mut state = AppState::new(1000);
+        state.gpu.hotspot_temperature.update(100.0);
+        state.gpu.package_temperature.update(70.0);
+        let expressions = vec!["gpu.hotspot_temperature - gpu.package_temperature".to_string(), "avg(".to_string()];
+        let mut engine = QueryEngine::new();
+        engine.evaluate_all(&mut state, &expressions, &BTreeMap::new(), Utc::now());
+
+        assert_eq!(state.current_value(&expressions[0]), Some(30.0));
+        assert_eq!(state.current_value(&expressions[1]), None);
+        assert!(state.queries[&expressions[1]].error.is_some());
+
+        // Expressions no longer in use are dropped
+        engine.evaluate_all(&mut state, &expressions[..1], &BTreeMap::new(), Utc::now());
+        assert_eq!(state.queries.len(), 1);
+        assert_eq!(state.queries[&expressions[0]].values.history.len(), 2);
+    }
+}
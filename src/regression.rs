@@ -0,0 +1,183 @@
+use crate::baseline::{MetricSummary, SessionSummary};
+use crate::model::{MetricDescriptor, METRIC_DESCRIPTORS};
+
+// Two-sided 95% critical value. Sessions hold hundreds of samples or more, where
+// Student's t is indistinguishable from the normal distribution
+const CRITICAL_T: f64 = 1.96;
+
+// Clocks move in 25-100 MHz bins, so smaller moves of the p99 are noise
+const PERCENTILE_TOLERANCE: f64 = 0.02;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Statistic {
+    Average,
+    P99,
+    Energy, // Wh over the whole session, from the average power
+}
+
+impl Statistic {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Statistic::Average => "average",
+            Statistic::P99 => "p99",
+            Statistic::Energy => "energy",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Worse {
+    Higher,
+    Lower,
+}
+
+// Which way a change counts as a regression; None for metrics that depend on the
+// workload rather than the machine, e.g. utilization or network speed
+fn worse_direction(descriptor: &MetricDescriptor) -> Option<Worse> {
+    match descriptor.unit.trim() {
+        "°C" | "W" | "ms" => Some(Worse::Higher),
+        "MHz" => Some(Worse::Lower),
+        _ => None,
+    }
+}
+
+// One statistic of one metric, before and after
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    pub metric_id: &'static str,
+    pub statistic: Statistic,
+    pub before: f64,
+    pub after: f64,
+    pub significant: bool,
+    pub regression: bool, // Significant, and in the direction that's worse for this metric
+}
+
+impl Difference {
+    pub fn delta(&self) -> f64 {
+        self.after - self.before
+    }
+}
+
+// Welch's t statistic for the difference in averages; None when either side lacks a spread
+pub fn welch_t(before: &MetricSummary, after: &MetricSummary) -> Option<f64> {
+    let standard_error = (before.std_dev?.powi(2) / before.samples as f64 + after.std_dev?.powi(2) / after.samples as f64).sqrt();
+    let delta = after.average - before.average;
+    if standard_error == 0.0 {
+        // Two constant series: any change at all is real
+        return Some(if delta == 0.0 { 0.0 } else { delta.signum() * f64::INFINITY });
+    }
+    Some(delta / standard_error)
+}
+
+// Every statistic both sessions recorded, in catalog order: averages of all metrics,
+// p99 of clocks and energy of power draws
+pub fn compare(before: &SessionSummary, after: &SessionSummary) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    for descriptor in METRIC_DESCRIPTORS {
+        let (Some(old), Some(new)) = (before.metrics.get(descriptor.id), after.metrics.get(descriptor.id)) else {
+            continue;
+        };
+        let worse = worse_direction(descriptor);
+        let is_worse = |delta: f64| match worse {
+            Some(Worse::Higher) => delta > 0.0,
+            Some(Worse::Lower) => delta < 0.0,
+            None => false,
+        };
+        let mut push = |statistic, before: f64, after: f64, significant: bool| {
+            differences.push(Difference {
+                metric_id: descriptor.id,
+                statistic,
+                before,
+                after,
+                significant,
+                regression: significant && is_worse(after - before),
+            });
+        };
+
+        let averages_differ = welch_t(old, new).is_some_and(|t| t.abs() >= CRITICAL_T);
+        push(Statistic::Average, old.average, new.average, averages_differ);
+        match descriptor.unit.trim() {
+            "MHz" => {
+                if let (Some(old_p99), Some(new_p99)) = (old.p99, new.p99) {
+                    let significant = (new_p99 - old_p99).abs() > old_p99.abs() * PERCENTILE_TOLERANCE;
+                    push(Statistic::P99, old_p99, new_p99, significant);
+                }
+            }
+            // Sessions differ in length, so whether energy use changed is judged on the
+            // average power; the totals are shown for reference
+            "W" => {
+                let hours = |summary: &SessionSummary| summary.duration().num_seconds() as f64 / 3600.0;
+                push(Statistic::Energy, old.average * hours(before), new.average * hours(after), averages_differ);
+            }
+            _ => {}
+        }
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use crate::model::AppState;
+
+    fn session(readings: &[(&str, &[f64])]) -> SessionSummary {
+        let mut summary = SessionSummary::from_state(&AppState::new(1000), false);
+        summary.session_start = Utc::now() - Duration::hours(2);
+        summary.session_end = summary.session_start + Duration::hours(1);
+        for (id, values) in readings {
+            summary.metrics.insert(id.to_string(), MetricSummary::from_values(values.iter().copied()).unwrap());
+        }
+        summary
+    }
+
+    fn find<'a>(differences: &'a [Difference], id: &str, statistic: Statistic) -> &'a Difference {
+        differences.iter().find(|difference| difference.metric_id == id && difference.statistic == statistic).unwrap()
+    }
+
+    #[test]
+    fn test_summary_spread_and_percentile() {
+        let summary = MetricSummary::from_values((1..=100).map(f64::from)).unwrap();
+        assert_eq!(summary.p99, Some(99.0));
+        assert!((summary.std_dev.unwrap() - 29.011).abs() < 0.001);
+        assert_eq!(MetricSummary::from_values([5.0].into_iter()).unwrap().std_dev, None);
+    }
+
+    #[test]
+    fn test_significant_changes_are_flagged_as_regressions() {
+        let before = session(&[
+            ("gpu.package_temperature", &[60.0, 61.0, 59.0, 60.0, 62.0, 58.0]),
+            ("cpu.clock_speed", &[4500.0, 4500.0, 4475.0, 4500.0, 4525.0, 4500.0]),
+            ("cpu.utilization", &[20.0, 30.0, 25.0, 20.0, 30.0, 25.0]),
+            ("gpu.power_consumption", &[200.0, 210.0, 190.0, 200.0, 205.0, 195.0]),
+        ]);
+        let after = session(&[
+            ("gpu.package_temperature", &[66.0, 67.0, 65.0, 66.0, 68.0, 64.0]),
+            ("cpu.clock_speed", &[4200.0, 4225.0, 4200.0, 4175.0, 4200.0, 4200.0]),
+            ("cpu.utilization", &[60.0, 70.0, 65.0, 60.0, 70.0, 65.0]),
+            ("gpu.power_consumption", &[201.0, 209.0, 191.0, 199.0, 206.0, 194.0]),
+        ]);
+        let differences = compare(&before, &after);
+
+        let temperature = find(&differences, "gpu.package_temperature", Statistic::Average);
+        assert_eq!(temperature.delta(), 6.0);
+        assert!(temperature.significant && temperature.regression);
+
+        // Lower clocks are the regression for frequencies
+        let p99 = find(&differences, "cpu.clock_speed", Statistic::P99);
+        assert_eq!((p99.before, p99.after), (4525.0, 4225.0));
+        assert!(p99.regression);
+        let improved = compare(&after, &before);
+        assert!(find(&improved, "cpu.clock_speed", Statistic::P99).significant);
+        assert!(!find(&improved, "cpu.clock_speed", Statistic::P99).regression);
+
+        // Busier, but utilization follows the workload, not the machine
+        let utilization = find(&differences, "cpu.utilization", Statistic::Average);
+        assert!(utilization.significant && !utilization.regression);
+
+        // Within the noise
+        let energy = find(&differences, "gpu.power_consumption", Statistic::Energy);
+        assert_eq!(energy.before, 200.0);
+        assert!(!energy.significant && !energy.regression);
+    }
+}
@@ -0,0 +1,714 @@
+use serde::{Deserialize, Serialize};
+use crate::model::{LatencyHistogram, MetricDescriptor};
+
+// Prometheus remote_write push target, e.g. Grafana Cloud or VictoriaMetrics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteWriteConfig {
+    pub enabled: bool,
+    pub url: String,
+    // Basic auth (Grafana Cloud uses the instance id and an API token)
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub bearer_token: Option<String>,
+    pub interval_secs: u64,
+    pub job: String,
+    // Defaults to the host name
+    pub instance: Option<String>,
+    // Pushes that fail are kept on disk up to this size and replayed on reconnect; 0 disables
+    pub buffer_max_mb: u64,
+}
+
+impl Default for RemoteWriteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            username: None,
+            password: None,
+            bearer_token: None,
+            interval_secs: 15,
+            job: "simple_performance_dashboard".to_string(),
+            instance: None,
+            buffer_max_mb: 64,
+        }
+    }
+}
+
+// Upper bounds of the histogram buckets latencies are counted into, in seconds
+pub const HISTOGRAM_BUCKETS_SECONDS: [f64; 11] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+
+// The dashboard's units count megabytes in MiB
+const BYTES_PER_MB: f64 = 1_048_576.0;
+
+// Prometheus metric type a metric is exported as, declared in the pushed metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    Gauge,
+    Histogram,
+}
+
+// Metrics whose samples are each a latency worth aggregating, exported as histograms
+// so percentiles can be queried across pushes. Other millisecond metrics, e.g. the NTP
+// offset or a percentile already computed, are readings and stay gauges
+const HISTOGRAM_METRICS: &[&str] = &["poller.cycle_duration", "poller.jitter"];
+
+pub fn metric_type(descriptor: &MetricDescriptor) -> MetricType {
+    if HISTOGRAM_METRICS.contains(&descriptor.id) {
+        MetricType::Histogram
+    } else {
+        MetricType::Gauge
+    }
+}
+
+// Base unit name suffix for a dashboard unit, and the factor converting values to it
+pub fn base_unit(unit: &str) -> Option<(&'static str, f64)> {
+    match unit.trim() {
+        "%" => Some(("ratio", 0.01)),
+        "ms" => Some(("seconds", 0.001)),
+        "MHz" => Some(("hertz", 1.0e6)),
+        "MB" => Some(("bytes", BYTES_PER_MB)),
+        "MB/s" => Some(("bytes_per_second", BYTES_PER_MB)),
+        "V" => Some(("volts", 1.0)),
+        "W" => Some(("watts", 1.0)),
+        "°C" => Some(("celsius", 1.0)),
+        "RPM" => Some(("rpm", 1.0)),
+        _ => None,
+    }
+}
+
+// Prometheus metric name for a descriptor, ending in its base unit,
+// e.g. cpu.utilization -> spd_cpu_utilization_ratio
+pub fn metric_name(descriptor: &MetricDescriptor) -> String {
+    let mut id = descriptor.id;
+    // Ids that already name their unit get the base unit instead
+    for unit in ["_percent", "_mb"] {
+        id = id.strip_suffix(unit).unwrap_or(id);
+    }
+    let sanitized: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match base_unit(descriptor.unit) {
+        Some((suffix, _)) => format!("spd_{}_{}", sanitized, suffix),
+        None => format!("spd_{}", sanitized),
+    }
+}
+
+// Cumulative latency histogram, as a client library keeps one between scrapes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub bounds: Vec<f64>,  // Bucket upper bounds in seconds; +Inf is implied
+    pub buckets: Vec<u64>, // Observations at or under each bound
+    pub count: u64,
+    pub sum: f64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        Self {
+            bounds: HISTOGRAM_BUCKETS_SECONDS.to_vec(),
+            buckets: vec![0; HISTOGRAM_BUCKETS_SECONDS.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    pub fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(&self.bounds) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    // Block I/O latency in its power-of-two microsecond buckets. The probe keeps only
+    // bucket counts, so the sum is estimated from each bucket's midpoint
+    pub fn from_latency(latency: &LatencyHistogram) -> Self {
+        let mut histogram = Self { bounds: Vec::new(), buckets: Vec::new(), count: 0, sum: 0.0 };
+        for (slot, count) in latency.buckets.iter().enumerate() {
+            let lower = (1u64 << slot) as f64 / 1.0e6;
+            histogram.count += count;
+            histogram.sum += *count as f64 * lower * 1.5;
+            histogram.bounds.push(lower * 2.0);
+            histogram.buckets.push(histogram.count);
+        }
+        histogram
+    }
+}
+
+#[cfg(feature = "remote-write")]
+pub use client::RemoteWriter;
+
+#[cfg(feature = "remote-write")]
+mod client {
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use chrono::{DateTime, Utc};
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use base64::Engine;
+    use prost::Message;
+    use tokio::runtime::Handle;
+    use tokio::task::JoinHandle;
+    use super::{base_unit, metric_name, metric_type, Histogram, MetricType, RemoteWriteConfig};
+    use crate::config::SharedConfig;
+    use crate::export_buffer::{ExportBuffer, SendFailure};
+    use crate::logger;
+    use crate::model::{AppState, MetricKind, SharedAppState, METRIC_DESCRIPTORS};
+
+    // Wire format from prometheus/prompb/remote.proto and types.proto
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct WriteRequest {
+        #[prost(message, repeated, tag = "1")]
+        pub timeseries: Vec<TimeSeries>,
+        #[prost(message, repeated, tag = "3")]
+        pub metadata: Vec<MetricMetadata>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TimeSeries {
+        #[prost(message, repeated, tag = "1")]
+        pub labels: Vec<Label>,
+        #[prost(message, repeated, tag = "2")]
+        pub samples: Vec<Sample>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Label {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(string, tag = "2")]
+        pub value: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Sample {
+        #[prost(double, tag = "1")]
+        pub value: f64,
+        #[prost(int64, tag = "2")]
+        pub timestamp: i64,
+    }
+
+    // TYPE, HELP and unit of one metric family
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct MetricMetadata {
+        #[prost(int32, tag = "1")]
+        pub r#type: i32,
+        #[prost(string, tag = "4")]
+        pub metric_family_name: String,
+        #[prost(string, tag = "5")]
+        pub help: String,
+        #[prost(string, tag = "6")]
+        pub unit: String,
+    }
+
+    // MetricMetadata.MetricType values
+    const TYPE_GAUGE: i32 = 2;
+    const TYPE_HISTOGRAM: i32 = 3;
+
+    // What a request covers, kept once it is delivered or buffered
+    #[derive(Debug, Default)]
+    pub struct PushProgress {
+        newest: Vec<(&'static str, DateTime<Utc>)>,
+        histograms: Vec<(&'static str, Histogram)>,
+    }
+
+    pub struct RemoteWriter {
+        state: SharedAppState,
+        config: SharedConfig,
+        agent: ureq::Agent,
+        // Newest sample already pushed for each metric
+        last_sent: HashMap<&'static str, DateTime<Utc>>,
+        // Latency histograms over everything pushed so far
+        histograms: HashMap<&'static str, Histogram>,
+        started: DateTime<Utc>,
+        instance: String,
+        buffer: Option<ExportBuffer>,
+    }
+
+    impl RemoteWriter {
+        pub fn new(state: SharedAppState, config: SharedConfig) -> Self {
+            let agent = ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(10))
+                .build();
+            let instance = crate::anonymize::host_name().unwrap_or_else(|| "unknown".to_string());
+            let max_bytes = config.read().remote_write.buffer_max_mb * 1024 * 1024;
+            let buffer = match ExportBuffer::for_exporter("remote_write", max_bytes) {
+                Ok(buffer) => Some(buffer),
+                Err(e) => {
+                    logger::log_error("Failed to create remote_write buffer, failed pushes will be retried from memory only", &*e);
+                    None
+                }
+            };
+
+            Self {
+                state,
+                config,
+                agent,
+                last_sent: HashMap::new(),
+                histograms: HashMap::new(),
+                started: Utc::now(),
+                instance,
+                buffer,
+            }
+        }
+
+        // Push on the runtime; the blocking HTTP call runs off the async workers
+        // so a slow endpoint can't hold up anything else
+        pub fn start(mut self, runtime: &Handle) -> JoinHandle<()> {
+            runtime.spawn(async move {
+                loop {
+                    let config = self.config.read().remote_write.clone();
+                    if config.enabled && !config.url.is_empty() {
+                        let pushed = tokio::task::spawn_blocking(move || {
+                            self.push_pending(&config);
+                            self
+                        }).await;
+                        match pushed {
+                            Ok(writer) => self = writer,
+                            Err(e) => {
+                                logger::log_error("remote_write task failed", &e);
+                                break;
+                            }
+                        }
+                    }
+                    let interval_secs = self.config.read().remote_write.interval_secs.max(1);
+                    tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+                }
+            })
+        }
+
+        fn push_pending(&mut self, config: &RemoteWriteConfig) {
+            let (request, progress) = {
+                let state = self.state.read();
+                self.build_request(&state, config)
+            };
+            let max_bytes = config.buffer_max_mb * 1024 * 1024;
+            if let Some(buffer) = &mut self.buffer {
+                buffer.set_max_bytes(max_bytes);
+            }
+            let buffer = self.buffer.as_ref().filter(|_| max_bytes > 0);
+
+            // What an earlier outage left goes first, so the endpoint sees samples in order
+            let mut reachable = true;
+            if let Some(buffer) = buffer {
+                match buffer.replay(|body| self.send(config, body)) {
+                    Ok(0) => {}
+                    Ok(replayed) => logger::log_info(&format!("Replayed {} buffered remote_write pushes", replayed)),
+                    Err(e) => {
+                        logger::log_error("Failed to replay buffered remote_write pushes", &*e);
+                        reachable = false;
+                    }
+                }
+            }
+            if request.timeseries.is_empty() {
+                return;
+            }
+
+            let body = match encode(&request) {
+                Ok(body) => body,
+                Err(e) => {
+                    logger::log_error("Failed to encode remote_write request", &*e);
+                    return;
+                }
+            };
+            let sent = if reachable {
+                self.send(config, &body)
+            } else {
+                Err(SendFailure::Unavailable("endpoint unreachable".into()))
+            };
+            match sent {
+                Ok(()) => self.commit(progress),
+                // Sending it again would be rejected too, so these samples are given up on
+                Err(SendFailure::Rejected(e)) => {
+                    logger::log_error("remote_write endpoint rejected samples, dropping them", &*e);
+                    self.commit(progress);
+                }
+                Err(SendFailure::Unavailable(e)) => {
+                    logger::log_error("Failed to push samples to remote_write endpoint", &*e);
+                    // On disk the push outlives the in-memory history and a crash; without
+                    // a buffer it is retried from memory next interval
+                    if let Some(buffer) = buffer {
+                        match buffer.push(&body) {
+                            Ok(()) => self.commit(progress),
+                            Err(e) => logger::log_error("Failed to buffer remote_write push", &e),
+                        }
+                    }
+                }
+            }
+        }
+
+        fn commit(&mut self, progress: PushProgress) {
+            self.last_sent.extend(progress.newest);
+            self.histograms.extend(progress.histograms);
+        }
+
+        // Collect samples recorded since the last successful push
+        pub fn build_request(&self, state: &AppState, config: &RemoteWriteConfig) -> (WriteRequest, PushProgress) {
+            let instance = config.instance.clone().unwrap_or_else(|| self.instance.clone());
+            let mut common = crate::export::sample_labels(&self.config.read().export_labels, state);
+            // Labels the exporter sets itself can't be replaced
+            for reserved in ["__name__", "instance", "job", "le"] {
+                common.remove(reserved);
+            }
+            common.insert("instance".to_string(), instance);
+            common.insert("job".to_string(), config.job.clone());
+            // Labels must be sorted by name, which the BTreeMap keeps them
+            let series = |name: String, extra: Option<(&str, String)>, samples: Vec<Sample>| {
+                let mut labels = common.clone();
+                labels.insert("__name__".to_string(), name);
+                if let Some((name, value)) = extra {
+                    labels.insert(name.to_string(), value);
+                }
+                let labels = labels.into_iter().map(|(name, value)| Label { name, value }).collect();
+                TimeSeries { labels, samples }
+            };
+            let mut request = WriteRequest::default();
+            let mut progress = PushProgress::default();
+
+            for descriptor in METRIC_DESCRIPTORS {
+                let since = self.last_sent.get(descriptor.id).copied().unwrap_or(self.started);
+                let Some(pending) = state.samples_since(descriptor.id, since) else {
+                    continue;
+                };
+                let Some(&(last, _)) = pending.last() else {
+                    continue;
+                };
+                progress.newest.push((descriptor.id, last));
+
+                let name = metric_name(descriptor);
+                let (unit, scale) = base_unit(descriptor.unit).unwrap_or(("", 1.0));
+                let kind = match metric_type(descriptor) {
+                    MetricType::Gauge => {
+                        let samples = pending
+                            .into_iter()
+                            .map(|(timestamp, value)| Sample { value: value * scale, timestamp: timestamp.timestamp_millis() })
+                            .collect();
+                        request.timeseries.push(series(name.clone(), None, samples));
+                        TYPE_GAUGE
+                    }
+                    MetricType::Histogram => {
+                        let mut histogram = self.histograms.get(descriptor.id).cloned().unwrap_or_default();
+                        for (_, value) in &pending {
+                            histogram.observe(value * scale);
+                        }
+                        request.timeseries.extend(histogram_series(&histogram, &name, last.timestamp_millis(), &series));
+                        progress.histograms.push((descriptor.id, histogram));
+                        TYPE_HISTOGRAM
+                    }
+                };
+                let help = match descriptor.kind {
+                    MetricKind::Event => format!("{} (1 while active)", descriptor.label),
+                    MetricKind::Gauge { .. } => descriptor.label.to_string(),
+                };
+                request.metadata.push(MetricMetadata { r#type: kind, metric_family_name: name, help, unit: unit.to_string() });
+            }
+
+            // The session's block I/O latency distribution, as it stands now
+            if state.storage.io_latency.count() > 0 {
+                let name = "spd_storage_io_latency_seconds".to_string();
+                let histogram = Histogram::from_latency(&state.storage.io_latency);
+                request.timeseries.extend(histogram_series(&histogram, &name, Utc::now().timestamp_millis(), &series));
+                request.metadata.push(MetricMetadata {
+                    r#type: TYPE_HISTOGRAM,
+                    metric_family_name: name,
+                    help: "Block I/O completion latency".to_string(),
+                    unit: "seconds".to_string(),
+                });
+            }
+
+            (request, progress)
+        }
+
+        // Like Prometheus, only server errors, timeouts, rate limiting and transport
+        // failures are retried; any other client error rejects the payload for good
+        fn send(&self, config: &RemoteWriteConfig, body: &[u8]) -> Result<(), SendFailure<Box<dyn std::error::Error>>> {
+            let mut http_request = self.agent
+                .post(&config.url)
+                .set("Content-Type", "application/x-protobuf")
+                .set("Content-Encoding", "snappy")
+                .set("X-Prometheus-Remote-Write-Version", "0.1.0");
+            if let Some(token) = &config.bearer_token {
+                http_request = http_request.set("Authorization", &format!("Bearer {}", token));
+            } else if let Some(username) = &config.username {
+                let credentials = format!("{}:{}", username, config.password.as_deref().unwrap_or(""));
+                http_request = http_request.set("Authorization", &format!("Basic {}", BASE64.encode(credentials)));
+            }
+
+            match http_request.send_bytes(body) {
+                Ok(_) => Ok(()),
+                Err(e @ ureq::Error::Status(code, _)) if (400..500).contains(&code) && code != 408 && code != 429 => {
+                    Err(SendFailure::Rejected(e.into()))
+                }
+                Err(e) => Err(SendFailure::Unavailable(e.into())),
+            }
+        }
+    }
+
+    // A histogram's _bucket series per bound plus +Inf, then _sum and _count
+    fn histogram_series(
+        histogram: &Histogram,
+        name: &str,
+        timestamp: i64,
+        series: &impl Fn(String, Option<(&str, String)>, Vec<Sample>) -> TimeSeries,
+    ) -> Vec<TimeSeries> {
+        let sample = |value: f64| vec![Sample { value, timestamp }];
+        let mut timeseries: Vec<TimeSeries> = histogram
+            .bounds
+            .iter()
+            .zip(&histogram.buckets)
+            .map(|(bound, count)| series(format!("{}_bucket", name), Some(("le", bound.to_string())), sample(*count as f64)))
+            .collect();
+        timeseries.push(series(format!("{}_bucket", name), Some(("le", "+Inf".to_string())), sample(histogram.count as f64)));
+        timeseries.push(series(format!("{}_sum", name), None, sample(histogram.sum)));
+        timeseries.push(series(format!("{}_count", name), None, sample(histogram.count as f64)));
+        timeseries
+    }
+
+    // Snappy-compressed protobuf, as sent and as buffered
+    fn encode(request: &WriteRequest) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(snap::raw::Encoder::new().compress_vec(&request.encode_to_vec())?)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::config::AppConfig;
+
+        #[test]
+        fn test_build_request_only_includes_new_samples() {
+            let state = AppState::new_shared(1000);
+            let mut writer = RemoteWriter::new(state.clone(), AppConfig::new_shared(AppConfig::default()));
+            let config = RemoteWriteConfig { instance: Some("test-host".to_string()), ..Default::default() };
+
+            writer.started = Utc::now() - chrono::Duration::seconds(1);
+            state.write().cpu.utilization.update(42.0);
+
+            let (request, progress) = writer.build_request(&state.read(), &config);
+            assert_eq!(request.timeseries.len(), 1);
+            let series = &request.timeseries[0];
+            assert_eq!(series.labels[0].value, "spd_cpu_utilization_ratio");
+            assert_eq!(series.labels[1].value, "test-host");
+            assert_eq!(series.samples[0].value, 0.42);
+            assert_eq!(request.metadata[0].r#type, TYPE_GAUGE);
+            assert_eq!(request.metadata[0].help, "CPU Utilization");
+
+            // Hardware and configured labels go on every series, after the exporter's own
+            state.write().cpu.name = Some("Test CPU".to_string());
+            writer.config.write().export_labels.custom.insert("location".to_string(), "office".to_string());
+            writer.config.write().export_labels.custom.insert("job".to_string(), "ignored".to_string());
+            let (labelled, _) = writer.build_request(&state.read(), &config);
+            let labels: Vec<(&str, &str)> = labelled.timeseries[0].labels.iter().map(|label| (label.name.as_str(), label.value.as_str())).collect();
+            assert_eq!(labels, vec![
+                ("__name__", "spd_cpu_utilization_ratio"),
+                ("cpu_model", "Test CPU"),
+                ("instance", "test-host"),
+                ("job", "simple_performance_dashboard"),
+                ("location", "office"),
+            ]);
+
+            // Nothing new after the push is recorded
+            writer.commit(progress);
+            let (request, _) = writer.build_request(&state.read(), &config);
+            assert!(request.timeseries.is_empty());
+
+            // Encoded request decodes back to the same message
+            let encoded = series.encode_to_vec();
+            assert_eq!(TimeSeries::decode(encoded.as_slice()).unwrap(), *series);
+        }
+
+        #[test]
+        fn test_failed_push_is_buffered_to_disk() {
+            let state = AppState::new_shared(1000);
+            let mut writer = RemoteWriter::new(state.clone(), AppConfig::new_shared(AppConfig::default()));
+            let dir = std::env::temp_dir().join("spd_remote_write_buffer_test");
+            let _ = std::fs::remove_dir_all(&dir);
+            writer.buffer = Some(ExportBuffer::new(dir.clone(), 1024 * 1024).unwrap());
+            // Nothing listens on the discard port, so the push fails straight away
+            let config = RemoteWriteConfig { url: "http://127.0.0.1:9/api/v1/write".to_string(), ..Default::default() };
+
+            writer.started = Utc::now() - chrono::Duration::seconds(1);
+            state.write().cpu.utilization.update(42.0);
+            writer.push_pending(&config);
+
+            // Buffered, and not pushed again from memory
+            let buffer = writer.buffer.as_ref().unwrap();
+            assert_eq!(buffer.len(), 1);
+            assert!(writer.build_request(&state.read(), &config).0.timeseries.is_empty());
+
+            // The buffered body is the request as it would have been sent
+            let mut bodies = Vec::new();
+            buffer.replay(|body| {
+                bodies.push(body.to_vec());
+                Ok::<(), SendFailure<&str>>(())
+            }).unwrap();
+            let decoded = snap::raw::Decoder::new().decompress_vec(&bodies[0]).unwrap();
+            let request = WriteRequest::decode(decoded.as_slice()).unwrap();
+            assert_eq!(request.timeseries[0].samples[0].value, 0.42);
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn test_rejected_pushes_are_dropped_not_retried() {
+            use std::io::{Read, Write};
+
+            // An endpoint answering every push with 400, as for out-of-order samples
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/api/v1/write", listener.local_addr().unwrap());
+            let server = std::thread::spawn(move || {
+                for _ in 0..2 {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = Vec::new();
+                    let mut chunk = [0; 4096];
+                    while let Ok(read @ 1..) = stream.read(&mut chunk) {
+                        request.extend_from_slice(&chunk[..read]);
+                        let text = String::from_utf8_lossy(&request);
+                        let Some(header_end) = text.find("\r\n\r\n") else {
+                            continue;
+                        };
+                        let length: usize = text[..header_end]
+                            .lines()
+                            .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|value| value.trim().to_string()))
+                            .and_then(|value| value.parse().ok())
+                            .unwrap_or(0);
+                        if request.len() >= header_end + 4 + length {
+                            break;
+                        }
+                    }
+                    let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+                }
+            });
+
+            let state = AppState::new_shared(1000);
+            let mut writer = RemoteWriter::new(state.clone(), AppConfig::new_shared(AppConfig::default()));
+            let dir = std::env::temp_dir().join("spd_remote_write_rejected_test");
+            let _ = std::fs::remove_dir_all(&dir);
+            let buffer = ExportBuffer::new(dir.clone(), 1024 * 1024).unwrap();
+            buffer.push(b"left over from an outage").unwrap();
+            writer.buffer = Some(buffer);
+            let config = RemoteWriteConfig { url, ..Default::default() };
+
+            writer.started = Utc::now() - chrono::Duration::seconds(1);
+            state.write().cpu.utilization.update(42.0);
+            writer.push_pending(&config);
+            server.join().unwrap();
+
+            // The buffered push was dropped rather than blocking the live one, which isn't kept either
+            assert!(writer.buffer.as_ref().unwrap().is_empty());
+            assert!(writer.build_request(&state.read(), &config).0.timeseries.is_empty());
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn test_latencies_are_pushed_as_cumulative_histograms() {
+            let state = AppState::new_shared(1000);
+            let mut writer = RemoteWriter::new(state.clone(), AppConfig::new_shared(AppConfig::default()));
+            let config = RemoteWriteConfig::default();
+            writer.started = Utc::now() - chrono::Duration::seconds(1);
+
+            let value = |request: &WriteRequest, name: &str, le: Option<&str>| {
+                request.timeseries.iter()
+                    .find(|series| {
+                        let le_label = series.labels.iter().find(|label| label.name == "le");
+                        series.labels[0].value == name && le_label.map(|label| label.value.as_str()) == le
+                    })
+                    .map(|series| series.samples[0].value)
+            };
+            state.write().poller.record_cycle(Utc::now(), 3.0, 100);
+            let (request, progress) = writer.build_request(&state.read(), &config);
+            assert_eq!(value(&request, "spd_poller_cycle_duration_seconds_bucket", Some("0.0025")), Some(0.0));
+            assert_eq!(value(&request, "spd_poller_cycle_duration_seconds_bucket", Some("0.005")), Some(1.0));
+            assert_eq!(value(&request, "spd_poller_cycle_duration_seconds_bucket", Some("+Inf")), Some(1.0));
+            assert_eq!(value(&request, "spd_poller_cycle_duration_seconds_sum", None), Some(0.003));
+            let metadata = request.metadata.iter().find(|metadata| metadata.metric_family_name == "spd_poller_cycle_duration_seconds").unwrap();
+            assert_eq!((metadata.r#type, metadata.unit.as_str()), (TYPE_HISTOGRAM, "seconds"));
+            writer.commit(progress);
+
+            // The next push counts on from the last one
+            std::thread::sleep(Duration::from_millis(5));
+            state.write().poller.record_cycle(Utc::now(), 300.0, 100);
+            let (request, _) = writer.build_request(&state.read(), &config);
+            assert_eq!(value(&request, "spd_poller_cycle_duration_seconds_bucket", Some("0.005")), Some(1.0));
+            assert_eq!(value(&request, "spd_poller_cycle_duration_seconds_count", None), Some(2.0));
+        }
+
+        #[test]
+        fn test_ntp_offset_is_pushed_as_a_gauge_beside_latency_histograms() {
+            let state = AppState::new_shared(1000);
+            let mut writer = RemoteWriter::new(state.clone(), AppConfig::new_shared(AppConfig::default()));
+            writer.started = Utc::now() - chrono::Duration::seconds(1);
+            {
+                let mut app_state = state.write();
+                app_state.clock.ntp_offset.update(-12.5);
+                app_state.poller.record_cycle(Utc::now(), 3.0, 100);
+            }
+            let (request, _) = writer.build_request(&state.read(), &RemoteWriteConfig::default());
+            let names: Vec<&str> = request.timeseries.iter().map(|series| series.labels[0].value.as_str()).collect();
+            let kind = |family: &str| request.metadata.iter().find(|metadata| metadata.metric_family_name == family).map(|metadata| metadata.r#type);
+
+            // The offset is a signed reading, not a latency, so it keeps its samples
+            let offset = request.timeseries.iter().find(|series| series.labels[0].value == "spd_clock_ntp_offset_seconds").unwrap();
+            assert_eq!(offset.samples.iter().map(|sample| sample.value).collect::<Vec<_>>(), vec![-0.0125]);
+            assert_eq!(kind("spd_clock_ntp_offset_seconds"), Some(TYPE_GAUGE));
+            assert!(!names.iter().any(|name| name.starts_with("spd_clock_ntp_offset_seconds_")));
+
+            assert_eq!(kind("spd_poller_cycle_duration_seconds"), Some(TYPE_HISTOGRAM));
+            assert!(names.contains(&"spd_poller_cycle_duration_seconds_bucket"));
+            assert!(!names.contains(&"spd_poller_cycle_duration_seconds"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metric_name() {
+        let name = |id| metric_name(crate::model::METRIC_DESCRIPTORS.iter().find(|descriptor| descriptor.id == id).unwrap());
+        assert_eq!(name("cpu.utilization"), "spd_cpu_utilization_ratio");
+        assert_eq!(name("gpu.memory_utilization_percent"), "spd_gpu_memory_utilization_ratio");
+        assert_eq!(name("memory.utilization_mb"), "spd_memory_utilization_bytes");
+        assert_eq!(name("cpu.clock_speed"), "spd_cpu_clock_speed_hertz");
+        assert_eq!(name("poller.jitter"), "spd_poller_jitter_seconds");
+        assert_eq!(name("cpu.thermal_throttling"), "spd_cpu_thermal_throttling");
+    }
+
+    #[test]
+    fn test_only_listed_latencies_are_histograms() {
+        let kind = |id| metric_type(crate::model::MetricDescriptor::find(id).unwrap());
+        assert_eq!(kind("poller.cycle_duration"), MetricType::Histogram);
+        assert_eq!(kind("poller.jitter"), MetricType::Histogram);
+        assert_eq!(kind("clock.ntp_offset"), MetricType::Gauge);
+        assert_eq!(kind("storage.io_latency_p99"), MetricType::Gauge);
+        assert_eq!(kind("cpu.utilization"), MetricType::Gauge);
+        assert!(HISTOGRAM_METRICS.iter().all(|id| crate::model::MetricDescriptor::find(id).is_some()));
+    }
+
+    #[test]
+    fn test_histogram_counts_cumulatively() {
+        let mut histogram = Histogram::new();
+        for seconds in [0.0005, 0.004, 0.004, 3.0] {
+            histogram.observe(seconds);
+        }
+        assert_eq!(histogram.buckets[0], 1);
+        assert_eq!(histogram.buckets[2], 3);
+        assert_eq!(*histogram.buckets.last().unwrap(), 3);
+        assert_eq!(histogram.count, 4);
+
+        let mut latency = LatencyHistogram::default();
+        latency.add_slots(&[2, 0, 1]);
+        let histogram = Histogram::from_latency(&latency);
+        assert_eq!(histogram.bounds, vec![0.000002, 0.000004, 0.000008]);
+        assert_eq!(histogram.buckets, vec![2, 2, 3]);
+        assert_eq!(histogram.count, 3);
+    }
+}
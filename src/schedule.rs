@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use crate::alerts::{AlertEngine, AlertRule, TimeWindow};
+use crate::config::MIN_POLLING_INTERVAL_MS;
+use crate::model::{AppState, Sample};
+
+// When the headless collector delivers samples, for always-on machines that only
+// need data around the interesting moments. Empty, it records all the time:
+//
+//     [recording]
+//     windows = [{ start = "09:00", end = "17:30" }]
+//     triggers = [{ metric = "cpu.utilization", threshold = 50.0, duration_secs = 30 }]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingSchedule {
+    pub windows: Vec<TimeWindow>, // Local time windows, may wrap past midnight
+    pub triggers: Vec<AlertRule>, // Record while any holds; clear_threshold and duration_secs apply
+    pub linger_secs: u64,         // Keep recording this long after the last window or trigger ends
+    // Outside the schedule, still deliver one sample per metric this often instead of none
+    pub idle_sample_interval_secs: Option<u64>,
+}
+
+impl RecordingSchedule {
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty() && self.triggers.is_empty()
+    }
+}
+
+// Applies a RecordingSchedule to the samples of each poll
+#[derive(Debug, Default)]
+pub struct RecordingScheduler {
+    schedule: RecordingSchedule,
+    triggers: AlertEngine,
+    active_until: Option<DateTime<Utc>>,
+    idle_delivered: HashMap<&'static str, DateTime<Utc>>, // Last sample delivered per metric while idle
+}
+
+impl RecordingScheduler {
+    pub fn new(schedule: RecordingSchedule) -> Self {
+        Self { schedule, ..Self::default() }
+    }
+
+    // Re-evaluate after a poll; true while inside a window or a trigger holds
+    pub fn update(&mut self, state: &AppState, now: DateTime<Utc>) -> bool {
+        if self.schedule.is_empty() {
+            return true;
+        }
+        self.triggers.evaluate(state, &self.schedule.triggers, now);
+        let local_time = now.with_timezone(&Local).time();
+        let active = self.schedule.windows.iter().any(|window| window.contains(local_time))
+            || self.triggers.statuses().iter().any(|status| status.is_firing());
+        if active {
+            self.active_until = Some(now + chrono::Duration::seconds(self.schedule.linger_secs as i64));
+            self.idle_delivered.clear();
+        }
+        self.active_until.is_some_and(|until| now <= until)
+    }
+
+    // Whether a sample from the last poll should be delivered
+    pub fn admit(&mut self, sample: &Sample, recording: bool) -> bool {
+        if recording {
+            return true;
+        }
+        let Some(interval) = self.schedule.idle_sample_interval_secs else {
+            return false;
+        };
+        let due = self.idle_delivered.get(sample.metric_id).is_none_or(|last| {
+            (sample.timestamp - *last).num_seconds() >= interval as i64
+        });
+        if due {
+            self.idle_delivered.insert(sample.metric_id, sample.timestamp);
+        }
+        due
+    }
+}
+
+// Fast polling for a while after a trigger, over a slow baseline interval, to
+// capture transients without the overhead of polling fast all the time:
+//
+//     polling_interval_ms = 5000
+//     [burst]
+//     triggers = [{ metric = "cpu.utilization", threshold = 90.0 }]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BurstSampling {
+    pub triggers: Vec<AlertRule>, // Any holding starts or extends a burst
+    pub interval_ms: u64,         // Polling interval during a burst
+    pub duration_secs: u64,       // Burst length after the last trigger stops holding
+}
+
+impl Default for BurstSampling {
+    fn default() -> Self {
+        Self {
+            triggers: Vec::new(),
+            interval_ms: 100,
+            duration_secs: 30,
+        }
+    }
+}
+
+// Tracks burst triggers across polls
+#[derive(Debug, Default)]
+pub struct BurstController {
+    triggers: AlertEngine,
+    until: Option<DateTime<Utc>>,
+}
+
+impl BurstController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // End of the current burst, if one is running
+    pub fn until(&self) -> Option<DateTime<Utc>> {
+        self.until
+    }
+
+    // Polling interval for the next poll, or None to leave the baseline interval alone
+    pub fn update(&mut self, state: &AppState, burst: &BurstSampling, baseline_ms: u64, now: DateTime<Utc>) -> Option<u64> {
+        self.triggers.evaluate(state, &burst.triggers, now);
+        if self.triggers.statuses().iter().any(|status| status.is_firing()) {
+            self.until = Some(now + chrono::Duration::seconds(burst.duration_secs as i64));
+        }
+        match self.until {
+            Some(until) if now <= until => Some(burst.interval_ms.max(MIN_POLLING_INTERVAL_MS)),
+            Some(_) => {
+                self.until = None;
+                Some(baseline_ms)
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use crate::alerts::AlertSeverity;
+
+    fn sample(timestamp: DateTime<Utc>) -> Sample {
+        Sample { metric_id: "cpu.utilization", timestamp, value: 10.0 }
+    }
+
+    #[test]
+    fn test_trigger_starts_and_lingers() {
+        let mut trigger = AlertRule::new("cpu.utilization", 50.0, AlertSeverity::Warning);
+        trigger.duration_secs = 30;
+        let mut scheduler = RecordingScheduler::new(RecordingSchedule {
+            triggers: vec![trigger],
+            linger_secs: 10,
+            ..RecordingSchedule::default()
+        });
+        let mut state = AppState::new(1000);
+        let start = Utc::now();
+
+        state.cpu.utilization.update(80.0);
+        assert!(!scheduler.update(&state, start));
+        assert!(!scheduler.update(&state, start + Duration::seconds(20)));
+        assert!(scheduler.update(&state, start + Duration::seconds(30)));
+
+        // Load drops: recording continues for linger_secs, then stops
+        state.cpu.utilization.update(5.0);
+        assert!(scheduler.update(&state, start + Duration::seconds(35)));
+        assert!(!scheduler.update(&state, start + Duration::seconds(45)));
+    }
+
+    #[test]
+    fn test_idle_samples_thinned_or_dropped() {
+        let mut dropped = RecordingScheduler::new(RecordingSchedule {
+            windows: vec![TimeWindow { start: chrono::NaiveTime::MIN, end: chrono::NaiveTime::MIN }],
+            ..RecordingSchedule::default()
+        });
+        let start = Utc::now();
+        assert!(dropped.admit(&sample(start), true));
+        assert!(!dropped.admit(&sample(start), false));
+
+        let mut thinned = RecordingScheduler::new(RecordingSchedule {
+            idle_sample_interval_secs: Some(60),
+            ..RecordingSchedule::default()
+        });
+        let admitted: Vec<bool> = [0, 30, 60, 90, 130]
+            .iter()
+            .map(|secs| thinned.admit(&sample(start + Duration::seconds(*secs)), false))
+            .collect();
+        assert_eq!(admitted, vec![true, false, true, false, true]);
+
+        // An empty schedule always records
+        assert!(RecordingScheduler::new(RecordingSchedule::default()).update(&AppState::new(1000), start));
+    }
+
+    #[test]
+    fn test_burst_switches_interval_and_back() {
+        let burst = BurstSampling {
+            triggers: vec![AlertRule::new("cpu.package_temperature", 80.0, AlertSeverity::Warning)],
+            interval_ms: 100,
+            duration_secs: 10,
+        };
+        let mut controller = BurstController::new();
+        let mut state = AppState::new(5000);
+        let start = Utc::now();
+
+        state.cpu.package_temperature.update(60.0);
+        assert_eq!(controller.update(&state, &burst, 5000, start), None);
+        state.cpu.package_temperature.update(85.0);
+        assert_eq!(controller.update(&state, &burst, 5000, start + Duration::seconds(5)), Some(100));
+
+        // Runs duration_secs past the last poll the trigger held, then restores the baseline once
+        state.cpu.package_temperature.update(70.0);
+        assert_eq!(controller.update(&state, &burst, 5000, start + Duration::seconds(10)), Some(100));
+        assert_eq!(controller.until(), Some(start + Duration::seconds(15)));
+        assert_eq!(controller.update(&state, &burst, 5000, start + Duration::seconds(16)), Some(5000));
+        assert_eq!(controller.update(&state, &burst, 5000, start + Duration::seconds(17)), None);
+    }
+}
@@ -0,0 +1,284 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use chrono::Utc;
+use crate::logger;
+use crate::model::{AppState, ScriptAlert};
+#[cfg(feature = "scripting")]
+use crate::model::METRIC_DESCRIPTORS;
+
+// Scripts live next to the config
+const SCRIPT_DIR: &str = "scripts";
+
+// Script files are picked up by this extension
+pub const SCRIPT_EXTENSION: &str = "rhai";
+
+// Bumped by "Reload scripts"; the poller reloads when it sees a new value
+static RELOAD_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub fn default_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(crate::paths::config_dir()?.join(SCRIPT_DIR))
+}
+
+pub fn request_reload() {
+    RELOAD_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn reload_generation() -> u64 {
+    RELOAD_GENERATION.load(Ordering::Relaxed)
+}
+
+// Script files in a directory, in name order so derived metrics keep a stable order
+pub fn script_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == SCRIPT_EXTENSION))
+        .collect();
+    files.sort();
+    files
+}
+
+// Results of running every script once
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptRun {
+    pub metrics: Vec<(String, f64)>,
+    pub alerts: Vec<ScriptAlert>,
+    pub reports: Vec<(String, String)>,
+    pub errors: Vec<String>,
+}
+
+// Store a run in the state: derived metrics get a sample each, and newly raised
+// alerts are logged once rather than on every poll
+pub fn apply(state: &mut AppState, run: ScriptRun) {
+    let now = Utc::now();
+    for (name, value) in run.metrics {
+        state.scripts.metrics.entry(name).or_default().record_at(now, value);
+    }
+    for alert in &run.alerts {
+        if !state.scripts.alerts.contains(alert) {
+            logger::log_warning(&format!("Script alert from {}: {}", alert.script, alert.message));
+        }
+    }
+    for error in &run.errors {
+        if !state.scripts.errors.contains(error) {
+            logger::log_warning(&format!("Script error: {}", error));
+        }
+    }
+    state.scripts.alerts = run.alerts;
+    state.scripts.reports = run.reports;
+    state.scripts.errors = run.errors;
+}
+
+// Runs the user's scripts against the latest readings. A script may define any of:
+//
+//     fn derived(m) { #{ "GPU-CPU delta": m["gpu.package_temperature"] - m["cpu.package_temperature"] } }
+//     fn alert(m) { if m["gpu.hotspot_temperature"] > 95.0 { "GPU hotspot over 95°C" } }
+//     fn report(m) { `Peak GPU temperature: ${m["gpu.package_temperature.max"]}°C` }
+//
+// where m maps each metric id to its current value, and "<id>.min"/"<id>.max" to its
+// session extremes. Each call is limited in operations, so a runaway loop stops
+// with an error rather than stalling the poller
+#[cfg(feature = "scripting")]
+pub struct ScriptHost {
+    engine: rhai::Engine,
+    scripts: Vec<(String, rhai::AST)>,
+    load_errors: Vec<String>,
+}
+
+#[cfg(feature = "scripting")]
+impl Default for ScriptHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "scripting")]
+impl ScriptHost {
+    const MAX_OPERATIONS: u64 = 100_000;
+
+    pub fn new() -> Self {
+        let mut engine = rhai::Engine::new();
+        engine.set_max_operations(Self::MAX_OPERATIONS);
+        engine.set_max_call_levels(32);
+        engine.on_print(|text| logger::log_info(&format!("Script: {}", text)));
+        Self { engine, scripts: Vec::new(), load_errors: Vec::new() }
+    }
+
+    // Replace the loaded scripts with those in dir; a script that doesn't compile is
+    // reported and left out
+    pub fn load_dir(&mut self, dir: &Path) {
+        self.scripts.clear();
+        self.load_errors.clear();
+        for path in script_files(dir) {
+            let name = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+            match fs::read_to_string(&path) {
+                Ok(source) => self.load(&name, &source),
+                Err(e) => self.load_errors.push(format!("{}: {}", name, e)),
+            }
+        }
+        if !self.scripts.is_empty() {
+            logger::log_info(&format!("Loaded {} scripts from {}", self.scripts.len(), dir.display()));
+        }
+    }
+
+    pub fn load(&mut self, name: &str, source: &str) {
+        match self.engine.compile(source) {
+            Ok(ast) => self.scripts.push((name.to_string(), ast)),
+            Err(e) => self.load_errors.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    pub fn loaded(&self) -> Vec<String> {
+        self.scripts.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    pub fn run(&self, state: &AppState) -> ScriptRun {
+        let mut run = ScriptRun { errors: self.load_errors.clone(), ..ScriptRun::default() };
+        if self.scripts.is_empty() {
+            return run;
+        }
+        let metrics = readings(state);
+        for (name, ast) in &self.scripts {
+            if let Err(e) = self.run_script(name, ast, &metrics, &mut run) {
+                run.errors.push(format!("{}: {}", name, e));
+            }
+        }
+        run
+    }
+
+    fn run_script(&self, name: &str, ast: &rhai::AST, metrics: &rhai::Map, run: &mut ScriptRun) -> Result<(), Box<dyn std::error::Error>> {
+        let defines = |function: &str| ast.iter_functions().any(|metadata| metadata.name == function && metadata.params.len() == 1);
+        let call = |function: &str| -> Result<rhai::Dynamic, Box<rhai::EvalAltResult>> {
+            // Top-level statements run once per call otherwise
+            let options = rhai::CallFnOptions::new().eval_ast(false);
+            self.engine.call_fn_with_options(options, &mut rhai::Scope::new(), ast, function, (metrics.clone(),))
+        };
+
+        if defines("derived") {
+            let derived = call("derived")?;
+            let derived = derived.try_cast::<rhai::Map>().ok_or("derived() must return a map of name to number")?;
+            for (metric, value) in derived {
+                let value = value
+                    .as_float()
+                    .or_else(|_| value.as_int().map(|value| value as f64))
+                    .map_err(|_| format!("derived metric \"{}\" is not a number", metric))?;
+                run.metrics.push((metric.to_string(), value));
+            }
+        }
+        if defines("alert") {
+            let raised = call("alert")?;
+            let messages = if raised.is_unit() {
+                Vec::new()
+            } else if raised.is_array() {
+                raised.into_array()?.into_iter().map(|message| message.to_string()).collect()
+            } else {
+                vec![raised.to_string()]
+            };
+            for message in messages {
+                run.alerts.push(ScriptAlert { script: name.to_string(), message });
+            }
+        }
+        if defines("report") {
+            let report = call("report")?;
+            if !report.is_unit() {
+                run.reports.push((name.to_string(), report.to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Current values and session extremes of every metric that has a reading
+#[cfg(feature = "scripting")]
+fn readings(state: &AppState) -> rhai::Map {
+    let mut metrics = rhai::Map::new();
+    for descriptor in METRIC_DESCRIPTORS {
+        let Some(current) = state.current_value(descriptor.id) else {
+            continue;
+        };
+        metrics.insert(descriptor.id.into(), rhai::Dynamic::from_float(current));
+        if let Some(metric) = state.metric(descriptor.id) {
+            for (suffix, extreme) in [("min", metric.session_min), ("max", metric.session_max)] {
+                if let Some(extreme) = extreme {
+                    metrics.insert(format!("{}.{}", descriptor.id, suffix).into(), rhai::Dynamic::from_float(extreme));
+                }
+            }
+        }
+    }
+    metrics
+}
+
+// Without the scripting feature, scripts are found but never run
+#[cfg(not(feature = "scripting"))]
+#[derive(Default)]
+pub struct ScriptHost {
+    load_errors: Vec<String>,
+}
+
+#[cfg(not(feature = "scripting"))]
+impl ScriptHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load_dir(&mut self, dir: &Path) {
+        self.load_errors = script_files(dir)
+            .iter()
+            .map(|path| format!("{}: this build was compiled without scripting", path.display()))
+            .collect();
+    }
+
+    pub fn load(&mut self, name: &str, _source: &str) {
+        self.load_errors.push(format!("{}: this build was compiled without scripting", name));
+    }
+
+    pub fn loaded(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    pub fn run(&self, _state: &AppState) -> ScriptRun {
+        ScriptRun { errors: self.load_errors.clone(), ..ScriptRun::default() }
+    }
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_derive_metrics_raise_alerts_and_report() {
+        let mut state = AppState::new(1000);
+        state.cpu.package_temperature.update(60.0);
+        state.gpu.package_temperature.update(70.0);
+        state.gpu.package_temperature.update(75.0);
+
+        let mut host = ScriptHost::new();
+        host.load("thermals.rhai", r#"
+            fn derived(m) { #{ "GPU-CPU delta": m["gpu.package_temperature"] - m["cpu.package_temperature"], "count": 2 } }
+            fn alert(m) { if m["gpu.package_temperature.max"] > 72.0 { "GPU ran hot" } }
+            fn report(m) { `Peak GPU ${m["gpu.package_temperature.max"]}` }
+        "#);
+        host.load("quiet.rhai", "fn alert(m) { }");
+        host.load("runaway.rhai", "fn derived(m) { loop { } }");
+        host.load("broken.rhai", "fn derived(m) {");
+        assert_eq!(host.loaded(), vec!["thermals.rhai", "quiet.rhai", "runaway.rhai"]);
+
+        let run = host.run(&state);
+        assert_eq!(run.metrics, vec![("GPU-CPU delta".to_string(), 15.0), ("count".to_string(), 2.0)]);
+        assert_eq!(run.alerts, vec![ScriptAlert { script: "thermals.rhai".to_string(), message: "GPU ran hot".to_string() }]);
+        assert_eq!(run.reports, vec![("thermals.rhai".to_string(), "Peak GPU 75.0".to_string())]);
+        // The compile error, then the loop stopped by the operation limit
+        assert_eq!(run.errors.len(), 2);
+        assert!(run.errors[0].starts_with("broken.rhai: "));
+        assert!(run.errors[1].starts_with("runaway.rhai: "));
+
+        apply(&mut state, run.clone());
+        apply(&mut state, run);
+        assert_eq!(state.scripts.metrics["GPU-CPU delta"].history.len(), 2);
+        assert_eq!(state.scripts.alerts.len(), 1);
+    }
+}
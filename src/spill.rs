@@ -0,0 +1,151 @@
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+
+// Each spilled sample is a millisecond timestamp followed by its value, little endian
+const RECORD_BYTES: usize = 16;
+
+// Spill directories live next to the config and log file
+const SPILL_DIR: &str = "history_spill";
+
+// Process id of the session writing a spill directory, so another running instance
+// leaves it alone
+const OWNER_FILE: &str = "owner.pid";
+
+// On-disk store for the oldest part of each metric's history once the in-memory
+// budget is exceeded. One append-only file per series, in the order samples were spilled
+#[derive(Debug, Clone)]
+pub struct HistorySpill {
+    dir: PathBuf,
+}
+
+impl HistorySpill {
+    pub fn new(dir: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    // Spill store for this session, removing any left behind by earlier sessions that
+    // have exited. Ones still written by a running instance (e.g. --new-instance) are kept
+    pub fn for_session(session_start: DateTime<Utc>) -> Result<Self, Box<dyn std::error::Error>> {
+        let root = crate::paths::data_dir()?.join(SPILL_DIR);
+        let name = format!("session_{}_{}", session_start.format("%Y%m%d_%H%M%S"), std::process::id());
+        remove_abandoned(&root, &name, owner_running);
+        let spill = Self::new(root.join(name))?;
+        fs::write(spill.dir.join(OWNER_FILE), std::process::id().to_string())?;
+        Ok(spill)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn append(&self, key: &str, samples: &[(DateTime<Utc>, f64)]) -> std::io::Result<()> {
+        let mut bytes = Vec::with_capacity(samples.len() * RECORD_BYTES);
+        for (timestamp, value) in samples {
+            bytes.extend_from_slice(&timestamp.timestamp_millis().to_le_bytes());
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(self.path(key))?;
+        file.write_all(&bytes)
+    }
+
+    // Every spilled sample of a series, oldest first; empty if nothing was spilled
+    pub fn load(&self, key: &str) -> std::io::Result<Vec<(DateTime<Utc>, f64)>> {
+        let mut bytes = Vec::new();
+        match fs::File::open(self.path(key)) {
+            Ok(mut file) => file.read_to_end(&mut bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        // A partial record from an interrupted write is ignored
+        Ok(bytes
+            .chunks_exact(RECORD_BYTES)
+            .filter_map(|record| {
+                let millis = i64::from_le_bytes(record[..8].try_into().ok()?);
+                let value = f64::from_le_bytes(record[8..].try_into().ok()?);
+                Some((DateTime::from_timestamp_millis(millis)?, value))
+            })
+            .collect())
+    }
+
+    // Series keys include sensor names, so anything unsafe in a file name is replaced
+    fn path(&self, key: &str) -> PathBuf {
+        let name: String = key
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.bin", name))
+    }
+}
+
+// Spill directories under root other than `current` whose owner isn't running. A
+// directory without an owner file predates them and is removed as well
+fn remove_abandoned(root: &Path, current: &str, running: impl Fn(u32) -> bool) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+    for entry in entries.flatten().filter(|entry| entry.file_name() != current) {
+        let owner = fs::read_to_string(entry.path().join(OWNER_FILE))
+            .ok()
+            .and_then(|pid| pid.trim().parse().ok());
+        if owner.is_none_or(|pid| !running(pid)) {
+            let _ = fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+fn owner_running(pid: u32) -> bool {
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spill_round_trip() {
+        let dir = std::env::temp_dir().join("spd_spill_round_trip_test");
+        let _ = fs::remove_dir_all(&dir);
+        let spill = HistorySpill::new(dir.clone()).expect("Failed to create spill dir");
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let at = |seconds: i64| start + chrono::Duration::seconds(seconds);
+
+        assert!(spill.load("cpu.utilization").unwrap().is_empty());
+        spill.append("cpu.utilization", &[(at(0), 10.5), (at(1), 20.0)]).unwrap();
+        spill.append("cpu.utilization", &[(at(2), 30.25)]).unwrap();
+        spill.append("cpu.sensors.Core 0/Tdie", &[(at(0), 55.0)]).unwrap();
+
+        assert_eq!(spill.load("cpu.utilization").unwrap(), vec![(at(0), 10.5), (at(1), 20.0), (at(2), 30.25)]);
+        assert_eq!(spill.load("cpu.sensors.Core 0/Tdie").unwrap(), vec![(at(0), 55.0)]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_only_abandoned_spill_dirs_are_removed() {
+        let root = std::env::temp_dir().join("spd_spill_cleanup_test");
+        let _ = fs::remove_dir_all(&root);
+        let session = |name: &str, owner: Option<u32>| {
+            let dir = root.join(name);
+            fs::create_dir_all(&dir).unwrap();
+            if let Some(pid) = owner {
+                fs::write(dir.join(OWNER_FILE), pid.to_string()).unwrap();
+            }
+        };
+        session("current", Some(1));
+        session("running", Some(2));
+        session("exited", Some(3));
+        session("unowned", None);
+
+        remove_abandoned(&root, "current", |pid| pid <= 2);
+        let mut left: Vec<_> = fs::read_dir(&root).unwrap().flatten().map(|entry| entry.file_name()).collect();
+        left.sort();
+        assert_eq!(left, vec!["current", "running"]);
+        assert!(owner_running(std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
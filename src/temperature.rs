@@ -0,0 +1,119 @@
+use crate::model::TemperatureSensors;
+
+// Readings outside this range are placeholders rather than temperatures: hwmon drivers
+// report e.g. -128 or -273.15 for a sensor that is wired up but absent
+pub const MIN_PLAUSIBLE_CELSIUS: f32 = -40.0;
+pub const MAX_PLAUSIBLE_CELSIUS: f32 = 150.0;
+
+// Offsets AMD adds to Tctl for fan control on these CPUs, by model name prefix, as
+// k10temp subtracts them; later CPUs report Tctl equal to the die temperature
+const TCTL_OFFSETS: &[(&str, f32)] = &[
+    ("AMD Ryzen 5 1600X", 20.0),
+    ("AMD Ryzen 7 1700X", 20.0),
+    ("AMD Ryzen 7 1800X", 20.0),
+    ("AMD Ryzen 7 2700X", 10.0),
+    ("AMD Ryzen Threadripper 19", 27.0),
+    ("AMD Ryzen Threadripper 29", 27.0),
+];
+
+pub fn is_plausible(celsius: f32) -> bool {
+    celsius.is_finite() && (MIN_PLAUSIBLE_CELSIUS..=MAX_PLAUSIBLE_CELSIUS).contains(&celsius)
+}
+
+pub fn tctl_offset(cpu_name: &str) -> f32 {
+    TCTL_OFFSETS
+        .iter()
+        .find(|(prefix, _)| cpu_name.trim().starts_with(prefix))
+        .map_or(0.0, |(_, offset)| *offset)
+}
+
+// A device temperature comparable across backends, worked out from its raw sensors,
+// which stay as they are in TemperatureSensors
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedTemperature {
+    pub celsius: f64,
+    pub detail: String, // Which sensor it came from and what was done to it, for the source tooltip
+}
+
+fn current(sensors: &TemperatureSensors, keyword: &str) -> Option<(String, f64)> {
+    sensors
+        .iter()
+        .find(|(name, _)| name.to_lowercase().contains(keyword))
+        .and_then(|(name, metric)| Some((name.clone(), metric.current?)))
+}
+
+// AMD die temperature: Tdie where the driver reports it, otherwise Tctl less the
+// model's offset. None when neither is present, e.g. on Intel
+pub fn cpu_temperature(sensors: &TemperatureSensors, cpu_name: Option<&str>) -> Option<NormalizedTemperature> {
+    if let Some((name, celsius)) = current(sensors, "tdie") {
+        return Some(NormalizedTemperature { celsius, detail: format!("sensor \"{}\"", name) });
+    }
+    let (name, celsius) = current(sensors, "tctl")?;
+    let offset = cpu_name.map_or(0.0, tctl_offset);
+    let detail = if offset > 0.0 {
+        format!("sensor \"{}\" {:.1}°C less its {:.0}°C offset", name, celsius, offset)
+    } else {
+        format!("sensor \"{}\"", name)
+    };
+    Some(NormalizedTemperature { celsius: celsius - offset as f64, detail })
+}
+
+// NVMe drives report a Composite temperature, the one their thermal limits apply to,
+// plus optional "Sensor 1/2" readings of single components. Composite where present,
+// otherwise the hottest sensor
+pub fn storage_temperature(sensors: &TemperatureSensors) -> Option<NormalizedTemperature> {
+    if let Some((name, celsius)) = current(sensors, "composite") {
+        return Some(NormalizedTemperature { celsius, detail: format!("sensor \"{}\"", name) });
+    }
+    sensors
+        .iter()
+        .filter_map(|(name, metric)| Some((name, metric.current?)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(name, celsius)| NormalizedTemperature { celsius, detail: format!("hottest sensor \"{}\"", name) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sensors(readings: &[(&str, f32)]) -> TemperatureSensors {
+        let mut sensors = TemperatureSensors::default();
+        for (name, celsius) in readings {
+            sensors.update(name, *celsius);
+        }
+        sensors
+    }
+
+    #[test]
+    fn test_placeholders_are_not_temperatures() {
+        assert!(is_plausible(45.0));
+        assert!(!is_plausible(-128.0));
+        assert!(!is_plausible(-273.15));
+        assert!(!is_plausible(255.0));
+        assert!(!is_plausible(f32::NAN));
+    }
+
+    #[test]
+    fn test_cpu_temperature_removes_tctl_offset() {
+        let tctl_only = sensors(&[("k10temp Tctl", 72.0), ("k10temp Tccd1", 60.0)]);
+        let normalized = cpu_temperature(&tctl_only, Some("AMD Ryzen 7 1700X Eight-Core Processor")).unwrap();
+        assert_eq!(normalized.celsius, 52.0);
+        assert!(normalized.detail.contains("20°C offset"));
+
+        // No offset on later parts, and Tdie wins whenever the driver reports it
+        assert_eq!(cpu_temperature(&tctl_only, Some("AMD Ryzen 9 7950X")).unwrap().celsius, 72.0);
+        let both = sensors(&[("k10temp Tctl", 72.0), ("k10temp Tdie", 52.5)]);
+        assert_eq!(cpu_temperature(&both, Some("AMD Ryzen 7 1700X")).unwrap().celsius, 52.5);
+        assert!(cpu_temperature(&sensors(&[("coretemp Package id 0", 50.0)]), None).is_none());
+    }
+
+    #[test]
+    fn test_storage_temperature_prefers_composite() {
+        let nvme = sensors(&[("nvme Sensor 1", 48.0), ("nvme Composite", 41.0), ("nvme Sensor 2", 55.0)]);
+        assert_eq!(storage_temperature(&nvme).unwrap().celsius, 41.0);
+
+        let sata = sensors(&[("drivetemp sda", 35.0), ("drivetemp sdb", 38.0)]);
+        assert_eq!(storage_temperature(&sata).unwrap().celsius, 38.0);
+        assert!(storage_temperature(&TemperatureSensors::default()).is_none());
+    }
+}
@@ -1,7 +1,30 @@
+use std::collections::{BTreeMap, HashMap};
+use serde::{Deserialize, Serialize};
 use eframe::egui;
-use egui_plot::{Line, Plot, PlotPoints, Corner, CoordinatesFormatter};
+use egui_plot::{AxisHints, HPlacement, Line, Plot, PlotPoints, Corner, CoordinatesFormatter};
 use egui::CollapsingHeader;
-use crate::model::{SharedAppState, MetricValue, ToF64};
+use crate::model::{self, AppState, BatteryStatus, DeviceKind, DeviceNode, EnergyTotal, EventMetric, SharedAppState, MetricValue, MetricDescriptor, DisplayedMetric, MetricSource, MonitorSelection, PoolKind, TemperatureSensors, ToF64, ENERGY_METRICS, METRIC_DESCRIPTORS};
+use crate::config::{AppConfig, DashboardConfig, DualAxisChart, SharedConfig, Theme, MIN_UI_SCALE, MAX_UI_SCALE, MIN_POLLING_INTERVAL_MS, MAX_POLLING_INTERVAL_MS, MIN_PLOT_REFRESH_HZ, MAX_PLOT_REFRESH_HZ};
+use crate::alerts::{AlertRule, AlertSeverity, ALERT_TEMPLATES};
+use crate::import::{self, ImportedSession};
+use crate::export;
+use crate::anonymize;
+use crate::update::{self, Release};
+use crate::logger;
+use crate::monitors::DEFAULT_MONITOR_BUDGET;
+use crate::power::SleepInhibitor;
+use crate::crash;
+use crate::instance;
+use crate::baseline::{SessionFilter, SessionSummary, MIN_SESSION_SECONDS};
+use crate::regression::{self, Difference};
+use crate::query;
+use crate::lm_sensors::{self, SensorReading};
+
+// Points the spilled part of a history is averaged down to when plotted
+const SPILLED_PLOT_POINTS: usize = 2000;
+
+// How often the live session summary is recomputed from the histories
+const SUMMARY_REFRESH: std::time::Duration = std::time::Duration::from_secs(5);
 
 // Helper function to interpolate data value at a given time position
 pub fn interpolate_data_value(data: &[(f64, f64)], target_time: f64) -> Option<f64> {
@@ -52,484 +75,3691 @@ pub fn interpolate_data_value(data: &[(f64, f64)], target_time: f64) -> Option<f
     }
 }
 
-pub struct PerformanceApp {
-    state: SharedAppState,
+// Samples and size of the sparklines in the pinned metrics bar
+const SPARKLINE_SAMPLES: usize = 120;
+const SPARKLINE_SIZE: [f32; 2] = [120.0, 28.0];
+
+// Narrowest a metric section gets before the layout drops a column, and the most
+// columns an ultrawide window is split into
+const MIN_COLUMN_WIDTH: f32 = 360.0;
+const MAX_COLUMNS: usize = 4;
+
+// How often the status bar's samples/s figure is recomputed
+const STATUS_RATE_PERIOD: std::time::Duration = std::time::Duration::from_secs(1);
+
+// Line colors offered by a metric's context menu
+const PLOT_COLORS: &[(&str, [u8; 3])] = &[
+    ("Blue", [90, 150, 220]),
+    ("Green", [100, 200, 100]),
+    ("Yellow", [230, 200, 80]),
+    ("Orange", [220, 120, 60]),
+    ("Red", [220, 80, 80]),
+    ("Purple", [170, 110, 220]),
+];
+
+// Imported series drawn behind the live data: plot points and (min, max)
+type PlotOverlay = (Vec<(f64, f64)>, (f64, f64));
+
+// Metrics plotted with the spread across units drawn around them: (metric, lowest, highest)
+const METRIC_SPREADS: &[(&str, &str, &str)] = &[
+    ("cpu.clock_speed", "cpu.clock_speed_min", "cpu.clock_speed_max"),
+];
+
+// On/off metric: current state, how often and how long it was active, and a stepped
+// plot of its transitions. Returns the title, which carries the metric menu
+fn render_event_section(
+    ui: &mut egui::Ui,
+    descriptor: &DisplayedMetric,
+    event: &EventMetric,
+    session_start: chrono::DateTime<chrono::Utc>,
+    color: Option<egui::Color32>,
+) -> egui::Response {
+    ui.group(|ui| {
+        let title = ui.add(egui::Label::new(egui::RichText::new(&descriptor.label).heading()).sense(egui::Sense::click()));
+        ui.horizontal(|ui| {
+            let current = event.active.map_or_else(|| "N/A".to_string(), |active| descriptor.format_value(active.to_f64()));
+            ui.label(format!("Current: {}", current));
+            ui.separator();
+            ui.label(format!("Episodes: {}", event.activations));
+            ui.label(format!("Active for: {}s", event.active_duration().num_seconds()));
+        });
+        render_metric_plot(
+            ui,
+            &descriptor.label,
+            &descriptor.unit,
+            event.step_plot_data(session_start),
+            Some((0.0, 1.0)),
+            session_start,
+            60.0,
+            None,
+            None,
+            color,
+        );
+        title
+    }).inner
 }
 
-impl PerformanceApp {
-    pub fn new(state: SharedAppState) -> Self {
-        Self { state }
+// Recent history without axes or interaction, for the pinned metrics bar
+fn render_sparkline(ui: &mut egui::Ui, metric_id: &str, points: Vec<(f64, f64)>, color: Option<egui::Color32>) {
+    let points: PlotPoints = points.into_iter().map(|(x, y)| [x, y]).collect();
+    Plot::new(("sparkline", metric_id))
+        .width(SPARKLINE_SIZE[0])
+        .height(SPARKLINE_SIZE[1])
+        .show_axes(false)
+        .show_grid(false)
+        .show_x(false)
+        .show_y(false)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .allow_boxed_zoom(false)
+        .allow_double_click_reset(false)
+        .show(ui, |plot_ui| {
+            let line = Line::new(points);
+            plot_ui.line(match color {
+                Some(color) => line.color(color),
+                None => line,
+            });
+        });
+}
+
+// Lowest and highest unit lines drawn either side of an average
+struct PlotSpread {
+    low: Vec<(f64, f64)>,
+    high: Vec<(f64, f64)>,
+    bounds: (f64, f64),
+    current: (Option<f64>, Option<f64>),
+}
+
+fn plot_spread(state: &AppState, metric_id: &str) -> Option<PlotSpread> {
+    let (_, low_id, high_id) = METRIC_SPREADS.iter().find(|(id, _, _)| *id == metric_id)?;
+    let (low, high) = (state.metric(low_id)?, state.metric(high_id)?);
+    Some(PlotSpread {
+        low: low.plot_data(state.session_start),
+        high: high.plot_data(state.session_start),
+        bounds: (low.session_min?, high.session_max?),
+        current: (low.current, high.current),
+    })
+}
+
+// Draw a metric's history with Y bounds fitted to the session min/max, optionally
+// overlaying an imported recording aligned to the same elapsed-time axis
+#[allow(clippy::too_many_arguments)]
+fn render_metric_plot(
+    ui: &mut egui::Ui,
+    title: &str,
+    unit: &str,
+    plot_data: Vec<(f64, f64)>,
+    bounds: Option<(f64, f64)>,
+    session_start: chrono::DateTime<chrono::Utc>,
+    height: f32,
+    overlay: Option<PlotOverlay>,
+    spread: Option<PlotSpread>,
+    color: Option<egui::Color32>,
+) {
+    let mut elapsed_seconds = model::elapsed_seconds(session_start, chrono::Utc::now());
+    let mut bounds = bounds;
+    
+    // No more points than the plot has pixels to show them
+    let columns = ui.available_width().max(1.0) as usize;
+    let plot_data = decimate_min_max(&plot_data, columns);
+    let overlay = overlay.map(|(data, bounds)| (decimate_min_max(&data, columns), bounds));
+    let spread = spread.map(|spread| PlotSpread {
+        low: decimate_min_max(&spread.low, columns),
+        high: decimate_min_max(&spread.high, columns),
+        ..spread
+    });
+    if let Some(spread) = &spread {
+        bounds = Some(match bounds {
+            Some((min_val, max_val)) => (min_val.min(spread.bounds.0), max_val.max(spread.bounds.1)),
+            None => spread.bounds,
+        });
+    }
+    if let Some((overlay_data, (overlay_min, overlay_max))) = &overlay {
+        if let Some(&(last_time, _)) = overlay_data.last() {
+            elapsed_seconds = elapsed_seconds.max(last_time);
+        }
+        bounds = Some(match bounds {
+            Some((min_val, max_val)) => (min_val.min(*overlay_min), max_val.max(*overlay_max)),
+            None => (*overlay_min, *overlay_max),
+        });
     }
     
-    fn render_metric_section<T>(
-        &self,
-        ui: &mut egui::Ui,
-        title: &str,
-        metric: &MetricValue<T>,
-        unit: &str,
-        format_fn: impl Fn(&T) -> String,
-        session_start: chrono::DateTime<chrono::Utc>,
-    ) where
-        T: ToF64 + Clone,
-    {
-        ui.group(|ui| {
-            ui.label(egui::RichText::new(title).heading());
-            
-            ui.horizontal(|ui| {
-                // Current value
-                if let Some(ref current) = metric.current {
-                    ui.label(format!("Current: {}{}", format_fn(current), unit));
+    // Calculate Y-axis bounds from session min/max values
+    let (y_min, y_max) = if let Some((min_val, max_val)) = bounds {
+        // Add 5% padding to bounds for better visualization
+        let padding = (max_val - min_val) * 0.05;
+        (min_val - padding, max_val + padding)
+    } else {
+        // Default bounds when no data available
+        (0.0, 100.0)
+    };
+    
+    Plot::new(format!("{}_plot", title))
+        .height(height)
+        .label_formatter(|_name, _value| String::new())
+        .coordinates_formatter(Corner::LeftBottom, CoordinatesFormatter::new({
+            let plot_data_clone = plot_data.clone();
+            let title = title.to_string();
+            let unit = unit.to_string();
+            move |point, _bounds| {
+                if point.x >= 0.0 && !plot_data_clone.is_empty() {
+                    // Find the actual data value at the cursor time position
+                    let cursor_time = point.x;
+                    let interpolated_value = interpolate_data_value(&plot_data_clone, cursor_time);
+                    
+                    if let Some(value) = interpolated_value {
+                        format!("Time: {:.1}s, {}: {:.1}{}", 
+                            cursor_time, 
+                            title,
+                            value, 
+                            unit
+                        )
+                    } else {
+                        String::new()
+                    }
                 } else {
-                    ui.label("Current: N/A");
+                    String::new()
                 }
-                
-                ui.separator();
-                
-                // Session min/max
-                if let (Some(ref min), Some(ref max)) = (&metric.session_min, &metric.session_max) {
-                    ui.label(format!("Min: {}{}", format_fn(min), unit));
-                    ui.label(format!("Max: {}{}", format_fn(max), unit));
-                } else {
-                    ui.label("Min: N/A");
-                    ui.label("Max: N/A");
+            }
+        }))
+        .show(ui, |plot_ui| {
+            if let Some((overlay_data, _)) = overlay {
+                let points: PlotPoints = overlay_data.into_iter().map(|(x, y)| [x, y]).collect();
+                plot_ui.line(Line::new(points).color(egui::Color32::GRAY).name("Imported"));
+            }
+            if let Some(spread) = spread {
+                let low: PlotPoints = spread.low.into_iter().map(|(x, y)| [x, y]).collect();
+                let high: PlotPoints = spread.high.into_iter().map(|(x, y)| [x, y]).collect();
+                plot_ui.line(Line::new(high).color(egui::Color32::from_rgb(220, 120, 60)).name("Max"));
+                plot_ui.line(Line::new(low).color(egui::Color32::from_rgb(90, 150, 220)).name("Min"));
+            }
+            if !plot_data.is_empty() {
+                let points: PlotPoints = plot_data.into_iter().map(|(x, y)| [x, y]).collect();
+                let mut line = Line::new(points);
+                if let Some(color) = color {
+                    line = line.color(color);
                 }
-            });
-            
-            // Plot - always show, even if no data
-            let plot_data = metric.get_plot_data(session_start);
-            let elapsed_seconds = (chrono::Utc::now() - session_start).num_seconds() as f64;
-            
-            // Calculate Y-axis bounds from session min/max values
-            let (y_min, y_max) = if let (Some(ref min), Some(ref max)) = (&metric.session_min, &metric.session_max) {
-                let min_val = min.to_f64();
-                let max_val = max.to_f64();
-                // Add 5% padding to bounds for better visualization
-                let padding = (max_val - min_val) * 0.05;
-                (min_val - padding, max_val + padding)
-            } else {
-                // Default bounds when no data available
-                (0.0, 100.0)
-            };
-            
-            Plot::new(format!("{}_plot", title))
-                .height(100.0)
-                .label_formatter(|_name, _value| String::new())
-                .coordinates_formatter(Corner::LeftBottom, CoordinatesFormatter::new({
-                    let plot_data_clone = plot_data.clone();
-                    move |point, _bounds| {
-                        if point.x >= 0.0 && !plot_data_clone.is_empty() {
-                            // Find the actual data value at the cursor time position
-                            let cursor_time = point.x;
-                            let interpolated_value = interpolate_data_value(&plot_data_clone, cursor_time);
-                            
-                            if let Some(value) = interpolated_value {
-                                format!("Time: {:.1}s, {}: {:.1}{}", 
-                                    cursor_time, 
-                                    title,
-                                    value, 
-                                    unit
-                                )
-                            } else {
-                                String::new()
-                            }
-                        } else {
-                            String::new()
-                        }
-                    }
-                }))
-                .show(ui, |plot_ui| {
-                    if !plot_data.is_empty() {
-                        let points: PlotPoints = plot_data.into_iter().map(|(x, y)| [x, y]).collect();
-                        let line = Line::new(points);
-                        plot_ui.line(line);
-                    }
-                    // Set bounds: X-axis from 0 to elapsed time, Y-axis to session min/max
-                    plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
-                        [0.0, y_min], 
-                        [elapsed_seconds.max(1.0), y_max]
-                    ));
-                });
+                plot_ui.line(line);
+            }
+            // Set bounds: X-axis from 0 to elapsed time, Y-axis to session min/max
+            plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
+                [0.0, y_min], 
+                [elapsed_seconds.max(1.0), y_max]
+            ));
         });
+}
+
+// Reduce a time-ordered series to at most two points per pixel column: the lowest and
+// highest in the order they occurred. A full-session view then keeps every spike without
+// building hundreds of thousands of PlotPoints per frame
+pub fn decimate_min_max(points: &[(f64, f64)], columns: usize) -> Vec<(f64, f64)> {
+    let (Some(&(start, _)), Some(&(end, _))) = (points.first(), points.last()) else {
+        return Vec::new();
+    };
+    if columns == 0 || points.len() <= columns * 2 || end <= start {
+        return points.to_vec();
     }
+    let column_width = (end - start) / columns as f64;
+    let mut decimated = Vec::with_capacity(columns * 2);
+    let mut flush = |low: (f64, f64), high: (f64, f64)| {
+        if low == high {
+            decimated.push(low);
+        } else if low.0 <= high.0 {
+            decimated.extend([low, high]);
+        } else {
+            decimated.extend([high, low]);
+        }
+    };
     
-    fn render_cpu_section(&self, ui: &mut egui::Ui) {
-        let state = self.state.read();
-        let session_start = state.session_start;
-        let has_data = state.has_cpu_data();
-        
-        // Determine if section should be open based on requirements:
-        // - Sections with data: default expanded
-        // - Sections without data: default collapsed  
-        let should_be_open = has_data;
+    // Lowest and highest point of the column being filled
+    let mut column = None;
+    let (mut low, mut high) = (points[0], points[0]);
+    for &point in points {
+        let point_column = (((point.0 - start) / column_width) as usize).min(columns - 1);
+        if column == Some(point_column) {
+            if point.1 < low.1 {
+                low = point;
+            }
+            if point.1 > high.1 {
+                high = point;
+            }
+            continue;
+        }
+        if column.is_some() {
+            flush(low, high);
+        }
+        column = Some(point_column);
+        (low, high) = (point, point);
+    }
+    flush(low, high);
+    decimated
+}
+
+// Metric sections side by side for a given width: one on a narrow window, more on ultrawide
+pub fn column_count(width: f32) -> usize {
+    ((width / MIN_COLUMN_WIDTH) as usize).clamp(1, MAX_COLUMNS)
+}
+
+// Map a value linearly from one range onto another, e.g. fan RPM onto the temperature axis
+pub fn rescale(value: f64, from: (f64, f64), to: (f64, f64)) -> f64 {
+    let span = from.1 - from.0;
+    if span == 0.0 {
+        return to.0;
+    }
+    to.0 + (value - from.0) / span * (to.1 - to.0)
+}
+
+// Padded Y range for a series, matching the single-metric plots
+fn padded_bounds(bounds: Option<(f64, f64)>) -> (f64, f64) {
+    match bounds {
+        Some((min_val, max_val)) if max_val > min_val => {
+            let padding = (max_val - min_val) * 0.05;
+            (min_val - padding, max_val + padding)
+        }
+        Some((value, _)) => (value - 1.0, value + 1.0),
+        None => (0.0, 100.0),
+    }
+}
+
+// Two metrics on one chart with their own Y axes. egui_plot has a single coordinate
+// space, so the right-hand series is rescaled onto the left axis and its tick labels
+// map back to the original units
+fn render_dual_axis_plot(ui: &mut egui::Ui, chart: &DualAxisChart, state: &AppState) {
+    let (Some(left), Some(right)) = (
+        MetricDescriptor::find(&chart.left).map(MetricDescriptor::displayed),
+        MetricDescriptor::find(&chart.right).map(MetricDescriptor::displayed),
+    ) else {
+        ui.label(format!("Unknown metric in chart: {} / {}", chart.left, chart.right));
+        return;
+    };
+    let (Some(left_series), Some(right_series)) = (state.metric(left.id), state.metric(right.id)) else {
+        return;
+    };
+    
+    let left_range = padded_bounds(left_series.session_min.zip(left_series.session_max));
+    let right_range = padded_bounds(right_series.session_min.zip(right_series.session_max));
+    let elapsed_seconds = model::elapsed_seconds(state.session_start, chrono::Utc::now());
+    
+    let format = |value: Option<f64>, descriptor: &DisplayedMetric| {
+        value.map_or_else(|| "N/A".to_string(), |v| descriptor.format_value(v))
+    };
+    
+    ui.group(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("{} vs {}", left.label, right.label)).heading());
+        });
+        ui.horizontal(|ui| {
+            ui.label(format!("{}: {}", left.label, format(left_series.current, &left)));
+            ui.separator();
+            ui.label(egui::RichText::new(format!("{}: {}", right.label, format(right_series.current, &right)))
+                .color(egui::Color32::LIGHT_RED));
+        });
         
-        let section_title = if has_data { "CPU Metrics" } else { "CPU Metrics (No Data)" };
-        let text_color = if has_data { egui::Color32::WHITE } else { egui::Color32::GRAY };
+        let left_points: PlotPoints = left_series.plot_data(state.session_start)
+            .into_iter()
+            .map(|(x, y)| [x, y])
+            .collect();
+        let right_points: PlotPoints = right_series.plot_data(state.session_start)
+            .into_iter()
+            .map(|(x, y)| [x, rescale(y, right_range, left_range)])
+            .collect();
         
-        CollapsingHeader::new(egui::RichText::new(section_title).color(text_color))
-            .default_open(should_be_open)
-            .show(ui, |ui| {
-            ui.columns(2, |columns| {
-                // Left column
-                self.render_metric_section(
-                    &mut columns[0],
-                    "CPU Utilization",
-                    &state.cpu.utilization,
-                    "%",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[0],
-                    "CPU Clock Speed",
-                    &state.cpu.clock_speed,
-                    " MHz",
-                    |v| format!("{}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[0],
-                    "CPU Core Voltage",
-                    &state.cpu.core_voltage,
-                    " V",
-                    |v| format!("{:.2}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[0],
-                    "CPU Power Consumption",
-                    &state.cpu.power_consumption,
-                    " W",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
-                
-                // Right column
-                self.render_metric_section(
-                    &mut columns[1],
-                    "CPU Package Temperature",
-                    &state.cpu.package_temperature,
-                    "°C",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[1],
-                    "CPU Hotspot Temperature",
-                    &state.cpu.hotspot_temperature,
-                    "°C",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
-                
-                // Right column continued - Thermal throttling as a proper metric
-                self.render_metric_section(
-                    &mut columns[1],
-                    "CPU Thermal Throttling",
-                    &state.cpu.thermal_throttling,
-                    "",
-                    |v| if *v { "1=Active".to_string() } else { "0=Inactive".to_string() },
-                    session_start,
-                );
+        Plot::new(format!("{}_{}_dual_plot", left.id, right.id))
+            .height(100.0)
+            .label_formatter(|_name, _value| String::new())
+            .custom_y_axes(vec![
+                AxisHints::new_y().label(left.unit.trim()),
+                AxisHints::new_y()
+                    .label(right.unit.trim())
+                    .placement(HPlacement::Right)
+                    .formatter(move |mark, _range| {
+                        format!("{:.*}", right.kind.decimals(), rescale(mark.value, left_range, right_range))
+                    }),
+            ])
+            .show(ui, |plot_ui| {
+                plot_ui.line(Line::new(left_points).name(left.label));
+                plot_ui.line(Line::new(right_points).color(egui::Color32::LIGHT_RED).name(right.label));
+                plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
+                    [0.0, left_range.0],
+                    [elapsed_seconds.max(1.0), left_range.1]
+                ));
             });
+    });
+}
+
+// Cumulative sums of the layers, bottom first, for drawing a stacked area chart.
+// Layers are aligned on their newest samples and trimmed to the shortest
+pub fn stack_series(layers: &[Vec<(f64, f64)>]) -> Vec<Vec<(f64, f64)>> {
+    let len = layers.iter().map(Vec::len).min().unwrap_or(0);
+    let mut totals = vec![0.0; len];
+    layers
+        .iter()
+        .map(|layer| {
+            layer[layer.len() - len..]
+                .iter()
+                .zip(totals.iter_mut())
+                .map(|(&(time, value), total)| {
+                    *total += value;
+                    (time, *total)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Memory composition as a stacked area: apps, cache and free add up to installed
+// RAM, with swap in use stacked on top
+fn render_memory_composition(ui: &mut egui::Ui, state: &AppState) {
+    let layers = [
+        ("Apps", &state.memory.utilization_mb, egui::Color32::from_rgb(220, 90, 90)),
+        ("Cache", &state.memory.cached_mb, egui::Color32::from_rgb(230, 180, 60)),
+        ("Free", &state.memory.free_mb, egui::Color32::from_rgb(90, 170, 90)),
+        ("Swap", &state.memory.swap_used_mb, egui::Color32::from_rgb(150, 110, 200)),
+    ];
+    let stacked = stack_series(
+        &layers.iter().map(|(_, series, _)| series.plot_data(state.session_start)).collect::<Vec<_>>(),
+    );
+    let top = stacked.last().and_then(|layer| layer.iter().map(|&(_, y)| y).reduce(f64::max)).unwrap_or(100.0);
+    let elapsed_seconds = model::elapsed_seconds(state.session_start, chrono::Utc::now());
+    
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Memory Composition").heading());
+        ui.horizontal_wrapped(|ui| {
+            for (name, series, color) in &layers {
+                let current = series.current.map_or_else(|| "N/A".to_string(), |mb| format!("{} MB", mb));
+                ui.label(egui::RichText::new(format!("{}: {}", name, current)).color(*color));
+            }
         });
+        
+        Plot::new("memory_composition_plot")
+            .height(100.0)
+            .label_formatter(|name, value| format!("{}\n{:.1}s, {:.0} MB total", name, value.x, value.y))
+            .show(ui, |plot_ui| {
+                // Top layer first so each lower band is painted over the one above it
+                for ((name, _, color), layer) in layers.iter().zip(&stacked).rev() {
+                    let points: PlotPoints = layer.iter().map(|&(x, y)| [x, y]).collect();
+                    plot_ui.line(Line::new(points).color(*color).fill(0.0).name(*name));
+                }
+                plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
+                    [0.0, 0.0],
+                    [elapsed_seconds.max(1.0), top * 1.05]
+                ));
+            });
+    });
+}
+
+// Cold-to-hot gradient (blue, green, yellow, red) for a value normalised to 0..1
+pub fn heatmap_color(fraction: f32) -> egui::Color32 {
+    const STOPS: [(f32, [u8; 3]); 4] = [
+        (0.0, [30, 40, 110]),
+        (0.4, [40, 160, 80]),
+        (0.7, [230, 200, 50]),
+        (1.0, [220, 50, 40]),
+    ];
+    let fraction = if fraction.is_nan() { 0.0 } else { fraction.clamp(0.0, 1.0) };
+    let upper = STOPS.iter().position(|(stop, _)| *stop >= fraction).unwrap_or(STOPS.len() - 1).max(1);
+    let ((low, low_rgb), (high, high_rgb)) = (STOPS[upper - 1], STOPS[upper]);
+    let t = (fraction - low) / (high - low);
+    let channel = |i: usize| (low_rgb[i] as f32 + (high_rgb[i] as f32 - low_rgb[i] as f32) * t).round() as u8;
+    egui::Color32::from_rgb(channel(0), channel(1), channel(2))
+}
+
+// Start time and tags, to pick a session from a list
+fn session_label(summary: &SessionSummary) -> String {
+    let started = summary.session_start.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+    if summary.tags.is_empty() {
+        started.to_string()
+    } else {
+        format!("{} ({})", started, summary.tags.join(", "))
+    }
+}
+
+// Stats listed for each session in the Sessions window: (metric, average or peak)
+const SESSION_HEADLINES: &[(&str, bool)] = &[
+    ("cpu.utilization", false),
+    ("cpu.package_temperature", true),
+    ("gpu.utilization", false),
+    ("gpu.package_temperature", true),
+];
+
+// One-line digest of a saved session, e.g. "CPU Utilization 42.0% avg, CPU Package Temperature 81.0°C max"
+pub fn session_headline(summary: &SessionSummary) -> String {
+    let stats: Vec<String> = SESSION_HEADLINES
+        .iter()
+        .filter_map(|(id, peak)| {
+            let descriptor = MetricDescriptor::find(id)?.displayed();
+            let metric = summary.metrics.get(*id)?;
+            Some(if *peak {
+                format!("{} {} max", descriptor.label, descriptor.format_value(metric.max))
+            } else {
+                format!("{} {} avg", descriptor.label, descriptor.format_value(metric.average))
+            })
+        })
+        .collect();
+    if stats.is_empty() {
+        "No headline metrics recorded".to_string()
+    } else {
+        stats.join(", ")
+    }
+}
+
+// Time x unit heatmap: one row per core (or CCD), one column per sample, newest on the
+// right. Shows scheduling and thread migration that per-core line plots can't for 32 cores
+fn render_heatmap(ui: &mut egui::Ui, rows: &[MetricValue], row_names: &[String], unit: &str, range: (f64, f64)) {
+    const CELL_WIDTH: f32 = 4.0;
+    let row_height = (160.0 / rows.len().max(1) as f32).clamp(4.0, 14.0);
+    let width = ui.available_width();
+    let columns = ((width / CELL_WIDTH) as usize).max(1);
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(width, row_height * rows.len() as f32),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+    
+    let span = (range.1 - range.0).max(f64::EPSILON);
+    for (row, series) in rows.iter().enumerate() {
+        let shown = series.history.len().min(columns);
+        for (column, (_, value)) in series.history.iter().skip(series.history.len() - shown).enumerate() {
+            // Right-align so every row's newest sample sits in the last column
+            let x = rect.right() - (shown - column) as f32 * CELL_WIDTH;
+            let y = rect.top() + row as f32 * row_height;
+            let cell = egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(CELL_WIDTH, row_height));
+            painter.rect_filled(cell, 0.0, heatmap_color(((value - range.0) / span) as f32));
+        }
     }
     
-    fn render_gpu_section(&self, ui: &mut egui::Ui) {
-        let state = self.state.read();
-        let session_start = state.session_start;
-        let has_data = state.has_gpu_data();
-        
-        // Determine if section should be open based on requirements:
-        // - Sections with data: default expanded
-        // - Sections without data: default collapsed  
-        let should_be_open = has_data;
-        
-        let section_title = if has_data { "GPU Metrics" } else { "GPU Metrics (No Data)" };
-        let text_color = if has_data { egui::Color32::WHITE } else { egui::Color32::GRAY };
+    if let Some(pointer) = response.hover_pos() {
+        let row = ((pointer.y - rect.top()) / row_height) as usize;
+        let samples_back = ((rect.right() - pointer.x) / CELL_WIDTH) as usize;
+        if let Some(series) = rows.get(row) {
+            let sample = series.history.len().checked_sub(samples_back + 1).and_then(|index| series.history.get(index));
+            if let Some((timestamp, value)) = sample {
+                response.on_hover_text(format!(
+                    "{}: {:.1}{} at {}",
+                    row_names.get(row).map_or("?", String::as_str),
+                    value,
+                    unit,
+                    timestamp.with_timezone(&chrono::Local).format("%H:%M:%S")
+                ));
+            }
+        }
+    }
+}
+
+// Several metrics sharing a unit on one chart, e.g. a GPU's graphics, memory and video clocks
+fn render_multi_metric_chart(ui: &mut egui::Ui, title: &str, metric_ids: &[&str], state: &AppState) {
+    const COLORS: [egui::Color32; 4] = [
+        egui::Color32::from_rgb(90, 150, 220),
+        egui::Color32::from_rgb(220, 120, 60),
+        egui::Color32::from_rgb(110, 190, 90),
+        egui::Color32::from_rgb(190, 110, 200),
+    ];
+    let metrics: Vec<(DisplayedMetric, &MetricValue)> = metric_ids
+        .iter()
+        .filter_map(|id| Some((MetricDescriptor::find(id)?.displayed(), state.metric(id)?)))
+        .filter(|(_, series)| series.current.is_some())
+        .collect();
+    let bounds = metrics
+        .iter()
+        .filter_map(|(_, series)| series.session_min.zip(series.session_max))
+        .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)));
+    let (y_min, y_max) = padded_bounds(bounds);
+    let elapsed_seconds = model::elapsed_seconds(state.session_start, chrono::Utc::now());
+    
+    ui.group(|ui| {
+        ui.label(egui::RichText::new(title).heading());
+        ui.horizontal_wrapped(|ui| {
+            for ((descriptor, series), color) in metrics.iter().zip(COLORS.iter().cycle()) {
+                let current = series.current.map_or_else(|| "N/A".to_string(), |v| descriptor.format_value(v));
+                ui.label(egui::RichText::new(format!("{}: {}", descriptor.label, current)).color(*color));
+            }
+        });
         
-        CollapsingHeader::new(egui::RichText::new(section_title).color(text_color))
-            .default_open(should_be_open)
-            .show(ui, |ui| {
-            ui.columns(2, |columns| {
-                // Left column
-                self.render_metric_section(
-                    &mut columns[0],
-                    "GPU Utilization",
-                    &state.gpu.utilization,
-                    "%",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[0],
-                    "GPU Clock Speed",
-                    &state.gpu.clock_speed,
-                    " MHz",
-                    |v| format!("{}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[0],
-                    "GPU Memory Utilization",
-                    &state.gpu.memory_utilization,
-                    " MB",
-                    |v| format!("{}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[0],
-                    "GPU Core Voltage",
-                    &state.gpu.core_voltage,
-                    " V",
-                    |v| format!("{:.2}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[0],
-                    "GPU Power Consumption",
-                    &state.gpu.power_consumption,
-                    " W",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
-                
-                // Right column
-                self.render_metric_section(
-                    &mut columns[1],
-                    "GPU Package Temperature",
-                    &state.gpu.package_temperature,
-                    "°C",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[1],
-                    "GPU Hotspot Temperature",
-                    &state.gpu.hotspot_temperature,
-                    "°C",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
-                
-                // Right column continued - Thermal throttling as a proper metric
-                self.render_metric_section(
-                    &mut columns[1],
-                    "GPU Thermal Throttling",
-                    &state.gpu.thermal_throttling,
-                    "",
-                    |v| if *v { "1=Active".to_string() } else { "0=Inactive".to_string() },
-                    session_start,
-                );
+        let columns = ui.available_width().max(1.0) as usize;
+        Plot::new(format!("{}_multi_plot", title))
+            .height(100.0)
+            .label_formatter(|name, value| format!("{}\n{:.1}s, {:.0}", name, value.x, value.y))
+            .show(ui, |plot_ui| {
+                for ((descriptor, series), color) in metrics.iter().zip(COLORS.iter().cycle()) {
+                    let data = decimate_min_max(&series.plot_data(state.session_start), columns);
+                    let points: PlotPoints = data.into_iter().map(|(x, y)| [x, y]).collect();
+                    plot_ui.line(Line::new(points).color(*color).name(&descriptor.label));
+                }
+                plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
+                    [0.0, y_min],
+                    [elapsed_seconds.max(1.0), y_max]
+                ));
             });
+    });
+}
+
+// Chart of a query expression on a custom dashboard, evaluated by the poller
+fn render_query_section(ui: &mut egui::Ui, expression: &str, state: &AppState) {
+    let query = state.queries.get(expression);
+    ui.group(|ui| {
+        ui.add(egui::Label::new(egui::RichText::new(expression).heading().monospace()).wrap());
+        let Some(query) = query else {
+            ui.label("Not evaluated yet");
+            return;
+        };
+        let values = &query.values;
+        let format = |value: Option<f64>| value.map_or_else(|| "N/A".to_string(), |value| format!("{:.2}", value));
+        ui.horizontal(|ui| {
+            ui.label(format!("Current: {}", format(values.current)));
+            ui.separator();
+            ui.label(format!("Min: {}", format(values.session_min)));
+            ui.label(format!("Max: {}", format(values.session_max)));
         });
+        if let Some(error) = &query.error {
+            ui.colored_label(egui::Color32::LIGHT_RED, error);
+        }
+        render_metric_plot(
+            ui,
+            expression,
+            "",
+            values.plot_data(state.session_start),
+            values.session_min.zip(values.session_max),
+            state.session_start,
+            100.0,
+            None,
+            None,
+            None,
+        );
+    });
+}
+
+// Every named temperature sensor of a device, e.g. Tctl/Tdie/Tccd1 or GPU edge/junction/mem
+fn render_temperature_sensors(ui: &mut egui::Ui, device: &str, sensors: &TemperatureSensors) {
+    if sensors.is_empty() {
+        return;
     }
     
-    fn render_memory_section(&self, ui: &mut egui::Ui) {
-        let state = self.state.read();
-        let session_start = state.session_start;
-        let has_data = state.has_memory_data();
-        
-        // Determine if section should be open based on requirements:
-        // - Sections with data: default expanded
-        // - Sections without data: default collapsed  
-        let should_be_open = has_data;
-        
-        let section_title = if has_data { "Memory Metrics" } else { "Memory Metrics (No Data)" };
-        let text_color = if has_data { egui::Color32::WHITE } else { egui::Color32::GRAY };
-        
-        CollapsingHeader::new(egui::RichText::new(section_title).color(text_color))
-            .default_open(should_be_open)
-            .show(ui, |ui| {
-            ui.columns(2, |columns| {
-                // Left column
-                self.render_metric_section(
-                    &mut columns[0],
-                    "Memory Utilization",
-                    &state.memory.utilization_mb,
-                    " MB",
-                    |v| format!("{}", v),
-                    session_start,
-                );
-                
-                // Right column
-                self.render_metric_section(
-                    &mut columns[1],
-                    "Memory Clock Speed",
-                    &state.memory.clock_speed,
-                    " MHz",
-                    |v| format!("{}", v),
-                    session_start,
-                );
+    CollapsingHeader::new(format!("Temperature Sensors ({})", sensors.len()))
+        .id_source(("temperature_sensors", device))
+        .default_open(false)
+        .show(ui, |ui| {
+            egui::Grid::new(("temperature_sensors_grid", device)).striped(true).num_columns(4).show(ui, |ui| {
+                for header in ["Sensor", "Current", "Min", "Max"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
                 
-                self.render_metric_section(
-                    &mut columns[1],
-                    "Memory Temperature",
-                    &state.memory.temperature,
-                    "°C",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
+                let format = |value: Option<f64>| value.map_or_else(|| "N/A".to_string(), |v| format!("{:.1}°C", v));
+                for (name, sensor) in sensors.iter() {
+                    ui.label(name);
+                    ui.label(format(sensor.current));
+                    ui.label(format(sensor.session_min));
+                    ui.label(format(sensor.session_max));
+                    ui.end_row();
+                }
             });
         });
-    }
+}
+
+// Per-core heatmap, plus per-cluster aggregation on hybrid and multi-CCD chips
+fn render_core_utilization(ui: &mut egui::Ui, state: &AppState) {
+    let topology = &state.cpu.topology;
+    // Hovering a core names the cluster it belongs to
+    let core_names: Vec<String> = (0..state.cpu.core_utilization.len())
+        .map(|cpu| match topology.cluster_of(cpu) {
+            Some(cluster) if topology.clusters.len() > 1 => format!("Core {} ({})", cpu, cluster.name),
+            _ => format!("Core {}", cpu),
+        })
+        .collect();
+    render_heatmap(ui, &state.cpu.core_utilization, &core_names, "%", (0.0, 100.0));
     
-    fn render_storage_section(&self, ui: &mut egui::Ui) {
-        let state = self.state.read();
-        let session_start = state.session_start;
-        let has_data = state.has_storage_data();
-        
-        // Determine if section should be open based on requirements:
-        // - Sections with data: default expanded
-        // - Sections without data: default collapsed  
-        let should_be_open = has_data;
-        
-        let section_title = if has_data { "Storage Metrics" } else { "Storage Metrics (No Data)" };
-        let text_color = if has_data { egui::Color32::WHITE } else { egui::Color32::GRAY };
-        
-        CollapsingHeader::new(egui::RichText::new(section_title).color(text_color))
-            .default_open(should_be_open)
+    if topology.clusters.len() > 1 {
+        let cluster_series = state.cpu.cluster_utilization();
+        let cluster_names: Vec<String> = topology.clusters.iter().map(|cluster| cluster.name.clone()).collect();
+        CollapsingHeader::new(format!(
+            "Per-Cluster Utilization ({} sockets, {} cores, {} threads)",
+            topology.sockets,
+            topology.physical_cores,
+            state.cpu.core_utilization.len()
+        ))
+            .default_open(false)
             .show(ui, |ui| {
-            ui.columns(2, |columns| {
-                // Left column
-                self.render_metric_section(
-                    &mut columns[0],
-                    "Drive Read Speed",
-                    &state.storage.read_speed,
-                    " MB/s",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[0],
-                    "Drive Write Speed",
-                    &state.storage.write_speed,
-                    " MB/s",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
+                egui::Grid::new("cpu_cluster_grid").striped(true).num_columns(4).show(ui, |ui| {
+                    for header in ["Cluster", "Logical CPUs", "Current", "Max"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    
+                    for (cluster, series) in topology.clusters.iter().zip(&cluster_series) {
+                        let format = |value: Option<f64>| value.map_or_else(|| "N/A".to_string(), |v| format!("{:.1}%", v));
+                        ui.label(&cluster.name);
+                        ui.label(cluster.cpus.len().to_string());
+                        ui.label(format(series.current));
+                        ui.label(format(series.session_max));
+                        ui.end_row();
+                    }
+                });
+                render_heatmap(ui, &cluster_series, &cluster_names, "%", (0.0, 100.0));
+            });
+    }
+}
+
+fn render_ccd_temperatures(ui: &mut egui::Ui, state: &AppState) {
+    // Scale to the session's own range so small CCD differences stand out
+    let (min, max) = state.cpu.ccd_temperatures.iter().fold((f64::MAX, f64::MIN), |(min, max), ccd| {
+        (min.min(ccd.session_min.unwrap_or(min)), max.max(ccd.session_max.unwrap_or(max)))
+    });
+    let ccd_names: Vec<String> = (0..state.cpu.ccd_temperatures.len()).map(|ccd| format!("CCD {}", ccd)).collect();
+    render_heatmap(ui, &state.cpu.ccd_temperatures, &ccd_names, "°C", (min, max));
+}
+
+fn render_top_processes(ui: &mut egui::Ui, state: &AppState) {
+    if state.cpu.top_processes.is_empty() {
+        return;
+    }
+    CollapsingHeader::new("Top Processes")
+        .default_open(false)
+        .show(ui, |ui| {
+            egui::Grid::new("cpu_process_grid").striped(true).num_columns(3).show(ui, |ui| {
+                for header in ["Process", "PID", "CPU"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
                 
-                // Right column
-                self.render_metric_section(
-                    &mut columns[1],
-                    "Drive Temperature",
-                    &state.storage.temperature,
-                    "°C",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
+                for process in &state.cpu.top_processes {
+                    ui.label(&process.name);
+                    ui.label(process.pid.to_string());
+                    ui.label(format!("{:.1}%", process.percent));
+                    ui.end_row();
+                }
             });
         });
+}
+
+// Who holds the VRAM, to see which job to stop or shrink before an out-of-memory error
+fn render_gpu_processes(ui: &mut egui::Ui, state: &AppState) {
+    if state.gpu.process_memory.is_empty() {
+        return;
     }
-    
-    fn render_motherboard_section(&self, ui: &mut egui::Ui) {
-        let state = self.state.read();
-        let session_start = state.session_start;
-        let has_data = state.has_motherboard_data();
-        
-        // Determine if section should be open based on requirements:
-        // - Sections with data: default expanded
-        // - Sections without data: default collapsed  
-        let should_be_open = has_data;
-        
-        let section_title = if has_data { "Motherboard Metrics" } else { "Motherboard Metrics (No Data)" };
-        let text_color = if has_data { egui::Color32::WHITE } else { egui::Color32::GRAY };
-        
-        CollapsingHeader::new(egui::RichText::new(section_title).color(text_color))
-            .default_open(should_be_open)
-            .show(ui, |ui| {
-            ui.columns(2, |columns| {
-                // Left column - Temperatures
-                self.render_metric_section(
-                    &mut columns[0],
-                    "Chipset Temperature",
-                    &state.motherboard.chipset_temperature,
-                    "°C",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[0],
-                    "Chassis Temperature",
-                    &state.motherboard.chassis_temperature,
-                    "°C",
-                    |v| format!("{:.1}", v),
-                    session_start,
-                );
-                
-                // Right column - Fan Speeds
-                self.render_metric_section(
-                    &mut columns[1],
-                    "AIO Pump Speed",
-                    &state.motherboard.aio_pump_speed,
-                    " RPM",
-                    |v| format!("{}", v),
-                    session_start,
-                );
-                
-                self.render_metric_section(
-                    &mut columns[1],
-                    "Chassis Fan Speed",
-                    &state.motherboard.chassis_fan_speed,
-                    " RPM",
-                    |v| format!("{}", v),
-                    session_start,
-                );
+    CollapsingHeader::new("VRAM by Process")
+        .default_open(false)
+        .show(ui, |ui| {
+            egui::Grid::new("gpu_process_grid").striped(true).num_columns(4).show(ui, |ui| {
+                for header in ["Process", "PID", "Type", "VRAM"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
                 
-                self.render_metric_section(
-                    &mut columns[1],
-                    "Chipset Fan Speed",
-                    &state.motherboard.chipset_fan_speed,
-                    " RPM",
-                    |v| format!("{}", v),
-                    session_start,
-                );
+                for process in &state.gpu.process_memory {
+                    ui.label(&process.name);
+                    ui.label(process.pid.to_string());
+                    ui.label(if process.compute { "Compute" } else { "Graphics" });
+                    match state.gpu.memory_total_mb.filter(|total| *total > 0) {
+                        Some(total) => ui.label(format!("{} MB ({:.0}%)", process.used_mb, process.used_mb as f64 / total as f64 * 100.0)),
+                        None => ui.label(format!("{} MB", process.used_mb)),
+                    };
+                    ui.end_row();
+                }
             });
         });
+}
+
+// SMART self-assessment and defect counters per drive, flagging what got worse since the last session
+fn render_drive_health(ui: &mut egui::Ui, state: &AppState) {
+    let drives = &state.storage.health;
+    if drives.is_empty() {
+        return;
     }
+    let unhealthy = drives.iter().filter(|drive| !drive.is_healthy()).count();
+    let title = match unhealthy {
+        0 => "Health".to_string(),
+        count => format!("Health ⚠ {} degraded", count),
+    };
+    CollapsingHeader::new(title)
+        .id_source("drive_health")
+        .default_open(unhealthy > 0)
+        .show(ui, |ui| {
+            let count = |value: Option<u64>| value.map_or_else(|| "N/A".to_string(), |v| v.to_string());
+            egui::Grid::new("drive_health_grid").striped(true).num_columns(7).show(ui, |ui| {
+                for header in ["Drive", "SMART", "Temperature", "Wear", "Reallocated", "Media Errors", "Power-On"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                
+                for drive in drives {
+                    ui.label(format!("{} ({})", drive.model, drive.device));
+                    match drive.passed {
+                        Some(true) => ui.colored_label(egui::Color32::LIGHT_GREEN, "PASSED"),
+                        Some(false) => ui.colored_label(egui::Color32::LIGHT_RED, "FAILED"),
+                        None => ui.label("N/A"),
+                    };
+                    ui.label(drive.temperature.map_or_else(|| "N/A".to_string(), |celsius| format!("{:.0}°C", celsius)));
+                    ui.label(drive.wear_percent.map_or_else(|| "N/A".to_string(), |percent| format!("{:.0}%", percent)));
+                    ui.label(count(drive.reallocated_sectors));
+                    ui.label(count(drive.media_errors));
+                    ui.label(drive.power_on_hours.map_or_else(|| "N/A".to_string(), |hours| format!("{} h", hours)));
+                    ui.end_row();
+                }
+            });
+            for drive in drives.iter().filter(|drive| !drive.degradations.is_empty()) {
+                ui.colored_label(
+                    egui::Color32::LIGHT_RED,
+                    format!("⚠ {} since the last session: {}", drive.model, drive.degradations.join(", ")),
+                );
+            }
+            for drive in drives {
+                let warnings = drive.critical_warnings();
+                if !warnings.is_empty() {
+                    ui.colored_label(egui::Color32::LIGHT_RED, format!("⚠ {} reports: {}", drive.model, warnings.join(", ")));
+                }
+            }
+        });
 }
 
-impl eframe::App for PerformanceApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Request repaint for continuous updates
-        ctx.request_repaint();
+// Throughput and busy time of one disk, under its own node in the device tree
+fn render_disk_io(ui: &mut egui::Ui, state: &AppState, node: &DeviceNode) {
+    let Some(disk) = state.storage.disks.iter().find(|disk| disk.name == node.name) else {
+        return;
+    };
+    let value = |metric: Option<f64>, unit: &str| metric.map_or_else(|| "N/A".to_string(), |v| format!("{:.1}{}", v, unit));
+    ui.label(format!(
+        "Read {}, write {}, busy {}",
+        value(disk.read_speed.current, " MB/s"),
+        value(disk.write_speed.current, " MB/s"),
+        value(disk.busy_percent.current, "%")
+    ));
+    
+    let columns = ui.available_width().max(1.0) as usize;
+    Plot::new(("disk_io_plot", &disk.name))
+        .height(100.0)
+        .legend(egui_plot::Legend::default())
+        .label_formatter(|name, value| format!("{}\n{:.1}s, {:.2} MB/s", name, value.x, value.y))
+        .show(ui, |plot_ui| {
+            for (direction, series) in [("Read", &disk.read_speed), ("Write", &disk.write_speed)] {
+                let data = decimate_min_max(&series.plot_data(state.session_start), columns);
+                let points: PlotPoints = data.into_iter().map(|(x, y)| [x, y]).collect();
+                plot_ui.line(Line::new(points).name(direction));
+            }
+        });
+}
+
+// Session latency percentiles and the processes doing the most file I/O, from the eBPF probes
+fn render_io_trace(ui: &mut egui::Ui, state: &AppState) {
+    let latency = &state.storage.io_latency;
+    if latency.count() == 0 && state.storage.process_io.is_empty() {
+        return;
+    }
+    CollapsingHeader::new("I/O Latency").default_open(false).show(ui, |ui| {
+        let quantile = |q: f64| latency.quantile_ms(q).map_or_else(|| "N/A".to_string(), |ms| format!("≤ {} ms", ms));
+        ui.label(format!(
+            "{} I/Os this session: p50 {}, p99 {}, p99.9 {}",
+            latency.count(),
+            quantile(0.5),
+            quantile(0.99),
+            quantile(0.999)
+        ));
         
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Simple Performance Dashboard");
-            
-            ui.separator();
-            
+        let processes = &state.storage.process_io;
+        if processes.is_empty() {
+            return;
+        }
+        ui.small("File reads and writes as each process made them, including reads from the page cache; the disk writes buffered data back later");
+        egui::Grid::new("process_io_grid").striped(true).num_columns(2).show(ui, |ui| {
+            ui.strong("Process");
+            ui.strong("I/O");
+            ui.end_row();
             
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                self.render_cpu_section(ui);
+            for process in processes {
+                ui.label(&process.name);
+                ui.label(format!("{:.2} MB/s", process.mb_per_sec));
+                ui.end_row();
+            }
+        });
+    });
+}
+
+// mdraid arrays and ZFS pools with their scrub or rebuild and the error counts of each device
+fn render_storage_pools(ui: &mut egui::Ui, state: &AppState) {
+    let pools = &state.storage.pools;
+    if pools.is_empty() {
+        return;
+    }
+    let unhealthy = pools.iter().filter(|pool| !pool.healthy).count();
+    let title = match unhealthy {
+        0 => format!("Pools ({})", pools.len()),
+        count => format!("Pools ⚠ {} degraded", count),
+    };
+    CollapsingHeader::new(title)
+        .id_source("storage_pools")
+        .default_open(unhealthy > 0)
+        .show(ui, |ui| {
+            for pool in pools {
+                let kind = match pool.kind {
+                    PoolKind::Mdraid => "mdraid",
+                    PoolKind::Zfs => "ZFS",
+                };
+                let color = if pool.healthy { egui::Color32::LIGHT_GREEN } else { egui::Color32::LIGHT_RED };
+                ui.horizontal(|ui| {
+                    ui.strong(format!("{} ({})", pool.name, kind));
+                    ui.colored_label(color, &pool.state);
+                });
+                if let Some(scan) = &pool.scan {
+                    match pool.scan_progress {
+                        Some(percent) => {
+                            ui.add(egui::ProgressBar::new((percent / 100.0) as f32).text(format!("{} ({:.1}%)", scan, percent)));
+                        }
+                        None => {
+                            ui.label(egui::RichText::new(scan).weak());
+                        }
+                    }
+                }
+                egui::Grid::new(("storage_pool_grid", &pool.name)).striped(true).num_columns(5).show(ui, |ui| {
+                    for header in ["Device", "State", "Read", "Write", "Checksum"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    
+                    for device in &pool.devices {
+                        ui.label(format!("{}{}", "  ".repeat(device.depth), device.name));
+                        ui.label(&device.state);
+                        for errors in [device.read_errors, device.write_errors, device.checksum_errors] {
+                            if errors > 0 {
+                                ui.colored_label(egui::Color32::LIGHT_RED, errors.to_string());
+                            } else {
+                                ui.label("0");
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.add_space(4.0);
+            }
+        });
+}
+
+// Host writes per drive against its rated endurance, from SMART
+fn render_drive_endurance(ui: &mut egui::Ui, state: &AppState) {
+    let drives = &state.storage.drives;
+    if drives.is_empty() {
+        return;
+    }
+    CollapsingHeader::new("Endurance")
+        .default_open(false)
+        .show(ui, |ui| {
+            egui::Grid::new("drive_endurance_grid").striped(true).num_columns(5).show(ui, |ui| {
+                for header in ["Drive", "This Session", "Lifetime", "Rated", "Used"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                
+                for drive in drives {
+                    ui.label(format!("{} ({})", drive.model, drive.device));
+                    ui.label(format!("{:.1} GB", drive.session_gb()));
+                    ui.label(format!("{:.2} TB", drive.lifetime_tb()));
+                    ui.label(drive.rated_tbw.map_or_else(|| "N/A".to_string(), |tbw| format!("{:.0} TBW", tbw)));
+                    ui.label(drive.endurance_used().map_or_else(|| "N/A".to_string(), |percent| format!("{:.1}%", percent)));
+                    ui.end_row();
+                }
+            });
+            if drives.iter().any(|drive| drive.rated_tbw.is_none()) {
+                ui.label(egui::RichText::new(
+                    "Without a rating in drive_rated_tbw, \"Used\" is the drive's own wear estimate.",
+                ).weak());
+            }
+        });
+}
+
+// Throughput of each interface, to tell which link is saturated
+fn render_network_interfaces(ui: &mut egui::Ui, state: &AppState) {
+    let interfaces = &state.network.interfaces;
+    if interfaces.is_empty() {
+        return;
+    }
+    CollapsingHeader::new(format!("Interfaces ({})", interfaces.len()))
+        .default_open(interfaces.len() > 1)
+        .show(ui, |ui| {
+            let rate = |value: Option<f64>| value.map_or_else(|| "N/A".to_string(), |v| format!("{:.2} MB/s", v));
+            egui::Grid::new("network_interface_grid").striped(true).num_columns(5).show(ui, |ui| {
+                for header in ["Interface", "Receive", "Transmit", "Errors", "Link"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                
+                for interface in interfaces {
+                    ui.label(&interface.name);
+                    ui.label(rate(interface.receive_speed.current));
+                    ui.label(rate(interface.transmit_speed.current));
+                    ui.label(interface.packet_errors.current.map_or_else(|| "N/A".to_string(), |v| format!("{:.1}/s", v)));
+                    match (interface.link_speed_mbps, interface.link_utilization()) {
+                        (Some(speed), Some(percent)) => ui.label(format!("{} Mb/s ({:.0}% used)", speed, percent)),
+                        (Some(speed), None) => ui.label(format!("{} Mb/s", speed)),
+                        _ => ui.label("N/A"),
+                    };
+                    ui.end_row();
+                }
+            });
+            
+            let columns = ui.available_width().max(1.0) as usize;
+            Plot::new("network_interface_plot")
+                .height(100.0)
+                .legend(egui_plot::Legend::default())
+                .label_formatter(|name, value| format!("{}\n{:.1}s, {:.2} MB/s", name, value.x, value.y))
+                .show(ui, |plot_ui| {
+                    for interface in interfaces {
+                        for (direction, series) in [("receive", &interface.receive_speed), ("transmit", &interface.transmit_speed)] {
+                            let data = decimate_min_max(&series.plot_data(state.session_start), columns);
+                            let points: PlotPoints = data.into_iter().map(|(x, y)| [x, y]).collect();
+                            plot_ui.line(Line::new(points).name(format!("{} {}", interface.name, direction)));
+                        }
+                    }
+                });
+        });
+}
+
+// Power source, charge state and how long the battery lasts at the current rate
+fn render_battery_summary(ui: &mut egui::Ui, state: &AppState) {
+    let source = match state.power.on_battery.active {
+        Some(true) => "On battery",
+        Some(false) => "Plugged in",
+        None => "Power source unknown",
+    };
+    let mut summary = source.to_string();
+    if let Some(status) = state.battery.status {
+        summary.push_str(&format!(", {}", status.label().to_lowercase()));
+    }
+    let minutes = state.battery.time_remaining.current.filter(|_| {
+        matches!(state.battery.status, Some(BatteryStatus::Charging | BatteryStatus::Discharging))
+    });
+    if let Some(minutes) = minutes {
+        let until = if state.battery.status == Some(BatteryStatus::Charging) { "full" } else { "empty" };
+        summary.push_str(&format!(" — {} h {:02} min to {}", minutes as u64 / 60, minutes as u64 % 60, until));
+    }
+    ui.label(egui::RichText::new(summary).strong());
+}
+
+// Battery drain in context of the components that usually cause it, and session energy
+fn render_power_summary(ui: &mut egui::Ui, state: &AppState) {
+    if let Some(discharge) = state.power.battery_discharge.current.filter(|watts| *watts > 0.0) {
+        let share = |watts: Option<f64>| watts.map(|watts| format!("{:.0}%", watts / discharge * 100.0));
+        if let Some(cpu) = share(state.cpu.power_consumption.current) {
+            ui.label(format!("CPU package: {} of battery drain", cpu));
+        }
+        if let Some(gpu) = share(state.gpu.power_consumption.current) {
+            ui.label(format!("GPU: {} of battery drain", gpu));
+        }
+    }
+    
+    let totals: Vec<(String, EnergyTotal)> = ENERGY_METRICS
+        .iter()
+        .filter_map(|id| Some((MetricDescriptor::find(id)?.displayed().label, state.session_energy(id)?)))
+        .filter(|(_, total)| total.measured_seconds > 0.0)
+        .collect();
+    if !totals.is_empty() {
+        let summary: Vec<String> = totals.iter().map(|(label, total)| format!("{} {:.2} Wh", label, total.watt_hours)).collect();
+        ui.label(format!("Session energy: {}", summary.join(", ")));
+        let skipped = totals.iter().map(|(_, total)| total.skipped_seconds).fold(0.0, f64::max);
+        if skipped > 0.0 {
+            ui.small(format!("Excludes {:.0} min spent suspended or not polling", skipped / 60.0));
+        }
+    }
+}
+
+// Metric picker shared by alert rules and dual-axis charts
+fn metric_combo(ui: &mut egui::Ui, id_salt: impl std::hash::Hash, metric: &mut String) {
+    let selected = MetricDescriptor::find(metric).map_or(metric.clone(), |d| d.displayed().label);
+    egui::ComboBox::from_id_source(id_salt)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for descriptor in METRIC_DESCRIPTORS.iter().map(MetricDescriptor::displayed) {
+                ui.selectable_value(metric, descriptor.id.to_string(), descriptor.label);
+            }
+        });
+}
+
+// A metric from the catalog, or any query expression typed in instead
+fn metric_or_expression(ui: &mut egui::Ui, id_salt: impl std::hash::Hash + Copy, metric: &mut String) {
+    ui.horizontal(|ui| {
+        let selected = MetricDescriptor::find(metric).map_or("Expression".to_string(), |d| d.displayed().label);
+        egui::ComboBox::from_id_source(id_salt)
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for descriptor in METRIC_DESCRIPTORS.iter().map(MetricDescriptor::displayed) {
+                    ui.selectable_value(metric, descriptor.id.to_string(), descriptor.label);
+                }
+            });
+        let response = ui.add(egui::TextEdit::singleline(metric).desired_width(200.0))
+            .on_hover_text("A metric id, or an expression such as max_over(5m, gpu.power_consumption)");
+        if let Err(e) = query::parse(metric) {
+            response.on_hover_text(e.to_string());
+            ui.colored_label(egui::Color32::LIGHT_RED, "⚠");
+        }
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingsTab {
+    General,
+    Monitors,
+    Alerts,
+    Exporters,
+    Appearance,
+    Sensors,
+}
+
+impl SettingsTab {
+    const ALL: [SettingsTab; 6] = [
+        SettingsTab::General,
+        SettingsTab::Monitors,
+        SettingsTab::Alerts,
+        SettingsTab::Exporters,
+        SettingsTab::Appearance,
+        SettingsTab::Sensors,
+    ];
+    
+    fn label(&self) -> &'static str {
+        match self {
+            SettingsTab::General => "General",
+            SettingsTab::Monitors => "Monitors",
+            SettingsTab::Alerts => "Alerts",
+            SettingsTab::Exporters => "Exporters",
+            SettingsTab::Appearance => "Appearance",
+            SettingsTab::Sensors => "Sensors",
+        }
+    }
+}
+
+// Text field for an optional string; clearing it unsets the value
+fn optional_text_edit(ui: &mut egui::Ui, value: &mut Option<String>, password: bool) {
+    let mut text = value.clone().unwrap_or_default();
+    if ui.add(egui::TextEdit::singleline(&mut text).password(password)).changed() {
+        *value = (!text.is_empty()).then_some(text);
+    }
+}
+
+fn settings_general(ui: &mut egui::Ui, config: &mut AppConfig) {
+    egui::Grid::new("settings_general_grid").num_columns(2).show(ui, |ui| {
+        if let Some(profile) = &config.profile {
+            ui.label("Machine profile:");
+            ui.label(profile).on_hover_text("Settings are kept per machine, keyed by its hardware");
+            ui.end_row();
+        }
+        
+        ui.label("Polling interval:");
+        ui.add(egui::DragValue::new(&mut config.polling_interval_ms)
+            .range(MIN_POLLING_INTERVAL_MS..=MAX_POLLING_INTERVAL_MS)
+            .speed(10.0)
+            .suffix(" ms"));
+        ui.end_row();
+        
+        ui.label("Keep awake:");
+        ui.checkbox(&mut config.keep_awake, "Prevent sleep while recording");
+        ui.end_row();
+        
+        ui.label("Updates:");
+        ui.checkbox(&mut config.check_updates, "Check for a new version at startup")
+            .on_hover_text("Asks GitHub for the latest release; nothing about this machine is sent");
+        ui.end_row();
+        
+        ui.label("Shared reports:");
+        ui.checkbox(&mut config.anonymize, "Anonymize")
+            .on_hover_text("Replace the host name, user name and serial numbers with stable pseudonyms in crash reports, copied system info and exported instance labels");
+        ui.end_row();
+        
+        ui.label("History memory budget:");
+        ui.add(egui::DragValue::new(&mut config.history_budget_mb).speed(4.0).suffix(" MB"))
+            .on_hover_text("Older samples are moved to disk past this; 0 keeps everything in memory");
+        ui.end_row();
+        
+        ui.label("Privileged helper:");
+        ui.checkbox(&mut config.helper.enabled, "Read root-only sensors through the helper");
+        ui.end_row();
+    });
+    ui.label(egui::RichText::new("Helper changes take effect after a restart.").weak());
+}
+
+fn settings_monitors(ui: &mut egui::Ui, config: &mut AppConfig, selections: &[MonitorSelection]) {
+    ui.label("Switch sensor backends on or off; changes apply on the next poll.");
+    egui::Grid::new("settings_monitors_grid").num_columns(2).show(ui, |ui| {
+        for selection in selections {
+            let mut enabled = config.monitor_enabled(&selection.name);
+            let checkbox = ui.add_enabled(selection.selected, egui::Checkbox::new(&mut enabled, &selection.name));
+            if checkbox.changed() {
+                config.set_monitor_enabled(&selection.name, enabled);
+            }
+            ui.label(egui::RichText::new(&selection.reason).weak());
+            ui.end_row();
+        }
+    });
+}
+
+fn settings_alerts(ui: &mut egui::Ui, config: &mut AppConfig) {
+    let alerts = &mut config.alerts;
+    ui.strong("Rules");
+    let mut removed = None;
+    egui::Grid::new("settings_alert_rules_grid").striped(true).num_columns(5).show(ui, |ui| {
+        for header in ["Metric", "Threshold", "Severity", "For", ""] {
+            ui.strong(header);
+        }
+        ui.end_row();
+        
+        for (index, rule) in alerts.rules.iter_mut().enumerate() {
+            metric_or_expression(ui, ("alert_rule_metric", index), &mut rule.metric);
+            let unit = MetricDescriptor::find(&rule.metric).map_or(String::new(), |d| d.displayed().unit);
+            ui.add(egui::DragValue::new(&mut rule.threshold).speed(0.5).suffix(unit));
+            egui::ComboBox::from_id_source(("alert_rule_severity", index))
+                .selected_text(format!("{:?}", rule.severity))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut rule.severity, AlertSeverity::Warning, "Warning");
+                    ui.selectable_value(&mut rule.severity, AlertSeverity::Critical, "Critical");
+                });
+            ui.add(egui::DragValue::new(&mut rule.duration_secs).suffix(" s"));
+            ui.horizontal(|ui| {
+                if let Some(action) = &rule.action {
+                    ui.label("⚙").on_hover_text(format!("Runs: {}", action.command_line()));
+                }
+                if ui.small_button("Remove").clicked() {
+                    removed = Some(index);
+                }
+            });
+            ui.end_row();
+        }
+    });
+    if let Some(index) = removed {
+        alerts.rules.remove(index);
+    }
+    ui.horizontal(|ui| {
+        if ui.button("Add Rule").clicked() {
+            alerts.rules.push(AlertRule::new("cpu.package_temperature", 90.0, AlertSeverity::Critical));
+        }
+        egui::ComboBox::from_id_source("alert_rule_template")
+            .selected_text("Add from template")
+            .show_ui(ui, |ui| {
+                for template in ALERT_TEMPLATES {
+                    if ui.selectable_label(false, template.name).clicked() {
+                        alerts.rules.push(template.rule());
+                    }
+                }
+            });
+    });
+    ui.label(egui::RichText::new("Rule actions and quiet hours are edited in the config file.").weak());
+    
+    ui.separator();
+    ui.strong("Alarm");
+    ui.checkbox(&mut alerts.alarm.enabled, "Sound an alarm for persistent critical alerts");
+    egui::Grid::new("settings_alarm_grid").num_columns(2).show(ui, |ui| {
+        ui.label("Persist before sounding:");
+        ui.add(egui::DragValue::new(&mut alerts.alarm.persist_secs).suffix(" s"));
+        ui.end_row();
+        
+        ui.label("Repeat every:");
+        ui.add(egui::DragValue::new(&mut alerts.alarm.repeat_interval_secs).suffix(" s"));
+        ui.end_row();
+        
+        ui.label("Sound file:");
+        let mut sound_file = alerts.alarm.sound_file.as_ref().map(|path| path.display().to_string());
+        optional_text_edit(ui, &mut sound_file, false);
+        alerts.alarm.sound_file = sound_file.map(std::path::PathBuf::from);
+        ui.end_row();
+    });
+    if alerts.alarm.enabled {
+        if let Some(reason) = crate::alerts::alarm_unavailable(alerts.alarm.sound_file.as_deref()) {
+            ui.colored_label(egui::Color32::YELLOW, format!("The alarm can't be played: {}", reason));
+        }
+    }
+    
+    ui.separator();
+    ui.strong("Trend warnings");
+    ui.checkbox(&mut alerts.prediction.enabled, "Warn before a threshold is reached");
+    egui::Grid::new("settings_prediction_grid").num_columns(2).show(ui, |ui| {
+        ui.label("Trend window:");
+        ui.add(egui::DragValue::new(&mut alerts.prediction.window_secs).range(5..=3600).suffix(" s"));
+        ui.end_row();
+        
+        ui.label("Warn when within:");
+        ui.add(egui::DragValue::new(&mut alerts.prediction.horizon_secs).range(10..=86400).suffix(" s"));
+        ui.end_row();
+    });
+}
+
+fn settings_exporters(ui: &mut egui::Ui, config: &mut AppConfig) {
+    let remote_write = &mut config.remote_write;
+    ui.strong("Prometheus remote_write");
+    ui.checkbox(&mut remote_write.enabled, "Push samples");
+    egui::Grid::new("settings_remote_write_grid").num_columns(2).show(ui, |ui| {
+        ui.label("URL:");
+        ui.text_edit_singleline(&mut remote_write.url);
+        ui.end_row();
+        
+        ui.label("Username:");
+        optional_text_edit(ui, &mut remote_write.username, false);
+        ui.end_row();
+        
+        ui.label("Password:");
+        optional_text_edit(ui, &mut remote_write.password, true);
+        ui.end_row();
+        
+        ui.label("Bearer token:");
+        optional_text_edit(ui, &mut remote_write.bearer_token, true);
+        ui.end_row();
+        
+        ui.label("Push every:");
+        ui.add(egui::DragValue::new(&mut remote_write.interval_secs).range(1..=3600).suffix(" s"));
+        ui.end_row();
+        
+        ui.label("Job label:");
+        ui.text_edit_singleline(&mut remote_write.job);
+        ui.end_row();
+        
+        ui.label("Instance label:");
+        optional_text_edit(ui, &mut remote_write.instance, false);
+        ui.end_row();
+        
+        ui.label("Offline buffer:");
+        ui.add(egui::DragValue::new(&mut remote_write.buffer_max_mb).range(0..=4096).suffix(" MB"))
+            .on_hover_text("Failed pushes are kept on disk up to this size and replayed on reconnect; 0 disables");
+        ui.end_row();
+    });
+    if !cfg!(feature = "remote-write") {
+        ui.label(egui::RichText::new("This build was compiled without remote_write support.").weak());
+    }
+    
+    ui.separator();
+    ui.strong("Labels");
+    let labels = &mut config.export_labels;
+    ui.checkbox(&mut labels.hardware, "Add cpu_model and gpu_model labels");
+    if !labels.custom.is_empty() {
+        let custom: Vec<String> = labels.custom.iter().map(|(name, value)| format!("{}=\"{}\"", name, value)).collect();
+        ui.label(custom.join(", "));
+    }
+    ui.label(egui::RichText::new("Custom labels are edited in the config file, under [export_labels.custom].").weak());
+}
+
+// `sensors -j` output the Sensors tab maps from, as pasted or read from this machine
+#[derive(Default)]
+struct SensorAssistant {
+    output: String,
+    readings: Vec<SensorReading>,
+    status: Option<String>,
+}
+
+impl SensorAssistant {
+    fn parse(&mut self) {
+        match lm_sensors::parse(&self.output) {
+            Ok(readings) => {
+                self.status = Some(format!("Found {} sensors", readings.len()));
+                self.readings = readings;
+            }
+            Err(e) => {
+                self.status = Some(format!("Could not read the sensors output: {}", e));
+                self.readings.clear();
+            }
+        }
+    }
+}
+
+fn settings_sensors(ui: &mut egui::Ui, config: &mut AppConfig, assistant: &mut SensorAssistant) {
+    ui.label("Choose the Linux sensor behind each temperature instead of letting the dashboard guess. \
+        Paste the output of `sensors -j`, or read it from this machine.");
+    ui.horizontal(|ui| {
+        if ui.button("Read sensors -j").clicked() {
+            match lm_sensors::read_live() {
+                Ok(output) => {
+                    assistant.output = output;
+                    assistant.parse();
+                }
+                Err(e) => assistant.status = Some(e.to_string()),
+            }
+        }
+        if ui.add_enabled(!assistant.output.trim().is_empty(), egui::Button::new("Parse pasted output")).clicked() {
+            assistant.parse();
+        }
+    });
+    ui.add(egui::TextEdit::multiline(&mut assistant.output)
+        .hint_text("sensors -j output")
+        .code_editor()
+        .desired_rows(4)
+        .desired_width(f32::INFINITY));
+    if let Some(status) = &assistant.status {
+        ui.label(status);
+    }
+    
+    if !assistant.readings.is_empty() {
+        ui.separator();
+        ui.strong("Sensors");
+        egui::Grid::new("settings_sensor_readings_grid").striped(true).num_columns(4).show(ui, |ui| {
+            for header in ["Chip", "Sensor", "Reading", "Metric"] {
+                ui.strong(header);
+            }
+            ui.end_row();
+            
+            for (index, reading) in assistant.readings.iter().enumerate() {
+                ui.label(&reading.chip);
+                ui.label(&reading.label);
+                ui.label(format!("{:.1}{}", reading.value, reading.kind.unit()));
+                if reading.kind != lm_sensors::ReadingKind::Temperature {
+                    ui.label(egui::RichText::new("—").weak());
+                    ui.end_row();
+                    continue;
+                }
+                let path = reading.path();
+                let mapped = config.sensor_mappings.iter().find(|(_, mapped)| **mapped == path).map(|(id, _)| id.clone());
+                let mut selected = mapped.clone();
+                let label = |id: &str| MetricDescriptor::find(id).map_or(id.to_string(), |d| d.displayed().label);
+                let combo = egui::ComboBox::from_id_source(("settings_sensor_metric", index))
+                    .selected_text(selected.as_deref().map_or("Not mapped".to_string(), label))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut selected, None, "Not mapped");
+                        for descriptor in lm_sensors::temperature_metrics().map(MetricDescriptor::displayed) {
+                            ui.selectable_value(&mut selected, Some(descriptor.id.to_string()), descriptor.label);
+                        }
+                    });
+                if let Some(suggested) = reading.suggested_metric() {
+                    combo.response.on_hover_text(format!("Usually {}", label(suggested)));
+                }
+                if selected != mapped {
+                    // One sensor per metric and one metric per sensor
+                    config.sensor_mappings.retain(|_, mapped| *mapped != path);
+                    if let Some(id) = selected {
+                        config.sensor_mappings.insert(id, path);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    }
+    
+    ui.separator();
+    ui.strong("Mappings");
+    if config.sensor_mappings.is_empty() {
+        ui.label(egui::RichText::new("No sensors mapped; temperatures are picked automatically.").weak());
+        return;
+    }
+    let problems = lm_sensors::validate(&config.sensor_mappings, &assistant.readings);
+    let mut removed = None;
+    egui::Grid::new("settings_sensor_mappings_grid").striped(true).num_columns(3).show(ui, |ui| {
+        for (id, path) in &config.sensor_mappings {
+            ui.label(MetricDescriptor::find(id).map_or(id.to_string(), |d| d.displayed().label));
+            ui.horizontal(|ui| {
+                ui.monospace(path);
+                // Only checked once there is sensors output to check against
+                if !assistant.readings.is_empty() {
+                    match problems.iter().find(|problem| problem.metric_id == *id) {
+                        Some(problem) => ui.colored_label(egui::Color32::LIGHT_RED, "⚠").on_hover_text(&problem.message),
+                        None => ui.colored_label(egui::Color32::LIGHT_GREEN, "✔").on_hover_text("Found in the sensors output"),
+                    };
+                }
+            });
+            if ui.small_button("Remove").clicked() {
+                removed = Some(id.clone());
+            }
+            ui.end_row();
+        }
+    });
+    if let Some(id) = removed {
+        config.sensor_mappings.remove(&id);
+    }
+    if assistant.readings.is_empty() {
+        ui.label(egui::RichText::new("Read or paste sensors output to check these against this machine.").weak());
+    }
+}
+
+fn settings_appearance(ui: &mut egui::Ui, config: &mut AppConfig) {
+    egui::Grid::new("settings_appearance_grid").num_columns(2).show(ui, |ui| {
+        ui.label("Theme:");
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut config.theme, Theme::System, "System");
+            ui.selectable_value(&mut config.theme, Theme::Dark, "Dark");
+            ui.selectable_value(&mut config.theme, Theme::Light, "Light");
+        });
+        ui.end_row();
+        
+        // Presets rather than a slider, which would rescale under the cursor while dragging
+        ui.label("UI scale:");
+        let selected = config.ui_scale.map_or_else(|| "Follow OS".to_string(), |scale| format!("{:.0}%", scale * 100.0));
+        egui::ComboBox::from_id_source("settings_ui_scale")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut config.ui_scale, None, "Follow OS");
+                for scale in [0.75, 1.0, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0] {
+                    ui.selectable_value(&mut config.ui_scale, Some(scale), format!("{:.0}%", scale * 100.0));
+                }
+            });
+        ui.end_row();
+        
+        ui.label("Plot refresh:");
+        ui.add(egui::DragValue::new(&mut config.plot_refresh_hz)
+            .range(MIN_PLOT_REFRESH_HZ..=MAX_PLOT_REFRESH_HZ)
+            .speed(0.5)
+            .suffix(" Hz"))
+            .on_hover_text("Redraws per second without input; lower uses less CPU on high refresh rate displays");
+        ui.end_row();
+    });
+}
+
+// UI-only preferences, kept out of the shared telemetry state and persisted in
+// their own file so layout survives restarts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    // Device tree nodes (DeviceNode::id) the user opened or closed against their default
+    pub toggled_devices: Vec<String>,
+    pub popped_out: Vec<String>, // Metric ids shown in their own viewport
+    pub pinned: Vec<String>,     // Metric ids in the bar above the dashboard, in pin order
+    pub hidden: Vec<String>,     // Metric ids left out of the hardware layout and dashboards
+    pub colors: BTreeMap<String, [u8; 3]>, // Plot line color per metric id, instead of the theme's
+    pub active_dashboard: Option<usize>, // Index into config.dashboards; None is the hardware layout
+}
+
+impl UiState {
+    pub fn color(&self, metric_id: &str) -> Option<egui::Color32> {
+        self.colors.get(metric_id).map(|[r, g, b]| egui::Color32::from_rgb(*r, *g, *b))
+    }
+    
+    pub fn is_hidden(&self, metric_id: &str) -> bool {
+        self.hidden.iter().any(|id| id == metric_id)
+    }
+    
+    // Next to the config file
+    pub fn path() -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+        let mut path = AppConfig::config_path()?;
+        path.set_file_name("simple_performance_dashboard_ui.toml");
+        Ok(path)
+    }
+    
+    // Defaults when the file is missing or unreadable; layout isn't worth failing over
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| Self::load_from(&path))
+            .unwrap_or_default()
+    }
+    
+    pub fn load_from(path: &std::path::Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+    
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_to(&Self::path()?)
+    }
+    
+    pub fn save_to(&self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+// The whole setup in one portable file: config (monitors, thresholds, dashboards,
+// exporters) plus the UI layout, to move a tuned setup to another machine or share it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub config: AppConfig,
+    #[serde(default)]
+    pub ui: UiState,
+}
+
+impl SettingsBundle {
+    // Next to the config file, unless the user picks another path
+    pub fn default_path() -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+        let mut path = AppConfig::config_path()?;
+        path.set_file_name("simple_performance_dashboard_settings.toml");
+        Ok(path)
+    }
+    
+    // Unlike the config file, a bad bundle is an error rather than defaults, so an
+    // import never silently wipes the current setup. A shared file may come from anyone,
+    // so every alert action it carries waits for confirmation before it runs
+    pub fn load_from(path: &std::path::Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut bundle: Self = toml::from_str(&std::fs::read_to_string(path)?)?;
+        bundle.config.sanitize();
+        for rule in &mut bundle.config.alerts.rules {
+            if let Some(action) = &mut rule.action {
+                action.require_confirmation = true;
+            }
+        }
+        Ok(bundle)
+    }
+    
+    // Credentials, the pseudonym salt and the helper setting stay on this machine
+    pub fn save_to(&self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut shared = self.clone();
+        shared.config.remote_write.password = None;
+        shared.config.remote_write.bearer_token = None;
+        shared.config.anonymize_salt = 0;
+        shared.config.helper = Default::default();
+        std::fs::write(path, toml::to_string_pretty(&shared)?)?;
+        Ok(())
+    }
+    
+    // Commands the bundle's alert rules would run, listed on import
+    pub fn alert_actions(&self) -> Vec<String> {
+        self.config.alerts.rules
+            .iter()
+            .filter_map(|rule| rule.action.as_ref())
+            .map(|action| action.command_line())
+            .collect()
+    }
+    
+    // The bundle's config as this machine should use it: its own profile, salt and helper
+    // setting, and its remote_write credentials as long as the endpoint is unchanged
+    pub fn config_for(&self, current: &AppConfig) -> AppConfig {
+        let mut config = AppConfig {
+            profile: current.profile.clone(),
+            anonymize_salt: current.anonymize_salt,
+            helper: current.helper.clone(),
+            ..self.config.clone()
+        };
+        if config.remote_write.url == current.remote_write.url {
+            config.remote_write.password = current.remote_write.password.clone();
+            config.remote_write.bearer_token = current.remote_write.bearer_token.clone();
+        }
+        config
+    }
+}
+
+pub struct PerformanceApp {
+    state: SharedAppState,
+    config: SharedConfig,
+    ui_scale_edit: Option<f32>, // Slider value while dragging, applied on release
+    ui_state: UiState,
+    saved_ui_state: UiState,    // As last written, to save only on change
+    show_alerts: bool,
+    imported: Vec<ImportedSession>,
+    overlay: Option<usize>,     // Imported session drawn alongside live data
+    show_import: bool,
+    import_path: String,
+    import_error: Option<String>,
+    export_status: Option<String>,
+    sample_rate: Option<(std::time::Instant, u64, f64)>, // When last measured, samples published by then, samples/s
+    menu_threshold: Option<(String, f64)>, // Alert threshold being edited in an open metric menu
+    sleep_inhibitor: Option<SleepInhibitor>,
+    keep_awake_error: Option<String>, // Set when the inhibit was refused; cleared on toggle
+    crash_report: Option<(std::path::PathBuf, bool)>, // Report to offer, and whether it's from a previous run
+    show_settings: bool,
+    settings_tab: SettingsTab,
+    settings_path: String, // Where "Export settings" writes and "Import settings" reads
+    settings_status: Option<String>,
+    sensor_assistant: SensorAssistant,
+    editing_dashboard: bool,
+    new_expression: String, // Expression being typed in the dashboard editor
+    spilled_plots: HashMap<String, (usize, Vec<(f64, f64)>)>, // Downsampled spilled history per metric, by spilled count
+    baseline: Option<SessionSummary>, // Most recent earlier session on this hardware, or one picked under Sessions
+    show_sessions: bool,
+    sessions: Vec<(std::path::PathBuf, SessionSummary)>, // Saved sessions, newest first, as of opening the window
+    opened_session: Option<std::path::PathBuf>, // Session whose full stats are shown
+    session_tags: String, // Tags of the opened session being edited, comma-separated
+    session_filter: SessionFilter,
+    session_dates: (String, String), // Date range as typed, parsed into session_filter
+    regression_pair: (Option<std::path::PathBuf>, Option<std::path::PathBuf>), // Before and after sessions to check
+    sessions_status: Option<String>,
+    session_summary: Option<(std::time::Instant, SessionSummary)>, // This session so far, and when computed
+    update_check: Option<std::sync::mpsc::Receiver<Release>>, // Startup update check still running
+    update: Option<Release>, // Newer release to mention, until dismissed
+}
+
+impl PerformanceApp {
+    pub fn new(state: SharedAppState, config: SharedConfig) -> Self {
+        let ui_state = UiState::load();
+        let hardware = state.read().poller.hardware_fingerprint.clone();
+        let baseline = SessionSummary::default_dir()
+            .ok()
+            .and_then(|dir| SessionSummary::load_latest(&dir, &hardware));
+        let update_check = config.read().check_updates.then(update::spawn_check);
+        Self {
+            state,
+            config,
+            ui_scale_edit: None,
+            ui_state: ui_state.clone(),
+            saved_ui_state: ui_state,
+            show_alerts: false,
+            imported: Vec::new(),
+            overlay: None,
+            show_import: false,
+            import_path: String::new(),
+            import_error: None,
+            export_status: None,
+            sample_rate: None,
+            menu_threshold: None,
+            sleep_inhibitor: None,
+            keep_awake_error: None,
+            crash_report: crash::take_previous_report().map(|path| (path, true)),
+            show_settings: false,
+            settings_tab: SettingsTab::General,
+            settings_path: SettingsBundle::default_path()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            settings_status: None,
+            sensor_assistant: SensorAssistant::default(),
+            editing_dashboard: false,
+            new_expression: String::new(),
+            spilled_plots: HashMap::new(),
+            baseline,
+            show_sessions: false,
+            sessions: Vec::new(),
+            opened_session: None,
+            session_tags: String::new(),
+            session_filter: SessionFilter::default(),
+            session_dates: (String::new(), String::new()),
+            regression_pair: (None, None),
+            sessions_status: None,
+            session_summary: None,
+            update_check,
+            update: None,
+        }
+    }
+    
+    // One line under the toolbar when the startup check found a newer release
+    fn render_update_banner(&mut self, ui: &mut egui::Ui) {
+        if let Some(check) = &self.update_check {
+            match check.try_recv() {
+                Ok(release) => {
+                    self.update = Some(release);
+                    self.update_check = None;
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => self.update_check = None,
+                Err(std::sync::mpsc::TryRecvError::Empty) => {}
+            }
+        }
+        let Some(release) = &self.update else {
+            return;
+        };
+        let mut dismissed = false;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("⬆ Version {} is available", release.version())).color(egui::Color32::LIGHT_BLUE));
+            ui.hyperlink_to("Release notes", &release.html_url);
+            dismissed = ui.small_button("Dismiss").clicked();
+        });
+        if dismissed {
+            self.update = None;
+        }
+    }
+    
+    // Persist UI preferences changed since the last frame
+    fn save_ui_state(&mut self) {
+        if self.ui_state == self.saved_ui_state {
+            return;
+        }
+        if let Err(e) = self.ui_state.save() {
+            logger::log_error("Failed to save UI state", &*e);
+        }
+        self.saved_ui_state = self.ui_state.clone();
+    }
+    
+    // Never in read-only mode, which may come from the command line rather than the file
+    fn save_config(&self) {
+        if self.config.read().read_only {
+            return;
+        }
+        if let Err(e) = self.config.read().save() {
+            logger::log_error("Failed to save config", &*e);
+        }
+    }
+    
+    // Default hardware layout plus one tab per custom dashboard
+    fn render_dashboard_tabs(&mut self, ui: &mut egui::Ui) {
+        let names: Vec<String> = self.config.read().dashboards.iter().map(|d| d.name.clone()).collect();
+        if self.ui_state.active_dashboard.is_some_and(|index| index >= names.len()) {
+            self.ui_state.active_dashboard = None;
+        }
+        
+        ui.horizontal(|ui| {
+            if ui.selectable_label(self.ui_state.active_dashboard.is_none(), "Hardware").clicked() {
+                self.ui_state.active_dashboard = None;
+                self.editing_dashboard = false;
+            }
+            for (index, name) in names.iter().enumerate() {
+                if ui.selectable_label(self.ui_state.active_dashboard == Some(index), name).clicked() {
+                    self.ui_state.active_dashboard = Some(index);
+                    self.editing_dashboard = false;
+                }
+            }
+            let read_only = self.config.read().read_only;
+            if ui.add_enabled(!read_only, egui::Button::new("+")).on_hover_text("New dashboard").clicked() {
+                self.config.write().dashboards.push(DashboardConfig {
+                    name: format!("Dashboard {}", names.len() + 1),
+                    metrics: Vec::new(),
+                    charts: Vec::new(),
+                });
+                self.save_config();
+                self.ui_state.active_dashboard = Some(names.len());
+                self.editing_dashboard = true;
+            }
+        });
+    }
+    
+    fn render_custom_dashboard(&mut self, ui: &mut egui::Ui, index: usize) {
+        let Some(dashboard) = self.config.read().dashboards.get(index).cloned() else {
+            return;
+        };
+        
+        ui.horizontal(|ui| {
+            if self.config.read().read_only {
+                self.editing_dashboard = false;
+            } else {
+                ui.toggle_value(&mut self.editing_dashboard, "Edit");
+            }
+        });
+        if self.editing_dashboard {
+            self.render_dashboard_editor(ui, index, dashboard.clone());
+            ui.separator();
+        }
+        
+        if dashboard.metrics.is_empty() && dashboard.charts.is_empty() {
+            ui.label("No metrics on this dashboard yet. Click Edit to choose some.");
+            return;
+        }
+        
+        let state_handle = self.state.clone();
+        let state = state_handle.read();
+        for chart in &dashboard.charts {
+            render_dual_axis_plot(ui, chart, &state);
+        }
+        let metric_ids: Vec<&str> = dashboard.metrics.iter().map(String::as_str).collect();
+        self.render_metric_columns(ui, &metric_ids, &state);
+    }
+    
+    // Rename, pick metrics for, or delete a custom dashboard; saved as it is edited
+    fn render_dashboard_editor(&mut self, ui: &mut egui::Ui, index: usize, mut dashboard: DashboardConfig) {
+        let mut deleted = false;
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            ui.text_edit_singleline(&mut dashboard.name);
+            if ui.button("Delete Dashboard").clicked() {
+                deleted = true;
+            }
+        });
+        
+        ui.label("Metrics:");
+        egui::Grid::new("dashboard_metrics_grid").num_columns(3).show(ui, |ui| {
+            for (position, descriptor) in METRIC_DESCRIPTORS.iter().map(MetricDescriptor::displayed).enumerate() {
+                let mut shown = dashboard.metrics.iter().any(|id| id == descriptor.id);
+                if ui.checkbox(&mut shown, descriptor.label).changed() {
+                    if shown {
+                        dashboard.metrics.push(descriptor.id.to_string());
+                    } else {
+                        dashboard.metrics.retain(|id| id != descriptor.id);
+                    }
+                }
+                if position % 3 == 2 {
+                    ui.end_row();
+                }
+            }
+        });
+        
+        ui.label("Expressions:");
+        let mut removed = None;
+        for (position, expression) in dashboard.metrics.iter().enumerate().filter(|(_, id)| !query::is_metric_id(id)) {
+            ui.horizontal(|ui| {
+                ui.monospace(expression);
+                if ui.small_button("Remove").clicked() {
+                    removed = Some(position);
+                }
+            });
+        }
+        if let Some(position) = removed {
+            dashboard.metrics.remove(position);
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.new_expression).hint_text("avg(cpu.core_utilization[*])").desired_width(300.0));
+            let parsed = query::parse(self.new_expression.trim());
+            let valid = parsed.is_ok() && !self.new_expression.trim().is_empty();
+            let add = ui.add_enabled(valid, egui::Button::new("Add Expression"));
+            if let Err(e) = &parsed {
+                if !self.new_expression.trim().is_empty() {
+                    ui.colored_label(egui::Color32::LIGHT_RED, e.to_string());
+                }
+            }
+            if add.clicked() {
+                dashboard.metrics.push(self.new_expression.trim().to_string());
+                self.new_expression.clear();
+            }
+        });
+        
+        ui.label("Dual-axis charts:");
+        let mut removed = None;
+        for (position, chart) in dashboard.charts.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label("Left:");
+                metric_combo(ui, ("dual_axis_left", position), &mut chart.left);
+                ui.label("Right:");
+                metric_combo(ui, ("dual_axis_right", position), &mut chart.right);
+                if ui.button("Remove").clicked() {
+                    removed = Some(position);
+                }
+            });
+        }
+        if let Some(position) = removed {
+            dashboard.charts.remove(position);
+        }
+        if ui.button("Add Chart").clicked() {
+            dashboard.charts.push(DualAxisChart {
+                left: "gpu.package_temperature".to_string(),
+                right: "motherboard.chassis_fan_speed".to_string(),
+            });
+        }
+        
+        let mut config = self.config.write();
+        if deleted {
+            config.dashboards.remove(index);
+            self.ui_state.active_dashboard = None;
+            self.editing_dashboard = false;
+        } else if config.dashboards[index] != dashboard {
+            config.dashboards[index] = dashboard;
+        } else {
+            return;
+        }
+        drop(config);
+        self.save_config();
+    }
+    
+    fn apply_theme(&self, ctx: &egui::Context, frame: &eframe::Frame) {
+        let dark = match self.config.read().theme {
+            Theme::Dark => true,
+            Theme::Light => false,
+            Theme::System => frame.info().system_theme.is_none_or(|theme| theme == eframe::Theme::Dark),
+        };
+        if ctx.style().visuals.dark_mode != dark {
+            ctx.set_visuals(if dark { egui::Visuals::dark() } else { egui::Visuals::light() });
+        }
+    }
+    
+    // Edits a copy of the config and writes it back (and to disk) as soon as anything
+    // changes; everything but the helper is re-read live by the code that uses it
+    fn render_settings_window(&mut self, ctx: &egui::Context) {
+        if !self.show_settings {
+            return;
+        }
+        let mut draft = self.config.read().clone();
+        let read_only = draft.read_only;
+        let selections = self.state.read().poller.monitor_selection.clone();
+        
+        let mut open = true;
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for tab in SettingsTab::ALL {
+                        ui.selectable_value(&mut self.settings_tab, tab, tab.label());
+                    }
+                });
+                ui.separator();
+                
+                if read_only {
+                    ui.label(egui::RichText::new("Read-only mode: settings can be viewed but not changed")
+                        .color(egui::Color32::YELLOW));
+                }
+                ui.add_enabled_ui(!read_only, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| match self.settings_tab {
+                        SettingsTab::General => settings_general(ui, &mut draft),
+                        SettingsTab::Monitors => settings_monitors(ui, &mut draft, &selections),
+                        SettingsTab::Alerts => settings_alerts(ui, &mut draft),
+                        SettingsTab::Exporters => settings_exporters(ui, &mut draft),
+                        SettingsTab::Appearance => settings_appearance(ui, &mut draft),
+                        SettingsTab::Sensors => settings_sensors(ui, &mut draft, &mut self.sensor_assistant),
+                    });
+                });
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.label("Settings file:");
+                    ui.text_edit_singleline(&mut self.settings_path);
+                    let has_path = !self.settings_path.trim().is_empty();
+                    if ui.add_enabled(has_path, egui::Button::new("Export settings"))
+                        .on_hover_text("Write config, alert thresholds and layout to one file")
+                        .clicked()
+                    {
+                        self.export_settings(&draft);
+                    }
+                    if ui.add_enabled(has_path && !read_only, egui::Button::new("Import settings"))
+                        .on_hover_text("Replace the current config and layout with the file's")
+                        .clicked()
+                    {
+                        if let Some(config) = self.import_settings() {
+                            draft = config;
+                        }
+                    }
+                });
+                if let Some(status) = &self.settings_status {
+                    ui.label(status);
+                }
+            });
+        self.show_settings = open;
+        
+        if read_only || draft == *self.config.read() {
+            return;
+        }
+        if draft.metric_overrides != self.config.read().metric_overrides {
+            model::set_display_overrides(&draft.metric_overrides);
+        }
+        if draft.sensor_mappings != self.config.read().sensor_mappings {
+            lm_sensors::set_mappings(&draft.sensor_mappings);
+        }
+        if draft.drive_rated_tbw != self.config.read().drive_rated_tbw {
+            crate::monitors::smart::set_rated_tbw(&draft.drive_rated_tbw);
+        }
+        if draft.anonymize != self.config.read().anonymize {
+            let salt = draft.ensure_anonymize_salt();
+            anonymize::configure(draft.anonymize, salt);
+        }
+        self.state.write().polling_interval_ms = draft.polling_interval_ms;
+        let mut config = self.config.write();
+        *config = draft;
+        if let Err(e) = config.save() {
+            logger::log_error("Failed to save config", &*e);
+        }
+    }
+    
+    fn export_settings(&mut self, config: &AppConfig) {
+        let path = std::path::PathBuf::from(self.settings_path.trim());
+        let bundle = SettingsBundle { config: config.clone(), ui: self.ui_state.clone() };
+        self.settings_status = Some(match bundle.save_to(&path) {
+            Ok(()) => {
+                logger::log_info(&format!("Exported settings to {}", path.display()));
+                format!("Exported settings to {}", path.display())
+            }
+            Err(e) => {
+                logger::log_error("Failed to export settings", &*e);
+                format!("Export failed: {}", e)
+            }
+        });
+    }
+    
+    // Layout applies at once; the config is returned to replace the settings draft
+    fn import_settings(&mut self) -> Option<AppConfig> {
+        let path = std::path::PathBuf::from(self.settings_path.trim());
+        match SettingsBundle::load_from(&path) {
+            Ok(bundle) => {
+                logger::log_info(&format!("Imported settings from {}", path.display()));
+                let actions = bundle.alert_actions();
+                self.settings_status = Some(if actions.is_empty() {
+                    format!("Imported settings from {}", path.display())
+                } else {
+                    format!(
+                        "Imported settings from {}. These alert actions will ask before running: {}",
+                        path.display(),
+                        actions.join(", ")
+                    )
+                });
+                let config = bundle.config_for(&self.config.read());
+                self.ui_state = bundle.ui;
+                Some(config)
+            }
+            Err(e) => {
+                logger::log_error("Failed to import settings", &*e);
+                self.settings_status = Some(format!("Import failed: {}", e));
+                None
+            }
+        }
+    }
+    
+    // Hold or release the sleep inhibit to match the "keep awake" setting
+    fn sync_keep_awake(&mut self) {
+        let keep_awake = self.config.read().keep_awake && !self.config.read().read_only;
+        if !keep_awake {
+            self.sleep_inhibitor = None;
+            return;
+        }
+        if let Some(inhibitor) = &mut self.sleep_inhibitor {
+            if !inhibitor.is_active() {
+                self.sleep_inhibitor = None;
+                self.keep_awake_error = Some("The system refused to stay awake".to_string());
+                logger::log_warning("Sleep inhibit was released by the system");
+            }
+        } else if self.keep_awake_error.is_none() {
+            match SleepInhibitor::acquire("Recording performance metrics") {
+                Ok(inhibitor) => {
+                    logger::log_info("Keeping the system awake while recording");
+                    self.sleep_inhibitor = Some(inhibitor);
+                }
+                Err(e) => {
+                    logger::log_error("Failed to keep the system awake", &*e);
+                    self.keep_awake_error = Some(format!("Couldn't keep the system awake: {}", e));
+                }
+            }
+        }
+    }
+    
+    fn apply_ui_scale(&self, ctx: &egui::Context) {
+        // Explicit override takes precedence over the OS-provided scaling factor
+        let ui_scale = self.config.read().ui_scale;
+        let target = ui_scale.unwrap_or_else(|| ctx.native_pixels_per_point().unwrap_or(1.0));
+        if (ctx.pixels_per_point() - target).abs() > f32::EPSILON {
+            ctx.set_pixels_per_point(target);
+        }
+    }
+    
+    fn set_ui_scale(&self, ui_scale: Option<f32>) {
+        self.config.write().ui_scale = ui_scale;
+        self.save_config();
+    }
+    
+    fn render_toolbar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("UI Scale:");
+            
+            let current = self.ui_scale_edit
+                .or(self.config.read().ui_scale)
+                .unwrap_or_else(|| ui.ctx().pixels_per_point());
+            let mut value = current;
+            let response = ui.add(
+                egui::Slider::new(&mut value, MIN_UI_SCALE..=MAX_UI_SCALE)
+                    .step_by(0.05)
+                    .fixed_decimals(2)
+            );
+            
+            // Changing the scale mid-drag moves the slider under the cursor, so only
+            // commit once the drag is released (or the value was typed in)
+            if response.dragged() {
+                self.ui_scale_edit = Some(value);
+            } else if response.drag_stopped() || response.changed() {
+                self.ui_scale_edit = None;
+                self.set_ui_scale(Some(value));
+            }
+            
+            let has_override = self.config.read().ui_scale.is_some();
+            if ui.add_enabled(has_override, egui::Button::new("Use OS Scaling")).clicked() {
+                self.ui_scale_edit = None;
+                self.set_ui_scale(None);
+            }
+            
+            ui.separator();
+            
+            let unacknowledged = self.state.read().alerts.unacknowledged_count();
+            let alerts_label = if unacknowledged > 0 {
+                egui::RichText::new(format!("Alerts ({})", unacknowledged)).color(egui::Color32::LIGHT_RED)
+            } else {
+                egui::RichText::new("Alerts")
+            };
+            if ui.selectable_label(self.show_alerts, alerts_label).clicked() {
+                self.show_alerts = !self.show_alerts;
+            }
+            if ui.selectable_label(self.show_settings, "Settings").clicked() {
+                self.show_settings = !self.show_settings;
+            }
+            if ui.selectable_label(self.show_sessions, "Sessions").clicked() {
+                self.show_sessions = !self.show_sessions;
+                if self.show_sessions {
+                    self.reload_sessions();
+                }
+            }
+            
+            ui.separator();
+            
+            let (mut keep_awake, read_only) = {
+                let config = self.config.read();
+                (config.keep_awake && !config.read_only, config.read_only)
+            };
+            let toggle = ui.add_enabled(!read_only, egui::Checkbox::new(&mut keep_awake, "Keep awake while recording"))
+                .on_hover_text(self.keep_awake_error.as_deref().unwrap_or("Prevent sleep and display blanking during long recordings"));
+            if toggle.changed() {
+                self.keep_awake_error = None;
+                self.config.write().keep_awake = keep_awake;
+                self.save_config();
+            }
+            if self.keep_awake_error.is_some() {
+                ui.label(egui::RichText::new("⚠").color(egui::Color32::YELLOW));
+            }
+            
+            ui.separator();
+            
+            if ui.selectable_label(self.show_import, "Import Log").clicked() {
+                self.show_import = !self.show_import;
+            }
+            if ui.button("Copy Values").on_hover_text("Copy current readings to the clipboard").clicked() {
+                let text = self.state.read().snapshot().to_text();
+                ui.ctx().copy_text(text);
+            }
+            
+            #[cfg(feature = "parquet")]
+            if ui.button("Export Parquet").on_hover_text("Write the full session for analysis in pandas/polars").clicked() {
+                self.export_parquet();
+            }
+            if !self.imported.is_empty() {
+                let selected = self.overlay
+                    .and_then(|index| self.imported.get(index))
+                    .map_or("None", |session| session.name.as_str());
+                egui::ComboBox::from_label("Compare with")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.overlay, None, "None");
+                        for (index, session) in self.imported.iter().enumerate() {
+                            ui.selectable_value(&mut self.overlay, Some(index), &session.name);
+                        }
+                    });
+            }
+        });
+    }
+    
+    #[cfg(feature = "parquet")]
+    fn export_parquet(&mut self) {
+        let state = self.state.read();
+        let result = export::default_export_path(&state, "parquet")
+            .and_then(|path| export::export_parquet(&state, &path).map(|rows| (path, rows)));
+        self.export_status = Some(match result {
+            Ok((path, rows)) => {
+                logger::log_info(&format!("Exported {} samples to {}", rows, path.display()));
+                format!("Exported {} samples to {}", rows, path.display())
+            }
+            Err(e) => {
+                logger::log_error("Failed to export session", &*e);
+                format!("Export failed: {}", e)
+            }
+        });
+    }
+    
+    fn import_log(&mut self) {
+        let path = std::path::PathBuf::from(self.import_path.trim());
+        match import::import_log(&path) {
+            Ok(session) => {
+                logger::log_info(&format!(
+                    "Imported {} log {} ({} samples, {} metrics)",
+                    session.format.name(),
+                    session.name,
+                    session.samples,
+                    session.metric_ids.len()
+                ));
+                self.imported.push(session);
+                self.overlay = Some(self.imported.len() - 1);
+                self.import_error = None;
+            }
+            Err(e) => {
+                logger::log_error("Failed to import log", &*e);
+                self.import_error = Some(e.to_string());
+            }
+        }
+    }
+    
+    // Offer the report from a panic in this session, or a crash that ended the last one
+    fn render_crash_dialog(&mut self, ctx: &egui::Context) {
+        if self.crash_report.is_none() {
+            self.crash_report = crash::take_new_report().map(|path| (path, false));
+        }
+        let Some((path, previous_run)) = self.crash_report.clone() else {
+            return;
+        };
+        
+        let mut dismissed = false;
+        egui::Window::new("Crash Report")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if previous_run {
+                    ui.label("The dashboard crashed last time it ran.");
+                } else {
+                    ui.label("Part of the dashboard crashed and was stopped.");
+                }
+                ui.label("A report with the error, backtrace, recent log and detected hardware was saved to:");
+                ui.monospace(path.display().to_string());
+                ui.label("Please attach it when filing a bug report.");
+                
+                ui.horizontal(|ui| {
+                    if ui.button("Open Report").clicked() {
+                        if let Err(e) = crash::open_report(&path) {
+                            logger::log_error("Failed to open crash report", &e);
+                        }
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        dismissed = true;
+                    }
+                });
+            });
+        if dismissed {
+            self.crash_report = None;
+        }
+    }
+    
+    fn render_import_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_import;
+        egui::Window::new("Import Log")
+            .open(&mut open)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.label("HWiNFO64 or GPU-Z CSV sensor log:");
+                ui.horizontal(|ui| {
+                    let response = ui.text_edit_singleline(&mut self.import_path);
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    let can_import = !self.import_path.trim().is_empty();
+                    if (ui.add_enabled(can_import, egui::Button::new("Import")).clicked() || submitted) && can_import {
+                        self.import_log();
+                    }
+                });
+                
+                if let Some(error) = &self.import_error {
+                    ui.label(egui::RichText::new(error).color(egui::Color32::LIGHT_RED));
+                }
+                
+                if self.imported.is_empty() {
+                    return;
+                }
+                ui.separator();
+                
+                let mut removed = None;
+                egui::Grid::new("imported_sessions_grid")
+                    .striped(true)
+                    .num_columns(5)
+                    .show(ui, |ui| {
+                        for header in ["File", "Format", "Samples", "Metrics", ""] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        
+                        for (index, session) in self.imported.iter().enumerate() {
+                            ui.label(&session.name);
+                            ui.label(session.format.name());
+                            ui.label(session.samples.to_string());
+                            ui.label(session.metric_ids.len().to_string());
+                            if ui.button("Remove").clicked() {
+                                removed = Some(index);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                
+                if let Some(index) = removed {
+                    self.imported.remove(index);
+                    self.overlay = match self.overlay {
+                        Some(selected) if selected == index => None,
+                        Some(selected) if selected > index => Some(selected - 1),
+                        other => other,
+                    };
+                }
+            });
+        self.show_import = open;
+    }
+    
+    fn render_poller_status(&self, ui: &mut egui::Ui) {
+        let mut state = self.state.write();
+        
+        // Stale data means polling has stalled even if the watchdog hasn't acted yet
+        let stale_after = chrono::Duration::milliseconds((state.polling_interval_ms * 3).max(5000) as i64);
+        if let Some(last_poll) = state.poller.last_poll {
+            let age = chrono::Utc::now() - last_poll;
+            if age > stale_after {
+                ui.label(egui::RichText::new(format!(
+                    "⚠ Data is stale: last sensor update {}s ago",
+                    age.num_seconds()
+                )).color(egui::Color32::LIGHT_RED));
+            }
+        }
+        
+        if let Some(until) = state.poller.burst_until {
+            ui.label(egui::RichText::new(format!(
+                "⚡ Burst sampling every {}ms for another {}s",
+                state.polling_interval_ms,
+                (until - chrono::Utc::now()).num_seconds().max(0)
+            )).color(egui::Color32::LIGHT_BLUE));
+        }
+        
+        let Some(error) = state.poller.last_error.clone() else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("⚠ {}", error)).color(egui::Color32::LIGHT_RED));
+            if state.poller.restarts > 0 {
+                ui.label(format!("(restarts: {})", state.poller.restarts));
+            }
+            if ui.small_button("Dismiss").clicked() {
+                state.poller.last_error = None;
+            }
+        });
+    }
+    
+    // Whether data is flowing, at a glance: last poll, monitors, sample rate,
+    // recording and export state, and monitor errors not shown elsewhere
+    fn render_status_bar(&mut self, ctx: &egui::Context) {
+        let state_handle = self.state.clone();
+        let state = state_handle.read();
+        
+        // Averaged over about a second, so the figure is readable
+        let published = state.poller.samples_published;
+        let now = std::time::Instant::now();
+        match self.sample_rate {
+            Some((measured, _, _)) if now.duration_since(measured) < STATUS_RATE_PERIOD => {}
+            Some((measured, count, _)) => {
+                let rate = published.saturating_sub(count) as f64 / now.duration_since(measured).as_secs_f64();
+                self.sample_rate = Some((now, published, rate));
+            }
+            None => self.sample_rate = Some((now, published, 0.0)),
+        }
+        
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                match state.poller.last_poll {
+                    Some(last_poll) => {
+                        let age = (chrono::Utc::now() - last_poll).num_milliseconds().max(0) as f64 / 1000.0;
+                        ui.label(format!("Last poll: {:.1}s ago", age));
+                    }
+                    None => {
+                        ui.label("Waiting for the first poll");
+                    }
+                }
+                ui.separator();
+                
+                if self.config.read().read_only {
+                    ui.label(egui::RichText::new("Read-only").color(egui::Color32::LIGHT_BLUE))
+                        .on_hover_text("Alert actions, keep awake and settings changes are disabled");
+                    ui.separator();
+                }
+                ui.label(format!("Monitors: {} active", state.poller.active_monitors()));
+                if let Some((_, _, rate)) = self.sample_rate {
+                    ui.separator();
+                    ui.label(format!("{:.1} samples/s", rate));
+                }
+                ui.separator();
+                
+                let mut recording = format!("Recording {}s", (chrono::Utc::now() - state.session_start).num_seconds());
+                if state.history_spill.is_some() {
+                    recording.push_str(", spilling to disk");
+                }
+                if self.sleep_inhibitor.is_some() {
+                    recording.push_str(", keeping awake");
+                }
+                ui.label(recording);
+                if let Some(status) = &self.export_status {
+                    ui.separator();
+                    ui.label(status);
+                }
+                
+                // Monitors quietly failing each poll, and any the watchdog gave up on
+                let errors: Vec<String> = state.poller.monitor_timings
+                    .iter()
+                    .filter_map(|timing| timing.error.as_ref().map(|error| format!("{}: {}", timing.name, error)))
+                    .chain(state.poller.disabled_monitors.iter().map(|name| format!("{}: disabled", name)))
+                    .collect();
+                if !errors.is_empty() {
+                    ui.separator();
+                    ui.label(egui::RichText::new(format!("⚠ {} monitor errors", errors.len())).color(egui::Color32::YELLOW))
+                        .on_hover_text(errors.join("\n"));
+                }
+            });
+        });
+    }
+    
+    fn render_trend_warnings(&self, ui: &mut egui::Ui) {
+        let state = self.state.read();
+        for warning in &state.alerts.predictions {
+            let descriptor = MetricDescriptor::find(&warning.rule.metric).map(MetricDescriptor::displayed);
+            let label = descriptor.as_ref().map_or(warning.rule.metric.as_str(), |d| d.label.as_str());
+            let threshold = descriptor.as_ref().map_or_else(
+                || format!("{:.1}", warning.rule.threshold),
+                |d| d.format_value(warning.rule.threshold),
+            );
+            let eta = if warning.eta_secs < 90.0 {
+                format!("~{:.0} s", warning.eta_secs)
+            } else {
+                format!("~{:.0} min", warning.eta_secs / 60.0)
+            };
+            ui.label(egui::RichText::new(format!(
+                "⚠ {} will hit {} in {} at current trend",
+                label, threshold, eta
+            )).color(egui::Color32::YELLOW));
+        }
+    }
+    
+    fn render_pending_actions(&self, ctx: &egui::Context) {
+        if self.state.read().alerts.pending_actions.iter().all(|pending| pending.confirmed) {
+            return;
+        }
+        
+        // Confirmation prompts stay on screen until answered
+        egui::Window::new("Confirm Alert Action")
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctx, |ui| {
+                let mut state = self.state.write();
+                let mut dismissed = None;
+                
+                for (index, pending) in state.alerts.pending_actions.iter().enumerate() {
+                    if pending.confirmed {
+                        continue;
+                    }
+                    let label = MetricDescriptor::find(&pending.rule.metric)
+                        .map_or(pending.rule.metric.clone(), |d| d.displayed().label);
+                    ui.label(format!(
+                        "{} reached {} at {}.",
+                        label,
+                        pending.rule.threshold,
+                        pending.requested.with_timezone(&chrono::Local).format("%H:%M:%S")
+                    ));
+                    ui.monospace(pending.action.command_line());
+                    ui.horizontal(|ui| {
+                        if ui.button("Run").clicked() {
+                            dismissed = Some((index, true));
+                        }
+                        if ui.button("Dismiss").clicked() {
+                            dismissed = Some((index, false));
+                        }
+                    });
+                    ui.separator();
+                }
+                
+                match dismissed {
+                    Some((index, true)) => state.alerts.confirm_action(index),
+                    Some((index, false)) => state.alerts.dismiss_action(index),
+                    None => {}
+                }
+            });
+    }
+    
+    fn render_alerts_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_alerts;
+        egui::Window::new("Alerts")
+            .open(&mut open)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                let mut state = self.state.write();
+                let now = chrono::Utc::now();
+                
+                if state.alerts.records.is_empty() {
+                    ui.label("No threshold breaches this session.");
+                    return;
+                }
+                
+                ui.horizontal(|ui| {
+                    if ui.button("Acknowledge All").clicked() {
+                        state.alerts.acknowledge_all();
+                    }
+                    let firing = &state.alerts.firing;
+                    ui.label(format!(
+                        "Alerts fired {} times, active for {}s in total",
+                        firing.activations,
+                        firing.active_duration().num_seconds()
+                    ));
+                });
+                ui.separator();
+                
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("alert_history_grid")
+                        .striped(true)
+                        .num_columns(7)
+                        .show(ui, |ui| {
+                            for header in ["Metric", "Started", "Duration", "Peak", "Status", "", ""] {
+                                ui.strong(header);
+                            }
+                            ui.end_row();
+                            
+                            // Newest first
+                            for index in (0..state.alerts.records.len()).rev() {
+                                let record = state.alerts.records[index].clone();
+                                let descriptor = MetricDescriptor::find(&record.rule.metric).map(MetricDescriptor::displayed);
+                                let label = descriptor.as_ref().map_or(record.rule.metric.as_str(), |d| d.label.as_str());
+                                let peak = descriptor.as_ref().map_or_else(
+                                    || format!("{:.1}", record.peak),
+                                    |d| d.format_value(record.peak),
+                                );
+                                let color = match record.rule.severity {
+                                    AlertSeverity::Critical => egui::Color32::LIGHT_RED,
+                                    AlertSeverity::Warning => egui::Color32::YELLOW,
+                                };
+                                
+                                ui.label(egui::RichText::new(label).color(color));
+                                ui.label(record.started.with_timezone(&chrono::Local).format("%H:%M:%S").to_string());
+                                ui.label(format!("{}s", record.duration_secs(now)));
+                                ui.label(peak);
+                                ui.label(if record.is_active() { "Active" } else { "Cleared" });
+                                
+                                if record.acknowledged {
+                                    ui.label("Acknowledged");
+                                } else if ui.button("Acknowledge").clicked() {
+                                    state.alerts.acknowledge(index);
+                                }
+                                
+                                let muted = state.alerts.is_muted(&record.rule);
+                                let mute_label = if muted { "Unmute" } else { "Mute for Session" };
+                                if ui.button(mute_label).clicked() {
+                                    state.alerts.set_muted(&record.rule, !muted);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        self.show_alerts = open;
+    }
+    
+    // One metric formatted from its descriptor; used by the sections and custom dashboards
+    fn render_metric_section(&mut self, ui: &mut egui::Ui, metric_id: &str, state: &AppState) {
+        if self.ui_state.is_hidden(metric_id) {
+            return;
+        }
+        if !query::is_metric_id(metric_id) {
+            render_query_section(ui, metric_id, state);
+            return;
+        }
+        if let (Some(descriptor), Some(event)) = (MetricDescriptor::find(metric_id), state.event(metric_id)) {
+            let title = render_event_section(ui, &descriptor.displayed(), event, state.session_start, self.ui_state.color(metric_id));
+            self.metric_menu(&title, metric_id, state);
+            return;
+        }
+        let (Some(descriptor), Some(series)) = (MetricDescriptor::find(metric_id).map(MetricDescriptor::displayed), state.metric(metric_id)) else {
+            return;
+        };
+        let current = series.current.map(|value| descriptor.format_value(value));
+        let range = series
+            .session_min
+            .zip(series.session_max)
+            .map(|(min, max)| (descriptor.format_value(min), descriptor.format_value(max)));
+        self.render_series_section(ui, metric_id, current, range, state);
+    }
+    
+    // Metric sections filled left to right across as many columns as the width allows
+    fn render_metric_columns(&mut self, ui: &mut egui::Ui, metric_ids: &[&str], state: &AppState) {
+        let count = column_count(ui.available_width()).min(metric_ids.len().max(1));
+        ui.columns(count, |columns| {
+            for (position, metric_id) in metric_ids.iter().enumerate() {
+                self.render_metric_section(&mut columns[position % count], metric_id, state);
+            }
+        });
+    }
+    
+    // Reloaded only when more samples were spilled since the last frame
+    fn spilled_plot_data(&mut self, metric_id: &str, spilled: usize, state: &AppState) -> Vec<(f64, f64)> {
+        if spilled == 0 {
+            return Vec::new();
+        }
+        if let Some((count, data)) = self.spilled_plots.get(metric_id) {
+            if *count == spilled {
+                return data.clone();
+            }
+        }
+        let data = state.spilled_plot_data(metric_id, SPILLED_PLOT_POINTS);
+        self.spilled_plots.insert(metric_id.to_string(), (spilled, data.clone()));
+        data
+    }
+    
+    // Title, current/min/max and plot for one metric; values arrive already formatted
+    fn render_series_section(
+        &mut self,
+        ui: &mut egui::Ui,
+        metric_id: &str,
+        current: Option<String>,
+        range: Option<(String, String)>,
+        state: &AppState,
+    ) {
+        let (Some(descriptor), Some(series)) = (MetricDescriptor::find(metric_id).map(MetricDescriptor::displayed), state.metric(metric_id)) else {
+            return;
+        };
+        let title = descriptor.label.as_str();
+        let unit = descriptor.unit.as_str();
+        let session_start = state.session_start;
+        
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                // Hovering the title shows which monitor produced the latest value
+                let source = match state.metric_sources.get(metric_id) {
+                    Some(MetricSource { monitor, detail: Some(detail) }) => format!("Source: {} ({})", monitor, detail),
+                    Some(MetricSource { monitor, detail: None }) => format!("Source: {}", monitor),
+                    None => "Source: no data yet".to_string(),
+                };
+                let title = ui.add(egui::Label::new(egui::RichText::new(title).heading()).sense(egui::Sense::click()));
+                self.metric_menu(&title.on_hover_text(source), metric_id, state);
+                
+                let popped_out = self.ui_state.popped_out.iter().any(|id| id == metric_id);
+                if ui.add_enabled(!popped_out, egui::Button::new("⏏").small())
+                    .on_hover_text("Pop out into its own window")
+                    .clicked()
+                {
+                    self.ui_state.popped_out.push(metric_id.to_string());
+                }
+            });
+            
+            ui.horizontal(|ui| {
+                // Current value
+                if let Some(current) = &current {
+                    ui.label(format!("Current: {}", current));
+                } else {
+                    ui.label("Current: N/A");
+                }
+                
+                ui.separator();
+                
+                // Session min/max
+                if let Some((min, max)) = &range {
+                    ui.label(format!("Min: {}", min));
+                    ui.label(format!("Max: {}", max));
+                } else {
+                    ui.label("Min: N/A");
+                    ui.label("Max: N/A");
+                }
+            });
+            
+            // Same statistics for the imported recording being compared against
+            let overlay = self.overlay
+                .and_then(|index| self.imported.get(index))
+                .and_then(|session| {
+                    let series = session.state.metric(metric_id)?;
+                    let bounds = series.session_min.zip(series.session_max)?;
+                    Some((series.plot_data(session.state.session_start), bounds))
+                });
+            if let Some((_, (min, max))) = &overlay {
+                ui.label(egui::RichText::new(format!(
+                    "Imported  Min: {}  Max: {}",
+                    descriptor.format_value(*min),
+                    descriptor.format_value(*max)
+                )).color(egui::Color32::GRAY));
+            }
+            
+            // Slowest and fastest unit right now, e.g. across all cores for the average clock
+            let spread = plot_spread(state, metric_id);
+            if let Some(PlotSpread { current: (low, high), .. }) = &spread {
+                let format = |value: &Option<f64>| value.map_or_else(|| "N/A".to_string(), |v| descriptor.format_value(v));
+                ui.label(format!("Now  Lowest: {}  Highest: {}", format(low), format(high)));
+            }
+            
+            // Plot - always show, even if no data. History spilled to disk is read back
+            // (averaged) so the zoomed-out view still covers the whole session
+            let bounds = series.session_min.zip(series.session_max);
+            let mut plot_data = self.spilled_plot_data(metric_id, series.spilled, state);
+            plot_data.extend(series.plot_data(session_start));
+            render_metric_plot(
+                ui,
+                title,
+                unit,
+                plot_data,
+                bounds,
+                session_start,
+                100.0,
+                overlay,
+                spread,
+                self.ui_state.color(metric_id),
+            );
+        });
+    }
+    
+    // Right-click menu on a metric's title for the per-metric tweaks: pinning, hiding,
+    // an alert threshold, the line color, and getting its history out
+    fn metric_menu(&mut self, response: &egui::Response, metric_id: &str, state: &AppState) {
+        response.context_menu(|ui| {
+            let pinned = self.ui_state.pinned.iter().any(|id| id == metric_id);
+            if pinned && ui.button("Unpin from top bar").clicked() {
+                self.ui_state.pinned.retain(|id| id != metric_id);
+                ui.close_menu();
+            } else if !pinned && ui.button("Pin to top bar").clicked() {
+                self.ui_state.pinned.push(metric_id.to_string());
+                ui.close_menu();
+            }
+            if ui.button("Hide").clicked() {
+                self.ui_state.hidden.push(metric_id.to_string());
+                ui.close_menu();
+            }
+            ui.separator();
+            
+            // Edits the metric's first alert rule, or starts one at the current value
+            let rule = self.config.read().alerts.rules.iter().position(|rule| rule.metric == metric_id);
+            let mut threshold = match &self.menu_threshold {
+                Some((id, threshold)) if id == metric_id => *threshold,
+                _ => match rule {
+                    Some(index) => self.config.read().alerts.rules[index].threshold,
+                    None => state.current_value(metric_id).unwrap_or_default(),
+                },
+            };
+            let read_only = self.config.read().read_only;
+            ui.add_enabled_ui(!read_only, |ui| ui.horizontal(|ui| {
+                ui.label("Alert above");
+                ui.add(egui::DragValue::new(&mut threshold).speed(1.0));
+                let label = if rule.is_some() { "Set" } else { "Add" };
+                if ui.button(label).clicked() {
+                    let mut config = self.config.write();
+                    match rule {
+                        Some(index) => config.alerts.rules[index].threshold = threshold,
+                        None => config.alerts.rules.push(AlertRule::new(metric_id, threshold, AlertSeverity::Warning)),
+                    }
+                    drop(config);
+                    self.save_config();
+                    ui.close_menu();
+                }
+            }));
+            self.menu_threshold = Some((metric_id.to_string(), threshold));
+            
+            ui.horizontal(|ui| {
+                ui.label("Color");
+                for (name, rgb) in PLOT_COLORS {
+                    let [r, g, b] = *rgb;
+                    let swatch = egui::Button::new("  ").fill(egui::Color32::from_rgb(r, g, b));
+                    if ui.add(swatch).on_hover_text(*name).clicked() {
+                        self.ui_state.colors.insert(metric_id.to_string(), *rgb);
+                    }
+                }
+                if ui.add_enabled(self.ui_state.colors.contains_key(metric_id), egui::Button::new("Default")).clicked() {
+                    self.ui_state.colors.remove(metric_id);
+                }
+            });
+            ui.separator();
+            
+            if ui.button("Copy history").on_hover_text("Timestamp and value per line, as CSV").clicked() {
+                ui.ctx().copy_text(export::metric_csv(state, metric_id));
+                ui.close_menu();
+            }
+            if ui.button("Export to CSV").clicked() {
+                self.export_metric(state, metric_id);
+                ui.close_menu();
+            }
+        });
+        // Start from the saved threshold again next time the menu opens
+        if !response.context_menu_opened() && self.menu_threshold.as_ref().is_some_and(|(id, _)| id == metric_id) {
+            self.menu_threshold = None;
+        }
+    }
+    
+    fn export_metric(&mut self, state: &AppState, metric_id: &str) {
+        let result = export::default_export_path(state, &format!("{}.csv", metric_id))
+            .and_then(|path| std::fs::write(&path, export::metric_csv(state, metric_id)).map(|_| path).map_err(Into::into));
+        self.export_status = Some(match result {
+            Ok(path) => {
+                logger::log_info(&format!("Exported {} to {}", metric_id, path.display()));
+                format!("Exported {} to {}", metric_id, path.display())
+            }
+            Err(e) => {
+                logger::log_error("Failed to export metric", &*e);
+                format!("Export failed: {}", e)
+            }
+        });
+    }
+    
+    // Pinned metrics with their value and recent trend, visible at any scroll position
+    fn render_pinned_bar(&mut self, ctx: &egui::Context) {
+        if self.ui_state.pinned.is_empty() {
+            return;
+        }
+        let state_handle = self.state.clone();
+        egui::TopBottomPanel::top("pinned_metrics").show(ctx, |ui| {
+            let state = state_handle.read();
+            ui.horizontal_wrapped(|ui| {
+                for metric_id in self.ui_state.pinned.clone() {
+                    let Some(descriptor) = MetricDescriptor::find(&metric_id).map(MetricDescriptor::displayed) else {
+                        continue;
+                    };
+                    let points = match (state.metric(&metric_id), state.event(&metric_id)) {
+                        (Some(series), _) => {
+                            let recent = series.history.len().saturating_sub(SPARKLINE_SAMPLES);
+                            series.history
+                                .range(recent..)
+                                .map(|(timestamp, value)| (model::elapsed_seconds(state.session_start, *timestamp), *value))
+                                .collect()
+                        }
+                        (None, Some(event)) => event.step_plot_data(state.session_start),
+                        (None, None) => Vec::new(),
+                    };
+                    let current = state.current_value(&metric_id).map_or_else(|| "N/A".to_string(), |value| descriptor.format_value(value));
+                    
+                    let label = ui.group(|ui| {
+                        ui.horizontal(|ui| {
+                            let label = ui.vertical(|ui| {
+                                let label = ui.add(egui::Label::new(egui::RichText::new(descriptor.label).small()).sense(egui::Sense::click()));
+                                ui.strong(current);
+                                label
+                            }).inner;
+                            render_sparkline(ui, &metric_id, points, self.ui_state.color(&metric_id));
+                            label
+                        }).inner
+                    }).inner;
+                    self.metric_menu(&label, &metric_id, &state);
+                }
+            });
+        });
+    }
+    
+    fn render_popped_out_plots(&mut self, ctx: &egui::Context) {
+        let state_handle = self.state.clone();
+        let mut closed = Vec::new();
+        
+        for metric_id in &self.ui_state.popped_out {
+            let Some(descriptor) = MetricDescriptor::find(metric_id).map(MetricDescriptor::displayed) else {
+                closed.push(metric_id.clone());
+                continue;
+            };
+            
+            let color = self.ui_state.color(metric_id);
+            let viewport_id = egui::ViewportId::from_hash_of(("popped_out_plot", metric_id));
+            let builder = egui::ViewportBuilder::default()
+                .with_title(&descriptor.label)
+                .with_inner_size([420.0, 220.0])
+                .with_always_on_top();
+            
+            ctx.show_viewport_immediate(viewport_id, builder, |ctx, _class| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let state = state_handle.read();
+                    let Some(metric) = state.metric(metric_id) else {
+                        return;
+                    };
+                    
+                    ui.horizontal(|ui| {
+                        let format = |value: Option<f64>| {
+                            value.map_or_else(|| "N/A".to_string(), |v| descriptor.format_value(v))
+                        };
+                        ui.label(format!("Current: {}", format(metric.current)));
+                        ui.separator();
+                        ui.label(format!("Min: {}", format(metric.session_min)));
+                        ui.label(format!("Max: {}", format(metric.session_max)));
+                    });
+                    
+                    let bounds = metric.session_min.zip(metric.session_max);
+                    let height = ui.available_height();
+                    render_metric_plot(
+                        ui,
+                        &descriptor.label,
+                        &descriptor.unit,
+                        metric.plot_data(state.session_start),
+                        bounds,
+                        state.session_start,
+                        height,
+                        None,
+                        plot_spread(&state, metric_id),
+                        color,
+                    );
+                });
+                
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    closed.push(metric_id.clone());
+                }
+            });
+        }
+        
+        self.ui_state.popped_out.retain(|id| !closed.contains(id));
+    }
+    
+    // Hardware layout: one collapsible node per device with data, nested as in
+    // AppState::device_tree, so missing devices simply don't appear
+    fn render_device_tree(&mut self, ui: &mut egui::Ui) {
+        let state_handle = self.state.clone();
+        let state = state_handle.read();
+        let tree = state.device_tree();
+        if tree.children.is_empty() {
+            ui.label(egui::RichText::new("No hardware data yet").color(egui::Color32::GRAY));
+            ui.separator();
+            return;
+        }
+        for node in &tree.children {
+            self.render_device_node(ui, node, &state);
+            ui.separator();
+        }
+    }
+    
+    fn render_device_node(&mut self, ui: &mut egui::Ui, node: &DeviceNode, state: &AppState) {
+        let mut title = node.name.clone();
+        if let (DeviceKind::Gpu, Some(used), Some(total)) = (node.kind, state.gpu.memory_utilization.current, state.gpu.memory_total_mb) {
+            if !node.metrics.is_empty() {
+                title.push_str(&format!(" ({} / {} MB VRAM)", used, total));
+            }
+        }
+        if let Some(disk) = state.storage.disks.iter().filter(|_| node.kind == DeviceKind::Disk).find(|disk| disk.name == node.name) {
+            let speed = |metric: &MetricValue| metric.current.map_or_else(|| "N/A".to_string(), |v| format!("{:.1}", v));
+            title.push_str(&format!(" ({} / {} MB/s read / write)", speed(&disk.read_speed), speed(&disk.write_speed)));
+        }
+        
+        // Devices start expanded, except per-unit breakdowns, until the user says otherwise
+        let default_open = !matches!(node.kind, DeviceKind::Cores | DeviceKind::Ccds | DeviceKind::Disk);
+        let toggled = self.ui_state.toggled_devices.contains(&node.id);
+        let response = CollapsingHeader::new(title)
+            .id_source(("device", &node.id))
+            .default_open(default_open != toggled)
+            .show(ui, |ui| {
+                self.render_device_metrics(ui, node, state);
+                for child in &node.children {
+                    self.render_device_node(ui, child, state);
+                }
+            });
+        
+        let is_toggled = (response.openness > 0.5) != default_open;
+        if is_toggled && !toggled {
+            self.ui_state.toggled_devices.push(node.id.clone());
+        } else if !is_toggled && toggled {
+            self.ui_state.toggled_devices.retain(|id| *id != node.id);
+        }
+    }
+    
+    fn render_device_metrics(&mut self, ui: &mut egui::Ui, node: &DeviceNode, state: &AppState) {
+        match node.kind {
+            DeviceKind::Cores => return render_core_utilization(ui, state),
+            DeviceKind::Ccds => return render_ccd_temperatures(ui, state),
+            DeviceKind::Disk => return render_disk_io(ui, state, node),
+            // Enumerated by the backend, which only reads the first GPU
+            DeviceKind::Gpu if node.metrics.is_empty() && node.id != "system/gpus/gpu0" => {
+                ui.label(egui::RichText::new("Not monitored: metrics are read from GPU0 only").weak());
+                return;
+            }
+            _ => {}
+        }
+        
+        // Metrics drawn together on one chart rather than each on its own
+        let mut combined: Vec<&str> = METRIC_SPREADS.iter().flat_map(|(_, low, high)| [*low, *high]).collect();
+        if node.kind == DeviceKind::Battery {
+            render_battery_summary(ui, state);
+        }
+        if node.kind == DeviceKind::Memory && state.memory.cached_mb.current.is_some() {
+            render_memory_composition(ui, state);
+            combined.extend(["memory.utilization_mb", "memory.cached_mb", "memory.free_mb", "memory.swap_used_mb"]);
+        }
+        if node.kind == DeviceKind::Gpu {
+            // Fan-stop cards read 0 at low load; say so rather than look like a dead sensor
+            if state.gpu.fan_stop.active == Some(true) {
+                ui.label(egui::RichText::new("🌀 Fan stop: fans are off at low load (0 RPM is normal)")
+                    .color(egui::Color32::LIGHT_BLUE));
+            }
+            combined.push("gpu.fan_stop");
+            if state.gpu.driver_reset.active == Some(true) {
+                ui.label(egui::RichText::new("⚠ GPU driver reset: reconnecting to the driver")
+                    .color(egui::Color32::LIGHT_RED));
+            }
+            // Graphics, memory and video clocks together once the backend reports them
+            let clocks = ["gpu.clock_speed", "gpu.memory_clock_speed", "gpu.video_clock_speed"];
+            if state.gpu.memory_clock_speed.current.is_some() || state.gpu.video_clock_speed.current.is_some() {
+                render_multi_metric_chart(ui, "GPU Clocks", &clocks, state);
+                combined.extend(clocks);
+            }
+            // Which engine is saturated: graphics for games, compute for ML, copy for transfers
+            let engines = ["gpu.graphics_utilization", "gpu.compute_utilization", "gpu.copy_utilization"];
+            if engines.iter().any(|id| state.current_value(id).is_some()) {
+                render_multi_metric_chart(ui, "GPU Engines", &engines, state);
+                combined.extend(engines);
+            }
+        }
+        
+        let shown: Vec<&str> = node.metrics.iter().copied().filter(|id| !combined.contains(id) && !self.ui_state.is_hidden(id)).collect();
+        self.render_metric_columns(ui, &shown, state);
+        
+        // Hidden from this device's metrics via their context menu
+        let hidden: Vec<&str> = node.metrics.iter().copied().filter(|id| self.ui_state.is_hidden(id)).collect();
+        if !hidden.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("{} hidden", hidden.len())).color(egui::Color32::GRAY));
+                if ui.small_button("Show").clicked() {
+                    self.ui_state.hidden.retain(|id| !hidden.contains(&id.as_str()));
+                }
+            });
+        }
+        
+        match node.kind {
+            DeviceKind::Cpu => {
+                render_top_processes(ui, state);
+                render_temperature_sensors(ui, "cpu", &state.cpu.temperature_sensors);
+            }
+            DeviceKind::Gpu => {
+                render_gpu_processes(ui, state);
+                render_temperature_sensors(ui, "gpu", &state.gpu.temperature_sensors);
+            }
+            DeviceKind::Drive => {
+                render_io_trace(ui, state);
+                render_drive_health(ui, state);
+                render_storage_pools(ui, state);
+                render_drive_endurance(ui, state);
+                render_temperature_sensors(ui, "storage", &state.storage.temperature_sensors);
+            }
+            DeviceKind::Network => render_network_interfaces(ui, state),
+            DeviceKind::Power => render_power_summary(ui, state),
+            DeviceKind::Motherboard => render_temperature_sensors(ui, "motherboard", &state.motherboard.temperature_sensors),
+            DeviceKind::Clock => {
+                if let Some(source) = &state.clock.ntp_source {
+                    ui.label(egui::RichText::new(format!("Measured by {}", source)).weak());
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    // Whether the configured polling rate is actually achieved on this machine
+    fn render_polling_section(&mut self, ui: &mut egui::Ui) {
+        let state_handle = self.state.clone();
+        let state = state_handle.read();
+        
+        CollapsingHeader::new("Polling Performance")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(format!("Configured interval: {} ms", state.polling_interval_ms));
+                self.render_metric_columns(ui, &["poller.cycle_duration", "poller.jitter"], &state);
+            });
+    }
+    
+    // Session averages against the last session on the same hardware
+    fn render_session_summary_section(&mut self, ui: &mut egui::Ui) {
+        if self.session_summary.as_ref().is_none_or(|(computed, _)| computed.elapsed() >= SUMMARY_REFRESH) {
+            let summary = SessionSummary::from_state(&self.state.read(), false);
+            self.session_summary = Some((std::time::Instant::now(), summary));
+        }
+        let Some((_, summary)) = &self.session_summary else {
+            return;
+        };
+        
+        CollapsingHeader::new("Session Summary")
+            .default_open(true)
+            .show(ui, |ui| {
+                let Some(baseline) = &self.baseline else {
+                    ui.label("No earlier session on this hardware to compare against yet.");
+                    return;
+                };
+                let started = baseline.session_start.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+                let minutes = baseline.duration().num_minutes();
+                ui.label(format!("Compared with the run started {} ({} min)", started, minutes));
+                for (name, before, now) in [
+                    ("GPU driver", &baseline.versions.gpu_driver, &summary.versions.gpu_driver),
+                    ("BIOS", &baseline.versions.bios, &summary.versions.bios),
+                    ("Kernel", &baseline.versions.kernel, &summary.versions.kernel),
+                ] {
+                    if let (Some(before), Some(now)) = (before, now) {
+                        if before != now {
+                            ui.colored_label(egui::Color32::YELLOW, format!("{} changed: {} → {}", name, before, now));
+                        }
+                    }
+                }
+                
+                let differences = regression::compare(baseline, summary);
+                let average_difference = |id: &str| {
+                    differences
+                        .iter()
+                        .find(|difference| difference.metric_id == id && difference.statistic == regression::Statistic::Average)
+                };
+                let regressions = differences.iter().filter(|difference| difference.regression).count();
+                if regressions > 0 {
+                    ui.colored_label(egui::Color32::LIGHT_RED, format!("⚠ {} significant regressions against that run", regressions));
+                }
+                
+                egui::Grid::new("session_summary_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Metric");
+                        ui.strong("Average");
+                        ui.strong("vs last run");
+                        ui.end_row();
+                        for descriptor in METRIC_DESCRIPTORS.iter().map(MetricDescriptor::displayed) {
+                            let (Some(current), Some(delta)) = (
+                                summary.metrics.get(descriptor.id),
+                                summary.average_delta(baseline, descriptor.id),
+                            ) else {
+                                continue;
+                            };
+                            let decimals = descriptor.kind.decimals();
+                            ui.label(&descriptor.label);
+                            ui.label(descriptor.format_value(current.average));
+                            let change = format!("{:+.*}{}", decimals, delta, descriptor.unit);
+                            match average_difference(descriptor.id) {
+                                Some(difference) if difference.regression => {
+                                    ui.colored_label(egui::Color32::LIGHT_RED, format!("{} ⚠", change));
+                                }
+                                Some(difference) if difference.significant => {
+                                    ui.strong(change);
+                                }
+                                _ => {
+                                    ui.weak(change);
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+    
+    fn reload_sessions(&mut self) {
+        self.sessions = SessionSummary::default_dir()
+            .map(|dir| SessionSummary::load_all(&dir))
+            .unwrap_or_default();
+        self.sessions_status = None;
+    }
+    
+    // Every saved session, to compare against, export or delete
+    fn render_sessions_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_sessions;
+        let read_only = self.config.read().read_only;
+        let mut compared = None;
+        let mut exported = None;
+        let mut deleted = None;
+        egui::Window::new("Sessions")
+            .open(&mut open)
+            .default_width(720.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} saved sessions", self.sessions.len()));
+                    if ui.button("Refresh").clicked() {
+                        self.reload_sessions();
+                    }
+                });
+                ui.label(egui::RichText::new("Sessions keep per-metric statistics, not samples; use Import Log to replay a recording").weak());
+                if let Some(status) = &self.sessions_status {
+                    ui.label(status);
+                }
+                if self.sessions.is_empty() {
+                    ui.label("No sessions saved yet. One is saved when the dashboard closes after a minute or more.");
+                    return;
+                }
+                ui.separator();
+                
+                let this_machine = self.state.read().poller.hardware_fingerprint.clone();
+                self.render_session_filter(ui, &this_machine);
+                
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("sessions_grid")
+                        .striped(true)
+                        .num_columns(6)
+                        .show(ui, |ui| {
+                            for header in ["Started", "Duration", "Machine", "Tags", "Headline", ""] {
+                                ui.strong(header);
+                            }
+                            ui.end_row();
+                            
+                            for (index, (path, summary)) in self.sessions.iter().enumerate() {
+                                if !self.session_filter.matches(summary) {
+                                    continue;
+                                }
+                                ui.label(summary.session_start.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string());
+                                ui.label(format!("{} min", summary.duration().num_minutes()));
+                                let machine = if summary.hardware == this_machine { "This machine" } else { "Other" };
+                                ui.label(machine).on_hover_text(&summary.hardware);
+                                ui.label(summary.tags.join(", "));
+                                ui.label(session_headline(summary));
+                                ui.horizontal(|ui| {
+                                    let opened = self.opened_session.as_ref() == Some(path);
+                                    if ui.selectable_label(opened, "Open").on_hover_text("Show every metric's statistics and tags").clicked() {
+                                        self.opened_session = if opened { None } else { Some(path.clone()) };
+                                        self.session_tags = summary.tags.join(", ");
+                                    }
+                                    if ui.button("Compare").on_hover_text("Compare this session's averages with it under Session Summary").clicked() {
+                                        compared = Some(index);
+                                    }
+                                    if ui.button("Export").on_hover_text("Write its statistics as CSV next to the config file").clicked() {
+                                        exported = Some(index);
+                                    }
+                                    if ui.add_enabled(!read_only, egui::Button::new("Delete")).clicked() {
+                                        deleted = Some(index);
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+                
+                self.render_regression_check(ui);
+                
+                let Some(opened) = self.sessions.iter().position(|(path, _)| self.opened_session.as_ref() == Some(path)) else {
+                    return;
+                };
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Tags:");
+                    ui.add_enabled(!read_only, egui::TextEdit::singleline(&mut self.session_tags).hint_text("stock, undervolt -50mV, summer"));
+                    if ui.add_enabled(!read_only, egui::Button::new("Save Tags")).clicked() {
+                        self.save_session_tags(opened);
+                    }
+                });
+                let summary = &self.sessions[opened].1;
+                egui::Grid::new("opened_session_grid")
+                    .striped(true)
+                    .num_columns(5)
+                    .show(ui, |ui| {
+                        for header in ["Metric", "Average", "Min", "Max", "Samples"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        for descriptor in METRIC_DESCRIPTORS.iter().map(MetricDescriptor::displayed) {
+                            let Some(metric) = summary.metrics.get(descriptor.id) else {
+                                continue;
+                            };
+                            ui.label(&descriptor.label);
+                            ui.label(descriptor.format_value(metric.average));
+                            ui.label(descriptor.format_value(metric.min));
+                            ui.label(descriptor.format_value(metric.max));
+                            ui.label(metric.samples.to_string());
+                            ui.end_row();
+                        }
+                    });
+            });
+        self.show_sessions = open;
+        
+        if let Some(index) = compared {
+            let summary = self.sessions[index].1.clone();
+            let started = summary.session_start.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+            self.sessions_status = Some(format!("Comparing with the session started {}", started));
+            self.baseline = Some(summary);
+        }
+        if let Some(index) = exported {
+            self.export_session(index);
+        }
+        if let Some(index) = deleted {
+            let (path, _) = self.sessions.remove(index);
+            if self.opened_session.as_ref() == Some(&path) {
+                self.opened_session = None;
+            }
+            self.sessions_status = Some(match std::fs::remove_file(&path) {
+                Ok(()) => {
+                    logger::log_info(&format!("Deleted session summary {}", path.display()));
+                    format!("Deleted {}", path.display())
+                }
+                Err(e) => {
+                    logger::log_error("Failed to delete session summary", &e);
+                    format!("Delete failed: {}", e)
+                }
+            });
+        }
+    }
+    
+    // Statistically significant differences between two saved sessions
+    fn render_regression_check(&mut self, ui: &mut egui::Ui) {
+        ui.separator();
+        let session_name = |path: &Option<std::path::PathBuf>| {
+            self.sessions
+                .iter()
+                .find(|(saved, _)| Some(saved) == path.as_ref())
+                .map_or("Choose a session".to_string(), |(_, summary)| session_label(summary))
+        };
+        let (before_name, after_name) = (session_name(&self.regression_pair.0), session_name(&self.regression_pair.1));
+        ui.horizontal(|ui| {
+            ui.strong("Regression check");
+            for (label, selected, name) in [
+                ("Before:", &mut self.regression_pair.0, before_name),
+                ("After:", &mut self.regression_pair.1, after_name),
+            ] {
+                ui.label(label);
+                egui::ComboBox::from_id_source(label)
+                    .selected_text(name)
+                    .show_ui(ui, |ui| {
+                        for (path, summary) in &self.sessions {
+                            ui.selectable_value(selected, Some(path.clone()), session_label(summary));
+                        }
+                    });
+            }
+        });
+        
+        let find = |path: &Option<std::path::PathBuf>| self.sessions.iter().find(|(saved, _)| Some(saved) == path.as_ref());
+        let (Some((_, before)), Some((_, after))) = (find(&self.regression_pair.0), find(&self.regression_pair.1)) else {
+            return;
+        };
+        let differences: Vec<Difference> = regression::compare(before, after)
+            .into_iter()
+            .filter(|difference| difference.significant)
+            .collect();
+        if differences.is_empty() {
+            ui.label("No significant differences between these sessions.");
+            return;
+        }
+        let regressions = differences.iter().filter(|difference| difference.regression).count();
+        if regressions > 0 {
+            ui.colored_label(egui::Color32::LIGHT_RED, format!("⚠ {} regressions", regressions));
+        }
+        egui::Grid::new("regression_grid")
+            .striped(true)
+            .num_columns(4)
+            .show(ui, |ui| {
+                for header in ["Metric", "Before", "After", "Change"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for difference in &differences {
+                    let Some(descriptor) = MetricDescriptor::find(difference.metric_id).map(MetricDescriptor::displayed) else {
+                        continue;
+                    };
+                    let (before, after, change) = match difference.statistic {
+                        regression::Statistic::Energy => (
+                            format!("{:.1} Wh", difference.before),
+                            format!("{:.1} Wh", difference.after),
+                            format!("{:+.1} Wh", difference.delta()),
+                        ),
+                        _ => (
+                            descriptor.format_value(difference.before),
+                            descriptor.format_value(difference.after),
+                            format!("{:+.*}{}", descriptor.kind.decimals(), difference.delta(), descriptor.unit),
+                        ),
+                    };
+                    ui.label(format!("{} ({})", descriptor.label, difference.statistic.name()));
+                    ui.label(before);
+                    ui.label(after);
+                    if difference.regression {
+                        ui.colored_label(egui::Color32::LIGHT_RED, format!("{} ⚠", change));
+                    } else {
+                        ui.label(change);
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+    
+    // Search, machine and date range narrowing the session list
+    fn render_session_filter(&mut self, ui: &mut egui::Ui, this_machine: &str) {
+        let mut machines: Vec<String> = self.sessions.iter().map(|(_, summary)| summary.hardware.clone()).collect();
+        machines.sort();
+        machines.dedup();
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.add(egui::TextEdit::singleline(&mut self.session_filter.query).hint_text("tag or hardware").desired_width(140.0));
+            let machine_name = |hardware: &str| if hardware == this_machine { "This machine".to_string() } else { hardware.to_string() };
+            egui::ComboBox::from_id_source("session_machine_filter")
+                .selected_text(self.session_filter.hardware.as_deref().map_or("Any machine".to_string(), machine_name))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.session_filter.hardware, None, "Any machine");
+                    for machine in machines {
+                        let name = machine_name(&machine);
+                        ui.selectable_value(&mut self.session_filter.hardware, Some(machine), name);
+                    }
+                });
+            
+            // Dates as YYYY-MM-DD; a field that doesn't parse is shown in red and ignored
+            let (from, to) = &mut self.session_dates;
+            for (label, text, bound) in [("From:", from, &mut self.session_filter.from), ("To:", to, &mut self.session_filter.to)] {
+                ui.label(label);
+                let invalid = bound.is_none() && !text.trim().is_empty();
+                let mut edit = egui::TextEdit::singleline(text).hint_text("YYYY-MM-DD").desired_width(90.0);
+                if invalid {
+                    edit = edit.text_color(egui::Color32::LIGHT_RED);
+                }
+                ui.add(edit);
+                *bound = chrono::NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok();
+            }
+        });
+    }
+    
+    fn save_session_tags(&mut self, index: usize) {
+        let (path, summary) = &mut self.sessions[index];
+        summary.set_tags(&self.session_tags);
+        self.session_tags = summary.tags.join(", ");
+        self.sessions_status = Some(match summary.rewrite(path) {
+            Ok(()) => format!("Saved tags for {}", path.display()),
+            Err(e) => {
+                logger::log_error("Failed to save session tags", &*e);
+                format!("Saving tags failed: {}", e)
+            }
+        });
+    }
+    
+    fn export_session(&mut self, index: usize) {
+        let summary = &self.sessions[index].1;
+        let result = crate::paths::data_dir().and_then(|mut path| {
+            let started = summary.session_start.with_timezone(&chrono::Local).format("%Y%m%d_%H%M%S");
+            path.push(format!("session_{}_summary.csv", started));
+            std::fs::write(&path, summary.to_csv())?;
+            Ok(path)
+        });
+        self.sessions_status = Some(match result {
+            Ok(path) => {
+                logger::log_info(&format!("Exported session summary to {}", path.display()));
+                format!("Exported to {}", path.display())
+            }
+            Err(e) => {
+                logger::log_error("Failed to export session summary", &*e);
+                format!("Export failed: {}", e)
+            }
+        });
+    }
+    
+    // Saved when the app closes, as the baseline for the next session
+    fn save_session_summary(&self) {
+        let state = self.state.read();
+        if (chrono::Utc::now() - state.session_start).num_seconds() < MIN_SESSION_SECONDS {
+            return;
+        }
+        let summary = SessionSummary::from_state(&state, true);
+        if let Err(e) = SessionSummary::default_dir().and_then(|dir| summary.save_to(&dir)) {
+            logger::log_error("Failed to save session summary", &*e);
+        }
+    }
+    
+    // Derived metrics, alerts and report sections from the user's scripts
+    fn render_scripts_section(&self, ui: &mut egui::Ui) {
+        let state = self.state.read();
+        let scripts = &state.scripts;
+        let title = if scripts.alerts.is_empty() {
+            format!("Scripts ({})", scripts.loaded.len())
+        } else {
+            format!("Scripts ({}) ⚠ {}", scripts.loaded.len(), scripts.alerts.len())
+        };
+        
+        CollapsingHeader::new(title)
+            .id_source("scripts_section")
+            .default_open(!scripts.alerts.is_empty())
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let dir = crate::scripting::default_dir().map(|dir| dir.display().to_string()).unwrap_or_default();
+                    ui.label(format!("*.{} files in {}", crate::scripting::SCRIPT_EXTENSION, dir));
+                    if ui.button("Reload").on_hover_text("Load the scripts again after editing them").clicked() {
+                        crate::scripting::request_reload();
+                    }
+                });
+                if scripts.loaded.is_empty() && scripts.errors.is_empty() {
+                    ui.label("No scripts loaded.");
+                    return;
+                }
+                if !scripts.loaded.is_empty() {
+                    ui.label(format!("Loaded: {}", scripts.loaded.join(", ")));
+                }
+                for error in &scripts.errors {
+                    ui.colored_label(egui::Color32::LIGHT_RED, error);
+                }
+                for alert in &scripts.alerts {
+                    ui.colored_label(egui::Color32::YELLOW, format!("⚠ {} ({})", alert.message, alert.script));
+                }
+                
+                for (name, metric) in &scripts.metrics {
+                    ui.group(|ui| {
+                        ui.strong(name);
+                        let format = |value: Option<f64>| value.map_or_else(|| "N/A".to_string(), |value| format!("{:.2}", value));
+                        ui.label(format!(
+                            "Current: {}  Min: {}  Max: {}",
+                            format(metric.current),
+                            format(metric.session_min),
+                            format(metric.session_max)
+                        ));
+                        render_metric_plot(
+                            ui,
+                            name,
+                            "",
+                            metric.plot_data(state.session_start),
+                            metric.session_min.zip(metric.session_max),
+                            state.session_start,
+                            60.0,
+                            None,
+                            None,
+                            None,
+                        );
+                    });
+                }
+                
+                for (script, report) in &scripts.reports {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.strong(script);
+                        if ui.small_button("📋").on_hover_text("Copy this report").clicked() {
+                            ui.ctx().copy_text(report.clone());
+                        }
+                    });
+                    ui.monospace(report);
+                }
+            });
+    }
+    
+    // Versions worth quoting alongside any thermal or performance comparison
+    fn render_system_info_section(&self, ui: &mut egui::Ui) {
+        let versions = self.state.read().poller.versions.clone();
+        let unknown = |version: &Option<String>| version.clone().unwrap_or_else(|| "Unknown".to_string());
+        let rows = [
+            ("App version", env!("CARGO_PKG_VERSION").to_string()),
+            ("OS", format!("{} {}", std::env::consts::OS, std::env::consts::ARCH)),
+            ("Kernel", unknown(&versions.kernel)),
+            ("GPU driver", unknown(&versions.gpu_driver)),
+            ("BIOS", unknown(&versions.bios)),
+        ];
+        
+        CollapsingHeader::new("System Info")
+            .default_open(false)
+            .show(ui, |ui| {
+                egui::Grid::new("system_info_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for (name, value) in &rows {
+                            ui.label(*name);
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                    });
+                let hover = if anonymize::enabled() {
+                    "Copy for a bug report or forum post, with identifiers replaced by pseudonyms"
+                } else {
+                    "Copy for a bug report or forum post"
+                };
+                if ui.button("📋 Copy").on_hover_text(hover).clicked() {
+                    let text: Vec<String> = rows.iter().map(|(name, value)| format!("{}: {}", name, value)).collect();
+                    ui.ctx().copy_text(anonymize::scrub(&text.join("\n")));
+                }
+            });
+    }
+    
+    fn render_backends_section(&mut self, ui: &mut egui::Ui) {
+        let state = self.state.read();
+        let timings = &state.poller.monitor_timings;
+        let budget_ms = DEFAULT_MONITOR_BUDGET.as_millis();
+        
+        CollapsingHeader::new("Sensor Backends")
+            .default_open(false)
+            .show(ui, |ui| {
+                // Which data path each vendor takes on this machine
+                ui.label(format!("Detected hardware: {}", state.poller.hardware_summary));
+                let mut toggled = None;
+                egui::Grid::new("monitor_selection_grid")
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        for selection in &state.poller.monitor_selection {
+                            let status = if selection.selected { "✔" } else { "✖" };
+                            ui.label(format!("{} {}", status, selection.name));
+                            ui.label(egui::RichText::new(&selection.reason).weak());
+                            
+                            // Registered monitors can be switched off, e.g. while a driver misbehaves
+                            if selection.selected {
+                                let mut enabled = self.config.read().monitor_enabled(&selection.name);
+                                let read_only = self.config.read().read_only;
+                                if ui.add_enabled(!read_only, egui::Checkbox::new(&mut enabled, "Enabled")).changed() {
+                                    toggled = Some((selection.name.clone(), enabled));
+                                }
+                            } else {
+                                ui.label("");
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if let Some((name, enabled)) = toggled {
+                    self.config.write().set_monitor_enabled(&name, enabled);
+                    self.save_config();
+                }
+                
+                // Diagnoses a mis-mapped sensor from the log, without a debug build
+                if ui.button("🔍 Trace sensors")
+                    .on_hover_text("Log every raw sensor label and value, and where it was mapped, for the next polling cycle")
+                    .clicked()
+                {
+                    crate::logger::request_sensor_trace();
+                }
+                ui.separator();
+                
+                if timings.is_empty() {
+                    ui.label("No monitors have been polled yet.");
+                    return;
+                }
+                
+                egui::Grid::new("sensor_backends_grid")
+                    .striped(true)
+                    .num_columns(5)
+                    .show(ui, |ui| {
+                        for header in ["Monitor", "Last Poll", "Slow Polls", "Cadence", "Status"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        
+                        for timing in timings {
+                            ui.label(&timing.name);
+                            
+                            let duration = format!("{:.1} ms", timing.last_duration_ms);
+                            if timing.last_duration_ms > budget_ms as f64 {
+                                ui.label(egui::RichText::new(duration).color(egui::Color32::YELLOW));
+                            } else {
+                                ui.label(duration);
+                            }
+                            
+                            ui.label(timing.slow_polls.to_string());
+                            
+                            if timing.cadence > 1 {
+                                ui.label(egui::RichText::new(format!("every {} polls (backed off)", timing.cadence))
+                                    .color(egui::Color32::YELLOW));
+                            } else {
+                                ui.label("every poll");
+                            }
+                            
+                            match &timing.error {
+                                Some(error) => {
+                                    let status = ui.label(egui::RichText::new(error).color(egui::Color32::YELLOW));
+                                    if let Some(hint) = timing.hint {
+                                        status.on_hover_text(format!("Try: {}", hint));
+                                    }
+                                }
+                                None => {
+                                    ui.label("OK");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                
+                if !state.poller.disabled_monitors.is_empty() {
+                    ui.label(format!("Disabled: {}", state.poller.disabled_monitors.join(", ")));
+                }
+                ui.label(format!("Budget per monitor: {} ms", budget_ms));
+            });
+    }
+}
+
+impl eframe::App for PerformanceApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.save_session_summary();
+    }
+    
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // Redraw at the plot refresh rate rather than every display frame; input
+        // (hovering a plot, dragging) still repaints immediately
+        let refresh_hz = self.config.read().plot_refresh_hz;
+        ctx.request_repaint_after(std::time::Duration::from_secs_f32(1.0 / refresh_hz));
+        
+        self.apply_ui_scale(ctx);
+        self.apply_theme(ctx, frame);
+        self.sync_keep_awake();
+        self.render_popped_out_plots(ctx);
+        self.render_alerts_window(ctx);
+        self.render_import_window(ctx);
+        self.render_sessions_window(ctx);
+        self.render_crash_dialog(ctx);
+        self.render_settings_window(ctx);
+        self.render_pending_actions(ctx);
+        self.render_pinned_bar(ctx);
+        self.render_status_bar(ctx);
+        self.save_ui_state();
+        
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Simple Performance Dashboard");
+            
+            self.render_toolbar(ui);
+            self.render_update_banner(ui);
+            self.render_poller_status(ui);
+            self.render_trend_warnings(ui);
+            
+            ui.separator();
+            self.render_dashboard_tabs(ui);
+            
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                if let Some(index) = self.ui_state.active_dashboard {
+                    self.render_custom_dashboard(ui, index);
+                    return;
+                }
+                
+                self.render_device_tree(ui);
+                
+                self.render_session_summary_section(ui);
                 ui.separator();
                 
-                self.render_gpu_section(ui);
+                self.render_scripts_section(ui);
                 ui.separator();
                 
-                self.render_memory_section(ui);
+                self.render_system_info_section(ui);
                 ui.separator();
                 
-                self.render_storage_section(ui);
+                self.render_backends_section(ui);
                 ui.separator();
                 
-                self.render_motherboard_section(ui);
+                self.render_polling_section(ui);
             });
         });
     }
 }
 
-pub fn run_app(state: SharedAppState) -> eframe::Result<()> {
+pub fn run_app(state: SharedAppState, config: SharedConfig) -> eframe::Result<()> {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1200.0, 800.0])
@@ -541,6 +3771,9 @@ pub fn run_app(state: SharedAppState) -> eframe::Result<()> {
     eframe::run_native(
         "Simple Performance Dashboard",
         options,
-        Box::new(|_cc| Ok(Box::new(PerformanceApp::new(state)))),
+        Box::new(|cc| {
+            instance::set_window(&cc.egui_ctx);
+            Ok(Box::new(PerformanceApp::new(state, config)))
+        }),
     )
 }
\ No newline at end of file
@@ -0,0 +1,99 @@
+use std::sync::mpsc::{self, Receiver};
+use serde::Deserialize;
+
+// `--version` prints the version; with `--check-update` too (or alone) it asks for the
+// latest release, for headless installs nobody looks at
+pub const VERSION_FLAG: &str = "--version";
+pub const CHECK_UPDATE_FLAG: &str = "--check-update";
+
+// Only the latest release is asked for; nothing about this machine or its use is sent
+pub const RELEASES_URL: &str = "https://api.github.com/repos/jwanga/simple_performance_dashboard/releases/latest";
+
+// The fields used from GitHub's release object
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub html_url: String,
+}
+
+impl Release {
+    pub fn version(&self) -> &str {
+        self.tag_name.trim().trim_start_matches('v')
+    }
+}
+
+// Numeric parts of a version like "v1.2.3" or "1.2.3-beta"; None if it isn't one
+pub fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim().trim_start_matches('v');
+    let release = version.split(['-', '+']).next()?;
+    release.split('.').map(|part| part.parse().ok()).collect()
+}
+
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    match (parse_version(candidate), parse_version(current)) {
+        (Some(candidate), Some(current)) => candidate > current,
+        _ => false,
+    }
+}
+
+#[cfg(feature = "update-check")]
+pub fn latest_release() -> Result<Release, Box<dyn std::error::Error>> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_secs(10))
+        .build();
+    // GitHub rejects requests without a User-Agent
+    let body = agent
+        .get(RELEASES_URL)
+        .set("Accept", "application/vnd.github+json")
+        .set("User-Agent", concat!("simple_performance_dashboard/", env!("CARGO_PKG_VERSION")))
+        .call()?
+        .into_string()?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+#[cfg(not(feature = "update-check"))]
+pub fn latest_release() -> Result<Release, Box<dyn std::error::Error>> {
+    Err("this build was compiled without update checking".into())
+}
+
+// The latest release if it is newer than this build
+pub fn check_for_update() -> Result<Option<Release>, Box<dyn std::error::Error>> {
+    let release = latest_release()?;
+    Ok(is_newer(release.version(), env!("CARGO_PKG_VERSION")).then_some(release))
+}
+
+// Check on a background thread so startup never waits on the network; the receiver
+// gets a release only if there is a newer one
+pub fn spawn_check() -> Receiver<Release> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || match check_for_update() {
+        Ok(Some(release)) => {
+            crate::logger::log_info(&format!("Version {} is available: {}", release.version(), release.html_url));
+            let _ = sender.send(release);
+        }
+        Ok(None) => crate::logger::log_info("Update check: this is the latest version"),
+        Err(e) => crate::logger::log_error("Update check failed", &*e),
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newer_versions_are_detected() {
+        assert_eq!(parse_version("v1.10.2"), Some(vec![1, 10, 2]));
+        assert_eq!(parse_version("2.0.0-beta.1"), Some(vec![2, 0, 0]));
+        assert_eq!(parse_version("nightly"), None);
+
+        assert!(is_newer("v0.2.0", "0.1.9"));
+        assert!(is_newer("0.1.10", "0.1.9"));
+        assert!(!is_newer("v0.1.0", "0.1.0"));
+        assert!(!is_newer("0.0.9", "0.1.0"));
+        assert!(!is_newer("latest", "0.1.0"));
+
+        let release = Release { tag_name: "v1.2.3".to_string(), html_url: String::new() };
+        assert_eq!(release.version(), "1.2.3");
+    }
+}
@@ -1,6 +1,12 @@
-use simple_performance_dashboard::model::AppState;
+#![allow(clippy::len_zero, clippy::manual_range_contains)]
+
+use simple_performance_dashboard::model::{AppState, SharedAppState};
+use simple_performance_dashboard::hardware_detection::{HardwareInfo, HardwareMonitor, MonitorError};
 use simple_performance_dashboard::hardware::HardwarePoller;
-use simple_performance_dashboard::ui::interpolate_data_value;
+use simple_performance_dashboard::config::AppConfig;
+use simple_performance_dashboard::alerts::{AlertAction, AlertRule};
+use simple_performance_dashboard::monitors::fault::FaultPlan;
+use simple_performance_dashboard::ui::{column_count, decimate_min_max, heatmap_color, interpolate_data_value, rescale, stack_series, SettingsBundle, UiState};
 use chrono::Utc;
 
 #[test]
@@ -86,11 +92,11 @@ fn test_data_availability_detection() {
     assert!(state.has_cpu_data());
     
     // Add GPU data
-    state.gpu.clock_speed.update(1500);
+    state.gpu.clock_speed.update(1500u32);
     assert!(state.has_gpu_data());
     
     // Add memory data
-    state.memory.utilization_mb.update(8192);
+    state.memory.utilization_mb.update(8192u32);
     assert!(state.has_memory_data());
     
     // Add storage data
@@ -119,7 +125,7 @@ fn test_metric_history_and_plotting() {
     
     // Test plot data generation
     let app_state = state.read();
-    let plot_data = app_state.cpu.utilization.get_plot_data(session_start);
+    let plot_data = app_state.cpu.utilization.plot_data(session_start);
     
     assert_eq!(plot_data.len(), 3);
     
@@ -195,9 +201,9 @@ fn test_metric_value_bounds_and_types() {
     state.cpu.package_temperature.update(65.2);
     
     // Integer metrics (frequencies, speeds, memory)
-    state.cpu.clock_speed.update(3400);
-    state.memory.utilization_mb.update(16384);
-    state.motherboard.aio_pump_speed.update(2500);
+    state.cpu.clock_speed.update(3400u32);
+    state.memory.utilization_mb.update(16384u32);
+    state.motherboard.aio_pump_speed.update(2500u32);
     
     // Boolean metrics (throttling status)
     state.cpu.thermal_throttling.update(false);
@@ -207,32 +213,117 @@ fn test_metric_value_bounds_and_types() {
     assert_eq!(state.cpu.utilization.current, Some(87.5));
     assert_eq!(state.cpu.core_voltage.current, Some(1.35));
     assert_eq!(state.cpu.package_temperature.current, Some(65.2));
-    assert_eq!(state.cpu.clock_speed.current, Some(3400));
-    assert_eq!(state.memory.utilization_mb.current, Some(16384));
-    assert_eq!(state.motherboard.aio_pump_speed.current, Some(2500));
-    assert_eq!(state.cpu.thermal_throttling.current, Some(false));
-    assert_eq!(state.gpu.thermal_throttling.current, Some(true));
+    assert_eq!(state.cpu.clock_speed.current, Some(3400.0));
+    assert_eq!(state.memory.utilization_mb.current, Some(16384.0));
+    assert_eq!(state.motherboard.aio_pump_speed.current, Some(2500.0));
+    assert_eq!(state.cpu.thermal_throttling.active, Some(false));
+    assert_eq!(state.gpu.thermal_throttling.active, Some(true));
 }
 
 #[test]
 fn test_ui_state_consistency() {
-    let state = AppState::default();
-    
-    // Verify default UI state
-    assert!(state.ui_state.cpu_section_expanded);
-    assert!(state.ui_state.gpu_section_expanded);
-    assert!(state.ui_state.memory_section_expanded);
-    assert!(state.ui_state.storage_section_expanded);
-    assert!(state.ui_state.motherboard_section_expanded);
-    
-    // Test shared state UI consistency
-    let shared_state = AppState::new_shared(1000);
-    let shared_app_state = shared_state.read();
-    assert!(shared_app_state.ui_state.cpu_section_expanded);
-    assert!(shared_app_state.ui_state.gpu_section_expanded);
-    assert!(shared_app_state.ui_state.memory_section_expanded);
-    assert!(shared_app_state.ui_state.storage_section_expanded);
-    assert!(shared_app_state.ui_state.motherboard_section_expanded);
+    let ui_state = UiState::default();
+    
+    // Verify default UI state: every device at its default expansion
+    assert!(ui_state.toggled_devices.is_empty());
+    assert!(ui_state.popped_out.is_empty());
+    assert!(ui_state.pinned.is_empty());
+    assert!(ui_state.hidden.is_empty());
+    assert_eq!(ui_state.color("cpu.utilization"), None);
+    
+    // Preferences survive a round trip through their own file
+    let path = std::env::temp_dir().join(format!("spd_ui_state_{}.toml", std::process::id()));
+    let changed = UiState {
+        toggled_devices: vec!["system/gpus/gpu0".to_string()],
+        popped_out: vec!["cpu.utilization".to_string()],
+        pinned: vec!["gpu.hotspot_temperature".to_string(), "cpu.thermal_throttling".to_string()],
+        hidden: vec!["memory.swap_used_mb".to_string()],
+        colors: [("cpu.utilization".to_string(), [220, 80, 80])].into_iter().collect(),
+        active_dashboard: Some(1),
+    };
+    assert!(changed.is_hidden("memory.swap_used_mb"));
+    assert_eq!(changed.color("cpu.utilization"), Some(eframe::egui::Color32::from_rgb(220, 80, 80)));
+    changed.save_to(&path).expect("Failed to save UI state");
+    let loaded = UiState::load_from(&path).expect("Failed to load UI state");
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded, changed);
+}
+
+#[test]
+fn test_settings_bundle_round_trip() {
+    let mut config = AppConfig { polling_interval_ms: 500, ..AppConfig::default() };
+    config.set_monitor_enabled("NVIDIA", false);
+    config.alerts.rules.push(AlertRule::new("cpu.package_temperature", 85.0, Default::default()));
+    let bundle = SettingsBundle {
+        config,
+        ui: UiState {
+            pinned: vec!["cpu.utilization".to_string()],
+            active_dashboard: Some(0),
+            ..UiState::default()
+        },
+    };
+    
+    let path = std::env::temp_dir().join(format!("spd_settings_{}.toml", std::process::id()));
+    bundle.save_to(&path).expect("Failed to export settings");
+    let loaded = SettingsBundle::load_from(&path);
+    
+    // A file that isn't a bundle is refused rather than read as defaults
+    std::fs::write(&path, "polling_interval_ms = 500").unwrap();
+    let refused = SettingsBundle::load_from(&path);
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.expect("Failed to import settings"), bundle);
+    assert!(refused.is_err());
+}
+
+#[test]
+fn test_settings_export_leaves_secrets_behind() {
+    let mut config = AppConfig { anonymize_salt: 42, ..AppConfig::default() };
+    config.remote_write.url = "https://metrics.example.com/api/v1/write".to_string();
+    config.remote_write.password = Some("hunter2".to_string());
+    config.remote_write.bearer_token = Some("s3cr3t-bearer".to_string());
+    config.helper.enabled = true;
+    let bundle = SettingsBundle { config: config.clone(), ui: UiState::default() };
+    
+    let path = std::env::temp_dir().join(format!("spd_settings_secrets_{}.toml", std::process::id()));
+    bundle.save_to(&path).expect("Failed to export settings");
+    let written = std::fs::read_to_string(&path).unwrap();
+    let loaded = SettingsBundle::load_from(&path).expect("Failed to import settings");
+    std::fs::remove_file(&path).ok();
+    assert!(!written.contains("hunter2") && !written.contains("s3cr3t-bearer"));
+    assert_eq!(loaded.config.anonymize_salt, 0);
+    assert!(!loaded.config.helper.enabled);
+    assert_eq!(loaded.config.remote_write.url, config.remote_write.url);
+    
+    // Importing on this machine keeps its own secrets for the same endpoint only
+    assert_eq!(loaded.config_for(&config), config);
+    let mut elsewhere = loaded.clone();
+    elsewhere.config.remote_write.url = "https://attacker.example.com/write".to_string();
+    let imported = elsewhere.config_for(&config);
+    assert_eq!(imported.remote_write.password, None);
+    assert_eq!(imported.remote_write.bearer_token, None);
+    assert_eq!(imported.anonymize_salt, 42);
+}
+
+#[test]
+fn test_imported_alert_actions_require_confirmation() {
+    let mut rule = AlertRule::new("cpu.package_temperature", 95.0, Default::default());
+    rule.action = Some(AlertAction {
+        command: "shutdown".to_string(),
+        args: vec!["-h".to_string(), "now".to_string()],
+        cooldown_secs: 300,
+        require_confirmation: false,
+    });
+    let mut config = AppConfig::default();
+    config.alerts.rules.push(rule);
+    let bundle = SettingsBundle { config, ui: UiState::default() };
+    
+    let path = std::env::temp_dir().join(format!("spd_settings_actions_{}.toml", std::process::id()));
+    bundle.save_to(&path).expect("Failed to export settings");
+    let loaded = SettingsBundle::load_from(&path).expect("Failed to import settings");
+    std::fs::remove_file(&path).ok();
+    let action = loaded.config.alerts.rules[0].action.as_ref().unwrap();
+    assert!(action.require_confirmation);
+    assert_eq!(loaded.alert_actions(), vec!["shutdown -h now".to_string()]);
 }
 
 #[test]
@@ -264,23 +355,24 @@ fn test_metric_min_max_tracking_comprehensive() {
     assert_eq!(state.cpu.utilization.current, Some(60.0));
     
     // Test memory utilization min/max tracking
-    let memory_values = [4096, 8192, 2048, 12288, 6144];
+    let memory_values: [u32; 5] = [4096, 8192, 2048, 12288, 6144];
     for &value in &memory_values {
         state.memory.utilization_mb.update(value);
     }
     
-    assert_eq!(state.memory.utilization_mb.session_min, Some(2048));
-    assert_eq!(state.memory.utilization_mb.session_max, Some(12288));
-    assert_eq!(state.memory.utilization_mb.current, Some(6144));
+    assert_eq!(state.memory.utilization_mb.session_min, Some(2048.0));
+    assert_eq!(state.memory.utilization_mb.session_max, Some(12288.0));
+    assert_eq!(state.memory.utilization_mb.current, Some(6144.0));
     
-    // Test boolean min/max tracking (thermal throttling)
+    // Thermal throttling is tracked as state changes rather than min/max
     state.cpu.thermal_throttling.update(false);
     state.cpu.thermal_throttling.update(true);
+    state.cpu.thermal_throttling.update(true);
     state.cpu.thermal_throttling.update(false);
     
-    assert_eq!(state.cpu.thermal_throttling.session_min, Some(false));
-    assert_eq!(state.cpu.thermal_throttling.session_max, Some(true));
-    assert_eq!(state.cpu.thermal_throttling.current, Some(false));
+    assert_eq!(state.cpu.thermal_throttling.changes.len(), 3);
+    assert_eq!(state.cpu.thermal_throttling.activations, 1);
+    assert_eq!(state.cpu.thermal_throttling.active, Some(false));
 }
 
 #[test]
@@ -392,4 +484,248 @@ fn test_data_consistency_under_load() {
     if let Some(current) = final_state.cpu.utilization.current {
         assert!(current >= 0.0 && current <= 100.0);
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_async_polling_tasks_update_state() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build runtime");
+    let state = AppState::new_shared(50);
+    let poller = HardwarePoller::new(state.clone(), 50);
+    
+    let handles = poller.start_async(runtime.handle());
+    std::thread::sleep(std::time::Duration::from_millis(400));
+    
+    {
+        let app_state = state.read();
+        assert!(app_state.poller.last_poll.is_some());
+        assert!(app_state.cpu.utilization.history.len() >= 2);
+        assert!(app_state.poller.monitor_timings.iter().any(|timing| timing.name == "Generic"));
+    }
+    
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+// Not supported at startup, so it's first initialized inside its polling task, where
+// the first attempt panics
+struct FlakyInitMonitor {
+    attempts: u32,
+}
+
+impl HardwareMonitor for FlakyInitMonitor {
+    fn name(&self) -> &'static str {
+        "Flaky"
+    }
+    
+    fn initialize(&mut self) -> Result<(), MonitorError> {
+        self.attempts += 1;
+        assert!(self.attempts > 1, "driver not ready");
+        Ok(())
+    }
+    
+    fn update_metrics(&mut self, _state: &SharedAppState) -> Result<(), MonitorError> {
+        Ok(())
+    }
+    
+    fn supports_hardware(&self, _info: &HardwareInfo) -> bool {
+        false
+    }
+}
+
+#[test]
+fn test_panicking_polling_task_is_restarted() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build runtime");
+    let state = AppState::new_shared(50);
+    let config = AppConfig::new_shared(AppConfig::default());
+    let poller = HardwarePoller::with_monitors(state.clone(), 50, config, |registry, _| {
+        registry.register(Box::new(FlakyInitMonitor { attempts: 0 }));
+        Vec::new()
+    });
+    
+    let handles = poller.start_async(runtime.handle());
+    // Rerun after a one second backoff
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while std::time::Instant::now() < deadline && state.read().poller.monitor_timings.is_empty() {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    
+    {
+        let app_state = state.read();
+        assert_eq!(app_state.poller.restarts, 1);
+        assert!(app_state.poller.last_error.as_deref().is_some_and(|error| error.contains("Flaky monitor task panicked")));
+        assert!(app_state.poller.disabled_monitors.is_empty());
+        assert!(app_state.poller.monitor_timings.iter().any(|timing| timing.name == "Flaky"));
+    }
+    
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[test]
+fn test_release_profile_unwinds_so_panics_can_be_caught() {
+    // Tests always unwind, whatever the profile says, so check what users actually get:
+    // with panic = "abort" a panicking monitor would take the whole process down
+    let manifest = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")).expect("Failed to read Cargo.toml");
+    let manifest: toml::Table = manifest.parse().expect("Failed to parse Cargo.toml");
+    let strategy = manifest["profile"]["release"].get("panic").and_then(|value| value.as_str()).unwrap_or("unwind");
+    assert_eq!(strategy, "unwind");
+}
+
+#[test]
+fn test_rescale_maps_right_axis_onto_left() {
+    // Fan RPM 0..3000 drawn on a 30..90 °C axis
+    assert_eq!(rescale(1500.0, (0.0, 3000.0), (30.0, 90.0)), 60.0);
+    assert_eq!(rescale(0.0, (0.0, 3000.0), (30.0, 90.0)), 30.0);
+    
+    // Round trip back to RPM for the right-hand tick labels
+    let on_left = rescale(2250.0, (0.0, 3000.0), (30.0, 90.0));
+    assert_eq!(rescale(on_left, (30.0, 90.0), (0.0, 3000.0)), 2250.0);
+    
+    // A flat series doesn't divide by zero
+    assert_eq!(rescale(5.0, (5.0, 5.0), (30.0, 90.0)), 30.0);
+}
+
+#[test]
+fn test_stack_series_accumulates_layers() {
+    let apps = vec![(0.0, 4000.0), (1.0, 4200.0)];
+    let cache = vec![(0.0, 2000.0), (1.0, 1900.0)];
+    // Free memory missed the first poll, so everything aligns on the newest sample
+    let free = vec![(1.0, 1900.0)];
+    
+    let stacked = stack_series(&[apps, cache, free]);
+    assert_eq!(stacked.len(), 3);
+    assert_eq!(stacked[0], vec![(1.0, 4200.0)]);
+    assert_eq!(stacked[1], vec![(1.0, 6100.0)]);
+    assert_eq!(stacked[2], vec![(1.0, 8000.0)]);
+    
+    assert!(stack_series(&[]).is_empty());
+}
+
+#[test]
+fn test_decimate_min_max_keeps_spikes() {
+    // A flat hour at 1 Hz with one spike and one dip
+    let mut points: Vec<(f64, f64)> = (0..3600).map(|second| (second as f64, 50.0)).collect();
+    points[1000].1 = 99.0;
+    points[2500].1 = 1.0;
+    
+    let decimated = decimate_min_max(&points, 400);
+    assert!(decimated.len() <= 800);
+    assert!(decimated.contains(&(1000.0, 99.0)));
+    assert!(decimated.contains(&(2500.0, 1.0)));
+    assert_eq!(decimated.first(), Some(&(0.0, 50.0)));
+    assert!(decimated.windows(2).all(|pair| pair[0].0 < pair[1].0), "Still in time order");
+    
+    // Already sparse enough: untouched
+    assert_eq!(decimate_min_max(&points[..100], 400), points[..100].to_vec());
+    assert!(decimate_min_max(&[], 400).is_empty());
+}
+
+#[test]
+fn test_column_count_follows_width() {
+    assert_eq!(column_count(300.0), 1);  // Compact overlay
+    assert_eq!(column_count(800.0), 2);
+    assert_eq!(column_count(1200.0), 3);
+    assert_eq!(column_count(3440.0), 4); // Ultrawide, capped
+    assert_eq!(column_count(0.0), 1);
+}
+
+#[test]
+fn test_heatmap_color_gradient() {
+    assert_eq!(heatmap_color(0.0), eframe::egui::Color32::from_rgb(30, 40, 110));
+    assert_eq!(heatmap_color(1.0), eframe::egui::Color32::from_rgb(220, 50, 40));
+    
+    // Out-of-range and missing values are clamped rather than wrapping
+    assert_eq!(heatmap_color(1.5), heatmap_color(1.0));
+    assert_eq!(heatmap_color(-0.2), heatmap_color(0.0));
+    assert_eq!(heatmap_color(f32::NAN), heatmap_color(0.0));
+    
+    // Hotter is redder
+    assert!(heatmap_color(0.9).r() > heatmap_color(0.5).r());
+}
+
+#[test]
+fn test_fault_injection_reaches_status_and_alerts() {
+    // Every Generic poll fails: the error shows in the status panel and nothing is recorded
+    let mut config = AppConfig::default();
+    config.fault_injection.insert("generic".to_string(), FaultPlan { fail_rate: 1.0, ..FaultPlan::default() });
+    let state = AppState::new_shared(100);
+    let mut poller = HardwarePoller::with_config(state.clone(), 100, AppConfig::new_shared(config));
+    poller.poll_hardware();
+    
+    {
+        let app_state = state.read();
+        let generic = app_state.poller.monitor_timings.iter().find(|timing| timing.name == "Generic").unwrap();
+        assert!(generic.error.as_deref().unwrap().contains("injected failure"));
+        assert!(app_state.cpu.utilization.current.is_none());
+        assert!(app_state.poller.disabled_monitors.is_empty());
+    }
+    
+    // Garbage readings: the first is absurdly high and trips an alert on the metric
+    let mut config = AppConfig::default();
+    config.fault_injection.insert("generic".to_string(), FaultPlan { garbage_rate: 1.0, ..FaultPlan::default() });
+    config.alerts.rules.push(AlertRule {
+        metric: "cpu.utilization".to_string(),
+        threshold: 100.0,
+        severity: Default::default(),
+        duration_secs: 0,
+        clear_threshold: None,
+        action: None,
+    });
+    let state = AppState::new_shared(100);
+    let mut poller = HardwarePoller::with_config(state.clone(), 100, AppConfig::new_shared(config));
+    poller.poll_hardware();
+    
+    let app_state = state.read();
+    assert_eq!(app_state.cpu.utilization.current, Some(1.0e12));
+    assert_eq!(app_state.alerts.records.len(), 1);
+    assert_eq!(app_state.alerts.firing.active, Some(true));
+}
+
+#[test]
+fn test_tick_scheduler_keeps_deadlines() {
+    use simple_performance_dashboard::hardware::TickScheduler;
+    use std::time::{Duration, Instant};
+    
+    let interval = Duration::from_millis(100);
+    let start = Instant::now();
+    let mut ticks = TickScheduler::starting_at(start);
+    
+    // A poll taking 30 ms sleeps the remaining 70 ms rather than the full interval
+    assert_eq!(ticks.advance(interval, start + Duration::from_millis(30)), Some(Duration::from_millis(70)));
+    // Overrunning the slot skips the sleep and re-anchors to the late poll
+    assert_eq!(ticks.advance(interval, start + Duration::from_millis(250)), None);
+    assert_eq!(ticks.advance(interval, start + Duration::from_millis(260)), Some(Duration::from_millis(90)));
+    
+    // Sub-second samples keep their spacing when plotted
+    let mut metric = simple_performance_dashboard::model::MetricValue::default();
+    let session_start = Utc::now();
+    metric.record_at(session_start + chrono::Duration::milliseconds(100), 1.0);
+    metric.record_at(session_start + chrono::Duration::milliseconds(350), 2.0);
+    assert_eq!(metric.plot_data(session_start), vec![(0.1, 1.0), (0.35, 2.0)]);
+}
+
+#[test]
+fn test_session_headline_lists_recorded_stats() {
+    use simple_performance_dashboard::baseline::SessionSummary;
+    use simple_performance_dashboard::ui::session_headline;
+    
+    let mut state = AppState::new(1000);
+    for value in [20.0, 40.0] {
+        state.cpu.utilization.update(value);
+    }
+    for value in [70.0, 82.5] {
+        state.gpu.package_temperature.update(value);
+    }
+    let summary = SessionSummary::from_state(&state, false);
+    assert_eq!(session_headline(&summary), "CPU Utilization 30.0% avg, GPU Package Temperature 82.5°C max");
+    
+    let empty = SessionSummary::from_state(&AppState::new(1000), false);
+    assert_eq!(session_headline(&empty), "No headline metrics recorded");
+}